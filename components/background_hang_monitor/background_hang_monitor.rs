@@ -267,6 +267,13 @@ impl SignalToExitOnDrop {
         }
     }
 
+    /// Call `BackgroundHangMonitorExitSignal::signal_to_interrupt` now.
+    fn signal_to_interrupt(&self) {
+        if let Some(signal) = self.0.as_ref() {
+            signal.signal_to_interrupt();
+        }
+    }
+
     /// Disassociate `BackgroundHangMonitorExitSignal` from itself, preventing
     /// `BackgroundHangMonitorExitSignal::signal_to_exit` from being called in
     /// the future.
@@ -427,6 +434,12 @@ impl BackgroundHangMonitorWorker {
                         self.sampling_duration = None;
                         return true;
                     },
+                    Ok(BackgroundHangMonitorControlMsg::InterruptComponent(component_id)) => {
+                        if let Some(component) = self.monitored_components.get(&component_id) {
+                            component.exit_signal.signal_to_interrupt();
+                        }
+                        None
+                    },
                     Ok(BackgroundHangMonitorControlMsg::Exit(sender)) => {
                         for component in self.monitored_components.values_mut() {
                             component.exit_signal.signal_to_exit();
@@ -260,3 +260,62 @@ fn test_hang_monitoring_exit_signal_inner(op_order: fn(&mut dyn FnMut(), &mut dy
         thread::sleep(Duration::from_millis(10));
     }
 }
+
+#[test]
+fn test_hang_monitoring_interrupt_signal() {
+    let _lock = SERIAL.lock().unwrap();
+
+    let (background_hang_monitor_ipc_sender, _background_hang_monitor_receiver) =
+        ipc::channel().expect("ipc channel failure");
+    let (control_sender, control_receiver) = ipc::channel().expect("ipc channel failure");
+
+    struct BHMExitSignal {
+        closing: Arc<AtomicBool>,
+        interrupted: Arc<AtomicBool>,
+    }
+
+    impl BackgroundHangMonitorExitSignal for BHMExitSignal {
+        fn signal_to_exit(&self) {
+            self.closing.store(true, Ordering::SeqCst);
+        }
+
+        fn signal_to_interrupt(&self) {
+            self.interrupted.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let closing = Arc::new(AtomicBool::new(false));
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let signal = Box::new(BHMExitSignal {
+        closing: closing.clone(),
+        interrupted: interrupted.clone(),
+    });
+
+    let background_hang_monitor_register = HangMonitorRegister::init(
+        background_hang_monitor_ipc_sender.clone(),
+        control_receiver,
+        false,
+    );
+    let background_hang_monitor = background_hang_monitor_register.register_component(
+        MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script),
+        Duration::from_millis(10),
+        Duration::from_millis(1000),
+        Some(signal),
+    );
+
+    // The registration and the control message are sent on different channels, so keep
+    // asking for an interrupt until the component is known to the monitor.
+    while !interrupted.load(Ordering::SeqCst) {
+        control_sender
+            .send(BackgroundHangMonitorControlMsg::InterruptComponent(
+                MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script),
+            ))
+            .unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // Assert the component was interrupted without being asked to exit.
+    assert!(!closing.load(Ordering::SeqCst));
+
+    background_hang_monitor.unregister();
+}
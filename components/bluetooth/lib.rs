@@ -15,29 +15,37 @@ pub mod test;
 
 use std::borrow::ToOwned;
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::string::String;
 use std::thread;
 use std::time::Duration;
 
+use base::id::TopLevelBrowsingContextId;
 use bitflags::bitflags;
 use bluetooth_traits::blocklist::{uuid_is_blocklisted, Blocklist};
 use bluetooth_traits::scanfilter::{
     BluetoothScanfilter, BluetoothScanfilterSequence, RequestDeviceoptions,
 };
 use bluetooth_traits::{
-    BluetoothCharacteristicMsg, BluetoothDescriptorMsg, BluetoothDeviceMsg, BluetoothError,
-    BluetoothRequest, BluetoothResponse, BluetoothResponseResult, BluetoothResult,
-    BluetoothServiceMsg, GATTType,
+    BluetoothAdvertisementMsg, BluetoothCharacteristicMsg, BluetoothDescriptorMsg,
+    BluetoothDeviceGrant, BluetoothDeviceMsg, BluetoothError, BluetoothRequest, BluetoothResponse,
+    BluetoothResponseResult, BluetoothResult, BluetoothServiceMsg, GATTType,
 };
-use embedder_traits::{EmbedderMsg, EmbedderProxy};
-use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use crossbeam_channel::{select, Receiver};
+use embedder_traits::{BluetoothDeviceDescription, EmbedderMsg, EmbedderProxy};
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
 use log::warn;
+use serde::{Deserialize, Serialize};
 use servo_config::pref;
 use servo_rand::{self, Rng};
+use servo_url::ImmutableOrigin;
 
 use crate::bluetooth::{
-    BluetoothAdapter, BluetoothDevice, BluetoothGATTCharacteristic, BluetoothGATTDescriptor,
-    BluetoothGATTService,
+    BluetoothAdapter, BluetoothDevice, BluetoothDiscoverySession, BluetoothGATTCharacteristic,
+    BluetoothGATTDescriptor, BluetoothGATTService,
 };
 
 // A transaction not completed within 30 seconds shall time out. Such a transaction shall be considered to have failed.
@@ -46,6 +54,10 @@ const MAXIMUM_TRANSACTION_TIME: u8 = 30;
 const CONNECTION_TIMEOUT_MS: u64 = 1000;
 // The discovery session needs some time to find any nearby devices
 const DISCOVERY_TIMEOUT_MS: u64 = 1500;
+// The file of the configuration directory in which the device grants are saved.
+const DEVICE_GRANTS_FILE: &str = "bluetooth_device_grants.json";
+// How often watched devices and characteristics are checked for new advertisements and values.
+const POLL_INTERVAL_MS: u64 = 500;
 
 bitflags! {
     struct Flags: u32 {
@@ -69,13 +81,22 @@ macro_rules! return_if_cached(
     );
 );
 
-pub trait BluetoothThreadFactory {
-    fn new(embedder_proxy: EmbedderProxy) -> Self;
+pub trait BluetoothThreadFactory: Sized {
+    /// Start the Bluetooth thread, returning the senders of the browsing contexts that are not
+    /// private and of those that are, in that order.
+    fn new(embedder_proxy: EmbedderProxy, config_dir: Option<PathBuf>) -> (Self, Self);
 }
 
 impl BluetoothThreadFactory for IpcSender<BluetoothRequest> {
-    fn new(embedder_proxy: EmbedderProxy) -> IpcSender<BluetoothRequest> {
+    fn new(
+        embedder_proxy: EmbedderProxy,
+        config_dir: Option<PathBuf>,
+    ) -> (IpcSender<BluetoothRequest>, IpcSender<BluetoothRequest>) {
         let (sender, receiver) = ipc::channel().unwrap();
+        let (private_sender, private_receiver) = ipc::channel().unwrap();
+        let receiver = ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(receiver);
+        let private_receiver =
+            ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(private_receiver);
         let adapter = if pref!(dom.bluetooth.enabled) {
             BluetoothAdapter::new()
         } else {
@@ -85,10 +106,17 @@ impl BluetoothThreadFactory for IpcSender<BluetoothRequest> {
         thread::Builder::new()
             .name("Bluetooth".to_owned())
             .spawn(move || {
-                BluetoothManager::new(receiver, adapter, embedder_proxy).start();
+                BluetoothManager::new(
+                    receiver,
+                    private_receiver,
+                    adapter,
+                    embedder_proxy,
+                    config_dir,
+                )
+                .start();
             })
             .expect("Thread spawning failed");
-        sender
+        (sender, private_sender)
     }
 }
 
@@ -196,8 +224,107 @@ fn is_mock_adapter(adapter: &BluetoothAdapter) -> bool {
     matches!(adapter, &BluetoothAdapter::Mock(_))
 }
 
+// On the mock adapter, fail a GATT operation on the attribute with the given UUID if the data set
+// has set it up to fail.
+fn check_simulated_gatt_error(
+    adapter: &BluetoothAdapter,
+    uuid: Result<String, Box<dyn Error>>,
+) -> BluetoothResult<()> {
+    if !is_mock_adapter(adapter) {
+        return Ok(());
+    }
+    match uuid.ok().and_then(|uuid| test::simulated_gatt_error(&uuid)) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// A grant as it is remembered by the Bluetooth thread, which also knows whether it may be
+/// saved.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct DeviceGrant {
+    origin: ImmutableOrigin,
+    address: String,
+    name: Option<String>,
+    /// The UUIDs of the services of the device that the origin may use.
+    services: Vec<String>,
+    /// Whether the grant was made in a private browsing context, and must not be saved.
+    #[serde(skip)]
+    private: bool,
+    /// Whether the grant was made for a device of the mock adapter, and must not be saved.
+    #[serde(skip)]
+    mock: bool,
+}
+
+impl DeviceGrant {
+    fn is_saved(&self) -> bool {
+        !self.private && !self.mock
+    }
+
+    /// Whether both grants give the same origin access to the same device, in the same kind of
+    /// browsing context.
+    fn is_for_same_device(&self, other: &DeviceGrant) -> bool {
+        self.origin == other.origin &&
+            self.address == other.address &&
+            self.private == other.private &&
+            self.mock == other.mock
+    }
+
+    fn to_embedder_grant(&self) -> BluetoothDeviceGrant {
+        BluetoothDeviceGrant {
+            origin: self.origin.clone(),
+            address: self.address.clone(),
+            name: self.name.clone(),
+            services: self.services.clone(),
+            private: self.private,
+        }
+    }
+}
+
+fn read_device_grants(config_dir: &Path) -> Vec<DeviceGrant> {
+    let path = config_dir.join(DEVICE_GRANTS_FILE);
+    let Ok(grants) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&grants).unwrap_or_else(|error| {
+        warn!("Failed to read {} ({error}).", path.display());
+        Vec::new()
+    })
+}
+
+// The lower level only exposes the data of the last advertisement received from a device, which
+// is only known while the device is in range.
+fn advertisement_from_device(device: &BluetoothDevice) -> Option<BluetoothAdvertisementMsg> {
+    let rssi = device.get_rssi().ok()?;
+    Some(BluetoothAdvertisementMsg {
+        name: device.get_name().ok(),
+        appearance: device.get_appearance().ok(),
+        tx_power: device
+            .get_tx_power()
+            .ok()
+            .and_then(|tx_power| i8::try_from(tx_power).ok()),
+        rssi: i8::try_from(rssi).ok(),
+    })
+}
+
+struct AdvertisementWatcher {
+    device_id: String,
+    sender: IpcSender<BluetoothAdvertisementMsg>,
+    last_advertisement: Option<BluetoothAdvertisementMsg>,
+}
+
+struct NotificationSubscription {
+    characteristic_id: String,
+    sender: IpcSender<Vec<u8>>,
+    /// The value of the characteristic that was last sent. Polling can only find notifications
+    /// that change it, see `poll_notifications`.
+    last_value: Option<Vec<u8>>,
+}
+
 pub struct BluetoothManager {
-    receiver: IpcReceiver<BluetoothRequest>,
+    receiver: Receiver<BluetoothRequest>,
+    /// Receives the requests of private browsing contexts.
+    private_receiver: Receiver<BluetoothRequest>,
     adapter: Option<BluetoothAdapter>,
     address_to_id: HashMap<String, String>,
     service_to_device: HashMap<String, String>,
@@ -208,17 +335,32 @@ pub struct BluetoothManager {
     cached_characteristics: HashMap<String, BluetoothGATTCharacteristic>,
     cached_descriptors: HashMap<String, BluetoothGATTDescriptor>,
     allowed_services: HashMap<String, HashSet<String>>,
+    advertisement_watchers: HashMap<String, AdvertisementWatcher>,
+    discovery_session: Option<BluetoothDiscoverySession>,
+    notification_subscriptions: HashMap<String, NotificationSubscription>,
+    /// The listeners for the disconnection of each connected device, by listener id.
+    disconnection_listeners: HashMap<String, HashMap<String, IpcSender<()>>>,
+    availability_listeners: HashMap<String, IpcSender<bool>>,
+    /// The availability that was last sent to the availability listeners.
+    availability: bool,
+    /// The grants of all the origins, including those that are only kept until Servo exits.
+    device_grants: Vec<DeviceGrant>,
     embedder_proxy: EmbedderProxy,
+    /// Where the device grants are saved, if anywhere.
+    config_dir: Option<PathBuf>,
 }
 
 impl BluetoothManager {
     pub fn new(
-        receiver: IpcReceiver<BluetoothRequest>,
+        receiver: Receiver<BluetoothRequest>,
+        private_receiver: Receiver<BluetoothRequest>,
         adapter: Option<BluetoothAdapter>,
         embedder_proxy: EmbedderProxy,
+        config_dir: Option<PathBuf>,
     ) -> BluetoothManager {
         BluetoothManager {
             receiver,
+            private_receiver,
             adapter,
             address_to_id: HashMap::new(),
             service_to_device: HashMap::new(),
@@ -229,18 +371,60 @@ impl BluetoothManager {
             cached_characteristics: HashMap::new(),
             cached_descriptors: HashMap::new(),
             allowed_services: HashMap::new(),
+            advertisement_watchers: HashMap::new(),
+            discovery_session: None,
+            notification_subscriptions: HashMap::new(),
+            disconnection_listeners: HashMap::new(),
+            availability_listeners: HashMap::new(),
+            availability: false,
+            device_grants: config_dir
+                .as_deref()
+                .map(read_device_grants)
+                .unwrap_or_default(),
             embedder_proxy,
+            config_dir,
         }
     }
 
     fn start(&mut self) {
-        while let Ok(msg) = self.receiver.recv() {
+        while let Some((msg, private)) = self.next_request() {
             match msg {
-                BluetoothRequest::RequestDevice(options, sender) => {
-                    let _ = sender.send(self.request_device(options));
+                BluetoothRequest::RequestDevice(webview_id, origin, options, sender) => {
+                    let _ = sender.send(self.request_device(webview_id, origin, options, private));
+                },
+                BluetoothRequest::GetDevices(origin, sender) => {
+                    let _ = sender.send(self.get_devices(origin, private));
+                },
+                BluetoothRequest::GetDeviceGrants(sender) => {
+                    let grants = self
+                        .device_grants
+                        .iter()
+                        .map(DeviceGrant::to_embedder_grant)
+                        .collect();
+                    let _ = sender.send(grants);
                 },
-                BluetoothRequest::GATTServerConnect(device_id, sender) => {
-                    let _ = sender.send(self.gatt_server_connect(device_id));
+                BluetoothRequest::RevokeDeviceGrants(origin, address) => {
+                    self.revoke_device_grants(|grant| {
+                        origin
+                            .as_ref()
+                            .map_or(true, |origin| grant.origin == *origin) &&
+                            address
+                                .as_ref()
+                                .map_or(true, |address| grant.address == *address)
+                    })
+                },
+                BluetoothRequest::GATTServerConnect(device_id, listener_id, listener, sender) => {
+                    let result = self.gatt_server_connect(device_id.clone());
+                    if result.is_ok() {
+                        self.disconnection_listeners
+                            .entry(device_id)
+                            .or_default()
+                            .insert(listener_id, listener);
+                    }
+                    let _ = sender.send(result);
+                },
+                BluetoothRequest::RemoveDisconnectionListener(device_id, listener_id) => {
+                    self.remove_disconnection_listener(&device_id, &listener_id)
                 },
                 BluetoothRequest::GATTServerDisconnect(device_id, sender) => {
                     let _ = sender.send(self.gatt_server_disconnect(device_id));
@@ -254,15 +438,24 @@ impl BluetoothManager {
                 BluetoothRequest::WriteValue(id, value, sender) => {
                     let _ = sender.send(self.write_value(id, value));
                 },
-                BluetoothRequest::EnableNotification(id, enable, sender) => {
-                    let _ = sender.send(self.enable_notification(id, enable));
+                BluetoothRequest::StartNotifications(id, subscription_id, values, sender) => {
+                    let _ = sender.send(self.start_notifications(id, subscription_id, values));
+                },
+                BluetoothRequest::StopNotifications(id, subscription_id, sender) => {
+                    let _ = sender.send(self.stop_notifications(id, &subscription_id));
+                },
+                BluetoothRequest::WatchAdvertisements(id, watch_id, advertisements, sender) => {
+                    let _ = sender.send(self.watch_advertisements(id, watch_id, advertisements));
                 },
-                BluetoothRequest::WatchAdvertisements(id, sender) => {
-                    let _ = sender.send(self.watch_advertisements(id));
+                BluetoothRequest::UnwatchAdvertisements(watch_id) => {
+                    self.unwatch_advertisements(&watch_id)
                 },
                 BluetoothRequest::Test(data_set_name, sender) => {
                     let _ = sender.send(self.test(data_set_name));
                 },
+                BluetoothRequest::SimulateDisconnection(id, sender) => {
+                    let _ = sender.send(self.simulate_disconnection(id));
+                },
                 BluetoothRequest::SetRepresentedToNull(
                     service_ids,
                     characteristic_ids,
@@ -274,6 +467,12 @@ impl BluetoothManager {
                 BluetoothRequest::GetAvailability(sender) => {
                     let _ = sender.send(self.get_availability());
                 },
+                BluetoothRequest::WatchAvailability(watch_id, sender) => {
+                    self.watch_availability(watch_id, sender)
+                },
+                BluetoothRequest::UnwatchAvailability(watch_id) => {
+                    self.availability_listeners.remove(&watch_id);
+                },
                 BluetoothRequest::MatchesFilter(id, filters, sender) => {
                     let _ = sender.send(self.device_matches_filter(&id, &filters));
                 },
@@ -282,6 +481,36 @@ impl BluetoothManager {
         }
     }
 
+    /// Wait for the next request, checking the watched devices for new advertisements, the
+    /// characteristics with notifications for new values, the connected devices for
+    /// disconnections and the adapter for availability changes in the meantime. Also returns
+    /// whether the request comes from a private browsing context. Both senders are dropped
+    /// together when Servo exits, so either of them closing ends the thread.
+    fn next_request(&mut self) -> Option<(BluetoothRequest, bool)> {
+        loop {
+            if self.advertisement_watchers.is_empty() &&
+                self.notification_subscriptions.is_empty() &&
+                self.disconnection_listeners.is_empty() &&
+                self.availability_listeners.is_empty()
+            {
+                return select! {
+                    recv(self.receiver) -> msg => msg.ok().map(|msg| (msg, false)),
+                    recv(self.private_receiver) -> msg => msg.ok().map(|msg| (msg, true)),
+                };
+            }
+            select! {
+                recv(self.receiver) -> msg => return msg.ok().map(|msg| (msg, false)),
+                recv(self.private_receiver) -> msg => return msg.ok().map(|msg| (msg, true)),
+                default(Duration::from_millis(POLL_INTERVAL_MS)) => {
+                    self.poll_advertisements();
+                    self.poll_notifications();
+                    self.poll_connections();
+                    self.poll_availability();
+                },
+            }
+        }
+    }
+
     // Test
 
     fn test(&mut self, data_set_name: String) -> BluetoothResult<()> {
@@ -294,6 +523,11 @@ impl BluetoothManager {
         self.cached_characteristics.clear();
         self.cached_descriptors.clear();
         self.allowed_services.clear();
+        self.advertisement_watchers.clear();
+        self.discovery_session = None;
+        self.notification_subscriptions.clear();
+        self.disconnection_listeners.clear();
+        self.device_grants.retain(|grant| !grant.mock);
         self.adapter = BluetoothAdapter::new_mock().ok();
         match test::test(self, data_set_name) {
             Ok(_) => Ok(()),
@@ -301,6 +535,13 @@ impl BluetoothManager {
         }
     }
 
+    fn simulate_disconnection(&mut self, device_id: String) -> BluetoothResult<()> {
+        match test::simulate_disconnection(self, &device_id) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(BluetoothError::Type(error.to_string())),
+        }
+    }
+
     fn remove_ids_from_caches(
         &mut self,
         service_ids: Vec<String>,
@@ -312,6 +553,10 @@ impl BluetoothManager {
             self.service_to_device.remove(&id);
         }
 
+        // The platform ends the notifications of the characteristics of a disconnected device.
+        self.notification_subscriptions.retain(|_, subscription| {
+            !characteristic_ids.contains(&subscription.characteristic_id)
+        });
         for id in characteristic_ids {
             self.cached_characteristics.remove(&id);
             self.characteristic_to_service.remove(&id);
@@ -323,6 +568,30 @@ impl BluetoothManager {
         }
     }
 
+    /// Forget the services, characteristics and descriptors of a device, so that any further
+    /// use of them fails.
+    fn remove_device_attributes_from_caches(&mut self, device_id: &str) {
+        let service_ids: Vec<String> = self
+            .service_to_device
+            .iter()
+            .filter(|(_, device)| *device == device_id)
+            .map(|(service, _)| service.clone())
+            .collect();
+        let characteristic_ids: Vec<String> = self
+            .characteristic_to_service
+            .iter()
+            .filter(|(_, service)| service_ids.contains(service))
+            .map(|(characteristic, _)| characteristic.clone())
+            .collect();
+        let descriptor_ids: Vec<String> = self
+            .descriptor_to_characteristic
+            .iter()
+            .filter(|(_, characteristic)| characteristic_ids.contains(characteristic))
+            .map(|(descriptor, _)| descriptor.clone())
+            .collect();
+        self.remove_ids_from_caches(service_ids, characteristic_ids, descriptor_ids);
+    }
+
     // Adapter
 
     pub fn get_or_create_adapter(&mut self) -> Option<BluetoothAdapter> {
@@ -385,11 +654,16 @@ impl BluetoothManager {
         None
     }
 
+    /// Let the user choose one of the devices for the origin, returning the address of the
+    /// chosen device, or `None` if the user cancelled the prompt.
     fn select_device(
         &mut self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: &ImmutableOrigin,
         devices: Vec<BluetoothDevice>,
         adapter: &BluetoothAdapter,
     ) -> Option<String> {
+        // The data sets of the mock adapter expect their first device to be chosen.
         if is_mock_adapter(adapter) {
             for device in &devices {
                 if let Ok(address) = device.get_address() {
@@ -399,28 +673,36 @@ impl BluetoothManager {
             return None;
         }
 
-        let mut dialog_rows: Vec<String> = vec![];
-        for device in devices {
-            dialog_rows.extend_from_slice(&[
-                device.get_address().unwrap_or("".to_string()),
-                device.get_name().unwrap_or("".to_string()),
-            ]);
-        }
+        let devices: Vec<BluetoothDeviceDescription> = devices
+            .iter()
+            .filter_map(|device| {
+                Some(BluetoothDeviceDescription {
+                    address: device.get_address().ok()?,
+                    name: device.get_name().unwrap_or_default(),
+                })
+            })
+            .collect();
 
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
         let msg = (
-            None,
-            EmbedderMsg::GetSelectedBluetoothDevice(dialog_rows, ipc_sender),
+            webview_id,
+            EmbedderMsg::GetSelectedBluetoothDevice(origin.clone(), devices.clone(), ipc_sender),
         );
         self.embedder_proxy.send(msg);
 
-        match ipc_receiver.recv() {
-            Ok(result) => result,
+        let address = match ipc_receiver.recv() {
+            Ok(result) => result?,
             Err(e) => {
-                warn!("Failed to receive files from embedder ({:?}).", e);
-                None
+                warn!("Failed to receive the selected device from embedder ({e:?}).");
+                return None;
             },
+        };
+        // Only the devices that were offered to the user can be chosen.
+        if !devices.iter().any(|device| device.address == address) {
+            warn!("The embedder selected a device that was not offered ({address}).");
+            return None;
         }
+        Some(address)
     }
 
     fn generate_device_id(&mut self) -> String {
@@ -606,7 +888,13 @@ impl BluetoothManager {
     // Methods
 
     // https://webbluetoothcg.github.io/web-bluetooth/#request-bluetooth-devices
-    fn request_device(&mut self, options: RequestDeviceoptions) -> BluetoothResponseResult {
+    fn request_device(
+        &mut self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: ImmutableOrigin,
+        options: RequestDeviceoptions,
+        private: bool,
+    ) -> BluetoothResponseResult {
         // Step 6.
         let mut adapter = self.get_adapter()?;
 
@@ -628,29 +916,133 @@ impl BluetoothManager {
         }
 
         // Step 9.
-        if let Some(address) = self.select_device(matched_devices, &adapter) {
+        if let Some(address) = self.select_device(webview_id, &origin, matched_devices, &adapter) {
             let device_id = match self.address_to_id.get(&address) {
                 Some(id) => id.clone(),
                 None => return Err(BluetoothError::NotFound),
             };
-            let mut services = options.get_services_set();
+            let granted_services = options.get_services_set();
+            let mut services = granted_services.clone();
             if let Some(services_set) = self.allowed_services.get(&device_id) {
                 services = services_set | &services;
             }
             self.allowed_services.insert(device_id.clone(), services);
             if let Some(device) = self.get_device(&mut adapter, &device_id) {
+                let name = device.get_name().ok();
+                let grant = DeviceGrant {
+                    origin,
+                    address,
+                    name: name.clone(),
+                    services: granted_services.into_iter().collect(),
+                    private,
+                    mock: is_mock_adapter(&adapter),
+                };
+                self.remember_device_grant(grant);
                 let message = BluetoothDeviceMsg {
                     id: device_id,
-                    name: device.get_name().ok(),
+                    name,
                 };
                 return Ok(BluetoothResponse::RequestDevice(message));
             }
         }
         // Step 10.
+        // This includes the user cancelling the prompt.
         Err(BluetoothError::NotFound)
         // Step 12: Missing, because it is optional.
     }
 
+    fn remember_device_grant(&mut self, mut new_grant: DeviceGrant) {
+        // An opaque origin is different from all the later ones, which could not use the grant.
+        if !new_grant.origin.is_tuple() {
+            return;
+        }
+        new_grant.services.sort();
+        let is_saved = new_grant.is_saved();
+        match self
+            .device_grants
+            .iter_mut()
+            .find(|grant| grant.is_for_same_device(&new_grant))
+        {
+            Some(grant) => {
+                grant.name = new_grant.name;
+                grant.services.extend(new_grant.services);
+                grant.services.sort();
+                grant.services.dedup();
+            },
+            None => self.device_grants.push(new_grant),
+        }
+        if is_saved {
+            self.save_device_grants();
+        }
+    }
+
+    fn revoke_device_grants(&mut self, revoke: impl Fn(&DeviceGrant) -> bool) {
+        let saved_grants = self.saved_device_grants().count();
+        self.device_grants.retain(|grant| !revoke(grant));
+        if self.saved_device_grants().count() != saved_grants {
+            self.save_device_grants();
+        }
+    }
+
+    fn saved_device_grants(&self) -> impl Iterator<Item = &DeviceGrant> {
+        self.device_grants.iter().filter(|grant| grant.is_saved())
+    }
+
+    fn save_device_grants(&self) {
+        let Some(ref config_dir) = self.config_dir else {
+            return;
+        };
+        let path = config_dir.join(DEVICE_GRANTS_FILE);
+        let grants: Vec<&DeviceGrant> = self.saved_device_grants().collect();
+        let result = serde_json::to_string_pretty(&grants)
+            .map_err(|error| error.to_string())
+            .and_then(|grants| fs::write(&path, grants).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            warn!("Failed to save {} ({error}).", path.display());
+        }
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getdevices
+    fn get_devices(&mut self, origin: ImmutableOrigin, private: bool) -> BluetoothResponseResult {
+        let mut adapter = self.get_adapter()?;
+        let mock = is_mock_adapter(&adapter);
+        let grants: Vec<DeviceGrant> = self
+            .device_grants
+            .iter()
+            .filter(|grant| {
+                grant.origin == origin && grant.private == private && grant.mock == mock
+            })
+            .cloned()
+            .collect();
+        if grants
+            .iter()
+            .any(|grant| !self.address_to_id.contains_key(&grant.address))
+        {
+            self.get_and_cache_devices(&mut adapter);
+        }
+
+        let mut devices = vec![];
+        for grant in grants {
+            // The devices that the adapter does not know about anymore are left out.
+            let Some(device_id) = self.address_to_id.get(&grant.address).cloned() else {
+                continue;
+            };
+            let Some(device) = self.get_device(&mut adapter, &device_id) else {
+                continue;
+            };
+            let name = device.get_name().ok();
+            self.allowed_services
+                .entry(device_id.clone())
+                .or_default()
+                .extend(grant.services);
+            devices.push(BluetoothDeviceMsg {
+                id: device_id,
+                name,
+            });
+        }
+        Ok(BluetoothResponse::GetDevices(devices))
+    }
+
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattserver-connect
     fn gatt_server_connect(&mut self, device_id: String) -> BluetoothResponseResult {
         // Step 2.
@@ -707,6 +1099,42 @@ impl BluetoothManager {
         }
     }
 
+    /// Notify the listeners of the connected devices that are no longer connected, and forget
+    /// the attributes of these devices.
+    fn poll_connections(&mut self) {
+        let Ok(mut adapter) = self.get_adapter() else {
+            return;
+        };
+        let device_ids: Vec<String> = self.disconnection_listeners.keys().cloned().collect();
+        for id in device_ids {
+            let connected = self
+                .get_device(&mut adapter, &id)
+                .is_some_and(|d| d.is_connected().unwrap_or(false));
+            if connected {
+                continue;
+            }
+            for listener in self
+                .disconnection_listeners
+                .remove(&id)
+                .unwrap_or_default()
+                .into_values()
+            {
+                let _ = listener.send(());
+            }
+            self.remove_device_attributes_from_caches(&id);
+        }
+    }
+
+    fn remove_disconnection_listener(&mut self, device_id: &str, listener_id: &str) {
+        let Some(listeners) = self.disconnection_listeners.get_mut(device_id) else {
+            return;
+        };
+        listeners.remove(listener_id);
+        if listeners.is_empty() {
+            self.disconnection_listeners.remove(device_id);
+        }
+    }
+
     // https://webbluetoothcg.github.io/web-bluetooth/#getgattchildren
     fn get_gatt_children(
         &mut self,
@@ -872,17 +1300,20 @@ impl BluetoothManager {
         let mut adapter = self.get_adapter()?;
 
         // (Characteristic) Step 5.3.
-        let mut value = self
-            .get_gatt_characteristic(&mut adapter, &id)
-            .map(|c| c.read_value().unwrap_or_default());
+        let mut value: Option<BluetoothResult<Vec<u8>>> =
+            self.get_gatt_characteristic(&mut adapter, &id).map(|c| {
+                check_simulated_gatt_error(&adapter, c.get_uuid())?;
+                Ok(c.read_value().unwrap_or_default())
+            });
 
         // (Characteristic) TODO: Step 5.4: Handle all the errors returned from the read_value call.
 
         // (Descriptor) Step 5.2.
         if value.is_none() {
-            value = self
-                .get_gatt_descriptor(&mut adapter, &id)
-                .map(|d| d.read_value().unwrap_or_default());
+            value = self.get_gatt_descriptor(&mut adapter, &id).map(|d| {
+                check_simulated_gatt_error(&adapter, d.get_uuid())?;
+                Ok(d.read_value().unwrap_or_default())
+            });
         }
 
         // (Descriptor) TODO: Step 5.3: Handle all the errors returned from the read_value call.
@@ -890,7 +1321,7 @@ impl BluetoothManager {
         match value {
             // (Characteristic) Step 5.5.4.
             // (Descriptor)     Step 5.4.3.
-            Some(v) => Ok(BluetoothResponse::ReadValue(v)),
+            Some(v) => Ok(BluetoothResponse::ReadValue(v?)),
 
             // (Characteristic) Step 4.
             // (Descriptor)     Step 4.
@@ -906,30 +1337,31 @@ impl BluetoothManager {
         let mut adapter = self.get_adapter()?;
 
         // (Characteristic) Step 7.3.
-        let mut result = self
-            .get_gatt_characteristic(&mut adapter, &id)
-            .map(|c| c.write_value(value.clone()));
+        let mut result: Option<BluetoothResult<()>> =
+            self.get_gatt_characteristic(&mut adapter, &id).map(|c| {
+                check_simulated_gatt_error(&adapter, c.get_uuid())?;
+                // (Characteristic) Step 7.1.
+                c.write_value(value.clone())
+                    .map_err(|_| BluetoothError::NotSupported)
+            });
 
         // (Characteristic) TODO: Step 7.4: Handle all the errors returned from the write_value call.
 
         // (Descriptor) Step 7.2.
         if result.is_none() {
-            result = self
-                .get_gatt_descriptor(&mut adapter, &id)
-                .map(|d| d.write_value(value.clone()));
+            result = self.get_gatt_descriptor(&mut adapter, &id).map(|d| {
+                check_simulated_gatt_error(&adapter, d.get_uuid())?;
+                d.write_value(value.clone())
+                    .map_err(|_| BluetoothError::NotSupported)
+            });
         }
 
         // (Descriptor) TODO: Step 7.3: Handle all the errors returned from the write_value call.
 
         match result {
-            Some(v) => match v {
-                // (Characteristic) Step 7.5.3.
-                // (Descriptor) Step 7.4.3.
-                Ok(_) => Ok(BluetoothResponse::WriteValue(value)),
-
-                // (Characteristic) Step 7.1.
-                Err(_) => Err(BluetoothError::NotSupported),
-            },
+            // (Characteristic) Step 7.5.3.
+            // (Descriptor) Step 7.4.3.
+            Some(v) => v.map(|_| BluetoothResponse::WriteValue(value)),
 
             // (Characteristic) Step 6.
             // (Descriptor)     Step 6.
@@ -938,45 +1370,215 @@ impl BluetoothManager {
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
+    fn start_notifications(
+        &mut self,
+        id: String,
+        subscription_id: String,
+        sender: IpcSender<Vec<u8>>,
+    ) -> BluetoothResponseResult {
+        // Step 3 - 4.
+        if !self.characteristic_is_cached(&id) {
+            return Err(BluetoothError::InvalidState);
+        }
+
+        // TODO: Step 7: Missing because it is optional.
+        let is_notifying = self.is_notifying(&id);
+        let mut adapter = self.get_adapter()?;
+        let characteristic = match self.get_gatt_characteristic(&mut adapter, &id) {
+            Some(c) => c,
+            // Step 4.
+            None => return Err(BluetoothError::InvalidState),
+        };
+        check_simulated_gatt_error(&adapter, characteristic.get_uuid())?;
+        // Step 8.
+        // The platform subscription is shared by all the subscriptions to the characteristic.
+        // TODO: Handle all the errors returned from the start_notify call.
+        if !is_notifying && characteristic.start_notify().is_err() {
+            // Step 5.
+            return Err(BluetoothError::NotSupported);
+        }
+        // Only the values received after this point are notified.
+        let last_value = characteristic.get_value().ok();
+        self.notification_subscriptions.insert(
+            subscription_id,
+            NotificationSubscription {
+                characteristic_id: id,
+                sender,
+                last_value,
+            },
+        );
+        // Step 11.
+        Ok(BluetoothResponse::StartNotifications)
+    }
+
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
-    fn enable_notification(&mut self, id: String, enable: bool) -> BluetoothResponseResult {
-        // (StartNotifications) Step 3 - 4.
-        // (StopNotifications) Step 1 - 2.
+    fn stop_notifications(&mut self, id: String, subscription_id: &str) -> BluetoothResponseResult {
+        // Step 1 - 2.
         if !self.characteristic_is_cached(&id) {
             return Err(BluetoothError::InvalidState);
         }
 
-        // (StartNotification) TODO: Step 7: Missing because it is optional.
+        // Step 4.
+        if self
+            .notification_subscriptions
+            .remove(subscription_id)
+            .is_some() &&
+            !self.is_notifying(&id)
+        {
+            let mut adapter = self.get_adapter()?;
+            let characteristic = self
+                .get_gatt_characteristic(&mut adapter, &id)
+                .ok_or(BluetoothError::InvalidState)?;
+            characteristic
+                .stop_notify()
+                .map_err(|_| BluetoothError::NotSupported)?;
+        }
+        // Step 5.
+        Ok(BluetoothResponse::StopNotifications)
+    }
+
+    fn is_notifying(&self, characteristic_id: &str) -> bool {
+        self.notification_subscriptions
+            .values()
+            .any(|subscription| subscription.characteristic_id == characteristic_id)
+    }
+
+    /// Send the values of the characteristics with notifications that changed since the last time
+    /// they were sent, forgetting the subscriptions that are gone.
+    ///
+    /// The adapters only expose the current value of a characteristic, so notifications are
+    /// found by polling it, and a poll can't tell a new notification with the same value apart
+    /// from the previous one. Unlike what the spec requires, a notification that carries the same
+    /// value as the previous one, like a heart rate that stays constant, or that is overwritten
+    /// by another before the next poll, does not fire `characteristicvaluechanged`.
+    // TODO: Listen to the `PropertiesChanged` signals of BlueZ, which are sent for every
+    // notification, once the adapters expose them.
+    fn poll_notifications(&mut self) {
+        let Ok(mut adapter) = self.get_adapter() else {
+            return;
+        };
+        let characteristic_ids: HashSet<String> = self
+            .notification_subscriptions
+            .values()
+            .map(|subscription| subscription.characteristic_id.clone())
+            .collect();
+        let values: HashMap<String, Vec<u8>> = characteristic_ids
+            .into_iter()
+            .filter_map(|id| {
+                let value = self
+                    .get_gatt_characteristic(&mut adapter, &id)?
+                    .get_value()
+                    .ok()?;
+                Some((id, value))
+            })
+            .collect();
+
+        let mut unsubscribed = HashSet::new();
+        self.notification_subscriptions.retain(|_, subscription| {
+            let Some(value) = values.get(&subscription.characteristic_id) else {
+                return true;
+            };
+            if subscription.last_value.as_ref() == Some(value) {
+                return true;
+            }
+            subscription.last_value = Some(value.clone());
+            if subscription.sender.send(value.clone()).is_ok() {
+                return true;
+            }
+            unsubscribed.insert(subscription.characteristic_id.clone());
+            false
+        });
+
+        // Like stopNotifications(), end the platform subscription with the last one.
+        for id in unsubscribed {
+            if self.is_notifying(&id) {
+                continue;
+            }
+            if let Some(characteristic) = self.get_gatt_characteristic(&mut adapter, &id) {
+                let _ = characteristic.stop_notify();
+            }
+        }
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
+    fn watch_advertisements(
+        &mut self,
+        device_id: String,
+        watch_id: String,
+        sender: IpcSender<BluetoothAdvertisementMsg>,
+    ) -> BluetoothResponseResult {
         let mut adapter = self.get_adapter()?;
-        match self.get_gatt_characteristic(&mut adapter, &id) {
-            Some(c) => {
-                let result = if enable {
-                    // (StartNotification) Step 8.
-                    // TODO: Handle all the errors returned from the start_notify call.
-                    c.start_notify()
-                } else {
-                    // (StopNotification) Step 4.
-                    c.stop_notify()
-                };
-                match result {
-                    // (StartNotification) Step 11.
-                    // (StopNotification)  Step 5.
-                    Ok(_) => Ok(BluetoothResponse::EnableNotification(())),
+        if self.get_device(&mut adapter, &device_id).is_none() {
+            return Err(BluetoothError::NotFound);
+        }
 
-                    // (StartNotification) Step 5.
-                    Err(_) => Err(BluetoothError::NotSupported),
-                }
+        // Scan for advertisements for as long as a device is watched.
+        if self.discovery_session.is_none() {
+            let session = adapter
+                .create_discovery_session()
+                .map_err(|_| BluetoothError::NotSupported)?;
+            session
+                .start_discovery()
+                .map_err(|_| BluetoothError::NotSupported)?;
+            self.discovery_session = Some(session);
+        }
+        self.advertisement_watchers.insert(
+            watch_id.clone(),
+            AdvertisementWatcher {
+                device_id,
+                sender,
+                last_advertisement: None,
             },
-            // (StartNotification) Step 4.
-            None => Err(BluetoothError::InvalidState),
+        );
+        Ok(BluetoothResponse::WatchAdvertisements(watch_id))
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-unwatchadvertisements
+    fn unwatch_advertisements(&mut self, watch_id: &str) {
+        self.advertisement_watchers.remove(watch_id);
+        self.stop_discovery_if_unwatched();
+    }
+
+    /// End the discovery session that was started for advertisement watchers once there are
+    /// none left.
+    fn stop_discovery_if_unwatched(&mut self) {
+        if self.advertisement_watchers.is_empty() {
+            if let Some(session) = self.discovery_session.take() {
+                let _ = session.stop_discovery();
+            }
         }
     }
 
-    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
-    fn watch_advertisements(&mut self, _device_id: String) -> BluetoothResponseResult {
-        // Step 2.
-        // TODO: Implement this when supported in lower level
-        Err(BluetoothError::NotSupported)
+    /// Send the advertisement data of the watched devices that changed since the last time it was
+    /// sent, forgetting the watchers that are gone.
+    fn poll_advertisements(&mut self) {
+        let Ok(mut adapter) = self.get_adapter() else {
+            return;
+        };
+        let device_ids: HashSet<String> = self
+            .advertisement_watchers
+            .values()
+            .map(|watcher| watcher.device_id.clone())
+            .collect();
+        let advertisements: HashMap<String, BluetoothAdvertisementMsg> = device_ids
+            .into_iter()
+            .filter_map(|id| {
+                let advertisement = advertisement_from_device(self.get_device(&mut adapter, &id)?)?;
+                Some((id, advertisement))
+            })
+            .collect();
+
+        self.advertisement_watchers.retain(|_, watcher| {
+            let Some(advertisement) = advertisements.get(&watcher.device_id) else {
+                return true;
+            };
+            if watcher.last_advertisement.as_ref() == Some(advertisement) {
+                return true;
+            }
+            watcher.last_advertisement = Some(advertisement.clone());
+            watcher.sender.send(advertisement.clone()).is_ok()
+        });
+        self.stop_discovery_if_unwatched();
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getavailability
@@ -985,4 +1587,23 @@ impl BluetoothManager {
             self.get_adapter().is_ok(),
         ))
     }
+
+    fn watch_availability(&mut self, watch_id: String, sender: IpcSender<bool>) {
+        if self.availability_listeners.is_empty() {
+            self.availability = self.get_adapter().is_ok();
+        }
+        self.availability_listeners.insert(watch_id, sender);
+    }
+
+    /// Send the availability to the listeners when it changes, forgetting the listeners that are
+    /// gone.
+    fn poll_availability(&mut self) {
+        let availability = self.get_adapter().is_ok();
+        if availability == self.availability {
+            return;
+        }
+        self.availability = availability;
+        self.availability_listeners
+            .retain(|_, listener| listener.send(availability).is_ok());
+    }
 }
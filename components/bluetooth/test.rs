@@ -8,6 +8,7 @@ use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::string::String;
 
+use bluetooth_traits::BluetoothError;
 use uuid::Uuid;
 
 use crate::bluetooth::{
@@ -20,6 +21,7 @@ thread_local!(pub static CACHED_IDS: RefCell<HashSet<Uuid>> = RefCell::new(HashS
 
 const ADAPTER_ERROR: &str = "No adapter found";
 const WRONG_DATA_SET_ERROR: &str = "Wrong data set name was provided";
+const DEVICE_NOT_FOUND_ERROR: &str = "No device found with the given id";
 const READ_FLAG: &str = "read";
 const WRITE_FLAG: &str = "write";
 const NOTIFY_FLAG: &str = "notify";
@@ -49,10 +51,13 @@ const NO_NAME_HEART_RATE_ADAPTER: &str = "NoNameHeartRateAdapter";
 // https://cs.chromium.org/chromium/src/content/shell/browser/layout_test/layout_test_bluetooth_adapter_provider.h?l=284
 const TWO_HEART_RATE_SERVICES_ADAPTER: &str = "TwoHeartRateServicesAdapter";
 const BLOCKLIST_TEST_ADAPTER: &str = "BlocklistTestAdapter";
+// https://cs.chromium.org/chromium/src/content/shell/browser/layout_test/layout_test_bluetooth_adapter_provider.h?l=456
+const FAILING_GATT_OPERATIONS_ADAPTER: &str = "FailingGATTOperationsAdapter";
 
 // Device names
 const CONNECTABLE_DEVICE_NAME: &str = "Connectable Device";
 const EMPTY_DEVICE_NAME: &str = "";
+const ERRORS_DEVICE_NAME: &str = "Errors Device";
 // https://webbluetoothcg.github.io/web-bluetooth/tests.html#glucosedevice
 const GLUCOSE_DEVICE_NAME: &str = "Glucose Device";
 // https://webbluetoothcg.github.io/web-bluetooth/tests.html#heartratedevice
@@ -66,11 +71,17 @@ const GLUCOSE_DEVICE_ADDRESS: &str = "00:00:00:00:00:02";
 // https://webbluetoothcg.github.io/web-bluetooth/tests.html#heartratedevice
 const HEART_RATE_DEVICE_ADDRESS: &str = "00:00:00:00:00:03";
 const UNICODE_DEVICE_ADDRESS: &str = "00:00:00:00:00:01";
+const ERRORS_DEVICE_ADDRESS: &str = "00:00:00:00:00:05";
+
+// Advertisement data
+const HEART_RATE_DEVICE_RSSI: i16 = -60;
+const HEART_RATE_DEVICE_TX_POWER: i16 = 4;
 
 // Service UUIDs
 const BLOCKLIST_TEST_SERVICE_UUID: &str = "611c954a-263b-4f4a-aab6-01ddb953f985";
 // https://www.bluetooth.com/specifications/gatt/viewer?attributeXmlFile=org.bluetooth.service.device_information.xml
 const DEVICE_INFORMATION_UUID: &str = "0000180a-0000-1000-8000-00805f9b34fb";
+const ERRORS_SERVICE_UUID: &str = "000000a0-97e5-4cd7-b9f1-f5a427670c59";
 // https://www.bluetooth.com/specifications/gatt/viewer?attributeXmlFile=org.bluetooth.service.generic_access.xml
 const GENERIC_ACCESS_SERVICE_UUID: &str = "00001800-0000-1000-8000-00805f9b34fb";
 // https://www.bluetooth.com/specifications/gatt/viewer?attributeXmlFile=org.bluetooth.service.glucose.xml
@@ -91,6 +102,11 @@ const BODY_SENSOR_LOCATION_CHARACTERISTIC_UUID: &str = "00002a38-0000-1000-8000-
 // https://www.bluetooth.com/specifications/gatt/
 // viewer?attributeXmlFile=org.bluetooth.characteristic.gap.device_name.xml
 const DEVICE_NAME_CHARACTERISTIC_UUID: &str = "00002a00-0000-1000-8000-00805f9b34fb";
+// Every GATT operation on these characteristics and their descriptors fails with the
+// corresponding error.
+const NETWORK_ERROR_CHARACTERISTIC_UUID: &str = "000000a1-97e5-4cd7-b9f1-f5a427670c59";
+const NOT_SUPPORTED_ERROR_CHARACTERISTIC_UUID: &str = "000000a2-97e5-4cd7-b9f1-f5a427670c59";
+const SECURITY_ERROR_CHARACTERISTIC_UUID: &str = "000000a3-97e5-4cd7-b9f1-f5a427670c59";
 // https://www.bluetooth.com/specifications/gatt/
 // viewer?attributeXmlFile=org.bluetooth.characteristic.heart_rate_measurement.xml
 const HEART_RATE_MEASUREMENT_CHARACTERISTIC_UUID: &str = "00002a37-0000-1000-8000-00805f9b34fb";
@@ -110,6 +126,9 @@ const CHARACTERISTIC_USER_DESCRIPTION_UUID: &str = "00002901-0000-1000-8000-0080
 // https://www.bluetooth.com/specifications/gatt/
 // viewer?attributeXmlFile=org.bluetooth.descriptor.gatt.client_characteristic_configuration.xml
 const CLIENT_CHARACTERISTIC_CONFIGURATION_UUID: &str = "00002902-0000-1000-8000-00805f9b34fb";
+const NETWORK_ERROR_DESCRIPTOR_UUID: &str = "000000b1-97e5-4cd7-b9f1-f5a427670c59";
+const NOT_SUPPORTED_ERROR_DESCRIPTOR_UUID: &str = "000000b2-97e5-4cd7-b9f1-f5a427670c59";
+const SECURITY_ERROR_DESCRIPTOR_UUID: &str = "000000b3-97e5-4cd7-b9f1-f5a427670c59";
 // https://www.bluetooth.com/specifications/gatt/
 // viewer?attributeXmlFile=org.bluetooth.descriptor.number_of_digitals.xml
 const NUMBER_OF_DIGITALS_UUID: &str = "00002909-0000-1000-8000-00805f9b34fb";
@@ -341,6 +360,8 @@ fn create_heart_rate_device(
             HEART_RATE_SERVICE_UUID.to_owned(),
         ],
     )?;
+    heart_rate_device.set_rssi(HEART_RATE_DEVICE_RSSI)?;
+    heart_rate_device.set_tx_power(HEART_RATE_DEVICE_TX_POWER)?;
 
     if empty {
         return Ok(heart_rate_device);
@@ -512,6 +533,81 @@ fn create_glucose_heart_rate_devices(adapter: &BluetoothAdapter) -> Result<(), B
     Ok(())
 }
 
+fn create_errors_device(adapter: &BluetoothAdapter) -> Result<(), Box<dyn Error>> {
+    let errors_device = create_device_with_uuids(
+        adapter,
+        ERRORS_DEVICE_NAME.to_owned(),
+        ERRORS_DEVICE_ADDRESS.to_owned(),
+        vec![ERRORS_SERVICE_UUID.to_owned()],
+    )?;
+
+    let errors_service = create_service(&errors_device, ERRORS_SERVICE_UUID.to_owned())?;
+
+    for (characteristic_uuid, descriptor_uuid) in [
+        (
+            NETWORK_ERROR_CHARACTERISTIC_UUID,
+            NETWORK_ERROR_DESCRIPTOR_UUID,
+        ),
+        (
+            NOT_SUPPORTED_ERROR_CHARACTERISTIC_UUID,
+            NOT_SUPPORTED_ERROR_DESCRIPTOR_UUID,
+        ),
+        (
+            SECURITY_ERROR_CHARACTERISTIC_UUID,
+            SECURITY_ERROR_DESCRIPTOR_UUID,
+        ),
+    ] {
+        let error_characteristic = create_characteristic_with_value(
+            &errors_service,
+            characteristic_uuid.to_owned(),
+            vec![0],
+        )?;
+        error_characteristic.set_flags(vec![
+            READ_FLAG.to_string(),
+            WRITE_FLAG.to_string(),
+            NOTIFY_FLAG.to_string(),
+        ])?;
+
+        let _error_descriptor = create_descriptor_with_value(
+            &error_characteristic,
+            descriptor_uuid.to_owned(),
+            vec![0],
+        )?;
+    }
+    Ok(())
+}
+
+/// The error that every GATT operation on the mock attribute with the given UUID fails with.
+pub fn simulated_gatt_error(uuid: &str) -> Option<BluetoothError> {
+    match uuid {
+        NETWORK_ERROR_CHARACTERISTIC_UUID | NETWORK_ERROR_DESCRIPTOR_UUID => {
+            Some(BluetoothError::Network)
+        },
+        NOT_SUPPORTED_ERROR_CHARACTERISTIC_UUID | NOT_SUPPORTED_ERROR_DESCRIPTOR_UUID => {
+            Some(BluetoothError::NotSupported)
+        },
+        SECURITY_ERROR_CHARACTERISTIC_UUID | SECURITY_ERROR_DESCRIPTOR_UUID => {
+            Some(BluetoothError::Security)
+        },
+        _ => None,
+    }
+}
+
+/// Make the mock device with the given id drop its connection, as if it went out of range.
+pub fn simulate_disconnection(
+    manager: &mut BluetoothManager,
+    device_id: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut adapter = match manager.get_or_create_adapter() {
+        Some(adapter) => adapter,
+        None => return Err(Box::from(ADAPTER_ERROR.to_string())),
+    };
+    match manager.get_device(&mut adapter, device_id) {
+        Some(device) => device.set_connected(false),
+        None => Err(Box::from(DEVICE_NOT_FOUND_ERROR.to_string())),
+    }
+}
+
 pub fn test(manager: &mut BluetoothManager, data_set_name: String) -> Result<(), Box<dyn Error>> {
     let may_existing_adapter = manager.get_or_create_adapter();
     let adapter = match may_existing_adapter.as_ref() {
@@ -588,6 +684,11 @@ pub fn test(manager: &mut BluetoothManager, data_set_name: String) -> Result<(),
 
             create_blocklisted_device(adapter)?;
         },
+        FAILING_GATT_OPERATIONS_ADAPTER => {
+            set_adapter(adapter, FAILING_GATT_OPERATIONS_ADAPTER.to_owned())?;
+
+            create_errors_device(adapter)?;
+        },
         _ => return Err(Box::from(WRONG_DATA_SET_ERROR.to_string())),
     }
     Ok(())
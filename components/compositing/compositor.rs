@@ -6,6 +6,7 @@ use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::env;
 use std::fs::{create_dir_all, File};
+use std::future::Future;
 use std::io::Write;
 use std::iter::once;
 use std::rc::Rc;
@@ -19,8 +20,10 @@ use compositing_traits::{
 };
 use crossbeam_channel::Sender;
 use embedder_traits::Cursor;
-use euclid::{Point2D, Rect, Scale, Transform3D, Vector2D};
+use euclid::{Point2D, Scale, Transform3D, Vector2D};
 use fnv::{FnvHashMap, FnvHashSet};
+use futures::channel::oneshot;
+use futures::FutureExt;
 use image::{DynamicImage, ImageFormat};
 use ipc_channel::ipc;
 use libc::c_void;
@@ -36,14 +39,15 @@ use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use webrender::{CaptureBits, RenderApi, Transaction};
 use webrender_api::units::{
-    DeviceIntPoint, DeviceIntSize, DevicePoint, DeviceRect, LayoutPoint, LayoutRect, LayoutSize,
-    LayoutVector2D, WorldPoint,
+    DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePoint, DeviceRect, LayoutPoint, LayoutRect,
+    LayoutSize, LayoutTransform, LayoutVector2D, WorldPoint,
 };
 use webrender_api::{
-    self, BuiltDisplayList, DirtyRect, DisplayListPayload, DocumentId, Epoch as WebRenderEpoch,
-    ExternalScrollId, FontInstanceOptions, HitTestFlags, PipelineId as WebRenderPipelineId,
-    PropertyBinding, ReferenceFrameKind, RenderReasons, SampledScrollOffset, ScrollLocation,
-    SpaceAndClipInfo, SpatialId, SpatialTreeItemKey, TransformStyle,
+    self, BuiltDisplayList, DirtyRect, DisplayListPayload, DocumentId, DynamicProperties,
+    Epoch as WebRenderEpoch, ExternalScrollId, FontInstanceOptions, HitTestFlags,
+    PipelineId as WebRenderPipelineId, PropertyBinding, PropertyValue, ReferenceFrameKind,
+    RenderReasons, SampledScrollOffset, ScrollLocation, SpaceAndClipInfo, SpatialId,
+    SpatialTreeItemKey, TransformStyle,
 };
 use webrender_traits::display_list::{HitTestInfo, ScrollTree};
 use webrender_traits::{
@@ -115,9 +119,6 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     min_viewport_zoom: Option<PinchZoomFactor>,
     max_viewport_zoom: Option<PinchZoomFactor>,
 
-    /// "Desktop-style" zoom that resizes the viewport to fit the window.
-    page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
-
     /// The type of composition to perform
     composite_target: CompositeTarget,
 
@@ -153,6 +154,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// image for the reftest framework.
     ready_to_save_state: ReadyState,
 
+    /// Screenshots requested by the embedder that are waiting for the output to be stable.
+    pending_screenshots: Vec<PendingScreenshot>,
+
     /// The webrender renderer.
     webrender: webrender::Renderer,
 
@@ -251,6 +255,8 @@ enum CompositingReason {
     NewWebRenderFrame,
     /// The window has been resized and will need to be synchronously repainted.
     Resize,
+    /// The embedder requested a screenshot of a webview.
+    Screenshot,
 }
 
 #[derive(Debug, PartialEq)]
@@ -293,6 +299,14 @@ struct PipelineDetails {
     /// The compositor-side [ScrollTree]. This is used to allow finding and scrolling
     /// nodes in the compositor before forwarding new offsets to WebRender.
     scroll_tree: ScrollTree,
+
+    /// The values of the transforms bound to WebRender properties by the most recent display
+    /// list of this pipeline, if they were updated after that display list was sent.
+    animated_transforms: Vec<PropertyValue<LayoutTransform>>,
+
+    /// The values of the opacities bound to WebRender properties by the most recent display
+    /// list of this pipeline, if they were updated after that display list was sent.
+    animated_opacities: Vec<PropertyValue<f32>>,
 }
 
 impl PipelineDetails {
@@ -306,6 +320,8 @@ impl PipelineDetails {
             throttled: false,
             hit_test_items: Vec::new(),
             scroll_tree: ScrollTree::default(),
+            animated_transforms: Vec::new(),
+            animated_opacities: Vec::new(),
         }
     }
 
@@ -333,6 +349,26 @@ impl PipelineDetails {
     }
 }
 
+/// A screenshot of a webview requested with [`IOCompositor::take_screenshot`].
+struct PendingScreenshot {
+    webview_id: WebViewId,
+    /// The area to capture, relative to the webview, or `None` for all of it.
+    rect: Option<DeviceRect>,
+    sender: oneshot::Sender<Image>,
+}
+
+/// The area of the output to capture for a screenshot of the given area of a webview, or of all
+/// of it, or `None` if there is nothing to capture.
+fn screenshot_area(webview_rect: DeviceRect, rect: Option<DeviceRect>) -> Option<DeviceIntRect> {
+    let rect = match rect {
+        Some(rect) => rect
+            .translate(webview_rect.min.to_vector())
+            .intersection(&webview_rect)?,
+        None => webview_rect,
+    };
+    Some(rect.round_out().to_i32()).filter(|rect| !rect.is_empty())
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum CompositeTarget {
     /// Draw directly to a window.
@@ -366,6 +402,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 WebView {
                     pipeline_id: None,
                     rect: embedder_coordinates.get_viewport().to_f32(),
+                    ..Default::default()
                 },
             )
             .expect("Infallible with a new WebViewManager");
@@ -388,7 +425,6 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             pending_scroll_zoom_events: Vec::new(),
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
-            page_zoom: Scale::new(1.0),
             viewport_zoom: PinchZoomFactor::new(1.0),
             min_viewport_zoom: Some(PinchZoomFactor::new(1.0)),
             max_viewport_zoom: None,
@@ -398,6 +434,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             constellation_chan: state.constellation_chan,
             time_profiler_chan: state.time_profiler_chan,
             ready_to_save_state: ReadyState::Unknown,
+            pending_screenshots: Vec::new(),
             webrender: state.webrender,
             webrender_document: state.webrender_document,
             webrender_api: state.webrender_api,
@@ -585,7 +622,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             },
 
             CompositorMsg::CreatePng(page_rect, reply) => {
-                let res = self.composite_specific_target(CompositeTarget::SharedMemory, page_rect);
+                let rect = page_rect.map(|rect| {
+                    self.device_pixels_per_page_pixel()
+                        .transform_rect(&rect)
+                        .to_box2d()
+                        .to_i32()
+                });
+                let res = self.composite_specific_target(CompositeTarget::SharedMemory, rect);
                 if let Err(ref e) = res {
                     info!("Error retrieving PNG: {:?}", e);
                 }
@@ -786,15 +829,45 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 details.hit_test_items = display_list_info.hit_test_info;
                 details.install_new_scroll_tree(display_list_info.scroll_tree);
 
+                // The new display list carries the current values of the properties it binds,
+                // which WebRender would otherwise override with the ones last sent for them.
+                let had_animated_properties = !details.animated_transforms.is_empty() ||
+                    !details.animated_opacities.is_empty();
+                details.animated_transforms.clear();
+                details.animated_opacities.clear();
+
                 let mut transaction = Transaction::new();
                 transaction
                     .set_display_list(display_list_info.epoch, (pipeline_id, built_display_list));
                 self.update_transaction_with_all_scroll_offsets(&mut transaction);
+                if had_animated_properties {
+                    self.update_transaction_with_all_animated_properties(&mut transaction);
+                }
                 self.generate_frame(&mut transaction, RenderReasons::SCENE);
                 self.webrender_api
                     .send_transaction(self.webrender_document, transaction);
             },
 
+            ForwardedToCompositorMsg::Layout(ScriptToCompositorMsg::UpdateAnimatedProperties {
+                pipeline_id,
+                transforms,
+                opacities,
+            }) => {
+                let pipeline_id: PipelineId = pipeline_id.into();
+                let pipeline_details = match self.pipeline_details.get_mut(&pipeline_id) {
+                    Some(details) => details,
+                    None => return,
+                };
+                pipeline_details.animated_transforms = transforms;
+                pipeline_details.animated_opacities = opacities;
+
+                let mut transaction = Transaction::new();
+                self.update_transaction_with_all_animated_properties(&mut transaction);
+                self.generate_frame(&mut transaction, RenderReasons::ANIMATED_PROPERTY);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, transaction);
+            },
+
             ForwardedToCompositorMsg::Layout(ScriptToCompositorMsg::HitTest(
                 pipeline,
                 point,
@@ -1098,7 +1171,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         let mut builder = webrender_api::DisplayListBuilder::new(root_pipeline);
         builder.begin();
 
-        let zoom_factor = self.device_pixels_per_page_pixel().0;
+        // The page zoom of each webview is applied by a reference frame of its own below.
+        let zoom_factor = (self.hidpi_factor() * self.pinch_zoom_level()).get();
         let zoom_reference_frame = builder.push_reference_frame(
             LayoutPoint::zero(),
             SpatialId::root_reference_frame(root_pipeline),
@@ -1120,19 +1194,33 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         let root_clip_id = builder.define_clip_rect(zoom_reference_frame, scaled_viewport_rect);
         let clip_chain_id = builder.define_clip_chain(None, [root_clip_id]);
-        for (_, webview) in self.webviews.painting_order() {
+        for (index, (_, webview)) in self.webviews.painting_order().enumerate() {
             if let Some(pipeline_id) = webview.pipeline_id {
-                let scaled_webview_rect = webview.rect / zoom_factor;
+                let page_zoom = webview.page_zoom.get();
+                let page_zoom_reference_frame = builder.push_reference_frame(
+                    LayoutPoint::zero(),
+                    zoom_reference_frame,
+                    TransformStyle::Flat,
+                    PropertyBinding::Value(Transform3D::scale(page_zoom, page_zoom, 1.)),
+                    ReferenceFrameKind::Transform {
+                        is_2d_scale_translation: true,
+                        should_snap: true,
+                        paired_with_perspective: false,
+                    },
+                    SpatialTreeItemKey::new(1, index as u64),
+                );
+                let scaled_webview_rect = webview.rect / (zoom_factor * page_zoom);
                 builder.push_iframe(
                     LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
                     LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
                     &SpaceAndClipInfo {
-                        spatial_id: zoom_reference_frame,
+                        spatial_id: page_zoom_reference_frame,
                         clip_chain_id,
                     },
                     pipeline_id.into(),
                     true,
                 );
+                builder.pop_reference_frame();
             }
         }
 
@@ -1172,6 +1260,25 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// WebRender replaces all of the dynamic properties of a document at once, so the ones of
+    /// every pipeline need to be sent whenever the ones of one pipeline change.
+    fn update_transaction_with_all_animated_properties(&self, transaction: &mut Transaction) {
+        let mut properties = DynamicProperties {
+            transforms: Vec::new(),
+            floats: Vec::new(),
+            colors: Vec::new(),
+        };
+        for details in self.pipeline_details.values() {
+            properties
+                .transforms
+                .extend_from_slice(&details.animated_transforms);
+            properties
+                .floats
+                .extend_from_slice(&details.animated_opacities);
+        }
+        transaction.update_dynamic_properties(properties);
+    }
+
     fn set_frame_tree_for_webview(&mut self, frame_tree: &SendableFrameTree) {
         debug!("{}: Setting frame tree for webview", frame_tree.pipeline.id);
 
@@ -1197,6 +1304,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 WebView {
                     pipeline_id,
                     rect: self.embedder_coordinates.get_viewport().to_f32(),
+                    ..Default::default()
                 },
             ) {
                 error!("{webview_id}: Creating webview that already exists");
@@ -1318,7 +1426,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     ) {
         // The device pixel ratio used by the style system should include the scale from page pixels
         // to device pixels, but not including any pinch zoom.
-        let device_pixel_ratio = self.device_pixels_per_page_pixel_not_including_page_zoom();
+        let device_pixel_ratio = self.device_pixels_per_page_pixel_not_including_pinch_zoom(Some(
+            top_level_browsing_context_id,
+        ));
         let initial_viewport = rect.size().to_f32() / device_pixel_ratio;
         let msg = ConstellationMsg::WindowSize(
             top_level_browsing_context_id,
@@ -1794,9 +1904,24 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         cursor: DevicePoint,
         scroll_location: ScrollLocation,
     ) -> Option<(PipelineId, ExternalScrollId, LayoutVector2D)> {
+        let hit_test_result = match self.hit_test_at_point(cursor) {
+            Some(result) => result,
+            None => return None,
+        };
+
+        // The delta is scaled by the zoom of the webview that is scrolled, which is not
+        // necessarily the topmost one.
+        let webview_id = self
+            .pipeline_details
+            .get(&hit_test_result.pipeline_id)?
+            .pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.top_level_browsing_context_id);
         let scroll_location = match scroll_location {
             ScrollLocation::Delta(delta) => {
-                let device_pixels_per_page = self.device_pixels_per_page_pixel();
+                let device_pixels_per_page = self
+                    .device_pixels_per_page_pixel_not_including_pinch_zoom(webview_id) *
+                    self.pinch_zoom_level();
                 let scaled_delta = (Vector2D::from_untyped(delta.to_untyped()) /
                     device_pixels_per_page)
                     .to_untyped();
@@ -1807,11 +1932,6 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             ScrollLocation::Start | ScrollLocation::End => scroll_location,
         };
 
-        let hit_test_result = match self.hit_test_at_point(cursor) {
-            Some(result) => result,
-            None => return None,
-        };
-
         let pipeline_details = match self.pipeline_details.get_mut(&hit_test_result.pipeline_id) {
             Some(details) => details,
             None => return None,
@@ -1884,14 +2004,50 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.embedder_coordinates.hidpi_factor
     }
 
+    /// The scale from page pixels to device pixels in the topmost webview, which is the one that
+    /// receives input events.
     fn device_pixels_per_page_pixel(&self) -> Scale<f32, CSSPixel, DevicePixel> {
-        self.device_pixels_per_page_pixel_not_including_page_zoom() * self.pinch_zoom_level()
+        self.device_pixels_per_page_pixel_not_including_pinch_zoom(None) * self.pinch_zoom_level()
     }
 
-    fn device_pixels_per_page_pixel_not_including_page_zoom(
+    /// The scale from page pixels to device pixels in the given webview, or in the topmost one,
+    /// which includes its page zoom.
+    fn device_pixels_per_page_pixel_not_including_pinch_zoom(
         &self,
+        webview_id: Option<WebViewId>,
     ) -> Scale<f32, CSSPixel, DevicePixel> {
-        self.page_zoom * self.hidpi_factor()
+        let webview = match webview_id {
+            Some(webview_id) => self.webviews.get(webview_id),
+            None => self
+                .webviews
+                .painting_order()
+                .last()
+                .map(|(_, webview)| webview),
+        };
+        let page_zoom = webview.map_or(Scale::new(1.0), |webview| webview.page_zoom);
+        page_zoom * self.hidpi_factor()
+    }
+
+    pub fn page_zoom(&self, webview_id: WebViewId) -> Option<f32> {
+        self.webviews
+            .get(webview_id)
+            .map(|webview| webview.page_zoom.get())
+    }
+
+    /// Set the page zoom of a webview, which changes the size of its viewport and its device
+    /// pixel ratio, so its pages are laid out again.
+    pub fn set_page_zoom(&mut self, webview_id: WebViewId, page_zoom: f32) {
+        if self.shutdown_state != ShutdownState::NotShuttingDown {
+            return;
+        }
+
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return warn!("{webview_id}: SetPageZoom on unknown webview id");
+        };
+        webview.page_zoom = Scale::new(page_zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        let rect = webview.rect;
+        self.send_window_size_message_for_top_level_browser_context(rect, webview_id);
+        self.send_root_pipeline_display_list();
     }
 
     pub fn on_zoom_reset_window_event(&mut self) {
@@ -1899,7 +2055,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             return;
         }
 
-        self.page_zoom = Scale::new(1.0);
+        for (_, webview) in self.webviews.iter_mut() {
+            webview.page_zoom = Scale::new(1.0);
+        }
         self.update_after_zoom_or_hidpi_change();
     }
 
@@ -1908,11 +2066,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             return;
         }
 
-        self.page_zoom = Scale::new(
-            (self.page_zoom.get() * magnification)
-                .max(MIN_ZOOM)
-                .min(MAX_ZOOM),
-        );
+        for (_, webview) in self.webviews.iter_mut() {
+            webview.page_zoom = Scale::new(
+                (webview.page_zoom.get() * magnification)
+                    .max(MIN_ZOOM)
+                    .min(MAX_ZOOM),
+            );
+        }
         self.update_after_zoom_or_hidpi_change();
     }
 
@@ -2052,11 +2212,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     /// Composite to the given target if any, or the current target otherwise.
     /// Returns Ok if composition was performed or Err if it was not possible to composite for some
     /// reason. When the target is [CompositeTarget::SharedMemory], the image is read back from the
-    /// GPU and returned as Ok(Some(png::Image)), otherwise we return Ok(None).
+    /// GPU and returned as Ok(Some(png::Image)), otherwise we return Ok(None). `rect` is the area
+    /// of the window to read back, or `None` for all of it.
     fn composite_specific_target(
         &mut self,
         target: CompositeTarget,
-        page_rect: Option<Rect<f32, CSSPixel>>,
+        rect: Option<DeviceIntRect>,
     ) -> Result<Option<Image>, UnableToComposite> {
         if self.waiting_on_present {
             debug!("tried to composite while waiting on present");
@@ -2184,17 +2345,16 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             }
         }
 
-        let (x, y, width, height) = if let Some(rect) = page_rect {
-            let rect = self.device_pixels_per_page_pixel().transform_rect(&rect);
-
-            let x = rect.origin.x as i32;
+        let (x, y, width, height) = if let Some(rect) = rect {
             // We need to convert to the bottom-left origin coordinate
             // system used by OpenGL
-            let y = (size.height as f32 - rect.origin.y - rect.size.height) as i32;
-            let w = rect.size.width as u32;
-            let h = rect.size.height as u32;
-
-            (x, y, w, h)
+            let rect = self.embedder_coordinates.flip_rect(&rect);
+            (
+                rect.min.x,
+                rect.min.y,
+                rect.width() as u32,
+                rect.height() as u32,
+            )
         } else {
             (0, 0, size.width, size.height)
         };
@@ -2408,9 +2568,57 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         if !self.pending_scroll_zoom_events.is_empty() {
             self.process_pending_scroll_events()
         }
+        if !self.pending_screenshots.is_empty() {
+            self.process_pending_screenshots();
+        }
         self.shutdown_state != ShutdownState::FinishedShuttingDown
     }
 
+    /// Capture the composited output of a webview, or of the given area of it, once the output
+    /// is stable. The returned future resolves to the image, or to `None` if the webview goes
+    /// away or if the area is empty.
+    ///
+    /// Other webviews painted on top of this one within that area are captured too.
+    pub fn take_screenshot(
+        &mut self,
+        webview_id: WebViewId,
+        rect: Option<DeviceRect>,
+    ) -> Result<impl Future<Output = Option<Image>>, UnknownWebView> {
+        if self.webviews.get(webview_id).is_none() {
+            return Err(UnknownWebView(webview_id));
+        }
+        let (sender, receiver) = oneshot::channel();
+        self.pending_screenshots.push(PendingScreenshot {
+            webview_id,
+            rect,
+            sender,
+        });
+        self.composite_if_necessary(CompositingReason::Screenshot);
+        Ok(receiver.map(Result::ok))
+    }
+
+    fn process_pending_screenshots(&mut self) {
+        for screenshot in std::mem::take(&mut self.pending_screenshots) {
+            let Some(webview) = self.webviews.get(screenshot.webview_id) else {
+                continue;
+            };
+            let Some(rect) = screenshot_area(webview.rect, screenshot.rect) else {
+                continue;
+            };
+            match self.composite_specific_target(CompositeTarget::SharedMemory, Some(rect)) {
+                Ok(image) => {
+                    if let Some(image) = image {
+                        let _ = screenshot.sender.send(image);
+                    }
+                },
+                Err(error) => {
+                    trace!("Unable to take screenshot: {error:?}");
+                    self.pending_screenshots.push(screenshot);
+                },
+            }
+        }
+    }
+
     /// Repaints and recomposites synchronously. You must be careful when calling this, as if a
     /// paint is not scheduled the compositor will hang forever.
     ///
@@ -2515,3 +2723,68 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use webrender_api::units::{DeviceIntRect, DeviceRect};
+
+    use super::screenshot_area;
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> DeviceRect {
+        DeviceRect::new((x0, y0).into(), (x1, y1).into())
+    }
+
+    fn int_rect(x0: i32, y0: i32, x1: i32, y1: i32) -> DeviceIntRect {
+        DeviceIntRect::new((x0, y0).into(), (x1, y1).into())
+    }
+
+    #[test]
+    fn test_screenshot_area_of_whole_webview() {
+        let webview = rect(100., 50., 500., 350.);
+        assert_eq!(
+            screenshot_area(webview, None),
+            Some(int_rect(100, 50, 500, 350))
+        );
+    }
+
+    #[test]
+    fn test_screenshot_area_is_relative_to_webview() {
+        let webview = rect(100., 50., 500., 350.);
+        assert_eq!(
+            screenshot_area(webview, Some(rect(10., 20., 110., 70.))),
+            Some(int_rect(110, 70, 210, 120))
+        );
+    }
+
+    #[test]
+    fn test_screenshot_area_is_clipped_to_webview() {
+        let webview = rect(100., 50., 500., 350.);
+        assert_eq!(
+            screenshot_area(webview, Some(rect(300., 200., 1000., 1000.))),
+            Some(int_rect(400, 250, 500, 350))
+        );
+    }
+
+    #[test]
+    fn test_screenshot_area_rounds_out() {
+        let webview = rect(0., 0., 100., 100.);
+        assert_eq!(
+            screenshot_area(webview, Some(rect(10.5, 10.5, 20.25, 20.75))),
+            Some(int_rect(10, 10, 21, 21))
+        );
+    }
+
+    #[test]
+    fn test_screenshot_area_outside_of_webview() {
+        let webview = rect(100., 50., 500., 350.);
+        assert_eq!(
+            screenshot_area(webview, Some(rect(400., 0., 500., 100.))),
+            None
+        );
+        assert_eq!(
+            screenshot_area(webview, Some(rect(10., 10., 10., 50.))),
+            None
+        );
+        assert_eq!(screenshot_area(rect(0., 0., 0., 0.), None), None);
+    }
+}
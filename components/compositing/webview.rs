@@ -5,12 +5,28 @@
 use std::collections::HashMap;
 
 use base::id::{PipelineId, WebViewId};
+use euclid::Scale;
+use servo_geometry::DeviceIndependentPixel;
+use style_traits::CSSPixel;
 use webrender_api::units::DeviceRect;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct WebView {
     pub pipeline_id: Option<PipelineId>,
     pub rect: DeviceRect,
+    /// "Desktop-style" zoom that resizes the viewport of this webview to fit its rect. It is
+    /// kept when the webview navigates.
+    pub page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
+}
+
+impl Default for WebView {
+    fn default() -> Self {
+        Self {
+            pipeline_id: None,
+            rect: DeviceRect::zero(),
+            page_zoom: Scale::new(1.0),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -102,6 +118,10 @@ impl<WebView> WebViewManager<WebView> {
         Ok(false)
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&WebViewId, &mut WebView)> {
+        self.webviews.iter_mut()
+    }
+
     pub fn painting_order(&self) -> impl Iterator<Item = (&WebViewId, &WebView)> {
         self.painting_order
             .iter()
@@ -5,10 +5,11 @@
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
 use std::fmt::{Debug, Error, Formatter};
+use std::ops::Range;
 use std::time::Duration;
 
 use base::id::{PipelineId, TopLevelBrowsingContextId};
-use embedder_traits::{EmbedderProxy, EventLoopWaker};
+use embedder_traits::{AutoplayPolicy, EmbedderProxy, EventLoopWaker, ImeEvent};
 use euclid::Scale;
 use keyboard_types::KeyboardEvent;
 use libc::c_void;
@@ -55,6 +56,9 @@ pub enum EmbedderEvent {
     WindowResize,
     /// Sent when a navigation request from script is allowed/refused.
     AllowNavigationResponse(PipelineId, bool),
+    /// Sent when the embedder decides whether a slow script should continue running (true)
+    /// or be stopped (false).
+    SlowScriptResponse(PipelineId, bool),
     /// Sent when a new URL is to be loaded.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Sent when a mouse hit test is to be performed.
@@ -74,8 +78,20 @@ pub enum EmbedderEvent {
     PinchZoom(f32),
     /// Sent when the user resets zoom to default.
     ResetZoom,
+    /// Set the page zoom of a webview, which scales the layout of its documents rather than
+    /// their rendering, and is kept when the webview navigates.
+    SetPageZoom(TopLevelBrowsingContextId, f32),
     /// Sent when the user uses chrome navigation (i.e. backspace or shift-backspace).
     Navigation(TopLevelBrowsingContextId, TraversalDirection),
+    /// Traverse the session history of a webview to the entry at the given index.
+    GoToHistoryIndex(TopLevelBrowsingContextId, usize),
+    /// Remove the session history entries of a webview outside of the given range of indices,
+    /// which must contain the current entry.
+    TruncateHistory(TopLevelBrowsingContextId, Range<usize>),
+    /// Replace the session history of a webview with entries for the given URLs, for example
+    /// to restore it from a previous run. The document currently loaded in the webview stands
+    /// for the entry at the given index, and the other entries are loaded when traversed to.
+    RestoreHistory(TopLevelBrowsingContextId, Vec<ServoUrl>, usize),
     /// Sent when the user quits the application
     Quit,
     /// Sent when the user exits from fullscreen mode
@@ -84,6 +100,8 @@ pub enum EmbedderEvent {
     Keyboard(KeyboardEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
+    /// Stop loading the current page, like the stop button of a browser.
+    Stop(TopLevelBrowsingContextId),
     /// Create a new top-level browsing context.
     NewWebView(ServoUrl, TopLevelBrowsingContextId),
     /// Close a top-level browsing context.
@@ -115,8 +133,14 @@ pub enum EmbedderEvent {
     MediaSessionAction(MediaSessionActionType),
     /// Set whether to use less resources, by stopping animations and running timers at a heavily limited rate.
     SetWebViewThrottled(TopLevelBrowsingContextId, bool),
+    /// Set whether the audio of a webview is silenced.
+    SetWebViewMuted(TopLevelBrowsingContextId, bool),
+    /// Set whether the media of a webview may start playing without the user interacting with it.
+    SetWebViewAutoplayPolicy(TopLevelBrowsingContextId, AutoplayPolicy),
     /// Virtual keyboard was dismissed
     IMEDismissed,
+    /// The input method edited the focused element of the focused webview.
+    Ime(ImeEvent),
     /// Sent on platforms like Android where the native widget surface can be
     /// automatically destroyed by the system, for example when the app
     /// is sent to background.
@@ -137,6 +161,7 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::WindowResize => write!(f, "Resize"),
             EmbedderEvent::Keyboard(..) => write!(f, "Keyboard"),
             EmbedderEvent::AllowNavigationResponse(..) => write!(f, "AllowNavigationResponse"),
+            EmbedderEvent::SlowScriptResponse(..) => write!(f, "SlowScriptResponse"),
             EmbedderEvent::LoadUrl(..) => write!(f, "LoadUrl"),
             EmbedderEvent::MouseWindowEventClass(..) => write!(f, "Mouse"),
             EmbedderEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
@@ -146,9 +171,14 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::Zoom(..) => write!(f, "Zoom"),
             EmbedderEvent::PinchZoom(..) => write!(f, "PinchZoom"),
             EmbedderEvent::ResetZoom => write!(f, "ResetZoom"),
+            EmbedderEvent::SetPageZoom(..) => write!(f, "SetPageZoom"),
             EmbedderEvent::Navigation(..) => write!(f, "Navigation"),
+            EmbedderEvent::GoToHistoryIndex(..) => write!(f, "GoToHistoryIndex"),
+            EmbedderEvent::TruncateHistory(..) => write!(f, "TruncateHistory"),
+            EmbedderEvent::RestoreHistory(..) => write!(f, "RestoreHistory"),
             EmbedderEvent::Quit => write!(f, "Quit"),
             EmbedderEvent::Reload(..) => write!(f, "Reload"),
+            EmbedderEvent::Stop(..) => write!(f, "Stop"),
             EmbedderEvent::NewWebView(_, TopLevelBrowsingContextId(webview_id)) => {
                 write!(f, "NewWebView({webview_id:?})")
             },
@@ -181,7 +211,10 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
             EmbedderEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
             EmbedderEvent::SetWebViewThrottled(..) => write!(f, "SetWebViewThrottled"),
+            EmbedderEvent::SetWebViewMuted(..) => write!(f, "SetWebViewMuted"),
+            EmbedderEvent::SetWebViewAutoplayPolicy(..) => write!(f, "SetWebViewAutoplayPolicy"),
             EmbedderEvent::IMEDismissed => write!(f, "IMEDismissed"),
+            EmbedderEvent::Ime(..) => write!(f, "Ime"),
             EmbedderEvent::ClearCache => write!(f, "ClearCache"),
             EmbedderEvent::InvalidateNativeSurface => write!(f, "InvalidateNativeSurface"),
             EmbedderEvent::ReplaceNativeSurface(..) => write!(f, "ReplaceNativeSurface"),
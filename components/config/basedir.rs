@@ -41,3 +41,13 @@ pub fn default_config_dir() -> Option<PathBuf> {
     config_dir.push("Servo");
     Some(config_dir)
 }
+
+#[cfg(not(target_os = "android"))]
+pub fn default_download_dir() -> Option<PathBuf> {
+    ::dirs_next::download_dir()
+}
+
+#[cfg(target_os = "android")]
+pub fn default_download_dir() -> Option<PathBuf> {
+    None
+}
@@ -87,6 +87,10 @@ pub struct Opts {
     /// Whether we're running in multiprocess mode.
     pub multiprocess: bool,
 
+    /// Whether documents of different origins run in different event loops, rather than only
+    /// documents of different sites. In multiprocess mode, each event loop is a content process.
+    pub isolate_origins: bool,
+
     /// Whether we want background hang monitor enabled or not
     pub background_hang_monitor: bool,
 
@@ -412,6 +416,7 @@ pub fn default_opts() -> Opts {
         webdriver_port: None,
         initial_window_size: Size2D::new(1024, 740),
         multiprocess: false,
+        isolate_origins: false,
         background_hang_monitor: false,
         random_pipeline_closure_probability: None,
         random_pipeline_closure_seed: None,
@@ -506,6 +511,11 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
     );
     opts.optopt("", "resolution", "Set window resolution.", "1024x740");
     opts.optflag("M", "multiprocess", "Run in multiprocess mode");
+    opts.optflag(
+        "",
+        "isolate-origins",
+        "Run documents of different origins in different event loops",
+    );
     opts.optflag("B", "bhm", "Background Hang Monitor enabled");
     opts.optflag("S", "sandbox", "Run in a sandbox if multiprocess");
     opts.optopt(
@@ -756,6 +766,7 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         webdriver_port,
         initial_window_size,
         multiprocess: opt_match.opt_present("M"),
+        isolate_origins: opt_match.opt_present("isolate-origins"),
         background_hang_monitor: opt_match.opt_present("B"),
         sandbox: opt_match.opt_present("S"),
         random_pipeline_closure_probability,
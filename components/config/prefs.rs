@@ -165,6 +165,23 @@ mod gen {
         std::cmp::max(num_cpus::get() * 3 / 4, 1) as i64
     }
 
+    fn default_slow_script_timeout_ms() -> i64 {
+        10000
+    }
+
+    fn default_microtask_budget_ms() -> i64 {
+        0
+    }
+
+    // hyper's own default for how long idle pooled connections are kept alive.
+    fn default_connection_pool_idle_timeout_ms() -> i64 {
+        90000
+    }
+
+    fn default_connection_pool_max_idle_per_host() -> i64 {
+        i64::MAX
+    }
+
     fn black() -> i64 {
         0x000000
     }
@@ -209,6 +226,18 @@ mod gen {
                     /// List of comma-separated backends to be used by wgpu
                     wgpu_backend: String,
                 },
+                abort_controller: {
+                    /// Expose the `AbortController` and `AbortSignal` interfaces. Fetches can
+                    /// not be aborted with them yet.
+                    #[serde(default)]
+                    enabled: bool,
+                },
+                audio_output_selection: {
+                    /// Expose `AudioContext.setSinkId()` and `sinkId`. Only the default audio
+                    /// output device and no device at all can be switched to for now.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
@@ -221,6 +250,12 @@ mod gen {
                 canvas_text: {
                     enabled: bool,
                 },
+                clipboard: {
+                    /// Expose `navigator.clipboard`, whose reads and writes go through the
+                    /// delegate set with `Servo::set_clipboard_delegate`.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 composition_event: {
                     #[serde(rename = "dom.compositionevent.enabled")]
                     enabled: bool,
@@ -233,6 +268,12 @@ mod gen {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                embedder_messages: {
+                    /// Expose `window.postMessageToEmbedder()`, so that pages can send messages to
+                    /// the handlers registered by the embedder.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
@@ -242,9 +283,21 @@ mod gen {
                 gamepad: {
                     enabled: bool,
                 },
+                geolocation: {
+                    /// Expose `navigator.geolocation`, whose positions come from the delegate
+                    /// set with `Servo::set_geolocation_delegate`.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 imagebitmap: {
                     enabled: bool,
                 },
+                longtask: {
+                    /// Report tasks that run for more than 50ms as `longtask` performance
+                    /// entries.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -259,6 +312,12 @@ mod gen {
                 mutation_observer: {
                     enabled: bool,
                 },
+                notifications: {
+                    /// Expose the `Notification` interface, whose notifications are shown by
+                    /// the delegate set with `Servo::set_notification_delegate`.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 offscreen_canvas: {
                     enabled: bool,
                 },
@@ -268,9 +327,20 @@ mod gen {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                request_idle_callback: {
+                    /// Expose `requestIdleCallback()` and `cancelIdleCallback()` on `Window`.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 resize_observer: {
                     enabled: bool,
                 },
+                scheduler: {
+                    /// Expose `window.scheduler` and the `TaskController` and `TaskSignal`
+                    /// interfaces of the Prioritized Task Scheduling API.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 script: {
                     asynch: bool,
                 },
@@ -435,6 +505,10 @@ mod gen {
                     },
                 },
                 mem: {
+                    /// The maximum size in MiB of the JS heap of a single event loop. Going over it,
+                    /// even after a full GC, crashes the pages it hosts. Zero disables the limit.
+                    #[serde(default)]
+                    event_loop_limit_mb: i64,
                     gc: {
                         allocation_threshold_mb: i64,
                         allocation_threshold_factor: i64,
@@ -483,6 +557,16 @@ mod gen {
                 shared_memory: {
                     enabled: bool,
                 },
+                slow_script: {
+                    /// How long a microtask checkpoint may run before it yields to the event loop,
+                    /// leaving the remaining microtasks to the checkpoint after the next task.
+                    /// Zero or less disables the budget.
+                    #[serde(default = "default_microtask_budget_ms")]
+                    microtask_budget_ms: i64,
+                    /// How long a script may run before the embedder is asked whether to stop it.
+                    #[serde(default = "default_slow_script_timeout_ms")]
+                    timeout_ms: i64,
+                },
                 strict: {
                     debug: {
                         enabled: bool,
@@ -520,10 +604,26 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                debug: {
+                    verify_incremental_box_tree: {
+                        /// After each incremental box tree update, construct the box tree again
+                        /// from scratch and panic if the two differ.
+                        #[serde(default)]
+                        enabled: bool,
+                    }
+                },
                 flexbox: {
                     enabled: bool,
                 },
                 legacy_layout: bool,
+                testing: {
+                    deterministic: {
+                        /// Disable timing- and machine-dependent behaviors so that reftests
+                        /// and screenshots are stable across runs and machines.
+                        #[serde(default)]
+                        enabled: bool,
+                    }
+                },
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 writing_mode: {
@@ -542,6 +642,14 @@ mod gen {
                 }
             },
             network: {
+                connection_pool: {
+                    /// How long an idle connection is kept in the pool before being closed.
+                    #[serde(default = "default_connection_pool_idle_timeout_ms")]
+                    idle_timeout_ms: i64,
+                    /// The maximum number of idle connections kept in the pool for each host.
+                    #[serde(default = "default_connection_pool_max_idle_per_host")]
+                    max_idle_per_host: i64,
+                },
                 enforce_tls: {
                     enabled: bool,
                     localhost: bool,
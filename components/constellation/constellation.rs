@@ -85,22 +85,25 @@
 //! See <https://github.com/servo/servo/issues/14704>
 
 use std::borrow::{Cow, ToOwned};
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::mem::replace;
+use std::ops::Range;
 use std::rc::{Rc, Weak};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::{process, thread};
 
 use background_hang_monitor::HangMonitorRegister;
 use background_hang_monitor_api::{
-    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangMonitorAlert,
+    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangAlert, HangMonitorAlert,
+    MonitoredComponentId, MonitoredComponentType,
 };
 use base::id::{
     BroadcastChannelRouterId, BrowsingContextGroupId, BrowsingContextId, HistoryStateId,
-    MessagePortId, MessagePortRouterId, PipelineId, PipelineNamespace, PipelineNamespaceId,
-    PipelineNamespaceRequest, TopLevelBrowsingContextId, WebViewId,
+    MessagePortId, MessagePortRouterId, NotificationId, PipelineId, PipelineNamespace,
+    PipelineNamespaceId, PipelineNamespaceRequest, TopLevelBrowsingContextId, WebViewId,
 };
 use base::Epoch;
 use bluetooth_traits::BluetoothRequest;
@@ -116,11 +119,14 @@ use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, DevtoolsPageInfo, NavigationState,
     ScriptToDevtoolsControlMsg,
 };
+use embedder_traits::user_script::UserScript;
 use embedder_traits::{
-    Cursor, EmbedderMsg, EmbedderProxy, MediaSessionEvent, MediaSessionPlaybackState,
+    Cursor, EmbedderMsg, EmbedderProxy, GeolocationUpdate, JavaScriptEvaluationError,
+    JavaScriptEvaluationId, MediaPolicy, MediaSessionEvent, MediaSessionPlaybackState,
+    NotificationEvent, SavedDocumentState, SessionHistoryEntry, WebViewState,
 };
 use euclid::default::Size2D as UntypedSize2D;
-use euclid::Size2D;
+use euclid::{Scale, Size2D};
 use fonts::FontCacheThread;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
@@ -132,7 +138,9 @@ use media::{GLPlayerThreads, WindowGLContext};
 use net_traits::pub_domains::reg_host;
 use net_traits::request::{Referrer, RequestBuilder};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::{
+    self, CoreResourceMsg, FetchChannels, FetchResponseMsg, IpcSend, ResourceThreads,
+};
 use profile_traits::{mem, time};
 use script_layout_interface::{LayoutFactory, ScriptThreadFactory};
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
@@ -154,6 +162,7 @@ use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use style_traits::CSSPixel;
 use webgpu::{self, WebGPU, WebGPURequest};
 use webrender::{RenderApi, RenderApiSender};
+use webrender_api::units::DevicePixel;
 use webrender_api::DocumentId;
 use webrender_traits::{WebRenderNetApi, WebRenderScriptApi, WebrenderExternalImageRegistry};
 
@@ -231,6 +240,16 @@ struct WebView {
 
     /// The joint session history for this webview.
     session_history: JointSessionHistory,
+
+    /// The device pixel ratio of this webview, which includes its page zoom, to lay out the
+    /// documents that it navigates to.
+    device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+
+    /// How the media of the documents of this webview is played.
+    media_policy: MediaPolicy,
+
+    /// The pipelines of this webview that are playing audio that the user can hear.
+    audible_pipelines: HashSet<PipelineId>,
 }
 
 /// A browsing context group.
@@ -243,17 +262,35 @@ struct BrowsingContextGroup {
 
     /// The set of all event loops in this BrowsingContextGroup.
     /// We store the event loops in a map
-    /// indexed by registered domain name (as a `Host`) to event loops.
-    /// It is important that scripts with the same eTLD+1,
-    /// who are part of the same browsing-context group
-    /// share an event loop, since they can use `document.domain`
-    /// to become same-origin, at which point they can share DOM objects.
-    event_loops: HashMap<Host, Weak<EventLoop>>,
+    /// indexed by the site or origin of their documents.
+    event_loops: HashMap<EventLoopKey, Weak<EventLoop>>,
 
     /// The set of all WebGPU channels in this BrowsingContextGroup.
     webgpus: HashMap<Host, WebGPU>,
 }
 
+/// The documents of a browsing context group that share an event loop.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum EventLoopKey {
+    /// By default, scripts with the same eTLD+1 share an event loop, since they can use
+    /// `document.domain` to become same-origin, at which point they can share DOM objects.
+    Site(Host),
+    /// With `--isolate-origins`, `document.domain` has no effect, so only scripts of the same
+    /// origin need to share an event loop.
+    Origin(ImmutableOrigin),
+}
+
+impl EventLoopKey {
+    fn for_url(url: &ServoUrl) -> Option<EventLoopKey> {
+        if opts::get().isolate_origins {
+            let origin = url.origin();
+            origin.is_tuple().then_some(EventLoopKey::Origin(origin))
+        } else {
+            reg_host(url).map(EventLoopKey::Site)
+        }
+    }
+}
+
 /// The `Constellation` itself. In the servo browser, there is one
 /// constellation, which maintains all of the browser global data.
 /// In embedded applications, there may be more than one constellation,
@@ -354,6 +391,9 @@ pub struct Constellation<STF, SWF> {
     /// bluetooth thread.
     bluetooth_ipc_sender: IpcSender<BluetoothRequest>,
 
+    /// An IPC channel to the bluetooth thread for private browsing contexts.
+    private_bluetooth_ipc_sender: IpcSender<BluetoothRequest>,
+
     /// A map of origin to sender to a Service worker manager.
     sw_managers: HashMap<ImmutableOrigin, IpcSender<ServiceWorkerMsg>>,
 
@@ -432,6 +472,33 @@ pub struct Constellation<STF, SWF> {
     /// we store a `SessionHistoryChange` object for the navigation in progress.
     pending_changes: Vec<SessionHistoryChange>,
 
+    /// The webview states that embedders restored, by the pipeline of the document that was
+    /// loaded for their current entry. The rest of the state is restored once it has loaded.
+    pending_webview_states: HashMap<PipelineId, WebViewState>,
+
+    /// The saved states of the documents of restored session history entries that have not
+    /// been traversed to yet, by the id of the discarded pipeline of their entry.
+    restored_document_states: HashMap<PipelineId, SavedDocumentState>,
+
+    /// The saved states of documents that are being loaded again, by the pipeline that loads
+    /// them. They are restored once the document has loaded.
+    pending_document_states: HashMap<PipelineId, SavedDocumentState>,
+
+    /// The pipelines that wait for the position of the device, and whether they asked for a
+    /// high accuracy position.
+    geolocation_watchers: HashMap<PipelineId, bool>,
+
+    /// The requests of the navigations that turned into downloads, until the embedder fetches
+    /// or discards them.
+    pending_downloads: HashMap<PipelineId, RequestBuilder>,
+
+    /// Whether the embedder was asked for position updates, and if so, whether with high
+    /// accuracy.
+    geolocation_updates: Option<bool>,
+
+    /// The pipelines that created the notifications shown by the embedder.
+    notifications: HashMap<NotificationId, PipelineId>,
+
     /// Pipeline IDs are namespaced in order to avoid name collisions,
     /// and the namespaces are allocated by the constellation.
     next_pipeline_namespace_id: PipelineNamespaceId,
@@ -497,6 +564,9 @@ pub struct Constellation<STF, SWF> {
 
     /// User agent string to report in network requests.
     user_agent: Cow<'static, str>,
+
+    /// The user scripts of the embedder, injected into the documents of new pipelines.
+    user_scripts: Arc<RwLock<Vec<UserScript>>>,
 }
 
 /// State needed to construct a constellation.
@@ -513,6 +583,9 @@ pub struct InitialConstellationState {
     /// A channel to the bluetooth thread.
     pub bluetooth_thread: IpcSender<BluetoothRequest>,
 
+    /// A channel to the bluetooth thread for private browsing contexts.
+    pub private_bluetooth_thread: IpcSender<BluetoothRequest>,
+
     /// A channel to the font cache thread.
     pub font_cache_thread: FontCacheThread,
 
@@ -551,6 +624,9 @@ pub struct InitialConstellationState {
     /// User agent string to report in network requests.
     pub user_agent: Cow<'static, str>,
 
+    /// The user scripts of the embedder, which it can change at any time.
+    pub user_scripts: Arc<RwLock<Vec<UserScript>>>,
+
     pub wgpu_image_map: Arc<Mutex<HashMap<u64, webgpu::PresentationData>>>,
 }
 
@@ -757,6 +833,7 @@ where
                     webviews: WebViewManager::default(),
                     devtools_sender: state.devtools_sender,
                     bluetooth_ipc_sender: state.bluetooth_thread,
+                    private_bluetooth_ipc_sender: state.private_bluetooth_thread,
                     public_resource_threads: state.public_resource_threads,
                     private_resource_threads: state.private_resource_threads,
                     font_cache_thread: state.font_cache_thread,
@@ -772,6 +849,13 @@ where
                     pipelines: HashMap::new(),
                     browsing_contexts: HashMap::new(),
                     pending_changes: vec![],
+                    pending_webview_states: HashMap::new(),
+                    restored_document_states: HashMap::new(),
+                    pending_document_states: HashMap::new(),
+                    geolocation_watchers: HashMap::new(),
+                    pending_downloads: HashMap::new(),
+                    geolocation_updates: None,
+                    notifications: HashMap::new(),
                     // We initialize the namespace at 2, since we reserved
                     // namespace 0 for the embedder, and 0 for the constellation
                     next_pipeline_namespace_id: PipelineNamespaceId(2),
@@ -809,6 +893,7 @@ where
                     player_context: state.player_context,
                     active_media_session: None,
                     user_agent: state.user_agent,
+                    user_scripts: state.user_scripts,
                 };
 
                 constellation.run();
@@ -845,7 +930,7 @@ where
 
     fn get_event_loop(
         &mut self,
-        host: &Host,
+        key: &EventLoopKey,
         top_level_browsing_context_id: &TopLevelBrowsingContextId,
         opener: &Option<BrowsingContextId>,
     ) -> Result<Weak<EventLoop>, &'static str> {
@@ -879,7 +964,7 @@ where
         };
         bc_group
             .event_loops
-            .get(host)
+            .get(key)
             .ok_or("Trying to get an event-loop from an unknown browsing context group")
             .map(|event_loop| event_loop.clone())
     }
@@ -887,7 +972,7 @@ where
     fn set_event_loop(
         &mut self,
         event_loop: Weak<EventLoop>,
-        host: Host,
+        key: EventLoopKey,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         opener: Option<BrowsingContextId>,
     ) {
@@ -926,12 +1011,12 @@ where
         if let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) {
             if bc_group
                 .event_loops
-                .insert(host.clone(), event_loop)
+                .insert(key.clone(), event_loop)
                 .is_some()
             {
                 warn!(
                     "Double-setting an event-loop for {:?} at {:?}",
-                    host, relevant_top_level
+                    key, relevant_top_level
                 );
             }
         }
@@ -964,7 +1049,7 @@ where
             pipeline_id, browsing_context_id
         );
 
-        let (event_loop, host) = match sandbox {
+        let (event_loop, event_loop_key) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
                 // If this is an about:blank or about:srcdoc load, it must share the creator's
@@ -973,23 +1058,20 @@ where
                 if load_data.url.as_str() != "about:blank" &&
                     load_data.url.as_str() != "about:srcdoc"
                 {
-                    match reg_host(&load_data.url) {
+                    match EventLoopKey::for_url(&load_data.url) {
                         None => (None, None),
-                        Some(host) => {
-                            match self.get_event_loop(
-                                &host,
-                                &top_level_browsing_context_id,
-                                &opener,
-                            ) {
+                        Some(key) => {
+                            match self.get_event_loop(&key, &top_level_browsing_context_id, &opener)
+                            {
                                 Err(err) => {
                                     warn!("{}", err);
-                                    (None, Some(host))
+                                    (None, Some(key))
                                 },
                                 Ok(event_loop) => {
                                     if let Some(event_loop) = event_loop.upgrade() {
                                         (Some(event_loop), None)
                                     } else {
-                                        (None, Some(host))
+                                        (None, Some(key))
                                     }
                                 },
                             }
@@ -1010,10 +1092,16 @@ where
             },
         };
 
-        let resource_threads = if is_private {
-            self.private_resource_threads.clone()
+        let (resource_threads, bluetooth_thread) = if is_private {
+            (
+                self.private_resource_threads.clone(),
+                self.private_bluetooth_ipc_sender.clone(),
+            )
         } else {
-            self.public_resource_threads.clone()
+            (
+                self.public_resource_threads.clone(),
+                self.bluetooth_ipc_sender.clone(),
+            )
         };
 
         let result = Pipeline::spawn::<STF>(InitialPipelineState {
@@ -1037,7 +1125,7 @@ where
             scheduler_chan: self.scheduler_ipc_sender.clone(),
             compositor_proxy: self.compositor_proxy.clone(),
             devtools_sender: self.devtools_sender.clone(),
-            bluetooth_thread: self.bluetooth_ipc_sender.clone(),
+            bluetooth_thread,
             swmanager_thread: self.swmanager_ipc_sender.clone(),
             font_cache_thread: self.font_cache_thread.clone(),
             resource_threads,
@@ -1045,7 +1133,7 @@ where
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: WindowSizeData {
                 initial_viewport: initial_window_size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                device_pixel_ratio: self.device_pixel_ratio(top_level_browsing_context_id),
             },
             event_loop,
             load_data,
@@ -1061,6 +1149,7 @@ where
             player_context: self.player_context.clone(),
             event_loop_waker: None,
             user_agent: self.user_agent.clone(),
+            user_scripts: self.user_scripts.read().unwrap().clone(),
         });
 
         let pipeline = match result {
@@ -1072,19 +1161,34 @@ where
             self.background_monitor_control_senders.push(chan);
         }
 
-        if let Some(host) = host {
+        if let Some(key) = event_loop_key {
             debug!(
-                "{}: Adding new host entry {}",
-                top_level_browsing_context_id, host,
+                "{}: Adding new event loop entry {:?}",
+                top_level_browsing_context_id, key,
             );
             self.set_event_loop(
                 Rc::downgrade(&pipeline.pipeline.event_loop),
-                host,
+                key,
                 top_level_browsing_context_id,
                 opener,
             );
         }
 
+        let media_policy = self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .map(|webview| webview.media_policy)
+            .unwrap_or_default();
+        if media_policy != MediaPolicy::default() {
+            let msg = ConstellationControlMsg::SetMediaPolicy(
+                top_level_browsing_context_id,
+                media_policy,
+            );
+            if let Err(e) = pipeline.pipeline.event_loop.send(msg) {
+                warn!("{pipeline_id}: Sending SetMediaPolicy to script failed ({e})");
+            }
+        }
+
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
     }
@@ -1300,13 +1404,44 @@ where
                 .embedder_proxy
                 .send((None, EmbedderMsg::ReportProfile(bytes))),
             HangMonitorAlert::Hang(hang) => {
-                // TODO: In case of a permanent hang being reported, add a "kill script" workflow,
-                // via the embedder?
                 warn!("Component hang alert: {:?}", hang);
+
+                // A permanently hanging script is reported to the embedder, which can choose to
+                // stop it via `SlowScriptResponse`.
+                if let HangAlert::Permanent(
+                    MonitoredComponentId(pipeline_id, MonitoredComponentType::Script),
+                    ..,
+                ) = hang
+                {
+                    let top_level_browsing_context_id = match self.pipelines.get(&pipeline_id) {
+                        Some(pipeline) => pipeline.top_level_browsing_context_id,
+                        None => return warn!("{}: Slow script after closure", pipeline_id),
+                    };
+                    self.embedder_proxy.send((
+                        Some(top_level_browsing_context_id),
+                        EmbedderMsg::SlowScript(pipeline_id),
+                    ));
+                }
             },
         }
     }
 
+    fn handle_slow_script_response(&self, pipeline_id: PipelineId, allow_continue: bool) {
+        if allow_continue {
+            return;
+        }
+        // The pipeline's event loop may live in any content process, but only the hang monitor
+        // that monitors it will act on the interrupt.
+        let component_id = MonitoredComponentId(pipeline_id, MonitoredComponentType::Script);
+        for background_monitor_control_sender in &self.background_monitor_control_senders {
+            if let Err(e) = background_monitor_control_sender.send(
+                BackgroundHangMonitorControlMsg::InterruptComponent(component_id.clone()),
+            ) {
+                warn!("error communicating with background hang monitor: {}", e);
+            }
+        }
+    }
+
     fn handle_request_from_network_listener(&mut self, message: (PipelineId, FetchResponseMsg)) {
         let (id, message_) = message;
         let result = match self.pipelines.get(&id) {
@@ -1351,7 +1486,13 @@ where
                 self.handle_key_msg(key_event);
             },
             FromCompositorMsg::IMEDismissed => {
-                self.handle_ime_dismissed();
+                self.forward_ime_event(CompositorEvent::IMEDismissedEvent);
+            },
+            FromCompositorMsg::Ime(event) => {
+                self.forward_ime_event(CompositorEvent::ImeEvent(event));
+            },
+            FromCompositorMsg::EvaluateJavaScript(webview_id, evaluation_id, script) => {
+                self.handle_evaluate_javascript(webview_id, evaluation_id, script);
             },
             // Perform a navigation previously requested by script, if approved by the embedder.
             // If there is already a pending page (self.pending_changes), it will not be overridden;
@@ -1497,6 +1638,21 @@ where
             FromCompositorMsg::TraverseHistory(top_level_browsing_context_id, direction) => {
                 self.handle_traverse_history_msg(top_level_browsing_context_id, direction);
             },
+            FromCompositorMsg::TraverseHistoryToIndex(top_level_browsing_context_id, index) => {
+                self.handle_traverse_history_to_index_msg(top_level_browsing_context_id, index);
+            },
+            FromCompositorMsg::TruncateHistory(top_level_browsing_context_id, entries) => {
+                self.handle_truncate_history_msg(top_level_browsing_context_id, entries);
+            },
+            FromCompositorMsg::RestoreHistory(top_level_browsing_context_id, urls, index) => {
+                self.handle_restore_history_msg(top_level_browsing_context_id, urls, index);
+            },
+            FromCompositorMsg::GetWebViewState(top_level_browsing_context_id, response_sender) => {
+                self.handle_get_webview_state_msg(top_level_browsing_context_id, response_sender);
+            },
+            FromCompositorMsg::RestoreWebViewState(top_level_browsing_context_id, state) => {
+                self.handle_restore_webview_state_msg(top_level_browsing_context_id, state);
+            },
             FromCompositorMsg::WindowSize(top_level_browsing_context_id, new_size, size_type) => {
                 self.handle_window_size_msg(top_level_browsing_context_id, new_size, size_type);
             },
@@ -1509,6 +1665,9 @@ where
             FromCompositorMsg::Reload(top_level_browsing_context_id) => {
                 self.handle_reload_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::Stop(top_level_browsing_context_id) => {
+                self.handle_stop_msg(top_level_browsing_context_id);
+            },
             FromCompositorMsg::LogEntry(top_level_browsing_context_id, thread_name, entry) => {
                 self.handle_log_entry(top_level_browsing_context_id, thread_name, entry);
             },
@@ -1525,6 +1684,9 @@ where
                     }
                 }
             },
+            FromCompositorMsg::SlowScriptResponse(pipeline_id, allow_continue) => {
+                self.handle_slow_script_response(pipeline_id, allow_continue);
+            },
             FromCompositorMsg::DisableProfiler => {
                 for background_monitor_control_sender in &self.background_monitor_control_senders {
                     if let Err(e) = background_monitor_control_sender
@@ -1543,6 +1705,12 @@ where
             FromCompositorMsg::SetWebViewThrottled(webview_id, throttled) => {
                 self.set_webview_throttled(webview_id, throttled);
             },
+            FromCompositorMsg::SetWebViewMuted(webview_id, muted) => {
+                self.update_webview_media_policy(webview_id, |policy| policy.muted = muted);
+            },
+            FromCompositorMsg::SetWebViewAutoplayPolicy(webview_id, autoplay) => {
+                self.update_webview_media_policy(webview_id, |policy| policy.autoplay = autoplay);
+            },
             FromCompositorMsg::ReadyToPresent(webview_ids) => {
                 self.embedder_proxy
                     .send((None, EmbedderMsg::ReadyToPresent(webview_ids)));
@@ -1550,6 +1718,18 @@ where
             FromCompositorMsg::Gamepad(gamepad_event) => {
                 self.handle_gamepad_msg(gamepad_event);
             },
+            FromCompositorMsg::GeolocationUpdate(update) => {
+                self.handle_geolocation_update(update);
+            },
+            FromCompositorMsg::NotificationEvent(notification_id, event) => {
+                self.handle_notification_event(notification_id, event);
+            },
+            FromCompositorMsg::FetchDownload(pipeline_id, channels) => {
+                self.handle_fetch_download(pipeline_id, channels);
+            },
+            FromCompositorMsg::DiscardDownload(pipeline_id) => {
+                self.pending_downloads.remove(&pipeline_id);
+            },
         }
     }
 
@@ -1632,6 +1812,9 @@ where
             FromScriptMsg::PipelineExited => {
                 self.handle_pipeline_exited(source_pipeline_id);
             },
+            FromScriptMsg::MemoryLimitExceeded(reason) => {
+                self.handle_panic(Some(source_top_ctx_id), reason, None);
+            },
             FromScriptMsg::DiscardDocument => {
                 self.handle_discard_document(source_top_ctx_id, source_pipeline_id);
             },
@@ -1642,6 +1825,14 @@ where
             FromScriptMsg::InitiateNavigateRequest(req_init, cancel_chan) => {
                 self.handle_navigate_request(source_pipeline_id, req_init, cancel_chan);
             },
+            FromScriptMsg::DownloadRequested(request, fetch_request) => {
+                self.pending_downloads
+                    .insert(source_pipeline_id, fetch_request);
+                self.embedder_proxy.send((
+                    Some(source_top_ctx_id),
+                    EmbedderMsg::DownloadRequested(request),
+                ));
+            },
             FromScriptMsg::ScriptLoadedURLInIFrame(load_info) => {
                 self.handle_script_loaded_url_in_iframe_msg(load_info);
             },
@@ -1661,6 +1852,9 @@ where
             FromScriptMsg::AbortLoadUrl => {
                 self.handle_abort_load_url_msg(source_pipeline_id);
             },
+            FromScriptMsg::CancelOngoingNavigation => {
+                self.handle_cancel_ongoing_navigation_msg(source_pipeline_id);
+            },
             // A page loaded has completed all parsing, script, and reflow messages have been sent.
             FromScriptMsg::LoadComplete => {
                 self.handle_load_complete_msg(source_top_ctx_id, source_pipeline_id)
@@ -1855,6 +2049,39 @@ where
             FromScriptMsg::TitleChanged(pipeline, title) => {
                 if let Some(pipeline) = self.pipelines.get_mut(&pipeline) {
                     pipeline.title = title;
+                    // The session history entries reported to the embedder carry the titles of
+                    // the documents of the top-level browsing context.
+                    if pipeline.browsing_context_id == BrowsingContextId::from(source_top_ctx_id) {
+                        self.notify_history_changed(source_top_ctx_id);
+                    }
+                }
+            },
+            FromScriptMsg::AudibleChanged(audible) => {
+                self.handle_audible_changed(source_top_ctx_id, source_pipeline_id, audible);
+            },
+            FromScriptMsg::StartGeolocationUpdates(high_accuracy) => {
+                self.geolocation_watchers
+                    .insert(source_pipeline_id, high_accuracy);
+                self.update_geolocation_updates();
+            },
+            FromScriptMsg::StopGeolocationUpdates => {
+                self.geolocation_watchers.remove(&source_pipeline_id);
+                self.update_geolocation_updates();
+            },
+            FromScriptMsg::ShowNotification(notification) => {
+                self.notifications
+                    .insert(notification.id, source_pipeline_id);
+                self.embedder_proxy.send((
+                    Some(source_top_ctx_id),
+                    EmbedderMsg::ShowNotification(notification),
+                ));
+            },
+            FromScriptMsg::CloseNotification(notification_id) => {
+                if self.notifications.remove(&notification_id).is_some() {
+                    self.embedder_proxy.send((
+                        Some(source_top_ctx_id),
+                        EmbedderMsg::CloseNotification(notification_id),
+                    ));
                 }
             },
         }
@@ -2743,18 +2970,32 @@ where
 
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("{}: Exited", pipeline_id);
-        self.pipelines.remove(&pipeline_id);
+        if let Some(pipeline) = self.pipelines.remove(&pipeline_id) {
+            self.handle_audible_changed(pipeline.top_level_browsing_context_id, pipeline_id, false);
+        }
+        if self.geolocation_watchers.remove(&pipeline_id).is_some() {
+            self.update_geolocation_updates();
+        }
+        self.close_notifications_of_pipeline(pipeline_id);
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
         // Treat send error the same as receiving a panic message
         error!("{}: Send error ({})", pipeline_id, err);
-        let top_level_browsing_context_id = self
-            .pipelines
-            .get(&pipeline_id)
-            .map(|pipeline| pipeline.top_level_browsing_context_id);
         let reason = format!("Send failed ({})", err);
-        self.handle_panic(top_level_browsing_context_id, reason, None);
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            return self.handle_panic(None, reason, None);
+        };
+
+        // Only the subframe is replaced with the crash page when its pipeline is gone, since the
+        // pipelines of its ancestors may be in another process.
+        let browsing_context_id = pipeline.browsing_context_id;
+        let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+        if browsing_context_id != BrowsingContextId::from(top_level_browsing_context_id) {
+            self.exit_if_hard_fail();
+            return self.load_crash_page(browsing_context_id, reason);
+        }
+        self.handle_panic(Some(top_level_browsing_context_id), reason, None);
     }
 
     fn handle_panic(
@@ -2763,12 +3004,7 @@ where
         reason: String,
         backtrace: Option<String>,
     ) {
-        if self.hard_fail {
-            // It's quite difficult to make Servo exit cleanly if some threads have failed.
-            // Hard fail exists for test runners so we crash and that's good enough.
-            println!("Pipeline failed in hard-fail mode.  Crashing!");
-            process::exit(1);
-        }
+        self.exit_if_hard_fail();
 
         let top_level_browsing_context_id = match top_level_browsing_context_id {
             Some(id) => id,
@@ -2787,12 +3023,32 @@ where
             EmbedderMsg::Panic(reason.clone(), backtrace.clone()),
         ));
 
+        let details = backtrace
+            .map(|b| format!("{}\n{}", reason, b))
+            .unwrap_or(reason);
+        self.load_crash_page(browsing_context_id, details);
+    }
+
+    /// Exit the process after a pipeline failed, if in hard-fail mode.
+    fn exit_if_hard_fail(&self) {
+        if self.hard_fail {
+            // It's quite difficult to make Servo exit cleanly if some threads have failed.
+            // Hard fail exists for test runners so we crash and that's good enough.
+            error!("Pipeline failed in hard-fail mode.  Crashing!");
+            process::exit(1);
+        }
+    }
+
+    /// Replace the document of a browsing context whose pipeline failed with the crash page.
+    fn load_crash_page(&mut self, browsing_context_id: BrowsingContextId, details: String) {
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(context) => context,
             None => return warn!("failed browsing context is missing"),
         };
+        let top_level_browsing_context_id = browsing_context.top_level_id;
         let window_size = browsing_context.size;
         let pipeline_id = browsing_context.pipeline_id;
+        let parent_pipeline_id = browsing_context.parent_pipeline_id;
         let throttled = browsing_context.throttled;
 
         let pipeline = match self.pipelines.get(&pipeline_id) {
@@ -2820,11 +3076,7 @@ where
 
         let new_pipeline_id = PipelineId::new();
         let new_load_data = LoadData {
-            crash: Some(
-                backtrace
-                    .map(|b| format!("{}\n{}", reason, b))
-                    .unwrap_or(reason),
-            ),
+            crash: Some(details),
             ..old_load_data.clone()
         };
 
@@ -2834,7 +3086,7 @@ where
             new_pipeline_id,
             browsing_context_id,
             top_level_browsing_context_id,
-            None,
+            parent_pipeline_id,
             opener,
             window_size,
             new_load_data,
@@ -2960,6 +3212,9 @@ where
             WebView {
                 focused_browsing_context_id: browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                media_policy: MediaPolicy::default(),
+                audible_pipelines: HashSet::new(),
             },
         );
 
@@ -3052,9 +3307,16 @@ where
             type_,
         } in iframe_sizes
         {
+            let Some(top_level_id) = self
+                .browsing_contexts
+                .get(&browsing_context_id)
+                .map(|browsing_context| browsing_context.top_level_id)
+            else {
+                continue;
+            };
             let window_size = WindowSizeData {
                 initial_viewport: size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                device_pixel_ratio: self.device_pixel_ratio(top_level_id),
             };
 
             self.resize_browsing_context(window_size, type_, browsing_context_id);
@@ -3117,6 +3379,30 @@ where
         listener.initiate_fetch(Some(cancel_chan));
     }
 
+    /// Fetch the resource of a download with the request of the navigation it came from, on
+    /// the same resource threads as the navigation.
+    fn handle_fetch_download(&mut self, pipeline_id: PipelineId, channels: FetchChannels) {
+        let Some(mut request) = self.pending_downloads.remove(&pipeline_id) else {
+            return warn!("{}: No download to fetch", pipeline_id);
+        };
+        // The navigation already read the body, so ask its source for it again.
+        if let Some(body) = request.body.as_mut() {
+            if body.source_is_null() {
+                return warn!(
+                    "{}: The body of the download can't be read again",
+                    pipeline_id
+                );
+            }
+            body.extract_source();
+        }
+        if let Err(error) = self
+            .public_resource_threads
+            .send(CoreResourceMsg::Fetch(request, channels))
+        {
+            warn!("Resource thread unavailable ({})", error);
+        }
+    }
+
     // The script thread associated with pipeline_id has loaded a URL in an
     // iframe via script. This will result in a new pipeline being spawned and
     // a child being added to the parent browsing context. This message is never
@@ -3334,6 +3620,9 @@ where
             WebView {
                 focused_browsing_context_id: new_browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                media_policy: MediaPolicy::default(),
+                audible_pipelines: HashSet::new(),
             },
         );
 
@@ -3585,6 +3874,34 @@ where
         }
     }
 
+    /// Cancel the navigations of the browsing context of `pipeline_id` whose new pipeline has
+    /// not been made active yet, closing those pipelines.
+    fn handle_cancel_ongoing_navigation_msg(&mut self, pipeline_id: PipelineId) {
+        let browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.browsing_context_id,
+            None => return warn!("{}: Cancelled navigation after closure", pipeline_id),
+        };
+
+        // Changes that create a new browsing context are iframes being added, not
+        // navigations of this browsing context.
+        let (cancelled, pending): (Vec<_>, Vec<_>) =
+            self.pending_changes.drain(..).partition(|change| {
+                change.browsing_context_id == browsing_context_id &&
+                    change.new_browsing_context_info.is_none()
+            });
+        self.pending_changes = pending;
+
+        // Documents are activated before they start parsing, so these ones have not told the
+        // embedder that they are loading, and there is no load to complete.
+        for change in cancelled {
+            self.close_pipeline(
+                change.new_pipeline_id,
+                DiscardBrowsingContext::No,
+                ExitPipelineMode::Normal,
+            );
+        }
+    }
+
     fn handle_load_complete_msg(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
@@ -3617,6 +3934,8 @@ where
             .map(|ctx| ctx.pipeline_id == pipeline_id)
             .unwrap_or(false);
         if pipeline_is_top_level_pipeline {
+            self.restore_pending_webview_state(top_level_browsing_context_id, pipeline_id);
+
             // Is there any pending pipeline that will replace the current top level pipeline
             let current_top_level_pipeline_will_be_replaced = self
                 .pending_changes
@@ -3805,6 +4124,343 @@ where
         self.update_webview_in_compositor(top_level_browsing_context_id);
     }
 
+    fn handle_traverse_history_to_index_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        index: usize,
+    ) {
+        let current_index = self
+            .get_joint_session_history(top_level_browsing_context_id)
+            .past
+            .len();
+        let direction = match index.cmp(&current_index) {
+            Ordering::Less => TraversalDirection::Back(current_index - index),
+            Ordering::Greater => TraversalDirection::Forward(index - current_index),
+            Ordering::Equal => return,
+        };
+        self.handle_traverse_history_msg(top_level_browsing_context_id, direction);
+    }
+
+    fn handle_truncate_history_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        entries: Range<usize>,
+    ) {
+        if self.truncate_history(top_level_browsing_context_id, entries) {
+            self.notify_history_changed(top_level_browsing_context_id);
+        }
+    }
+
+    /// Remove the session history entries outside of the given range, closing the pipelines
+    /// that no remaining entry refers to. Returns false if the range excludes the current entry.
+    fn truncate_history(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        entries: Range<usize>,
+    ) -> bool {
+        let session_history = self.get_joint_session_history(top_level_browsing_context_id);
+        if !entries.contains(&session_history.past.len()) {
+            warn!(
+                "{}: Cannot truncate the session history to {:?} without its current entry",
+                top_level_browsing_context_id, entries
+            );
+            return false;
+        }
+        let removed_diffs = session_history.truncate(entries);
+        let alive_pipelines = |diff: &SessionHistoryDiff| {
+            [diff.alive_old_pipeline(), diff.alive_new_pipeline()]
+                .into_iter()
+                .flatten()
+        };
+        let remaining_pipelines: HashSet<PipelineId> = session_history
+            .past
+            .iter()
+            .chain(session_history.future.iter())
+            .flat_map(alive_pipelines)
+            .collect();
+        let pipelines_to_close: HashSet<PipelineId> = removed_diffs
+            .iter()
+            .flat_map(alive_pipelines)
+            .filter(|pipeline_id| !remaining_pipelines.contains(pipeline_id))
+            .collect();
+        let remaining_discarded_pipelines: HashSet<PipelineId> = session_history
+            .past
+            .iter()
+            .chain(session_history.future.iter())
+            .flat_map(SessionHistoryDiff::discarded_pipelines)
+            .collect();
+        for diff in &removed_diffs {
+            for pipeline_id in diff.discarded_pipelines() {
+                if !remaining_discarded_pipelines.contains(&pipeline_id) {
+                    self.restored_document_states.remove(&pipeline_id);
+                }
+            }
+        }
+        for pipeline_id in pipelines_to_close {
+            let is_active = self
+                .browsing_contexts
+                .values()
+                .any(|browsing_context| browsing_context.pipeline_id == pipeline_id);
+            if !is_active {
+                self.close_pipeline(
+                    pipeline_id,
+                    DiscardBrowsingContext::No,
+                    ExitPipelineMode::Normal,
+                );
+            }
+        }
+        true
+    }
+
+    fn handle_restore_history_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        urls: Vec<ServoUrl>,
+        current_index: usize,
+    ) {
+        let entries = urls.into_iter().map(|url| (url, None)).collect();
+        self.restore_session_history(top_level_browsing_context_id, entries, current_index);
+    }
+
+    /// Replace the session history of a webview with the given entries, keeping the current
+    /// document as the entry at `current_index`. The saved document states of the entries are
+    /// restored when their document is loaded again.
+    fn restore_session_history(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        entries: Vec<(ServoUrl, Option<SavedDocumentState>)>,
+        current_index: usize,
+    ) {
+        if current_index >= entries.len() {
+            return warn!(
+                "{}: Cannot restore {} session history entries with current entry {}",
+                top_level_browsing_context_id,
+                entries.len(),
+                current_index
+            );
+        }
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: RestoreHistory for unknown browsing context",
+                    top_level_browsing_context_id
+                );
+            },
+        };
+
+        let current_entry = {
+            let session_history = self.get_joint_session_history(top_level_browsing_context_id);
+            session_history.past.len()..session_history.past.len() + 1
+        };
+        self.truncate_history(top_level_browsing_context_id, current_entry);
+
+        // Every entry other than the current one is a discarded document, which is loaded
+        // again if it is ever traversed to.
+        let mut reloaders = Vec::with_capacity(entries.len());
+        for (index, (url, document_state)) in entries.into_iter().enumerate() {
+            if index == current_index {
+                if let Some(document_state) = document_state {
+                    self.pending_document_states
+                        .insert(pipeline_id, document_state);
+                }
+                reloaders.push(NeedsToReload::No(pipeline_id));
+                continue;
+            }
+            let load_data = LoadData::new(
+                LoadOrigin::Constellation,
+                url,
+                None,
+                Referrer::NoReferrer,
+                None,
+                None,
+            );
+            let discarded_pipeline_id = PipelineId::new();
+            if let Some(document_state) = document_state {
+                self.restored_document_states
+                    .insert(discarded_pipeline_id, document_state);
+            }
+            reloaders.push(NeedsToReload::Yes(discarded_pipeline_id, load_data));
+        }
+        let diff = |index: usize| SessionHistoryDiff::BrowsingContext {
+            browsing_context_id,
+            old_reloader: reloaders[index].clone(),
+            new_reloader: reloaders[index + 1].clone(),
+        };
+
+        let session_history = self.get_joint_session_history(top_level_browsing_context_id);
+        session_history.past = (0..current_index).map(diff).collect();
+        // The future is stored with its oldest entries at the front.
+        session_history.future = (current_index..reloaders.len() - 1)
+            .rev()
+            .map(diff)
+            .collect();
+        self.notify_history_changed(top_level_browsing_context_id);
+    }
+
+    fn handle_get_webview_state_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        response_sender: IpcSender<Option<WebViewState>>,
+    ) {
+        let Some((entries, current_index)) =
+            self.session_history_entries(top_level_browsing_context_id)
+        else {
+            let _ = response_sender.send(None);
+            return;
+        };
+
+        // Discarded documents keep the state they were restored with, if any, while the
+        // documents that are still loaded are asked for theirs.
+        let (document_state_sender, document_state_receiver) =
+            ipc::channel().expect("ipc channel failure");
+        let mut entries_of_pipeline: HashMap<PipelineId, Vec<usize>> = HashMap::new();
+        let mut session_history = Vec::with_capacity(entries.len());
+        for (index, (mut entry, pipeline_id)) in entries.into_iter().enumerate() {
+            if self.pipelines.contains_key(&pipeline_id) {
+                entries_of_pipeline
+                    .entry(pipeline_id)
+                    .or_default()
+                    .push(index);
+            } else {
+                entry.document_state = self.restored_document_states.get(&pipeline_id).cloned();
+            }
+            session_history.push(entry);
+        }
+        let mut waiting_pipelines = HashSet::new();
+        for &pipeline_id in entries_of_pipeline.keys() {
+            let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+                continue;
+            };
+            let msg = ConstellationControlMsg::GetDocumentState(
+                pipeline_id,
+                document_state_sender.clone(),
+            );
+            match pipeline.event_loop.send(msg) {
+                Ok(()) => {
+                    waiting_pipelines.insert(pipeline_id);
+                },
+                Err(e) => self.handle_send_error(pipeline_id, e),
+            }
+        }
+
+        let state = WebViewState {
+            session_history,
+            current_index,
+        };
+        if waiting_pipelines.is_empty() {
+            let _ = response_sender.send(Some(state));
+            return;
+        }
+        let mut state = Some(state);
+        ROUTER.add_route(
+            document_state_receiver.to_opaque(),
+            Box::new(move |message| {
+                let Ok((pipeline_id, document_state)) =
+                    message.to::<(PipelineId, Option<SavedDocumentState>)>()
+                else {
+                    return;
+                };
+                let Some(ref mut webview_state) = state else {
+                    return;
+                };
+                if !waiting_pipelines.remove(&pipeline_id) {
+                    return;
+                }
+                for &index in entries_of_pipeline.get(&pipeline_id).into_iter().flatten() {
+                    webview_state.session_history[index].document_state = document_state.clone();
+                }
+                if waiting_pipelines.is_empty() {
+                    let _ = response_sender.send(state.take());
+                }
+            }),
+        );
+    }
+
+    fn handle_restore_webview_state_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        state: WebViewState,
+    ) {
+        let Some(current_entry) = state.session_history.get(state.current_index) else {
+            return warn!(
+                "{}: Cannot restore {} session history entries with current entry {}",
+                top_level_browsing_context_id,
+                state.session_history.len(),
+                state.current_index
+            );
+        };
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: RestoreWebViewState for unknown browsing context",
+                    top_level_browsing_context_id
+                );
+            },
+        };
+
+        let load_data = LoadData::new(
+            LoadOrigin::Constellation,
+            current_entry.url.clone(),
+            None,
+            Referrer::NoReferrer,
+            None,
+            None,
+        );
+        let new_pipeline_id = self.load_url(
+            top_level_browsing_context_id,
+            pipeline_id,
+            load_data,
+            HistoryEntryReplacement::Enabled,
+        );
+        match new_pipeline_id {
+            Some(new_pipeline_id) => {
+                self.pending_webview_states.insert(new_pipeline_id, state);
+            },
+            None => warn!(
+                "{}: Not restoring state while another navigation is in progress",
+                top_level_browsing_context_id
+            ),
+        }
+    }
+
+    /// Restore the session history of a webview whose state was restored once the document of
+    /// its current entry has loaded, and the saved state of a document once it was loaded
+    /// again.
+    fn restore_pending_webview_state(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+    ) {
+        if let Some(state) = self.pending_webview_states.remove(&pipeline_id) {
+            let entries = state
+                .session_history
+                .into_iter()
+                .map(|entry| (entry.url, entry.document_state))
+                .collect();
+            self.restore_session_history(
+                top_level_browsing_context_id,
+                entries,
+                state.current_index,
+            );
+        }
+
+        let Some(document_state) = self.pending_document_states.remove(&pipeline_id) else {
+            return;
+        };
+        let msg = ConstellationControlMsg::RestoreDocumentState(pipeline_id, document_state);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return warn!("{}: RestoreDocumentState after closure", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn update_browsing_context(
         &mut self,
         browsing_context_id: BrowsingContextId,
@@ -3843,6 +4499,10 @@ where
                     None => None,
                 };
                 let new_pipeline_id = PipelineId::new();
+                if let Some(document_state) = self.restored_document_states.remove(&pipeline_id) {
+                    self.pending_document_states
+                        .insert(new_pipeline_id, document_state);
+                }
                 self.new_pipeline(
                     new_pipeline_id,
                     browsing_context_id,
@@ -4031,8 +4691,8 @@ where
         session_history.replace_history_state(pipeline_id, history_state_id, url);
     }
 
-    fn handle_ime_dismissed(&mut self) {
-        // Send to the focused browsing contexts' current pipeline.
+    /// Send an input method event to the current pipeline of the focused browsing context.
+    fn forward_ime_event(&mut self, event: CompositorEvent) {
         let focused_browsing_context_id = self
             .webviews
             .focused_webview()
@@ -4042,17 +4702,16 @@ where
                 Some(ctx) => ctx.pipeline_id,
                 None => {
                     return warn!(
-                        "{}: Got IME dismissed event for nonexistent browsing context",
+                        "{}: Got input method event for nonexistent browsing context",
                         browsing_context_id,
                     );
                 },
             };
-            let msg =
-                ConstellationControlMsg::SendEvent(pipeline_id, CompositorEvent::IMEDismissedEvent);
+            let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
             let result = match self.pipelines.get(&pipeline_id) {
                 Some(pipeline) => pipeline.event_loop.send(msg),
                 None => {
-                    return debug!("{}: Got IME dismissed event after closure", pipeline_id);
+                    return debug!("{}: Got input method event after closure", pipeline_id);
                 },
             };
             if let Err(e) = result {
@@ -4061,6 +4720,47 @@ where
         }
     }
 
+    fn handle_evaluate_javascript(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        evaluation_id: JavaScriptEvaluationId,
+        script: String,
+    ) {
+        // The embedder waits for a result, so every evaluation that can't run is answered.
+        let not_found = || {
+            self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::JavaScriptEvaluated(
+                    evaluation_id,
+                    Err(JavaScriptEvaluationError::WebViewNotFound),
+                ),
+            ))
+        };
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                warn!(
+                    "{}: EvaluateJavaScript for unknown browsing context",
+                    top_level_browsing_context_id
+                );
+                return not_found();
+            },
+        };
+        let msg = ConstellationControlMsg::EvaluateJavaScript(pipeline_id, evaluation_id, script);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                warn!("{}: EvaluateJavaScript after closure", pipeline_id);
+                return not_found();
+            },
+        };
+        if let Err(e) = result {
+            not_found();
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_key_msg(&mut self, event: KeyboardEvent) {
         // Send to the focused browsing contexts' current pipeline.  If it
         // doesn't exist, fall back to sending to the compositor.
@@ -4117,6 +4817,24 @@ where
         }
     }
 
+    fn handle_stop_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!("{}: Got stop event after closure", browsing_context_id);
+            },
+        };
+        let msg = ConstellationControlMsg::Stop(pipeline_id);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("{}: Got stop event after closure", pipeline_id),
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_post_message_msg(
         &mut self,
         browsing_context_id: BrowsingContextId,
@@ -4467,8 +5185,163 @@ where
         }
     }
 
+    fn update_webview_media_policy(
+        &mut self,
+        webview_id: WebViewId,
+        update: impl FnOnce(&mut MediaPolicy),
+    ) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return warn!("{webview_id}: Tried to set the media policy after closure");
+        };
+        update(&mut webview.media_policy);
+        let media_policy = webview.media_policy;
+
+        // The documents in the session history of the webview may live in other event loops
+        // than the active one, so tell each of them once.
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if pipeline.top_level_browsing_context_id == webview_id &&
+                !event_loops
+                    .iter()
+                    .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        for event_loop in event_loops {
+            let msg = ConstellationControlMsg::SetMediaPolicy(webview_id, media_policy);
+            if let Err(e) = event_loop.send(msg) {
+                warn!("{webview_id}: Sending SetMediaPolicy to script failed ({e})");
+            }
+        }
+    }
+
+    /// Tell the embedder when a webview starts or stops being audible, which it is while any of
+    /// its pipelines is.
+    fn handle_audible_changed(
+        &mut self,
+        webview_id: WebViewId,
+        pipeline_id: PipelineId,
+        audible: bool,
+    ) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return;
+        };
+        let was_audible = !webview.audible_pipelines.is_empty();
+        if audible {
+            webview.audible_pipelines.insert(pipeline_id);
+        } else {
+            webview.audible_pipelines.remove(&pipeline_id);
+        }
+        let is_audible = !webview.audible_pipelines.is_empty();
+        if was_audible != is_audible {
+            self.embedder_proxy
+                .send((Some(webview_id), EmbedderMsg::AudibleChanged(is_audible)));
+        }
+    }
+
+    /// Ask the embedder to start or stop sending the position of the device, or to change its
+    /// accuracy, according to what the pipelines that wait for it asked for.
+    fn update_geolocation_updates(&mut self) {
+        let geolocation_updates = if self.geolocation_watchers.is_empty() {
+            None
+        } else {
+            Some(
+                self.geolocation_watchers
+                    .values()
+                    .any(|high_accuracy| *high_accuracy),
+            )
+        };
+        if geolocation_updates == self.geolocation_updates {
+            return;
+        }
+        self.geolocation_updates = geolocation_updates;
+        let msg = match geolocation_updates {
+            Some(high_accuracy) => EmbedderMsg::StartGeolocationUpdates(high_accuracy),
+            None => EmbedderMsg::StopGeolocationUpdates,
+        };
+        self.embedder_proxy.send((None, msg));
+    }
+
+    /// Forward a position of the device from the embedder to the pipelines that wait for it.
+    fn handle_geolocation_update(&mut self, update: GeolocationUpdate) {
+        let pipeline_ids: Vec<PipelineId> = self.geolocation_watchers.keys().cloned().collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::GeolocationUpdate(pipeline_id, update.clone());
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Forward something that happened to a notification to the pipeline that created it.
+    fn handle_notification_event(
+        &mut self,
+        notification_id: NotificationId,
+        event: NotificationEvent,
+    ) {
+        let pipeline_id = match event {
+            NotificationEvent::Show | NotificationEvent::Click => {
+                self.notifications.get(&notification_id).cloned()
+            },
+            NotificationEvent::Error | NotificationEvent::Close => {
+                self.notifications.remove(&notification_id)
+            },
+        };
+        let Some(pipeline_id) = pipeline_id else {
+            return debug!("{}: Event for closed notification", notification_id);
+        };
+        let msg = ConstellationControlMsg::NotificationEvent(pipeline_id, notification_id, event);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Ask the embedder to close the notifications of a pipeline that exited, as their events
+    /// can't be handled anymore.
+    fn close_notifications_of_pipeline(&mut self, pipeline_id: PipelineId) {
+        let notification_ids: Vec<NotificationId> = self
+            .notifications
+            .iter()
+            .filter(|(_, notification_pipeline_id)| **notification_pipeline_id == pipeline_id)
+            .map(|(notification_id, _)| *notification_id)
+            .collect();
+        for notification_id in notification_ids {
+            self.notifications.remove(&notification_id);
+            self.embedder_proxy
+                .send((None, EmbedderMsg::CloseNotification(notification_id)));
+        }
+    }
+
     fn notify_history_changed(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
-        // Send a flat projection of the history to embedder.
+        let Some((entries, current_index)) =
+            self.session_history_entries(top_level_browsing_context_id)
+        else {
+            return;
+        };
+        let entries = entries.into_iter().map(|(entry, _)| entry).collect();
+        let msg = (
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::HistoryChanged(entries, current_index),
+        );
+        self.embedder_proxy.send(msg);
+    }
+
+    /// A flat projection of the session history of a webview, with the pipeline of the document
+    /// of every entry, followed by the index of the current entry. The pipeline of a discarded
+    /// document is the one it is replaced with when traversed to.
+    fn session_history_entries(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Option<(Vec<(SessionHistoryEntry, PipelineId)>, usize)> {
         // The final vector is a concatenation of the LoadData of the past
         // entries, the current entry and the future entries.
         // LoadData of inner frames are ignored and replaced with the LoadData
@@ -4477,10 +5350,11 @@ where
         let session_history = match self.webviews.get(top_level_browsing_context_id) {
             Some(webview) => &webview.session_history,
             None => {
-                return warn!(
+                warn!(
                     "{}: Session history does not exist for browsing context",
                     top_level_browsing_context_id
                 );
+                return None;
             },
         };
 
@@ -4488,99 +5362,106 @@ where
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(browsing_context) => browsing_context,
             None => {
-                return warn!(
-                    "notify_history_changed error after top-level browsing context closed."
-                );
+                warn!("session_history_entries error after top-level browsing context closed.");
+                return None;
             },
         };
 
-        let current_load_data = match self.pipelines.get(&browsing_context.pipeline_id) {
-            Some(pipeline) => pipeline.load_data.clone(),
+        let current_entry = match self.pipelines.get(&browsing_context.pipeline_id) {
+            Some(pipeline) => (
+                pipeline.load_data.clone(),
+                Some(pipeline.title.clone()),
+                pipeline.id,
+            ),
             None => {
-                return warn!("{}: Refresh after closure", browsing_context.pipeline_id);
+                warn!("{}: Refresh after closure", browsing_context.pipeline_id);
+                return None;
             },
         };
 
+        // The LoadData, title and pipeline of the entry the given reloader stands for.
+        // Discarded documents have no title.
+        let resolve_reloader = |previous_entry: &(LoadData, Option<String>, PipelineId),
+                                reloader: &NeedsToReload| {
+            match *reloader {
+                NeedsToReload::No(pipeline_id) => match self.pipelines.get(&pipeline_id) {
+                    Some(pipeline) => (
+                        pipeline.load_data.clone(),
+                        Some(pipeline.title.clone()),
+                        pipeline_id,
+                    ),
+                    None => previous_entry.clone(),
+                },
+                NeedsToReload::Yes(pipeline_id, ref load_data) => {
+                    (load_data.clone(), None, pipeline_id)
+                },
+            }
+        };
+
         // If LoadData was ignored, use the LoadData of the previous SessionHistoryEntry, which
         // is the LoadData of the parent browsing context.
-        let resolve_load_data_future =
-            |previous_load_data: &mut LoadData, diff: &SessionHistoryDiff| match *diff {
+        let resolve_entry_future = |previous_entry: &mut (LoadData, Option<String>, PipelineId),
+                                    diff: &SessionHistoryDiff| {
+            match *diff {
                 SessionHistoryDiff::BrowsingContext {
                     browsing_context_id,
                     ref new_reloader,
                     ..
-                } => {
-                    if browsing_context_id == top_level_browsing_context_id {
-                        let load_data = match *new_reloader {
-                            NeedsToReload::No(pipeline_id) => {
-                                match self.pipelines.get(&pipeline_id) {
-                                    Some(pipeline) => pipeline.load_data.clone(),
-                                    None => previous_load_data.clone(),
-                                }
-                            },
-                            NeedsToReload::Yes(_, ref load_data) => load_data.clone(),
-                        };
-                        *previous_load_data = load_data.clone();
-                        Some(load_data)
-                    } else {
-                        Some(previous_load_data.clone())
-                    }
+                } if browsing_context_id == top_level_browsing_context_id => {
+                    *previous_entry = resolve_reloader(previous_entry, new_reloader);
+                    Some(previous_entry.clone())
                 },
-                _ => Some(previous_load_data.clone()),
-            };
+                _ => Some(previous_entry.clone()),
+            }
+        };
 
-        let resolve_load_data_past =
-            |previous_load_data: &mut LoadData, diff: &SessionHistoryDiff| match *diff {
+        let resolve_entry_past = |previous_entry: &mut (LoadData, Option<String>, PipelineId),
+                                  diff: &SessionHistoryDiff| {
+            match *diff {
                 SessionHistoryDiff::BrowsingContext {
                     browsing_context_id,
                     ref old_reloader,
                     ..
-                } => {
-                    if browsing_context_id == top_level_browsing_context_id {
-                        let load_data = match *old_reloader {
-                            NeedsToReload::No(pipeline_id) => {
-                                match self.pipelines.get(&pipeline_id) {
-                                    Some(pipeline) => pipeline.load_data.clone(),
-                                    None => previous_load_data.clone(),
-                                }
-                            },
-                            NeedsToReload::Yes(_, ref load_data) => load_data.clone(),
-                        };
-                        *previous_load_data = load_data.clone();
-                        Some(load_data)
-                    } else {
-                        Some(previous_load_data.clone())
-                    }
+                } if browsing_context_id == top_level_browsing_context_id => {
+                    *previous_entry = resolve_reloader(previous_entry, old_reloader);
+                    Some(previous_entry.clone())
                 },
-                _ => Some(previous_load_data.clone()),
-            };
+                _ => Some(previous_entry.clone()),
+            }
+        };
 
-        let mut entries: Vec<LoadData> = session_history
+        let mut entries: Vec<(LoadData, Option<String>, PipelineId)> = session_history
             .past
             .iter()
             .rev()
-            .scan(current_load_data.clone(), &resolve_load_data_past)
+            .scan(current_entry.clone(), &resolve_entry_past)
             .collect();
 
         entries.reverse();
 
         let current_index = entries.len();
 
-        entries.push(current_load_data.clone());
+        entries.push(current_entry.clone());
 
         entries.extend(
             session_history
                 .future
                 .iter()
                 .rev()
-                .scan(current_load_data, &resolve_load_data_future),
-        );
-        let urls = entries.iter().map(|entry| entry.url.clone()).collect();
-        let msg = (
-            Some(top_level_browsing_context_id),
-            EmbedderMsg::HistoryChanged(urls, current_index),
+                .scan(current_entry, &resolve_entry_future),
         );
-        self.embedder_proxy.send(msg);
+        let entries = entries
+            .into_iter()
+            .map(|(load_data, title, pipeline_id)| {
+                let entry = SessionHistoryEntry {
+                    url: load_data.url,
+                    title,
+                    document_state: None,
+                };
+                (entry, pipeline_id)
+            })
+            .collect();
+        Some((entries, current_index))
     }
 
     fn load_url_for_webdriver(
@@ -4896,6 +5777,9 @@ where
 
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         self.resize_browsing_context(new_size, size_type, browsing_context_id);
+        if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+            webview.device_pixel_ratio = new_size.device_pixel_ratio;
+        }
 
         if let Some(response_sender) = self.webdriver.resize_channel.take() {
             let _ = response_sender.send(new_size);
@@ -4904,6 +5788,18 @@ where
         self.window_size = new_size;
     }
 
+    /// The device pixel ratio that the documents of a webview are laid out with.
+    fn device_pixel_ratio(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Scale<f32, CSSPixel, DevicePixel> {
+        self.webviews
+            .get(top_level_browsing_context_id)
+            .map_or(self.window_size.device_pixel_ratio, |webview| {
+                webview.device_pixel_ratio
+            })
+    }
+
     /// Called when the window exits from fullscreen mode
     fn handle_exit_fullscreen_msg(
         &mut self,
@@ -5287,6 +6183,8 @@ where
         if let Some(pending_index) = pending_index {
             self.pending_changes.remove(pending_index);
         }
+        self.pending_webview_states.remove(&pipeline_id);
+        self.pending_document_states.remove(&pipeline_id);
 
         // Inform script, compositor that this pipeline has exited.
         match exit_mode {
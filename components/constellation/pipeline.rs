@@ -21,6 +21,7 @@ use canvas_traits::webgl::WebGLPipeline;
 use compositing_traits::{CompositionPipeline, CompositorMsg, CompositorProxy};
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{DevtoolsControlMsg, ScriptToDevtoolsControlMsg};
+use embedder_traits::user_script::UserScript;
 use embedder_traits::EventLoopWaker;
 use fonts::FontCacheThread;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -207,6 +208,9 @@ pub struct InitialPipelineState {
 
     /// User agent string to report in network requests.
     pub user_agent: Cow<'static, str>,
+
+    /// The user scripts injected into the documents of this pipeline that they match.
+    pub user_scripts: Vec<UserScript>,
 }
 
 pub struct NewPipeline {
@@ -231,6 +235,7 @@ impl Pipeline {
                     opener: state.opener,
                     load_data: state.load_data.clone(),
                     window_size: state.window_size,
+                    user_scripts: state.user_scripts.clone(),
                 };
 
                 if let Err(e) =
@@ -304,6 +309,7 @@ impl Pipeline {
                     webxr_registry: state.webxr_registry,
                     player_context: state.player_context,
                     user_agent: state.user_agent,
+                    user_scripts: state.user_scripts,
                 };
 
                 // Spawn the child process.
@@ -509,6 +515,7 @@ pub struct UnprivilegedPipelineContent {
     webxr_registry: webxr_api::Registry,
     player_context: WindowGLContext,
     user_agent: Cow<'static, str>,
+    user_scripts: Vec<UserScript>,
 }
 
 impl UnprivilegedPipelineContent {
@@ -552,6 +559,7 @@ impl UnprivilegedPipelineContent {
                 webrender_api_sender: self.webrender_api_sender.clone(),
                 player_context: self.player_context.clone(),
                 inherited_secure_context: self.load_data.inherited_secure_context,
+                user_scripts: self.user_scripts,
             },
             layout_factory,
             self.font_cache_thread.clone(),
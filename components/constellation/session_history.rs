@@ -4,6 +4,7 @@
 
 use std::cmp::PartialEq;
 use std::fmt;
+use std::ops::Range;
 
 use base::id::{BrowsingContextId, HistoryStateId, PipelineId, TopLevelBrowsingContextId};
 use euclid::Size2D;
@@ -88,6 +89,25 @@ impl JointSessionHistory {
         }
     }
 
+    /// Remove the entries outside of the given range of indices into all the entries, where the
+    /// current entry is at `past.len()`. The range must contain the current entry, and is clamped
+    /// to the entries that exist. Returns the diffs that led to the removed entries. The diff at
+    /// each end of the range also refers to the first or last entry that is kept.
+    pub fn truncate(&mut self, entries: Range<usize>) -> Vec<SessionHistoryDiff> {
+        debug!("truncating to entries {:?}", entries);
+        let current_index = self.past.len();
+        let future_length = entries
+            .end
+            .saturating_sub(current_index + 1)
+            .min(self.future.len());
+        let mut removed: Vec<_> = self
+            .past
+            .drain(..entries.start.min(current_index))
+            .collect();
+        removed.extend(self.future.drain(..self.future.len() - future_length));
+        removed
+    }
+
     pub fn remove_entries_for_browsing_context(&mut self, context_id: BrowsingContextId) {
         debug!("{}: Removing entries for browsing context", context_id);
         self.past.retain(|diff| match diff {
@@ -233,6 +253,25 @@ impl SessionHistoryDiff {
         }
     }
 
+    /// Returns the ids of the discarded pipelines of this diff, which are replaced by a new
+    /// pipeline if they are ever traversed to.
+    pub fn discarded_pipelines(&self) -> impl Iterator<Item = PipelineId> + '_ {
+        let reloaders = match *self {
+            SessionHistoryDiff::BrowsingContext {
+                ref old_reloader,
+                ref new_reloader,
+                ..
+            } => vec![old_reloader, new_reloader],
+            _ => vec![],
+        };
+        reloaders
+            .into_iter()
+            .filter_map(|reloader| match *reloader {
+                NeedsToReload::No(..) => None,
+                NeedsToReload::Yes(pipeline_id, ..) => Some(pipeline_id),
+            })
+    }
+
     /// Replaces all occurances of the replaced pipeline with a new pipeline
     pub fn replace_reloader(
         &mut self,
@@ -271,3 +310,173 @@ impl SessionHistoryDiff {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use base::id::{BrowsingContextId, PipelineId, PipelineNamespace, PipelineNamespaceId};
+    use net_traits::request::Referrer;
+    use script_traits::{LoadData, LoadOrigin};
+    use servo_url::ServoUrl;
+
+    use crate::session_history::{JointSessionHistory, NeedsToReload, SessionHistoryDiff};
+
+    /// A history of fragment navigations between the given URLs, at the given current entry.
+    fn hash_history(urls: &[&str], current_index: usize) -> JointSessionHistory {
+        let pipeline_reloader = NeedsToReload::No(PipelineId::new());
+        let diff = |index: usize| SessionHistoryDiff::Hash {
+            pipeline_reloader: pipeline_reloader.clone(),
+            old_url: ServoUrl::parse(urls[index]).expect("Incorrect test case"),
+            new_url: ServoUrl::parse(urls[index + 1]).expect("Incorrect test case"),
+        };
+        let mut session_history = JointSessionHistory::new();
+        session_history.past = (0..current_index).map(diff).collect();
+        session_history.future = (current_index..urls.len() - 1).rev().map(diff).collect();
+        session_history
+    }
+
+    fn old_urls(diffs: &[SessionHistoryDiff]) -> Vec<String> {
+        diffs
+            .iter()
+            .map(|diff| match diff {
+                SessionHistoryDiff::Hash { old_url, .. } => old_url.to_string(),
+                _ => unreachable!("Incorrect test case"),
+            })
+            .collect()
+    }
+
+    const URLS: [&str; 5] = [
+        "https://example.com/#0",
+        "https://example.com/#1",
+        "https://example.com/#2",
+        "https://example.com/#3",
+        "https://example.com/#4",
+    ];
+
+    #[test]
+    fn test_truncate_to_all_entries() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let mut session_history = hash_history(&URLS, 2);
+        assert!(session_history.truncate(0..5).is_empty());
+        assert_eq!(session_history.history_length(), 5);
+    }
+
+    #[test]
+    fn test_truncate_past_the_limit() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let mut session_history = hash_history(&URLS, 2);
+        assert!(session_history.truncate(0..usize::MAX).is_empty());
+        assert_eq!(session_history.history_length(), 5);
+    }
+
+    #[test]
+    fn test_truncate_at_the_limit() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let mut session_history = hash_history(&URLS, 2);
+        // Only the first and the last entries are outside of the range.
+        let removed = session_history.truncate(1..4);
+        assert_eq!(
+            old_urls(&removed),
+            ["https://example.com/#0", "https://example.com/#3"]
+        );
+        assert_eq!(session_history.history_length(), 3);
+        assert_eq!(old_urls(&session_history.past), ["https://example.com/#1"]);
+        assert_eq!(
+            old_urls(&session_history.future),
+            ["https://example.com/#2"]
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_current_entry() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let mut session_history = hash_history(&URLS, 2);
+        assert_eq!(session_history.truncate(2..3).len(), 4);
+        assert_eq!(session_history.history_length(), 1);
+    }
+
+    #[test]
+    fn test_truncate_keeps_multibyte_urls() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let urls = [
+            "https://example.com/#0",
+            "https://例え.jp/ページ#ü",
+            "https://example.com/#2",
+        ];
+        let mut session_history = hash_history(&urls, 1);
+        let removed = session_history.truncate(1..3);
+        assert_eq!(old_urls(&removed), ["https://example.com/#0"]);
+        let kept = ServoUrl::parse(urls[1]).expect("Incorrect test case");
+        assert_eq!(old_urls(&session_history.future), [kept.to_string()]);
+    }
+
+    fn load_data(url: &str) -> LoadData {
+        let url = ServoUrl::parse(url).expect("Incorrect test case");
+        LoadData::new(
+            LoadOrigin::Constellation,
+            url,
+            None,
+            Referrer::NoReferrer,
+            None,
+            None,
+        )
+    }
+
+    fn discarded_pipelines(session_history: &JointSessionHistory) -> Vec<PipelineId> {
+        let mut pipelines: Vec<PipelineId> = session_history
+            .past
+            .iter()
+            .chain(session_history.future.iter())
+            .flat_map(SessionHistoryDiff::discarded_pipelines)
+            .collect();
+        pipelines.sort();
+        pipelines.dedup();
+        pipelines
+    }
+
+    #[test]
+    fn test_discarded_pipelines_on_traversal() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let browsing_context_id = BrowsingContextId::new();
+        let current = NeedsToReload::No(PipelineId::new());
+        let (first_id, last_id) = (PipelineId::new(), PipelineId::new());
+        let first = NeedsToReload::Yes(first_id, load_data("https://example.com/first"));
+        let last = NeedsToReload::Yes(last_id, load_data("https://example.com/last"));
+
+        // A restored history of three entries, where only the middle one is loaded.
+        let mut session_history = JointSessionHistory::new();
+        session_history
+            .past
+            .push(SessionHistoryDiff::BrowsingContext {
+                browsing_context_id,
+                old_reloader: first.clone(),
+                new_reloader: current.clone(),
+            });
+        session_history
+            .future
+            .push(SessionHistoryDiff::BrowsingContext {
+                browsing_context_id,
+                old_reloader: current.clone(),
+                new_reloader: last,
+            });
+        let mut expected = vec![first_id, last_id];
+        expected.sort();
+        assert_eq!(discarded_pipelines(&session_history), expected);
+
+        // Traversing back loads the first document again in a new pipeline, which is no longer
+        // discarded.
+        let diff = session_history.past.pop().expect("Incorrect test case");
+        session_history.future.push(diff);
+        let reloaded = NeedsToReload::No(PipelineId::new());
+        session_history.replace_reloader(first, reloaded);
+        assert_eq!(discarded_pipelines(&session_history), vec![last_id]);
+
+        // Removing the last entry removes the last discarded document.
+        let removed = session_history.truncate(0..2);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(
+            removed[0].discarded_pipelines().collect::<Vec<_>>(),
+            vec![last_id]
+        );
+        assert!(discarded_pipelines(&session_history).is_empty());
+    }
+}
@@ -67,13 +67,20 @@ mod from_compositor {
                 Self::IsReadyToSaveImage(..) => target!("IsReadyToSaveImage"),
                 Self::Keyboard(..) => target!("Keyboard"),
                 Self::AllowNavigationResponse(..) => target!("AllowNavigationResponse"),
+                Self::SlowScriptResponse(..) => target!("SlowScriptResponse"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::ClearCache => target!("ClearCache"),
                 Self::TraverseHistory(..) => target!("TraverseHistory"),
+                Self::TraverseHistoryToIndex(..) => target!("TraverseHistoryToIndex"),
+                Self::TruncateHistory(..) => target!("TruncateHistory"),
+                Self::RestoreHistory(..) => target!("RestoreHistory"),
+                Self::GetWebViewState(..) => target!("GetWebViewState"),
+                Self::RestoreWebViewState(..) => target!("RestoreWebViewState"),
                 Self::WindowSize(..) => target!("WindowSize"),
                 Self::TickAnimation(..) => target!("TickAnimation"),
                 Self::WebDriverCommand(..) => target!("WebDriverCommand"),
                 Self::Reload(..) => target!("Reload"),
+                Self::Stop(..) => target!("Stop"),
                 Self::LogEntry(..) => target!("LogEntry"),
                 Self::NewWebView(..) => target!("NewWebView"),
                 Self::WebViewOpened(..) => target!("WebViewOpened"),
@@ -88,9 +95,17 @@ mod from_compositor {
                 Self::ExitFullScreen(_) => target!("ExitFullScreen"),
                 Self::MediaSessionAction(_) => target!("MediaSessionAction"),
                 Self::SetWebViewThrottled(_, _) => target!("SetWebViewThrottled"),
+                Self::SetWebViewMuted(_, _) => target!("SetWebViewMuted"),
+                Self::SetWebViewAutoplayPolicy(_, _) => target!("SetWebViewAutoplayPolicy"),
                 Self::IMEDismissed => target!("IMEDismissed"),
+                Self::Ime(..) => target!("Ime"),
+                Self::EvaluateJavaScript(..) => target!("EvaluateJavaScript"),
                 Self::ReadyToPresent(..) => target!("ReadyToPresent"),
                 Self::Gamepad(..) => target!("Gamepad"),
+                Self::GeolocationUpdate(..) => target!("GeolocationUpdate"),
+                Self::NotificationEvent(..) => target!("NotificationEvent"),
+                Self::FetchDownload(..) => target!("FetchDownload"),
+                Self::DiscardDownload(..) => target!("DiscardDownload"),
             }
         }
     }
@@ -111,6 +126,7 @@ mod from_compositor {
                 Self::KeyboardEvent(..) => target_variant!("KeyboardEvent"),
                 Self::CompositionEvent(..) => target_variant!("CompositionEvent"),
                 Self::IMEDismissedEvent => target_variant!("IMEDismissedEvent"),
+                Self::ImeEvent(..) => target_variant!("ImeEvent"),
                 Self::GamepadEvent(..) => target_variant!("GamepadEvent"),
             }
         }
@@ -149,6 +165,7 @@ mod from_script {
                 Self::ScheduleBroadcast(..) => target!("ScheduleBroadcast"),
                 Self::ForwardToEmbedder(msg) => msg.log_target(),
                 Self::InitiateNavigateRequest(..) => target!("InitiateNavigateRequest"),
+                Self::DownloadRequested(..) => target!("DownloadRequested"),
                 Self::BroadcastStorageEvent(..) => target!("BroadcastStorageEvent"),
                 Self::ChangeRunningAnimationsState(..) => target!("ChangeRunningAnimationsState"),
                 Self::CreateCanvasPaintThread(..) => target!("CreateCanvasPaintThread"),
@@ -159,6 +176,7 @@ mod from_script {
                 Self::LoadComplete => target!("LoadComplete"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::AbortLoadUrl => target!("AbortLoadUrl"),
+                Self::CancelOngoingNavigation => target!("CancelOngoingNavigation"),
                 Self::PostMessage { .. } => target!("PostMessage"),
                 Self::NavigatedToFragment(..) => target!("NavigatedToFragment"),
                 Self::TraverseHistory(..) => target!("TraverseHistory"),
@@ -179,6 +197,7 @@ mod from_script {
                 Self::DiscardDocument => target!("DiscardDocument"),
                 Self::DiscardTopLevelBrowsingContext => target!("DiscardTopLevelBrowsingContext"),
                 Self::PipelineExited => target!("PipelineExited"),
+                Self::MemoryLimitExceeded(..) => target!("MemoryLimitExceeded"),
                 Self::ForwardDOMMessage(..) => target!("ForwardDOMMessage"),
                 Self::ScheduleJob(..) => target!("ScheduleJob"),
                 Self::GetClientWindow(..) => target!("GetClientWindow"),
@@ -188,6 +207,11 @@ mod from_script {
                 Self::RequestAdapter(..) => target!("RequestAdapter"),
                 Self::GetWebGPUChan(..) => target!("GetWebGPUChan"),
                 Self::TitleChanged(..) => target!("TitleChanged"),
+                Self::AudibleChanged(..) => target!("AudibleChanged"),
+                Self::StartGeolocationUpdates(..) => target!("StartGeolocationUpdates"),
+                Self::StopGeolocationUpdates => target!("StopGeolocationUpdates"),
+                Self::ShowNotification(..) => target!("ShowNotification"),
+                Self::CloseNotification(..) => target!("CloseNotification"),
             }
         }
     }
@@ -227,16 +251,29 @@ mod from_script {
                 Self::GetSelectedBluetoothDevice(..) => {
                     target_variant!("GetSelectedBluetoothDevice")
                 },
+                Self::GetAudioOutputDevices(..) => target_variant!("GetAudioOutputDevices"),
                 Self::SelectFiles(..) => target_variant!("SelectFiles"),
                 Self::PromptPermission(..) => target_variant!("PromptPermission"),
                 Self::ShowIME(..) => target_variant!("ShowIME"),
                 Self::HideIME => target_variant!("HideIME"),
+                Self::UpdateIME(..) => target_variant!("UpdateIME"),
                 Self::Shutdown => target_variant!("Shutdown"),
                 Self::ReportProfile(..) => target_variant!("ReportProfile"),
                 Self::MediaSessionEvent(..) => target_variant!("MediaSessionEvent"),
                 Self::OnDevtoolsStarted(..) => target_variant!("OnDevtoolsStarted"),
                 Self::ReadyToPresent(..) => target_variant!("ReadyToPresent"),
                 Self::EventDelivered(..) => target_variant!("EventDelivered"),
+                Self::SlowScript(..) => target_variant!("SlowScript"),
+                Self::DownloadRequested(..) => target_variant!("DownloadRequested"),
+                Self::ContextMenuRequested(..) => target_variant!("ContextMenuRequested"),
+                Self::ScriptMessage(..) => target_variant!("ScriptMessage"),
+                Self::AudibleChanged(..) => target_variant!("AudibleChanged"),
+                Self::ConsoleMessage(..) => target_variant!("ConsoleMessage"),
+                Self::JavaScriptEvaluated(..) => target_variant!("JavaScriptEvaluated"),
+                Self::StartGeolocationUpdates(..) => target_variant!("StartGeolocationUpdates"),
+                Self::StopGeolocationUpdates => target_variant!("StopGeolocationUpdates"),
+                Self::ShowNotification(..) => target_variant!("ShowNotification"),
+                Self::CloseNotification(..) => target_variant!("CloseNotification"),
             }
         }
     }
@@ -194,10 +194,7 @@ impl ActorRegistry {
                 }
             },
         }
-        let new_actors = mem::take(&mut *self.new_actors.borrow_mut());
-        for actor in new_actors.into_iter() {
-            self.actors.insert(actor.name().to_owned(), actor);
-        }
+        self.register_new_actors();
 
         let old_actors = mem::take(&mut *self.old_actors.borrow_mut());
         for name in old_actors {
@@ -206,6 +203,14 @@ impl ActorRegistry {
         Ok(())
     }
 
+    /// Register the actors created with `register_later` since the last call.
+    pub(crate) fn register_new_actors(&mut self) {
+        let new_actors = mem::take(&mut *self.new_actors.borrow_mut());
+        for actor in new_actors.into_iter() {
+            self.actors.insert(actor.name().to_owned(), actor);
+        }
+    }
+
     pub fn drop_actor(&mut self, name: String) {
         self.actors.remove(&name);
     }
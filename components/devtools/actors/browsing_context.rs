@@ -12,7 +12,10 @@ use std::net::TcpStream;
 
 use base::id::{BrowsingContextId, PipelineId};
 use devtools_traits::DevtoolScriptControlMsg::{self, WantsLiveNotifications};
-use devtools_traits::{DevtoolsPageInfo, NavigationState};
+use devtools_traits::{
+    DevtoolsPageInfo, InputEventInfo, LayoutInvalidationInfo, NavigationState, NodeInfo,
+    SelectionInfo,
+};
 use ipc_channel::ipc::IpcSender;
 use serde::Serialize;
 use serde_json::{Map, Value};
@@ -20,9 +23,10 @@ use serde_json::{Map, Value};
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::actors::configuration::{TargetConfigurationActor, ThreadConfigurationActor};
 use crate::actors::emulation::EmulationActor;
-use crate::actors::inspector::InspectorActor;
+use crate::actors::inspector::{InspectorActor, NodeActorMsg, NodeInfoToProtocol};
 use crate::actors::performance::PerformanceActor;
 use crate::actors::profiler::ProfilerActor;
+use crate::actors::security::SecurityActor;
 use crate::actors::stylesheets::StyleSheetsActor;
 use crate::actors::tab::TabDescriptorActor;
 use crate::actors::thread::ThreadActor;
@@ -49,11 +53,11 @@ struct FrameUpdateMsg {
 }
 
 #[derive(Serialize)]
-struct ResourceAvailableReply {
+struct ResourceAvailableReply<T: Serialize> {
     from: String,
     #[serde(rename = "type")]
     type_: String,
-    resources: Vec<ResourceAvailableMsg>,
+    resources: Vec<T>,
 }
 
 #[derive(Serialize)]
@@ -70,6 +74,55 @@ struct ResourceAvailableMsg {
     url: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FocusChangedMsg {
+    resource_type: String,
+    node: Option<NodeActorMsg>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelectionChangedMsg {
+    resource_type: String,
+    anchor_node: Option<NodeActorMsg>,
+    anchor_offset: u32,
+    focus_node: Option<NodeActorMsg>,
+    focus_offset: u32,
+    is_collapsed: bool,
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InputEventMsg {
+    resource_type: String,
+    event_type: String,
+    target: Option<NodeActorMsg>,
+    dispatch_duration: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DamagedNodeMsg {
+    node: Option<NodeActorMsg>,
+    damage: Vec<String>,
+    depth: usize,
+    causes: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutInvalidationMsg {
+    resource_type: String,
+    dirty_root: Option<NodeActorMsg>,
+    damaged_nodes: Vec<DamagedNodeMsg>,
+    elements_traversed: usize,
+    box_tree_update: String,
+    layout_skipped: bool,
+    reflow_reason: String,
+}
+
 #[derive(Serialize)]
 struct TabNavigated {
     from: String,
@@ -102,6 +155,7 @@ pub struct BrowsingContextActorMsg {
     is_top_level_target: bool,
     console_actor: String,
     thread_actor: String,
+    security_actor: String,
     traits: BrowsingContextTraits,
     // Part of the official protocol, but not yet implemented.
     // emulation_actor: String,
@@ -138,6 +192,7 @@ pub(crate) struct BrowsingContextActor {
     pub _inspector: String,
     pub _performance: String,
     pub _profiler: String,
+    pub security: String,
     pub _style_sheets: String,
     pub target_configuration: String,
     pub thread_configuration: String,
@@ -204,6 +259,8 @@ impl BrowsingContextActor {
 
         let profiler = ProfilerActor::new(actors.new_name("profiler"));
 
+        let security = SecurityActor::new(actors.new_name("security"), name.clone());
+
         // the strange switch between styleSheets and stylesheets is due
         // to an inconsistency in devtools. See Bug #1498893 in bugzilla
         let style_sheets = StyleSheetsActor::new(actors.new_name("stylesheets"));
@@ -211,12 +268,12 @@ impl BrowsingContextActor {
         let tabdesc = TabDescriptorActor::new(actors, name.clone());
 
         let target_configuration =
-            TargetConfigurationActor::new(actors.new_name("target-configuration"));
+            TargetConfigurationActor::new(actors.new_name("target-configuration"), name.clone());
 
         let thread_configuration =
             ThreadConfigurationActor::new(actors.new_name("thread-configuration"));
 
-        let thread = ThreadActor::new(actors.new_name("context"));
+        let thread = ThreadActor::new(actors.new_name("context"), pipeline, script_sender.clone());
 
         let timeline =
             TimelineActor::new(actors.new_name("timeline"), pipeline, script_sender.clone());
@@ -239,6 +296,7 @@ impl BrowsingContextActor {
             _inspector: inspector.name(),
             _performance: performance.name(),
             _profiler: profiler.name(),
+            security: security.name(),
             streams: RefCell::new(HashMap::new()),
             _style_sheets: style_sheets.name(),
             _tab: tabdesc.name(),
@@ -253,6 +311,7 @@ impl BrowsingContextActor {
         actors.register(Box::new(inspector));
         actors.register(Box::new(performance));
         actors.register(Box::new(profiler));
+        actors.register(Box::new(security));
         actors.register(Box::new(style_sheets));
         actors.register(Box::new(tabdesc));
         actors.register(Box::new(target_configuration));
@@ -279,6 +338,7 @@ impl BrowsingContextActor {
             is_top_level_target: true,
             console_actor: self.console.clone(),
             thread_actor: self.thread.clone(),
+            security_actor: self.security.clone(),
             // emulation_actor: self.emulation.clone(),
             // inspector_actor: self.inspector.clone(),
             // performance_actor: self.performance.clone(),
@@ -325,6 +385,113 @@ impl BrowsingContextActor {
         *self.title.borrow_mut() = title;
     }
 
+    fn encode_node(
+        &self,
+        registry: &ActorRegistry,
+        node: Option<NodeInfo>,
+    ) -> Option<NodeActorMsg> {
+        node.map(|node| {
+            node.encode(
+                registry,
+                true,
+                self.script_chan.clone(),
+                self.active_pipeline.get(),
+            )
+        })
+    }
+
+    fn resource_available<T: Serialize>(&self, resource: T) {
+        let msg = ResourceAvailableReply {
+            from: self.name(),
+            type_: "resource-available-form".into(),
+            resources: vec![resource],
+        };
+        for stream in self.streams.borrow_mut().values_mut() {
+            let _ = stream.write_json_packet(&msg);
+        }
+    }
+
+    pub(crate) fn focus_changed(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        node: Option<NodeInfo>,
+    ) {
+        if pipeline != self.active_pipeline.get() {
+            return;
+        }
+        self.resource_available(FocusChangedMsg {
+            resource_type: "focus-changed".into(),
+            node: self.encode_node(registry, node),
+        });
+    }
+
+    pub(crate) fn selection_changed(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        selection: SelectionInfo,
+    ) {
+        if pipeline != self.active_pipeline.get() {
+            return;
+        }
+        self.resource_available(SelectionChangedMsg {
+            resource_type: "selection-changed".into(),
+            anchor_node: self.encode_node(registry, selection.anchor_node),
+            anchor_offset: selection.anchor_offset,
+            focus_node: self.encode_node(registry, selection.focus_node),
+            focus_offset: selection.focus_offset,
+            is_collapsed: selection.is_collapsed,
+            text: selection.text,
+        });
+    }
+
+    pub(crate) fn input_event_dispatched(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        event: InputEventInfo,
+    ) {
+        if pipeline != self.active_pipeline.get() {
+            return;
+        }
+        self.resource_available(InputEventMsg {
+            resource_type: "input-event".into(),
+            event_type: event.event_type,
+            target: self.encode_node(registry, event.target),
+            dispatch_duration: event.dispatch_duration,
+        });
+    }
+
+    pub(crate) fn layout_invalidated(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        invalidation: LayoutInvalidationInfo,
+    ) {
+        if pipeline != self.active_pipeline.get() {
+            return;
+        }
+        self.resource_available(LayoutInvalidationMsg {
+            resource_type: "layout-invalidation".into(),
+            dirty_root: self.encode_node(registry, invalidation.dirty_root),
+            damaged_nodes: invalidation
+                .damaged_nodes
+                .into_iter()
+                .map(|damaged_node| DamagedNodeMsg {
+                    node: self.encode_node(registry, Some(damaged_node.node)),
+                    damage: damaged_node.damage,
+                    depth: damaged_node.depth,
+                    causes: damaged_node.causes,
+                })
+                .collect(),
+            elements_traversed: invalidation.elements_traversed,
+            box_tree_update: invalidation.box_tree_update,
+            layout_skipped: invalidation.layout_skipped,
+            reflow_reason: invalidation.reflow_reason,
+        });
+    }
+
     pub(crate) fn frame_update(&self, stream: &mut TcpStream) {
         let _ = stream.write_json_packet(&FrameUpdateReply {
             from: self.name(),
@@ -6,13 +6,16 @@
 //! and <https://searchfox.org/mozilla-central/source/devtools/server/actors/thread-configuration.js>
 //! These actors manage the configuration flags that the devtools host can apply to the targets and threads.
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::net::TcpStream;
 
+use devtools_traits::DevtoolScriptControlMsg;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
 use crate::protocol::JsonPacketStream;
 use crate::{EmptyReplyMsg, StreamId};
 
@@ -31,7 +34,8 @@ pub struct TargetConfigurationActorMsg {
 
 pub struct TargetConfigurationActor {
     name: String,
-    configuration: HashMap<&'static str, bool>,
+    browsing_context: String,
+    configuration: RefCell<HashMap<&'static str, bool>>,
     supported_options: HashMap<&'static str, bool>,
 }
 
@@ -55,15 +59,18 @@ impl Actor for TargetConfigurationActor {
     /// - `updateConfiguration`: Receives new configuration flags from the devtools host.
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
+        registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "updateConfiguration" => {
-                // TODO: Actually update configuration
+                let configuration = msg.get("configuration").and_then(|c| c.as_object());
+                if let Some(configuration) = configuration {
+                    self.update_configuration(registry, configuration);
+                }
                 let _ = stream.write_json_packet(&EmptyReplyMsg { from: self.name() });
 
                 ActorMessageStatus::Processed
@@ -74,10 +81,11 @@ impl Actor for TargetConfigurationActor {
 }
 
 impl TargetConfigurationActor {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, browsing_context: String) -> Self {
         Self {
             name,
-            configuration: HashMap::new(),
+            browsing_context,
+            configuration: RefCell::new(HashMap::new()),
             supported_options: HashMap::from([
                 ("cacheDisabled", false),
                 ("colorSchemeSimulation", false),
@@ -85,7 +93,7 @@ impl TargetConfigurationActor {
                 ("customUserAgent", false),
                 ("javascriptEnabled", false),
                 ("overrideDPPX", false),
-                ("printSimulationEnabled", false),
+                ("printSimulationEnabled", true),
                 ("rdmPaneMaxTouchPoints", false),
                 ("rdmPaneOrientation", false),
                 ("recordAllocations", false),
@@ -100,10 +108,29 @@ impl TargetConfigurationActor {
         }
     }
 
+    /// Apply the supported options of a new configuration to the target's active pipeline.
+    fn update_configuration(&self, registry: &ActorRegistry, configuration: &Map<String, Value>) {
+        let browsing_context = registry.find::<BrowsingContextActor>(&self.browsing_context);
+        let pipeline = browsing_context.active_pipeline.get();
+        if let Some(enabled) = configuration
+            .get("printSimulationEnabled")
+            .and_then(|v| v.as_bool())
+        {
+            let previous = self
+                .configuration
+                .borrow_mut()
+                .insert("printSimulationEnabled", enabled);
+            if previous != Some(enabled) {
+                let msg = DevtoolScriptControlMsg::SimulatePrintMedia(pipeline, enabled);
+                let _ = browsing_context.script_chan.send(msg);
+            }
+        }
+    }
+
     pub fn encodable(&self) -> TargetConfigurationActorMsg {
         TargetConfigurationActorMsg {
             actor: self.name(),
-            configuration: self.configuration.clone(),
+            configuration: self.configuration.borrow().clone(),
             traits: TargetConfigurationTraits {
                 supported_options: self.supported_options.clone(),
             },
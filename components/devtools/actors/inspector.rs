@@ -9,7 +9,7 @@ use std::net::TcpStream;
 
 use base::id::PipelineId;
 use devtools_traits::DevtoolScriptControlMsg::{
-    GetChildren, GetDocumentElement, GetLayout, GetRootNode, ModifyAttribute,
+    GetChildren, GetDocumentElement, GetElementsFromPoint, GetLayout, GetRootNode, ModifyAttribute,
 };
 use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, NodeInfo};
 use ipc_channel::ipc::{self, IpcSender};
@@ -158,7 +158,7 @@ struct AttrMsg {
 }
 
 #[derive(Serialize)]
-struct NodeActorMsg {
+pub(crate) struct NodeActorMsg {
     actor: String,
     baseURI: String,
     parent: String,
@@ -185,7 +185,7 @@ struct NodeActorMsg {
     incompleteValue: bool,
 }
 
-trait NodeInfoToProtocol {
+pub(crate) trait NodeInfoToProtocol {
     fn encode(
         self,
         actors: &ActorRegistry,
@@ -220,7 +220,11 @@ impl NodeInfoToProtocol for NodeInfo {
         NodeActorMsg {
             actor: actor_name,
             baseURI: self.baseURI,
-            parent: actors.script_to_actor(self.parent.clone()),
+            parent: if actors.script_actor_registered(self.parent.clone()) {
+                actors.script_to_actor(self.parent)
+            } else {
+                String::new()
+            },
             nodeType: self.nodeType,
             namespaceURI: self.namespaceURI,
             nodeName: self.nodeName,
@@ -284,6 +288,12 @@ struct ChildrenReply {
     from: String,
 }
 
+#[derive(Serialize)]
+struct ElementsFromPointReply {
+    from: String,
+    nodes: Vec<NodeActorMsg>,
+}
+
 impl Actor for WalkerActor {
     fn name(&self) -> String {
         self.name.clone()
@@ -354,6 +364,30 @@ impl Actor for WalkerActor {
                 ActorMessageStatus::Processed
             },
 
+            // Servo-specific: the hit chain at a viewport point, as seen by the same hit tester
+            // that routes input events, for debugging transforms, clips and pointer-events.
+            "elementsFromPoint" => {
+                let x = msg.get("x").and_then(Value::as_f64).ok_or(())? as f32;
+                let y = msg.get("y").and_then(Value::as_f64).ok_or(())? as f32;
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetElementsFromPoint(self.pipeline, x, y, tx))
+                    .unwrap();
+                let elements = rx.recv().unwrap();
+
+                let msg = ElementsFromPointReply {
+                    from: self.name(),
+                    nodes: elements
+                        .into_iter()
+                        .map(|element| {
+                            element.encode(registry, true, self.script_chan.clone(), self.pipeline)
+                        })
+                        .collect(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
             _ => ActorMessageStatus::Ignored,
         })
     }
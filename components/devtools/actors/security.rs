@@ -0,0 +1,107 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The security actor reports the state that decides whether the scripts and frames of a document
+//! may be blocked: its content security policies, the sandboxing of its frame and its referrer
+//! policy. There is one per browsing context, so every frame can be inspected on its own.
+
+use std::net::TcpStream;
+
+use devtools_traits::DevtoolScriptControlMsg::GetSecurityInfo;
+use devtools_traits::SecurityInfo;
+use ipc_channel::ipc;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
+use crate::protocol::JsonPacketStream;
+use crate::StreamId;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PolicyMsg {
+    directives: Vec<String>,
+    report_only: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SecurityInfoReply {
+    from: String,
+    origin: String,
+    is_secure_context: bool,
+    content_security_policies: Vec<PolicyMsg>,
+    sandboxed: bool,
+    sandbox_flags: Vec<String>,
+    referrer_policy: Option<String>,
+}
+
+pub struct SecurityActor {
+    pub name: String,
+    pub browsing_context: String,
+}
+
+impl Actor for SecurityActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The security actor can handle the following messages:
+    ///
+    /// - `getSecurityInfo`: Returns the effective security state of the active document of the
+    ///   browsing context
+    fn handle_message(
+        &self,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getSecurityInfo" => {
+                let browsing_context =
+                    registry.find::<BrowsingContextActor>(&self.browsing_context);
+                let (tx, rx) = ipc::channel().map_err(|_| ())?;
+                browsing_context
+                    .script_chan
+                    .send(GetSecurityInfo(browsing_context.active_pipeline.get(), tx))
+                    .map_err(|_| ())?;
+                let info = rx.recv().map_err(|_| ())?.ok_or(())?;
+                let _ = stream.write_json_packet(&self.encode(info));
+                ActorMessageStatus::Processed
+            },
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl SecurityActor {
+    pub fn new(name: String, browsing_context: String) -> Self {
+        Self {
+            name,
+            browsing_context,
+        }
+    }
+
+    fn encode(&self, info: SecurityInfo) -> SecurityInfoReply {
+        SecurityInfoReply {
+            from: self.name(),
+            origin: info.origin,
+            is_secure_context: info.is_secure_context,
+            content_security_policies: info
+                .content_security_policies
+                .into_iter()
+                .map(|policy| PolicyMsg {
+                    directives: policy.directives,
+                    report_only: policy.report_only,
+                })
+                .collect(),
+            sandboxed: info.sandbox.is_some(),
+            sandbox_flags: info.sandbox.unwrap_or_default(),
+            referrer_policy: info.referrer_policy,
+        }
+    }
+}
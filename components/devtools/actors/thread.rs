@@ -2,8 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
 use std::net::TcpStream;
 
+use base::id::PipelineId;
+use devtools_traits::DevtoolScriptControlMsg;
+use ipc_channel::ipc::IpcSender;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
@@ -69,11 +73,24 @@ struct VoidAttachedReply {
 
 pub struct ThreadActor {
     name: String,
+    pipeline: PipelineId,
+    script_sender: IpcSender<DevtoolScriptControlMsg>,
+    /// The connection that paused the event loop of the pipeline, if it is paused.
+    paused_by: Cell<Option<StreamId>>,
 }
 
 impl ThreadActor {
-    pub fn new(name: String) -> ThreadActor {
-        ThreadActor { name }
+    pub fn new(
+        name: String,
+        pipeline: PipelineId,
+        script_sender: IpcSender<DevtoolScriptControlMsg>,
+    ) -> ThreadActor {
+        ThreadActor {
+            name,
+            pipeline,
+            script_sender,
+            paused_by: Cell::new(None),
+        }
     }
 }
 
@@ -88,7 +105,7 @@ impl Actor for ThreadActor {
         msg_type: &str,
         _msg: &Map<String, Value>,
         stream: &mut TcpStream,
-        _id: StreamId,
+        id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "attach" => {
@@ -111,6 +128,10 @@ impl Actor for ThreadActor {
             },
 
             "resume" => {
+                self.paused_by.set(None);
+                let _ = self
+                    .script_sender
+                    .send(DevtoolScriptControlMsg::Resume(self.pipeline));
                 let msg = ThreadResumedReply {
                     from: self.name(),
                     type_: "resumed".to_owned(),
@@ -121,6 +142,10 @@ impl Actor for ThreadActor {
             },
 
             "interrupt" => {
+                self.paused_by.set(Some(id));
+                let _ = self
+                    .script_sender
+                    .send(DevtoolScriptControlMsg::Pause(self.pipeline));
                 let msg = ThreadInterruptedReply {
                     from: self.name(),
                     type_: "interrupted".to_owned(),
@@ -146,4 +171,87 @@ impl Actor for ThreadActor {
             _ => ActorMessageStatus::Ignored,
         })
     }
+
+    fn cleanup(&self, id: StreamId) {
+        // A client that goes away while the event loop is paused can never resume it.
+        if self.paused_by.get() == Some(id) {
+            self.paused_by.set(None);
+            let _ = self
+                .script_sender
+                .send(DevtoolScriptControlMsg::Resume(self.pipeline));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{TcpListener, TcpStream};
+
+    use base::id::TEST_PIPELINE_ID;
+    use devtools_traits::DevtoolScriptControlMsg;
+    use ipc_channel::ipc;
+    use serde_json::Map;
+
+    use super::ThreadActor;
+    use crate::actor::{Actor, ActorRegistry};
+    use crate::StreamId;
+
+    #[test]
+    fn closing_the_pausing_connection_resumes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (script_sender, script_receiver) = ipc::channel().unwrap();
+        let actor = ThreadActor::new("context0".to_owned(), TEST_PIPELINE_ID, script_sender);
+        let registry = ActorRegistry::new();
+
+        actor
+            .handle_message(
+                &registry,
+                "interrupt",
+                &Map::new(),
+                &mut stream,
+                StreamId(0),
+            )
+            .unwrap();
+        assert!(matches!(
+            script_receiver.recv().unwrap(),
+            DevtoolScriptControlMsg::Pause(TEST_PIPELINE_ID)
+        ));
+
+        // Another client going away leaves the event loop paused.
+        actor.cleanup(StreamId(1));
+        assert!(script_receiver.try_recv().is_err());
+
+        actor.cleanup(StreamId(0));
+        assert!(matches!(
+            script_receiver.recv().unwrap(),
+            DevtoolScriptControlMsg::Resume(TEST_PIPELINE_ID)
+        ));
+    }
+
+    #[test]
+    fn closing_a_connection_after_resuming_does_not_resume_again() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (script_sender, script_receiver) = ipc::channel().unwrap();
+        let actor = ThreadActor::new("context0".to_owned(), TEST_PIPELINE_ID, script_sender);
+        let registry = ActorRegistry::new();
+
+        for msg_type in ["interrupt", "resume"] {
+            actor
+                .handle_message(&registry, msg_type, &Map::new(), &mut stream, StreamId(0))
+                .unwrap();
+        }
+        assert!(matches!(
+            script_receiver.recv().unwrap(),
+            DevtoolScriptControlMsg::Pause(TEST_PIPELINE_ID)
+        ));
+        assert!(matches!(
+            script_receiver.recv().unwrap(),
+            DevtoolScriptControlMsg::Resume(TEST_PIPELINE_ID)
+        ));
+
+        actor.cleanup(StreamId(0));
+        assert!(script_receiver.try_recv().is_err());
+    }
 }
@@ -129,7 +129,12 @@ impl TimelineActor {
         pipeline: PipelineId,
         script_sender: IpcSender<DevtoolScriptControlMsg>,
     ) -> TimelineActor {
-        let marker_types = vec![TimelineMarkerType::Reflow, TimelineMarkerType::DOMEvent];
+        let marker_types = vec![
+            TimelineMarkerType::Reflow,
+            TimelineMarkerType::DOMEvent,
+            TimelineMarkerType::MicrotaskCheckpoint,
+            TimelineMarkerType::LongMicrotask,
+        ];
 
         TimelineActor {
             name,
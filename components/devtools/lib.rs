@@ -15,17 +15,19 @@
 use std::borrow::ToOwned;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 use base::id::{BrowsingContextId, PipelineId};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolScriptControlMsg, DevtoolsControlMsg,
-    DevtoolsPageInfo, LogLevel, NavigationState, NetworkEvent, PageError,
-    ScriptToDevtoolsControlMsg, WorkerId,
+    DevtoolsPageInfo, InputEventInfo, LayoutInvalidationInfo, LogLevel, NavigationState,
+    NetworkEvent, NodeInfo, PageError, ScriptToDevtoolsControlMsg, SelectionInfo, WorkerId,
 };
 use embedder_traits::{EmbedderMsg, EmbedderProxy, PromptDefinition, PromptOrigin, PromptResult};
 use ipc_channel::ipc::{self, IpcSender};
@@ -65,6 +67,7 @@ mod actors {
     pub mod process;
     pub mod profiler;
     pub mod root;
+    pub mod security;
     pub mod stylesheets;
     pub mod tab;
     pub mod thread;
@@ -74,6 +77,12 @@ mod actors {
 }
 mod protocol;
 
+/// How often the acceptor thread checks for new clients.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The generation of the next server started with [`listen`].
+static NEXT_SERVER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 enum UniqueId {
     Pipeline(PipelineId),
@@ -120,46 +129,74 @@ pub struct EmptyReplyMsg {
     pub from: String,
 }
 
-/// Spin up a devtools server that listens for connections on the specified port.
-pub fn start_server(port: u16, embedder: EmbedderProxy) -> Sender<DevtoolsControlMsg> {
+/// Spin up the devtools thread. It keeps track of pages and workers from the start, but does not
+/// accept clients until it is told to listen on a port with [`listen`].
+pub fn start_thread(embedder: EmbedderProxy) -> Sender<DevtoolsControlMsg> {
     let (sender, receiver) = unbounded();
     {
         let sender = sender.clone();
         thread::Builder::new()
             .name("Devtools".to_owned())
-            .spawn(move || run_server(sender, receiver, port, embedder))
+            .spawn(move || run_server(sender, receiver, embedder))
             .expect("Thread spawning failed");
     }
     sender
 }
 
+/// Spin up a devtools server that listens for connections on the specified port.
+pub fn start_server(port: u16, embedder: EmbedderProxy) -> Sender<DevtoolsControlMsg> {
+    let sender = start_thread(embedder);
+    listen(&sender, port);
+    sender
+}
+
+/// Make the devtools thread accept clients on the specified port, stopping any server that
+/// already runs. The embedder is sent `EmbedderMsg::OnDevtoolsStarted` with the bound port and a
+/// token that lets clients connect without a permission prompt.
+pub fn listen(sender: &Sender<DevtoolsControlMsg>, port: u16) -> DevtoolsServerHandle {
+    let generation = NEXT_SERVER_GENERATION.fetch_add(1, Ordering::SeqCst);
+    let msg = DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::StartListening(
+        port, generation,
+    ));
+    if let Err(e) = sender.send(msg) {
+        warn!("Starting devtools server failed ({:?})", e);
+    }
+    DevtoolsServerHandle {
+        sender: sender.clone(),
+        generation,
+    }
+}
+
+/// A devtools server started by the embedder.
+#[derive(Clone)]
+pub struct DevtoolsServerHandle {
+    sender: Sender<DevtoolsControlMsg>,
+    /// Tells this server apart from the ones that replaced it, which `stop` must leave running.
+    generation: u64,
+}
+
+impl DevtoolsServerHandle {
+    /// Stop accepting clients and disconnect the connected ones, unless another server replaced
+    /// this one already. The devtools thread keeps running, so a later server still knows about
+    /// every open page.
+    pub fn stop(self) {
+        let msg = DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::StopListening(
+            self.generation,
+        ));
+        if let Err(e) = self.sender.send(msg) {
+            warn!("Stopping devtools server failed ({:?})", e);
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub(crate) struct StreamId(u32);
 
 fn run_server(
     sender: Sender<DevtoolsControlMsg>,
     receiver: Receiver<DevtoolsControlMsg>,
-    port: u16,
     embedder: EmbedderProxy,
 ) {
-    let bound = TcpListener::bind(("0.0.0.0", port)).ok().and_then(|l| {
-        l.local_addr()
-            .map(|addr| addr.port())
-            .ok()
-            .map(|port| (l, port))
-    });
-
-    // A token shared with the embedder to bypass permission prompt.
-    let token = format!("{:X}", servo_rand::ServoRng::default().next_u32());
-
-    let port = bound.as_ref().map(|(_, port)| *port).ok_or(());
-    embedder.send((None, EmbedderMsg::OnDevtoolsStarted(port, token.clone())));
-
-    let listener = match bound {
-        Some((l, _)) => l,
-        None => return,
-    };
-
     let mut registry = ActorRegistry::new();
 
     let performance = PerformanceActor::new(registry.new_name("performance"));
@@ -275,6 +312,80 @@ fn run_server(
         browsing_context.title_changed(pipeline, title);
     }
 
+    fn find_browsing_context_actor(
+        pipelines: &HashMap<PipelineId, BrowsingContextId>,
+        browsing_contexts: &HashMap<BrowsingContextId, String>,
+        pipeline: PipelineId,
+    ) -> Option<String> {
+        let bc = pipelines.get(&pipeline)?;
+        browsing_contexts.get(bc).cloned()
+    }
+
+    fn handle_focus_changed(
+        actors: Arc<Mutex<ActorRegistry>>,
+        pipelines: &HashMap<PipelineId, BrowsingContextId>,
+        browsing_contexts: &HashMap<BrowsingContextId, String>,
+        pipeline: PipelineId,
+        node: Option<NodeInfo>,
+    ) {
+        let Some(name) = find_browsing_context_actor(pipelines, browsing_contexts, pipeline) else {
+            return;
+        };
+        let mut actors = actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(&name);
+        browsing_context.focus_changed(&actors, pipeline, node);
+        // Node actors for the nodes in the notification have to be available right away.
+        actors.register_new_actors();
+    }
+
+    fn handle_selection_changed(
+        actors: Arc<Mutex<ActorRegistry>>,
+        pipelines: &HashMap<PipelineId, BrowsingContextId>,
+        browsing_contexts: &HashMap<BrowsingContextId, String>,
+        pipeline: PipelineId,
+        selection: SelectionInfo,
+    ) {
+        let Some(name) = find_browsing_context_actor(pipelines, browsing_contexts, pipeline) else {
+            return;
+        };
+        let mut actors = actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(&name);
+        browsing_context.selection_changed(&actors, pipeline, selection);
+        actors.register_new_actors();
+    }
+
+    fn handle_input_event_dispatched(
+        actors: Arc<Mutex<ActorRegistry>>,
+        pipelines: &HashMap<PipelineId, BrowsingContextId>,
+        browsing_contexts: &HashMap<BrowsingContextId, String>,
+        pipeline: PipelineId,
+        event: InputEventInfo,
+    ) {
+        let Some(name) = find_browsing_context_actor(pipelines, browsing_contexts, pipeline) else {
+            return;
+        };
+        let mut actors = actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(&name);
+        browsing_context.input_event_dispatched(&actors, pipeline, event);
+        actors.register_new_actors();
+    }
+
+    fn handle_layout_invalidated(
+        actors: Arc<Mutex<ActorRegistry>>,
+        pipelines: &HashMap<PipelineId, BrowsingContextId>,
+        browsing_contexts: &HashMap<BrowsingContextId, String>,
+        pipeline: PipelineId,
+        invalidation: LayoutInvalidationInfo,
+    ) {
+        let Some(name) = find_browsing_context_actor(pipelines, browsing_contexts, pipeline) else {
+            return;
+        };
+        let mut actors = actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(&name);
+        browsing_context.layout_invalidated(&actors, pipeline, invalidation);
+        actors.register_new_actors();
+    }
+
     // We need separate actor representations for each script global that exists;
     // clients can theoretically connect to multiple globals simultaneously.
     // TODO: move this into the root or target modules?
@@ -297,7 +408,8 @@ fn run_server(
             assert!(pipelines.get(&pipeline).is_some());
             assert!(browsing_contexts.get(&browsing_context).is_some());
 
-            let thread = ThreadActor::new(actors.new_name("context"));
+            let thread =
+                ThreadActor::new(actors.new_name("context"), pipeline, script_sender.clone());
             let thread_name = thread.name();
             actors.register(Box::new(thread));
 
@@ -579,29 +691,39 @@ fn run_server(
         }
     }
 
-    thread::Builder::new()
-        .name("DevtCliAcceptor".to_owned())
-        .spawn(move || {
-            // accept connections and process them, spawning a new thread for each one
-            for stream in listener.incoming() {
-                let mut stream = stream.expect("Can't retrieve stream");
-                if !allow_devtools_client(&mut stream, &embedder, &token) {
-                    continue;
-                };
-                // connection succeeded and accepted
-                sender
-                    .send(DevtoolsControlMsg::FromChrome(
-                        ChromeToDevtoolsControlMsg::AddClient(stream),
-                    ))
-                    .unwrap();
-            }
-        })
-        .expect("Thread spawning failed");
+    // The generation of the current listener, and the flag that stops it.
+    let mut listening: Option<(u64, Arc<AtomicBool>)> = None;
+    // The channels of every global, which are told whether a server is running.
+    let mut script_senders: Vec<IpcSender<DevtoolScriptControlMsg>> = Vec::new();
 
     let mut next_id = StreamId(0);
     while let Ok(msg) = receiver.recv() {
         debug!("{:?}", msg);
+        // Nobody can see these events while no server is running, and the actors would keep
+        // them around for good.
+        if listening.is_none() && is_transient_event(&msg) {
+            continue;
+        }
+        let was_listening = listening.is_some();
         match msg {
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::StartListening(
+                port,
+                generation,
+            )) => {
+                if let Some((_, stopped)) = listening.take() {
+                    stop_listening(&stopped, &mut accepted_connections);
+                }
+                listening = start_listening(port, sender.clone(), embedder.clone())
+                    .map(|stopped| (generation, stopped));
+            },
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::StopListening(
+                generation,
+            )) => {
+                if listening.as_ref().map(|(current, _)| *current) == Some(generation) {
+                    let (_, stopped) = listening.take().unwrap();
+                    stop_listening(&stopped, &mut accepted_connections);
+                }
+            },
             DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::AddClient(stream)) => {
                 let actors = actors.clone();
                 let id = next_id;
@@ -626,19 +748,65 @@ fn run_server(
                 pipeline,
                 title,
             ),
-            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::NewGlobal(
-                ids,
-                script_sender,
-                pageinfo,
-            )) => handle_new_global(
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::FocusChanged(
+                pipeline,
+                node,
+            )) => handle_focus_changed(
                 actors.clone(),
+                &pipelines,
+                &browsing_contexts,
+                pipeline,
+                node,
+            ),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::SelectionChanged(
+                pipeline,
+                selection,
+            )) => handle_selection_changed(
+                actors.clone(),
+                &pipelines,
+                &browsing_contexts,
+                pipeline,
+                selection,
+            ),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::InputEventDispatched(
+                pipeline,
+                event,
+            )) => handle_input_event_dispatched(
+                actors.clone(),
+                &pipelines,
+                &browsing_contexts,
+                pipeline,
+                event,
+            ),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::LayoutInvalidated(
+                pipeline,
+                invalidation,
+            )) => handle_layout_invalidated(
+                actors.clone(),
+                &pipelines,
+                &browsing_contexts,
+                pipeline,
+                invalidation,
+            ),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::NewGlobal(
                 ids,
                 script_sender,
-                &mut browsing_contexts,
-                &mut pipelines,
-                &mut actor_workers,
                 pageinfo,
-            ),
+            )) => {
+                if was_listening {
+                    let _ = script_sender.send(DevtoolScriptControlMsg::SetServerRunning(true));
+                }
+                script_senders.push(script_sender.clone());
+                handle_new_global(
+                    actors.clone(),
+                    ids,
+                    script_sender,
+                    &mut browsing_contexts,
+                    &mut pipelines,
+                    &mut actor_workers,
+                    pageinfo,
+                )
+            },
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::Navigate(
                 browsing_context,
                 state,
@@ -717,8 +885,107 @@ fn run_server(
             },
             DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::ServerExitMsg) => break,
         }
+        if listening.is_some() != was_listening {
+            // Senders of globals that are gone fail, and are forgotten.
+            let running = listening.is_some();
+            script_senders.retain(|sender| {
+                sender
+                    .send(DevtoolScriptControlMsg::SetServerRunning(running))
+                    .is_ok()
+            });
+        }
+    }
+    if let Some((_, stopped)) = listening.take() {
+        stop_listening(&stopped, &mut accepted_connections);
+    }
+}
+
+/// Whether the message reports something that happened, rather than a change to the pages and
+/// workers the devtools keep track of.
+fn is_transient_event(msg: &DevtoolsControlMsg) -> bool {
+    matches!(
+        msg,
+        DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(..)) |
+            DevtoolsControlMsg::FromScript(
+                ScriptToDevtoolsControlMsg::FramerateTick(..) |
+                    ScriptToDevtoolsControlMsg::InputEventDispatched(..) |
+                    ScriptToDevtoolsControlMsg::LayoutInvalidated(..) |
+                    ScriptToDevtoolsControlMsg::ConsoleAPI(..) |
+                    ScriptToDevtoolsControlMsg::ReportPageError(..) |
+                    ScriptToDevtoolsControlMsg::ReportCSSError(..)
+            )
+    )
+}
+
+/// Bind a listener on the given port, and accept clients on a new thread until the returned flag
+/// is set.
+fn start_listening(
+    port: u16,
+    sender: Sender<DevtoolsControlMsg>,
+    embedder: EmbedderProxy,
+) -> Option<Arc<AtomicBool>> {
+    let bound = TcpListener::bind(("0.0.0.0", port)).ok().and_then(|l| {
+        l.local_addr()
+            .map(|addr| addr.port())
+            .ok()
+            .map(|port| (l, port))
+    });
+
+    // A token shared with the embedder to bypass permission prompt.
+    let token = format!("{:X}", servo_rand::ServoRng::default().next_u32());
+
+    let port = bound.as_ref().map(|(_, port)| *port).ok_or(());
+    embedder.send((None, EmbedderMsg::OnDevtoolsStarted(port, token.clone())));
+
+    let (listener, _) = bound?;
+    // Poll for clients, so that the acceptor can notice when it has to stop.
+    if let Err(e) = listener.set_nonblocking(true) {
+        warn!("Devtools listener setup failed ({:?})", e);
+        return None;
     }
-    for connection in &mut accepted_connections {
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let acceptor_stopped = stopped.clone();
+    thread::Builder::new()
+        .name("DevtCliAcceptor".to_owned())
+        .spawn(move || {
+            // accept connections and process them, spawning a new thread for each one
+            while !acceptor_stopped.load(Ordering::SeqCst) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                        continue;
+                    },
+                    Err(e) => {
+                        warn!("Devtools listener failed ({:?})", e);
+                        break;
+                    },
+                };
+                if stream.set_nonblocking(false).is_err() ||
+                    !allow_devtools_client(&mut stream, &embedder, &token)
+                {
+                    continue;
+                };
+                // The server may have been stopped while the user was prompted.
+                if acceptor_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                // connection succeeded and accepted
+                let msg =
+                    DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::AddClient(stream));
+                if sender.send(msg).is_err() {
+                    break;
+                }
+            }
+        })
+        .expect("Thread spawning failed");
+    Some(stopped)
+}
+
+fn stop_listening(stopped: &AtomicBool, accepted_connections: &mut Vec<TcpStream>) {
+    stopped.store(true, Ordering::SeqCst);
+    for connection in accepted_connections.drain(..) {
         let _ = connection.shutdown(Shutdown::Both);
     }
 }
@@ -17,6 +17,7 @@ use malloc_size_of_derive::MallocSizeOf;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use servo_atoms::{atom, Atom};
+use servo_config::pref;
 use smallvec::SmallVec;
 use style::computed_values::font_variant_caps;
 use style::properties::style_structs::Font as FontStyleStruct;
@@ -617,7 +618,9 @@ impl FontGroup {
     /// Attempts to find a suitable fallback font which matches the given `template_predicate` and
     /// `font_predicate`. The default family (i.e. "serif") will be tried first, followed by
     /// platform-specific family names. If a `codepoint` is provided, then its Unicode block may be
-    /// used to refine the list of family names which will be tried.
+    /// used to refine the list of family names which will be tried. In deterministic layout mode
+    /// only the default family is tried, so that the result does not depend on which fonts
+    /// happen to be installed.
     fn find_fallback<S, TemplatePredicate, FontPredicate>(
         &mut self,
         font_context: &FontContext<S>,
@@ -630,8 +633,13 @@ impl FontGroup {
         TemplatePredicate: Fn(FontTemplateRef) -> bool,
         FontPredicate: Fn(&FontRef) -> bool,
     {
+        let platform_families = if pref!(layout.testing.deterministic.enabled) {
+            vec![]
+        } else {
+            fallback_font_families(options)
+        };
         iter::once(FontFamilyDescriptor::serif())
-            .chain(fallback_font_families(options).into_iter().map(|family| {
+            .chain(platform_families.into_iter().map(|family| {
                 FontFamilyDescriptor::new(FontFamilyName::from(family), FontSearchScope::Local)
             }))
             .filter_map(|family_descriptor| {
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Transforms and opacities are pushed to WebRender as property bindings, so that when only
+//! their values change the compositor can update the display list it already has instead of
+//! receiving a new one.
+
+use std::hash::{Hash, Hasher};
+
+use fxhash::{FxHashSet, FxHasher};
+use style::dom::OpaqueNode;
+use webrender_api::units::LayoutTransform;
+use webrender_api::{self as wr, PropertyBindingKey, PropertyValue};
+
+use crate::fragment_tree::{Fragment, FragmentTree, Tag};
+
+/// The nodes whose transform or opacity a display list bound to a WebRender property.
+pub struct AnimatedPropertyBindings {
+    /// The pipeline of the display list. The properties of all the pipelines of a WebRender
+    /// document share one namespace, whatever content process they come from.
+    pipeline_id: wr::PipelineId,
+    transforms: FxHashSet<OpaqueNode>,
+    opacities: FxHashSet<OpaqueNode>,
+    /// Nodes with more than one transformed fragment. The transforms of those fragments depend
+    /// on their sizes, so they cannot share a property binding.
+    unbound_transforms: FxHashSet<OpaqueNode>,
+}
+
+/// New values for the properties bound by a display list.
+pub struct AnimatedPropertyValues {
+    pub transforms: Vec<PropertyValue<LayoutTransform>>,
+    pub opacities: Vec<PropertyValue<f32>>,
+}
+
+impl AnimatedPropertyBindings {
+    pub fn new(pipeline_id: wr::PipelineId) -> Self {
+        Self {
+            pipeline_id,
+            transforms: Default::default(),
+            opacities: Default::default(),
+            unbound_transforms: Default::default(),
+        }
+    }
+
+    /// The key of the property of the given node. Node addresses are only unique within a
+    /// process, so the key is derived from the pipeline, including its namespace, as well.
+    fn property_binding_key<T>(&self, node: OpaqueNode) -> PropertyBindingKey<T> {
+        let mut hasher = FxHasher::default();
+        self.pipeline_id.hash(&mut hasher);
+        node.hash(&mut hasher);
+        PropertyBindingKey::new(hasher.finish())
+    }
+
+    /// Bind the transform of the reference frame of a fragment, if that fragment is the only
+    /// one of its element with a transform.
+    pub(super) fn bind_transform(
+        &mut self,
+        tag: Option<Tag>,
+        transform: LayoutTransform,
+    ) -> wr::PropertyBinding<LayoutTransform> {
+        let Some(node) = tag.filter(|tag| !tag.is_pseudo()).map(|tag| tag.node) else {
+            return wr::PropertyBinding::Value(transform);
+        };
+        if !self.transforms.insert(node) {
+            self.unbound_transforms.insert(node);
+            return wr::PropertyBinding::Value(transform);
+        }
+        wr::PropertyBinding::Binding(self.property_binding_key(node), transform)
+    }
+
+    /// Bind the opacity of the stacking context of a fragment. The opacity does not depend on
+    /// the geometry of the fragment, so all the fragments of an element share a binding.
+    pub(super) fn bind_opacity(
+        &mut self,
+        tag: Option<Tag>,
+        opacity: f32,
+    ) -> wr::PropertyBinding<f32> {
+        let Some(node) = tag.filter(|tag| !tag.is_pseudo()).map(|tag| tag.node) else {
+            return wr::PropertyBinding::Value(opacity);
+        };
+        self.opacities.insert(node);
+        wr::PropertyBinding::Binding(self.property_binding_key(node), opacity)
+    }
+
+    fn transform_is_bound(&self, node: OpaqueNode) -> bool {
+        self.transforms.contains(&node) && !self.unbound_transforms.contains(&node)
+    }
+
+    /// Compute the values of all the bound properties from the styles of the given fragment
+    /// tree, which must have the same geometry as the one the display list was built from.
+    ///
+    /// Returns `None` if the transform or opacity of one of the `changed_nodes` is not bound,
+    /// or if its new transform would change the structure of the display list, in which case
+    /// a new display list has to be built.
+    pub fn values(
+        &self,
+        fragment_tree: &FragmentTree,
+        changed_nodes: &[OpaqueNode],
+    ) -> Option<AnimatedPropertyValues> {
+        let mut values = AnimatedPropertyValues {
+            transforms: Vec::with_capacity(self.transforms.len()),
+            opacities: Vec::with_capacity(self.opacities.len()),
+        };
+        let mut changed_nodes_found = FxHashSet::default();
+        let mut opacities_found = FxHashSet::default();
+        let mut structure_changed = false;
+        fragment_tree.find(|fragment, _, containing_block| {
+            let (Fragment::Box(fragment) | Fragment::Float(fragment)) = fragment else {
+                return None::<()>;
+            };
+            let tag = fragment.base.tag.filter(|tag| !tag.is_pseudo())?;
+            if changed_nodes.contains(&tag.node) {
+                changed_nodes_found.insert(tag.node);
+                let transform_is_unbound = !fragment.style.get_box().transform.0.is_empty() &&
+                    !self.transform_is_bound(tag.node);
+                let opacity_is_unbound = fragment.style.get_effects().opacity != 1.0 &&
+                    !self.opacities.contains(&tag.node);
+                if transform_is_unbound || opacity_is_unbound {
+                    structure_changed = true;
+                    return Some(());
+                }
+            }
+
+            if self.transform_is_bound(tag.node) {
+                match fragment.bound_transform(containing_block) {
+                    Some(transform) => values.transforms.push(PropertyValue {
+                        key: self.property_binding_key(tag.node),
+                        value: transform,
+                    }),
+                    None => {
+                        structure_changed = true;
+                        return Some(());
+                    },
+                }
+            }
+            if self.opacities.contains(&tag.node) && opacities_found.insert(tag.node) {
+                values.opacities.push(PropertyValue {
+                    key: self.property_binding_key(tag.node),
+                    value: fragment.style.get_effects().opacity,
+                });
+            }
+            None
+        });
+
+        // A changed node without fragments, or without a box fragment, is not bound either.
+        if structure_changed || changed_nodes_found.len() != changed_nodes.len() {
+            return None;
+        }
+        Some(values)
+    }
+}
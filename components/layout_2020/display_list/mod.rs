@@ -39,11 +39,13 @@ use crate::geom::{PhysicalPoint, PhysicalRect};
 use crate::replaced::IntrinsicSizes;
 use crate::style_ext::ComputedValuesExt;
 
+mod animated_properties;
 mod background;
 mod conversions;
 mod gradient;
 mod stacking_context;
 
+pub use animated_properties::{AnimatedPropertyBindings, AnimatedPropertyValues};
 use background::BackgroundPainter;
 pub use stacking_context::*;
 
@@ -76,6 +78,9 @@ pub struct DisplayList {
     /// list. This is merely to ensure that the currently-unused SpatialTreeItemKey
     /// produced for every SpatialTree node is unique.
     pub spatial_tree_count: u64,
+
+    /// The transforms and opacities that this display list binds to WebRender properties.
+    pub animated_properties: AnimatedPropertyBindings,
 }
 
 impl DisplayList {
@@ -98,6 +103,7 @@ impl DisplayList {
                 root_scroll_sensitivity,
             ),
             spatial_tree_count: 0,
+            animated_properties: AnimatedPropertyBindings::new(pipeline_id),
         }
     }
 
@@ -33,7 +33,8 @@ use crate::cell::ArcRefCell;
 use crate::display_list::conversions::{FilterToWebRender, ToWebRender};
 use crate::display_list::DisplayListBuilder;
 use crate::fragment_tree::{
-    BoxFragment, ContainingBlockManager, Fragment, FragmentFlags, FragmentTree, PositioningFragment,
+    BoxFragment, ContainingBlockManager, Fragment, FragmentFlags, FragmentTree,
+    PositioningFragment, Tag,
 };
 use crate::geom::{AuOrAuto, PhysicalRect, PhysicalSides};
 use crate::style_ext::ComputedValuesExt;
@@ -317,6 +318,10 @@ pub struct StackingContext {
     /// The fragment that established this stacking context.
     initializing_fragment_style: Option<ServoArc<ComputedValues>>,
 
+    /// The tag of the fragment that established this stacking context, used to bind its
+    /// opacity to a WebRender property.
+    initializing_fragment_tag: Option<Tag>,
+
     /// The type of this stacking context. Used for collecting and sorting.
     context_type: StackingContextType,
 
@@ -375,6 +380,7 @@ impl StackingContext {
         spatial_id: wr::SpatialId,
         clip_chain_id: wr::ClipChainId,
         initializing_fragment_style: ServoArc<ComputedValues>,
+        initializing_fragment_tag: Option<Tag>,
         context_type: StackingContextType,
     ) -> Self {
         // WebRender has two different ways of expressing "no clip." ClipChainId::INVALID should be
@@ -389,6 +395,7 @@ impl StackingContext {
             spatial_id,
             clip_chain_id,
             initializing_fragment_style: Some(initializing_fragment_style),
+            initializing_fragment_tag,
             context_type,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
@@ -403,6 +410,7 @@ impl StackingContext {
             spatial_id: wr::SpaceAndClipInfo::root_scroll(wr.pipeline_id).spatial_id,
             clip_chain_id: None,
             initializing_fragment_style: None,
+            initializing_fragment_tag: None,
             context_type: StackingContextType::RealStackingContext,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
@@ -493,10 +501,11 @@ impl StackingContext {
             .map(|filter| FilterToWebRender::to_webrender(filter, &current_color))
             .collect();
         if effects.opacity != 1.0 {
-            filters.push(wr::FilterOp::Opacity(
-                effects.opacity.into(),
-                effects.opacity,
-            ));
+            let opacity = builder
+                .display_list
+                .animated_properties
+                .bind_opacity(self.initializing_fragment_tag, effects.opacity);
+            filters.push(wr::FilterOp::Opacity(opacity, effects.opacity));
         }
 
         // TODO(jdm): WebRender now requires us to create stacking context items
@@ -975,11 +984,14 @@ impl BoxFragment {
                 },
             };
 
+        let transform = display_list
+            .animated_properties
+            .bind_transform(self.base.tag, reference_frame_data.transform);
         let new_spatial_id = display_list.push_reference_frame(
             reference_frame_data.origin.to_webrender(),
             &containing_block.scroll_node_id,
             self.style.get_box().transform_style.to_webrender(),
-            wr::PropertyBinding::Value(reference_frame_data.transform),
+            transform,
             reference_frame_data.kind,
         );
 
@@ -1054,6 +1066,7 @@ impl BoxFragment {
             containing_block.scroll_node_id.spatial_id,
             containing_block.clip_chain_id,
             self.style.clone(),
+            self.base.tag,
             context_type,
         );
         self.build_stacking_context_tree_for_children(
@@ -1455,6 +1468,19 @@ impl BoxFragment {
         })
     }
 
+    /// The transform of the reference frame built for this fragment, if building the stacking
+    /// context tree would still create one for it.
+    pub(super) fn bound_transform(
+        &self,
+        containing_block_rect: &PhysicalRect<Au>,
+    ) -> Option<LayoutTransform> {
+        if self.has_non_invertible_transform_or_zero_scale(&containing_block_rect.to_untyped()) {
+            return None;
+        }
+        self.reference_frame_data_if_necessary(containing_block_rect)
+            .map(|reference_frame_data| reference_frame_data.transform)
+    }
+
     /// Returns true if the given style contains a transform that is not invertible.
     fn has_non_invertible_transform_or_zero_scale(&self, containing_block: &Rect<Au>) -> bool {
         let list = &self.style.get_box().transform;
@@ -14,7 +14,7 @@ use style::str::char_is_whitespace;
 use style::values::specified::text::TextDecorationLine;
 
 use super::inline::construct::InlineFormattingContextBuilder;
-use super::inline::{InlineBox, InlineFormattingContext};
+use super::inline::{InlineBox, InlineFormattingContext, InlineItem};
 use super::OutsideMarker;
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
@@ -87,6 +87,24 @@ enum BlockLevelCreator {
     },
 }
 
+/// An inline-level box of an inline formatting context that establishes an independent
+/// formatting context. Its item in the inline formatting context is a placeholder until the
+/// whole block container has been traversed, which allows constructing the contents of many
+/// inline-blocks, floats or absolutely positioned boxes in parallel, just like block-level boxes.
+struct InlineLevelJob<Node> {
+    info: NodeAndStyleInfo<Node>,
+    display_inside: DisplayInside,
+    contents: Contents,
+    kind: InlineLevelCreator,
+    inline_item: ArcRefCell<InlineItem>,
+}
+
+enum InlineLevelCreator {
+    Atomic,
+    OutOfFlowAbsolutelyPositionedBox,
+    OutOfFlowFloatBox,
+}
+
 /// A block container that may still have to be constructed.
 ///
 /// Represents either the inline formatting context of an anonymous block
@@ -129,6 +147,10 @@ pub(crate) struct BlockContainerBuilder<'dom, 'style, Node> {
     /// (see `handle_block_level_element`).
     block_level_boxes: Vec<BlockLevelJob<'dom, Node>>,
 
+    /// The inline-level boxes found in the inline formatting contexts of this block container
+    /// whose contents are constructed in `finish`.
+    inline_level_boxes: Vec<InlineLevelJob<Node>>,
+
     /// Whether or not this builder has yet produced a block which would be
     /// be considered the first line for the purposes of `text-indent`.
     have_already_seen_first_line_for_text_indent: bool,
@@ -196,6 +218,7 @@ where
             context,
             info,
             block_level_boxes: Vec::new(),
+            inline_level_boxes: Vec::new(),
             text_decoration_line,
             have_already_seen_first_line_for_text_indent: false,
             anonymous_style: None,
@@ -211,6 +234,18 @@ where
 
         self.finish_anonymous_table_if_needed();
 
+        let context = self.context;
+        let inline_level_boxes = std::mem::take(&mut self.inline_level_boxes);
+        if context.use_rayon {
+            inline_level_boxes
+                .into_par_iter()
+                .for_each(|inline_level_job| inline_level_job.finish(context));
+        } else {
+            inline_level_boxes
+                .into_iter()
+                .for_each(|inline_level_job| inline_level_job.finish(context));
+        }
+
         if let Some(inline_formatting_context) = self.inline_formatting_context_builder.finish(
             self.context,
             self.text_decoration_line,
@@ -226,8 +261,7 @@ where
             self.push_block_level_job_for_inline_formatting_context(inline_formatting_context);
         }
 
-        let context = self.context;
-        let block_level_boxes = if self.context.use_rayon {
+        let block_level_boxes = if context.use_rayon {
             self.block_level_boxes
                 .into_par_iter()
                 .map(|block_level_job| block_level_job.finish(context))
@@ -429,16 +463,16 @@ where
             (display_inside, contents.is_replaced())
         else {
             // If this inline element is an atomic, handle it and return.
-            let atomic = self.inline_formatting_context_builder.push_atomic(
-                IndependentFormattingContext::construct(
-                    self.context,
-                    info,
-                    display_inside,
-                    contents,
-                    // Text decorations are not propagated to atomic inline-level descendants.
-                    TextDecorationLine::NONE,
-                ),
-            );
+            let atomic = self
+                .inline_formatting_context_builder
+                .push_deferred_atomic();
+            self.inline_level_boxes.push(InlineLevelJob {
+                info: info.clone(),
+                display_inside,
+                contents,
+                kind: InlineLevelCreator::Atomic,
+                inline_item: atomic.clone(),
+            });
             box_slot.set(LayoutBox::InlineLevel(atomic));
             return;
         };
@@ -543,12 +577,14 @@ where
         if !self.inline_formatting_context_builder.is_empty() {
             let inline_level_box = self
                 .inline_formatting_context_builder
-                .push_absolutely_positioned_box(AbsolutelyPositionedBox::construct(
-                    self.context,
-                    info,
-                    display_inside,
-                    contents,
-                ));
+                .push_deferred_absolutely_positioned_box();
+            self.inline_level_boxes.push(InlineLevelJob {
+                info: info.clone(),
+                display_inside,
+                contents,
+                kind: InlineLevelCreator::OutOfFlowAbsolutelyPositionedBox,
+                inline_item: inline_level_box.clone(),
+            });
             box_slot.set(LayoutBox::InlineLevel(inline_level_box));
             return;
         }
@@ -572,14 +608,16 @@ where
         box_slot: BoxSlot<'dom>,
     ) {
         if !self.inline_formatting_context_builder.is_empty() {
-            let inline_level_box =
-                self.inline_formatting_context_builder
-                    .push_float_box(FloatBox::construct(
-                        self.context,
-                        info,
-                        display_inside,
-                        contents,
-                    ));
+            let inline_level_box = self
+                .inline_formatting_context_builder
+                .push_deferred_float_box();
+            self.inline_level_boxes.push(InlineLevelJob {
+                info: info.clone(),
+                display_inside,
+                contents,
+                kind: InlineLevelCreator::OutOfFlowFloatBox,
+                inline_item: inline_level_box.clone(),
+            });
             box_slot.set(LayoutBox::InlineLevel(inline_level_box));
             return;
         }
@@ -721,6 +759,41 @@ where
     }
 }
 
+impl<'dom, Node> InlineLevelJob<Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn finish(self, context: &LayoutContext) {
+        let info = &self.info;
+        let inline_item = match self.kind {
+            InlineLevelCreator::Atomic => {
+                InlineItem::Atomic(IndependentFormattingContext::construct(
+                    context,
+                    info,
+                    self.display_inside,
+                    self.contents,
+                    // Text decorations are not propagated to atomic inline-level descendants.
+                    TextDecorationLine::NONE,
+                ))
+            },
+            InlineLevelCreator::OutOfFlowAbsolutelyPositionedBox => {
+                InlineItem::OutOfFlowAbsolutelyPositionedBox(ArcRefCell::new(
+                    AbsolutelyPositionedBox::construct(
+                        context,
+                        info,
+                        self.display_inside,
+                        self.contents,
+                    ),
+                ))
+            },
+            InlineLevelCreator::OutOfFlowFloatBox => InlineItem::OutOfFlowFloatBox(
+                FloatBox::construct(context, info, self.display_inside, self.contents),
+            ),
+        };
+        *self.inline_item.borrow_mut() = inline_item;
+    }
+}
+
 impl IntermediateBlockContainer {
     fn finish<'dom, Node>(
         self,
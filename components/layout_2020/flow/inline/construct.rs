@@ -16,9 +16,7 @@ use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::dom::NodeExt;
 use crate::dom_traversal::NodeAndStyleInfo;
-use crate::flow::float::FloatBox;
 use crate::formatting_contexts::IndependentFormattingContext;
-use crate::positioned::AbsolutelyPositionedBox;
 
 #[derive(Default)]
 pub(crate) struct InlineFormattingContextBuilder {
@@ -116,8 +114,15 @@ impl InlineFormattingContextBuilder {
         &mut self,
         independent_formatting_context: IndependentFormattingContext,
     ) -> ArcRefCell<InlineItem> {
-        let inline_level_box = ArcRefCell::new(InlineItem::Atomic(independent_formatting_context));
-        self.inline_items.push(inline_level_box.clone());
+        let inline_level_box = self.push_deferred_atomic();
+        *inline_level_box.borrow_mut() = InlineItem::Atomic(independent_formatting_context);
+        inline_level_box
+    }
+
+    /// Push the item of an atomic inline-level box whose [`IndependentFormattingContext`] is
+    /// constructed later. See [`Self::push_deferred_item`].
+    pub(crate) fn push_deferred_atomic(&mut self) -> ArcRefCell<InlineItem> {
+        let inline_level_box = self.push_deferred_item();
 
         // Push an object replacement character for this atomic, which will ensure that the line breaker
         // inserts a line breaking opportunity here.
@@ -131,22 +136,27 @@ impl InlineFormattingContextBuilder {
         inline_level_box
     }
 
-    pub(crate) fn push_absolutely_positioned_box(
-        &mut self,
-        absolutely_positioned_box: AbsolutelyPositionedBox,
-    ) -> ArcRefCell<InlineItem> {
-        let absolutely_positioned_box = ArcRefCell::new(absolutely_positioned_box);
-        let inline_level_box = ArcRefCell::new(InlineItem::OutOfFlowAbsolutelyPositionedBox(
-            absolutely_positioned_box,
-        ));
-        self.inline_items.push(inline_level_box.clone());
-        inline_level_box
+    /// Push the item of an absolutely positioned box whose contents are constructed later. See
+    /// [`Self::push_deferred_item`].
+    pub(crate) fn push_deferred_absolutely_positioned_box(&mut self) -> ArcRefCell<InlineItem> {
+        self.push_deferred_item()
     }
 
-    pub(crate) fn push_float_box(&mut self, float_box: FloatBox) -> ArcRefCell<InlineItem> {
-        let inline_level_box = ArcRefCell::new(InlineItem::OutOfFlowFloatBox(float_box));
-        self.inline_items.push(inline_level_box.clone());
+    /// Push the item of a float whose contents are constructed later. See
+    /// [`Self::push_deferred_item`].
+    pub(crate) fn push_deferred_float_box(&mut self) -> ArcRefCell<InlineItem> {
         self.contains_floats = true;
+        self.push_deferred_item()
+    }
+
+    /// Push a placeholder for an inline-level box that establishes an independent formatting
+    /// context, so that its contents can be constructed once the whole block container has been
+    /// traversed, in parallel with those of its siblings. The placeholder has to be replaced
+    /// before the box tree is laid out. Until then it is an [`InlineItem::EndInlineBox`], which
+    /// makes the builder non-empty like the box it stands for and is ignored while shaping.
+    fn push_deferred_item(&mut self) -> ArcRefCell<InlineItem> {
+        let inline_level_box = ArcRefCell::new(InlineItem::EndInlineBox);
+        self.inline_items.push(inline_level_box.clone());
         inline_level_box
     }
 
@@ -13,6 +13,7 @@ use servo_arc::Arc;
 use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::Overflow;
+use style::values::specified::text::TextDecorationLine;
 use style_traits::CSSPixel;
 use webrender_traits::display_list::ScrollSensitivity;
 
@@ -24,12 +25,17 @@ use crate::flexbox::FlexLevelBox;
 use crate::flow::float::FloatBox;
 use crate::flow::inline::InlineItem;
 use crate::flow::{BlockContainer, BlockFormattingContext, BlockLevelBox};
-use crate::formatting_contexts::IndependentFormattingContext;
+use crate::formatting_contexts::{
+    IndependentFormattingContext, NonReplacedFormattingContext,
+    NonReplacedFormattingContextContents,
+};
 use crate::fragment_tree::FragmentTree;
 use crate::geom::{LogicalVec2, PhysicalPoint, PhysicalRect, PhysicalSize};
 use crate::positioned::{AbsolutelyPositionedBox, PositioningContext};
 use crate::replaced::ReplacedContent;
-use crate::style_ext::{ComputedValuesExt, Display, DisplayGeneratingBox, DisplayInside};
+use crate::style_ext::{
+    ComputedValuesExt, Display, DisplayGeneratingBox, DisplayInside, DisplayOutside,
+};
 use crate::DefiniteContainingBlock;
 
 #[derive(Serialize)]
@@ -103,9 +109,10 @@ impl BoxTree {
     /// loops over its parent. The only valid candidates for now are absolutely
     /// positioned boxes which don't change their outside display mode (i.e. it
     /// will not attempt to update from an absolutely positioned inline element
-    /// which became an absolutely positioned block element). The value `true`
-    /// is returned if an incremental update could be done, and `false`
-    /// otherwise.
+    /// which became an absolutely positioned block element), and in-flow
+    /// children of a multi-column container that establish an independent
+    /// formatting context and keep doing so. The value `true` is returned if
+    /// an incremental update could be done, and `false` otherwise.
     ///
     /// There are various pain points that need to be taken care of to extend
     /// the set of valid candidates:
@@ -125,6 +132,7 @@ impl BoxTree {
             AbsolutelyPositionedBlockLevelBox(ArcRefCell<BlockLevelBox>),
             AbsolutelyPositionedInlineLevelBox(ArcRefCell<InlineItem>),
             AbsolutelyPositionedFlexLevelBox(ArcRefCell<FlexLevelBox>),
+            MulticolChild(ArcRefCell<BlockLevelBox>, TextDecorationLine),
         }
 
         fn update_point<'dom, Node>(
@@ -159,17 +167,23 @@ impl BoxTree {
             let primary_style = element_data.styles.primary();
             let box_style = primary_style.get_box();
 
-            if !box_style.position.is_absolutely_positioned() {
-                return None;
-            }
-
-            let display_inside = match Display::from(box_style.display) {
-                Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) => {
-                    inside
+            let (display_outside, display_inside) = match Display::from(box_style.display) {
+                Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { outside, inside }) => {
+                    (outside, inside)
                 },
                 _ => return None,
             };
 
+            if !box_style.position.is_absolutely_positioned() {
+                let update_point = multicol_child_update_point(
+                    node,
+                    primary_style,
+                    display_outside,
+                    display_inside,
+                )?;
+                return Some((primary_style.clone(), display_inside, update_point));
+            }
+
             let update_point =
                 match &*AtomicRef::filter_map(layout_data.self_box.borrow(), Option::as_ref)? {
                     LayoutBox::DisplayContents => return None,
@@ -204,33 +218,117 @@ impl BoxTree {
             Some((primary_style.clone(), display_inside, update_point))
         }
 
+        fn block_level_box<'dom>(node: impl NodeExt<'dom>) -> Option<ArcRefCell<BlockLevelBox>> {
+            let layout_data = node.layout_data()?;
+            match &*AtomicRef::filter_map(layout_data.self_box.borrow(), Option::as_ref)? {
+                LayoutBox::BlockLevel(block_level_box) => Some(block_level_box.clone()),
+                _ => None,
+            }
+        }
+
+        /// In-flow children of a multi-column container that establish an independent formatting
+        /// context can be rebuilt on their own: they don't take part in the floats of the
+        /// container, and the container keeps the text decorations that propagate to them. This
+        /// is only possible if neither the container nor its ancestors cached intrinsic sizes
+        /// that the child contributes to.
+        fn multicol_child_update_point<'dom>(
+            node: impl NodeExt<'dom>,
+            style: &ComputedValues,
+            display_outside: DisplayOutside,
+            display_inside: DisplayInside,
+        ) -> Option<UpdatePoint> {
+            if display_outside != DisplayOutside::Block || style.get_box().float.is_floating() {
+                return None;
+            }
+            if let DisplayInside::Flow { .. } = display_inside {
+                if !style.establishes_block_formatting_context() {
+                    return None;
+                }
+            }
+
+            let child_box = block_level_box(node)?;
+            if !matches!(&*child_box.borrow(), BlockLevelBox::Independent(_)) {
+                return None;
+            }
+
+            let parent = node.parent_node()?;
+            let propagated_text_decoration_line = match &*block_level_box(parent)?.borrow() {
+                BlockLevelBox::Independent(IndependentFormattingContext::NonReplaced(
+                    NonReplacedFormattingContext {
+                        content_sizes: None,
+                        contents: NonReplacedFormattingContextContents::Multicol(multicol),
+                        ..
+                    },
+                )) => multicol.children_text_decoration_line,
+                _ => return None,
+            };
+
+            let mut ancestor = parent.parent_node();
+            while let Some(node) = ancestor.filter(|node| node.is_element()) {
+                match &*block_level_box(node)?.borrow() {
+                    BlockLevelBox::SameFormattingContextBlock { .. } |
+                    BlockLevelBox::Independent(IndependentFormattingContext::NonReplaced(
+                        NonReplacedFormattingContext {
+                            content_sizes: None,
+                            ..
+                        },
+                    )) => {},
+                    _ => return None,
+                }
+                ancestor = node.parent_node();
+            }
+
+            Some(UpdatePoint::MulticolChild(
+                child_box,
+                propagated_text_decoration_line,
+            ))
+        }
+
         loop {
             if let Some((primary_style, display_inside, update_point)) = update_point(dirty_node) {
                 let contents = ReplacedContent::for_element(dirty_node, context)
                     .map_or_else(|| NonReplacedContents::OfElement.into(), Contents::Replaced);
                 let info = NodeAndStyleInfo::new(dirty_node, Arc::clone(&primary_style));
-                let out_of_flow_absolutely_positioned_box = ArcRefCell::new(
-                    AbsolutelyPositionedBox::construct(context, &info, display_inside, contents),
-                );
+                let out_of_flow_absolutely_positioned_box = |contents| {
+                    ArcRefCell::new(AbsolutelyPositionedBox::construct(
+                        context,
+                        &info,
+                        display_inside,
+                        contents,
+                    ))
+                };
                 match update_point {
                     UpdatePoint::AbsolutelyPositionedBlockLevelBox(block_level_box) => {
                         *block_level_box.borrow_mut() =
                             BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(
-                                out_of_flow_absolutely_positioned_box,
+                                out_of_flow_absolutely_positioned_box(contents),
                             );
                     },
                     UpdatePoint::AbsolutelyPositionedInlineLevelBox(inline_level_box) => {
                         *inline_level_box.borrow_mut() =
                             InlineItem::OutOfFlowAbsolutelyPositionedBox(
-                                out_of_flow_absolutely_positioned_box,
+                                out_of_flow_absolutely_positioned_box(contents),
                             );
                     },
                     UpdatePoint::AbsolutelyPositionedFlexLevelBox(flex_level_box) => {
                         *flex_level_box.borrow_mut() =
                             FlexLevelBox::OutOfFlowAbsolutelyPositionedBox(
-                                out_of_flow_absolutely_positioned_box,
+                                out_of_flow_absolutely_positioned_box(contents),
                             );
                     },
+                    UpdatePoint::MulticolChild(
+                        block_level_box,
+                        propagated_text_decoration_line,
+                    ) => {
+                        *block_level_box.borrow_mut() =
+                            BlockLevelBox::Independent(IndependentFormattingContext::construct(
+                                context,
+                                &info,
+                                display_inside,
+                                contents,
+                                propagated_text_decoration_line,
+                            ));
+                    },
                 }
                 return true;
             }
@@ -15,6 +15,7 @@ use crate::dom_traversal::{Contents, NodeAndStyleInfo};
 use crate::flexbox::FlexContainer;
 use crate::flow::BlockFormattingContext;
 use crate::fragment_tree::{BaseFragmentInfo, Fragment, FragmentFlags};
+use crate::multicol::MulticolContainer;
 use crate::positioned::PositioningContext;
 use crate::replaced::ReplacedContent;
 use crate::sizing::{self, ContentSizes};
@@ -54,6 +55,7 @@ pub(crate) enum NonReplacedFormattingContextContents {
     Flow(BlockFormattingContext),
     Flex(FlexContainer),
     Table(Table),
+    Multicol(MulticolContainer),
     // Other layout modes go here
 }
 
@@ -93,6 +95,20 @@ impl IndependentFormattingContext {
         match contents {
             Contents::NonReplaced(non_replaced_contents) => {
                 let contents = match display_inside {
+                    DisplayInside::Flow { is_list_item } |
+                    DisplayInside::FlowRoot { is_list_item }
+                        if node_and_style_info.style.get_column().is_multicol() =>
+                    {
+                        NonReplacedFormattingContextContents::Multicol(
+                            MulticolContainer::construct(
+                                context,
+                                node_and_style_info,
+                                non_replaced_contents,
+                                propagated_text_decoration_line,
+                                is_list_item,
+                            ),
+                        )
+                    },
                     DisplayInside::Flow { is_list_item } |
                     DisplayInside::FlowRoot { is_list_item } => {
                         NonReplacedFormattingContextContents::Flow(
@@ -159,7 +175,7 @@ impl IndependentFormattingContext {
         match self {
             Self::NonReplaced(inner) => inner
                 .contents
-                .inline_content_sizes(layout_context, inner.style.writing_mode),
+                .inline_content_sizes(layout_context, &inner.style),
             Self::Replaced(inner) => inner.contents.inline_content_sizes(&inner.style),
         }
     }
@@ -175,9 +191,8 @@ impl IndependentFormattingContext {
                 let content_sizes = &mut non_replaced.content_sizes;
                 let contents = &mut non_replaced.contents;
                 sizing::outer_inline(style, containing_block_writing_mode, || {
-                    *content_sizes.get_or_insert_with(|| {
-                        contents.inline_content_sizes(layout_context, style.writing_mode)
-                    })
+                    *content_sizes
+                        .get_or_insert_with(|| contents.inline_content_sizes(layout_context, style))
                 })
             },
             Self::Replaced(replaced) => {
@@ -214,15 +229,20 @@ impl NonReplacedFormattingContext {
                 containing_block_for_children,
                 containing_block,
             ),
+            NonReplacedFormattingContextContents::Multicol(multicol) => multicol.layout(
+                layout_context,
+                positioning_context,
+                containing_block_for_children,
+            ),
         }
     }
 
     pub fn inline_content_sizes(&mut self, layout_context: &LayoutContext) -> ContentSizes {
-        let writing_mode = self.style.writing_mode;
+        let style = &self.style;
         let contents = &mut self.contents;
         *self
             .content_sizes
-            .get_or_insert_with(|| contents.inline_content_sizes(layout_context, writing_mode))
+            .get_or_insert_with(|| contents.inline_content_sizes(layout_context, style))
     }
 }
 
@@ -230,14 +250,16 @@ impl NonReplacedFormattingContextContents {
     pub fn inline_content_sizes(
         &mut self,
         layout_context: &LayoutContext,
-        writing_mode: WritingMode,
+        style: &ComputedValues,
     ) -> ContentSizes {
+        let writing_mode = style.writing_mode;
         match self {
             Self::Flow(inner) => inner
                 .contents
                 .inline_content_sizes(layout_context, writing_mode),
             Self::Flex(inner) => inner.inline_content_sizes(),
             Self::Table(table) => table.inline_content_sizes(layout_context, writing_mode),
+            Self::Multicol(multicol) => multicol.inline_content_sizes(layout_context, style),
         }
     }
 }
@@ -17,12 +17,13 @@ use style::Zero;
 use webrender_api::{FontInstanceKey, ImageKey};
 
 use super::{
-    BaseFragment, BoxFragment, ContainingBlockManager, HoistedSharedFragment, PositioningFragment,
-    Tag,
+    BackgroundMode, BaseFragment, BoxFragment, ContainingBlockManager, HoistedSharedFragment,
+    PositioningFragment, Tag,
 };
 use crate::cell::ArcRefCell;
 use crate::geom::{LogicalRect, LogicalSides, PhysicalRect};
 use crate::style_ext::ComputedValuesExt;
+use crate::traversal::RestyledStyles;
 
 #[derive(Serialize)]
 pub(crate) enum Fragment {
@@ -133,6 +134,49 @@ impl Fragment {
         }
     }
 
+    /// Replace the styles of this fragment and its descendants that were restyled without
+    /// needing layout. Returns false if a fragment of a restyled node has a style that was not
+    /// recorded, such as an anonymous style inheriting from the old one, in which case the
+    /// fragment tree has to be laid out again.
+    pub(crate) fn update_styles(&mut self, restyled_styles: &RestyledStyles) -> bool {
+        let tag = self.tag();
+        match self {
+            Fragment::Box(fragment) | Fragment::Float(fragment) => {
+                if let BackgroundMode::Extra(extra_backgrounds) = &fragment.background_mode {
+                    if extra_backgrounds
+                        .iter()
+                        .any(|background| restyled_styles.replaces(&background.style))
+                    {
+                        return false;
+                    }
+                }
+                update_style(&mut fragment.style, tag, restyled_styles) &&
+                    fragment
+                        .children
+                        .iter()
+                        .all(|child| child.borrow_mut().update_styles(restyled_styles))
+            },
+            Fragment::Positioning(fragment) => {
+                fragment
+                    .style
+                    .as_mut()
+                    .map_or(true, |style| update_style(style, tag, restyled_styles)) &&
+                    fragment
+                        .children
+                        .iter()
+                        .all(|child| child.borrow_mut().update_styles(restyled_styles))
+            },
+            // Hoisted fragments are also children of their containing block, which is where
+            // they are updated.
+            Fragment::AbsoluteOrFixedPositioned(_) => true,
+            Fragment::Text(fragment) => {
+                update_style(&mut fragment.parent_style, tag, restyled_styles)
+            },
+            Fragment::Image(fragment) => update_style(&mut fragment.style, tag, restyled_styles),
+            Fragment::IFrame(fragment) => update_style(&mut fragment.style, tag, restyled_styles),
+        }
+    }
+
     pub fn scrolling_area(&self, containing_block: &PhysicalRect<Au>) -> PhysicalRect<Au> {
         match self {
             Fragment::Box(fragment) | Fragment::Float(fragment) => fragment
@@ -217,6 +261,26 @@ impl Fragment {
     }
 }
 
+fn update_style(
+    style: &mut ServoArc<ComputedValues>,
+    tag: Option<Tag>,
+    restyled_styles: &RestyledStyles,
+) -> bool {
+    let Some(replacements) = tag.and_then(|tag| restyled_styles.0.get(&tag.node)) else {
+        return !restyled_styles.replaces(style);
+    };
+    match replacements
+        .iter()
+        .find(|(old_style, _)| ServoArc::ptr_eq(old_style, style))
+    {
+        Some((_, new_style)) => {
+            *style = new_style.clone();
+            true
+        },
+        None => false,
+    }
+}
+
 impl TextFragment {
     pub fn print(&self, tree: &mut PrintTree) {
         tree.add_item(format!(
@@ -17,6 +17,7 @@ use crate::cell::ArcRefCell;
 use crate::display_list::StackingContext;
 use crate::flow::CanvasBackground;
 use crate::geom::PhysicalRect;
+use crate::traversal::RestyledStyles;
 
 #[derive(Serialize)]
 pub struct FragmentTree {
@@ -60,6 +61,26 @@ impl FragmentTree {
         root_stacking_context.build_display_list(builder);
     }
 
+    /// Apply the new styles of a restyle that only needs repainting to the fragments of this
+    /// tree. Returns false if the tree could not be updated in place and has to be laid out
+    /// again, in which case some fragments may already have been updated.
+    pub fn update_styles(&self, restyled_styles: &RestyledStyles) -> bool {
+        // The canvas background may be taken from another element depending on the style of
+        // the root element, so leave any change to either of them to layout.
+        if restyled_styles
+            .0
+            .contains_key(&self.canvas_background.root_element) ||
+            restyled_styles
+                .0
+                .contains_key(&self.canvas_background.from_element)
+        {
+            return false;
+        }
+        self.root_fragments
+            .iter()
+            .all(|fragment| fragment.borrow_mut().update_styles(restyled_styles))
+    }
+
     pub fn print(&self) {
         let mut print_tree = PrintTree::new("Fragment Tree".to_string());
         for fragment in &self.root_fragments {
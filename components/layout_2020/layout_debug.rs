@@ -11,6 +11,7 @@ use std::fs;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use script_layout_interface::InvalidationTrace;
 #[cfg(not(debug_assertions))]
 use serde::ser::Serializer;
 use serde::Serialize;
@@ -147,6 +148,103 @@ pub fn end_trace(generation: u32) {
     .unwrap();
 }
 
+/// The state of layout after a reflow, as returned by `ServoTestUtils.forceLayoutWithTrace()`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutDump<'a> {
+    box_tree_update: &'static str,
+    damaged_nodes: Vec<DamagedNodeDump>,
+    box_tree: Option<&'a BoxTree>,
+    fragment_tree: Option<&'a FragmentTree>,
+}
+
+#[derive(Serialize)]
+struct DamagedNodeDump {
+    /// The address of the element, as found in the tags of the fragments generated for it.
+    node: usize,
+    depth: usize,
+    damage: Vec<&'static str>,
+}
+
+/// Serialize the box tree and the fragment tree to JSON, along with the elements whose damage
+/// the last reflow found, if it traced invalidations.
+pub fn dump_layout(
+    box_tree: Option<&BoxTree>,
+    fragment_tree: Option<&FragmentTree>,
+    invalidation_trace: Option<&InvalidationTrace>,
+) -> String {
+    let box_tree_update = invalidation_trace
+        .map(|trace| trace.box_tree_update)
+        .unwrap_or_default();
+    let dump = LayoutDump {
+        box_tree_update: box_tree_update.as_str(),
+        damaged_nodes: invalidation_trace
+            .into_iter()
+            .flat_map(|trace| &trace.damaged_nodes)
+            .map(|damaged_node| DamagedNodeDump {
+                node: damaged_node.node.0 as usize,
+                depth: damaged_node.depth,
+                damage: damaged_node.damage.clone(),
+            })
+            .collect(),
+        box_tree,
+        fragment_tree,
+    };
+    to_string(&dump).unwrap()
+}
+
+/// Compare a box tree that was updated incrementally with one constructed from scratch for the
+/// same DOM, returning a description of the first place where their structures differ.
+pub fn box_tree_divergence(updated: &BoxTree, constructed: &BoxTree) -> Option<String> {
+    first_difference(
+        &to_value(updated).unwrap(),
+        &to_value(constructed).unwrap(),
+        "boxTree".to_owned(),
+    )
+}
+
+fn first_difference(updated: &Value, constructed: &Value, path: String) -> Option<String> {
+    match (updated, constructed) {
+        (Value::Array(updated), Value::Array(constructed)) => {
+            if updated.len() != constructed.len() {
+                return Some(format!(
+                    "{path}: {} items instead of {}",
+                    updated.len(),
+                    constructed.len()
+                ));
+            }
+            updated.iter().zip(constructed).enumerate().find_map(
+                |(index, (updated, constructed))| {
+                    first_difference(updated, constructed, format!("{path}[{index}]"))
+                },
+            )
+        },
+        (Value::Object(updated), Value::Object(constructed)) => {
+            for (key, updated_value) in updated {
+                // Content sizes are cached lazily during layout, so a tree that has been laid
+                // out before has them where a fresh one does not.
+                if key == "content_sizes" {
+                    continue;
+                }
+                let Some(constructed_value) = constructed.get(key) else {
+                    return Some(format!("{path}.{key}: unexpected field"));
+                };
+                if let Some(difference) =
+                    first_difference(updated_value, constructed_value, format!("{path}.{key}"))
+                {
+                    return Some(difference);
+                }
+            }
+            constructed
+                .keys()
+                .find(|key| !updated.contains_key(*key))
+                .map(|key| format!("{path}.{key}: missing field"))
+        },
+        _ if updated == constructed => None,
+        _ => Some(format!("{path}: {updated} instead of {constructed}")),
+    }
+}
+
 #[cfg(not(debug_assertions))]
 #[derive(Clone, Debug)]
 pub struct DebugId;
@@ -17,6 +17,7 @@ pub mod geom;
 #[macro_use]
 pub mod layout_debug;
 mod lists;
+mod multicol;
 mod positioned;
 pub mod query;
 mod replaced;
@@ -0,0 +1,303 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Multi-column layout: <https://drafts.csswg.org/css-multicol/>
+//!
+//! The contents of a multi-column container are laid out as a block formatting context whose
+//! inline size is the used column width. The resulting fragments are then distributed into column
+//! boxes, only breaking between fragments: every in-flow child of the container is treated as
+//! monolithic.
+
+use app_units::Au;
+use serde::Serialize;
+use style::properties::ComputedValues;
+use style::values::computed::length::{
+    NonNegativeLengthOrAuto, NonNegativeLengthPercentageOrNormal,
+};
+use style::values::generics::column::ColumnCount;
+use style::values::specified::text::TextDecorationLine;
+use style::Zero;
+
+use crate::context::LayoutContext;
+use crate::dom::NodeExt;
+use crate::dom_traversal::{NodeAndStyleInfo, NonReplacedContents};
+use crate::flow::BlockFormattingContext;
+use crate::formatting_contexts::{Baselines, IndependentLayout};
+use crate::fragment_tree::{Fragment, PositioningFragment};
+use crate::geom::{AuOrAuto, LogicalRect, LogicalVec2};
+use crate::positioned::PositioningContext;
+use crate::sizing::ContentSizes;
+use crate::ContainingBlock;
+
+/// A block container that establishes a multi-column formatting context.
+/// <https://drafts.csswg.org/css-multicol/#multi-column-container>
+#[derive(Debug, Serialize)]
+pub(crate) struct MulticolContainer {
+    pub contents: BlockFormattingContext,
+
+    /// The text decoration lines propagated to the children of this container. These are
+    /// kept so that the box of one of the children can be rebuilt on its own.
+    #[serde(skip_serializing)]
+    pub children_text_decoration_line: TextDecorationLine,
+}
+
+/// The used column count, width and gap of a multi-column container.
+/// <https://drafts.csswg.org/css-multicol/#pseudo-algorithm>
+struct ColumnGeometry {
+    count: i32,
+    width: Au,
+    gap: Au,
+}
+
+impl ColumnGeometry {
+    fn new(style: &ComputedValues, available_inline_size: Au) -> Self {
+        let gap = match &style.get_position().column_gap {
+            NonNegativeLengthPercentageOrNormal::LengthPercentage(length_percentage) => {
+                length_percentage.0.to_used_value(available_inline_size)
+            },
+            NonNegativeLengthPercentageOrNormal::Normal => {
+                Au::from(style.get_font().font_size.computed_size())
+            },
+        };
+
+        let column_style = style.get_column();
+        let count = match column_style.column_width {
+            NonNegativeLengthOrAuto::LengthPercentage(column_width) => {
+                let pitch = (Au::from(column_width) + gap).max(Au(1));
+                let count = ((available_inline_size + gap).0 / pitch.0).max(1);
+                match column_style.column_count {
+                    ColumnCount::Integer(column_count) => count.min(column_count.0),
+                    ColumnCount::Auto => count,
+                }
+            },
+            NonNegativeLengthOrAuto::Auto => match column_style.column_count {
+                ColumnCount::Integer(column_count) => column_count.0,
+                ColumnCount::Auto => 1,
+            },
+        };
+        let width = ((available_inline_size + gap) / count - gap).max(Au::zero());
+        Self { count, width, gap }
+    }
+
+    fn pitch(&self) -> Au {
+        self.width + self.gap
+    }
+}
+
+/// The block range taken by a fragment of the contents, in the coordinates of the block
+/// formatting context they were laid out in.
+fn block_range(fragment: &Fragment) -> Option<(Au, Au)> {
+    let rect = match fragment {
+        Fragment::Box(fragment) | Fragment::Float(fragment) => {
+            fragment.border_rect().inflate(&fragment.margin)
+        },
+        Fragment::Positioning(fragment) => fragment.rect,
+        Fragment::Text(fragment) => fragment.rect,
+        Fragment::Image(fragment) => fragment.rect,
+        Fragment::IFrame(fragment) => fragment.rect,
+        Fragment::AbsoluteOrFixedPositioned(_) => return None,
+    };
+    Some((rect.start_corner.block, rect.max_block_position()))
+}
+
+/// Distribute the given block ranges into columns of the given block size, returning the block
+/// offset at which each column starts and the smallest increase of the column block size that
+/// would have avoided one of the breaks.
+fn fill_columns(ranges: &[(Au, Au)], column_block_size: Au) -> (Vec<Au>, Option<Au>) {
+    let mut column_starts = vec![Au::zero()];
+    let mut smallest_stretch: Option<Au> = None;
+    for &(start, end) in ranges {
+        let column_start = *column_starts.last().unwrap();
+        let overflow = end - column_start - column_block_size;
+        // A column always takes at least one fragment, even if it does not fit.
+        if overflow > Au::zero() && start > column_start {
+            smallest_stretch = Some(smallest_stretch.map_or(overflow, |s| s.min(overflow)));
+            column_starts.push(start);
+        }
+    }
+    (column_starts, smallest_stretch)
+}
+
+/// Find the smallest column block size that fits the given block ranges into `column_count`
+/// columns, only breaking between ranges.
+/// <https://drafts.csswg.org/css-multicol/#balancing>
+fn balanced_column_block_size(ranges: &[(Au, Au)], column_count: i32) -> Au {
+    let content_block_size = ranges.iter().map(|&(_, end)| end).max().unwrap_or_default();
+    let tallest_range = ranges
+        .iter()
+        .map(|&(start, end)| end - start)
+        .max()
+        .unwrap_or_default();
+    let mut column_block_size = (content_block_size / column_count).max(tallest_range);
+    loop {
+        match fill_columns(ranges, column_block_size) {
+            (column_starts, Some(stretch)) if column_starts.len() > column_count as usize => {
+                column_block_size += stretch
+            },
+            _ => return column_block_size,
+        }
+    }
+}
+
+impl MulticolContainer {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<impl NodeExt<'dom>>,
+        contents: NonReplacedContents,
+        propagated_text_decoration_line: TextDecorationLine,
+        is_list_item: bool,
+    ) -> Self {
+        Self {
+            contents: BlockFormattingContext::construct(
+                context,
+                info,
+                contents,
+                propagated_text_decoration_line,
+                is_list_item,
+            ),
+            children_text_decoration_line: propagated_text_decoration_line |
+                info.style.clone_text_decoration_line(),
+        }
+    }
+
+    pub fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        style: &ComputedValues,
+    ) -> ContentSizes {
+        let column_sizes = self
+            .contents
+            .contents
+            .inline_content_sizes(layout_context, style.writing_mode);
+        let column_style = style.get_column();
+        let column_width = match column_style.column_width {
+            NonNegativeLengthOrAuto::LengthPercentage(column_width) => Au::from(column_width),
+            NonNegativeLengthOrAuto::Auto => Au::zero(),
+        };
+        let min_content = column_sizes.min_content.max(column_width);
+        let max_content = match column_style.column_count {
+            ColumnCount::Integer(column_count) => {
+                let geometry = ColumnGeometry::new(style, Au::zero());
+                (column_sizes.max_content.max(column_width) + geometry.gap) * column_count.0 -
+                    geometry.gap
+            },
+            ColumnCount::Auto => column_sizes.max_content.max(column_width),
+        };
+        ContentSizes {
+            min_content,
+            max_content: max_content.max(min_content),
+        }
+    }
+
+    pub fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+    ) -> IndependentLayout {
+        let style = containing_block.style;
+        let geometry = ColumnGeometry::new(style, containing_block.inline_size);
+        let containing_block_for_columns = ContainingBlock {
+            inline_size: geometry.width,
+            block_size: AuOrAuto::auto(),
+            style,
+        };
+        let layout = self.contents.layout(
+            layout_context,
+            positioning_context,
+            &containing_block_for_columns,
+        );
+
+        let ranges: Vec<_> = layout.fragments.iter().filter_map(block_range).collect();
+        // With a definite block size, columns are filled sequentially and the ones that do not
+        // fit overflow in the inline direction. Otherwise the content is balanced.
+        // TODO: Support `column-fill` and `max-block-size`.
+        let column_block_size = match containing_block.block_size {
+            AuOrAuto::LengthPercentage(block_size) => block_size,
+            AuOrAuto::Auto => balanced_column_block_size(&ranges, geometry.count),
+        };
+        let (column_starts, _) = fill_columns(&ranges, column_block_size);
+
+        // Move each fragment into its column box. Fragments that do not take any space stay in
+        // the column that their preceding fragment went to.
+        // TODO: The static positions of absolutely positioned descendants are not adjusted for
+        // the column they end up in.
+        let mut columns: Vec<Vec<Fragment>> = column_starts.iter().map(|_| Vec::new()).collect();
+        let mut column_index = 0;
+        for mut fragment in layout.fragments {
+            if let Some((start, _)) = block_range(&fragment) {
+                while column_index + 1 < column_starts.len() &&
+                    start >= column_starts[column_index + 1]
+                {
+                    column_index += 1;
+                }
+            }
+            offset_block_position(&mut fragment, -column_starts[column_index]);
+            columns[column_index].push(fragment);
+        }
+
+        let mut used_block_size = Au::zero();
+        let column_count = columns.len();
+        let fragments = columns
+            .into_iter()
+            .enumerate()
+            .map(|(index, children)| {
+                let block_size = children
+                    .iter()
+                    .filter_map(block_range)
+                    .map(|(_, end)| end)
+                    .max()
+                    .unwrap_or_default();
+                used_block_size = used_block_size.max(block_size);
+                let rect = LogicalRect {
+                    start_corner: LogicalVec2 {
+                        inline: geometry.pitch() * index as i32,
+                        block: Au::zero(),
+                    },
+                    size: LogicalVec2 {
+                        inline: geometry.width,
+                        block: column_block_size,
+                    },
+                };
+                Fragment::Positioning(PositioningFragment::new_anonymous(
+                    rect,
+                    children,
+                    style.writing_mode,
+                ))
+            })
+            .collect();
+
+        let last_column_start = column_starts[column_count - 1];
+        IndependentLayout {
+            fragments,
+            content_block_size: match containing_block.block_size {
+                AuOrAuto::LengthPercentage(block_size) => block_size,
+                AuOrAuto::Auto => used_block_size,
+            },
+            content_inline_size_for_table: None,
+            baselines: Baselines {
+                first: layout.baselines.first,
+                last: layout
+                    .baselines
+                    .last
+                    .map(|baseline| baseline - last_column_start),
+            },
+        }
+    }
+}
+
+fn offset_block_position(fragment: &mut Fragment, offset: Au) {
+    if offset.is_zero() {
+        return;
+    }
+    let rect = match fragment {
+        Fragment::Box(fragment) | Fragment::Float(fragment) => &mut fragment.content_rect,
+        Fragment::Positioning(fragment) => &mut fragment.rect,
+        Fragment::Text(fragment) => &mut fragment.rect,
+        Fragment::Image(fragment) => &mut fragment.rect,
+        Fragment::IFrame(fragment) => &mut fragment.rect,
+        Fragment::AbsoluteOrFixedPositioned(_) => return,
+    };
+    rect.start_corner.block += offset;
+}
@@ -2,28 +2,312 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use bitflags::bitflags;
+use fxhash::FxHashMap;
+use parking_lot::Mutex;
 use script_layout_interface::wrapper_traits::LayoutNode;
+use script_layout_interface::{DamagedNode, RestyleStatistics};
+use servo_arc::Arc as ServoArc;
+use style::computed_values::position::T as ComputedPosition;
 use style::context::{SharedStyleContext, StyleContext};
-use style::data::ElementData;
-use style::dom::{NodeInfo, TElement, TNode};
+use style::data::{ElementData, ElementStyles};
+use style::dom::{NodeInfo, OpaqueNode, TElement, TNode};
+use style::properties::ComputedValues;
+use style::selector_parser::{PseudoElement, RestyleDamage};
 use style::traversal::{recalc_style_at, DomTraversal, PerLevelTraversalData};
+use style::values::specified::box_::DisplayOutside;
 
 use crate::context::LayoutContext;
 use crate::dom::DOMLayoutData;
 
+bitflags! {
+    /// The layout work needed to reflect the style changes found by a restyle, derived from the
+    /// [`RestyleDamage`] that the style system computes by comparing old and new styles.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub struct LayoutDamage: u8 {
+        /// Properties that only affect painting changed, e.g. `color` or `visibility`. This is
+        /// the only kind of damage that does not require laying out the fragment tree again.
+        const REPAINT_ONLY = 0b00001;
+        /// Overflow areas need to be recomputed.
+        const RECOMPUTE_OVERFLOW = 0b00010;
+        /// Positioned boxes moved without changing size.
+        const REPOSITION = 0b00100;
+        /// Boxes need to be laid out again.
+        const RELAYOUT = 0b01000;
+        /// Boxes need to be rebuilt from the DOM.
+        const REBUILD_BOX = 0b10000;
+        /// Only the transform or opacity of a box that already had one changed, which the
+        /// compositor can apply to the current display list if it bound them to properties.
+        const COMPOSITE = 0b100000;
+    }
+}
+
+impl From<RestyleDamage> for LayoutDamage {
+    fn from(damage: RestyleDamage) -> Self {
+        let mut layout_damage = LayoutDamage::empty();
+        layout_damage.set(
+            LayoutDamage::REPAINT_ONLY,
+            damage.contains(RestyleDamage::REPAINT),
+        );
+        layout_damage.set(
+            LayoutDamage::RECOMPUTE_OVERFLOW,
+            damage.contains(RestyleDamage::STORE_OVERFLOW),
+        );
+        layout_damage.set(
+            LayoutDamage::REPOSITION,
+            damage.contains(RestyleDamage::REPOSITION),
+        );
+        layout_damage.set(
+            LayoutDamage::RELAYOUT,
+            damage.intersects(
+                RestyleDamage::BUBBLE_ISIZES |
+                    RestyleDamage::REFLOW_OUT_OF_FLOW |
+                    RestyleDamage::REFLOW,
+            ),
+        );
+        layout_damage.set(
+            LayoutDamage::REBUILD_BOX,
+            damage.intersects(
+                RestyleDamage::RESOLVE_GENERATED_CONTENT | RestyleDamage::RECONSTRUCT_FLOW,
+            ),
+        );
+        layout_damage
+    }
+}
+
+impl LayoutDamage {
+    /// The layout damage of an element restyled from `old_styles` to `new_styles`.
+    fn for_restyle(
+        damage: RestyleDamage,
+        old_styles: &ElementStyles,
+        new_styles: &ElementStyles,
+    ) -> Self {
+        let old_style = old_styles.primary.as_deref();
+        let new_style = new_styles.primary();
+        if old_style.map_or(false, |old_style| {
+            only_composited_properties_differ(old_style, new_style)
+        }) && pseudo_styles_are_unchanged(old_styles, new_styles)
+        {
+            return LayoutDamage::COMPOSITE;
+        }
+
+        let layout_damage = LayoutDamage::from(damage);
+        if layout_damage - (LayoutDamage::REPAINT_ONLY | LayoutDamage::RECOMPUTE_OVERFLOW) ==
+            LayoutDamage::REPOSITION
+        {
+            // The insets of a block-level sticky box don't move its fragment: they are only
+            // resolved into the constraints of its sticky frame while building the display
+            // list, so changing them, e.g. from script while scrolling, only needs a repaint.
+            let is_block_level_sticky = |style: &ComputedValues| {
+                style.get_box().position == ComputedPosition::Sticky &&
+                    style.get_box().display.outside() == DisplayOutside::Block
+            };
+            if old_style.map_or(false, is_block_level_sticky) && is_block_level_sticky(new_style) {
+                return LayoutDamage::REPAINT_ONLY;
+            }
+        }
+        layout_damage
+    }
+
+    /// Whether the fragment tree can be kept as is, with only its styles updated.
+    pub fn is_repaint_only(&self) -> bool {
+        !self.is_empty() && (LayoutDamage::REPAINT_ONLY | LayoutDamage::COMPOSITE).contains(*self)
+    }
+
+    /// Whether the display list can be kept as is, with only the values of the properties it
+    /// bound updated.
+    pub fn is_composite_only(&self) -> bool {
+        *self == LayoutDamage::COMPOSITE
+    }
+}
+
+/// Whether `new_style` differs from `old_style` in nothing but a transform that was and still is
+/// not `none`, or an opacity that was and still is not 1. Both create a stacking context and do
+/// not affect layout, so neither the fragment tree nor the structure of the display list
+/// changes.
+fn only_composited_properties_differ(
+    old_style: &ComputedValues,
+    new_style: &ComputedValues,
+) -> bool {
+    let (old_box, new_box) = (old_style.get_box(), new_style.get_box());
+    let (old_effects, new_effects) = (old_style.get_effects(), new_style.get_effects());
+    let transform_may_differ = !old_box.transform.0.is_empty() && !new_box.transform.0.is_empty();
+    let opacity_may_differ = old_effects.opacity != 1.0 && new_effects.opacity != 1.0;
+    if !transform_may_differ && !opacity_may_differ {
+        return false;
+    }
+
+    let mut old_box_with_new_transform = old_box.clone();
+    if transform_may_differ {
+        old_box_with_new_transform.transform = new_box.transform.clone();
+    }
+    let mut old_effects_with_new_opacity = old_effects.clone();
+    if opacity_may_differ {
+        old_effects_with_new_opacity.opacity = new_effects.opacity;
+    }
+    old_box_with_new_transform == *new_box &&
+        old_effects_with_new_opacity == *new_effects &&
+        other_style_structs_are_equal(old_style, new_style)
+}
+
+/// Whether all the style structs of `old_style` and `new_style` but `Box` and `Effects` are equal.
+/// This has to list every style struct of the Servo engine, as a difference in one that is left
+/// out would not be damaged.
+fn other_style_structs_are_equal(old_style: &ComputedValues, new_style: &ComputedValues) -> bool {
+    old_style.get_background() == new_style.get_background() &&
+        old_style.get_border() == new_style.get_border() &&
+        old_style.get_column() == new_style.get_column() &&
+        old_style.get_counters() == new_style.get_counters() &&
+        old_style.get_font() == new_style.get_font() &&
+        old_style.get_inherited_box() == new_style.get_inherited_box() &&
+        old_style.get_inherited_svg() == new_style.get_inherited_svg() &&
+        old_style.get_inherited_table() == new_style.get_inherited_table() &&
+        old_style.get_inherited_text() == new_style.get_inherited_text() &&
+        old_style.get_inherited_ui() == new_style.get_inherited_ui() &&
+        old_style.get_list() == new_style.get_list() &&
+        old_style.get_margin() == new_style.get_margin() &&
+        old_style.get_outline() == new_style.get_outline() &&
+        old_style.get_padding() == new_style.get_padding() &&
+        old_style.get_position() == new_style.get_position() &&
+        old_style.get_svg() == new_style.get_svg() &&
+        old_style.get_table() == new_style.get_table() &&
+        old_style.get_text() == new_style.get_text() &&
+        old_style.get_ui() == new_style.get_ui()
+}
+
+/// Whether the `::before` and `::after` styles of an element did not change in any way that
+/// matters to layout or painting. Their fragments never have their properties bound.
+fn pseudo_styles_are_unchanged(old_styles: &ElementStyles, new_styles: &ElementStyles) -> bool {
+    [PseudoElement::Before, PseudoElement::After]
+        .iter()
+        .all(|pseudo| {
+            match (
+                old_styles.pseudos.get(pseudo),
+                new_styles.pseudos.get(pseudo),
+            ) {
+                (Some(old), Some(new)) => {
+                    ServoArc::ptr_eq(old, new) ||
+                        (old.get_box() == new.get_box() &&
+                            old.get_effects() == new.get_effects() &&
+                            other_style_structs_are_equal(old, new))
+                },
+                (None, None) => true,
+                _ => false,
+            }
+        })
+}
+
+/// The styles replaced by the elements whose restyle only needs repainting, keyed by the node
+/// whose fragments may refer to them. Text nodes map to the primary style of their parent, as
+/// that is the style their fragments are painted with.
+#[derive(Default)]
+pub struct RestyledStyles(
+    pub(crate) FxHashMap<OpaqueNode, Vec<(ServoArc<ComputedValues>, ServoArc<ComputedValues>)>>,
+);
+
+impl RestyledStyles {
+    /// Whether the given style is one of the old styles that were replaced.
+    pub(crate) fn replaces(&self, style: &ServoArc<ComputedValues>) -> bool {
+        self.0
+            .values()
+            .flatten()
+            .any(|(old_style, _)| ServoArc::ptr_eq(old_style, style))
+    }
+}
+
 pub struct RecalcStyle<'a> {
     context: LayoutContext<'a>,
+    elements_traversed: AtomicUsize,
+    elements_damaged: AtomicUsize,
+    elements_rebuilt: AtomicUsize,
+    layout_damage: AtomicU8,
+    restyled_styles: Mutex<RestyledStyles>,
+    /// The elements whose restyle only needs compositing.
+    composited_nodes: Mutex<Vec<OpaqueNode>>,
+    /// The damaged elements, if invalidations are being traced.
+    damaged_nodes: Option<Mutex<Vec<DamagedNode>>>,
 }
 
 impl<'a> RecalcStyle<'a> {
-    pub fn new(context: LayoutContext<'a>) -> Self {
-        RecalcStyle { context }
+    pub fn new(context: LayoutContext<'a>, trace_invalidations: bool) -> Self {
+        RecalcStyle {
+            context,
+            elements_traversed: AtomicUsize::new(0),
+            elements_damaged: AtomicUsize::new(0),
+            elements_rebuilt: AtomicUsize::new(0),
+            layout_damage: AtomicU8::new(0),
+            restyled_styles: Default::default(),
+            composited_nodes: Default::default(),
+            damaged_nodes: trace_invalidations.then(Default::default),
+        }
     }
 
     pub fn context(&self) -> &LayoutContext<'a> {
         &self.context
     }
 
+    pub fn statistics(&self) -> RestyleStatistics {
+        RestyleStatistics {
+            elements_traversed: self.elements_traversed.load(Ordering::Relaxed),
+            elements_damaged: self.elements_damaged.load(Ordering::Relaxed),
+            elements_rebuilt: self.elements_rebuilt.load(Ordering::Relaxed),
+            box_tree_update_skipped: false,
+            layout_skipped_for_repaint: false,
+        }
+    }
+
+    /// The union of the layout damage of all the elements restyled by this traversal.
+    pub fn layout_damage(&self) -> LayoutDamage {
+        LayoutDamage::from_bits_truncate(self.layout_damage.load(Ordering::Relaxed))
+    }
+
+    /// The styles replaced by elements whose restyle only needs repainting.
+    pub fn take_restyled_styles(&self) -> RestyledStyles {
+        std::mem::take(&mut *self.restyled_styles.lock())
+    }
+
+    /// The elements whose restyle only changed a transform or opacity, see
+    /// [`LayoutDamage::COMPOSITE`].
+    pub fn take_composited_nodes(&self) -> Vec<OpaqueNode> {
+        std::mem::take(&mut *self.composited_nodes.lock())
+    }
+
+    /// The elements damaged by this traversal, or `None` if invalidations were not traced.
+    pub fn take_damaged_nodes(&self) -> Option<Vec<DamagedNode>> {
+        self.damaged_nodes.as_ref().map(|damaged_nodes| {
+            let mut damaged_nodes = std::mem::take(&mut *damaged_nodes.lock());
+            // The traversal can visit elements in parallel, which makes the order in which they
+            // were pushed vary from one reflow to the next.
+            damaged_nodes
+                .sort_by_key(|damaged_node| (damaged_node.depth, damaged_node.node.0 as usize));
+            damaged_nodes
+        })
+    }
+
+    fn note_restyled_styles<N: TNode>(&self, node: N, old: &ElementStyles, new: &ElementStyles) {
+        let (Some(old_primary), Some(new_primary)) = (old.primary.as_ref(), new.primary.as_ref())
+        else {
+            return;
+        };
+        let mut replacements = vec![(old_primary.clone(), new_primary.clone())];
+        for pseudo in [PseudoElement::Before, PseudoElement::After] {
+            if let (Some(old), Some(new)) = (old.pseudos.get(&pseudo), new.pseudos.get(&pseudo)) {
+                replacements.push((old.clone(), new.clone()));
+            }
+        }
+
+        let mut restyled_styles = self.restyled_styles.lock();
+        for child in node.dom_children().filter(|child| child.is_text_node()) {
+            restyled_styles.0.insert(
+                child.opaque(),
+                vec![(old_primary.clone(), new_primary.clone())],
+            );
+        }
+        restyled_styles.0.insert(node.opaque(), replacements);
+    }
+
     pub fn destroy(self) -> LayoutContext<'a> {
         self.context
     }
@@ -49,8 +333,36 @@ where
             if !node.is_text_node() {
                 let el = node.as_element().unwrap();
                 let mut data = el.mutate_data().unwrap();
+                let old_styles = data.styles.clone();
                 recalc_style_at(self, traversal_data, context, el, &mut data, note_child);
                 el.unset_dirty_descendants();
+
+                // The damage of an element is only consulted while its children are noted
+                // above, so reset it to make the next traversal only see its own changes.
+                self.elements_traversed.fetch_add(1, Ordering::Relaxed);
+                if !data.damage.is_empty() {
+                    self.elements_damaged.fetch_add(1, Ordering::Relaxed);
+                    let damage = LayoutDamage::for_restyle(data.damage, &old_styles, &data.styles);
+                    if damage.contains(LayoutDamage::REBUILD_BOX) {
+                        self.elements_rebuilt.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.layout_damage
+                        .fetch_or(damage.bits(), Ordering::Relaxed);
+                    if damage.is_repaint_only() {
+                        self.note_restyled_styles(node, &old_styles, &data.styles);
+                    }
+                    if damage.is_composite_only() {
+                        self.composited_nodes.lock().push(node.opaque());
+                    }
+                    if let Some(damaged_nodes) = &self.damaged_nodes {
+                        damaged_nodes.lock().push(DamagedNode {
+                            node: node.opaque().into(),
+                            damage: damage.iter_names().map(|(name, _)| name).collect(),
+                            depth: traversal_data.current_dom_depth,
+                        });
+                    }
+                }
+                data.damage = RestyleDamage::empty();
             }
         }
     }
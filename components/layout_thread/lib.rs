@@ -152,6 +152,10 @@ pub struct LayoutThread {
     /// constraints.
     viewport_size: UntypedSize2D<Au>,
 
+    /// The media type used to evaluate media queries. This is `screen` unless it has been
+    /// overridden, for instance by the devtools print simulation.
+    media_type: MediaType,
+
     /// The root stacking context.
     display_list: RefCell<Option<DisplayList>>,
 
@@ -485,6 +489,10 @@ impl Layout for LayoutThread {
         self.stylist.set_quirks_mode(quirks_mode);
     }
 
+    fn set_media_type(&mut self, media_type: MediaType) {
+        self.media_type = media_type;
+    }
+
     fn register_paint_worklet_modules(
         &mut self,
         name: Atom,
@@ -608,6 +616,7 @@ impl LayoutThread {
                 Au::from_f32_px(window_size.initial_viewport.width),
                 Au::from_f32_px(window_size.initial_viewport.height),
             ),
+            media_type: MediaType::screen(),
             webrender_api,
             stylist: Stylist::new(device, QuirksMode::NoQuirks),
             display_list: Default::default(),
@@ -1391,20 +1400,22 @@ impl LayoutThread {
         window_size_data: WindowSizeData,
         guards: &StylesheetGuards,
     ) -> bool {
-        // If the viewport size and device pixel ratio has not changed, do not make any changes.
+        // If the viewport size, device pixel ratio and media type have not changed, do not make
+        // any changes.
         let au_viewport_size = Size2D::new(
             Au::from_f32_px(window_size_data.initial_viewport.width),
             Au::from_f32_px(window_size_data.initial_viewport.height),
         );
 
         if self.stylist.device().au_viewport_size() == au_viewport_size &&
-            self.stylist.device().device_pixel_ratio() == window_size_data.device_pixel_ratio
+            self.stylist.device().device_pixel_ratio() == window_size_data.device_pixel_ratio &&
+            self.stylist.device().media_type() == self.media_type
         {
             return false;
         }
 
         let device = Device::new(
-            MediaType::screen(),
+            self.media_type.clone(),
             self.stylist.quirks_mode(),
             window_size_data.initial_viewport,
             window_size_data.device_pixel_ratio,
@@ -14,6 +14,7 @@ use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
 use std::process;
 use std::sync::Arc;
+use std::time::Instant;
 
 use app_units::Au;
 use base::id::{BrowsingContextId, PipelineId};
@@ -27,7 +28,7 @@ use fonts_traits::WebFontLoadFinishedCallback;
 use fxhash::FxHashMap;
 use ipc_channel::ipc::IpcSender;
 use layout::context::LayoutContext;
-use layout::display_list::{DisplayList, WebRenderImageInfo};
+use layout::display_list::{AnimatedPropertyBindings, DisplayList, WebRenderImageInfo};
 use layout::query::{
     process_content_box_request, process_content_boxes_request, process_element_inner_text_query,
     process_node_geometry_request, process_node_scroll_area_request, process_offset_parent_query,
@@ -36,7 +37,7 @@ use layout::query::{
 use layout::traversal::RecalcStyle;
 use layout::{layout_debug, BoxTree, FragmentTree};
 use lazy_static::lazy_static;
-use log::{debug, error, warn};
+use log::{debug, error, trace, warn};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use metrics::{PaintTimeMetrics, ProfilerMetadataFactory};
 use net_traits::image_cache::{ImageCache, UsePlaceholder};
@@ -49,8 +50,9 @@ use profile_traits::time::{
 };
 use script::layout_dom::{ServoLayoutDocument, ServoLayoutElement, ServoLayoutNode};
 use script_layout_interface::{
-    Layout, LayoutConfig, LayoutFactory, NodesFromPointQueryType, OffsetParentResponse,
-    ReflowComplete, ReflowGoal, ScriptReflow, TrustedNodeAddress,
+    BoxTreeUpdate, InvalidationTrace, Layout, LayoutConfig, LayoutFactory, NodesFromPointQueryType,
+    OffsetParentResponse, PendingRestyle, ReflowComplete, ReflowGoal, ReflowPhaseDurations,
+    RestyleStatistics, ScriptReflow, TrustedNodeAddress,
 };
 use script_traits::{
     ConstellationControlMsg, DrawAPaintImageResult, IFrameSizeMsg, LayoutMsg as ConstellationMsg,
@@ -59,6 +61,7 @@ use script_traits::{
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_config::opts::{self, DebugOptions};
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use style::animation::DocumentAnimationSet;
 use style::context::{
@@ -132,6 +135,18 @@ pub struct LayoutThread {
     /// The fragment tree.
     fragment_tree: RefCell<Option<Arc<FragmentTree>>>,
 
+    /// Whether the styles of the fragment tree were updated in place after a restyle that only
+    /// needed repainting, leaving the box tree with styles that are out of date.
+    box_tree_styles_stale: Cell<bool>,
+
+    /// Whether the last display list sent to WebRender was built from the current fragment tree,
+    /// so that a reflow which only moves a scroll node does not need to build it again.
+    display_list_is_current: Cell<bool>,
+
+    /// The transforms and opacities that the last display list sent to WebRender bound to
+    /// properties, which can be updated without building a new display list.
+    animated_property_bindings: RefCell<AnimatedPropertyBindings>,
+
     /// A counter for epoch messages
     epoch: Cell<Epoch>,
 
@@ -139,6 +154,10 @@ pub struct LayoutThread {
     /// constraints.
     viewport_size: UntypedSize2D<Au>,
 
+    /// The media type used to evaluate media queries. This is `screen` unless it has been
+    /// overridden, for instance by the devtools print simulation.
+    media_type: MediaType,
+
     /// Scroll offsets of nodes that scroll.
     scroll_offsets: RefCell<HashMap<ExternalScrollId, Vector2D<f32, LayoutPixel>>>,
 
@@ -446,6 +465,10 @@ impl Layout for LayoutThread {
         self.stylist.set_quirks_mode(quirks_mode);
     }
 
+    fn set_media_type(&mut self, media_type: MediaType) {
+        self.media_type = media_type;
+    }
+
     fn reflow(&mut self, script_reflow: ScriptReflow) {
         let mut result = ScriptReflowResult::new(script_reflow);
         profile(
@@ -519,12 +542,16 @@ impl LayoutThread {
             generation: Cell::new(0),
             box_tree: Default::default(),
             fragment_tree: Default::default(),
+            box_tree_styles_stale: Cell::new(false),
+            display_list_is_current: Cell::new(false),
+            animated_property_bindings: RefCell::new(AnimatedPropertyBindings::new(id.into())),
             // Epoch starts at 1 because of the initial display list for epoch 0 that we send to WR
             epoch: Cell::new(Epoch(1)),
             viewport_size: Size2D::new(
                 Au::from_f32_px(window_size.initial_viewport.width),
                 Au::from_f32_px(window_size.initial_viewport.height),
             ),
+            media_type: MediaType::screen(),
             webrender_api: webrender_api_sender,
             scroll_offsets: Default::default(),
             stylist: Stylist::new(device, QuirksMode::NoQuirks),
@@ -687,6 +714,14 @@ impl LayoutThread {
         let restyles = std::mem::take(&mut data.pending_restyles);
         debug!("Draining restyles: {}", restyles.len());
 
+        let layout_may_be_skipped =
+            !self.first_reflow.get() && !viewport_size_changed && !data.stylesheets_changed;
+        let only_selector_restyles = layout_may_be_skipped &&
+            !restyles.is_empty() &&
+            restyles
+                .iter()
+                .all(|(_, restyle)| restyle_only_affects_selectors(restyle));
+
         let mut map = SnapshotMap::new();
         let elements_with_snapshot: Vec<_> = restyles
             .iter()
@@ -741,29 +776,113 @@ impl LayoutThread {
                 .unwrap()
         };
 
-        let traversal = RecalcStyle::new(layout_context);
+        let traversal = RecalcStyle::new(
+            layout_context,
+            data.trace_invalidations ||
+                log::log_enabled!(target: INVALIDATION_LOG_TARGET, log::Level::Trace),
+        );
+        let traversal_root: UntrustedNodeAddress = dirty_root.as_node().opaque().into();
         let token = {
             let shared = DomTraversal::<ServoLayoutElement>::shared_context(&traversal);
             RecalcStyle::pre_traverse(dirty_root, shared)
         };
 
-        if token.should_traverse() {
+        let mut restyle_statistics = RestyleStatistics::default();
+        let mut phase_durations = ReflowPhaseDurations::default();
+        let restyled = token.should_traverse();
+        let mut animated_property_values = None;
+        let traversed_root = if restyled {
+            let start = Instant::now();
             let dirty_root: ServoLayoutNode =
                 driver::traverse_dom(&traversal, token, rayon_pool).as_node();
+            phase_durations.style = start.elapsed();
+            restyle_statistics = traversal.statistics();
+            debug!("Restyle statistics: {:?}", restyle_statistics);
+
+            // When the restyle only came from attribute or state changes that no selector turned
+            // into a style change, the box tree and fragment tree of the last reflow are still up
+            // to date.
+            restyle_statistics.box_tree_update_skipped = only_selector_restyles &&
+                restyle_statistics.elements_damaged == 0 &&
+                self.fragment_tree.borrow().is_some();
+
+            // When every damaged element only changed properties that affect painting, the
+            // geometry of the fragment tree still holds and only its styles need replacing.
+            if !restyle_statistics.box_tree_update_skipped &&
+                layout_may_be_skipped &&
+                traversal.layout_damage().is_repaint_only()
+            {
+                let start = Instant::now();
+                let restyled_styles = traversal.take_restyled_styles();
+                if let Some(fragment_tree) = &*self.fragment_tree.borrow() {
+                    if fragment_tree.update_styles(&restyled_styles) {
+                        restyle_statistics.box_tree_update_skipped = true;
+                        restyle_statistics.layout_skipped_for_repaint = true;
+                        self.box_tree_styles_stale.set(true);
+
+                        // When only transforms or opacities bound by the current display list
+                        // changed, the compositor can apply their new values to it.
+                        if traversal.layout_damage().is_composite_only() &&
+                            self.display_list_is_current.get() &&
+                            data.reflow_goal.needs_display()
+                        {
+                            animated_property_values = self
+                                .animated_property_bindings
+                                .borrow()
+                                .values(fragment_tree, &traversal.take_composited_nodes());
+                        }
+                    }
+                }
+                phase_durations.layout = start.elapsed();
+            }
+            Some(dirty_root).filter(|_| !restyle_statistics.box_tree_update_skipped)
+        } else {
+            None
+        };
 
+        let mut box_tree_update = BoxTreeUpdate::Skipped;
+        if let Some(dirty_root) = traversed_root {
             let root_node = root_element.as_node();
             let mut box_tree = self.box_tree.borrow_mut();
             let box_tree = &mut *box_tree;
+            // The box tree can only be updated incrementally if none of its styles are stale.
+            let rebuild = self.box_tree_styles_stale.replace(false);
             let mut build_box_tree = || {
-                if !BoxTree::update(traversal.context(), dirty_root) {
+                if rebuild || !BoxTree::update(traversal.context(), dirty_root) {
                     *box_tree = Some(Arc::new(BoxTree::construct(traversal.context(), root_node)));
+                    BoxTreeUpdate::Rebuilt
+                } else {
+                    BoxTreeUpdate::Incremental
                 }
             };
-            if let Some(pool) = rayon_pool {
+            let start = Instant::now();
+            box_tree_update = if let Some(pool) = rayon_pool {
                 pool.install(build_box_tree)
             } else {
                 build_box_tree()
             };
+            phase_durations.box_tree = start.elapsed();
+
+            if box_tree_update == BoxTreeUpdate::Incremental &&
+                pref!(layout.debug.verify_incremental_box_tree.enabled)
+            {
+                let construct = || BoxTree::construct(traversal.context(), root_node);
+                let constructed = if let Some(pool) = rayon_pool {
+                    pool.install(construct)
+                } else {
+                    construct()
+                };
+                if let Some(difference) =
+                    layout_debug::box_tree_divergence(box_tree.as_ref().unwrap(), &constructed)
+                {
+                    panic!(
+                        "Incrementally updated box tree diverged from a fresh one: {difference}"
+                    );
+                }
+                // Construction bound the layout data of every node to the boxes of the new tree,
+                // so it has to become the live one.
+                *box_tree = Some(Arc::new(constructed));
+            }
 
             let viewport_size = Size2D::new(
                 self.viewport_size.width.to_f32_px(),
@@ -775,14 +894,29 @@ impl LayoutThread {
                     .unwrap()
                     .layout(traversal.context(), viewport_size)
             };
+            let start = Instant::now();
             let fragment_tree = Arc::new(if let Some(pool) = rayon_pool {
                 pool.install(run_layout)
             } else {
                 run_layout()
             });
+            phase_durations.layout = start.elapsed();
             *self.fragment_tree.borrow_mut() = Some(fragment_tree);
         }
 
+        let invalidation_trace =
+            traversal
+                .take_damaged_nodes()
+                .filter(|_| restyled)
+                .map(|damaged_nodes| InvalidationTrace {
+                    dirty_root: Some(traversal_root),
+                    damaged_nodes,
+                    box_tree_update,
+                });
+        if let Some(invalidation_trace) = &invalidation_trace {
+            log_invalidation_trace(invalidation_trace, &restyle_statistics);
+        }
+
         layout_context = traversal.destroy();
 
         for element in elements_with_snapshot {
@@ -807,20 +941,53 @@ impl LayoutThread {
         // GC the rule tree if some heuristics are met.
         layout_context.style_context.stylist.rule_tree().maybe_gc();
 
+        // Scroll offsets are applied by WebRender to the spatial nodes of the display list, so when
+        // nothing else changed since that display list was sent, moving a scroll node needs
+        // neither layout nor a new display list.
+        let fragment_tree_unchanged = layout_may_be_skipped && !restyled;
+        if !fragment_tree_unchanged && animated_property_values.is_none() {
+            self.display_list_is_current.set(false);
+        }
+        let only_scroll_offset_changed = fragment_tree_unchanged &&
+            self.display_list_is_current.get() &&
+            matches!(data.reflow_goal, ReflowGoal::UpdateScrollNode(_));
+
         // Perform post-style recalculation layout passes.
-        if let Some(root) = &*self.fragment_tree.borrow() {
+        if only_scroll_offset_changed {
+            debug!("Layout: only a scroll offset changed, reusing the display list");
+        } else if let Some(values) = animated_property_values {
+            debug!("Layout: only animated properties changed, reusing the display list");
+            self.webrender_api.send_animated_properties(
+                self.id.into(),
+                values.transforms,
+                values.opacities,
+            );
+        } else if let Some(root) = &*self.fragment_tree.borrow() {
+            let start = Instant::now();
             self.perform_post_style_recalc_layout_passes(
                 root.clone(),
                 &data.reflow_goal,
                 Some(&document),
                 &mut layout_context,
             );
+            phase_durations.display_list = start.elapsed();
         }
 
         self.first_reflow.set(false);
 
-        data.result.borrow_mut().as_mut().unwrap().pending_images =
-            std::mem::take(&mut *layout_context.pending_images.lock());
+        let mut result = data.result.borrow_mut();
+        let result = result.as_mut().unwrap();
+        result.pending_images = std::mem::take(&mut *layout_context.pending_images.lock());
+        result.restyle_statistics = restyle_statistics;
+        if data.dump_layout {
+            result.layout_dump = Some(layout_debug::dump_layout(
+                self.box_tree.borrow().as_deref(),
+                self.fragment_tree.borrow().as_deref(),
+                invalidation_trace.as_ref(),
+            ));
+        }
+        result.invalidation_trace = invalidation_trace.filter(|_| data.trace_invalidations);
+        result.phase_durations = phase_durations;
         if let ReflowGoal::UpdateScrollNode(scroll_state) = data.reflow_goal {
             self.update_scroll_node_state(&scroll_state);
         }
@@ -923,6 +1090,8 @@ impl LayoutThread {
         if reflow_goal.needs_display() {
             self.webrender_api
                 .send_display_list(display_list.compositor_info, display_list.wr.end().1);
+            self.display_list_is_current.set(true);
+            *self.animated_property_bindings.borrow_mut() = display_list.animated_properties;
 
             let (keys, instance_keys) = self
                 .font_context
@@ -1025,20 +1194,22 @@ impl LayoutThread {
         window_size_data: WindowSizeData,
         guards: &StylesheetGuards,
     ) -> bool {
-        // If the viewport size and device pixel ratio has not changed, do not make any changes.
+        // If the viewport size, device pixel ratio and media type have not changed, do not make
+        // any changes.
         let au_viewport_size = Size2D::new(
             Au::from_f32_px(window_size_data.initial_viewport.width),
             Au::from_f32_px(window_size_data.initial_viewport.height),
         );
 
         if self.stylist.device().au_viewport_size() == au_viewport_size &&
-            self.stylist.device().device_pixel_ratio() == window_size_data.device_pixel_ratio
+            self.stylist.device().device_pixel_ratio() == window_size_data.device_pixel_ratio &&
+            self.stylist.device().media_type() == self.media_type
         {
             return false;
         }
 
         let device = Device::new(
-            MediaType::screen(),
+            self.media_type.clone(),
             self.stylist.quirks_mode(),
             window_size_data.initial_viewport,
             window_size_data.device_pixel_ratio,
@@ -1063,6 +1234,50 @@ impl ProfilerMetadataFactory for LayoutThread {
     }
 }
 
+/// The log target of invalidation traces.
+/// - To enable them: RUST_LOG='layout::invalidation=trace'
+const INVALIDATION_LOG_TARGET: &str = "layout::invalidation";
+
+/// Log the style invalidations found by a reflow, one line for the reflow followed by one line
+/// per damaged element, so that restyles that rebuild far more than expected can be told apart.
+fn log_invalidation_trace(trace: &InvalidationTrace, statistics: &RestyleStatistics) {
+    trace!(
+        target: INVALIDATION_LOG_TARGET,
+        "Restyle from {:?}: {} elements traversed, {} damaged, box tree {:?}, layout skipped: {}",
+        trace.dirty_root,
+        statistics.elements_traversed,
+        statistics.elements_damaged,
+        trace.box_tree_update,
+        statistics.layout_skipped_for_repaint,
+    );
+    for damaged_node in &trace.damaged_nodes {
+        trace!(
+            target: INVALIDATION_LOG_TARGET,
+            "  {:?} at depth {}: {}",
+            damaged_node.node,
+            damaged_node.depth,
+            damaged_node.damage.join(" | "),
+        );
+    }
+}
+
+/// Whether a pending restyle can only change styles through selector matching, i.e. it comes
+/// from `id`, `class` or `data-*` attribute changes or element state changes, and layout has not
+/// been asked to rebuild the element.
+fn restyle_only_affects_selectors(restyle: &PendingRestyle) -> bool {
+    if !restyle.damage.is_empty() {
+        return false;
+    }
+    let Some(ref snapshot) = restyle.snapshot else {
+        return true;
+    };
+    !snapshot.other_attributes_changed ||
+        snapshot
+            .changed_attrs
+            .iter()
+            .all(|name| name.starts_with("data-"))
+}
+
 fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
     fn parse_ua_stylesheet(
         shared_lock: &SharedRwLock,
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Built-in `about:` pages other than `about:blank`.
+
+use std::sync::Mutex;
+
+use headers::{ContentType, HeaderMapExt};
+use http::StatusCode;
+use lazy_static::lazy_static;
+use log::warn;
+use net_traits::request::Request;
+use net_traits::response::{Response, ResponseBody};
+use net_traits::{NetworkError, ResourceFetchTiming};
+use servo_config::pref_util::PrefValue;
+use servo_config::prefs;
+use servo_url::ServoUrl;
+
+/// The number of crash reports kept for `about:crashes`.
+const CRASH_REPORTS_SIZE: usize = 16;
+
+lazy_static! {
+    /// The crash pages shown in this session, oldest first.
+    static ref CRASH_REPORTS: Mutex<Vec<(ServoUrl, String)>> = Mutex::new(Vec::new());
+}
+
+/// Remember that a crash page was shown for `url`, so that it can be listed in `about:crashes`.
+pub fn record_crash(url: ServoUrl, details: String) {
+    let mut reports = CRASH_REPORTS.lock().unwrap();
+    if reports.len() == CRASH_REPORTS_SIZE {
+        reports.remove(0);
+    }
+    reports.push((url, details));
+}
+
+pub fn fetch(request: &Request, url: ServoUrl) -> Response {
+    let page = match url.path() {
+        "config" => {
+            if let Err(error) = handle_config_update(request, &url) {
+                return Response::network_error(NetworkError::Internal(error));
+            }
+            config_page()
+        },
+        "crashes" => crashes_page(),
+        "version" => version_page(),
+        _ => {
+            return Response::network_error(NetworkError::Internal(format!(
+                "Unknown about: page ({})",
+                url.path()
+            )));
+        },
+    };
+
+    let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+    response.headers.typed_insert(ContentType::html());
+    *response.body.lock().unwrap() = ResponseBody::Done(page.into_bytes());
+    response.status = Some((StatusCode::OK, "OK".to_string()));
+    response.raw_status = Some((StatusCode::OK.as_u16(), b"OK".to_vec()));
+    response
+}
+
+/// Apply a preference change requested as `about:config?name=<pref>&value=<value>`.
+fn handle_config_update(request: &Request, url: &ServoUrl) -> Result<(), String> {
+    let mut name = None;
+    let mut value = None;
+    for (key, pair_value) in url.as_url().query_pairs() {
+        match &*key {
+            "name" => name = Some(pair_value.into_owned()),
+            "value" => value = Some(pair_value.into_owned()),
+            _ => {},
+        }
+    }
+    let (Some(name), Some(value)) = (name, value) else {
+        return Ok(());
+    };
+
+    // Content must not be able to change preferences, neither through a subresource load nor
+    // by navigating itself or another browsing context here.
+    if !request.initiated_by_embedder || !request.is_navigation_request() {
+        return Err("Preferences can only be changed from a URL entered by the user.".into());
+    }
+
+    let prefs = prefs::pref_map();
+    let new_value = match prefs.get(&name) {
+        PrefValue::Bool(_) => value.parse().ok().map(PrefValue::Bool),
+        PrefValue::Int(_) => value.parse().ok().map(PrefValue::Int),
+        PrefValue::Float(_) => value.parse().ok().map(PrefValue::Float),
+        PrefValue::Str(_) => Some(PrefValue::Str(value.clone())),
+        PrefValue::Array(_) | PrefValue::Missing => None,
+    };
+    let Some(new_value) = new_value else {
+        return Err(format!("Cannot set preference {name} to {value}"));
+    };
+
+    prefs.set(&name, new_value).map_err(|error| {
+        warn!("Could not set preference {name}: {error}");
+        error.to_string()
+    })
+}
+
+fn config_page() -> String {
+    let mut preferences: Vec<_> = prefs::pref_map()
+        .iter()
+        .filter(|(_, value)| !value.is_missing())
+        .collect();
+    preferences.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut page = page_header("Preferences");
+    page.push_str("<table>\n<tr><th>Name</th><th>Value</th><th>Modified</th></tr>\n");
+    for (name, value) in preferences {
+        let value = match value {
+            PrefValue::Bool(value) => value.to_string(),
+            PrefValue::Int(value) => value.to_string(),
+            PrefValue::Float(value) => value.to_string(),
+            PrefValue::Str(value) => value,
+            PrefValue::Array(values) => format!("{values:?}"),
+            PrefValue::Missing => continue,
+        };
+        let modified = match prefs::pref_map().is_default(&name) {
+            Ok(true) => "",
+            _ => "yes",
+        };
+        page.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{modified}</td></tr>\n",
+            escape_html(&name),
+            escape_html(&value),
+        ));
+    }
+    page.push_str("</table>\n</body></html>\n");
+    page
+}
+
+fn crashes_page() -> String {
+    let mut page = page_header("Crash reports");
+    let reports = CRASH_REPORTS.lock().unwrap();
+    if reports.is_empty() {
+        page.push_str("<p>No crashes have been reported in this session.</p>\n");
+    }
+    for (url, details) in reports.iter().rev() {
+        page.push_str(&format!(
+            "<h2>{}</h2>\n<pre>{}</pre>\n",
+            escape_html(url.as_str()),
+            escape_html(details),
+        ));
+    }
+    page.push_str("</body></html>\n");
+    page
+}
+
+fn version_page() -> String {
+    let mut page = page_header("Version");
+    page.push_str(&format!(
+        "<p>Servo {} ({}-{})</p>\n</body></html>\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+    ));
+    page
+}
+
+fn page_header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title></head>\n\
+         <body>\n<h1>{title}</h1>\n"
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
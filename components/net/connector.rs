@@ -4,52 +4,207 @@
 
 use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::error::Error;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fmt, io};
 
 use futures::task::{Context, Poll};
-use futures::Future;
+use futures::{Future, TryFutureExt};
 use http::uri::{Authority, Uri as Destination};
+use hyper::client::connect::dns::{GaiAddrs, GaiResolver, Name};
+use hyper::client::connect::{Connected, Connection};
 use hyper::client::HttpConnector as HyperHttpConnector;
 use hyper::rt::Executor;
 use hyper::service::Service;
 use hyper::{Body, Client};
 use hyper_rustls::HttpsConnector as HyperRustlsHttpsConnector;
-use log::warn;
+use lazy_static::lazy_static;
+use log::{debug, warn};
 use rustls::client::WebPkiVerifier;
 use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use servo_config::pref;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 use crate::async_runtime::HANDLE;
 use crate::hosts::replace_host;
 
 pub const BUF_SIZE: usize = 32768;
 
+lazy_static! {
+    /// The number of connections that the HTTP clients of this process have open to each host,
+    /// which is logged with each new connection. The connector is only asked for a connection when
+    /// the pool has no idle one to reuse, so a count that keeps growing for a host points at
+    /// connections not being pooled. Hosts without an open connection are removed.
+    static ref OPEN_CONNECTIONS: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
+}
+
+/// The error of a host name that could not be resolved.
+#[derive(Debug)]
+pub struct DnsResolutionError(io::Error);
+
+impl DnsResolutionError {
+    /// The resolution failure that caused `error`, if any.
+    pub fn find_in(error: &hyper::Error) -> Option<&DnsResolutionError> {
+        let mut source = error.source();
+        while let Some(cause) = source {
+            if let Some(dns_error) = cause.downcast_ref::<DnsResolutionError>() {
+                return Some(dns_error);
+            }
+            source = cause.source();
+        }
+        None
+    }
+}
+
+impl fmt::Display for DnsResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for DnsResolutionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Resolves host names with `getaddrinfo` like the default resolver of hyper, but with an error
+/// type of its own so that resolution failures can be told apart from other connection errors.
+#[derive(Clone)]
+pub struct ServoResolver {
+    inner: GaiResolver,
+}
+
+impl Service<Name> for ServoResolver {
+    type Response = GaiAddrs;
+    type Error = DnsResolutionError;
+    type Future = Pin<Box<dyn Future<Output = Result<GaiAddrs, DnsResolutionError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(DnsResolutionError)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        Box::pin(self.inner.call(name).map_err(DnsResolutionError))
+    }
+}
+
 #[derive(Clone)]
 pub struct ServoHttpConnector {
-    inner: HyperHttpConnector,
+    inner: HyperHttpConnector<ServoResolver>,
 }
 
 impl ServoHttpConnector {
     fn new() -> ServoHttpConnector {
-        let mut inner = HyperHttpConnector::new();
+        let mut inner = HyperHttpConnector::new_with_resolver(ServoResolver {
+            inner: GaiResolver::new(),
+        });
         inner.enforce_http(false);
         inner.set_happy_eyeballs_timeout(None);
         ServoHttpConnector { inner }
     }
 }
 
+type HttpStream = <HyperHttpConnector<ServoResolver> as Service<Destination>>::Response;
+
+/// A connection opened by `ServoHttpConnector`, which stops being counted as open to its host
+/// once it is dropped.
+pub struct ServoConnection {
+    stream: HttpStream,
+    host: Option<String>,
+}
+
+impl ServoConnection {
+    fn new(stream: HttpStream, host: Option<String>) -> ServoConnection {
+        if let Some(ref host) = host {
+            let open = {
+                let mut open_connections = OPEN_CONNECTIONS.lock().unwrap();
+                let open = open_connections.entry(host.clone()).or_default();
+                *open += 1;
+                *open
+            };
+            debug!("Opened a connection to {host}, {open} now open");
+        }
+        ServoConnection { stream, host }
+    }
+}
+
+impl Drop for ServoConnection {
+    fn drop(&mut self) {
+        let Some(ref host) = self.host else {
+            return;
+        };
+        let mut open_connections = OPEN_CONNECTIONS.lock().unwrap();
+        if let Some(open) = open_connections.get_mut(host) {
+            *open -= 1;
+            if *open == 0 {
+                open_connections.remove(host);
+            }
+        }
+    }
+}
+
+impl AsyncRead for ServoConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ServoConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+impl Connection for ServoConnection {
+    fn connected(&self) -> Connected {
+        self.stream.connected()
+    }
+}
+
 impl Service<Destination> for ServoHttpConnector {
-    type Response = <HyperHttpConnector as Service<Destination>>::Response;
-    type Error = <HyperHttpConnector as Service<Destination>>::Error;
-    type Future = <HyperHttpConnector as Service<Destination>>::Future;
+    type Response = ServoConnection;
+    type Error = <HyperHttpConnector<ServoResolver> as Service<Destination>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<ServoConnection, Self::Error>> + Send>>;
 
     fn call(&mut self, dest: Destination) -> Self::Future {
         // Perform host replacement when making the actual TCP connection.
         let mut new_dest = dest.clone();
         let mut parts = dest.into_parts();
+        let original_host = parts.authority.as_ref().map(|auth| auth.host().to_owned());
 
         if let Some(auth) = parts.authority {
-            let host = auth.host();
-            let host = replace_host(host);
+            let host = replace_host(auth.host());
 
             let authority = if let Some(port) = auth.port() {
                 format!("{}:{}", host, port.as_str())
@@ -65,7 +220,11 @@ impl Service<Destination> for ServoHttpConnector {
             }
         }
 
-        self.inner.call(new_dest)
+        let connecting = self.inner.call(new_dest);
+        Box::pin(async move {
+            let stream = connecting.await?;
+            Ok(ServoConnection::new(stream, original_host))
+        })
     }
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -255,8 +414,15 @@ pub fn create_http_client(tls_config: TlsConfig) -> Client<Connector, Body> {
         .enable_http2()
         .wrap_connector(ServoHttpConnector::new());
 
+    let idle_timeout_ms = pref!(network.connection_pool.idle_timeout_ms).max(0) as u64;
+    let max_idle_per_host =
+        usize::try_from(pref!(network.connection_pool.max_idle_per_host).max(0))
+            .unwrap_or(usize::MAX);
+
     Client::builder()
         .http1_title_case_headers(true)
+        .pool_idle_timeout(Duration::from_millis(idle_timeout_ms))
+        .pool_max_idle_per_host(max_idle_per_host)
         .executor(TokioExecutor {})
         .build(connector)
 }
@@ -0,0 +1,236 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Blocks the requests that match the filter lists of embedders, for example to block ads and
+//! trackers.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use base::id::TopLevelBrowsingContextId;
+use log::debug;
+use net_traits::request::{Destination, Request};
+use servo_url::ServoUrl;
+
+/// The kind of resource a request is for, as far as content filters are concerned.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ResourceType {
+    Document,
+    /// The document of an iframe.
+    Subdocument,
+    Script,
+    Style,
+    Image,
+    Font,
+    Media,
+    /// A request made with `fetch()` or `XMLHttpRequest`.
+    Fetch,
+    Other,
+}
+
+impl From<Destination> for ResourceType {
+    fn from(destination: Destination) -> Self {
+        match destination {
+            Destination::Document => ResourceType::Document,
+            Destination::Frame | Destination::IFrame => ResourceType::Subdocument,
+            Destination::Style => ResourceType::Style,
+            Destination::Image => ResourceType::Image,
+            Destination::Font => ResourceType::Font,
+            Destination::Audio | Destination::Track | Destination::Video => ResourceType::Media,
+            Destination::None => ResourceType::Fetch,
+            destination if destination.is_script_like() => ResourceType::Script,
+            _ => ResourceType::Other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContentFilterAction {
+    Block,
+    /// Let the matching requests through, even if another rule blocks them.
+    Allow,
+}
+
+/// A rule of a content filter list. A request matches it if its URL matches all the conditions
+/// that are set.
+#[derive(Clone, Debug)]
+pub struct ContentFilterRule {
+    /// The domain of the URLs the rule applies to, including its subdomains.
+    pub domain: Option<String>,
+    /// The start of the path of the URLs the rule applies to.
+    pub path_prefix: Option<String>,
+    /// The kinds of resources the rule applies to, or all of them if empty.
+    pub resource_types: Vec<ResourceType>,
+    pub action: ContentFilterAction,
+}
+
+#[derive(Debug)]
+pub enum ContentFilterError {
+    /// The domain of the rule at this index of the list is not a valid host name.
+    InvalidDomain(usize),
+    /// The path prefix of the rule at this index of the list does not start with a slash.
+    InvalidPathPrefix(usize),
+}
+
+/// The part of a rule that is checked once the domain of the URL matched.
+#[derive(Debug)]
+struct CompiledRule {
+    path_prefix: Option<String>,
+    resource_types: Vec<ResourceType>,
+    action: ContentFilterAction,
+}
+
+impl CompiledRule {
+    fn matches(&self, url: &ServoUrl, resource_type: ResourceType) -> bool {
+        self.path_prefix
+            .as_ref()
+            .map_or(true, |prefix| url.path().starts_with(&**prefix)) &&
+            (self.resource_types.is_empty() || self.resource_types.contains(&resource_type))
+    }
+}
+
+/// A content filter list, with its rules indexed by domain so that requests can be checked
+/// without going through all of them.
+#[derive(Debug, Default)]
+pub struct ContentFilterList {
+    rules_by_domain: HashMap<String, Vec<CompiledRule>>,
+    rules_for_any_domain: Vec<CompiledRule>,
+}
+
+impl ContentFilterList {
+    pub fn compile(rules: Vec<ContentFilterRule>) -> Result<Self, ContentFilterError> {
+        let mut list = ContentFilterList::default();
+        for (index, rule) in rules.into_iter().enumerate() {
+            if rule
+                .path_prefix
+                .as_ref()
+                .is_some_and(|prefix| !prefix.starts_with('/'))
+            {
+                return Err(ContentFilterError::InvalidPathPrefix(index));
+            }
+            let compiled_rule = CompiledRule {
+                path_prefix: rule.path_prefix,
+                resource_types: rule.resource_types,
+                action: rule.action,
+            };
+            match rule.domain {
+                Some(domain) => {
+                    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+                    if !is_valid_domain(&domain) {
+                        return Err(ContentFilterError::InvalidDomain(index));
+                    }
+                    list.rules_by_domain
+                        .entry(domain)
+                        .or_default()
+                        .push(compiled_rule);
+                },
+                None => list.rules_for_any_domain.push(compiled_rule),
+            }
+        }
+        Ok(list)
+    }
+
+    /// The action of the rules of this list that match the URL, with `Allow` taking precedence.
+    fn action_for(
+        &self,
+        url: &ServoUrl,
+        resource_type: ResourceType,
+    ) -> Option<ContentFilterAction> {
+        let host = url.host_str()?.trim_end_matches('.').to_ascii_lowercase();
+        let rules_for_host = superdomains(&host)
+            .filter_map(|domain| self.rules_by_domain.get(domain))
+            .flatten();
+        rules_for_host
+            .chain(&self.rules_for_any_domain)
+            .filter(|rule| rule.matches(url, resource_type))
+            .map(|rule| rule.action)
+            .max_by_key(|action| *action == ContentFilterAction::Allow)
+    }
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    !domain.is_empty() &&
+        domain.split('.').all(|label| {
+            !label.is_empty() &&
+                label
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '-')
+        })
+}
+
+/// The host itself, followed by the domains it is a subdomain of.
+fn superdomains(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |domain| {
+        domain.split_once('.').map(|(_, parent)| parent)
+    })
+}
+
+/// The content filter lists that embedders added, by identifier, and the number of requests
+/// they blocked for each webview.
+#[derive(Default)]
+pub struct ContentFilters {
+    lists: RwLock<HashMap<String, ContentFilterList>>,
+    blocked_requests: Mutex<HashMap<TopLevelBrowsingContextId, usize>>,
+}
+
+impl ContentFilters {
+    /// Add a list, replacing the one with the same identifier.
+    pub fn add(&self, identifier: String, list: ContentFilterList) {
+        self.lists.write().unwrap().insert(identifier, list);
+    }
+
+    pub fn remove(&self, identifier: &str) {
+        self.lists.write().unwrap().remove(identifier);
+    }
+
+    pub fn remove_all(&self) {
+        self.lists.write().unwrap().clear();
+    }
+
+    /// The number of requests that were blocked for a webview since it was opened.
+    pub fn blocked_request_count(&self, webview_id: TopLevelBrowsingContextId) -> usize {
+        self.blocked_requests
+            .lock()
+            .unwrap()
+            .get(&webview_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Forget the requests that were blocked for a webview, once it is closed.
+    pub fn forget_webview(&self, webview_id: TopLevelBrowsingContextId) {
+        self.blocked_requests.lock().unwrap().remove(&webview_id);
+    }
+
+    /// Whether a request is blocked by a list, in which case it is counted for its webview.
+    pub(crate) fn should_block(&self, request: &Request) -> bool {
+        let url = request.current_url();
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+
+        let resource_type = ResourceType::from(request.destination);
+        let mut blocked = false;
+        for list in self.lists.read().unwrap().values() {
+            match list.action_for(&url, resource_type) {
+                Some(ContentFilterAction::Allow) => return false,
+                Some(ContentFilterAction::Block) => blocked = true,
+                None => {},
+            }
+        }
+
+        if blocked {
+            debug!("Request to {} blocked by a content filter", url);
+            if let Some(webview_id) = request.target_webview_id {
+                *self
+                    .blocked_requests
+                    .lock()
+                    .unwrap()
+                    .entry(webview_id)
+                    .or_default() += 1;
+            }
+        }
+        blocked
+    }
+}
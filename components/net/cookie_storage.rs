@@ -11,7 +11,7 @@ use std::collections::HashMap;
 
 use log::{debug, info};
 use net_traits::pub_domains::reg_suffix;
-use net_traits::CookieSource;
+use net_traits::{CookieSource, SiteDataFilter};
 use serde::{Deserialize, Serialize};
 use servo_url::ServoUrl;
 use time::{self, Tm};
@@ -94,6 +94,41 @@ impl CookieStorage {
             Ok(None)
         }
     }
+    /// The unexpired cookies that match the given filter.
+    pub fn cookies_matching(&self, filter: &SiteDataFilter) -> Vec<cookie_rs::Cookie<'static>> {
+        self.cookies_map
+            .values()
+            .flatten()
+            .filter(|cookie| !is_cookie_expired(cookie) && cookie_matches(cookie, filter))
+            .map(|cookie| cookie.cookie.clone())
+            .collect()
+    }
+
+    /// Remove the cookies that match the given filter.
+    pub fn remove_matching(&mut self, filter: &SiteDataFilter) {
+        self.cookies_map.retain(|_, cookies| {
+            cookies.retain(|cookie| !cookie_matches(cookie, filter));
+            !cookies.is_empty()
+        });
+    }
+
+    /// Remove the cookie with the same name, domain and path as the given one, regardless of
+    /// where it was set from.
+    pub fn delete(&mut self, cookie: &cookie_rs::Cookie) {
+        let domain = reg_host(cookie.domain().unwrap_or(""));
+        if let Entry::Occupied(mut entry) = self.cookies_map.entry(domain) {
+            let cookies = entry.get_mut();
+            cookies.retain(|c| {
+                c.cookie.domain() != cookie.domain() ||
+                    c.cookie.path() != cookie.path() ||
+                    c.cookie.name() != cookie.name()
+            });
+            if cookies.is_empty() {
+                entry.remove_entry();
+            }
+        }
+    }
+
     pub fn clear_storage(&mut self, url: &ServoUrl) {
         let domain = reg_host(url.host_str().unwrap_or(""));
         let cookies = self.cookies_map.entry(domain).or_default();
@@ -233,6 +268,11 @@ fn reg_host(url: &str) -> String {
     reg_suffix(url).to_lowercase()
 }
 
+fn cookie_matches(cookie: &Cookie, filter: &SiteDataFilter) -> bool {
+    filter.matches_host(cookie.cookie.domain().unwrap_or("")) &&
+        filter.matches_time(cookie.creation_time.to_timespec().sec)
+}
+
 fn is_cookie_expired(cookie: &Cookie) -> bool {
     match cookie.expiry_time {
         Some(ref t) => t.to_timespec() <= time::get_time(),
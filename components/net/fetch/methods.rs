@@ -42,6 +42,8 @@ use tokio::sync::mpsc::{
     unbounded_channel, UnboundedReceiver as TokioReceiver, UnboundedSender as TokioSender,
 };
 
+use crate::about_loader;
+use crate::content_filters::ContentFilters;
 use crate::data_loader::decode;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::headers::determine_nosniff;
@@ -51,6 +53,7 @@ use crate::http_loader::{
     HttpState,
 };
 use crate::local_directory_listing;
+use crate::protocols::{self, ProtocolRegistry};
 use crate::subresource_integrity::is_response_integrity_valid;
 
 lazy_static! {
@@ -75,6 +78,8 @@ pub struct FetchContext {
     pub file_token: FileTokenCheck,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: ServoArc<Mutex<ResourceFetchTiming>>,
+    pub protocols: Arc<ProtocolRegistry>,
+    pub content_filters: Arc<ContentFilters>,
 }
 
 pub struct CancellationListener {
@@ -204,6 +209,7 @@ pub async fn main_fetch(
 
     // Servo internal: return a crash error when a crash error page is needed
     if let Some(ref details) = request.crash {
+        about_loader::record_crash(request.current_url(), details.clone());
         response = Some(Response::network_error(NetworkError::Crash(
             details.clone(),
         )));
@@ -247,6 +253,13 @@ pub async fn main_fetch(
     // TODO: handle blocking as mixed content.
     // TODO: handle blocking by content security policy.
 
+    // Servo internal: block the requests that match the content filters of the embedder.
+    if response.is_none() && context.content_filters.should_block(request) {
+        response = Some(Response::network_error(NetworkError::Internal(
+            "Blocked by a content filter".into(),
+        )));
+    }
+
     // Step 6
     // TODO: handle request's client's referrer policy.
 
@@ -694,6 +707,8 @@ async fn scheme_fetch(
     match url.scheme() {
         "about" if url.path() == "blank" => create_blank_reply(url, request.timing_type()),
 
+        "about" => about_loader::fetch(request, url),
+
         "chrome" if url.path() == "allowcert" => {
             if let Err(error) = handle_allowcert_request(request, context) {
                 warn!("Could not handle allowcert request: {error}");
@@ -859,7 +874,10 @@ async fn scheme_fetch(
             Response::network_error(NetworkError::Internal("Unexpected scheme".into()))
         },
 
-        _ => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        _ => match protocols::fetch(request, done_chan, context).await {
+            Some(response) => response,
+            None => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        },
     }
 }
 
@@ -26,7 +26,7 @@ use malloc_size_of::{
 use malloc_size_of_derive::MallocSizeOf;
 use net_traits::request::Request;
 use net_traits::response::{HttpsState, Response, ResponseBody};
-use net_traits::{FetchMetadata, Metadata, ResourceFetchTiming};
+use net_traits::{FetchMetadata, Metadata, ResourceFetchTiming, SiteDataFilter};
 use servo_arc::Arc;
 use servo_config::pref;
 use servo_url::ServoUrl;
@@ -910,4 +910,17 @@ impl HttpCache {
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Remove the resources whose URL and last validation match the given filter.
+    pub fn clear_matching(&mut self, filter: &SiteDataFilter) {
+        self.entries.retain(|key, resources| {
+            if !filter.matches_host(key.url.host_str().unwrap_or("")) {
+                return true;
+            }
+            resources.retain(|resource| {
+                !filter.matches_time(resource.data.last_validated.to_timespec().sec)
+            });
+            !resources.is_empty()
+        });
+    }
 }
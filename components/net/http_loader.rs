@@ -58,7 +58,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use crate::async_runtime::HANDLE;
 use crate::connector::{
     create_http_client, create_tls_config, CACertificates, CertificateErrorOverrideManager,
-    Connector,
+    Connector, DnsResolutionError,
 };
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
@@ -346,7 +346,7 @@ fn set_cookies_from_headers(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn prepare_devtools_request(
+pub(crate) fn prepare_devtools_request(
     request_id: String,
     url: ServoUrl,
     method: Method,
@@ -375,7 +375,7 @@ fn prepare_devtools_request(
     ChromeToDevtoolsControlMsg::NetworkEvent(request_id, net_event)
 }
 
-fn send_request_to_devtools(
+pub(crate) fn send_request_to_devtools(
     msg: ChromeToDevtoolsControlMsg,
     devtools_chan: &Sender<DevtoolsControlMsg>,
 ) {
@@ -384,7 +384,7 @@ fn send_request_to_devtools(
         .unwrap();
 }
 
-fn send_response_to_devtools(
+pub(crate) fn send_response_to_devtools(
     devtools_chan: &Sender<DevtoolsControlMsg>,
     request_id: String,
     headers: Option<HeaderMap>,
@@ -653,6 +653,14 @@ async fn obtain_response(
 
         let host = request.uri().host().unwrap_or("").to_owned();
         let override_manager = context.state.override_manager.clone();
+        // Certificate errors on HSTS hosts must not be bypassable, so don't offer
+        // the failing certificate for an override.
+        let host_is_hsts = context
+            .state
+            .hsts_list
+            .read()
+            .unwrap()
+            .is_host_secure(&host);
         let headers = headers.clone();
 
         client
@@ -691,10 +699,12 @@ async fn obtain_response(
                 future::ready(Ok((Decoder::detect(res), msg)))
             })
             .map_err(move |error| {
-                NetworkError::from_hyper_error(
-                    &error,
-                    override_manager.remove_certificate_failing_verification(host.as_str()),
-                )
+                if let Some(dns_error) = DnsResolutionError::find_in(&error) {
+                    return NetworkError::DnsResolution(dns_error.to_string());
+                }
+                let certificate =
+                    override_manager.remove_certificate_failing_verification(host.as_str());
+                NetworkError::from_hyper_error(&error, certificate.filter(|_| !host_is_hsts))
             })
             .await
     }
@@ -655,6 +655,19 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    fn decoded_images_size(&self) -> usize {
+        let store = self.store.lock().unwrap();
+        store
+            .completed_loads
+            .values()
+            .map(|load| match load.image_response {
+                // The placeholder image is shared by every failed load, and always kept alive.
+                ImageResponse::Loaded(ref image, _) => image.bytes.len(),
+                _ => 0,
+            })
+            .sum()
+    }
 }
 
 impl ImageCacheImpl {
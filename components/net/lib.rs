@@ -4,8 +4,10 @@
 
 #![deny(unsafe_code)]
 
+mod about_loader;
 pub mod async_runtime;
 pub mod connector;
+pub mod content_filters;
 pub mod cookie;
 pub mod cookie_storage;
 mod data_loader;
@@ -18,6 +20,7 @@ pub mod http_loader;
 pub mod image_cache;
 pub mod local_directory_listing;
 pub mod mime_classifier;
+pub mod protocols;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
@@ -0,0 +1,232 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Loads the URL schemes that embedders register handlers for, like `app:` or `resource:`.
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use base::id::TopLevelBrowsingContextId;
+use headers::{ContentType, HeaderMapExt};
+use http::StatusCode;
+use log::warn;
+use mime::Mime;
+use net_traits::request::{Destination, Request};
+use net_traits::response::{Response, ResponseBody};
+use net_traits::{NetworkError, ResourceFetchTiming};
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::fetch::methods::{Data, DoneChannel, FetchContext};
+use crate::filemanager_thread::FILE_CHUNK_SIZE;
+use crate::http_loader::{
+    prepare_devtools_request, send_request_to_devtools, send_response_to_devtools,
+};
+
+/// The schemes that the network layer loads itself, which cannot be handed to a handler.
+const RESERVED_SCHEMES: &[&str] = &[
+    "about", "blob", "chrome", "data", "file", "ftp", "http", "https", "ws", "wss",
+];
+
+/// Produces the resources of a URL scheme. Handlers are called on a thread of the network
+/// layer that is allowed to block.
+pub trait ProtocolHandler: Send + Sync {
+    /// Load the resource of a request, or return why it could not be loaded, which becomes a
+    /// network error.
+    fn load(&self, request: &Request) -> Result<ProtocolResponse, String>;
+}
+
+/// The resource that a [`ProtocolHandler`] loaded.
+pub struct ProtocolResponse {
+    pub mime_type: Mime,
+    pub body: ProtocolResponseBody,
+}
+
+pub enum ProtocolResponseBody {
+    Bytes(Vec<u8>),
+    /// A body that is read in chunks while the response is delivered.
+    Stream(Box<dyn Read + Send>),
+}
+
+#[derive(Debug)]
+pub enum ProtocolRegistrationError {
+    /// The scheme is not a valid URL scheme.
+    InvalidScheme,
+    /// The scheme is loaded by Servo itself.
+    ReservedScheme,
+}
+
+/// The protocol handlers that embedders registered, either for all webviews or for one of them.
+/// A handler registered for a webview takes precedence for the requests made for it.
+#[derive(Default)]
+pub struct ProtocolRegistry {
+    handlers:
+        RwLock<HashMap<(String, Option<TopLevelBrowsingContextId>), Arc<dyn ProtocolHandler>>>,
+}
+
+impl ProtocolRegistry {
+    pub fn register(
+        &self,
+        scheme: &str,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        handler: Arc<dyn ProtocolHandler>,
+    ) -> Result<(), ProtocolRegistrationError> {
+        let scheme = scheme.to_ascii_lowercase();
+        if !is_valid_scheme(&scheme) {
+            return Err(ProtocolRegistrationError::InvalidScheme);
+        }
+        if RESERVED_SCHEMES.contains(&&*scheme) {
+            return Err(ProtocolRegistrationError::ReservedScheme);
+        }
+        self.handlers
+            .write()
+            .unwrap()
+            .insert((scheme, webview_id), handler);
+        Ok(())
+    }
+
+    pub fn unregister(&self, scheme: &str, webview_id: Option<TopLevelBrowsingContextId>) {
+        self.handlers
+            .write()
+            .unwrap()
+            .remove(&(scheme.to_ascii_lowercase(), webview_id));
+    }
+
+    /// Remove the handlers registered for a webview, once it is closed.
+    pub fn unregister_webview(&self, webview_id: TopLevelBrowsingContextId) {
+        self.handlers
+            .write()
+            .unwrap()
+            .retain(|(_, handler_webview_id), _| *handler_webview_id != Some(webview_id));
+    }
+
+    fn handler_for(
+        &self,
+        scheme: &str,
+        webview_id: Option<TopLevelBrowsingContextId>,
+    ) -> Option<Arc<dyn ProtocolHandler>> {
+        let handlers = self.handlers.read().unwrap();
+        webview_id
+            .and_then(|webview_id| handlers.get(&(scheme.to_owned(), Some(webview_id))))
+            .or_else(|| handlers.get(&(scheme.to_owned(), None)))
+            .cloned()
+    }
+}
+
+/// <https://url.spec.whatwg.org/#url-scheme-string>
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut characters = scheme.chars();
+    characters
+        .next()
+        .is_some_and(|character| character.is_ascii_alphabetic()) &&
+        characters.all(|character| {
+            character.is_ascii_alphanumeric() || matches!(character, '+' | '-' | '.')
+        })
+}
+
+/// Load a request with the handler registered for its scheme, or return `None` if there is
+/// none.
+pub(crate) async fn fetch(
+    request: &Request,
+    done_chan: &mut DoneChannel,
+    context: &FetchContext,
+) -> Option<Response> {
+    let url = request.current_url();
+    let handler = context
+        .protocols
+        .handler_for(url.scheme(), request.target_webview_id)?;
+
+    let handler_request = request.clone();
+    let loaded = tokio::task::spawn_blocking(move || handler.load(&handler_request))
+        .await
+        .unwrap_or_else(|error| Err(format!("The protocol handler panicked ({error})")));
+    let loaded = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => return Some(Response::network_error(NetworkError::Internal(error))),
+    };
+
+    let mut response = Response::new(url.clone(), ResourceFetchTiming::new(request.timing_type()));
+    response.status = Some((StatusCode::OK, "OK".to_string()));
+    response.raw_status = Some((StatusCode::OK.as_u16(), b"OK".to_vec()));
+    response
+        .headers
+        .typed_insert(ContentType::from(loaded.mime_type));
+
+    if let (Some(devtools_chan), Some(pipeline_id)) = (&context.devtools_chan, request.pipeline_id)
+    {
+        let request_id = uuid::Uuid::new_v4().simple().to_string();
+        let devtools_chan = devtools_chan.lock().unwrap();
+        send_request_to_devtools(
+            prepare_devtools_request(
+                request_id.clone(),
+                url,
+                request.method.clone(),
+                request.headers.clone(),
+                None,
+                pipeline_id,
+                SystemTime::now(),
+                0,
+                0,
+                request.destination == Destination::None,
+            ),
+            &devtools_chan,
+        );
+        send_response_to_devtools(
+            &devtools_chan,
+            request_id,
+            Some(response.headers.clone()),
+            response.raw_status.clone(),
+            pipeline_id,
+        );
+    }
+
+    match loaded.body {
+        ProtocolResponseBody::Bytes(bytes) => {
+            *response.body.lock().unwrap() = ResponseBody::Done(bytes);
+        },
+        ProtocolResponseBody::Stream(mut reader) => {
+            let (done_sender, done_receiver) = unbounded_channel();
+            *done_chan = Some((done_sender.clone(), done_receiver));
+            *response.body.lock().unwrap() = ResponseBody::Receiving(vec![]);
+
+            let body = response.body.clone();
+            let cancellation_listener = context.cancellation_listener.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut buffer = vec![0; FILE_CHUNK_SIZE];
+                loop {
+                    if cancellation_listener.lock().unwrap().cancelled() {
+                        *body.lock().unwrap() = ResponseBody::Done(vec![]);
+                        let _ = done_sender.send(Data::Cancelled);
+                        return;
+                    }
+                    let length = match reader.read(&mut buffer) {
+                        Ok(length) => length,
+                        Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                        Err(error) => {
+                            // The response has already been delivered, so end its body early.
+                            warn!("Reading the body of a protocol handler failed ({error}).");
+                            0
+                        },
+                    };
+                    let mut body = body.lock().unwrap();
+                    if length == 0 {
+                        let completed_body = match *body {
+                            ResponseBody::Receiving(ref mut body) => std::mem::take(body),
+                            _ => vec![],
+                        };
+                        *body = ResponseBody::Done(completed_body);
+                        let _ = done_sender.send(Data::Done);
+                        return;
+                    }
+                    if let ResponseBody::Receiving(ref mut body) = *body {
+                        body.extend_from_slice(&buffer[..length]);
+                    }
+                    let _ = done_sender.send(Data::Payload(buffer[..length].to_vec()));
+                }
+            });
+        },
+    }
+    Some(response)
+}
@@ -43,6 +43,7 @@ use crate::async_runtime::HANDLE;
 use crate::connector::{
     create_http_client, create_tls_config, CACertificates, CertificateErrorOverrideManager,
 };
+use crate::content_filters::ContentFilters;
 use crate::cookie_storage::CookieStorage;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
@@ -50,6 +51,7 @@ use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState};
+use crate::protocols::ProtocolRegistry;
 use crate::storage_thread::StorageThreadFactory;
 use crate::{cookie, websocket_loader};
 
@@ -74,6 +76,8 @@ pub fn new_resource_threads(
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
     ignore_certificate_errors: bool,
+    protocols: Arc<ProtocolRegistry>,
+    content_filters: Arc<ContentFilters>,
 ) -> (ResourceThreads, ResourceThreads) {
     let ca_certificates = match certificate_path {
         Some(path) => match load_root_cert_store_from_file(path) {
@@ -95,6 +99,8 @@ pub fn new_resource_threads(
         config_dir.clone(),
         ca_certificates,
         ignore_certificate_errors,
+        protocols,
+        content_filters,
     );
     let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
     (
@@ -114,6 +120,8 @@ pub fn new_core_resource_thread(
     config_dir: Option<PathBuf>,
     ca_certificates: CACertificates,
     ignore_certificate_errors: bool,
+    protocols: Arc<ProtocolRegistry>,
+    content_filters: Arc<ContentFilters>,
 ) -> (CoreResourceThread, CoreResourceThread) {
     let (public_setup_chan, public_setup_port) = ipc::channel().unwrap();
     let (private_setup_chan, private_setup_port) = ipc::channel().unwrap();
@@ -129,6 +137,8 @@ pub fn new_core_resource_thread(
                 embedder_proxy,
                 ca_certificates.clone(),
                 ignore_certificate_errors,
+                protocols,
+                content_filters,
             );
 
             let mut channel_manager = ResourceChannelManager {
@@ -310,6 +320,36 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::GetCookies(filter, consumer) => {
+                let cookies = http_state
+                    .cookie_jar
+                    .read()
+                    .unwrap()
+                    .cookies_matching(&filter)
+                    .into_iter()
+                    .map(Serde)
+                    .collect();
+                let _ = consumer.send(cookies);
+            },
+            CoreResourceMsg::DeleteCookie(cookie) => {
+                http_state.cookie_jar.write().unwrap().delete(&cookie);
+            },
+            CoreResourceMsg::ClearCookies(filter, sender) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .remove_matching(&filter);
+                let _ = sender.send(());
+            },
+            CoreResourceMsg::ClearHttpCache(filter, sender) => {
+                http_state
+                    .http_cache
+                    .write()
+                    .unwrap()
+                    .clear_matching(&filter);
+                let _ = sender.send(());
+            },
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
@@ -474,6 +514,8 @@ pub struct CoreResourceManager {
     thread_pool: Arc<CoreResourceThreadPool>,
     ca_certificates: CACertificates,
     ignore_certificate_errors: bool,
+    protocols: Arc<ProtocolRegistry>,
+    content_filters: Arc<ContentFilters>,
 }
 
 /// The state of the thread-pool used by CoreResource.
@@ -610,6 +652,8 @@ impl CoreResourceManager {
         embedder_proxy: EmbedderProxy,
         ca_certificates: CACertificates,
         ignore_certificate_errors: bool,
+        protocols: Arc<ProtocolRegistry>,
+        content_filters: Arc<ContentFilters>,
     ) -> CoreResourceManager {
         let pool = CoreResourceThreadPool::new(16);
         let pool_handle = Arc::new(pool);
@@ -621,6 +665,8 @@ impl CoreResourceManager {
             thread_pool: pool_handle,
             ca_certificates,
             ignore_certificate_errors,
+            protocols,
+            content_filters,
         }
     }
 
@@ -659,6 +705,8 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_sender.clone();
         let filemanager = self.filemanager.clone();
+        let protocols = self.protocols.clone();
+        let content_filters = self.content_filters.clone();
 
         let timing_type = match request_builder.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -702,6 +750,8 @@ impl CoreResourceManager {
                 file_token,
                 cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
                 timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
+                protocols,
+                content_filters,
             };
 
             match res_init_ {
@@ -9,6 +9,7 @@ use std::thread;
 
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::SiteDataFilter;
 use servo_url::ServoUrl;
 
 use crate::resource_thread;
@@ -83,6 +84,11 @@ impl StorageManager {
                     self.clear(sender, url, storage_type);
                     self.save_state()
                 },
+                StorageThreadMsg::Estimate(sender, url) => self.estimate(sender, url),
+                StorageThreadMsg::ClearSiteData(filter, sender) => {
+                    self.clear_site_data(sender, filter);
+                    self.save_state()
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -126,6 +132,19 @@ impl StorageManager {
             .unwrap();
     }
 
+    fn estimate(&self, sender: IpcSender<(usize, usize)>, url: ServoUrl) {
+        let origin = self.origin_as_string(url);
+        let usage = [StorageType::Local, StorageType::Session]
+            .into_iter()
+            .map(|storage_type| {
+                self.select_data(storage_type)
+                    .get(&origin)
+                    .map_or(0, |&(total, _)| total)
+            })
+            .sum();
+        sender.send((usage, QUOTA_SIZE_LIMIT)).unwrap();
+    }
+
     fn key(
         &self,
         sender: IpcSender<Option<String>>,
@@ -271,6 +290,22 @@ impl StorageManager {
             .unwrap();
     }
 
+    fn clear_site_data(&mut self, sender: IpcSender<()>, filter: SiteDataFilter) {
+        // Web storage keeps no timestamps, so it can only be cleared for all time.
+        if filter.matches_untimed_data() {
+            let origin_matches = |origin: &String| {
+                ServoUrl::parse(origin)
+                    .ok()
+                    .and_then(|url| url.host_str().map(|host| filter.matches_host(host)))
+                    .unwrap_or(false)
+            };
+            self.local_data.retain(|origin, _| !origin_matches(origin));
+            self.session_data
+                .retain(|origin, _| !origin_matches(origin));
+        }
+        let _ = sender.send(());
+    }
+
     fn origin_as_string(&self, url: ServoUrl) -> String {
         url.origin().ascii_serialization()
     }
@@ -4,7 +4,7 @@
 
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
-use net_traits::CookieSource;
+use net_traits::{CookieSource, SiteDataFilter};
 use servo_url::ServoUrl;
 
 #[test]
@@ -475,3 +475,41 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+#[test]
+fn test_cookie_storage_site_data_filter() {
+    let mut storage = CookieStorage::new(150);
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "a=1");
+    let url = ServoUrl::parse("http://www.example.com/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "b=2");
+    let url = ServoUrl::parse("http://example.org/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "c=3");
+
+    let example_com = SiteDataFilter {
+        hosts: Some(vec!["example.com".to_owned()]),
+        since: None,
+    };
+    let mut names: Vec<_> = storage
+        .cookies_matching(&example_com)
+        .iter()
+        .map(|cookie| cookie.name().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["a", "b"]);
+
+    let b = storage
+        .cookies_matching(&example_com)
+        .into_iter()
+        .find(|cookie| cookie.name() == "b")
+        .unwrap();
+    storage.delete(&b);
+    assert_eq!(storage.cookies_matching(&example_com).len(), 1);
+
+    storage.remove_matching(&example_com);
+    assert!(storage.cookies_matching(&example_com).is_empty());
+    assert_eq!(
+        storage.cookies_matching(&SiteDataFilter::default()).len(),
+        1
+    );
+}
@@ -11,7 +11,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 use std::time::{Duration, SystemTime};
 
-use base::id::TEST_PIPELINE_ID;
+use base::id::{TopLevelBrowsingContextId, TEST_BROWSING_CONTEXT_ID, TEST_PIPELINE_ID};
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
 use headers::{
@@ -23,10 +23,14 @@ use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::{Method, StatusCode};
 use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
 use mime::{self, Mime};
+use net::content_filters::{
+    ContentFilterAction, ContentFilterList, ContentFilterRule, ContentFilters, ResourceType,
+};
 use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
 use net::hsts::HstsEntry;
+use net::protocols::{ProtocolHandler, ProtocolRegistry, ProtocolResponse, ProtocolResponseBody};
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
@@ -39,6 +43,8 @@ use net_traits::{
     ResourceTimingType,
 };
 use servo_arc::Arc as ServoArc;
+use servo_config::pref_util::PrefValue;
+use servo_config::prefs::pref_map;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use tokio_test::block_on;
 use uuid::Uuid;
@@ -153,6 +159,37 @@ fn test_fetch_aboutblank() {
     assert_eq!(*resp_body, ResponseBody::Done(vec![]));
 }
 
+#[test]
+fn test_fetch_about_config_only_applies_writes_from_embedder() {
+    let url = ServoUrl::parse("about:config?name=dom.testable_crash.enabled&value=true").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let initial = pref_map().get("dom.testable_crash.enabled");
+
+    // Neither a subresource load nor a navigation started by content may change preferences.
+    for destination in [Destination::None, Destination::Document] {
+        let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+            .origin(origin.clone())
+            .destination(destination)
+            .build();
+        let fetch_response = fetch(&mut request, None);
+        assert!(fetch_response.is_network_error());
+        assert_eq!(pref_map().get("dom.testable_crash.enabled"), initial);
+    }
+
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .origin(origin)
+        .destination(Destination::Document)
+        .initiated_by_embedder(true)
+        .build();
+    let fetch_response = fetch(&mut request, None);
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(
+        pref_map().get("dom.testable_crash.enabled"),
+        PrefValue::Bool(true)
+    );
+    pref_map().reset("dom.testable_crash.enabled").unwrap();
+}
+
 #[test]
 fn test_fetch_blob() {
     use net_traits::blob_url_store::BlobBuf;
@@ -237,6 +274,111 @@ fn test_fetch_blob() {
     );
 }
 
+#[test]
+fn test_fetch_registered_protocol() {
+    struct AppProtocolHandler;
+
+    impl ProtocolHandler for AppProtocolHandler {
+        fn load(&self, request: &Request) -> Result<ProtocolResponse, String> {
+            Ok(ProtocolResponse {
+                mime_type: mime::TEXT_PLAIN,
+                body: ProtocolResponseBody::Bytes(request.current_url().path().as_bytes().to_vec()),
+            })
+        }
+    }
+
+    let mut context = new_fetch_context(None, None, None);
+    let registry = ProtocolRegistry::default();
+    assert!(registry
+        .register("http", None, Arc::new(AppProtocolHandler))
+        .is_err());
+    registry
+        .register("app", None, Arc::new(AppProtocolHandler))
+        .unwrap();
+    context.protocols = Arc::new(registry);
+
+    let url = ServoUrl::parse("app://bundle/index.txt").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    request.mode = RequestMode::Navigate;
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+
+    assert!(!fetch_response.is_network_error());
+    let content_type: Mime = fetch_response
+        .headers
+        .typed_get::<ContentType>()
+        .unwrap()
+        .into();
+    assert_eq!(content_type, mime::TEXT_PLAIN);
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, b"/index.txt"),
+        _ => panic!(),
+    };
+}
+
+#[test]
+fn test_fetch_blocked_by_content_filter() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let rules = vec![
+        ContentFilterRule {
+            domain: Some("localhost".into()),
+            path_prefix: None,
+            resource_types: vec![ResourceType::Image, ResourceType::Script],
+            action: ContentFilterAction::Block,
+        },
+        ContentFilterRule {
+            domain: Some("localhost".into()),
+            path_prefix: Some("/allowed/".into()),
+            resource_types: vec![],
+            action: ContentFilterAction::Allow,
+        },
+    ];
+    assert!(ContentFilterList::compile(vec![ContentFilterRule {
+        domain: Some("not a domain".into()),
+        path_prefix: None,
+        resource_types: vec![],
+        action: ContentFilterAction::Block,
+    }])
+    .is_err());
+
+    let content_filters = Arc::new(ContentFilters::default());
+    content_filters.add("ads".into(), ContentFilterList::compile(rules).unwrap());
+    let mut context = new_fetch_context(None, None, None);
+    context.content_filters = content_filters.clone();
+
+    let webview_id = TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID);
+    let mut fetch = |path: &str, destination: Destination| {
+        let url = url.join(path).unwrap();
+        let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+            .origin(url.origin())
+            .destination(destination)
+            .target_webview_id(Some(webview_id))
+            .build();
+        fetch_with_context(&mut request, &mut context)
+    };
+
+    assert!(fetch("/banner.png", Destination::Image).is_network_error());
+    assert!(!fetch("/allowed/banner.png", Destination::Image).is_network_error());
+    assert!(!fetch("/index.html", Destination::Document).is_network_error());
+    assert_eq!(content_filters.blocked_request_count(webview_id), 1);
+
+    content_filters.forget_webview(webview_id);
+    assert_eq!(content_filters.blocked_request_count(webview_id), 0);
+
+    let _ = server.close();
+}
+
 #[test]
 fn test_file() {
     let path = Path::new("../../resources/servo.css")
@@ -769,6 +911,8 @@ fn test_fetch_with_hsts() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        content_filters: Arc::new(ContentFilters::default()),
     };
 
     // The server certificate is self-signed, so we need to add an override
@@ -828,6 +972,8 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        content_filters: Arc::new(ContentFilters::default()),
     };
 
     // The server certificate is self-signed, so we need to add an override
@@ -885,6 +1031,8 @@ fn test_fetch_self_signed() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        content_filters: Arc::new(ContentFilters::default()),
     };
 
     let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
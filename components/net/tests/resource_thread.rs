@@ -3,9 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::net::IpAddr;
+use std::sync::Arc;
 
 use ipc_channel::ipc;
 use net::connector::CACertificates;
+use net::content_filters::ContentFilters;
+use net::protocols::ProtocolRegistry;
 use net::resource_thread::new_core_resource_thread;
 use net::test::parse_hostsfile;
 use net_traits::CoreResourceMsg;
@@ -32,6 +35,8 @@ fn test_exit() {
         None,
         CACertificates::Default,
         false, /* ignore_certificate_errors */
+        Arc::new(ProtocolRegistry::default()),
+        Arc::new(ContentFilters::default()),
     );
     resource_thread.send(CoreResourceMsg::Exit(sender)).unwrap();
     receiver.recv().unwrap();
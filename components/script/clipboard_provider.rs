@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{ClipboardContents, EmbedderMsg};
 use ipc_channel::ipc::channel;
 use script_traits::{ScriptMsg, ScriptToConstellationChan};
 
@@ -15,17 +15,27 @@ pub trait ClipboardProvider {
 
 impl ClipboardProvider for ScriptToConstellationChan {
     fn clipboard_contents(&mut self) -> String {
-        let (tx, rx) = channel().unwrap();
-        self.send(ScriptMsg::ForwardToEmbedder(
-            EmbedderMsg::GetClipboardContents(tx),
-        ))
-        .unwrap();
-        rx.recv().unwrap()
+        get_clipboard_contents(self).text.unwrap_or_default()
     }
     fn set_clipboard_contents(&mut self, s: String) {
-        self.send(ScriptMsg::ForwardToEmbedder(
-            EmbedderMsg::SetClipboardContents(s),
-        ))
-        .unwrap();
+        set_clipboard_contents(self, ClipboardContents::from_text(s));
     }
 }
+
+/// Get the contents of the clipboard in all the formats the embedder has, blocking until it
+/// answers. This is shared by text editing and the Clipboard API.
+pub fn get_clipboard_contents(chan: &ScriptToConstellationChan) -> ClipboardContents {
+    let (tx, rx) = channel().unwrap();
+    chan.send(ScriptMsg::ForwardToEmbedder(
+        EmbedderMsg::GetClipboardContents(tx),
+    ))
+    .unwrap();
+    rx.recv().unwrap_or_default()
+}
+
+pub fn set_clipboard_contents(chan: &ScriptToConstellationChan, contents: ClipboardContents) {
+    chan.send(ScriptMsg::ForwardToEmbedder(
+        EmbedderMsg::SetClipboardContents(contents),
+    ))
+    .unwrap();
+}
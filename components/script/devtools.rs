@@ -4,12 +4,15 @@
 
 use std::rc::Rc;
 use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use base::id::PipelineId;
+use content_security_policy as csp;
 use devtools_traits::{
-    AutoMargins, ComputedNodeLayout, EvaluateJSReply, Modification, NodeInfo, TimelineMarker,
-    TimelineMarkerType,
+    AutoMargins, ComputedNodeLayout, ContentSecurityPolicyInfo, EvaluateJSReply, Modification,
+    NodeInfo, SecurityInfo, TimelineMarker, TimelineMarkerType,
 };
+use html5ever::{local_name, namespace_url, ns};
 use ipc_channel::ipc::IpcSender;
 use js::jsval::UndefinedValue;
 use js::rust::ToString;
@@ -22,6 +25,7 @@ use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::{jsstring_to_str, ConversionResult, FromJSValConvertible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::AnimationFrameCallback;
@@ -33,6 +37,17 @@ use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_thread::Documents;
 
+/// Whether a devtools server is running, shared by all the globals of this process.
+static SERVER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn handle_set_server_running(running: bool) {
+    SERVER_RUNNING.store(running, Ordering::Relaxed);
+}
+
+pub fn server_running() -> bool {
+    SERVER_RUNNING.load(Ordering::Relaxed)
+}
+
 #[allow(unsafe_code)]
 pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<EvaluateJSReply>) {
     // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
@@ -132,6 +147,87 @@ pub fn handle_get_children(
     };
 }
 
+pub fn handle_get_elements_from_point(
+    documents: &Documents,
+    pipeline: PipelineId,
+    x: f32,
+    y: f32,
+    reply: IpcSender<Vec<NodeInfo>>,
+) {
+    // The point comes from the devtools client, and may not be finite.
+    let point = Finite::new(x as f64).zip(Finite::new(y as f64));
+    let elements = match (documents.find_document(pipeline), point) {
+        (Some(document), Some((x, y))) => document.ElementsFromPoint(x, y),
+        _ => vec![],
+    };
+    let info = elements
+        .iter()
+        .map(|element| element.upcast::<Node>().summarize())
+        .collect();
+    reply.send(info).unwrap();
+}
+
+pub fn handle_get_security_info(
+    documents: &Documents,
+    pipeline: PipelineId,
+    reply: IpcSender<Option<SecurityInfo>>,
+) {
+    let Some(document) = documents.find_document(pipeline) else {
+        return reply.send(None).unwrap();
+    };
+    let window = document.window();
+    let global = window.upcast::<GlobalScope>();
+
+    let content_security_policies = document
+        .get_csp_list()
+        .map(|csp_list| {
+            csp_list
+                .0
+                .iter()
+                .map(|policy| ContentSecurityPolicyInfo {
+                    directives: policy
+                        .directive_set
+                        .iter()
+                        .map(|directive| {
+                            let mut serialized = directive.name.clone();
+                            for value in &directive.value {
+                                serialized.push(' ');
+                                serialized.push_str(value);
+                            }
+                            serialized
+                        })
+                        .collect(),
+                    report_only: matches!(policy.disposition, csp::PolicyDisposition::Report),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let window_proxy = window.window_proxy();
+    let sandbox = window_proxy
+        .frame_element()
+        .and_then(|element| element.get_attribute(&ns!(), &local_name!("sandbox")))
+        .map(|attr| {
+            attr.value()
+                .as_tokens()
+                .iter()
+                .map(|token| token.to_string())
+                .collect()
+        });
+
+    reply
+        .send(Some(SecurityInfo {
+            origin: global.origin().immutable().ascii_serialization(),
+            is_secure_context: global.is_secure_context(),
+            content_security_policies,
+            sandbox,
+            referrer_policy: document
+                .get_referrer_policy()
+                .map(|policy| policy.to_string()),
+        }))
+        .unwrap();
+}
+
 pub fn handle_get_layout(
     documents: &Documents,
     pipeline: PipelineId,
@@ -262,3 +358,9 @@ pub fn handle_reload(documents: &Documents, id: PipelineId) {
         win.Location().reload_without_origin_check();
     }
 }
+
+pub fn handle_simulate_print_media(documents: &Documents, id: PipelineId, enabled: bool) {
+    if let Some(win) = documents.find_window(id) {
+        win.set_print_media_simulation(enabled);
+    }
+}
@@ -127,8 +127,20 @@ impl DocumentLoader {
         request: RequestBuilder,
         fetch_target: IpcSender<FetchResponseMsg>,
     ) {
+        self.fetch_async_background_cancellable(request, fetch_target)
+            .ignore();
+    }
+
+    /// Initiate a new fetch that does not block the document load event, returning a canceller
+    /// that can stop it before the document unloads.
+    pub fn fetch_async_background_cancellable(
+        &mut self,
+        request: RequestBuilder,
+        fetch_target: IpcSender<FetchResponseMsg>,
+    ) -> FetchCanceller {
         let mut canceller = FetchCanceller::new();
         let cancel_receiver = canceller.initialize();
+        let fetch_canceller = canceller.duplicate();
         self.cancellers.push(canceller);
         self.resource_threads
             .sender()
@@ -137,6 +149,7 @@ impl DocumentLoader {
                 FetchChannels::ResponseMsg(fetch_target, Some(cancel_receiver)),
             ))
             .unwrap();
+        fetch_canceller
     }
 
     /// Mark an in-progress network request complete.
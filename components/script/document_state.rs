@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The state of a document that embedders save with `Servo::serialize_webview_state`, to
+//! restore it once the document is loaded again.
+
+use embedder_traits::{FormControlState, FormControlValue, SavedDocumentState};
+use html5ever::local_name;
+use log::debug;
+
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::htmlselectelement::HTMLSelectElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+
+/// A form control whose value the user can change.
+enum FormControl {
+    Input(DomRoot<HTMLInputElement>),
+    TextArea(DomRoot<HTMLTextAreaElement>),
+    Select(DomRoot<HTMLSelectElement>),
+}
+
+impl FormControl {
+    fn from_node(node: DomRoot<Node>) -> Option<FormControl> {
+        let node = match DomRoot::downcast::<HTMLInputElement>(node) {
+            Ok(input) => return Some(FormControl::Input(input)),
+            Err(node) => node,
+        };
+        let node = match DomRoot::downcast::<HTMLTextAreaElement>(node) {
+            Ok(textarea) => return Some(FormControl::TextArea(textarea)),
+            Err(node) => node,
+        };
+        DomRoot::downcast::<HTMLSelectElement>(node)
+            .ok()
+            .map(FormControl::Select)
+    }
+
+    fn element(&self) -> &Element {
+        match self {
+            FormControl::Input(input) => input.upcast(),
+            FormControl::TextArea(textarea) => textarea.upcast(),
+            FormControl::Select(select) => select.upcast(),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from(self.element().get_string_attribute(&local_name!("name")))
+    }
+
+    /// The value of the control, or `None` if it is not saved. The values of hidden inputs come
+    /// from the document, passwords are not written to disk and file selections can't be
+    /// restored without the user choosing the files again.
+    fn value(&self) -> Option<FormControlValue> {
+        match self {
+            FormControl::Input(input) => match input.input_type() {
+                InputType::Checkbox | InputType::Radio => {
+                    Some(FormControlValue::Checked(input.Checked()))
+                },
+                InputType::Button |
+                InputType::File |
+                InputType::Hidden |
+                InputType::Image |
+                InputType::Password |
+                InputType::Reset |
+                InputType::Submit => None,
+                _ => Some(FormControlValue::Text(String::from(input.Value()))),
+            },
+            FormControl::TextArea(textarea) => {
+                Some(FormControlValue::Text(String::from(textarea.Value())))
+            },
+            FormControl::Select(select) => Some(FormControlValue::SelectedOptions(
+                select
+                    .list_of_options()
+                    .enumerate()
+                    .filter(|(_, option)| option.Selected())
+                    .map(|(index, _)| index)
+                    .collect(),
+            )),
+        }
+    }
+
+    fn restore_value(&self, value: &FormControlValue) {
+        match (self, value) {
+            (FormControl::Input(input), FormControlValue::Checked(checked)) => {
+                input.SetChecked(*checked)
+            },
+            (FormControl::Input(input), FormControlValue::Text(text)) => {
+                // Setting the value only fails for file inputs, which are not saved.
+                let _ = input.SetValue(DOMString::from(text.clone()));
+            },
+            (FormControl::TextArea(textarea), FormControlValue::Text(text)) => {
+                textarea.SetValue(DOMString::from(text.clone()))
+            },
+            (FormControl::Select(select), FormControlValue::SelectedOptions(indices)) => {
+                for (index, option) in select.list_of_options().enumerate() {
+                    option.SetSelected(indices.contains(&index));
+                }
+            },
+            _ => debug!("Form control state does not match the type of the control"),
+        }
+    }
+}
+
+/// The form controls of the document whose value is saved, in tree order.
+fn saved_form_controls(document: &Document) -> impl Iterator<Item = FormControl> + '_ {
+    document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(FormControl::from_node)
+        .filter(|control| control.value().is_some())
+}
+
+pub fn collect_document_state(document: &Document) -> SavedDocumentState {
+    let viewport_origin = document.window().current_viewport().origin;
+    SavedDocumentState {
+        scroll_x: viewport_origin.x.to_f64_px(),
+        scroll_y: viewport_origin.y.to_f64_px(),
+        form_controls: saved_form_controls(document)
+            .filter_map(|control| {
+                Some(FormControlState {
+                    name: control.name(),
+                    value: control.value()?,
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Restore the state of a document that was loaded again. The saved form controls are matched
+/// with those of the document in tree order, until one of them has another name, which means
+/// that the document changed since the state was saved.
+pub fn restore_document_state(document: &Document, state: &SavedDocumentState) {
+    let controls: Vec<FormControl> = saved_form_controls(document).collect();
+    for (control, control_state) in controls.iter().zip(&state.form_controls) {
+        if control.name() != control_state.name {
+            debug!(
+                "Not restoring form control {:?}, the document changed",
+                control_state.name
+            );
+            break;
+        }
+        control.restore_value(&control_state.value);
+    }
+
+    document
+        .window()
+        .scroll(state.scroll_x, state.scroll_y, ScrollBehavior::Instant);
+}
@@ -0,0 +1,142 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{ExceptionStackBehavior, Heap};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_SetPendingException;
+use js::rust::HandleValue;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::scheduler::Scheduler;
+use crate::script_runtime::JSContext;
+
+/// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
+#[derive(JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+pub enum AbortAlgorithm {
+    /// Remove the task with the given id from the queue of the scheduler and reject its
+    /// promise.
+    ScheduledTask(Dom<Scheduler>, u64),
+}
+
+impl AbortAlgorithm {
+    fn run(&self, reason: HandleValue) {
+        match self {
+            AbortAlgorithm::ScheduledTask(scheduler, task_id) => {
+                scheduler.abort_task(*task_id, reason)
+            },
+        }
+    }
+}
+
+/// <https://dom.spec.whatwg.org/#interface-AbortSignal>
+#[dom_struct]
+pub struct AbortSignal {
+    eventtarget: EventTarget,
+    /// <https://dom.spec.whatwg.org/#abortsignal-abort-reason>
+    #[ignore_malloc_size_of = "mozjs"]
+    abort_reason: Heap<JSVal>,
+    abort_algorithms: DomRefCell<Vec<AbortAlgorithm>>,
+}
+
+impl AbortSignal {
+    pub fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            eventtarget: EventTarget::new_inherited(),
+            abort_reason: Heap::default(),
+            abort_algorithms: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<AbortSignal> {
+        reflect_dom_object(Box::new(AbortSignal::new_inherited()), global)
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-abort>
+    #[allow(non_snake_case)]
+    pub fn Abort(cx: JSContext, global: &GlobalScope, reason: HandleValue) -> DomRoot<AbortSignal> {
+        let signal = AbortSignal::new(global);
+        signal.signal_abort(cx, reason);
+        signal
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-aborted>
+    pub fn aborted(&self) -> bool {
+        !self.abort_reason.get().is_undefined()
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-add>
+    #[allow(crown::unrooted_must_root)]
+    pub fn add_algorithm(&self, algorithm: AbortAlgorithm) {
+        if self.aborted() {
+            return;
+        }
+        self.abort_algorithms.borrow_mut().push(algorithm);
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-signal-abort>
+    #[allow(unsafe_code)]
+    pub fn signal_abort(&self, cx: JSContext, reason: HandleValue) {
+        // Step 1. If signal is aborted, then return.
+        if self.aborted() {
+            return;
+        }
+
+        // Step 2. Set signal’s abort reason to reason if it is given; otherwise to a new
+        // "AbortError" DOMException.
+        rooted!(in(*cx) let mut abort_reason = reason.get());
+        if reason.is_undefined() {
+            let exception = DOMException::new(&self.global(), DOMErrorName::AbortError);
+            unsafe { exception.to_jsval(*cx, abort_reason.handle_mut()) };
+        }
+        self.abort_reason.set(abort_reason.get());
+
+        // Step 3-5. There are no dependent signals yet, so only signal itself is aborted.
+        // Step 6. Run the abort steps for signal: run its abort algorithms and empty them, then
+        // fire an event named abort at it. None of the algorithms run script, so they can't
+        // add more algorithms while they run.
+        for algorithm in self.abort_algorithms.borrow().iter() {
+            algorithm.run(abort_reason.handle());
+        }
+        self.abort_algorithms.borrow_mut().clear();
+        self.upcast::<EventTarget>().fire_event(atom!("abort"));
+    }
+}
+
+impl AbortSignalMethods for AbortSignal {
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-aborted>
+    fn Aborted(&self) -> bool {
+        self.aborted()
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-reason>
+    fn Reason(&self, _cx: JSContext) -> JSVal {
+        self.abort_reason.get()
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-throwifaborted>
+    #[allow(unsafe_code)]
+    fn ThrowIfAborted(&self) -> ErrorResult {
+        if !self.aborted() {
+            return Ok(());
+        }
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let reason = self.abort_reason.get());
+        unsafe { JS_SetPendingException(*cx, reason.handle(), ExceptionStackBehavior::Capture) };
+        Err(Error::JSFailed)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
@@ -2,9 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+
+use crossbeam_channel::{Receiver, Sender as BoundedSender};
 use dom_struct::dom_struct;
-use ipc_channel::ipc::{self, IpcReceiver};
-use ipc_channel::router::ROUTER;
 use js::rust::{CustomAutoRooterGuard, HandleObject};
 use js::typedarray::{Float32Array, Uint8Array};
 use servo_media::audio::analyser_node::AnalysisEngine;
@@ -20,20 +24,78 @@ use crate::dom::bindings::codegen::Bindings::AnalyserNodeBinding::{
 use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::{
     ChannelCountMode, ChannelInterpretation,
 };
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContext_Binding::BaseAudioContextMethods;
+use crate::dom::bindings::codegen::Bindings::OfflineAudioContextBinding::OfflineAudioContextMethods;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::offlineaudiocontext::OfflineAudioContext;
 use crate::dom::window::Window;
-use crate::task_source::TaskSource;
+
+/// <https://webaudio.github.io/web-audio-api/#dom-analysernode-fftsize>
+const MAX_FFT_SIZE: u32 = 32768;
+
+/// <https://webaudio.github.io/web-audio-api/#render-quantum-size>
+const RENDER_QUANTUM_SIZE: u32 = 128;
+
+/// Changes made by script to the parameters of the analysis running on the audio thread.
+enum AnalysisUpdate {
+    FftSize(usize),
+    SmoothingTimeConstant(f64),
+}
+
+/// The result of analysing the most recent render quantum, computed on the audio thread.
+struct AnalysisData {
+    /// The smoothed frequency data in dB, `fftSize / 2` values.
+    frequency: Vec<f32>,
+    /// The last `fftSize` input samples, down-mixed to mono.
+    time_domain: Vec<f32>,
+}
+
+impl AnalysisData {
+    /// The data of an analyser that has not rendered anything yet, which is silence.
+    fn silent(fft_size: usize) -> AnalysisData {
+        AnalysisData {
+            frequency: vec![f32::NEG_INFINITY; fft_size / 2],
+            time_domain: vec![0.; fft_size],
+        }
+    }
+}
 
 #[dom_struct]
 pub struct AnalyserNode {
     node: AudioNode,
-    #[ignore_malloc_size_of = "Defined in servo-media"]
+    fft_size: Cell<u32>,
+    min_decibels: Cell<f64>,
+    max_decibels: Cell<f64>,
+    smoothing_time_constant: Cell<f64>,
+    /// Sends parameter changes to the analysis engine, which lives on the audio thread.
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    updates: Sender<AnalysisUpdate>,
+    /// The slot in which the audio thread publishes its latest analysis. It holds at most one.
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    published: Receiver<AnalysisData>,
+    /// Hands consumed data back to the audio thread, so that it does not have to allocate
+    /// new buffers while rendering.
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    recycled: BoundedSender<AnalysisData>,
+    /// How many more render quanta the audio thread analyses. Reading the data resets it, so
+    /// that the FFT only runs while script reads the data.
+    #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
-    engine: DomRefCell<AnalysisEngine>,
+    quanta_to_analyse: Arc<AtomicU32>,
+    /// The number of render quanta in about a second, which is how long the audio thread
+    /// keeps analysing after the data was last read.
+    quanta_per_read: u32,
+    /// The most recent data published by the audio thread.
+    #[ignore_malloc_size_of = "Vectors of floats"]
+    #[no_trace]
+    latest: DomRefCell<AnalysisData>,
 }
 
 impl AnalyserNode {
@@ -42,13 +104,13 @@ impl AnalyserNode {
         _: &Window,
         context: &BaseAudioContext,
         options: &AnalyserOptions,
-    ) -> Fallible<(AnalyserNode, IpcReceiver<Block>)> {
+    ) -> Fallible<AnalyserNode> {
         let node_options =
             options
                 .parent
                 .unwrap_or(2, ChannelCountMode::Max, ChannelInterpretation::Speakers);
 
-        if options.fftSize > 32768 ||
+        if options.fftSize > MAX_FFT_SIZE ||
             options.fftSize < 32 ||
             (options.fftSize & (options.fftSize - 1) != 0)
         {
@@ -63,9 +125,71 @@ impl AnalyserNode {
             return Err(Error::IndexSize);
         }
 
-        let (send, rcv) = ipc::channel().unwrap();
-        let callback = move |block| {
-            send.send(block).unwrap();
+        let (updates, pending_updates) = mpsc::channel();
+
+        // The audio thread and script pass three buffers around, so that neither allocates
+        // nor waits for the other: the latest analysis is published in a slot that holds a
+        // single one, a stale analysis is taken back out of it before publishing a new one,
+        // and script hands back the analysis it replaces.
+        let fft_size = options.fftSize as usize;
+        let (publisher, published) = crossbeam_channel::bounded(1);
+        let stale_data = published.clone();
+        let (recycled, spare_data) = crossbeam_channel::bounded(2);
+        for _ in 0..2 {
+            let _ = recycled.try_send(AnalysisData::silent(fft_size));
+        }
+
+        // The FFT is computed on the audio thread, but only while script reads its result. An
+        // offline context also analyses its last render quantum, since that is what script
+        // can read once rendering is complete.
+        let quanta_per_read = (*context.SampleRate() as u32 / RENDER_QUANTUM_SIZE).max(1);
+        let quanta_to_analyse = Arc::new(AtomicU32::new(0));
+        let quanta_to_analyse_ = quanta_to_analyse.clone();
+        let last_quantum = context
+            .downcast::<OfflineAudioContext>()
+            .map(|context| context.Length().div_ceil(RENDER_QUANTUM_SIZE));
+        let mut quanta_rendered = 0;
+
+        let mut engine = AnalysisEngine::new(
+            fft_size,
+            *options.smoothingTimeConstant,
+            *options.minDecibels,
+            *options.maxDecibels,
+        );
+        let callback = move |block: Block| {
+            for update in pending_updates.try_iter() {
+                match update {
+                    AnalysisUpdate::FftSize(fft_size) => engine.set_fft_size(fft_size),
+                    AnalysisUpdate::SmoothingTimeConstant(smoothing) => {
+                        engine.set_smoothing_constant(smoothing)
+                    },
+                }
+            }
+            engine.push(block);
+
+            quanta_rendered += 1;
+            let is_read = quanta_to_analyse_
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |quanta| {
+                    quanta.checked_sub(1)
+                })
+                .is_ok();
+            if !is_read && last_quantum != Some(quanta_rendered) {
+                return;
+            }
+            let Ok(mut data) = stale_data.try_recv().or_else(|_| spare_data.try_recv()) else {
+                return;
+            };
+
+            // The buffers only need to grow when script makes fftSize larger.
+            let fft_size = engine.get_fft_size();
+            data.frequency.resize(fft_size / 2, f32::NEG_INFINITY);
+            data.time_domain.resize(fft_size, 0.);
+            engine.fill_frequency_data(&mut data.frequency);
+            engine.fill_time_domain_data(&mut data.time_domain);
+
+            // The slot was emptied above and only script takes from it otherwise, so this
+            // only fails once the node is gone.
+            let _ = publisher.try_send(data);
         };
 
         let node = AudioNode::new_inherited(
@@ -76,19 +200,19 @@ impl AnalyserNode {
             1, // outputs
         )?;
 
-        let engine = AnalysisEngine::new(
-            options.fftSize as usize,
-            *options.smoothingTimeConstant,
-            *options.minDecibels,
-            *options.maxDecibels,
-        );
-        Ok((
-            AnalyserNode {
-                node,
-                engine: DomRefCell::new(engine),
-            },
-            rcv,
-        ))
+        Ok(AnalyserNode {
+            node,
+            fft_size: Cell::new(options.fftSize),
+            min_decibels: Cell::new(*options.minDecibels),
+            max_decibels: Cell::new(*options.maxDecibels),
+            smoothing_time_constant: Cell::new(*options.smoothingTimeConstant),
+            updates,
+            published,
+            recycled,
+            quanta_to_analyse,
+            quanta_per_read,
+            latest: DomRefCell::new(AnalysisData::silent(options.fftSize as usize)),
+        })
     }
 
     pub fn new(
@@ -106,27 +230,8 @@ impl AnalyserNode {
         context: &BaseAudioContext,
         options: &AnalyserOptions,
     ) -> Fallible<DomRoot<AnalyserNode>> {
-        let (node, recv) = AnalyserNode::new_inherited(window, context, options)?;
-        let object = reflect_dom_object_with_proto(Box::new(node), window, proto);
-        let (source, canceller) = window
-            .task_manager()
-            .dom_manipulation_task_source_with_canceller();
-        let this = Trusted::new(&*object);
-
-        ROUTER.add_route(
-            recv.to_opaque(),
-            Box::new(move |block| {
-                let this = this.clone();
-                let _ = source.queue_with_canceller(
-                    task!(append_analysis_block: move || {
-                        let this = this.root();
-                        this.push_block(block.to().unwrap())
-                    }),
-                    &canceller,
-                );
-            }),
-        );
-        Ok(object)
+        let node = AnalyserNode::new_inherited(window, context, options)?;
+        Ok(reflect_dom_object_with_proto(Box::new(node), window, proto))
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-analysernode>
@@ -140,8 +245,18 @@ impl AnalyserNode {
         AnalyserNode::new_with_proto(window, proto, context, options)
     }
 
-    pub fn push_block(&self, block: Block) {
-        self.engine.borrow_mut().push(block)
+    /// Make the data last published by the audio thread the current one, and hand the one it
+    /// replaces back to the audio thread for reuse.
+    ///
+    /// The audio thread only analyses its input while the data is read, so the first read
+    /// after a pause gets the data from when the analysis stopped.
+    fn update_latest_data(&self) {
+        self.quanta_to_analyse
+            .store(self.quanta_per_read, Ordering::Relaxed);
+        if let Ok(data) = self.published.try_recv() {
+            let stale = std::mem::replace(&mut *self.latest.borrow_mut(), data);
+            let _ = self.recycled.try_send(stale);
+        }
     }
 }
 
@@ -152,7 +267,11 @@ impl AnalyserNodeMethods for AnalyserNode {
         // Invariant to maintain: No JS code that may touch the array should
         // run whilst we're writing to it
         let dest = unsafe { array.as_mut_slice() };
-        self.engine.borrow_mut().fill_frequency_data(dest);
+        self.update_latest_data();
+        let latest = self.latest.borrow();
+        for (dest, value) in dest.iter_mut().zip(&latest.frequency) {
+            *dest = *value;
+        }
     }
 
     #[allow(unsafe_code)]
@@ -161,7 +280,15 @@ impl AnalyserNodeMethods for AnalyserNode {
         // Invariant to maintain: No JS code that may touch the array should
         // run whilst we're writing to it
         let dest = unsafe { array.as_mut_slice() };
-        self.engine.borrow_mut().fill_byte_frequency_data(dest);
+        self.update_latest_data();
+        let min_decibels = self.min_decibels.get();
+        let scale = 255. / (self.max_decibels.get() - min_decibels);
+        let latest = self.latest.borrow();
+        for (dest, value) in dest.iter_mut().zip(&latest.frequency) {
+            *dest = (scale * (*value as f64 - min_decibels))
+                .floor()
+                .clamp(0., 255.) as u8;
+        }
     }
 
     #[allow(unsafe_code)]
@@ -170,7 +297,11 @@ impl AnalyserNodeMethods for AnalyserNode {
         // Invariant to maintain: No JS code that may touch the array should
         // run whilst we're writing to it
         let dest = unsafe { array.as_mut_slice() };
-        self.engine.borrow().fill_time_domain_data(dest);
+        self.update_latest_data();
+        let latest = self.latest.borrow();
+        for (dest, value) in dest.iter_mut().zip(&latest.time_domain) {
+            *dest = *value;
+        }
     }
 
     #[allow(unsafe_code)]
@@ -179,21 +310,26 @@ impl AnalyserNodeMethods for AnalyserNode {
         // Invariant to maintain: No JS code that may touch the array should
         // run whilst we're writing to it
         let dest = unsafe { array.as_mut_slice() };
-        self.engine.borrow().fill_byte_time_domain_data(dest);
+        self.update_latest_data();
+        let latest = self.latest.borrow();
+        for (dest, value) in dest.iter_mut().zip(&latest.time_domain) {
+            *dest = (128. * (1. + value)).floor().clamp(0., 255.) as u8;
+        }
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-fftsize>
     fn SetFftSize(&self, value: u32) -> Fallible<()> {
-        if !(32..=32768).contains(&value) || (value & (value - 1) != 0) {
+        if !(32..=MAX_FFT_SIZE).contains(&value) || (value & (value - 1) != 0) {
             return Err(Error::IndexSize);
         }
-        self.engine.borrow_mut().set_fft_size(value as usize);
+        self.fft_size.set(value);
+        let _ = self.updates.send(AnalysisUpdate::FftSize(value as usize));
         Ok(())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-fftsize>
     fn FftSize(&self) -> u32 {
-        self.engine.borrow().get_fft_size() as u32
+        self.fft_size.get()
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-frequencybincount>
@@ -203,35 +339,35 @@ impl AnalyserNodeMethods for AnalyserNode {
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-mindecibels>
     fn MinDecibels(&self) -> Finite<f64> {
-        Finite::wrap(self.engine.borrow().get_min_decibels())
+        Finite::wrap(self.min_decibels.get())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-mindecibels>
     fn SetMinDecibels(&self, value: Finite<f64>) -> Fallible<()> {
-        if *value >= self.engine.borrow().get_max_decibels() {
+        if *value >= self.max_decibels.get() {
             return Err(Error::IndexSize);
         }
-        self.engine.borrow_mut().set_min_decibels(*value);
+        self.min_decibels.set(*value);
         Ok(())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-maxdecibels>
     fn MaxDecibels(&self) -> Finite<f64> {
-        Finite::wrap(self.engine.borrow().get_max_decibels())
+        Finite::wrap(self.max_decibels.get())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-maxdecibels>
     fn SetMaxDecibels(&self, value: Finite<f64>) -> Fallible<()> {
-        if *value <= self.engine.borrow().get_min_decibels() {
+        if *value <= self.min_decibels.get() {
             return Err(Error::IndexSize);
         }
-        self.engine.borrow_mut().set_max_decibels(*value);
+        self.max_decibels.set(*value);
         Ok(())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-smoothingtimeconstant>
     fn SmoothingTimeConstant(&self) -> Finite<f64> {
-        Finite::wrap(self.engine.borrow().get_smoothing_constant())
+        Finite::wrap(self.smoothing_time_constant.get())
     }
 
     /// <https://webaudio.github.io/web-audio-api/#dom-analysernode-smoothingtimeconstant>
@@ -239,7 +375,10 @@ impl AnalyserNodeMethods for AnalyserNode {
         if *value < 0. || *value > 1. {
             return Err(Error::IndexSize);
         }
-        self.engine.borrow_mut().set_smoothing_constant(*value);
+        self.smoothing_time_constant.set(*value);
+        let _ = self
+            .updates
+            .send(AnalysisUpdate::SmoothingTimeConstant(*value));
         Ok(())
     }
 }
@@ -6,23 +6,36 @@ use std::rc::Rc;
 
 use base::id::PipelineId;
 use dom_struct::dom_struct;
+use embedder_traits::{AudioOutputDeviceDescription, EmbedderMsg};
+use ipc_channel::ipc;
 use js::rust::HandleObject;
+use servo_atoms::Atom;
 use servo_media::audio::context::{LatencyCategory, ProcessingState, RealTimeAudioContextOptions};
+use servo_media::streams::device_monitor::MediaDeviceKind;
+use servo_media::ServoMedia;
 
+use crate::dom::audiosinkinfo::AudioSinkInfo;
 use crate::dom::baseaudiocontext::{BaseAudioContext, BaseAudioContextOptions};
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AudioContextBinding::{
     AudioContextLatencyCategory, AudioContextMethods, AudioContextOptions, AudioTimestamp,
 };
 use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::AudioNodeOptions;
+use crate::dom::bindings::codegen::Bindings::AudioSinkInfoBinding::AudioSinkInfoMethods;
 use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::AudioContextState;
 use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContext_Binding::BaseAudioContextMethods;
-use crate::dom::bindings::codegen::UnionTypes::AudioContextLatencyCategoryOrDouble;
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    AudioContextLatencyCategoryOrDouble, StringOrAudioSinkInfo, StringOrAudioSinkOptions,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::mediaelementaudiosourcenode::MediaElementAudioSourceNode;
 use crate::dom::mediastream::MediaStream;
@@ -43,6 +56,15 @@ pub struct AudioContext {
     base_latency: f64,
     /// <https://webaudio.github.io/web-audio-api/#dom-audiocontext-outputlatency>
     output_latency: f64,
+    /// <https://webaudio.github.io/web-audio-api/#dom-audiocontext-sink-id-slot>, when it
+    /// identifies an audio output device, the empty string being the default one.
+    sink_id: DomRefCell<DOMString>,
+    /// <https://webaudio.github.io/web-audio-api/#dom-audiocontext-sink-id-slot>, when the
+    /// context renders without any audio output device. Takes precedence over `sink_id`.
+    sink_info: MutNullableDom<AudioSinkInfo>,
+    /// The nodes routing the audio of media elements to this context, which is output directly
+    /// again once the context is closed.
+    media_element_sources: DomRefCell<Vec<Dom<MediaElementAudioSourceNode>>>,
 }
 
 impl AudioContext {
@@ -75,6 +97,9 @@ impl AudioContext {
             latency_hint,
             base_latency: 0.,   // TODO
             output_latency: 0., // TODO
+            sink_id: Default::default(),
+            sink_info: Default::default(),
+            media_element_sources: Default::default(),
         }
     }
 
@@ -87,6 +112,7 @@ impl AudioContext {
         let pipeline_id = window.pipeline_id();
         let context = AudioContext::new_inherited(options, pipeline_id);
         let context = reflect_dom_object_with_proto(Box::new(context), window, proto);
+        window.track_audio_context(&context);
         context.resume();
         context
     }
@@ -112,6 +138,104 @@ impl AudioContext {
     pub fn base(&self) -> DomRoot<BaseAudioContext> {
         DomRoot::from_ref(&self.context)
     }
+
+    pub fn track_media_element_source(&self, node: &MediaElementAudioSourceNode) {
+        self.media_element_sources
+            .borrow_mut()
+            .push(Dom::from_ref(node));
+    }
+
+    /// Whether this context renders to an audio output device, rather than to a sink of type
+    /// "none".
+    pub fn has_audio_output(&self) -> bool {
+        self.sink_info.get().is_none()
+    }
+
+    /// Whether the given sink is the one this context currently renders to.
+    fn is_current_sink(&self, sink_id: &StringOrAudioSinkOptions) -> bool {
+        match (sink_id, self.sink_info.get()) {
+            (StringOrAudioSinkOptions::String(id), None) => *id == *self.sink_id.borrow(),
+            (StringOrAudioSinkOptions::AudioSinkOptions(options), Some(info)) => {
+                options.type_ == info.Type()
+            },
+            _ => false,
+        }
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#sink-identifier-validation>
+    fn validate_sink_id(&self, sink_id: &StringOrAudioSinkOptions) -> ErrorResult {
+        // Steps 1-3.
+        if !self
+            .global()
+            .as_window()
+            .Document()
+            .is_allowed_to_use_feature("speaker-selection")
+        {
+            return Err(Error::NotAllowed);
+        }
+
+        // Step 4. The type of AudioSinkOptions was already validated by the bindings.
+        let StringOrAudioSinkOptions::String(ref id) = *sink_id else {
+            return Ok(());
+        };
+        if id.is_empty() {
+            return Ok(());
+        }
+        // The devices that the embedder does not expose are reported as if they did not exist,
+        // so that pages cannot probe for them.
+        let exposed = self.exposed_audio_output_devices();
+        if !exposed.iter().any(|device_id| **id == **device_id) {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    /// The ids of the audio output devices enumerated by servo-media that the embedder
+    /// exposes to the origin of this context.
+    fn exposed_audio_output_devices(&self) -> Vec<String> {
+        let devices: Vec<_> = ServoMedia::get()
+            .unwrap()
+            .get_device_monitor()
+            .enumerate_devices()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|device| matches!(device.kind, MediaDeviceKind::AudioOutput))
+            .map(|device| AudioOutputDeviceDescription {
+                device_id: device.device_id,
+                label: device.label,
+            })
+            .collect();
+        if devices.is_empty() {
+            return Vec::new();
+        }
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let origin = global.origin().immutable().clone();
+        global.send_to_embedder(EmbedderMsg::GetAudioOutputDevices(origin, devices, sender));
+        receiver.recv().unwrap_or_else(|error| {
+            warn!("Failed to receive audio output devices from embedder ({error:?}).");
+            Vec::new()
+        })
+    }
+
+    /// Switch to the given sink, as queued by step 5 of `setSinkId()`.
+    fn set_sink(&self, sink_id: StringOrAudioSinkOptions) {
+        match sink_id {
+            StringOrAudioSinkOptions::String(id) => {
+                self.sink_info.set(None);
+                *self.sink_id.borrow_mut() = id;
+            },
+            StringOrAudioSinkOptions::AudioSinkOptions(options) => {
+                let info = AudioSinkInfo::new(self.global().as_window(), options.type_);
+                self.sink_info.set(Some(&info));
+            },
+        }
+        // Stop or start rendering if the context is running, depending on whether there is
+        // something to render to.
+        self.context.apply_media_policy();
+        self.global().as_window().update_audible();
+    }
 }
 
 impl AudioContextMethods for AudioContext {
@@ -125,6 +249,63 @@ impl AudioContextMethods for AudioContext {
         Finite::wrap(self.output_latency)
     }
 
+    /// <https://webaudio.github.io/web-audio-api/#dom-audiocontext-sinkid>
+    fn SinkId(&self) -> StringOrAudioSinkInfo {
+        match self.sink_info.get() {
+            Some(info) => StringOrAudioSinkInfo::AudioSinkInfo(info),
+            None => StringOrAudioSinkInfo::String(self.sink_id.borrow().clone()),
+        }
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-onsinkchange
+    event_handler!(sinkchange, GetOnsinkchange, SetOnsinkchange);
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-audiocontext-setsinkid>
+    fn SetSinkId(&self, sink_id: StringOrAudioSinkOptions, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+
+        // Steps 1-2.
+        if self.is_current_sink(&sink_id) {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Steps 3-4.
+        if let Err(error) = self.validate_sink_id(&sink_id) {
+            promise.reject_error(error);
+            return promise;
+        }
+
+        if self.context.control_thread_state() == ProcessingState::Closed {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // servo-media always renders to the default audio output device, so the only sinks
+        // that can be switched to are that device and no device at all.
+        if matches!(sink_id, StringOrAudioSinkOptions::String(ref id) if !id.is_empty()) {
+            promise.reject_error(Error::InvalidAccess);
+            return promise;
+        }
+
+        // Steps 5-7.
+        let window = DomRoot::downcast::<Window>(self.global()).unwrap();
+        let task_source = window.task_manager().dom_manipulation_task_source();
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let context = Trusted::new(self);
+        let _ = task_source.queue(
+            task!(set_sink_id: move || {
+                let context = context.root();
+                context.set_sink(sink_id);
+                trusted_promise.root().resolve_native(&());
+                context.upcast::<EventTarget>().fire_event(Atom::from("sinkchange"));
+            }),
+            window.upcast(),
+        );
+
+        promise
+    }
+
     // https://webaudio.github.io/web-audio-api/#dom-audiocontext-outputlatency
     fn GetOutputTimestamp(&self) -> AudioTimestamp {
         // TODO
@@ -225,6 +406,16 @@ impl AudioContextMethods for AudioContext {
                         let base_context = base_context.root();
                         let context = context.root();
                         let promise = trusted_promise.root();
+                        let media_element_sources: Vec<_> = context
+                            .media_element_sources
+                            .borrow()
+                            .iter()
+                            .map(|node| DomRoot::from_ref(&**node))
+                            .collect();
+                        context.media_element_sources.borrow_mut().clear();
+                        for node in media_element_sources {
+                            node.restore_media_element_output();
+                        }
                         promise.resolve_native(&());
                         if base_context.State() != AudioContextState::Closed {
                             base_context.set_state_attribute(AudioContextState::Closed);
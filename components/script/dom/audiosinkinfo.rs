@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::AudioContextBinding::AudioSinkType;
+use crate::dom::bindings::codegen::Bindings::AudioSinkInfoBinding::AudioSinkInfoMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+
+/// <https://webaudio.github.io/web-audio-api/#AudioSinkInfo>
+#[dom_struct]
+pub struct AudioSinkInfo {
+    reflector_: Reflector,
+    type_: AudioSinkType,
+}
+
+impl AudioSinkInfo {
+    fn new_inherited(type_: AudioSinkType) -> AudioSinkInfo {
+        AudioSinkInfo {
+            reflector_: Reflector::new(),
+            type_,
+        }
+    }
+
+    pub fn new(window: &Window, type_: AudioSinkType) -> DomRoot<AudioSinkInfo> {
+        reflect_dom_object(Box::new(AudioSinkInfo::new_inherited(type_)), window)
+    }
+}
+
+impl AudioSinkInfoMethods for AudioSinkInfo {
+    /// <https://webaudio.github.io/web-audio-api/#dom-audiosinkinfo-type>
+    fn Type(&self) -> AudioSinkType {
+        self.type_
+    }
+}
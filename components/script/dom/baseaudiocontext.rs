@@ -24,6 +24,7 @@ use uuid::Uuid;
 use crate::dom::analysernode::AnalyserNode;
 use crate::dom::audiobuffer::AudioBuffer;
 use crate::dom::audiobuffersourcenode::AudioBufferSourceNode;
+use crate::dom::audiocontext::AudioContext;
 use crate::dom::audiodestinationnode::AudioDestinationNode;
 use crate::dom::audiolistener::AudioListener;
 use crate::dom::audionode::MAX_CHANNEL_COUNT;
@@ -58,6 +59,7 @@ use crate::dom::constantsourcenode::ConstantSourceNode;
 use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gainnode::GainNode;
+use crate::dom::offlineaudiocontext::OfflineAudioContext;
 use crate::dom::oscillatornode::OscillatorNode;
 use crate::dom::pannernode::PannerNode;
 use crate::dom::promise::Promise;
@@ -157,7 +159,47 @@ impl BaseAudioContext {
 
     // https://webaudio.github.io/web-audio-api/#allowed-to-start
     pub fn is_allowed_to_start(&self) -> bool {
-        self.state.get() == AudioContextState::Suspended
+        self.state.get() == AudioContextState::Suspended &&
+            (self.is::<OfflineAudioContext>() ||
+                self.global()
+                    .as_window()
+                    .is_allowed_to_start_media(self.is_muted_by_webview()))
+    }
+
+    /// Whether the output of this context is silenced because the embedder muted its webview.
+    /// The rendering thread is then kept suspended, without the page being able to tell.
+    fn is_muted_by_webview(&self) -> bool {
+        !self.is::<OfflineAudioContext>() && self.global().as_window().media_policy().muted
+    }
+
+    /// Whether there is nothing to hear from this context, either because the embedder muted its
+    /// webview or because the page chose to render without an audio output device.
+    fn is_silenced(&self) -> bool {
+        self.is_muted_by_webview() ||
+            self.downcast::<AudioContext>()
+                .is_some_and(|context| !context.has_audio_output())
+    }
+
+    /// Whether this context is rendering audio that the user can hear.
+    pub fn is_audible(&self) -> bool {
+        self.state.get() == AudioContextState::Running && !self.is_silenced()
+    }
+
+    /// Suspend or resume the rendering thread of a running context when it gets silenced or
+    /// unsilenced, see `is_silenced`.
+    pub fn apply_media_policy(&self) {
+        if self.state.get() != AudioContextState::Running {
+            return;
+        }
+        let audio_context_impl = self.audio_context_impl.lock().unwrap();
+        let result = if self.is_silenced() {
+            audio_context_impl.suspend()
+        } else {
+            audio_context_impl.resume()
+        };
+        if result.is_err() {
+            warn!("Could not apply the media policy to an audio context");
+        }
     }
 
     fn push_pending_resume_promise(&self, promise: &Rc<Promise>) {
@@ -219,6 +261,7 @@ impl BaseAudioContext {
     /// Set audio context state
     pub fn set_state_attribute(&self, state: AudioContextState) {
         self.state.set(state);
+        self.global().as_window().update_audible();
     }
 
     pub fn resume(&self) {
@@ -227,8 +270,13 @@ impl BaseAudioContext {
         let task_source = window.task_manager().dom_manipulation_task_source();
         let this = Trusted::new(self);
         // Set the rendering thread state to 'running' and start
-        // rendering the audio graph.
-        match self.audio_context_impl.lock().unwrap().resume() {
+        // rendering the audio graph, unless there is nothing to hear.
+        let result = if self.is_silenced() {
+            Ok(())
+        } else {
+            self.audio_context_impl.lock().unwrap().resume()
+        };
+        match result {
             Ok(()) => {
                 self.take_pending_resume_promises(Ok(()));
                 let _ = task_source.queue(
@@ -236,7 +284,9 @@ impl BaseAudioContext {
                         let this = this.root();
                         this.fulfill_in_flight_resume_promises(|| {
                             if this.state.get() != AudioContextState::Running {
-                                this.state.set(AudioContextState::Running);
+                                this.set_state_attribute(AudioContextState::Running);
+                                // The webview may have been muted or unmuted meanwhile.
+                                this.apply_media_policy();
                                 let window = DomRoot::downcast::<Window>(this.global()).unwrap();
                                 window.task_manager().dom_manipulation_task_source().queue_simple_event(
                                     this.upcast(),
@@ -292,6 +342,15 @@ impl BaseAudioContextMethods for BaseAudioContext {
             return promise;
         }
 
+        // An offline context only starts rendering through `startRendering()`.
+        if self
+            .downcast::<OfflineAudioContext>()
+            .is_some_and(|context| !context.rendering_started())
+        {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
         // Step 3.
         if self.state.get() == AudioContextState::Running {
             promise.resolve_native(&());
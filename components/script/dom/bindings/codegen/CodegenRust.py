@@ -6090,7 +6090,7 @@ def finalizeHook(descriptor, hookName, context):
     elif descriptor.weakReferenceable:
         release = "finalize_weak_referenceable(obj, this);"
     else:
-        release = "finalize_common(this);"
+        release = "finalize_common(obj, this);"
     return release
 
 
@@ -87,6 +87,8 @@ pub enum Error {
     NotReadable,
     /// OperationError DOMException
     Operation,
+    /// NotAllowedError DOMException
+    NotAllowed,
 
     /// TypeError JavaScript Error
     Type(String),
@@ -140,6 +142,7 @@ pub fn throw_dom_exception(cx: SafeJSContext, global: &GlobalScope, result: Erro
         Error::InvalidModification => DOMErrorName::InvalidModificationError,
         Error::NotReadable => DOMErrorName::NotReadableError,
         Error::Operation => DOMErrorName::OperationError,
+        Error::NotAllowed => DOMErrorName::NotAllowedError,
         Error::Type(message) => unsafe {
             assert!(!JS_IsExceptionPending(*cx));
             throw_type_error(*cx, &message);
@@ -238,7 +241,7 @@ impl ErrorInfo {
         None
     }
 
-    unsafe fn from_value(value: HandleValue, cx: *mut JSContext) -> ErrorInfo {
+    pub unsafe fn from_value(value: HandleValue, cx: *mut JSContext) -> ErrorInfo {
         if value.is_object() {
             rooted!(in(cx) let object = value.to_object());
             if let Some(info) = ErrorInfo::from_object(object.handle(), cx) {
@@ -300,6 +303,18 @@ pub unsafe fn report_pending_exception(cx: *mut JSContext, dispatch_event: bool,
     }
 }
 
+/// Store the pending exception in `rval` without reporting it, thereby clearing it.
+/// Returns false if there is no pending exception.
+pub unsafe fn take_pending_exception(cx: *mut JSContext, rval: MutableHandleValue) -> bool {
+    if !JS_IsExceptionPending(cx) {
+        return false;
+    }
+
+    let result = JS_GetPendingException(cx, rval);
+    JS_ClearPendingException(cx);
+    result
+}
+
 /// Throw an exception to signal that a `JSObject` can not be converted to a
 /// given DOM type.
 pub unsafe fn throw_invalid_this(cx: *mut JSContext, proto_id: u16) {
@@ -9,14 +9,16 @@ use js::glue::JS_GetReservedSlot;
 use js::jsapi::JSObject;
 use js::jsval::UndefinedValue;
 
+use crate::dom::bindings::live_objects::note_reflector_finalized;
 use crate::dom::bindings::utils::finalize_global as do_finalize_global;
 use crate::dom::bindings::weakref::{WeakBox, WeakReferenceable, DOM_WEAK_SLOT};
 
 /// Generic finalizer implementations for DOM binding implementations.
 
-pub unsafe fn finalize_common<T>(this: *const T) {
+pub unsafe fn finalize_common<T>(obj: *mut JSObject, this: *const T) {
     if !this.is_null() {
         // The pointer can be null if the object is the unforgeable holder of that interface.
+        note_reflector_finalized(obj);
         let _ = Box::from_raw(this as *mut T);
     }
     debug!("{} finalize: {:p}", type_name::<T>(), this);
@@ -24,7 +26,7 @@ pub unsafe fn finalize_common<T>(this: *const T) {
 
 pub unsafe fn finalize_global<T>(obj: *mut JSObject, this: *const T) {
     do_finalize_global(obj);
-    finalize_common::<T>(this);
+    finalize_common::<T>(obj, this);
 }
 
 pub unsafe fn finalize_weak_referenceable<T: WeakReferenceable>(
@@ -48,5 +50,5 @@ pub unsafe fn finalize_weak_referenceable<T: WeakReferenceable>(
             mem::drop(Box::from_raw(weak_box_ptr));
         }
     }
-    finalize_common::<T>(this);
+    finalize_common::<T>(obj, this);
 }
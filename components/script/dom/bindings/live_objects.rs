@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Counts of the DOM objects of a thread that have a reflector, per interface.
+//!
+//! Counting is off unless enabled for the thread before any reflector is
+//! created, and is used by tests to check that objects do not leak. An
+//! object counts towards every interface in its prototype chain, and stops
+//! counting once its reflector is finalized.
+
+use std::cell::{Cell, RefCell};
+
+use js::jsapi::JSObject;
+
+use crate::dom::bindings::codegen::PrototypeList::{proto_id_to_name, ID};
+use crate::dom::bindings::conversions::get_dom_class;
+
+thread_local!(static COUNTING_ENABLED: Cell<bool> = Cell::new(false));
+thread_local!(static LIVE_OBJECTS: RefCell<Vec<usize>> = RefCell::new(Vec::new()));
+
+/// Start counting the DOM objects of the current thread.
+pub fn enable_live_object_counting() {
+    LIVE_OBJECTS.with(|counts| *counts.borrow_mut() = vec![0; ID::Last as usize]);
+    COUNTING_ENABLED.with(|enabled| enabled.set(true));
+}
+
+unsafe fn update_counts(reflector: *mut JSObject, update: impl Fn(&mut usize)) {
+    if !COUNTING_ENABLED.with(Cell::get) {
+        return;
+    }
+    // Some reflectors, like the one of the `WindowProxy`, are not DOM objects.
+    let Ok(dom_class) = get_dom_class(reflector) else {
+        return;
+    };
+    LIVE_OBJECTS.with(|counts| {
+        let mut counts = counts.borrow_mut();
+        for id in &dom_class.interface_chain[..=dom_class.depth as usize] {
+            update(&mut counts[*id as usize]);
+        }
+    });
+}
+
+/// Note that a DOM object was given the reflector `reflector`.
+pub unsafe fn note_reflector_created(reflector: *mut JSObject) {
+    update_counts(reflector, |count| *count += 1);
+}
+
+/// Note that the reflector `reflector` of a DOM object is being finalized.
+pub unsafe fn note_reflector_finalized(reflector: *mut JSObject) {
+    update_counts(reflector, |count| *count = count.saturating_sub(1));
+}
+
+/// The number of live DOM objects of the current thread that implement the
+/// interface with the given name, or `None` if there is no such interface.
+/// Always zero if counting is not enabled.
+pub fn live_object_count(interface: &str) -> Option<usize> {
+    let id = (0..ID::Last as u16).find(|id| proto_id_to_name(*id) == interface)?;
+    Some(LIVE_OBJECTS.with(|counts| counts.borrow().get(id as usize).copied().unwrap_or(0)))
+}
@@ -148,6 +148,7 @@ pub mod inheritance;
 pub mod interface;
 pub mod iterable;
 pub mod like;
+pub mod live_objects;
 pub mod namespace;
 pub mod num;
 pub mod principals;
@@ -11,6 +11,7 @@ use js::rust::HandleObject;
 
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::iterable::{Iterable, IterableIterator};
+use crate::dom::bindings::live_objects::note_reflector_created;
 use crate::dom::bindings::root::{Dom, DomRoot, Root};
 use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::globalscope::GlobalScope;
@@ -71,6 +72,7 @@ impl Reflector {
         assert!(self.object.get().is_null());
         assert!(!object.is_null());
         self.object.set(object);
+        note_reflector_created(object);
     }
 
     /// Return a pointer to the memory location at which the JS reflector
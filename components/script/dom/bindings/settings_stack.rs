@@ -3,18 +3,22 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::RefCell;
-use std::thread;
+use std::{ptr, thread};
 
-use js::jsapi::{GetScriptedCallerGlobal, HideScriptedCaller, JSTracer, UnhideScriptedCaller};
+use js::jsapi::{
+    GetObjectRealmOrNull, GetScriptedCallerGlobal, HideScriptedCaller, JSTracer, Realm,
+    UnhideScriptedCaller,
+};
 use js::rust::Runtime;
 
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::globalscope::GlobalScope;
 
 thread_local!(static STACK: RefCell<Vec<StackEntry>> = RefCell::new(Vec::new()));
 
-#[derive(Debug, Eq, JSTraceable, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, JSTraceable, PartialEq)]
 enum StackEntryKind {
     Incumbent,
     Entry,
@@ -38,6 +42,56 @@ pub fn is_execution_stack_empty() -> bool {
     STACK.with(|stack| stack.borrow().is_empty())
 }
 
+/// Check that every entry pushed on the script settings stack was popped again, which has to be
+/// the case whenever the event loop is not running script. This is only checked in debug builds,
+/// where the leftover entries are reported.
+pub fn debug_assert_execution_stack_is_empty() {
+    STACK.with(|stack| {
+        let stack = stack.borrow();
+        debug_assert!(
+            stack.is_empty(),
+            "The script settings stack is unbalanced, with leftover entries {:?}",
+            stack
+                .iter()
+                .map(|entry| (entry.kind, &*entry.global as *const GlobalScope))
+                .collect::<Vec<_>>()
+        );
+    });
+}
+
+/// Pop the entry pushed by the guard of the given kind for the given global, which has to be the
+/// topmost one.
+///
+/// When this happens while unwinding from a panic, the entries pushed above it are discarded
+/// along with it instead of panicking again, so that the stack is balanced once the panic is
+/// caught, whatever point of running script it was raised at.
+fn pop_entry(global: *const GlobalScope, kind: StackEntryKind, guard_name: &str) {
+    STACK.with(|stack| {
+        if thread::panicking() {
+            // The stack may still be borrowed if the panic happened while it was being updated.
+            let Ok(mut stack) = stack.try_borrow_mut() else {
+                return;
+            };
+            if let Some(index) = stack
+                .iter()
+                .rposition(|entry| ptr::eq(&*entry.global, global) && entry.kind == kind)
+            {
+                stack.truncate(index);
+            }
+            return;
+        }
+
+        let mut stack = stack.borrow_mut();
+        let entry = stack.pop().unwrap();
+        assert!(
+            ptr::eq(&*entry.global, global),
+            "Dropped {} out of order.",
+            guard_name
+        );
+        assert_eq!(entry.kind, kind);
+    })
+}
+
 /// RAII struct that pushes and pops entries from the script settings stack.
 pub struct AutoEntryScript {
     global: DomRoot<GlobalScope>,
@@ -63,16 +117,8 @@ impl AutoEntryScript {
 impl Drop for AutoEntryScript {
     /// <https://html.spec.whatwg.org/multipage/#clean-up-after-running-script>
     fn drop(&mut self) {
-        STACK.with(|stack| {
-            let mut stack = stack.borrow_mut();
-            let entry = stack.pop().unwrap();
-            assert_eq!(
-                &*entry.global as *const GlobalScope, &*self.global as *const GlobalScope,
-                "Dropped AutoEntryScript out of order."
-            );
-            assert_eq!(entry.kind, StackEntryKind::Entry);
-            trace!("Clean up after running script with {:p}", &*entry.global);
-        });
+        pop_entry(&*self.global, StackEntryKind::Entry, "AutoEntryScript");
+        trace!("Clean up after running script with {:p}", &*self.global);
 
         // Step 5
         if !thread::panicking() && incumbent_global().is_none() {
@@ -85,16 +131,41 @@ impl Drop for AutoEntryScript {
 ///
 /// ["entry"]: https://html.spec.whatwg.org/multipage/#entry
 pub fn entry_global() -> DomRoot<GlobalScope> {
-    STACK
-        .with(|stack| {
-            stack
-                .borrow()
-                .iter()
-                .rev()
-                .find(|entry| entry.kind == StackEntryKind::Entry)
-                .map(|entry| DomRoot::from_ref(&*entry.global))
-        })
-        .unwrap()
+    current_entry_global().unwrap()
+}
+
+/// Returns the ["entry"] global object, or `None` if no script is running.
+///
+/// ["entry"]: https://html.spec.whatwg.org/multipage/#entry
+fn current_entry_global() -> Option<DomRoot<GlobalScope>> {
+    STACK.with(|stack| {
+        stack
+            .borrow()
+            .iter()
+            .rev()
+            .find(|entry| entry.kind == StackEntryKind::Entry)
+            .map(|entry| DomRoot::from_ref(&*entry.global))
+    })
+}
+
+/// Returns the realm of the ["entry"] global object, or `None` if no script is running.
+///
+/// ["entry"]: https://html.spec.whatwg.org/multipage/#entry
+pub fn current_entry_realm() -> Option<*mut Realm> {
+    current_entry_global().map(|global| unsafe { realm_of(&global) })
+}
+
+/// Returns the realm of the ["incumbent"] global object, or `None` if there is none.
+///
+/// ["incumbent"]: https://html.spec.whatwg.org/multipage/#incumbent
+pub fn incumbent_realm() -> Option<*mut Realm> {
+    incumbent_global().map(|global| unsafe { realm_of(&global) })
+}
+
+unsafe fn realm_of(global: &GlobalScope) -> *mut Realm {
+    let realm = GetObjectRealmOrNull(global.reflector().get_jsobject().get());
+    assert!(!realm.is_null());
+    realm
 }
 
 /// RAII struct that pushes and pops entries from the script settings stack.
@@ -129,21 +200,10 @@ impl AutoIncumbentScript {
 impl Drop for AutoIncumbentScript {
     /// <https://html.spec.whatwg.org/multipage/#clean-up-after-running-a-callback>
     fn drop(&mut self) {
-        STACK.with(|stack| {
-            // Step 4.
-            let mut stack = stack.borrow_mut();
-            let entry = stack.pop().unwrap();
-            // Step 3.
-            assert_eq!(
-                &*entry.global as *const GlobalScope as usize, self.global,
-                "Dropped AutoIncumbentScript out of order."
-            );
-            assert_eq!(entry.kind, StackEntryKind::Incumbent);
-            trace!(
-                "Clean up after running a callback with {:p}",
-                &*entry.global
-            );
-        });
+        // Step 3-4.
+        let global = self.global as *const GlobalScope;
+        pop_entry(global, StackEntryKind::Incumbent, "AutoIncumbentScript");
+        trace!("Clean up after running a callback with {:p}", global);
         unsafe {
             // Step 1-2.
             let cx = Runtime::get();
@@ -2,47 +2,57 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use bluetooth_traits::{BluetoothError, BluetoothRequest, GATTType};
+use bluetooth_traits::{BluetoothDeviceMsg, BluetoothError, BluetoothRequest, GATTType};
 use bluetooth_traits::{BluetoothResponse, BluetoothResponseResult};
 use bluetooth_traits::blocklist::{Blocklist, uuid_is_blocklisted};
 use bluetooth_traits::scanfilter::{BluetoothScanfilter, BluetoothScanfilterSequence};
 use bluetooth_traits::scanfilter::{RequestDeviceoptions, ServiceUUIDSequence};
-use crate::realms::{AlreadyInRealm, InRealm};
+use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::BluetoothBinding::BluetoothDataFilterInit;
 use crate::dom::bindings::codegen::Bindings::BluetoothBinding::{BluetoothMethods, RequestDeviceOptions};
 use crate::dom::bindings::codegen::Bindings::BluetoothBinding::BluetoothLEScanFilterInit;
+use crate::dom::bindings::codegen::Bindings::BluetoothDeviceBinding::BluetoothDeviceMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothPermissionResultBinding::BluetoothPermissionDescriptor;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServer_Binding::
 BluetoothRemoteGATTServerMethods;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{PermissionName, PermissionState};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::UnionTypes::{ArrayBufferViewOrArrayBuffer, StringOrUnsignedLong};
 use crate::dom::bindings::error::Error::{self, Network, Security, Type};
 use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{DomObject, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bluetoothdevice::BluetoothDevice;
 use crate::dom::bluetoothpermissionresult::BluetoothPermissionResult;
+use crate::dom::bluetoothremotegattserver::BluetoothRemoteGATTServer;
 use crate::dom::bluetoothuuid::{BluetoothServiceUUID, BluetoothUUID, UUID};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::permissions::{get_descriptor_permission_state, PermissionAlgorithm};
 use crate::dom::promise::Promise;
+use crate::dom::valueevent::ValueEvent;
 use crate::script_runtime::JSContext;
 use crate::task::TaskOnce;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::conversions::ConversionResult;
-use js::jsapi::JSObject;
-use js::jsval::{ObjectValue, UndefinedValue};
+use js::jsapi::{Heap, JSObject, JS_NewDataView};
+use js::jsval::{BooleanValue, ObjectValue, UndefinedValue};
+use js::typedarray::{ArrayBuffer, CreateWith};
 use profile_traits::ipc as ProfiledIpc;
+use servo_atoms::Atom;
 use std::collections::HashMap;
+use std::ptr;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 
 const KEY_CONVERSION_ERROR: &str =
     "This `manufacturerData` key can not be parsed as unsigned short:";
@@ -60,6 +70,8 @@ const SERVICE_DATA_ERROR: &str = "'serviceData', if present, must be non-empty t
 const SERVICE_ERROR: &str = "'services', if present, must contain at least one service.";
 const OPTIONS_ERROR: &str = "Fields of 'options' conflict with each other.
  Either 'acceptAllDevices' member must be true, or 'filters' member must be set to a value.";
+// https://webbluetoothcg.github.io/web-bluetooth/#permissions-policy
+const BLUETOOTH_FEATURE: &str = "bluetooth";
 const BT_DESC_CONVERSION_ERROR: &str =
     "Can't convert to an IDL value of type BluetoothPermissionDescriptor";
 
@@ -107,10 +119,16 @@ impl Default for BluetoothExtraPermissionData {
 struct BluetoothContext<T: AsyncBluetoothListener + DomObject> {
     promise: Option<TrustedPromise>,
     receiver: Trusted<T>,
+    /// The GATT server whose connection the response depends on, if any.
+    server: Option<Trusted<BluetoothRemoteGATTServer>>,
 }
 
 pub trait AsyncBluetoothListener {
     fn handle_response(&self, result: BluetoothResponse, promise: &Rc<Promise>);
+
+    fn handle_error(&self, error: BluetoothError, promise: &Rc<Promise>) {
+        promise.reject_error(Error::from(error));
+    }
 }
 
 impl<T> BluetoothContext<T>
@@ -121,13 +139,21 @@ where
     fn response(&mut self, response: BluetoothResponseResult) {
         let promise = self.promise.take().expect("bt promise is missing").root();
 
+        // https://webbluetoothcg.github.io/web-bluetooth/#connection-checking-wrapper
+        // Step 4.
+        if let Some(server) = self.server.take() {
+            if !server.root().remove_active_algorithm(&promise) {
+                return promise.reject_error(Network);
+            }
+        }
+
         // JSAutoRealm needs to be manually made.
         // Otherwise, Servo will crash.
         match response {
             Ok(response) => self.receiver.root().handle_response(response, &promise),
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-requestdevice
             // Step 3 - 4.
-            Err(error) => promise.reject_error(Error::from(error)),
+            Err(error) => self.receiver.root().handle_error(error, &promise),
         }
     }
 }
@@ -137,13 +163,19 @@ where
 pub struct Bluetooth {
     eventtarget: EventTarget,
     device_instance_map: DomRefCell<HashMap<String, Dom<BluetoothDevice>>>,
+    /// The id of the availability watch requested from the bluetooth thread, once something
+    /// listens for availabilitychanged.
+    availability_watch: DomRefCell<Option<String>>,
 }
 
 impl Bluetooth {
     pub fn new_inherited() -> Bluetooth {
         Bluetooth {
-            eventtarget: EventTarget::new_inherited(),
+            eventtarget: EventTarget::new_inherited_with_listener_added_hook(
+                Bluetooth::listener_added,
+            ),
             device_instance_map: DomRefCell::new(HashMap::new()),
+            availability_watch: DomRefCell::new(None),
         }
     }
 
@@ -151,6 +183,96 @@ impl Bluetooth {
         reflect_dom_object(Box::new(Bluetooth::new_inherited()), global)
     }
 
+    /// Start watching the availability of Bluetooth when the first availabilitychanged listener
+    /// is added, the bluetooth thread has to poll the adapter for it.
+    fn listener_added(target: &EventTarget, ty: &Atom) {
+        let Some(bluetooth) = target.downcast::<Bluetooth>() else {
+            return;
+        };
+        if &**ty == "availabilitychanged" && bluetooth.availability_watch.borrow().is_none() {
+            bluetooth.watch_availability();
+        }
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#abort-all-active-watchadvertisements
+    pub fn abort_all_active_watch_advertisements(&self) {
+        let devices: Vec<DomRoot<BluetoothDevice>> = self
+            .device_instance_map
+            .borrow()
+            .values()
+            .map(|device| DomRoot::from_ref(&**device))
+            .collect();
+        for device in devices {
+            device.UnwatchAdvertisements();
+        }
+    }
+
+    /// Stop everything the bluetooth thread watches on behalf of this object, as its global is
+    /// going away.
+    pub fn stop_watching(&self) {
+        if let Some(watch_id) = self.availability_watch.borrow_mut().take() {
+            let _ = self
+                .get_bluetooth_thread()
+                .send(BluetoothRequest::UnwatchAvailability(watch_id));
+        }
+        let devices: Vec<DomRoot<BluetoothDevice>> = self
+            .device_instance_map
+            .borrow()
+            .values()
+            .map(|device| DomRoot::from_ref(&**device))
+            .collect();
+        for device in devices {
+            device.stop_watching();
+        }
+    }
+
+    /// Ask the bluetooth thread to tell this object about availability changes.
+    fn watch_availability(&self) {
+        let watch_id = Uuid::new_v4().simple().to_string();
+        *self.availability_watch.borrow_mut() = Some(watch_id.clone());
+        let (sender, receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let availability: bool = message.to().unwrap();
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(fire_availabilitychanged: move || {
+                        this.root().fire_availabilitychanged(availability);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        self.get_bluetooth_thread()
+            .send(BluetoothRequest::WatchAvailability(watch_id, sender))
+            .unwrap();
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-onavailabilitychanged
+    fn fire_availabilitychanged(&self, availability: bool) {
+        let global = self.global();
+        if !is_allowed_to_use_bluetooth(&global) {
+            return;
+        }
+        rooted!(in(*GlobalScope::get_cx()) let value = BooleanValue(availability));
+        let event = ValueEvent::new(
+            global.as_window(),
+            None,
+            Atom::from("availabilitychanged"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            value.handle(),
+        );
+        event.upcast::<Event>().fire(self.upcast::<EventTarget>());
+    }
+
     fn get_bluetooth_thread(&self) -> IpcSender<BluetoothRequest> {
         self.global().as_window().bluetooth_thread()
     }
@@ -230,20 +352,73 @@ impl Bluetooth {
         // Note: Step 3, 6 - 8 are implemented in
         // components/net/bluetooth_thread.rs in request_device function.
         self.get_bluetooth_thread()
-            .send(BluetoothRequest::RequestDevice(option, sender))
+            .send(BluetoothRequest::RequestDevice(
+                self.global().as_window().webview_id(),
+                self.global().origin().immutable().clone(),
+                option,
+                sender,
+            ))
             .unwrap();
     }
+
+    /// The object representing `device` in this context, which script is allowed to use.
+    #[allow(crown::unrooted_must_root)]
+    fn get_or_create_device(&self, device: BluetoothDeviceMsg) -> DomRoot<BluetoothDevice> {
+        let mut device_instance_map = self.device_instance_map.borrow_mut();
+        if let Some(existing_device) = device_instance_map.get(&device.id) {
+            return DomRoot::from_ref(&**existing_device);
+        }
+        let bt_device = BluetoothDevice::new(
+            &self.global(),
+            DOMString::from(device.id.clone()),
+            device.name.map(DOMString::from),
+            self,
+        );
+        device_instance_map.insert(device.id.clone(), Dom::from_ref(&bt_device));
+
+        self.global()
+            .as_window()
+            .bluetooth_extra_permission_data()
+            .add_new_allowed_device(AllowedBluetoothDevice {
+                deviceId: DOMString::from(device.id),
+                mayUseGATT: true,
+            });
+        bt_device
+    }
 }
 
 pub fn response_async<T: AsyncBluetoothListener + DomObject + 'static>(
     promise: &Rc<Promise>,
     receiver: &T,
+) -> IpcSender<BluetoothResponseResult> {
+    response_sender(promise, receiver, None)
+}
+
+// https://webbluetoothcg.github.io/web-bluetooth/#connection-checking-wrapper
+/// Like `response_async`, but `promise` is rejected with a `NetworkError` instead if the
+/// connection to `server` is lost before the response arrives.
+pub fn connection_checked_response_async<T: AsyncBluetoothListener + DomObject + 'static>(
+    promise: &Rc<Promise>,
+    receiver: &T,
+    server: &BluetoothRemoteGATTServer,
+) -> IpcSender<BluetoothResponseResult> {
+    // Note: Step 1 is done by the callers.
+    // Step 2 - 3.
+    server.add_active_algorithm(promise);
+    response_sender(promise, receiver, Some(server))
+}
+
+fn response_sender<T: AsyncBluetoothListener + DomObject + 'static>(
+    promise: &Rc<Promise>,
+    receiver: &T,
+    server: Option<&BluetoothRemoteGATTServer>,
 ) -> IpcSender<BluetoothResponseResult> {
     let (action_sender, action_receiver) = ipc::channel().unwrap();
     let task_source = receiver.global().networking_task_source();
     let context = Arc::new(Mutex::new(BluetoothContext {
         promise: Some(TrustedPromise::new(promise.clone())),
         receiver: Trusted::new(receiver),
+        server: server.map(Trusted::new),
     }));
     ROUTER.add_route(
         action_receiver.to_opaque(),
@@ -277,6 +452,23 @@ pub fn response_async<T: AsyncBluetoothListener + DomObject + 'static>(
     action_sender
 }
 
+/// Replace `value`, the value of `attribute`, with a `DataView` over a new `ArrayBuffer`
+/// holding `bytes`.
+#[allow(unsafe_code)]
+pub fn set_attribute_value(attribute: &impl DomObject, value: &Heap<*mut JSObject>, bytes: &[u8]) {
+    let cx = GlobalScope::get_cx();
+    let _ac = enter_realm(attribute);
+    rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+    unsafe {
+        assert!(
+            ArrayBuffer::create(*cx, CreateWith::Slice(bytes), array_buffer.handle_mut()).is_ok()
+        );
+        let data_view = JS_NewDataView(*cx, array_buffer.handle().into(), 0, bytes.len());
+        assert!(!data_view.is_null());
+        value.set(data_view);
+    }
+}
+
 // https://webbluetoothcg.github.io/web-bluetooth/#getgattchildren
 pub fn get_gatt_children<T, F>(
     attribute: &T,
@@ -284,7 +476,7 @@ pub fn get_gatt_children<T, F>(
     uuid_canonicalizer: F,
     uuid: Option<StringOrUnsignedLong>,
     instance_id: String,
-    connected: bool,
+    server: &BluetoothRemoteGATTServer,
     child_type: GATTType,
 ) -> Rc<Promise>
 where
@@ -314,7 +506,7 @@ where
     };
 
     // Step 3 - 4.
-    if !connected {
+    if !server.Connected() {
         p.reject_error(Network);
         return p;
     }
@@ -323,7 +515,7 @@ where
 
     // Note: Steps 6 - 7 are implemented in components/bluetooth/lib.rs in get_descriptor function
     // and in handle_response function.
-    let sender = response_async(&p, attribute);
+    let sender = connection_checked_response_async(&p, attribute, server);
     attribute
         .global()
         .as_window()
@@ -529,10 +721,22 @@ impl From<BluetoothError> for Error {
     }
 }
 
+/// Whether the permissions policy of the document of `global` lets it use Web Bluetooth.
+fn is_allowed_to_use_bluetooth(global: &GlobalScope) -> bool {
+    global
+        .as_window()
+        .Document()
+        .is_allowed_to_use_feature(BLUETOOTH_FEATURE)
+}
+
 impl BluetoothMethods for Bluetooth {
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-requestdevice
     fn RequestDevice(&self, option: &RequestDeviceOptions, comp: InRealm) -> Rc<Promise> {
         let p = Promise::new_in_current_realm(comp);
+        if !is_allowed_to_use_bluetooth(&self.global()) {
+            p.reject_error(Security);
+            return p;
+        }
         // Step 1.
         if (option.filters.is_some() && option.acceptAllDevices) ||
             (option.filters.is_none() && !option.acceptAllDevices)
@@ -548,9 +752,32 @@ impl BluetoothMethods for Bluetooth {
         p
     }
 
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getdevices
+    fn GetDevices(&self, comp: InRealm) -> Rc<Promise> {
+        let p = Promise::new_in_current_realm(comp);
+        if !is_allowed_to_use_bluetooth(&self.global()) {
+            p.reject_error(Security);
+            return p;
+        }
+        // Note: Step 1 - 3 are in components/bluetooth/lib.rs in the get_devices function,
+        // Step 4 is in handle_response.
+        let sender = response_async(&p, self);
+        self.get_bluetooth_thread()
+            .send(BluetoothRequest::GetDevices(
+                self.global().origin().immutable().clone(),
+                sender,
+            ))
+            .unwrap();
+        p
+    }
+
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getavailability
     fn GetAvailability(&self, comp: InRealm) -> Rc<Promise> {
         let p = Promise::new_in_current_realm(comp);
+        if !is_allowed_to_use_bluetooth(&self.global()) {
+            p.resolve_native(&false);
+            return p;
+        }
         // Step 1. We did not override the method
         // Step 2 - 3. in handle_response
         let sender = response_async(&p, self);
@@ -574,29 +801,20 @@ impl AsyncBluetoothListener for Bluetooth {
             // https://webbluetoothcg.github.io/web-bluetooth/#request-bluetooth-devices
             // Step 11, 13 - 14.
             BluetoothResponse::RequestDevice(device) => {
-                let mut device_instance_map = self.device_instance_map.borrow_mut();
-                if let Some(existing_device) = device_instance_map.get(&device.id.clone()) {
-                    return promise.resolve_native(&**existing_device);
-                }
-                let bt_device = BluetoothDevice::new(
-                    &self.global(),
-                    DOMString::from(device.id.clone()),
-                    device.name.map(DOMString::from),
-                    self,
-                );
-                device_instance_map.insert(device.id.clone(), Dom::from_ref(&bt_device));
-
-                self.global()
-                    .as_window()
-                    .bluetooth_extra_permission_data()
-                    .add_new_allowed_device(AllowedBluetoothDevice {
-                        deviceId: DOMString::from(device.id),
-                        mayUseGATT: true,
-                    });
+                let bt_device = self.get_or_create_device(device);
                 // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-requestdevice
                 // Step 5.
                 promise.resolve_native(&bt_device);
             },
+            // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getdevices
+            // Step 4.
+            BluetoothResponse::GetDevices(devices) => {
+                let devices: Vec<DomRoot<BluetoothDevice>> = devices
+                    .into_iter()
+                    .map(|device| self.get_or_create_device(device))
+                    .collect();
+                promise.resolve_native(&devices);
+            },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-getavailability
             // Step 2 - 3.
             BluetoothResponse::GetAvailability(is_available) => {
@@ -636,7 +854,11 @@ impl PermissionAlgorithm for Bluetooth {
         // Step 1: We are not using the `global` variable.
 
         // Step 2.
-        status.set_state(get_descriptor_permission_state(status.get_query(), None));
+        if is_allowed_to_use_bluetooth(&status.global()) {
+            status.set_state(get_descriptor_permission_state(status.get_query(), None));
+        } else {
+            status.set_state(PermissionState::Denied);
+        }
 
         // Step 3.
         if let PermissionState::Denied = status.get_state() {
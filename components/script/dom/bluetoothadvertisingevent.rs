@@ -51,7 +51,7 @@ impl BluetoothAdvertisingEvent {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    pub fn new(
         global: &GlobalScope,
         proto: Option<HandleObject>,
         type_: Atom,
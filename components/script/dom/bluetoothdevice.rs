@@ -7,27 +7,33 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use bluetooth_traits::{
-    BluetoothCharacteristicMsg, BluetoothDescriptorMsg, BluetoothRequest, BluetoothResponse,
-    BluetoothServiceMsg,
+    BluetoothAdvertisementMsg, BluetoothCharacteristicMsg, BluetoothDescriptorMsg,
+    BluetoothRequest, BluetoothResponse, BluetoothServiceMsg,
 };
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
+use ipc_channel::router::ROUTER;
 use profile_traits::ipc;
+use servo_atoms::Atom;
+use uuid::Uuid;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BluetoothDeviceBinding::BluetoothDeviceMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServerMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bluetooth::{response_async, AsyncBluetoothListener, Bluetooth};
+use crate::dom::bluetoothadvertisingevent::BluetoothAdvertisingEvent;
 use crate::dom::bluetoothcharacteristicproperties::BluetoothCharacteristicProperties;
 use crate::dom::bluetoothremotegattcharacteristic::BluetoothRemoteGATTCharacteristic;
 use crate::dom::bluetoothremotegattdescriptor::BluetoothRemoteGATTDescriptor;
 use crate::dom::bluetoothremotegattserver::BluetoothRemoteGATTServer;
 use crate::dom::bluetoothremotegattservice::BluetoothRemoteGATTService;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
@@ -47,6 +53,10 @@ pub struct BluetoothDevice {
         DomRefCell<HashMap<String, Dom<BluetoothRemoteGATTDescriptor>>>,
     ),
     watching_advertisements: Cell<bool>,
+    /// The id of the most recent advertisement watch requested from the bluetooth thread.
+    advertisement_watch: DomRefCell<Option<String>>,
+    /// Identifies the disconnection listener of this device to the bluetooth thread.
+    disconnection_listener_id: String,
 }
 
 impl BluetoothDevice {
@@ -67,6 +77,8 @@ impl BluetoothDevice {
                 DomRefCell::new(HashMap::new()),
             ),
             watching_advertisements: Cell::new(false),
+            advertisement_watch: DomRefCell::new(None),
+            disconnection_listener_id: Uuid::new_v4().simple().to_string(),
         }
     }
 
@@ -192,7 +204,8 @@ impl BluetoothDevice {
         // Step 1.
         self.get_gatt().set_connected(false);
 
-        // TODO: Step 2: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+        // Step 2.
+        self.get_gatt().clear_active_algorithms();
 
         // Step 3: We don't need `context`, we get the attributeInstanceMap from the device.
         // https://github.com/WebBluetoothCG/web-bluetooth/issues/330
@@ -202,13 +215,19 @@ impl BluetoothDevice {
         let service_ids = service_map.drain().map(|(id, _)| id).collect();
 
         let mut characteristic_map = self.attribute_instance_map.1.borrow_mut();
-        let characteristic_ids = characteristic_map.drain().map(|(id, _)| id).collect();
+        let characteristic_ids = characteristic_map
+            .drain()
+            .map(|(id, characteristic)| {
+                // Step 6.
+                characteristic.clean_up_notifications();
+                id
+            })
+            .collect();
 
         let mut descriptor_map = self.attribute_instance_map.2.borrow_mut();
         let descriptor_ids = descriptor_map.drain().map(|(id, _)| id).collect();
 
         // Step 5, 6.4, 7.
-        // TODO: Step 6: Implement `active notification context set` for BluetoothRemoteGATTCharacteristic.
         let _ = self
             .get_bluetooth_thread()
             .send(BluetoothRequest::SetRepresentedToNull(
@@ -246,6 +265,111 @@ impl BluetoothDevice {
             .unwrap();
         receiver.recv().unwrap().map_err(Error::from)
     }
+
+    /// Create a channel on which the bluetooth thread sends the advertisements received
+    /// from this device, each of which is turned into an `advertisementreceived` event.
+    fn advertisement_sender(&self) -> IpcSender<BluetoothAdvertisementMsg> {
+        let (sender, receiver) = ipc_channel::ipc::channel().unwrap();
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(fire_advertisement_event: move || {
+                        this.root().fire_advertisement_event(message.to().unwrap());
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        sender
+    }
+
+    /// Stop everything the bluetooth thread watches on behalf of this device, as its global is
+    /// going away.
+    pub fn stop_watching(&self) {
+        let characteristics: Vec<DomRoot<BluetoothRemoteGATTCharacteristic>> = self
+            .attribute_instance_map
+            .1
+            .borrow()
+            .values()
+            .map(|characteristic| DomRoot::from_ref(&**characteristic))
+            .collect();
+        for characteristic in characteristics {
+            characteristic.unsubscribe();
+        }
+        let _ = self
+            .get_bluetooth_thread()
+            .send(BluetoothRequest::RemoveDisconnectionListener(
+                String::from(self.Id()),
+                self.disconnection_listener_id.clone(),
+            ));
+    }
+
+    pub fn disconnection_listener_id(&self) -> String {
+        self.disconnection_listener_id.clone()
+    }
+
+    /// Create a channel on which the bluetooth thread notifies that this device is no longer
+    /// connected.
+    pub fn disconnection_sender(&self) -> IpcSender<()> {
+        let (sender, receiver) = ipc_channel::ipc::channel().unwrap();
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |_| {
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_device_disconnection: move || {
+                        this.root().handle_disconnection();
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        sender
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#disconnection-events
+    fn handle_disconnection(&self) {
+        // The device was already cleaned up if it was disconnected by disconnect().
+        if !self.get_gatt().Connected() {
+            return;
+        }
+        self.clean_up_disconnected_device();
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#fire-an-advertisementreceived-event
+    fn fire_advertisement_event(&self, advertisement: BluetoothAdvertisementMsg) {
+        // Advertisements can still be in flight after unwatchAdvertisements().
+        if !self.watching_advertisements.get() {
+            return;
+        }
+        let event = BluetoothAdvertisingEvent::new(
+            &self.global(),
+            None,
+            Atom::from("advertisementreceived"),
+            EventBubbles::Bubbles,
+            EventCancelable::NotCancelable,
+            self,
+            advertisement.name.map(DOMString::from),
+            advertisement.appearance,
+            advertisement.tx_power,
+            advertisement.rssi,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
 }
 
 impl BluetoothDeviceMethods for BluetoothDevice {
@@ -278,6 +402,13 @@ impl BluetoothDeviceMethods for BluetoothDevice {
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
     fn WatchAdvertisements(&self, comp: InRealm) -> Rc<Promise> {
         let p = Promise::new_in_current_realm(comp);
+        if self.watching_advertisements.get() {
+            p.resolve_native(&());
+            return p;
+        }
+        // A watch that is still pending is aborted by this one, see handle_response.
+        let watch_id = Uuid::new_v4().simple().to_string();
+        *self.advertisement_watch.borrow_mut() = Some(watch_id.clone());
         let sender = response_async(&p, self);
         // TODO: Step 1.
         // Note: Steps 2 - 3 are implemented in components/bluetooth/lib.rs in watch_advertisements function
@@ -285,6 +416,8 @@ impl BluetoothDeviceMethods for BluetoothDevice {
         self.get_bluetooth_thread()
             .send(BluetoothRequest::WatchAdvertisements(
                 String::from(self.Id()),
+                watch_id,
+                self.advertisement_sender(),
                 sender,
             ))
             .unwrap();
@@ -294,8 +427,13 @@ impl BluetoothDeviceMethods for BluetoothDevice {
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-unwatchadvertisements
     fn UnwatchAdvertisements(&self) {
         // Step 1.
-        self.watching_advertisements.set(false)
-        // TODO: Step 2.
+        self.watching_advertisements.set(false);
+        // Step 2.
+        if let Some(watch_id) = self.advertisement_watch.borrow_mut().take() {
+            self.get_bluetooth_thread()
+                .send(BluetoothRequest::UnwatchAdvertisements(watch_id))
+                .unwrap();
+        }
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchingadvertisements
@@ -309,13 +447,28 @@ impl BluetoothDeviceMethods for BluetoothDevice {
         GetOngattserverdisconnected,
         SetOngattserverdisconnected
     );
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdeviceeventhandlers-onadvertisementreceived
+    event_handler!(
+        advertisementreceived,
+        GetOnadvertisementreceived,
+        SetOnadvertisementreceived
+    );
 }
 
 impl AsyncBluetoothListener for BluetoothDevice {
     fn handle_response(&self, response: BluetoothResponse, promise: &Rc<Promise>) {
         match response {
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-unwatchadvertisements
-            BluetoothResponse::WatchAdvertisements(_result) => {
+            BluetoothResponse::WatchAdvertisements(watch_id) => {
+                // The watch was aborted by unwatchAdvertisements() or a later
+                // watchAdvertisements() call while it was pending.
+                if self.advertisement_watch.borrow().as_ref() != Some(&watch_id) {
+                    self.get_bluetooth_thread()
+                        .send(BluetoothRequest::UnwatchAdvertisements(watch_id))
+                        .unwrap();
+                    return promise.reject_error(Error::Abort);
+                }
                 // Step 3.1.
                 self.watching_advertisements.set(true);
                 // Step 3.2.
@@ -2,14 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
+use std::ptr::NonNull;
 use std::rc::Rc;
 
 use bluetooth_traits::blocklist::{uuid_is_blocklisted, Blocklist};
 use bluetooth_traits::{BluetoothRequest, BluetoothResponse, GATTType};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
+use ipc_channel::router::ROUTER;
+use js::jsapi::{Heap, JSObject};
+use uuid::Uuid;
 
-use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BluetoothCharacteristicPropertiesBinding::BluetoothCharacteristicPropertiesMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTCharacteristicBinding::BluetoothRemoteGATTCharacteristicMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServerMethods;
@@ -19,10 +23,14 @@ use crate::dom::bindings::error::Error::{
     self, InvalidModification, Network, NotSupported, Security,
 };
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
-use crate::dom::bindings::str::{ByteString, DOMString};
-use crate::dom::bluetooth::{get_gatt_children, response_async, AsyncBluetoothListener};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bluetooth::{
+    connection_checked_response_async, get_gatt_children, response_async, set_attribute_value,
+    AsyncBluetoothListener,
+};
 use crate::dom::bluetoothcharacteristicproperties::BluetoothCharacteristicProperties;
 use crate::dom::bluetoothremotegattservice::BluetoothRemoteGATTService;
 use crate::dom::bluetoothuuid::{BluetoothDescriptorUUID, BluetoothUUID};
@@ -30,6 +38,7 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
 
 // Maximum length of an attribute value.
 // https://www.bluetooth.org/DocMan/handlers/DownloadDoc.ashx?doc_id=286439 (Vol. 3, page 2169)
@@ -42,8 +51,14 @@ pub struct BluetoothRemoteGATTCharacteristic {
     service: Dom<BluetoothRemoteGATTService>,
     uuid: DOMString,
     properties: Dom<BluetoothCharacteristicProperties>,
-    value: DomRefCell<Option<ByteString>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Heap<*mut JSObject>,
     instance_id: String,
+    /// Whether this characteristic is in the active notification context set of the
+    /// characteristic it represents.
+    notifying: Cell<bool>,
+    /// Identifies the notification subscription of this characteristic to the bluetooth thread.
+    subscription_id: String,
 }
 
 impl BluetoothRemoteGATTCharacteristic {
@@ -58,8 +73,10 @@ impl BluetoothRemoteGATTCharacteristic {
             service: Dom::from_ref(service),
             uuid,
             properties: Dom::from_ref(properties),
-            value: DomRefCell::new(None),
+            value: Heap::default(),
             instance_id,
+            notifying: Cell::new(false),
+            subscription_id: Uuid::new_v4().simple().to_string(),
         }
     }
 
@@ -88,6 +105,74 @@ impl BluetoothRemoteGATTCharacteristic {
     fn get_instance_id(&self) -> String {
         self.instance_id.clone()
     }
+
+    /// Remove this characteristic from the active notification context set, as its device is
+    /// no longer connected.
+    pub fn clean_up_notifications(&self) {
+        self.notifying.set(false);
+    }
+
+    /// End the notification subscription of this characteristic, as its global is going away.
+    pub fn unsubscribe(&self) {
+        if !self.notifying.replace(false) {
+            return;
+        }
+        // Nobody is left to hear back from the bluetooth thread.
+        let (sender, _) = ipc_channel::ipc::channel().unwrap();
+        let _ = self
+            .get_bluetooth_thread()
+            .send(BluetoothRequest::StopNotifications(
+                self.get_instance_id(),
+                self.subscription_id.clone(),
+                sender,
+            ));
+    }
+
+    /// Create a channel on which the bluetooth thread sends the values notified by the
+    /// characteristic.
+    fn notification_sender(&self) -> IpcSender<Vec<u8>> {
+        let (sender, receiver) = ipc_channel::ipc::channel().unwrap();
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_characteristic_notification: move || {
+                        this.root().handle_notification(message.to().unwrap());
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        sender
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#notification-events
+    fn handle_notification(&self, value: Vec<u8>) {
+        // Step 1: This characteristic is the one the value was routed to.
+        // Values can still be in flight after stopNotifications().
+        if !self.notifying.get() {
+            return;
+        }
+
+        // Step 2.
+        if !self.Service().Device().get_gatt().Connected() {
+            return;
+        }
+
+        // Step 3.
+        set_attribute_value(self, &self.value, &value);
+
+        // Step 4.
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(atom!("characteristicvaluechanged"));
+    }
 }
 
 impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteristic {
@@ -114,7 +199,7 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             BluetoothUUID::descriptor,
             Some(descriptor),
             self.get_instance_id(),
-            self.Service().Device().get_gatt().Connected(),
+            &self.Service().Device().get_gatt(),
             GATTType::Descriptor,
         )
     }
@@ -127,14 +212,14 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             BluetoothUUID::descriptor,
             descriptor,
             self.get_instance_id(),
-            self.Service().Device().get_gatt().Connected(),
+            &self.Service().Device().get_gatt(),
             GATTType::Descriptor,
         )
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-value
-    fn GetValue(&self) -> Option<ByteString> {
-        self.value.borrow().clone()
+    fn GetValue(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        NonNull::new(self.value.get())
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-readvalue
@@ -153,8 +238,6 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             return p;
         }
 
-        // TODO: Step 5: Implement the `connection-checking-wrapper` algorithm for BluetoothRemoteGATTServer.
-
         // Step 5.1.
         if !self.Properties().Read() {
             p.reject_error(NotSupported);
@@ -163,7 +246,9 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
 
         // Note: Steps 3 - 4 and the remaining substeps of Step 5 are implemented in components/bluetooth/lib.rs
         // in readValue function and in handle_response function.
-        let sender = response_async(&p, self);
+        // Step 5.
+        let sender =
+            connection_checked_response_async(&p, self, &self.Service().Device().get_gatt());
         self.get_bluetooth_thread()
             .send(BluetoothRequest::ReadValue(self.get_instance_id(), sender))
             .unwrap();
@@ -197,8 +282,6 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             return p;
         }
 
-        // TODO: Step 7: Implement the `connection-checking-wrapper` algorithm for BluetoothRemoteGATTServer.
-
         // Step 7.1.
         if !(self.Properties().Write() ||
             self.Properties().WriteWithoutResponse() ||
@@ -210,7 +293,9 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
 
         // Note: Steps 5 - 6 and the remaining substeps of Step 7 are implemented in components/bluetooth/lib.rs
         // in writeValue function and in handle_response function.
-        let sender = response_async(&p, self);
+        // Step 7.
+        let sender =
+            connection_checked_response_async(&p, self, &self.Service().Device().get_gatt());
         self.get_bluetooth_thread()
             .send(BluetoothRequest::WriteValue(
                 self.get_instance_id(),
@@ -243,15 +328,20 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             return p;
         }
 
-        // TODO: Step 6: Implement `active notification context set` for BluetoothRemoteGATTCharacteristic.
+        // Step 6.
+        if self.notifying.get() {
+            p.resolve_native(self);
+            return p;
+        }
 
-        // Note: Steps 3 - 4, 7 - 11 are implemented in components/bluetooth/lib.rs in enable_notification function
+        // Note: Steps 3 - 4, 7 - 11 are implemented in components/bluetooth/lib.rs in start_notifications function
         // and in handle_response function.
         let sender = response_async(&p, self);
         self.get_bluetooth_thread()
-            .send(BluetoothRequest::EnableNotification(
+            .send(BluetoothRequest::StartNotifications(
                 self.get_instance_id(),
-                true,
+                self.subscription_id.clone(),
+                self.notification_sender(),
                 sender,
             ))
             .unwrap();
@@ -263,14 +353,15 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
         let p = Promise::new_in_current_realm(comp);
         let sender = response_async(&p, self);
 
-        // TODO: Step 3 - 4: Implement `active notification context set` for BluetoothRemoteGATTCharacteristic,
+        // Step 3.
+        self.notifying.set(false);
 
-        // Note: Steps 1 - 2, and part of Step 4 and Step 5 are implemented in components/bluetooth/lib.rs
-        // in enable_notification function and in handle_response function.
+        // Note: Steps 1 - 2, Step 4 and Step 5 are implemented in components/bluetooth/lib.rs
+        // in stop_notifications function and in handle_response function.
         self.get_bluetooth_thread()
-            .send(BluetoothRequest::EnableNotification(
+            .send(BluetoothRequest::StopNotifications(
                 self.get_instance_id(),
-                false,
+                self.subscription_id.clone(),
                 sender,
             ))
             .unwrap();
@@ -307,39 +398,44 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTCharacteristic {
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-readvalue
             BluetoothResponse::ReadValue(result) => {
-                // TODO: Step 5.5.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+                // Note: Step 5.5.1 is done by the connection-checking wrapper.
 
                 // Step 5.5.2.
-                // TODO(#5014): Replace ByteString with ArrayBuffer when it is implemented.
-                let value = ByteString::new(result);
-                *self.value.borrow_mut() = Some(value.clone());
+                set_attribute_value(self, &self.value, &result);
 
                 // Step 5.5.3.
                 self.upcast::<EventTarget>()
                     .fire_bubbling_event(atom!("characteristicvaluechanged"));
 
                 // Step 5.5.4.
-                promise.resolve_native(&value);
+                promise.resolve_native(&self.value.get());
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-writevalue
             BluetoothResponse::WriteValue(result) => {
-                // TODO: Step 7.5.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+                // Note: Step 7.5.1 is done by the connection-checking wrapper.
 
                 // Step 7.5.2.
-                // TODO(#5014): Replace ByteString with an ArrayBuffer wrapped in a DataView.
-                *self.value.borrow_mut() = Some(ByteString::new(result));
+                set_attribute_value(self, &self.value, &result);
 
                 // Step 7.5.3.
                 promise.resolve_native(&());
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
-            BluetoothResponse::EnableNotification(_result) => {
-                // (StartNotification) TODO: Step 10:  Implement `active notification context set`
-                // for BluetoothRemoteGATTCharacteristic.
+            BluetoothResponse::StartNotifications => {
+                // Step 10.
+                self.notifying.set(true);
+
+                // Step 11.
+                promise.resolve_native(self);
+            },
+            // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
+            BluetoothResponse::StopNotifications => {
+                // A startNotifications() call that was pending when this call was made has
+                // completed by now, so undo its Step 10.
+                self.notifying.set(false);
 
-                // (StartNotification) Step 11.
-                // (StopNotification)  Step 5.
+                // Step 5.
                 promise.resolve_native(self);
             },
             _ => promise.reject_error(Error::Type("Something went wrong...".to_owned())),
@@ -2,14 +2,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::ptr::NonNull;
 use std::rc::Rc;
 
 use bluetooth_traits::blocklist::{uuid_is_blocklisted, Blocklist};
 use bluetooth_traits::{BluetoothRequest, BluetoothResponse};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
+use js::jsapi::{Heap, JSObject};
 
-use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTCharacteristicBinding::BluetoothRemoteGATTCharacteristicMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTDescriptorBinding::BluetoothRemoteGATTDescriptorMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServerMethods;
@@ -18,14 +19,17 @@ use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
 use crate::dom::bindings::error::Error::{self, InvalidModification, Network, Security};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
-use crate::dom::bindings::str::{ByteString, DOMString};
-use crate::dom::bluetooth::{response_async, AsyncBluetoothListener};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bluetooth::{
+    connection_checked_response_async, set_attribute_value, AsyncBluetoothListener,
+};
 use crate::dom::bluetoothremotegattcharacteristic::{
     BluetoothRemoteGATTCharacteristic, MAXIMUM_ATTRIBUTE_LENGTH,
 };
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
 
 // http://webbluetoothcg.github.io/web-bluetooth/#bluetoothremotegattdescriptor
 #[dom_struct]
@@ -33,7 +37,8 @@ pub struct BluetoothRemoteGATTDescriptor {
     reflector_: Reflector,
     characteristic: Dom<BluetoothRemoteGATTCharacteristic>,
     uuid: DOMString,
-    value: DomRefCell<Option<ByteString>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Heap<*mut JSObject>,
     instance_id: String,
 }
 
@@ -47,7 +52,7 @@ impl BluetoothRemoteGATTDescriptor {
             reflector_: Reflector::new(),
             characteristic: Dom::from_ref(characteristic),
             uuid,
-            value: DomRefCell::new(None),
+            value: Heap::default(),
             instance_id,
         }
     }
@@ -89,8 +94,8 @@ impl BluetoothRemoteGATTDescriptorMethods for BluetoothRemoteGATTDescriptor {
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattdescriptor-value
-    fn GetValue(&self) -> Option<ByteString> {
-        self.value.borrow().clone()
+    fn GetValue(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        NonNull::new(self.value.get())
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattdescriptor-readvalue
@@ -115,10 +120,14 @@ impl BluetoothRemoteGATTDescriptorMethods for BluetoothRemoteGATTDescriptor {
             return p;
         }
 
-        // TODO: Step 5: Implement the `connection-checking-wrapper` algorithm for BluetoothRemoteGATTServer.
         // Note: Steps 3 - 4 and substeps of Step 5 are implemented in components/bluetooth/lib.rs
         // in readValue function and in handle_response function.
-        let sender = response_async(&p, self);
+        // Step 5.
+        let sender = connection_checked_response_async(
+            &p,
+            self,
+            &self.Characteristic().Service().Device().get_gatt(),
+        );
         self.get_bluetooth_thread()
             .send(BluetoothRequest::ReadValue(self.get_instance_id(), sender))
             .unwrap();
@@ -157,10 +166,14 @@ impl BluetoothRemoteGATTDescriptorMethods for BluetoothRemoteGATTDescriptor {
             return p;
         }
 
-        // TODO: Step 7: Implement the `connection-checking-wrapper` algorithm for BluetoothRemoteGATTServer.
         // Note: Steps 5 - 6 and substeps of Step 7 are implemented in components/bluetooth/lib.rs
         // in writeValue function and in handle_response function.
-        let sender = response_async(&p, self);
+        // Step 7.
+        let sender = connection_checked_response_async(
+            &p,
+            self,
+            &self.Characteristic().Service().Device().get_gatt(),
+        );
         self.get_bluetooth_thread()
             .send(BluetoothRequest::WriteValue(
                 self.get_instance_id(),
@@ -177,26 +190,22 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTDescriptor {
         match response {
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattdescriptor-readvalue
             BluetoothResponse::ReadValue(result) => {
-                // TODO: Step 5.4.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+                // Note: Step 5.4.1 is done by the connection-checking wrapper.
 
                 // Step 5.4.2.
-                // TODO(#5014): Replace ByteString with ArrayBuffer when it is implemented.
-                let value = ByteString::new(result);
-                *self.value.borrow_mut() = Some(value.clone());
+                set_attribute_value(self, &self.value, &result);
 
                 // Step 5.4.3.
-                promise.resolve_native(&value);
+                promise.resolve_native(&self.value.get());
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattdescriptor-writevalue
             BluetoothResponse::WriteValue(result) => {
-                // TODO: Step 7.4.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+                // Note: Step 7.4.1 is done by the connection-checking wrapper.
 
                 // Step 7.4.2.
-                // TODO(#5014): Replace ByteString with an ArrayBuffer wrapped in a DataView.
-                *self.value.borrow_mut() = Some(ByteString::new(result));
+                set_attribute_value(self, &self.value, &result);
 
                 // Step 7.4.3.
-                // TODO: Resolve promise with undefined instead of a value.
                 promise.resolve_native(&());
             },
             _ => promise.reject_error(Error::Type("Something went wrong...".to_owned())),
@@ -5,10 +5,11 @@
 use std::cell::Cell;
 use std::rc::Rc;
 
-use bluetooth_traits::{BluetoothRequest, BluetoothResponse, GATTType};
+use bluetooth_traits::{BluetoothError, BluetoothRequest, BluetoothResponse, GATTType};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BluetoothDeviceBinding::BluetoothDeviceMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServerMethods;
 use crate::dom::bindings::error::{Error, ErrorResult};
@@ -27,6 +28,9 @@ pub struct BluetoothRemoteGATTServer {
     reflector_: Reflector,
     device: Dom<BluetoothDevice>,
     connected: Cell<bool>,
+    /// <https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattserver-activealgorithms-slot>
+    #[ignore_malloc_size_of = "promises are hard"]
+    active_algorithms: DomRefCell<Vec<Rc<Promise>>>,
 }
 
 impl BluetoothRemoteGATTServer {
@@ -35,6 +39,7 @@ impl BluetoothRemoteGATTServer {
             reflector_: Reflector::new(),
             device: Dom::from_ref(device),
             connected: Cell::new(false),
+            active_algorithms: DomRefCell::new(Vec::new()),
         }
     }
 
@@ -55,6 +60,30 @@ impl BluetoothRemoteGATTServer {
     pub fn set_connected(&self, connected: bool) {
         self.connected.set(connected);
     }
+
+    pub fn add_active_algorithm(&self, promise: &Rc<Promise>) {
+        self.active_algorithms.borrow_mut().push(promise.clone());
+    }
+
+    /// Remove `promise` from the active algorithms. Returns false if it was not there anymore,
+    /// because the connection was lost since it was added.
+    pub fn remove_active_algorithm(&self, promise: &Rc<Promise>) -> bool {
+        let mut active_algorithms = self.active_algorithms.borrow_mut();
+        match active_algorithms
+            .iter()
+            .position(|active_algorithm| Rc::ptr_eq(active_algorithm, promise))
+        {
+            Some(index) => {
+                active_algorithms.swap_remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn clear_active_algorithms(&self) {
+        self.active_algorithms.borrow_mut().clear();
+    }
 }
 
 impl BluetoothRemoteGATTServerMethods for BluetoothRemoteGATTServer {
@@ -77,15 +106,16 @@ impl BluetoothRemoteGATTServerMethods for BluetoothRemoteGATTServer {
 
         // TODO: Step 3: Check if the UA is currently using the Bluetooth system.
 
-        // TODO: Step 4: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
-
-        // TODO: Step 5.1 - 5.2: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+        // Step 4.
+        self.add_active_algorithm(&p);
 
         // Note: Steps 2, 5.1.1 and 5.1.3 are in components/bluetooth/lib.rs in the gatt_server_connect function.
-        // Steps 5.2.3 - 5.2.5  are in response function.
+        // Steps 5.2.1 - 5.2.5  are in response function.
         self.get_bluetooth_thread()
             .send(BluetoothRequest::GATTServerConnect(
                 String::from(self.Device().Id()),
+                self.Device().disconnection_listener_id(),
+                self.Device().disconnection_sender(),
                 sender,
             ))
             .unwrap();
@@ -95,7 +125,8 @@ impl BluetoothRemoteGATTServerMethods for BluetoothRemoteGATTServer {
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattserver-disconnect
     fn Disconnect(&self) -> ErrorResult {
-        // TODO: Step 1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
+        // Step 1.
+        self.clear_active_algorithms();
 
         // Step 2.
         if !self.Connected() {
@@ -118,7 +149,7 @@ impl BluetoothRemoteGATTServerMethods for BluetoothRemoteGATTServer {
             BluetoothUUID::service,
             Some(service),
             String::from(self.Device().Id()),
-            self.Device().get_gatt().Connected(),
+            self,
             GATTType::PrimaryService,
         )
     }
@@ -132,7 +163,7 @@ impl BluetoothRemoteGATTServerMethods for BluetoothRemoteGATTServer {
             BluetoothUUID::service,
             service,
             String::from(self.Device().Id()),
-            self.Connected(),
+            self,
             GATTType::PrimaryService,
         )
     }
@@ -143,6 +174,14 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTServer {
         match response {
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattserver-connect
             BluetoothResponse::GATTServerConnect(connected) => {
+                // Step 5.2.1 - 5.2.2.
+                if !self.remove_active_algorithm(promise) {
+                    if let Err(e) = self.Device().garbage_collect_the_connection() {
+                        return promise.reject_error(e);
+                    }
+                    return promise.reject_error(Error::Abort);
+                }
+
                 // Step 5.2.3
                 if self.Device().is_represented_device_null() {
                     if let Err(e) = self.Device().garbage_collect_the_connection() {
@@ -175,4 +214,11 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTServer {
             _ => promise.reject_error(Error::Type("Something went wrong...".to_owned())),
         }
     }
+
+    fn handle_error(&self, error: BluetoothError, promise: &Rc<Promise>) {
+        // A connection attempt that failed is not an active algorithm anymore. The promises of
+        // other requests were already removed by the connection-checking wrapper.
+        self.remove_active_algorithm(promise);
+        promise.reject_error(Error::from(error));
+    }
 }
@@ -91,7 +91,7 @@ impl BluetoothRemoteGATTServiceMethods for BluetoothRemoteGATTService {
             BluetoothUUID::characteristic,
             Some(characteristic),
             self.get_instance_id(),
-            self.Device().get_gatt().Connected(),
+            &self.Device().get_gatt(),
             GATTType::Characteristic,
         )
     }
@@ -107,7 +107,7 @@ impl BluetoothRemoteGATTServiceMethods for BluetoothRemoteGATTService {
             BluetoothUUID::characteristic,
             characteristic,
             self.get_instance_id(),
-            self.Device().get_gatt().Connected(),
+            &self.Device().get_gatt(),
             GATTType::Characteristic,
         )
     }
@@ -120,7 +120,7 @@ impl BluetoothRemoteGATTServiceMethods for BluetoothRemoteGATTService {
             BluetoothUUID::service,
             Some(service),
             self.get_instance_id(),
-            self.Device().get_gatt().Connected(),
+            &self.Device().get_gatt(),
             GATTType::IncludedService,
         )
     }
@@ -133,7 +133,7 @@ impl BluetoothRemoteGATTServiceMethods for BluetoothRemoteGATTService {
             BluetoothUUID::service,
             service,
             self.get_instance_id(),
-            self.Device().get_gatt().Connected(),
+            &self.Device().get_gatt(),
             GATTType::IncludedService,
         )
     }
@@ -0,0 +1,140 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::{ClipboardContents, PermissionPrompt};
+
+use crate::clipboard_provider::{get_clipboard_contents, set_clipboard_contents};
+use crate::dom::bindings::codegen::Bindings::ClipboardBinding::ClipboardMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::clipboarditem::ClipboardItem;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::dom::promise::Promise;
+use crate::realms::{AlreadyInRealm, InRealm};
+
+/// <https://w3c.github.io/clipboard-apis/#clipboard-interface>
+#[dom_struct]
+pub struct Clipboard {
+    eventtarget: EventTarget,
+}
+
+impl Clipboard {
+    fn new_inherited() -> Clipboard {
+        Clipboard {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Clipboard> {
+        reflect_dom_object(Box::new(Clipboard::new_inherited()), global)
+    }
+
+    /// Create the promise returned by one of the methods of this interface.
+    fn new_promise(&self) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        Promise::new_in_current_realm(InRealm::Already(&in_realm_proof))
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#check-clipboard-read-permission>
+    fn read_permission_granted(&self) -> bool {
+        let global = self.global();
+        match get_descriptor_permission_state(PermissionName::Clipboard_read, Some(&global)) {
+            PermissionState::Granted => true,
+            PermissionState::Denied => false,
+            PermissionState::Prompt => {
+                let state = prompt_user_from_embedder(
+                    PermissionPrompt::Request(embedder_traits::PermissionName::ClipboardRead),
+                    &global,
+                );
+                global
+                    .permission_state_invocation_results()
+                    .borrow_mut()
+                    .insert(PermissionName::Clipboard_read.to_string(), state);
+                state == PermissionState::Granted
+            },
+        }
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#check-clipboard-write-permission>
+    ///
+    /// Writing is allowed without asking the user, as long as the user just interacted with
+    /// the page.
+    fn write_permission_granted(&self) -> bool {
+        self.global().as_window().has_transient_activation()
+    }
+
+    /// Read the contents of the clipboard if the user allows it, or reject `promise`.
+    fn read_clipboard(&self, promise: &Promise) -> Option<ClipboardContents> {
+        if !self.read_permission_granted() {
+            promise.reject_error(Error::NotAllowed);
+            return None;
+        }
+        Some(get_clipboard_contents(
+            self.global().script_to_constellation_chan(),
+        ))
+    }
+
+    /// Write to the clipboard if the user just interacted with the page, and settle `promise`.
+    fn write_clipboard(&self, promise: &Promise, contents: ClipboardContents) {
+        if !self.write_permission_granted() {
+            return promise.reject_error(Error::NotAllowed);
+        }
+        set_clipboard_contents(self.global().script_to_constellation_chan(), contents);
+        promise.resolve_native(&());
+    }
+}
+
+impl ClipboardMethods for Clipboard {
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-read>
+    fn Read(&self) -> Rc<Promise> {
+        let promise = self.new_promise();
+        if let Some(contents) = self.read_clipboard(&promise) {
+            let item = ClipboardItem::from_clipboard_contents(&self.global(), contents);
+            promise.resolve_native(&vec![item]);
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext>
+    fn ReadText(&self) -> Rc<Promise> {
+        let promise = self.new_promise();
+        if let Some(contents) = self.read_clipboard(&promise) {
+            promise.resolve_native(&DOMString::from(contents.text.unwrap_or_default()));
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-write>
+    fn Write(&self, data: Vec<DomRoot<ClipboardItem>>) -> Rc<Promise> {
+        let promise = self.new_promise();
+
+        // Only one item can be written, with the types the embedder supports.
+        let contents = match &*data {
+            [item] => item.to_clipboard_contents(),
+            _ => None,
+        };
+        match contents {
+            Some(contents) => self.write_clipboard(&promise, contents),
+            None => promise.reject_error(Error::NotAllowed),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-writetext>
+    fn WriteText(&self, data: DOMString) -> Rc<Promise> {
+        let promise = self.new_promise();
+        self.write_clipboard(&promise, ClipboardContents::from_text(String::from(data)));
+        promise
+    }
+}
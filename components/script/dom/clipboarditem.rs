@@ -0,0 +1,172 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::ClipboardContents;
+use js::jsval::JSVal;
+use js::rust::HandleObject;
+use script_traits::serializable::BlobImpl;
+
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::ClipboardItemMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrBlob;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::record::Record;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::blob::Blob;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::{AlreadyInRealm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// The types that can be read from and written to the clipboard.
+/// <https://w3c.github.io/clipboard-apis/#mandatory-data-types-x>
+const SUPPORTED_TYPES: [&str; 3] = ["text/plain", "text/html", "image/png"];
+
+/// The data of a clipboard item for one of its types.
+#[derive(JSTraceable, MallocSizeOf)]
+struct Representation {
+    mime_type: DOMString,
+    data: Vec<u8>,
+}
+
+/// <https://w3c.github.io/clipboard-apis/#clipboard-item-interface>
+#[dom_struct]
+pub struct ClipboardItem {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/clipboard-apis/#clipboard-item-list-of-representations>
+    representations: Vec<Representation>,
+}
+
+impl ClipboardItem {
+    fn new_inherited(representations: Vec<Representation>) -> ClipboardItem {
+        ClipboardItem {
+            reflector_: Reflector::new(),
+            representations,
+        }
+    }
+
+    fn new(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        representations: Vec<Representation>,
+    ) -> DomRoot<ClipboardItem> {
+        reflect_dom_object_with_proto(
+            Box::new(ClipboardItem::new_inherited(representations)),
+            global,
+            proto,
+        )
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-clipboarditem>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        items: Record<DOMString, StringOrBlob>,
+    ) -> Fallible<DomRoot<ClipboardItem>> {
+        // Step 1. If items is empty, then throw a TypeError.
+        if items.is_empty() {
+            return Err(Error::Type("ClipboardItem must not be empty".to_owned()));
+        }
+
+        // Step 3. For each (key, value) in items, add a representation with the MIME type key
+        // and the data of value.
+        let representations = items
+            .iter()
+            .map(|(mime_type, data)| Representation {
+                mime_type: DOMString::from(mime_type.to_ascii_lowercase()),
+                data: match data {
+                    StringOrBlob::String(text) => String::from(text.clone()).into_bytes(),
+                    StringOrBlob::Blob(blob) => blob.get_bytes().unwrap_or_default(),
+                },
+            })
+            .collect();
+        Ok(ClipboardItem::new(window.upcast(), proto, representations))
+    }
+
+    /// An item with the contents of the clipboard, in the formats that are available.
+    pub fn from_clipboard_contents(
+        global: &GlobalScope,
+        contents: ClipboardContents,
+    ) -> DomRoot<ClipboardItem> {
+        let ClipboardContents { text, html, png } = contents;
+        let representations = [
+            ("text/plain", text.map(String::into_bytes)),
+            ("text/html", html.map(String::into_bytes)),
+            ("image/png", png),
+        ]
+        .into_iter()
+        .filter_map(|(mime_type, data)| {
+            Some(Representation {
+                mime_type: DOMString::from(mime_type),
+                data: data?,
+            })
+        })
+        .collect();
+        ClipboardItem::new(global, None, representations)
+    }
+
+    /// The contents to write to the clipboard for this item, or `None` if one of its types
+    /// can't be written.
+    pub fn to_clipboard_contents(&self) -> Option<ClipboardContents> {
+        let mut contents = ClipboardContents::default();
+        for representation in &self.representations {
+            let data = representation.data.clone();
+            match &*representation.mime_type {
+                "text/plain" => contents.text = Some(String::from_utf8_lossy(&data).into_owned()),
+                "text/html" => contents.html = Some(String::from_utf8_lossy(&data).into_owned()),
+                "image/png" => contents.png = Some(data),
+                _ => return None,
+            }
+        }
+        Some(contents)
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-supports>
+    #[allow(non_snake_case)]
+    pub fn Supports(_: &Window, type_: DOMString) -> bool {
+        SUPPORTED_TYPES.contains(&&*type_)
+    }
+}
+
+impl ClipboardItemMethods for ClipboardItem {
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-types>
+    fn Types(&self, cx: JSContext) -> JSVal {
+        let types: Vec<DOMString> = self
+            .representations
+            .iter()
+            .map(|representation| representation.mime_type.clone())
+            .collect();
+        to_frozen_array(&types, cx)
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>
+    fn GetType(&self, type_: DOMString) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+        let mime_type = type_.to_ascii_lowercase();
+        match self
+            .representations
+            .iter()
+            .find(|representation| *representation.mime_type == *mime_type)
+        {
+            Some(representation) => {
+                let blob = Blob::new(
+                    &self.global(),
+                    BlobImpl::new_from_bytes(representation.data.clone(), mime_type),
+                );
+                promise.resolve_native(&blob);
+            },
+            None => promise.reject_error(Error::NotFound),
+        }
+        promise
+    }
+}
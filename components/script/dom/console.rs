@@ -6,13 +6,14 @@ use std::convert::TryFrom;
 use std::io;
 
 use devtools_traits::{ConsoleMessage, LogLevel, ScriptToDevtoolsControlMsg};
+use embedder_traits::{ConsoleLogLevel, EmbedderMsg};
 use js::jsapi::{self, ESClass, PropertyDescriptor};
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::{
     GetBuiltinClass, GetPropertyKeys, JS_GetOwnPropertyDescriptorById, JS_GetPropertyById,
     JS_IdToValue, JS_ValueToSource,
 };
-use js::rust::{describe_scripted_caller, HandleValue, IdVector};
+use js::rust::{describe_scripted_caller, HandleValue, IdVector, ScriptedCaller};
 
 use crate::dom::bindings::conversions::jsstring_to_str;
 use crate::dom::bindings::inheritance::Castable;
@@ -30,15 +31,17 @@ const MAX_LOG_CHILDREN: usize = 15;
 pub struct Console(());
 
 impl Console {
-    #[allow(unsafe_code)]
-    fn send_to_devtools(global: &GlobalScope, level: LogLevel, message: String) {
-        if let Some(chan) = global.devtools_chan() {
-            let caller =
-                unsafe { describe_scripted_caller(*GlobalScope::get_cx()) }.unwrap_or_default();
+    fn send_to_devtools(
+        global: &GlobalScope,
+        level: LogLevel,
+        message: String,
+        caller: &ScriptedCaller,
+    ) {
+        if let Some(chan) = global.running_devtools_chan() {
             let console_message = ConsoleMessage {
                 message,
                 logLevel: level,
-                filename: caller.filename,
+                filename: caller.filename.clone(),
                 lineNumber: caller.line as usize,
                 columnNumber: caller.col as usize,
             };
@@ -53,6 +56,31 @@ impl Console {
             chan.send(devtools_message).unwrap();
         }
     }
+
+    fn send_to_embedder(
+        global: &GlobalScope,
+        level: LogLevel,
+        message: &str,
+        caller: &ScriptedCaller,
+    ) {
+        let level = match level {
+            LogLevel::Log => ConsoleLogLevel::Log,
+            LogLevel::Debug => ConsoleLogLevel::Debug,
+            LogLevel::Info => ConsoleLogLevel::Info,
+            LogLevel::Warn => ConsoleLogLevel::Warn,
+            LogLevel::Error => ConsoleLogLevel::Error,
+            LogLevel::Clear => return,
+        };
+        global.send_to_embedder(EmbedderMsg::ConsoleMessage(
+            embedder_traits::ConsoleMessage {
+                level,
+                message: message.to_owned(),
+                source_url: caller.filename.clone(),
+                line: caller.line,
+                column: caller.col,
+            },
+        ));
+    }
 }
 
 // In order to avoid interleaving the stdout output of the Console API methods
@@ -207,12 +235,16 @@ fn console_messages(global: &GlobalScope, messages: Vec<HandleValue>, level: Log
     console_message(global, message, level)
 }
 
+#[allow(unsafe_code)]
 fn console_message(global: &GlobalScope, message: DOMString, level: LogLevel) {
     with_stderr_lock(move || {
         let prefix = global.current_group_label().unwrap_or_default();
         let message = format!("{}{}", prefix, message);
         println!("{}", message);
-        Console::send_to_devtools(global, level, message);
+        let caller =
+            unsafe { describe_scripted_caller(*GlobalScope::get_cx()) }.unwrap_or_default();
+        Console::send_to_embedder(global, level.clone(), &message, &caller);
+        Console::send_to_devtools(global, level, message, &caller);
     })
 }
 
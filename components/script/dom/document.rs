@@ -20,14 +20,17 @@ use cookie::Cookie;
 use cssparser::match_ignore_ascii_case;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::user_script::UserScriptRunAt;
+use embedder_traits::{
+    ContextMenuInfo, ContextMenuMediaInfo, EmbedderMsg, ImeEvent, TextInputState,
+};
 use encoding_rs::{Encoding, UTF_8};
 use euclid::default::{Point2D, Rect, Size2D};
 use html5ever::{local_name, namespace_url, ns, LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::rust::{HandleObject, HandleValue};
-use keyboard_types::{Code, Key, KeyState};
+use keyboard_types::{Code, CompositionState, Key, KeyState};
 use lazy_static::lazy_static;
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
@@ -44,7 +47,7 @@ use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
 use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
-use script_layout_interface::{PendingRestyle, ReflowGoal, TrustedNodeAddress};
+use script_layout_interface::{PendingRestyle, QueryMsg, ReflowGoal, TrustedNodeAddress};
 use script_traits::{
     AnimationState, AnimationTickType, CompositorEvent, DocumentActivity, MouseButton,
     MouseEventType, MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress,
@@ -52,7 +55,7 @@ use script_traits::{
 };
 use servo_arc::Arc;
 use servo_atoms::Atom;
-use servo_config::pref;
+use servo_config::{opts, pref};
 use servo_media::{ClientContextId, ServoMedia};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::attr::AttrValue;
@@ -81,12 +84,15 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElement_Binding::HTMLIFrameElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::Navigator_Binding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -130,7 +136,7 @@ use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
-use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlelement::{is_in_editing_host, HTMLElement};
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
 use crate::dom::htmlheadelement::HTMLHeadElement;
@@ -138,10 +144,12 @@ use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::htmlmetaelement::RefreshRedirectDue;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
+use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
 use crate::dom::messageevent::MessageEvent;
@@ -168,6 +176,7 @@ use crate::dom::touchevent::TouchEvent;
 use crate::dom::touchlist::TouchList;
 use crate::dom::treewalker::TreeWalker;
 use crate::dom::uievent::UIEvent;
+use crate::dom::userscripts::run_user_scripts;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
@@ -294,6 +303,11 @@ pub struct Document {
     focus_transaction: DomRefCell<FocusTransaction>,
     /// The element that currently has the document focus context.
     focused: MutNullableDom<Element>,
+    /// The text of the input method composition in progress in the focused element, if any.
+    ime_composition: DomRefCell<Option<String>>,
+    /// The state of the focused editable element that was last sent to the embedder.
+    #[no_trace]
+    ime_state: DomRefCell<Option<TextInputState>>,
     /// The script element that is currently executing.
     current_script: MutNullableDom<HTMLScriptElement>,
     /// <https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script>
@@ -334,6 +348,10 @@ pub struct Document {
     /// Information on elements needing restyle to ship over to layout when the
     /// time comes.
     pending_restyles: DomRefCell<HashMap<Dom<Element>, NoTrace<PendingRestyle>>>,
+    /// If a web font finished loading since the pending restyles were last drained, and so
+    /// forced the damage of every element, the addresses of the elements whose damage was
+    /// already forced before, so that devtools can still tell DOM mutations apart.
+    damaged_before_web_font_load: DomRefCell<Option<HashSet<usize>>>,
     /// This flag will be true if layout suppressed a reflow attempt that was
     /// needed in order for the page to be painted.
     needs_paint: Cell<bool>,
@@ -418,6 +436,9 @@ pub struct Document {
     delayed_tasks: DomRefCell<Vec<Box<dyn TaskBox>>>,
     /// <https://html.spec.whatwg.org/multipage/#completely-loaded>
     completely_loaded: Cell<bool>,
+    /// Whether the embedder was told that this document is no longer loading, because it was
+    /// loaded or because its load was stopped.
+    load_complete_sent_to_embedder: Cell<bool>,
     /// Set of shadow roots connected to the document tree.
     shadow_roots: DomRefCell<HashSet<Dom<ShadowRoot>>>,
     /// Whether any of the shadow roots need the stylesheets flushed.
@@ -1057,9 +1078,7 @@ impl Document {
                 update_with_current_time_ms(&self.dom_loading);
             },
             DocumentReadyState::Complete => {
-                if self.window().is_top_level() {
-                    self.send_to_embedder(EmbedderMsg::LoadComplete);
-                }
+                self.send_load_complete_to_embedder();
                 update_with_current_time_ms(&self.dom_complete);
             },
             DocumentReadyState::Interactive => update_with_current_time_ms(&self.dom_interactive),
@@ -1133,6 +1152,15 @@ impl Document {
             return;
         }
         if let Some(ref elem) = self.focused.get() {
+            // Leave the text of a composition in progress where it is.
+            let composition = self.ime_composition.borrow_mut().take();
+            if let Some(text) = composition {
+                self.dispatch_composition_event(keyboard_types::CompositionEvent {
+                    state: CompositionState::End,
+                    data: text,
+                });
+            }
+
             let node = elem.upcast::<Node>();
             elem.set_focus_state(false);
             // FIXME: pass appropriate relatedTarget
@@ -1146,6 +1174,20 @@ impl Document {
 
         self.focused.set(possibly_focused.as_deref());
 
+        let global = self.window.upcast::<GlobalScope>();
+        if global.live_devtools_updates() {
+            if let Some(chan) = global.devtools_chan() {
+                let node = self
+                    .focused
+                    .get()
+                    .map(|elem| elem.upcast::<Node>().summarize());
+                let _ = chan.send(ScriptToDevtoolsControlMsg::FocusChanged(
+                    global.pipeline_id(),
+                    node,
+                ));
+            }
+        }
+
         if let Some(ref elem) = self.focused.get() {
             elem.set_focus_state(true);
             let node = elem.upcast::<Node>();
@@ -1159,11 +1201,6 @@ impl Document {
 
             // Notify the embedder to display an input method.
             if let Some(kind) = elem.input_method_type() {
-                let rect = elem.upcast::<Node>().bounding_content_box_or_zero();
-                let rect = Rect::new(
-                    Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
-                    Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
-                );
                 let (text, multiline) = if let Some(input) = elem.downcast::<HTMLInputElement>() {
                     (
                         Some((
@@ -1187,10 +1224,12 @@ impl Document {
                     kind,
                     text,
                     multiline,
-                    DeviceIntRect::from_untyped(&rect.to_box2d()),
+                    input_method_bounds(elem),
                 ));
             }
         }
+
+        self.update_ime_state();
     }
 
     /// Handles any updates when the document's title has changed.
@@ -1280,6 +1319,11 @@ impl Document {
             self.request_focus(Some(&*el), FocusType::Element);
         }
 
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if let MouseEventType::MouseDown = mouse_event_type {
+            self.window.notify_activation();
+        }
+
         // https://w3c.github.io/uievents/#event-type-click
         let client_x = client_point.x as i32;
         let client_y = client_point.y as i32;
@@ -1327,6 +1371,15 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+
+                if let MouseButton::Right = button {
+                    self.maybe_request_context_menu(
+                        client_point,
+                        &el,
+                        pressed_mouse_buttons,
+                        point_in_node,
+                    );
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -1347,6 +1400,81 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Fire a `contextmenu` event at the element that was pressed with the secondary button and,
+    /// unless the page cancels it, ask the embedder to show a context menu for that element.
+    /// <https://w3c.github.io/pointerevents/#contextmenu>
+    fn maybe_request_context_menu(
+        &self,
+        client_point: Point2D<f32>,
+        target: &Element,
+        pressed_mouse_buttons: u16,
+        point_in_node: Option<Point2D<f32>>,
+    ) {
+        let client_x = client_point.x as i32;
+        let client_y = client_point.y as i32;
+        let event = MouseEvent::new(
+            &self.window,
+            DOMString::from("contextmenu"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            2i16,
+            pressed_mouse_buttons,
+            None,
+            point_in_node,
+        );
+        if let EventStatus::Canceled = event.upcast::<Event>().fire(target.upcast()) {
+            return;
+        }
+        let info = self.context_menu_info(target);
+        self.send_to_embedder(EmbedderMsg::ContextMenuRequested(info));
+    }
+
+    fn context_menu_info(&self, target: &Element) -> ContextMenuInfo {
+        let link_url = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter(|node| node.is::<HTMLAnchorElement>() || node.is::<HTMLAreaElement>())
+            .filter_map(|node| {
+                node.downcast::<Element>()?
+                    .get_attribute(&ns!(), &local_name!("href"))
+            })
+            .find_map(|href| self.base_url().join(&href.value()).ok());
+        let image_url = target
+            .downcast::<HTMLImageElement>()
+            .and_then(|image| ServoUrl::parse(&image.CurrentSrc()).ok());
+        let selected_text = self
+            .GetSelection()
+            .map(|selection| String::from(selection.Stringifier()))
+            .filter(|text| !text.is_empty());
+        let media = target
+            .downcast::<HTMLMediaElement>()
+            .map(|media| ContextMenuMediaInfo {
+                source_url: ServoUrl::parse(&media.CurrentSrc()).ok(),
+                is_video: target.is::<HTMLVideoElement>(),
+                paused: media.Paused(),
+                muted: media.Muted(),
+                has_controls: media.Controls(),
+            });
+        ContextMenuInfo {
+            link_url,
+            image_url,
+            selected_text,
+            is_editable: target.input_method_type().is_some() ||
+                is_in_editing_host(target.upcast()),
+            media,
+        }
+    }
+
     fn maybe_fire_dblclick(
         &self,
         click_pos: Point2D<f32>,
@@ -1678,6 +1806,11 @@ impl Document {
             TouchEventType::Cancel => "touchcancel",
         };
 
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if let TouchEventType::Up = event_type {
+            self.window.notify_activation();
+        }
+
         let el = node_address.and_then(|address| {
             let node = node::from_untrusted_node_address(address);
             node.inclusive_ancestors(ShadowIncluding::No)
@@ -1772,6 +1905,11 @@ impl Document {
 
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if keyboard_event.state == KeyState::Down && keyboard_event.key != Key::Escape {
+            self.window.notify_activation();
+        }
+
         let focused = self.get_focused_element();
         let body = self.GetBody();
 
@@ -1849,6 +1987,7 @@ impl Document {
         }
 
         self.window.reflow(ReflowGoal::Full, ReflowReason::KeyEvent);
+        self.update_ime_state();
     }
 
     pub fn ime_dismissed(&self) {
@@ -1858,6 +1997,62 @@ impl Document {
         )
     }
 
+    /// Turn an edit from the input method of the embedder into composition events for the
+    /// focused element, which apply the edit to its text.
+    pub fn handle_ime_event(&self, ime_event: ImeEvent) {
+        let composing = self.ime_composition.borrow().is_some();
+        let (text, commit) = match ime_event {
+            // An empty composition that was never started needs no events.
+            ImeEvent::SetComposition(text) if !composing && text.is_empty() => return,
+            ImeEvent::SetComposition(text) => (text, false),
+            ImeEvent::Commit(text) => (text, true),
+            ImeEvent::Cancel if !composing => return,
+            ImeEvent::Cancel => (String::new(), true),
+        };
+
+        if !composing {
+            self.dispatch_composition_event(keyboard_types::CompositionEvent {
+                state: CompositionState::Start,
+                data: String::new(),
+            });
+        }
+        *self.ime_composition.borrow_mut() = if commit { None } else { Some(text.clone()) };
+        self.dispatch_composition_event(keyboard_types::CompositionEvent {
+            state: CompositionState::Update,
+            data: text.clone(),
+        });
+        if commit {
+            self.dispatch_composition_event(keyboard_types::CompositionEvent {
+                state: CompositionState::End,
+                data: text,
+            });
+        }
+
+        self.window.reflow(ReflowGoal::Full, ReflowReason::KeyEvent);
+        self.update_ime_state();
+    }
+
+    /// Send the state of the focused editable element to the embedder, if it changed since it
+    /// was last sent.
+    fn update_ime_state(&self) {
+        let state = self.focused.get().and_then(|elem| {
+            let kind = elem.input_method_type()?;
+            if let Some(input) = elem.downcast::<HTMLInputElement>() {
+                Some(input.text_input_state(kind, input_method_bounds(&elem)))
+            } else {
+                elem.downcast::<HTMLTextAreaElement>()
+                    .map(|textarea| textarea.text_input_state(kind, input_method_bounds(&elem)))
+            }
+        });
+        if *self.ime_state.borrow() == state {
+            return;
+        }
+        *self.ime_state.borrow_mut() = state.clone();
+        if let Some(state) = state {
+            self.send_to_embedder(EmbedderMsg::UpdateIME(state));
+        }
+    }
+
     pub fn dispatch_composition_event(
         &self,
         composition_event: ::keyboard_types::CompositionEvent,
@@ -2004,6 +2199,10 @@ impl Document {
         ident
     }
 
+    pub fn has_pending_animation_frame_callbacks(&self) -> bool {
+        !self.animation_frame_list.borrow().is_empty()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
     pub fn cancel_animation_frame(&self, ident: u32) {
         let mut list = self.animation_frame_list.borrow_mut();
@@ -2097,6 +2296,7 @@ impl Document {
     ) {
         request.csp_list = self.get_csp_list().map(|x| x.clone());
         request.https_state = self.https_state.get();
+        request.target_webview_id = self.window.webview_id();
         let mut loader = self.loader.borrow_mut();
         loader.fetch_async(load, request, fetch_target);
     }
@@ -2279,6 +2479,12 @@ impl Document {
         // https://w3c.github.io/FileAPI/#lifeTime
         global_scope.clean_up_all_file_resources();
 
+        // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
+        // The watches end once the document is not fully active.
+        if let Some(bluetooth) = self.window.bluetooth() {
+            bluetooth.abort_all_active_watch_advertisements();
+        }
+
         // Step 15, End
         self.decr_ignore_opens_during_unload_counter();
     }
@@ -2349,6 +2555,8 @@ impl Document {
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
                     update_with_current_time_ms(&document.load_event_end);
 
+                    run_user_scripts(&document, UserScriptRunAt::DocumentIdle);
+
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
                     if let Some(fragment) = document.url().fragment() {
@@ -2452,6 +2660,14 @@ impl Document {
         self.completely_loaded.get()
     }
 
+    /// Tell the embedder that this top-level document is no longer loading, unless it already
+    /// knows. A stopped document can still reach the complete state afterwards.
+    pub fn send_load_complete_to_embedder(&self) {
+        if self.window().is_top_level() && !self.load_complete_sent_to_embedder.replace(true) {
+            self.send_to_embedder(EmbedderMsg::LoadComplete);
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script
     pub fn set_pending_parsing_blocking_script(
         &self,
@@ -2596,6 +2812,7 @@ impl Document {
             .queue(
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
+                run_user_scripts(&document, UserScriptRunAt::DocumentEnd);
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
                 update_with_current_time_ms(&document.dom_content_loaded_event_end);
                 }),
@@ -2949,6 +3166,19 @@ impl Document {
         &self,
         depth: &ResizeObservationDepth,
     ) -> bool {
+        if self.resize_observers.borrow().is_empty() {
+            return false;
+        }
+
+        // Lay out once for all the observation targets. The callbacks broadcast by the previous
+        // iteration of the resize observer loop only dirtied the subtrees they changed, which
+        // this reflow restyles and lays out incrementally, and the size of every target is then
+        // read from its fragment tree. If layout did not run, that fragment tree is stale, so
+        // gathering waits for the next update of the rendering instead of reporting stale sizes.
+        if !self.window.layout_reflow(QueryMsg::ContentBoxes) {
+            return false;
+        }
+
         let mut has_active_resize_observations = false;
         for observer in self.resize_observers.borrow_mut().iter_mut() {
             observer.gather_active_resize_observations_at_depth(
@@ -2998,6 +3228,18 @@ fn is_character_value_key(key: &Key) -> bool {
     matches!(key, Key::Character(_) | Key::Enter)
 }
 
+/// Where the embedder should place its input method for the given editable element.
+///
+/// TODO: Use the rect of the caret once layout can report it.
+fn input_method_bounds(elem: &Element) -> DeviceIntRect {
+    let rect = elem.upcast::<Node>().bounding_content_box_or_zero();
+    let rect = Rect::new(
+        Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+        Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+    );
+    DeviceIntRect::from_untyped(&rect.to_box2d())
+}
+
 #[derive(MallocSizeOf, PartialEq)]
 pub enum DocumentSource {
     FromParser,
@@ -3126,6 +3368,12 @@ fn url_has_network_scheme(url: &ServoUrl) -> bool {
     matches!(url.scheme(), "ftp" | "http" | "https")
 }
 
+/// Whether the animation timeline only moves when a test advances it through
+/// `TestBinding::advanceClock`, rather than following the wall clock.
+fn animation_clock_is_managed_by_test() -> bool {
+    pref!(layout.animations.test.enabled) || pref!(layout.testing.deterministic.enabled)
+}
+
 #[derive(Clone, Copy, Eq, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum HasBrowsingContext {
     No,
@@ -3223,6 +3471,8 @@ impl Document {
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             focus_transaction: DomRefCell::new(FocusTransaction::NotInTransaction),
             focused: Default::default(),
+            ime_composition: Default::default(),
+            ime_state: Default::default(),
             current_script: Default::default(),
             pending_parsing_blocking_script: Default::default(),
             script_blocking_stylesheets_count: Cell::new(0u32),
@@ -3239,6 +3489,7 @@ impl Document {
             base_element: Default::default(),
             appropriate_template_contents_owner_document: Default::default(),
             pending_restyles: DomRefCell::new(HashMap::new()),
+            damaged_before_web_font_load: Default::default(),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
             dom_loading: Cell::new(Default::default()),
@@ -3274,6 +3525,7 @@ impl Document {
             responsive_images: Default::default(),
             redirect_count: Cell::new(0),
             completely_loaded: Cell::new(false),
+            load_complete_sent_to_embedder: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
             delayed_tasks: Default::default(),
             shadow_roots: DomRefCell::new(HashSet::new()),
@@ -3283,7 +3535,7 @@ impl Document {
             dirty_webgpu_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
             selection: MutNullableDom::new(None),
-            animation_timeline: if pref!(layout.animations.test.enabled) {
+            animation_timeline: if animation_clock_is_managed_by_test() {
                 DomRefCell::new(AnimationTimeline::new_for_testing())
             } else {
                 DomRefCell::new(AnimationTimeline::new())
@@ -3892,6 +4144,30 @@ impl Document {
         }
     }
 
+    /// Whether this document is allowed to use the policy-controlled feature with the given name,
+    /// whose default allowlist is `'self'`.
+    /// <https://html.spec.whatwg.org/multipage/#allowed-to-use>
+    pub fn is_allowed_to_use_feature(&self, feature: &str) -> bool {
+        // Step 1.
+        if self.browsing_context().is_none() {
+            return false;
+        }
+        // Step 2.
+        if !self.is_fully_active() {
+            return false;
+        }
+        // Step 3.
+        // https://w3c.github.io/webappsec-permissions-policy/#algo-define-inherited-policy-in-container
+        if self.window().is_top_level() {
+            return true;
+        }
+        // The container policy comes with the load of the document, as the iframe may be in
+        // another script thread.
+        self.window()
+            .container_policy()
+            .is_some_and(|policy| policy.allows(feature, self.origin().immutable()))
+    }
+
     fn reset_form_owner_for_listeners(&self, id: &Atom) {
         let map = self.form_id_listener_map.borrow();
         if let Some(listeners) = map.get(id) {
@@ -4008,6 +4284,33 @@ impl Document {
         })
     }
 
+    /// Restyle every element because a web font finished loading.
+    pub(crate) fn dirty_all_nodes_for_web_font(&self) {
+        {
+            let mut damaged = self.damaged_before_web_font_load.borrow_mut();
+            // The damage forced by an earlier font load can not be told apart anymore.
+            if damaged.is_none() {
+                let pending_restyles = self.pending_restyles.borrow();
+                *damaged = Some(
+                    pending_restyles
+                        .iter()
+                        .filter(|(_, restyle)| !restyle.0.damage.is_empty())
+                        .map(|(element, _)| {
+                            element.upcast::<Node>().to_trusted_node_address().0 as usize
+                        })
+                        .collect(),
+                );
+            }
+        }
+        self.dirty_all_nodes();
+    }
+
+    /// If a web font finished loading since the pending restyles were last drained, the
+    /// addresses of the elements whose damage was forced before it.
+    pub(crate) fn take_damaged_before_web_font_load(&self) -> Option<HashSet<usize>> {
+        self.damaged_before_web_font_load.borrow_mut().take()
+    }
+
     #[allow(crown::unrooted_must_root)]
     pub fn drain_pending_restyles(&self) -> Vec<(TrustedNodeAddress, PendingRestyle)> {
         self.pending_restyles
@@ -4034,7 +4337,7 @@ impl Document {
 
     pub(crate) fn update_animation_timeline(&self) {
         // Only update the time if it isn't being managed by a test.
-        if !pref!(layout.animations.test.enabled) {
+        if !animation_clock_is_managed_by_test() {
             self.animation_timeline.borrow_mut().update();
         }
 
@@ -4179,7 +4482,13 @@ impl DocumentMethods for Document {
             Some(host) => host,
         };
 
-        // Step 6
+        // Step 6. With `--isolate-origins`, documents of other origins of the same site might
+        // run in another event loop, so they are in an origin-keyed agent cluster.
+        if opts::get().isolate_origins {
+            return Ok(());
+        }
+
+        // Step 7
         self.origin.set_domain(host);
 
         Ok(())
@@ -100,6 +100,15 @@ impl DocumentOrShadowRoot {
             .query_nodes_from_point(*client_point, query_type)
     }
 
+    /// The element that a hit test result belongs to: the node itself for elements, or the
+    /// parent element for the text runs of an element.
+    fn element_for_hit_node(node: &Node) -> Option<DomRoot<Element>> {
+        match node.downcast::<Element>() {
+            Some(element) => Some(DomRoot::from_ref(element)),
+            None => node.GetParentElement(),
+        }
+    }
+
     #[allow(unsafe_code)]
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     pub fn element_from_point(
@@ -128,12 +137,7 @@ impl DocumentOrShadowRoot {
         {
             Some(address) => {
                 let node = unsafe { node::from_untrusted_node_address(*address) };
-                let parent_node = node.GetParentNode().unwrap();
-                let element_ref = node
-                    .downcast::<Element>()
-                    .unwrap_or_else(|| parent_node.downcast::<Element>().unwrap());
-
-                Some(DomRoot::from_ref(element_ref))
+                Self::element_for_hit_node(&node).or(document_element)
             },
             None => document_element,
         }
@@ -164,13 +168,17 @@ impl DocumentOrShadowRoot {
 
         // Step 1 and Step 3
         let nodes = self.nodes_from_point(point, NodesFromPointQueryType::All);
-        let mut elements: Vec<DomRoot<Element>> = nodes
-            .iter()
-            .flat_map(|&untrusted_node_address| {
-                let node = unsafe { node::from_untrusted_node_address(untrusted_node_address) };
-                DomRoot::downcast::<Element>(node)
-            })
-            .collect();
+        let mut elements: Vec<DomRoot<Element>> = vec![];
+        for &untrusted_node_address in &nodes {
+            let node = unsafe { node::from_untrusted_node_address(untrusted_node_address) };
+            // An element with several boxes, or with text runs of its own, can be hit more
+            // than once, but it must only appear in the sequence once.
+            if let Some(element) = Self::element_for_hit_node(&node) {
+                if !elements.contains(&element) {
+                    elements.push(element);
+                }
+            }
+        }
 
         // Step 4
         if let Some(root_element) = document_element {
@@ -44,6 +44,7 @@ pub enum DOMErrorName {
     EncodingError,
     NotReadableError,
     OperationError,
+    NotAllowedError,
 }
 
 impl DOMErrorName {
@@ -74,6 +75,7 @@ impl DOMErrorName {
             "EncodingError" => Some(DOMErrorName::EncodingError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
             "OperationError" => Some(DOMErrorName::OperationError),
+            "NotAllowedError" => Some(DOMErrorName::NotAllowedError),
             _ => None,
         }
     }
@@ -124,6 +126,9 @@ impl DOMException {
             DOMErrorName::OperationError => {
                 "The operation failed for an operation-specific reason."
             },
+            DOMErrorName::NotAllowedError => {
+                "The request is not allowed by the user agent or the platform in the current context."
+            },
         };
 
         (
@@ -339,6 +339,22 @@ impl Element {
         }
     }
 
+    /// Restyle this element because a change elsewhere in the tree may have changed which
+    /// structural selectors (sibling combinators, `:nth-child()`, `:first-child`, ...) match it.
+    /// Its descendants are restyled too, since selectors such as `:first-child > p` can match
+    /// them through this element. Unlike [`Self::restyle`], this doesn't force damage: the style
+    /// system derives the damage from the styles that actually changed, so an element whose
+    /// computed style is unaffected keeps its boxes.
+    fn restyle_for_selector_change(&self) {
+        if !self.upcast::<Node>().is_connected() {
+            return;
+        }
+        let doc = self.node.owner_doc();
+        doc.ensure_pending_restyle(self)
+            .hint
+            .insert(RestyleHint::restyle_subtree());
+    }
+
     pub fn set_is(&self, is: LocalName) {
         *self.is.borrow_mut() = Some(is);
     }
@@ -3540,21 +3556,30 @@ impl VirtualMethods for Element {
 
         let flags = self.selector_flags.get();
         if flags.intersects(ElementSelectorFlags::HAS_SLOW_SELECTOR) {
-            // All children of this node need to be restyled when any child changes.
-            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+            // Selectors like `:nth-child()` may now match any of the children differently.
+            for child in self
+                .upcast::<Node>()
+                .children()
+                .filter_map(DomRoot::downcast::<Element>)
+            {
+                child.restyle_for_selector_change();
+            }
         } else {
             if flags.intersects(ElementSelectorFlags::HAS_SLOW_SELECTOR_LATER_SIBLINGS) {
                 if let Some(next_child) = mutation.next_child() {
-                    for child in next_child.inclusively_following_siblings() {
-                        if child.is::<Element>() {
-                            child.dirty(NodeDamage::OtherNodeDamage);
-                        }
+                    for child in next_child
+                        .inclusively_following_siblings()
+                        .filter_map(DomRoot::downcast::<Element>)
+                    {
+                        child.restyle_for_selector_change();
                     }
                 }
             }
             if flags.intersects(ElementSelectorFlags::HAS_EDGE_CHILD_SELECTOR) {
                 if let Some(child) = mutation.modified_edge_element() {
-                    child.dirty(NodeDamage::OtherNodeDamage);
+                    if let Some(child) = child.downcast::<Element>() {
+                        child.restyle_for_selector_change();
+                    }
                 }
             }
         }
@@ -350,13 +350,29 @@ impl EventListeners {
 pub struct EventTarget {
     reflector_: Reflector,
     handlers: DomRefCell<HashMapTracedValues<Atom, EventListeners, BuildHasherDefault<FnvHasher>>>,
+    /// Called with the type of every listener added, for the targets that only start watching
+    /// for their events once someone listens.
+    #[ignore_malloc_size_of = "fn pointer"]
+    #[no_trace]
+    listener_added_hook: Option<ListenerAddedHook>,
 }
 
+/// A hook called with an event target and the type of a listener that was added to it.
+pub type ListenerAddedHook = fn(&EventTarget, &Atom);
+
 impl EventTarget {
     pub fn new_inherited() -> EventTarget {
         EventTarget {
             reflector_: Reflector::new(),
             handlers: DomRefCell::new(Default::default()),
+            listener_added_hook: None,
+        }
+    }
+
+    pub fn new_inherited_with_listener_added_hook(hook: ListenerAddedHook) -> EventTarget {
+        EventTarget {
+            listener_added_hook: Some(hook),
+            ..EventTarget::new_inherited()
         }
     }
 
@@ -409,8 +425,9 @@ impl EventTarget {
 
     /// <https://html.spec.whatwg.org/multipage/#event-handler-attributes:event-handlers-11>
     fn set_inline_event_listener(&self, ty: Atom, listener: Option<InlineEventListener>) {
+        let added = listener.is_some();
         let mut handlers = self.handlers.borrow_mut();
-        let entries = match handlers.entry(ty) {
+        let entries = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -440,6 +457,17 @@ impl EventTarget {
                 }
             },
         }
+        drop(handlers);
+
+        if added {
+            self.listener_added(&ty);
+        }
+    }
+
+    fn listener_added(&self, ty: &Atom) {
+        if let Some(hook) = self.listener_added_hook {
+            hook(self, ty);
+        }
     }
 
     pub fn remove_listener_if_once(&self, ty: &Atom, listener: &Rc<EventListener>) {
@@ -703,8 +731,9 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entry = match handlers.entry(Atom::from(ty)) {
+        let entry = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -722,6 +751,9 @@ impl EventTarget {
         if !entry.contains(&new_entry) {
             entry.push(new_entry);
         }
+        drop(handlers);
+
+        self.listener_added(&ty);
     }
 
     // https://dom.spec.whatwg.org/#dom-eventtarget-removeeventlistener
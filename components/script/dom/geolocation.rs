@@ -0,0 +1,382 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dom_struct::dom_struct;
+use embedder_traits::{GeolocationUpdate, PermissionPrompt};
+use euclid::Length;
+use script_traits::ScriptMsg;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::{
+    GeolocationMethods, PositionCallback, PositionErrorCallback, PositionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::GeolocationPositionErrorConstants;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::geolocationposition::GeolocationPosition;
+use crate::dom::geolocationpositionerror::GeolocationPositionError;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::task_source::TaskSource;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+
+/// A call to `getCurrentPosition()` or `watchPosition()` that waits for a position.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PositionRequest {
+    #[ignore_malloc_size_of = "Rc are hard"]
+    success_callback: Rc<PositionCallback>,
+    #[ignore_malloc_size_of = "Rc are hard"]
+    error_callback: Option<Rc<PositionErrorCallback>>,
+    /// Whether the request was made with `watchPosition()`, in which case it waits for all the
+    /// positions until `clearWatch()` is called.
+    is_watch: bool,
+    high_accuracy: bool,
+    /// Reports a timeout if no position arrives before the timeout of the options elapses.
+    timeout_handle: Option<OneshotTimerHandle>,
+}
+
+/// <https://w3c.github.io/geolocation/#geolocation_interface>
+#[dom_struct]
+pub struct Geolocation {
+    reflector_: Reflector,
+    /// The requests that wait for a position, by id. The id of a watch is the one returned by
+    /// `watchPosition()`.
+    requests: DomRefCell<HashMap<i32, PositionRequest>>,
+    next_request_id: Cell<i32>,
+    /// <https://w3c.github.io/geolocation/#dfn-cachedposition>
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    cached_position: DomRefCell<Option<embedder_traits::GeolocationPosition>>,
+    /// Whether the constellation was asked for position updates, and if so, whether with high
+    /// accuracy.
+    requested_updates: Cell<Option<bool>>,
+}
+
+impl Geolocation {
+    fn new_inherited() -> Geolocation {
+        Geolocation {
+            reflector_: Reflector::new(),
+            requests: DomRefCell::new(HashMap::new()),
+            next_request_id: Cell::new(1),
+            cached_position: DomRefCell::new(None),
+            requested_updates: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Geolocation> {
+        reflect_dom_object(Box::new(Geolocation::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dfn-request-a-position>
+    fn request_position(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+        is_watch: bool,
+    ) -> i32 {
+        let request_id = self.next_request_id.get();
+        self.next_request_id.set(request_id + 1);
+        self.requests.borrow_mut().insert(
+            request_id,
+            PositionRequest {
+                success_callback,
+                error_callback,
+                is_watch,
+                high_accuracy: options.enableHighAccuracy,
+                timeout_handle: None,
+            },
+        );
+
+        // Step 6-7. Request permission to use "geolocation", and report an error if it is
+        // denied.
+        if !self.permission_granted() {
+            self.queue_request_task(request_id, |geolocation, request_id| {
+                geolocation.fail_request(
+                    request_id,
+                    GeolocationPositionErrorConstants::PERMISSION_DENIED,
+                    "User denied Geolocation",
+                    true,
+                );
+            });
+            return request_id;
+        }
+
+        // Use the cached position if it is recent enough.
+        let cached_position_is_fresh =
+            self.cached_position
+                .borrow()
+                .as_ref()
+                .is_some_and(|position| {
+                    now_in_milliseconds().saturating_sub(position.timestamp) <=
+                        options.maximumAge as u64
+                });
+        if cached_position_is_fresh {
+            self.queue_request_task(request_id, |geolocation, request_id| {
+                let position = geolocation.cached_position.borrow().clone();
+                if let Some(position) = position {
+                    geolocation.succeed_request(request_id, &position);
+                }
+            });
+            if !is_watch {
+                return request_id;
+            }
+        }
+
+        if options.timeout != u32::MAX {
+            let callback = OneshotTimerCallback::GeolocationTimeout(GeolocationTimeoutCallback {
+                geolocation: Trusted::new(self),
+                request_id,
+            });
+            let duration = Length::new(options.timeout as u64);
+            let handle = self.global().schedule_callback(callback, duration);
+            if let Some(request) = self.requests.borrow_mut().get_mut(&request_id) {
+                request.timeout_handle = Some(handle);
+            }
+        }
+        self.update_requested_updates();
+        request_id
+    }
+
+    /// Whether the document may use the position of the device, asking the user if it was not
+    /// decided yet.
+    fn permission_granted(&self) -> bool {
+        let global = self.global();
+        match get_descriptor_permission_state(PermissionName::Geolocation, Some(&global)) {
+            PermissionState::Granted => true,
+            PermissionState::Denied => false,
+            PermissionState::Prompt => {
+                let state = prompt_user_from_embedder(
+                    PermissionPrompt::Request(embedder_traits::PermissionName::Geolocation),
+                    &global,
+                );
+                global
+                    .permission_state_invocation_results()
+                    .borrow_mut()
+                    .insert(PermissionName::Geolocation.to_string(), state);
+                state == PermissionState::Granted
+            },
+        }
+    }
+
+    /// Run `steps` for a request in a task, so that its callbacks are not called before
+    /// `getCurrentPosition()` or `watchPosition()` returns.
+    fn queue_request_task(&self, request_id: i32, steps: fn(&Geolocation, i32)) {
+        let global = self.global();
+        let geolocation = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(geolocation_request_steps: move || {
+                steps(&geolocation.root(), request_id);
+            }),
+            &global,
+        );
+    }
+
+    /// Call the success callback of a request, which is then removed unless it is a watch.
+    fn succeed_request(&self, request_id: i32, position: &embedder_traits::GeolocationPosition) {
+        let callback = {
+            let mut requests = self.requests.borrow_mut();
+            // The request may have been cleared by a callback called before this one.
+            let Some(request) = requests.get_mut(&request_id) else {
+                return;
+            };
+            if let Some(handle) = request.timeout_handle.take() {
+                self.global().unschedule_callback(handle);
+            }
+            let callback = request.success_callback.clone();
+            if !request.is_watch {
+                requests.remove(&request_id);
+            }
+            callback
+        };
+        let position = GeolocationPosition::new(&self.global(), position);
+        let _ = callback.Call__(&position, ExceptionHandling::Report);
+    }
+
+    /// Call the error callback of a request, which is then removed unless it is a watch that
+    /// should keep waiting for positions.
+    fn fail_request(&self, request_id: i32, code: u16, message: &str, remove_watch: bool) {
+        let callback = {
+            let mut requests = self.requests.borrow_mut();
+            let Some(request) = requests.get_mut(&request_id) else {
+                return;
+            };
+            if let Some(handle) = request.timeout_handle.take() {
+                self.global().unschedule_callback(handle);
+            }
+            let callback = request.error_callback.clone();
+            if remove_watch || !request.is_watch {
+                requests.remove(&request_id);
+            }
+            callback
+        };
+        self.update_requested_updates();
+        if let Some(callback) = callback {
+            let error =
+                GeolocationPositionError::new(&self.global(), code, DOMString::from(message));
+            let _ = callback.Call__(&error, ExceptionHandling::Report);
+        }
+    }
+
+    fn handle_timeout(&self, request_id: i32) {
+        if let Some(request) = self.requests.borrow_mut().get_mut(&request_id) {
+            request.timeout_handle = None;
+        }
+        self.fail_request(
+            request_id,
+            GeolocationPositionErrorConstants::TIMEOUT,
+            "Position acquisition timed out",
+            false,
+        );
+    }
+
+    /// Deliver a position, or the reason it can't be acquired, to the requests that wait for
+    /// one, in the order they were made.
+    pub fn handle_update(&self, update: GeolocationUpdate) {
+        if !self.global().as_window().Document().is_fully_active() {
+            return;
+        }
+        let mut request_ids: Vec<i32> = self.requests.borrow().keys().cloned().collect();
+        request_ids.sort_unstable();
+        match update {
+            GeolocationUpdate::Position(position) => {
+                *self.cached_position.borrow_mut() = Some(position.clone());
+                for request_id in request_ids {
+                    self.succeed_request(request_id, &position);
+                }
+            },
+            GeolocationUpdate::PositionUnavailable(reason) => {
+                for request_id in request_ids {
+                    self.fail_request(
+                        request_id,
+                        GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                        &reason,
+                        false,
+                    );
+                }
+            },
+        }
+        self.update_requested_updates();
+    }
+
+    /// Tell the constellation whether this document waits for position updates, and with
+    /// which accuracy, when that changes.
+    fn update_requested_updates(&self) {
+        let requested_updates = {
+            let requests = self.requests.borrow();
+            if requests.is_empty() {
+                None
+            } else {
+                Some(requests.values().any(|request| request.high_accuracy))
+            }
+        };
+        if requested_updates == self.requested_updates.replace(requested_updates) {
+            return;
+        }
+        let msg = match requested_updates {
+            Some(high_accuracy) => ScriptMsg::StartGeolocationUpdates(high_accuracy),
+            None => ScriptMsg::StopGeolocationUpdates,
+        };
+        self.global().as_window().send_to_constellation(msg);
+    }
+}
+
+impl GeolocationMethods for Geolocation {
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-getcurrentposition>
+    fn GetCurrentPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) {
+        // Step 1. If this's relevant global object's associated Document is not fully active,
+        // call back with error POSITION_UNAVAILABLE.
+        if !self.global().as_window().Document().is_fully_active() {
+            if let Some(error_callback) = error_callback {
+                let error = GeolocationPositionError::new(
+                    &self.global(),
+                    GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                    DOMString::from("Document is not fully active"),
+                );
+                let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+            }
+            return;
+        }
+
+        // Step 2. Request a position.
+        self.request_position(success_callback, error_callback, options, false);
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-watchposition>
+    fn WatchPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) -> i32 {
+        // Step 1. If this's relevant global object's associated Document is not fully active,
+        // call back with error POSITION_UNAVAILABLE and return 0.
+        if !self.global().as_window().Document().is_fully_active() {
+            if let Some(error_callback) = error_callback {
+                let error = GeolocationPositionError::new(
+                    &self.global(),
+                    GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                    DOMString::from("Document is not fully active"),
+                );
+                let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+            }
+            return 0;
+        }
+
+        // Step 2-5. Request a position repeatedly, with a new watch id.
+        self.request_position(success_callback, error_callback, options, true)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocation-clearwatch>
+    fn ClearWatch(&self, watch_id: i32) {
+        let request = {
+            let mut requests = self.requests.borrow_mut();
+            match requests.get(&watch_id) {
+                Some(request) if request.is_watch => requests.remove(&watch_id),
+                _ => None,
+            }
+        };
+        if let Some(handle) = request.and_then(|request| request.timeout_handle) {
+            self.global().unschedule_callback(handle);
+        }
+        self.update_requested_updates();
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct GeolocationTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    geolocation: Trusted<Geolocation>,
+    request_id: i32,
+}
+
+impl GeolocationTimeoutCallback {
+    pub fn invoke(self) {
+        self.geolocation.root().handle_timeout(self.request_id);
+    }
+}
+
+fn now_in_milliseconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition;
+
+use crate::dom::bindings::codegen::Bindings::GeolocationCoordinatesBinding::GeolocationCoordinatesMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+
+#[dom_struct]
+pub struct GeolocationCoordinates {
+    reflector_: Reflector,
+    accuracy: f64,
+    latitude: f64,
+    longitude: f64,
+    altitude: Option<f64>,
+    altitude_accuracy: Option<f64>,
+    heading: Option<f64>,
+    speed: Option<f64>,
+}
+
+impl GeolocationCoordinates {
+    fn new_inherited(position: &GeolocationPosition) -> GeolocationCoordinates {
+        GeolocationCoordinates {
+            reflector_: Reflector::new(),
+            accuracy: position.accuracy,
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude: position.altitude,
+            altitude_accuracy: position.altitude_accuracy,
+            heading: position.heading,
+            speed: position.speed,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        position: &GeolocationPosition,
+    ) -> DomRoot<GeolocationCoordinates> {
+        reflect_dom_object(
+            Box::new(GeolocationCoordinates::new_inherited(position)),
+            global,
+        )
+    }
+}
+
+impl GeolocationCoordinatesMethods for GeolocationCoordinates {
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-accuracy>
+    fn Accuracy(&self) -> f64 {
+        self.accuracy
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-latitude>
+    fn Latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-longitude>
+    fn Longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-altitude>
+    fn GetAltitude(&self) -> Option<f64> {
+        self.altitude
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-altitudeaccuracy>
+    fn GetAltitudeAccuracy(&self) -> Option<f64> {
+        self.altitude_accuracy
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-heading>
+    fn GetHeading(&self) -> Option<f64> {
+        self.heading
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationcoordinates-speed>
+    fn GetSpeed(&self) -> Option<f64> {
+        self.speed
+    }
+}
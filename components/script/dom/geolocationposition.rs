@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionBinding::GeolocationPositionMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::geolocationcoordinates::GeolocationCoordinates;
+use crate::dom::globalscope::GlobalScope;
+
+#[dom_struct]
+pub struct GeolocationPosition {
+    reflector_: Reflector,
+    coords: Dom<GeolocationCoordinates>,
+    timestamp: u64,
+}
+
+impl GeolocationPosition {
+    fn new_inherited(coords: &GeolocationCoordinates, timestamp: u64) -> GeolocationPosition {
+        GeolocationPosition {
+            reflector_: Reflector::new(),
+            coords: Dom::from_ref(coords),
+            timestamp,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        position: &embedder_traits::GeolocationPosition,
+    ) -> DomRoot<GeolocationPosition> {
+        let coords = GeolocationCoordinates::new(global, position);
+        reflect_dom_object(
+            Box::new(GeolocationPosition::new_inherited(
+                &coords,
+                position.timestamp,
+            )),
+            global,
+        )
+    }
+}
+
+impl GeolocationPositionMethods for GeolocationPosition {
+    /// <https://w3c.github.io/geolocation/#dom-geolocationposition-coords>
+    fn Coords(&self) -> DomRoot<GeolocationCoordinates> {
+        DomRoot::from_ref(&*self.coords)
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationposition-timestamp>
+    fn Timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::GeolocationPositionErrorMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+
+#[dom_struct]
+pub struct GeolocationPositionError {
+    reflector_: Reflector,
+    code: u16,
+    message: DOMString,
+}
+
+impl GeolocationPositionError {
+    fn new_inherited(code: u16, message: DOMString) -> GeolocationPositionError {
+        GeolocationPositionError {
+            reflector_: Reflector::new(),
+            code,
+            message,
+        }
+    }
+
+    /// Create an error with one of the codes defined by the interface.
+    pub fn new(
+        global: &GlobalScope,
+        code: u16,
+        message: DOMString,
+    ) -> DomRoot<GeolocationPositionError> {
+        reflect_dom_object(
+            Box::new(GeolocationPositionError::new_inherited(code, message)),
+            global,
+        )
+    }
+}
+
+impl GeolocationPositionErrorMethods for GeolocationPositionError {
+    /// <https://w3c.github.io/geolocation/#dom-geolocationpositionerror-code>
+    fn Code(&self) -> u16 {
+        self.code
+    }
+
+    /// <https://w3c.github.io/geolocation/#dom-geolocationpositionerror-message>
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+}
@@ -22,7 +22,7 @@ use content_security_policy::CspList;
 use crossbeam_channel::Sender;
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{ConsoleLogLevel, ConsoleMessage, EmbedderMsg};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::{IsWrapper, UnwrapObjectDynamic};
@@ -60,6 +60,7 @@ use webgpu::{DeviceLostReason, WebGPUDevice};
 
 use super::bindings::codegen::Bindings::WebGPUBinding::GPUDeviceLostReason;
 use super::bindings::trace::HashMapTracedValues;
+use crate::devtools;
 use crate::dom::bindings::cell::{DomRefCell, RefMut};
 use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSource_Binding::EventSourceMethods;
@@ -73,7 +74,9 @@ use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::conversions::{root_from_object, root_from_object_static};
-use crate::dom::bindings::error::{report_pending_exception, Error, ErrorInfo};
+use crate::dom::bindings::error::{
+    report_pending_exception, take_pending_exception, Error, ErrorInfo,
+};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
@@ -104,7 +107,7 @@ use crate::dom::messageevent::MessageEvent;
 use crate::dom::messageport::MessagePort;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
-use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
+use crate::dom::performanceobserver::{is_supported_entry_type, VALID_ENTRY_TYPES};
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ExternalUnderlyingSource, ReadableStream};
 use crate::dom::serviceworker::ServiceWorker;
@@ -112,7 +115,7 @@ use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
-use crate::microtask::{Microtask, MicrotaskQueue, UserMicrotask};
+use crate::microtask::{ContinuationState, Microtask, MicrotaskQueue, UserMicrotask};
 use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::script_module::{DynamicModuleList, ModuleScript, ModuleTree, ScriptFetchOptions};
 use crate::script_runtime::{
@@ -272,7 +275,7 @@ pub struct GlobalScope {
     /// The microtask queue associated with this global.
     ///
     /// It is refcounted because windows in the same script thread share the
-    /// same microtask queue.
+    /// same microtask queue. Worker and worklet globals each own theirs.
     ///
     /// <https://html.spec.whatwg.org/multipage/#microtask-queue>
     #[ignore_malloc_size_of = "Rc<T> is hard"]
@@ -2294,8 +2297,16 @@ impl GlobalScope {
         self.devtools_chan.as_ref()
     }
 
+    /// Get the channel to the devtools thread if a devtools server is running, for the events
+    /// that only a connected client could show.
+    pub fn running_devtools_chan(&self) -> Option<&IpcSender<ScriptToDevtoolsControlMsg>> {
+        self.devtools_chan
+            .as_ref()
+            .filter(|_| devtools::server_running())
+    }
+
     pub fn issue_page_warning(&self, warning: &str) {
-        if let Some(ref chan) = self.devtools_chan {
+        if let Some(chan) = self.running_devtools_chan() {
             let _ = chan.send(ScriptToDevtoolsControlMsg::ReportPageError(
                 self.pipeline_id,
                 PageError {
@@ -2483,7 +2494,14 @@ impl GlobalScope {
             if let Some(dedicated) = self.downcast::<DedicatedWorkerGlobalScope>() {
                 dedicated.forward_error_to_worker_object(error_info);
             } else if self.is::<Window>() {
-                if let Some(ref chan) = self.devtools_chan {
+                self.send_to_embedder(EmbedderMsg::ConsoleMessage(ConsoleMessage {
+                    level: ConsoleLogLevel::Error,
+                    message: error_info.message.clone(),
+                    source_url: error_info.filename.clone(),
+                    line: error_info.lineno,
+                    column: error_info.column,
+                }));
+                if let Some(chan) = self.running_devtools_chan() {
                     let _ = chan.send(ScriptToDevtoolsControlMsg::ReportPageError(
                         self.pipeline_id,
                         PageError {
@@ -2618,7 +2636,6 @@ impl GlobalScope {
     }
 
     /// Evaluate a JS script on this global scope.
-    #[allow(unsafe_code)]
     pub fn evaluate_script_on_global_with_result(
         &self,
         code: &SourceCode,
@@ -2627,6 +2644,50 @@ impl GlobalScope {
         line_number: u32,
         fetch_options: ScriptFetchOptions,
         script_base_url: ServoUrl,
+    ) -> bool {
+        self.evaluate_script(
+            code,
+            filename,
+            rval,
+            line_number,
+            fetch_options,
+            script_base_url,
+            true,
+        )
+    }
+
+    /// Evaluate a JS script on this global scope without reporting the exception it throws, if
+    /// any. The exception is cleared and stored in `rval` instead, and false is returned.
+    pub fn evaluate_script_on_global_catching_exception(
+        &self,
+        code: &SourceCode,
+        filename: &str,
+        rval: MutableHandleValue,
+        line_number: u32,
+        fetch_options: ScriptFetchOptions,
+        script_base_url: ServoUrl,
+    ) -> bool {
+        self.evaluate_script(
+            code,
+            filename,
+            rval,
+            line_number,
+            fetch_options,
+            script_base_url,
+            false,
+        )
+    }
+
+    #[allow(unsafe_code, clippy::too_many_arguments)]
+    fn evaluate_script(
+        &self,
+        code: &SourceCode,
+        filename: &str,
+        mut rval: MutableHandleValue,
+        line_number: u32,
+        fetch_options: ScriptFetchOptions,
+        script_base_url: ServoUrl,
+        report_exceptions: bool,
     ) -> bool {
         let metadata = profile_time::TimerMetadata {
             url: if filename.is_empty() {
@@ -2663,7 +2724,11 @@ impl GlobalScope {
 
                             if compiled_script.is_null() {
                                 debug!("error compiling Dom string");
-                                report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                                if report_exceptions {
+                                    report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                                } else {
+                                    take_pending_exception(*cx, rval);
+                                }
                                 return false;
                             }
                         },
@@ -2712,7 +2777,11 @@ impl GlobalScope {
 
                     if !result {
                         debug!("error evaluating Dom string");
-                        report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                        if report_exceptions {
+                            report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                        } else {
+                            take_pending_exception(*cx, rval);
+                        }
                     }
 
                     maybe_resume_unwind();
@@ -2737,6 +2806,10 @@ impl GlobalScope {
         self.timers.unschedule_callback(handle);
     }
 
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        self.timers.time_until_next_timer()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#timer-initialisation-steps>
     pub fn set_timeout_or_interval(
         &self,
@@ -2764,6 +2837,7 @@ impl GlobalScope {
         self.enqueue_microtask(Microtask::User(UserMicrotask {
             callback,
             pipeline: self.pipeline_id(),
+            continuation_state: ContinuationState::capture(),
         }))
     }
 
@@ -2892,12 +2966,18 @@ impl GlobalScope {
 
     /// Perform a microtask checkpoint.
     pub fn perform_a_microtask_checkpoint(&self) {
+        if self.is::<Window>() {
+            return ScriptThread::perform_a_microtask_checkpoint_for_event_loop();
+        }
         // Only perform the checkpoint if we're not shutting down.
         if self.can_continue_running() {
+            // Worker and worklet globals own their microtask queue, so that everything in it
+            // was queued by this global. Their checkpoints have no budget, and always finish.
             self.microtask_queue.checkpoint(
                 GlobalScope::get_cx(),
                 |_| Some(DomRoot::from_ref(self)),
                 vec![DomRoot::from_ref(self)],
+                |_| {},
             );
         }
     }
@@ -3031,6 +3111,7 @@ impl GlobalScope {
 
         let types: Vec<DOMString> = VALID_ENTRY_TYPES
             .iter()
+            .filter(|t| is_supported_entry_type(t))
             .map(|t| DOMString::from(t.to_string()))
             .collect();
         let frozen_types = to_frozen_array(types.as_slice(), cx);
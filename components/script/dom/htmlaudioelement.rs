@@ -41,13 +41,15 @@ impl HTMLAudioElement {
         document: &Document,
         proto: Option<HandleObject>,
     ) -> DomRoot<HTMLAudioElement> {
-        Node::reflect_node_with_proto(
+        let element: DomRoot<HTMLAudioElement> = Node::reflect_node_with_proto(
             Box::new(HTMLAudioElement::new_inherited(
                 local_name, prefix, document,
             )),
             document,
             proto,
-        )
+        );
+        document.window().track_media_element(element.upcast());
+        element
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-audio
@@ -612,6 +612,50 @@ fn append_text_node_to_fragment(document: &Document, fragment: &DocumentFragment
         .unwrap();
 }
 
+/// The state of an element with the given `contenteditable` attribute value: `Some(true)` for
+/// the true and plaintext-only states, `Some(false)` for the false state, and `None` for the
+/// inherit state of a missing attribute or an invalid value.
+/// <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+fn contenteditable_state(value: Option<&str>) -> Option<bool> {
+    let value = value?;
+    if value.is_empty() ||
+        value.eq_ignore_ascii_case("true") ||
+        value.eq_ignore_ascii_case("plaintext-only")
+    {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Whether an element is an editing host or editable, given the `contenteditable` attribute
+/// values of its inclusive ancestors, starting with its own.
+/// <https://html.spec.whatwg.org/multipage/#editing-host>
+pub fn is_editable_by_contenteditable<S: AsRef<str>>(
+    values: impl IntoIterator<Item = Option<S>>,
+) -> bool {
+    values
+        .into_iter()
+        .find_map(|value| contenteditable_state(value.as_ref().map(AsRef::as_ref)))
+        .unwrap_or(false)
+}
+
+/// Whether the node is in an editing host, as far as the `contenteditable` attributes of the
+/// HTML elements it is in go.
+pub fn is_in_editing_host(node: &Node) -> bool {
+    let values = node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter(|node| node.is::<HTMLElement>())
+        .map(|node| {
+            node.downcast::<Element>()
+                .and_then(|element| element.get_attribute(&ns!(), &local_name!("contenteditable")))
+                .map(|attr| String::from(&**attr.value()))
+        });
+    is_editable_by_contenteditable(values)
+}
+
 // https://html.spec.whatwg.org/multipage/#attr-data-*
 
 static DATA_PREFIX: &str = "data-";
@@ -13,8 +13,9 @@ use profile_traits::ipc as ProfiledIpc;
 use script_layout_interface::ReflowGoal;
 use script_traits::IFrameSandboxState::{IFrameSandboxed, IFrameUnsandboxed};
 use script_traits::{
-    HistoryEntryReplacement, IFrameLoadInfo, IFrameLoadInfoWithData, JsEvalResult, LoadData,
-    LoadOrigin, NewLayoutInfo, ScriptMsg, UpdatePipelineIdReason, WindowSizeData,
+    ContainerPolicy, FeatureAllowlist, HistoryEntryReplacement, IFrameLoadInfo,
+    IFrameLoadInfoWithData, JsEvalResult, LoadData, LoadOrigin, NewLayoutInfo, ScriptMsg,
+    UpdatePipelineIdReason, WindowSizeData,
 };
 use servo_atoms::Atom;
 use servo_url::ServoUrl;
@@ -44,6 +45,10 @@ use crate::dom::window::ReflowReason;
 use crate::dom::windowproxy::WindowProxy;
 use crate::script_thread::ScriptThread;
 
+/// The policy-controlled features Servo checks, whose allowlists are passed to the documents
+/// loaded in iframes.
+const POLICY_CONTROLLED_FEATURES: &[&str] = &["bluetooth", "speaker-selection"];
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
 struct SandboxAllowance(u8);
 
@@ -95,6 +100,58 @@ impl HTMLIFrameElement {
         self.sandbox_allowance.get().is_some()
     }
 
+    /// The allowlist the `allow` attribute declares for the policy-controlled feature, or
+    /// `None` if the attribute does not mention the feature.
+    /// <https://w3c.github.io/webappsec-permissions-policy/#algo-parse-policy-directive>
+    fn allow_attribute_allowlist(&self, feature: &str) -> Option<FeatureAllowlist> {
+        let allow = self
+            .upcast::<Element>()
+            .get_string_attribute(&local_name!("allow"));
+        let mut allowlist = allow
+            .split(';')
+            .map(str::split_ascii_whitespace)
+            .find_map(|mut tokens| (tokens.next()? == feature).then_some(tokens))?
+            .peekable();
+
+        let src_origin = || self.get_url().origin();
+        // An empty allowlist is the origin of the src of the iframe.
+        if allowlist.peek().is_none() {
+            return Some(FeatureAllowlist::Origins(vec![src_origin()]));
+        }
+        let mut origins = vec![];
+        for item in allowlist {
+            match item {
+                "*" => return Some(FeatureAllowlist::All),
+                "'none'" => {},
+                "'self'" => origins.push(document_from_node(self).origin().immutable().clone()),
+                "'src'" => origins.push(src_origin()),
+                url => origins.extend(ServoUrl::parse(url).ok().map(|url| url.origin())),
+            }
+        }
+        Some(FeatureAllowlist::Origins(origins))
+    }
+
+    /// The container policy of the documents loaded in this iframe, whose features all have
+    /// `'self'` as their default allowlist.
+    /// <https://w3c.github.io/webappsec-permissions-policy/#algo-process-policy-attributes>
+    fn container_policy(&self) -> ContainerPolicy {
+        let document = document_from_node(self);
+        let allowlists = POLICY_CONTROLLED_FEATURES
+            .iter()
+            .map(|&feature| {
+                let allowlist = if !document.is_allowed_to_use_feature(feature) {
+                    FeatureAllowlist::Origins(vec![])
+                } else {
+                    self.allow_attribute_allowlist(feature).unwrap_or_else(|| {
+                        FeatureAllowlist::Origins(vec![document.origin().immutable().clone()])
+                    })
+                };
+                (feature.to_owned(), allowlist)
+            })
+            .collect();
+        ContainerPolicy { allowlists }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#otherwise-steps-for-iframe-or-frame-elements>,
     /// step 1.
     fn get_url(&self) -> ServoUrl {
@@ -174,6 +231,8 @@ impl HTMLIFrameElement {
             },
         };
 
+        load_data.container_policy = Some(self.container_policy());
+
         let window = window_from_node(self);
         let old_pipeline_id = self.pipeline_id();
         let new_pipeline_id = PipelineId::new();
@@ -602,6 +661,11 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
         Some(document)
     }
 
+    // https://html.spec.whatwg.org/multipage/#attr-iframe-allow
+    make_getter!(Allow, "allow");
+    // https://html.spec.whatwg.org/multipage/#attr-iframe-allow
+    make_setter!(SetAllow, "allow");
+
     // https://html.spec.whatwg.org/multipage/#attr-iframe-allowfullscreen
     make_bool_getter!(AllowFullscreen, "allowfullscreen");
     // https://html.spec.whatwg.org/multipage/#attr-iframe-allowfullscreen
@@ -91,7 +91,7 @@ use crate::dom::promise::Promise;
 use crate::dom::values::UNSIGNED_LONG_MAX;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
-use crate::fetch::create_a_potential_cors_request;
+use crate::fetch::{create_a_potential_cors_request, FetchCanceller};
 use crate::image_listener::{generate_cache_listener_for_element, ImageCacheListener};
 use crate::microtask::{Microtask, MicrotaskRunnable};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
@@ -200,6 +200,141 @@ impl HTMLImageElement {
     }
 }
 
+/// The state of a [`MixedReplaceFirstPart`] parser.
+#[derive(PartialEq)]
+enum MixedReplaceState {
+    /// Skipping anything before the first delimiter.
+    Preamble,
+    /// Skipping the headers of the first part.
+    Headers,
+    /// Reading the body of the first part.
+    Body,
+    /// The body of the first part has been read in full.
+    Complete,
+    /// The delimiter or header lines of the first part were too long.
+    Failed,
+}
+
+/// How long the delimiter and header lines before the body of the first part of a
+/// `multipart/x-mixed-replace` response can be, since they are buffered until they end.
+const MAX_MIXED_REPLACE_LINE_LENGTH: usize = 16 * 1024;
+
+/// Extracts the body of the first part of a `multipart/x-mixed-replace` response, which is
+/// what gets displayed until rolling replacement with the following parts is supported.
+pub struct MixedReplaceFirstPart {
+    /// The delimiter line that starts every part: `--` followed by the boundary.
+    delimiter: Vec<u8>,
+    /// Bytes that have been received but not yet handled.
+    buffer: Vec<u8>,
+    state: MixedReplaceState,
+}
+
+impl MixedReplaceFirstPart {
+    pub fn new(boundary: &str) -> MixedReplaceFirstPart {
+        MixedReplaceFirstPart {
+            delimiter: [b"--", boundary.as_bytes()].concat(),
+            buffer: vec![],
+            state: MixedReplaceState::Preamble,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.state == MixedReplaceState::Complete
+    }
+
+    /// Whether the first part could not be read, because a line before its body was longer
+    /// than [`MAX_MIXED_REPLACE_LINE_LENGTH`].
+    pub fn is_failed(&self) -> bool {
+        self.state == MixedReplaceState::Failed
+    }
+
+    /// Handle a chunk of the response, returning the bytes of the first part's body that are
+    /// now known.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.buffer.extend_from_slice(chunk);
+        let body = self.read_buffer();
+        if matches!(
+            self.state,
+            MixedReplaceState::Preamble | MixedReplaceState::Headers
+        ) && self.buffer.len() > MAX_MIXED_REPLACE_LINE_LENGTH
+        {
+            self.buffer = vec![];
+            self.state = MixedReplaceState::Failed;
+        }
+        body
+    }
+
+    fn read_buffer(&mut self) -> Vec<u8> {
+        fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+            haystack
+                .windows(needle.len())
+                .position(|window| window == needle)
+        }
+
+        let mut body = vec![];
+        loop {
+            match self.state {
+                MixedReplaceState::Preamble => {
+                    let Some(start) = find(&self.buffer, &self.delimiter) else {
+                        // Only the end of the preamble can be the start of the delimiter.
+                        let skipped = self.buffer.len().saturating_sub(self.delimiter.len() - 1);
+                        self.buffer.drain(..skipped);
+                        return body;
+                    };
+                    self.buffer.drain(..start);
+                    let after_delimiter = self.delimiter.len();
+                    let Some(end_of_line) = find(&self.buffer[after_delimiter..], b"\n") else {
+                        return body;
+                    };
+                    self.buffer.drain(..after_delimiter + end_of_line + 1);
+                    self.state = MixedReplaceState::Headers;
+                },
+                MixedReplaceState::Headers => {
+                    let Some(end_of_line) = find(&self.buffer, b"\n") else {
+                        return body;
+                    };
+                    let is_blank_line = matches!(&self.buffer[..end_of_line], b"" | b"\r");
+                    self.buffer.drain(..end_of_line + 1);
+                    if is_blank_line {
+                        self.state = MixedReplaceState::Body;
+                    }
+                },
+                MixedReplaceState::Body => match find(&self.buffer, &self.delimiter) {
+                    Some(mut end) => {
+                        // The line break before the delimiter belongs to the delimiter.
+                        if self.buffer[..end].ends_with(b"\r\n") {
+                            end -= 2;
+                        } else if self.buffer[..end].ends_with(b"\n") {
+                            end -= 1;
+                        }
+                        body.extend(self.buffer.drain(..end));
+                        self.buffer.clear();
+                        self.state = MixedReplaceState::Complete;
+                        return body;
+                    },
+                    None => {
+                        // Hold back anything that could be the start of the next delimiter.
+                        let held_back = self.delimiter.len() + 2;
+                        let available = self.buffer.len().saturating_sub(held_back);
+                        body.extend(self.buffer.drain(..available));
+                        return body;
+                    },
+                },
+                MixedReplaceState::Complete | MixedReplaceState::Failed => return body,
+            }
+        }
+    }
+
+    /// The rest of the first part's body, for a response that ended without another delimiter.
+    pub fn finish(&mut self) -> Vec<u8> {
+        if self.state != MixedReplaceState::Body {
+            return vec![];
+        }
+        self.state = MixedReplaceState::Complete;
+        mem::take(&mut self.buffer)
+    }
+}
+
 /// The context required for asynchronously loading an external image.
 struct ImageContext {
     /// Reference to the script thread image cache.
@@ -215,6 +350,10 @@ struct ImageContext {
     /// timing data for this resource
     resource_timing: ResourceFetchTiming,
     url: ServoUrl,
+    /// For `multipart/x-mixed-replace` responses, the parser extracting the first part.
+    mixed_replace: Option<MixedReplaceFirstPart>,
+    /// Stops the fetch once the rest of the response is not needed.
+    canceller: FetchCanceller,
 }
 
 impl FetchResponseListener for ImageContext {
@@ -236,7 +375,17 @@ impl FetchResponseListener for ImageContext {
             if let Some(ref content_type) = metadata.content_type {
                 let mime: Mime = content_type.clone().into_inner().into();
                 if mime.type_() == mime::MULTIPART && mime.subtype().as_str() == "x-mixed-replace" {
-                    self.aborted = true;
+                    // TODO: Replace the image with each of the following parts as they arrive,
+                    // instead of only showing the first one.
+                    match mime.get_param(mime::BOUNDARY) {
+                        Some(boundary) => {
+                            self.mixed_replace = Some(MixedReplaceFirstPart::new(boundary.as_str()))
+                        },
+                        None => {
+                            self.aborted = true;
+                            self.canceller.cancel();
+                        },
+                    }
                 }
             }
         }
@@ -259,13 +408,50 @@ impl FetchResponseListener for ImageContext {
     }
 
     fn process_response_chunk(&mut self, payload: Vec<u8>) {
-        if self.status.is_ok() {
+        if self.status.is_err() {
+            return;
+        }
+        let Some(first_part) = self.mixed_replace.as_mut() else {
             self.image_cache
                 .notify_pending_response(self.id, FetchResponseMsg::ProcessResponseChunk(payload));
+            return;
+        };
+        if first_part.is_complete() || first_part.is_failed() {
+            return;
         }
+
+        let body = first_part.push(&payload);
+        if !body.is_empty() {
+            self.image_cache
+                .notify_pending_response(self.id, FetchResponseMsg::ProcessResponseChunk(body));
+        }
+        // The image only consists of the first part, so it can be decoded without waiting for
+        // the rest of the stream, which might never end, and the fetch is no longer needed.
+        let result = if first_part.is_complete() {
+            Ok(self.resource_timing.clone())
+        } else if first_part.is_failed() {
+            Err(NetworkError::Internal(
+                "Malformed multipart/x-mixed-replace response".to_owned(),
+            ))
+        } else {
+            return;
+        };
+        self.image_cache
+            .notify_pending_response(self.id, FetchResponseMsg::ProcessResponseEOF(result));
+        self.canceller.cancel();
     }
 
     fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        if let Some(first_part) = self.mixed_replace.as_mut() {
+            if first_part.is_complete() || first_part.is_failed() {
+                return;
+            }
+            let body = first_part.finish();
+            if !body.is_empty() && self.status.is_ok() {
+                self.image_cache
+                    .notify_pending_response(self.id, FetchResponseMsg::ProcessResponseChunk(body));
+            }
+        }
         self.image_cache
             .notify_pending_response(self.id, FetchResponseMsg::ProcessResponseEOF(response));
     }
@@ -378,6 +564,8 @@ impl HTMLImageElement {
             doc: Trusted::new(&document),
             resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
             url: img_url.clone(),
+            mixed_replace: None,
+            canceller: FetchCanceller::new(),
         }));
 
         let (action_sender, action_receiver) = ipc::channel().unwrap();
@@ -386,7 +574,7 @@ impl HTMLImageElement {
             .task_manager()
             .networking_task_source_with_canceller();
         let listener = NetworkListener {
-            context,
+            context: context.clone(),
             task_source,
             canceller: Some(canceller),
         };
@@ -413,9 +601,10 @@ impl HTMLImageElement {
 
         // This is a background load because the load blocker already fulfills the
         // purpose of delaying the document's load event.
-        document
+        let canceller = document
             .loader_mut()
-            .fetch_async_background(request, action_sender);
+            .fetch_async_background_cancellable(request, action_sender);
+        context.lock().unwrap().canceller = canceller;
     }
 
     // Steps common to when an image has been loaded.
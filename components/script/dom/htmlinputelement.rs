@@ -11,7 +11,7 @@ use std::{f64, ptr};
 use chrono::naive::{NaiveDate, NaiveDateTime};
 use chrono::{DateTime, Datelike, Weekday};
 use dom_struct::dom_struct;
-use embedder_traits::{FilterPattern, InputMethodType};
+use embedder_traits::{FilterPattern, InputMethodType, TextInputState};
 use encoding_rs::Encoding;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::jsapi::{
@@ -32,6 +32,7 @@ use style::str::{split_commas, str_join};
 use style_traits::dom::ElementState;
 use unicode_bidi::{bidi_class, BidiClass};
 use url::Url;
+use webrender_api::units::DeviceIntRect;
 
 use crate::dom::activation::Activatable;
 use crate::dom::attr::Attr;
@@ -50,7 +51,7 @@ use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
-use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::file::File;
 use crate::dom::filelist::FileList;
@@ -62,6 +63,7 @@ use crate::dom::htmlformelement::{
     FormControl, FormDatum, FormDatumValue, FormSubmitter, HTMLFormElement, ResetFrom,
     SubmittedFrom,
 };
+use crate::dom::inputevent::InputEvent;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{
@@ -401,6 +403,17 @@ impl HTMLInputElement {
         self.input_type.get()
     }
 
+    /// The state of this element for the input method of the embedder.
+    pub fn text_input_state(
+        &self,
+        input_method_type: InputMethodType,
+        caret_rect: DeviceIntRect,
+    ) -> TextInputState {
+        self.textinput
+            .borrow()
+            .text_input_state(input_method_type, caret_rect)
+    }
+
     #[inline]
     pub fn is_submit_button(&self) -> bool {
         let input_type = self.input_type.get();
@@ -2543,6 +2556,19 @@ impl VirtualMethods for HTMLInputElement {
             if let Some(keyevent) = event.downcast::<KeyboardEvent>() {
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
+                let window = window_from_node(self);
+                let edit = if event.IsTrusted() {
+                    self.textinput.borrow().input_type_for_keydown(keyevent)
+                } else {
+                    None
+                };
+                let proceed = edit.as_ref().map_or(true, |(input_type, data)| {
+                    InputEvent::fire_beforeinput(self.upcast(), &window, input_type, data.clone())
+                });
+                if !proceed {
+                    event.mark_as_handled();
+                    return;
+                }
                 let action = self.textinput.borrow_mut().handle_keydown(keyevent);
                 match action {
                     TriggerDefaultAction => {
@@ -2553,6 +2579,9 @@ impl VirtualMethods for HTMLInputElement {
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
+                        if let Some((input_type, data)) = edit {
+                            InputEvent::queue_input(self.upcast(), &window, input_type, data);
+                        }
                     },
                     RedrawSelection => {
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -2561,37 +2590,34 @@ impl VirtualMethods for HTMLInputElement {
                     Nothing => (),
                 }
             }
-        } else if event.type_() == atom!("keypress") &&
-            !event.DefaultPrevented() &&
-            self.input_type().is_textual_or_password()
-        {
-            if event.IsTrusted() {
-                let window = window_from_node(self);
-                window
-                    .task_manager()
-                    .user_interaction_task_source()
-                    .queue_event(
-                        self.upcast(),
-                        atom!("input"),
-                        EventBubbles::Bubbles,
-                        EventCancelable::NotCancelable,
-                        &window,
-                    );
-            }
         } else if (event.type_() == atom!("compositionstart") ||
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")) &&
             self.input_type().is_textual_or_password()
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
+                let action = if event.type_() == atom!("compositionupdate") {
+                    self.textinput
+                        .borrow_mut()
+                        .handle_compositionupdate(compositionevent)
+                } else if event.type_() == atom!("compositionend") {
+                    self.textinput
                         .borrow_mut()
-                        .handle_compositionend(compositionevent);
+                        .handle_compositionend(compositionevent)
+                } else {
+                    Nothing
+                };
+                if let DispatchInput = action {
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    let window = window_from_node(self);
+                    InputEvent::queue_input(
+                        self.upcast(),
+                        &window,
+                        "insertCompositionText",
+                        Some(DOMString::from(compositionevent.data())),
+                    );
                 }
                 event.mark_as_handled();
             }
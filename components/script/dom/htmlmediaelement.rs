@@ -5,7 +5,7 @@
 use std::cell::Cell;
 use std::collections::VecDeque;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{f64, mem};
 
@@ -28,6 +28,9 @@ use net_traits::{
 use pixels::Image;
 use script_layout_interface::HTMLMediaData;
 use servo_config::pref;
+use servo_media::audio::context::{AudioContext, AudioContextOptions};
+use servo_media::audio::media_element_source_node::MediaElementSourceNodeMessage;
+use servo_media::audio::node::{AudioNodeInit, AudioNodeMessage, ChannelInfo};
 use servo_media::player::audio::AudioRenderer;
 use servo_media::player::video::{VideoFrame, VideoFrameRenderer};
 use servo_media::player::{PlaybackState, Player, PlayerError, PlayerEvent, SeekLock, StreamType};
@@ -82,6 +85,7 @@ use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
 use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
+use crate::dom::mediaelementaudiosourcenode::MediaElementAudioSourceNode;
 use crate::dom::mediaerror::MediaError;
 use crate::dom::mediafragmentparser::MediaFragmentParser;
 use crate::dom::mediastream::MediaStream;
@@ -183,6 +187,108 @@ impl MediaFrameRenderer {
     }
 }
 
+/// The audio renderer of the players of a media element. servo-media does not let the audio
+/// renderer of a player be replaced, so the audio is passed on to the renderer that it is
+/// currently routed to instead.
+#[derive(Default)]
+struct MediaElementAudioRenderer {
+    /// The renderer of the `MediaElementAudioSourceNode` the audio is routed to, if any.
+    target: Option<Arc<Mutex<dyn AudioRenderer>>>,
+    /// The output used while the audio is not routed to a node. It is only created once there
+    /// is audio to render, on the thread of the player that renders it.
+    direct_output: Option<DirectAudioOutput>,
+    /// Whether creating the direct output failed, in which case the audio is dropped rather
+    /// than trying again for every sample.
+    direct_output_failed: bool,
+    /// The client of the audio contexts created for the direct output.
+    client_context_id: Option<ClientContextId>,
+}
+
+impl MediaElementAudioRenderer {
+    /// Route the audio to the renderer of a node, or back to the direct output.
+    fn set_target(&mut self, target: Option<Arc<Mutex<dyn AudioRenderer>>>) {
+        if target.is_some() {
+            self.close_direct_output();
+        }
+        self.target = target;
+    }
+
+    /// Stop outputting audio directly. The output is created again if more audio comes.
+    fn close_direct_output(&mut self) {
+        self.direct_output = None;
+        self.direct_output_failed = false;
+    }
+}
+
+impl AudioRenderer for MediaElementAudioRenderer {
+    fn render(&mut self, sample: Box<dyn AsRef<[f32]>>, channel: u32) {
+        if let Some(ref target) = self.target {
+            return target.lock().unwrap().render(sample, channel);
+        }
+        if self.direct_output.is_none() && !self.direct_output_failed {
+            let Some(client_context_id) = self.client_context_id.as_ref() else {
+                return;
+            };
+            self.direct_output = DirectAudioOutput::new(client_context_id);
+            self.direct_output_failed = self.direct_output.is_none();
+        }
+        if let Some(ref output) = self.direct_output {
+            output.renderer.lock().unwrap().render(sample, channel);
+        }
+    }
+}
+
+/// An audio context of a media element's own, which outputs its audio to the default audio
+/// output device like its players would on their own.
+struct DirectAudioOutput {
+    context: Arc<Mutex<AudioContext>>,
+    renderer: Arc<Mutex<dyn AudioRenderer>>,
+}
+
+impl DirectAudioOutput {
+    fn new(client_context_id: &ClientContextId) -> Option<DirectAudioOutput> {
+        let context = ServoMedia::get().unwrap().create_audio_context(
+            client_context_id,
+            AudioContextOptions::RealTimeAudioContext(Default::default()),
+        );
+        let renderer = {
+            let context = context.lock().unwrap();
+            let node_id = context.create_node(
+                AudioNodeInit::MediaElementSourceNode,
+                ChannelInfo::default(),
+            );
+            context.connect_ports(node_id.output(0), context.dest_node().input(0));
+            let (sender, receiver) = mpsc::channel();
+            context.message_node(
+                node_id,
+                AudioNodeMessage::MediaElementSourceNode(
+                    MediaElementSourceNodeMessage::GetAudioRenderer(sender),
+                ),
+            );
+            if let Err(error) = context.resume() {
+                warn!("Could not start the audio output of a media element ({error:?}).");
+            }
+            receiver.recv()
+        };
+        match renderer {
+            Ok(renderer) => Some(DirectAudioOutput { context, renderer }),
+            Err(error) => {
+                warn!("Could not get the audio renderer of a media element ({error:?}).");
+                let _ = context.lock().unwrap().close();
+                None
+            },
+        }
+    }
+}
+
+impl Drop for DirectAudioOutput {
+    fn drop(&mut self) {
+        if let Err(error) = self.context.lock().unwrap().close() {
+            warn!("Could not stop the audio output of a media element ({error:?}).");
+        }
+    }
+}
+
 impl VideoFrameRenderer for MediaFrameRenderer {
     fn render(&mut self, frame: VideoFrame) {
         // Don't render new frames if the poster should be shown
@@ -351,7 +457,9 @@ pub struct HTMLMediaElement {
     video_renderer: Arc<Mutex<MediaFrameRenderer>>,
     #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
-    audio_renderer: DomRefCell<Option<Arc<Mutex<dyn AudioRenderer>>>>,
+    audio_renderer: Arc<Mutex<MediaElementAudioRenderer>>,
+    /// <https://webaudio.github.io/web-audio-api/#mediaelementaudiosourcenode>
+    audio_source_node: MutNullableDom<MediaElementAudioSourceNode>,
     /// <https://html.spec.whatwg.org/multipage/#show-poster-flag>
     show_poster: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#dom-media-duration>
@@ -366,6 +474,8 @@ pub struct HTMLMediaElement {
     seeking: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#dom-media-muted>
     muted: Cell<bool>,
+    /// Whether the player last reported that it is playing.
+    playing: Cell<bool>,
     /// URL of the media resource, if any.
     #[no_trace]
     resource_url: DomRefCell<Option<ServoUrl>>,
@@ -436,6 +546,7 @@ impl HTMLMediaElement {
             defaultPlaybackRate: Cell::new(1.0),
             playbackRate: Cell::new(1.0),
             muted: Cell::new(false),
+            playing: Cell::new(false),
             // FIXME(nox): Why is this initialised to true?
             autoplaying: Cell::new(true),
             delaying_the_load_event_flag: Default::default(),
@@ -446,6 +557,7 @@ impl HTMLMediaElement {
                 document.window().get_webrender_api_sender(),
             ))),
             audio_renderer: Default::default(),
+            audio_source_node: Default::default(),
             show_poster: Cell::new(true),
             duration: Cell::new(f64::NAN),
             playback_position: Cell::new(0.),
@@ -571,7 +683,35 @@ impl HTMLMediaElement {
     }
     // https://html.spec.whatwg.org/multipage/#allowed-to-play
     fn is_allowed_to_play(&self) -> bool {
-        true
+        window_from_node(self).is_allowed_to_start_media(self.is_effectively_muted())
+    }
+
+    /// Whether the audio of this element is silenced, either by the page or by the embedder
+    /// muting its webview.
+    fn is_effectively_muted(&self) -> bool {
+        self.muted.get() || window_from_node(self).media_policy().muted
+    }
+
+    /// Whether this element is playing audio that the user can hear.
+    pub fn is_audible(&self) -> bool {
+        // Audio routed to an audio context is only heard through that context.
+        self.playing.get() &&
+            self.volume.get() > 0. &&
+            !self.is_effectively_muted() &&
+            self.audio_source_node.get().is_none()
+    }
+
+    /// Mute or unmute the player, as the page and the embedder want it.
+    pub fn update_player_mute(&self) {
+        if let Some(ref player) = *self.player.borrow() {
+            let _ = player.lock().unwrap().set_mute(self.is_effectively_muted());
+        }
+    }
+
+    fn set_playing(&self, playing: bool) {
+        if self.playing.replace(playing) != playing {
+            window_from_node(self).update_audible();
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#notify-about-playing
@@ -670,7 +810,11 @@ impl HTMLMediaElement {
             // FIXME(nox): I have no idea what this TODO is about.
 
             // FIXME(nox): Review this block.
-            if self.autoplaying.get() && self.Paused() && self.Autoplay() {
+            if self.autoplaying.get() &&
+                self.Paused() &&
+                self.Autoplay() &&
+                self.is_allowed_to_play()
+            {
                 // Step 1
                 self.paused.set(false);
                 // Step 2
@@ -1045,6 +1189,7 @@ impl HTMLMediaElement {
                             eprintln!("Could not stop player {:?}", e);
                         }
                     }
+                    this.close_direct_audio_output();
 
                     // Step 6.
                     // Done after running this closure in
@@ -1130,6 +1275,7 @@ impl HTMLMediaElement {
 
             // Step 6.3.
             // FIXME(nox): Detach MediaSource media provider object.
+            self.close_direct_audio_output();
 
             // Step 6.4.
             self.AudioTracks().clear();
@@ -1366,11 +1512,15 @@ impl HTMLMediaElement {
             HTMLMediaElementTypeId::HTMLVideoElement => Some(self.video_renderer.clone()),
         };
 
-        let audio_renderer = self.audio_renderer.borrow().as_ref().cloned();
+        // The previous player, if any, is torn down below, along with its audio output.
+        self.close_direct_audio_output();
+        let audio_renderer: Option<Arc<Mutex<dyn AudioRenderer>>> =
+            Some(self.audio_renderer.clone());
 
         let pipeline_id = window.pipeline_id();
         let client_context_id =
             ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+        self.audio_renderer.lock().unwrap().client_context_id = Some(client_context_id);
         let player = ServoMedia::get().unwrap().create_player(
             &client_context_id,
             stream_type,
@@ -1380,7 +1530,11 @@ impl HTMLMediaElement {
             Box::new(window.get_player_context()),
         );
 
+        if self.is_effectively_muted() {
+            let _ = player.lock().unwrap().set_mute(true);
+        }
         *self.player.borrow_mut() = Some(player);
+        self.set_playing(false);
 
         let trusted_node = Trusted::new(self);
         let (task_source, canceller) = window
@@ -1506,6 +1660,8 @@ impl HTMLMediaElement {
                                     /* approximate_for_speed*/ false,
                                 );
                             } else {
+                                self.set_playing(false);
+
                                 // Step 2.
                                 // The **ended playback** condition is implemented inside of
                                 // the HTMLMediaElementMethods::Ended method
@@ -1851,12 +2007,14 @@ impl HTMLMediaElement {
                 let mut media_session_playback_state = MediaSessionPlaybackState::None_;
                 match *state {
                     PlaybackState::Paused => {
+                        self.set_playing(false);
                         media_session_playback_state = MediaSessionPlaybackState::Paused;
                         if self.ready_state.get() == ReadyState::HaveMetadata {
                             self.change_ready_state(ReadyState::HaveEnoughData);
                         }
                     },
                     PlaybackState::Playing => {
+                        self.set_playing(true);
                         media_session_playback_state = MediaSessionPlaybackState::Playing;
                     },
                     PlaybackState::Buffering => {
@@ -1865,7 +2023,7 @@ impl HTMLMediaElement {
                         // session.
                         return;
                     },
-                    _ => {},
+                    _ => self.set_playing(false),
                 };
                 debug!(
                     "Sending media session event playback state changed to {:?}",
@@ -1959,18 +2117,39 @@ impl HTMLMediaElement {
             .map(|holder| holder.get_frame())
     }
 
-    /// By default the audio is rendered through the audio sink automatically
-    /// selected by the servo-media Player instance. However, in some cases, like
-    /// the WebAudio MediaElementAudioSourceNode, we need to set a custom audio
-    /// renderer.
-    pub fn set_audio_renderer(&self, audio_renderer: Arc<Mutex<dyn AudioRenderer>>) {
-        *self.audio_renderer.borrow_mut() = Some(audio_renderer);
-        if let Some(ref player) = *self.player.borrow() {
-            if let Err(e) = player.lock().unwrap().stop() {
-                eprintln!("Could not stop player {:?}", e);
-            }
-            self.media_element_load_algorithm();
+    /// The node the audio of this element is routed to, instead of being output directly.
+    pub fn audio_source_node(&self) -> Option<DomRoot<MediaElementAudioSourceNode>> {
+        self.audio_source_node.get()
+    }
+
+    /// Route the audio of this element to the given node, through its audio renderer.
+    pub fn route_audio_to(
+        &self,
+        node: &MediaElementAudioSourceNode,
+        audio_renderer: Arc<Mutex<dyn AudioRenderer>>,
+    ) {
+        self.audio_source_node.set(Some(node));
+        self.audio_renderer
+            .lock()
+            .unwrap()
+            .set_target(Some(audio_renderer));
+        window_from_node(self).update_audible();
+    }
+
+    /// Output the audio of this element directly again, if it was routed to the given node.
+    pub fn restore_audio_output(&self, node: &MediaElementAudioSourceNode) {
+        if self.audio_source_node.get().as_deref() != Some(node) {
+            return;
         }
+        self.audio_source_node.set(None);
+        self.audio_renderer.lock().unwrap().set_target(None);
+        window_from_node(self).update_audible();
+    }
+
+    /// Close the audio context that outputs the audio of this element while it is not routed
+    /// to a node, if there is one. It is created again if the element renders audio later.
+    fn close_direct_audio_output(&self) {
+        self.audio_renderer.lock().unwrap().close_direct_output();
     }
 
     fn send_media_session_event(&self, event: MediaSessionEvent) {
@@ -2001,6 +2180,7 @@ impl HTMLMediaElement {
                 eprintln!("Could not stop player {:?}", e);
             }
         }
+        self.close_direct_audio_output();
     }
 }
 
@@ -2024,6 +2204,7 @@ impl HTMLMediaElement {
 
 impl Drop for HTMLMediaElement {
     fn drop(&mut self) {
+        self.close_direct_audio_output();
         if let Some(ref pipeline) = self.player_context.glplayer_chan {
             if let Err(err) = pipeline
                 .channel()
@@ -2092,12 +2273,11 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             return;
         }
 
-        if let Some(ref player) = *self.player.borrow() {
-            let _ = player.lock().unwrap().set_mute(value);
-        }
-
         self.muted.set(value);
+        self.update_player_mute();
+
         let window = window_from_node(self);
+        window.update_audible();
         window
             .task_manager()
             .media_element_task_source()
@@ -2159,7 +2339,10 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
     fn Play(&self, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
         // Step 1.
-        // FIXME(nox): Reject promise if not allowed to play.
+        if !self.is_allowed_to_play() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
 
         // Step 2.
         if self
@@ -2439,6 +2622,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             self.volume.set(*value);
 
             let window = window_from_node(self);
+            window.update_audible();
             window
                 .task_manager()
                 .media_element_task_source()
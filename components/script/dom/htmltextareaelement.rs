@@ -7,11 +7,13 @@ use std::default::Default;
 use std::ops::Range;
 
 use dom_struct::dom_struct;
+use embedder_traits::{InputMethodType, TextInputState};
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::rust::HandleObject;
 use script_traits::ScriptToConstellationChan;
 use style::attr::AttrValue;
 use style_traits::dom::ElementState;
+use webrender_api::units::DeviceIntRect;
 
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
@@ -26,12 +28,13 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
-use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::event::Event;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::inputevent::InputEvent;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::{
     window_from_node, BindContext, ChildrenMutation, CloneChildrenFlag, Node, NodeDamage,
@@ -190,6 +193,17 @@ impl HTMLTextAreaElement {
         HTMLInputElement::directionality_from_value(&value)
     }
 
+    /// The state of this element for the input method of the embedder.
+    pub fn text_input_state(
+        &self,
+        input_method_type: InputMethodType,
+        caret_rect: DeviceIntRect,
+    ) -> TextInputState {
+        self.textinput
+            .borrow()
+            .text_input_state(input_method_type, caret_rect)
+    }
+
     fn update_placeholder_shown_state(&self) {
         let has_placeholder = !self.placeholder.borrow().is_empty();
         let has_value = !self.textinput.borrow().is_empty();
@@ -630,6 +644,19 @@ impl VirtualMethods for HTMLTextAreaElement {
             if let Some(kevent) = event.downcast::<KeyboardEvent>() {
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
+                let window = window_from_node(self);
+                let edit = if event.IsTrusted() {
+                    self.textinput.borrow().input_type_for_keydown(kevent)
+                } else {
+                    None
+                };
+                let proceed = edit.as_ref().map_or(true, |(input_type, data)| {
+                    InputEvent::fire_beforeinput(self.upcast(), &window, input_type, data.clone())
+                });
+                if !proceed {
+                    event.mark_as_handled();
+                    return;
+                }
                 let action = self.textinput.borrow_mut().handle_keydown(kevent);
                 match action {
                     KeyReaction::TriggerDefaultAction => (),
@@ -638,6 +665,9 @@ impl VirtualMethods for HTMLTextAreaElement {
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
+                        if let Some((input_type, data)) = edit {
+                            InputEvent::queue_input(self.upcast(), &window, input_type, data);
+                        }
                     },
                     KeyReaction::RedrawSelection => {
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -646,33 +676,33 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::Nothing => (),
                 }
             }
-        } else if event.type_() == atom!("keypress") && !event.DefaultPrevented() {
-            if event.IsTrusted() {
-                let window = window_from_node(self);
-                window
-                    .task_manager()
-                    .user_interaction_task_source()
-                    .queue_event(
-                        self.upcast(),
-                        atom!("input"),
-                        EventBubbles::Bubbles,
-                        EventCancelable::NotCancelable,
-                        &window,
-                    );
-            }
         } else if event.type_() == atom!("compositionstart") ||
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
+                let action = if event.type_() == atom!("compositionupdate") {
+                    self.textinput
+                        .borrow_mut()
+                        .handle_compositionupdate(compositionevent)
+                } else if event.type_() == atom!("compositionend") {
+                    self.textinput
                         .borrow_mut()
-                        .handle_compositionend(compositionevent);
+                        .handle_compositionend(compositionevent)
+                } else {
+                    KeyReaction::Nothing
+                };
+                if let KeyReaction::DispatchInput = action {
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    let window = window_from_node(self);
+                    InputEvent::queue_input(
+                        self.upcast(),
+                        &window,
+                        "insertCompositionText",
+                        Some(DOMString::from(compositionevent.data())),
+                    );
                 }
                 event.mark_as_handled();
             }
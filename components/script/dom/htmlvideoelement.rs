@@ -90,13 +90,15 @@ impl HTMLVideoElement {
         document: &Document,
         proto: Option<HandleObject>,
     ) -> DomRoot<HTMLVideoElement> {
-        Node::reflect_node_with_proto(
+        let element: DomRoot<HTMLVideoElement> = Node::reflect_node_with_proto(
             Box::new(HTMLVideoElement::new_inherited(
                 local_name, prefix, document,
             )),
             document,
             proto,
-        )
+        );
+        document.window().track_media_element(element.upcast());
+        element
     }
 
     pub fn get_video_width(&self) -> u32 {
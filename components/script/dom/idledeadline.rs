@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use metrics::ToMs;
+
+use crate::dom::bindings::codegen::Bindings::IdleDeadlineBinding::IdleDeadlineMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::performance::reduce_timing_resolution;
+use crate::dom::window::Window;
+
+/// <https://w3c.github.io/requestidlecallback/#the-idledeadline-interface>
+#[dom_struct]
+pub struct IdleDeadline {
+    reflector_: Reflector,
+    /// The end of the idle period the callback runs in, in `time::precise_time_ns()` units.
+    deadline: u64,
+    did_timeout: bool,
+}
+
+impl IdleDeadline {
+    fn new_inherited(deadline: u64, did_timeout: bool) -> IdleDeadline {
+        IdleDeadline {
+            reflector_: Reflector::new(),
+            deadline,
+            did_timeout,
+        }
+    }
+
+    pub fn new(window: &Window, deadline: u64, did_timeout: bool) -> DomRoot<IdleDeadline> {
+        reflect_dom_object(
+            Box::new(IdleDeadline::new_inherited(deadline, did_timeout)),
+            window,
+        )
+    }
+}
+
+impl IdleDeadlineMethods for IdleDeadline {
+    /// <https://w3c.github.io/requestidlecallback/#dom-idledeadline-timeremaining>
+    fn TimeRemaining(&self) -> DOMHighResTimeStamp {
+        // The time remaining is clamped to zero once the deadline passed.
+        let remaining = self.deadline.saturating_sub(time::precise_time_ns());
+        reduce_timing_resolution(remaining.to_ms())
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#dom-idledeadline-didtimeout>
+    fn DidTimeout(&self) -> bool {
+        self.did_timeout
+    }
+}
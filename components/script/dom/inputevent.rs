@@ -8,22 +8,55 @@ use js::rust::HandleObject;
 use crate::dom::bindings::codegen::Bindings::InputEventBinding::{self, InputEventMethods};
 use crate::dom::bindings::codegen::Bindings::UIEventBinding::UIEvent_Binding::UIEventMethods;
 use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::staticrange::StaticRange;
 use crate::dom::uievent::UIEvent;
 use crate::dom::window::Window;
+use crate::task_source::TaskSource;
 
 #[dom_struct]
 pub struct InputEvent {
     uievent: UIEvent,
     data: Option<DOMString>,
     is_composing: bool,
+    input_type: DOMString,
 }
 
 impl InputEvent {
     #[allow(clippy::too_many_arguments)]
-    fn new(
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: bool,
+        cancelable: bool,
+        view: Option<&Window>,
+        detail: i32,
+        data: Option<DOMString>,
+        is_composing: bool,
+        input_type: DOMString,
+    ) -> DomRoot<InputEvent> {
+        Self::new_with_proto(
+            window,
+            None,
+            type_,
+            can_bubble,
+            cancelable,
+            view,
+            detail,
+            data,
+            is_composing,
+            input_type,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_proto(
         window: &Window,
         proto: Option<HandleObject>,
         type_: DOMString,
@@ -33,12 +66,14 @@ impl InputEvent {
         detail: i32,
         data: Option<DOMString>,
         is_composing: bool,
+        input_type: DOMString,
     ) -> DomRoot<InputEvent> {
         let ev = reflect_dom_object_with_proto(
             Box::new(InputEvent {
                 uievent: UIEvent::new_inherited(),
                 data,
                 is_composing,
+                input_type,
             }),
             window,
             proto,
@@ -55,7 +90,7 @@ impl InputEvent {
         type_: DOMString,
         init: &InputEventBinding::InputEventInit,
     ) -> Fallible<DomRoot<InputEvent>> {
-        let event = InputEvent::new(
+        let event = InputEvent::new_with_proto(
             window,
             proto,
             type_,
@@ -65,9 +100,65 @@ impl InputEvent {
             init.parent.detail,
             init.data.clone(),
             init.isComposing,
+            init.inputType.clone(),
         );
         Ok(event)
     }
+
+    /// Synchronously fire a trusted `beforeinput` event at `target`, returning
+    /// whether the edit it describes may go ahead.
+    /// <https://w3c.github.io/input-events/#event-type-beforeinput>
+    pub fn fire_beforeinput(
+        target: &EventTarget,
+        window: &Window,
+        input_type: &str,
+        data: Option<DOMString>,
+    ) -> bool {
+        let event = InputEvent::new(
+            window,
+            DOMString::from("beforeinput"),
+            true,
+            true,
+            Some(window),
+            0,
+            data,
+            false,
+            DOMString::from(input_type),
+        );
+        event.upcast::<Event>().fire(target) == EventStatus::NotCanceled
+    }
+
+    /// Queue a task on the user interaction task source to fire a trusted `input`
+    /// event at `target` once an edit has been performed.
+    /// <https://w3c.github.io/input-events/#event-type-input>
+    pub fn queue_input(
+        target: &EventTarget,
+        window: &Window,
+        input_type: &'static str,
+        data: Option<DOMString>,
+    ) {
+        let target = Trusted::new(target);
+        let trusted_window = Trusted::new(window);
+        let _ = window.task_manager().user_interaction_task_source().queue(
+            task!(fire_input_event: move || {
+                let target = target.root();
+                let window = trusted_window.root();
+                let event = InputEvent::new(
+                    &window,
+                    DOMString::from("input"),
+                    true,
+                    false,
+                    Some(&window),
+                    0,
+                    data,
+                    false,
+                    DOMString::from(input_type),
+                );
+                event.upcast::<Event>().fire(&target);
+            }),
+            window.upcast(),
+        );
+    }
 }
 
 impl InputEventMethods for InputEvent {
@@ -81,6 +172,18 @@ impl InputEventMethods for InputEvent {
         self.is_composing
     }
 
+    // https://w3c.github.io/input-events/#dom-inputevent-inputtype
+    fn InputType(&self) -> DOMString {
+        self.input_type.clone()
+    }
+
+    // https://w3c.github.io/input-events/#dom-inputevent-gettargetranges
+    fn GetTargetRanges(&self) -> Vec<DomRoot<StaticRange>> {
+        // Target ranges are only exposed for edits in editing hosts; edits in
+        // <input> and <textarea> always report an empty list.
+        vec![]
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     fn IsTrusted(&self) -> bool {
         self.uievent.IsTrusted()
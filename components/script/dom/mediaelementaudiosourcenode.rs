@@ -2,19 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 
 use dom_struct::dom_struct;
 use js::rust::HandleObject;
+use servo_media::audio::context::ProcessingState;
 use servo_media::audio::media_element_source_node::MediaElementSourceNodeMessage;
 use servo_media::audio::node::{AudioNodeInit, AudioNodeMessage};
+use servo_media::player::audio::AudioRenderer;
 
 use crate::dom::audiocontext::AudioContext;
 use crate::dom::audionode::AudioNode;
+use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::AudioNodeMethods;
 use crate::dom::bindings::codegen::Bindings::MediaElementAudioSourceNodeBinding::{
     MediaElementAudioSourceNodeMethods, MediaElementAudioSourceOptions,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::htmlmediaelement::HTMLMediaElement;
@@ -31,7 +34,15 @@ impl MediaElementAudioSourceNode {
     fn new_inherited(
         context: &AudioContext,
         media_element: &HTMLMediaElement,
-    ) -> Fallible<MediaElementAudioSourceNode> {
+    ) -> Fallible<(MediaElementAudioSourceNode, Arc<Mutex<dyn AudioRenderer>>)> {
+        // An element can only be routed to a single node at a time, but once the context of
+        // that node is closed its audio can be routed again.
+        if media_element.audio_source_node().is_some_and(|node| {
+            node.node.Context().control_thread_state() != ProcessingState::Closed
+        }) {
+            return Err(Error::InvalidState);
+        }
+
         let node = AudioNode::new_inherited(
             AudioNodeInit::MediaElementSourceNode,
             &context.base(),
@@ -44,12 +55,14 @@ impl MediaElementAudioSourceNode {
             MediaElementSourceNodeMessage::GetAudioRenderer(sender),
         ));
         let audio_renderer = receiver.recv().unwrap();
-        media_element.set_audio_renderer(audio_renderer);
         let media_element = Dom::from_ref(media_element);
-        Ok(MediaElementAudioSourceNode {
-            node,
-            media_element,
-        })
+        Ok((
+            MediaElementAudioSourceNode {
+                node,
+                media_element,
+            },
+            audio_renderer,
+        ))
     }
 
     pub fn new(
@@ -67,8 +80,13 @@ impl MediaElementAudioSourceNode {
         context: &AudioContext,
         media_element: &HTMLMediaElement,
     ) -> Fallible<DomRoot<MediaElementAudioSourceNode>> {
-        let node = MediaElementAudioSourceNode::new_inherited(context, media_element)?;
-        Ok(reflect_dom_object_with_proto(Box::new(node), window, proto))
+        let (node, audio_renderer) =
+            MediaElementAudioSourceNode::new_inherited(context, media_element)?;
+        let node = reflect_dom_object_with_proto(Box::new(node), window, proto);
+        // Only output the audio of the element through the context from now on.
+        media_element.route_audio_to(&node, audio_renderer);
+        context.track_media_element_source(&node);
+        Ok(node)
     }
 
     #[allow(non_snake_case)]
@@ -80,6 +98,11 @@ impl MediaElementAudioSourceNode {
     ) -> Fallible<DomRoot<MediaElementAudioSourceNode>> {
         MediaElementAudioSourceNode::new_with_proto(window, proto, context, &options.mediaElement)
     }
+
+    /// Output the audio of the media element directly again, once the context is closed.
+    pub fn restore_media_element_output(&self) {
+        self.media_element.restore_audio_output(self);
+    }
 }
 
 impl MediaElementAudioSourceNodeMethods for MediaElementAudioSourceNode {
@@ -209,6 +209,8 @@ pub mod types {
     include!(concat!(env!("OUT_DIR"), "/InterfaceTypes.rs"));
 }
 
+pub mod abortcontroller;
+pub mod abortsignal;
 pub mod abstractrange;
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
@@ -224,6 +226,7 @@ pub mod audiolistener;
 pub mod audionode;
 pub mod audioparam;
 pub mod audioscheduledsourcenode;
+pub mod audiosinkinfo;
 pub mod audiotrack;
 pub mod audiotracklist;
 pub mod baseaudiocontext;
@@ -250,6 +253,8 @@ pub mod channelmergernode;
 pub mod channelsplitternode;
 pub mod characterdata;
 pub mod client;
+pub mod clipboard;
+pub mod clipboarditem;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
@@ -322,6 +327,10 @@ pub mod gamepadbutton;
 pub mod gamepadbuttonlist;
 pub mod gamepadevent;
 pub mod gamepadpose;
+pub mod geolocation;
+pub mod geolocationcoordinates;
+pub mod geolocationposition;
+pub mod geolocationpositionerror;
 pub mod globalscope;
 pub mod gpu;
 pub mod gpuadapter;
@@ -438,6 +447,7 @@ pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
 pub mod identityhub;
+pub mod idledeadline;
 pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
@@ -473,6 +483,7 @@ pub mod navigatorinfo;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
+pub mod notification;
 pub mod offlineaudiocompletionevent;
 pub mod offlineaudiocontext;
 pub mod offscreencanvas;
@@ -485,6 +496,7 @@ pub mod paintworkletglobalscope;
 pub mod pannernode;
 pub mod performance;
 pub mod performanceentry;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigation;
@@ -523,6 +535,7 @@ pub(crate) mod rtcrtpsender;
 pub(crate) mod rtcrtptransceiver;
 pub mod rtcsessiondescription;
 pub mod rtctrackevent;
+pub mod scheduler;
 pub mod screen;
 pub mod selection;
 pub mod serviceworker;
@@ -530,11 +543,13 @@ pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
+pub mod servotestutils;
 pub mod shadowroot;
 pub mod staticrange;
 pub mod stereopannernode;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
@@ -542,6 +557,10 @@ pub mod submitevent;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
+pub mod taskcontroller;
+pub mod taskprioritychangeevent;
+pub mod tasksignal;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingmaplike;
@@ -575,6 +594,7 @@ pub mod urlsearchparams;
 pub mod userscripts;
 pub mod validation;
 pub mod validitystate;
+pub mod valueevent;
 pub mod values;
 pub mod vertexarrayobject;
 pub mod videotrack;
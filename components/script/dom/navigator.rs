@@ -17,8 +17,10 @@ use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::clipboard::Clipboard;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gamepadevent::GamepadEventType;
+use crate::dom::geolocation::Geolocation;
 use crate::dom::gpu::GPU;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
@@ -27,6 +29,7 @@ use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::window::Window;
 use crate::dom::xrsystem::XRSystem;
 use crate::script_runtime::JSContext;
@@ -50,8 +53,11 @@ pub struct Navigator {
     /// <https://www.w3.org/TR/gamepad/#dfn-gamepads>
     gamepads: DomRefCell<Vec<MutNullableDom<Gamepad>>>,
     permissions: MutNullableDom<Permissions>,
+    storage: MutNullableDom<StorageManager>,
     mediasession: MutNullableDom<MediaSession>,
     gpu: MutNullableDom<GPU>,
+    geolocation: MutNullableDom<Geolocation>,
+    clipboard: MutNullableDom<Clipboard>,
     /// <https://www.w3.org/TR/gamepad/#dfn-hasgamepadgesture>
     has_gamepad_gesture: Cell<bool>,
 }
@@ -68,8 +74,11 @@ impl Navigator {
             mediadevices: Default::default(),
             gamepads: Default::default(),
             permissions: Default::default(),
+            storage: Default::default(),
             mediasession: Default::default(),
             gpu: Default::default(),
+            geolocation: Default::default(),
+            clipboard: Default::default(),
             has_gamepad_gesture: Cell::new(false),
         }
     }
@@ -82,6 +91,14 @@ impl Navigator {
         self.xr.get()
     }
 
+    pub fn geolocation(&self) -> Option<DomRoot<Geolocation>> {
+        self.geolocation.get()
+    }
+
+    pub fn bluetooth(&self) -> Option<DomRoot<Bluetooth>> {
+        self.bluetooth.get()
+    }
+
     pub fn get_gamepad(&self, index: usize) -> Option<DomRoot<Gamepad>> {
         self.gamepads.borrow().get(index).and_then(|g| g.get())
     }
@@ -250,6 +267,11 @@ impl NavigatorMethods for Navigator {
             .or_init(|| Permissions::new(&self.global()))
     }
 
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
     /// <https://immersive-web.github.io/webxr/#dom-navigator-xr>
     fn Xr(&self) -> DomRoot<XRSystem> {
         self.xr.or_init(|| XRSystem::new(self.global().as_window()))
@@ -282,6 +304,17 @@ impl NavigatorMethods for Navigator {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
 
+    /// <https://w3c.github.io/geolocation/#navigator_interface>
+    fn Geolocation(&self) -> DomRoot<Geolocation> {
+        self.geolocation
+            .or_init(|| Geolocation::new(&self.global()))
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-navigator-clipboard>
+    fn Clipboard(&self) -> DomRoot<Clipboard> {
+        self.clipboard.or_init(|| Clipboard::new(&self.global()))
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-navigator-hardwareconcurrency>
     fn HardwareConcurrency(&self) -> u64 {
         hardware_concurrency()
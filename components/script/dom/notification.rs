@@ -0,0 +1,304 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use base::id::NotificationId;
+use dom_struct::dom_struct;
+use embedder_traits::{NotificationEvent, PermissionPrompt};
+use js::rust::HandleObject;
+use script_traits::ScriptMsg;
+use servo_url::ServoUrl;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::NotificationBinding::{
+    NotificationDirection, NotificationMethods, NotificationOptions, NotificationPermission,
+    NotificationPermissionCallback,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::{AlreadyInRealm, InRealm};
+use crate::task_source::TaskSource;
+
+/// <https://notifications.spec.whatwg.org/#concept-notification>
+#[dom_struct]
+pub struct Notification {
+    eventtarget: EventTarget,
+    #[no_trace]
+    id: NotificationId,
+    title: DOMString,
+    dir: NotificationDirection,
+    lang: DOMString,
+    body: DOMString,
+    tag: DOMString,
+    #[no_trace]
+    icon: Option<ServoUrl>,
+    silent: Option<bool>,
+    require_interaction: bool,
+    /// Whether the notification is in the list of notifications, which it leaves when it is
+    /// closed or could not be shown.
+    shown: Cell<bool>,
+}
+
+impl Notification {
+    fn new_inherited(
+        title: DOMString,
+        options: &NotificationOptions,
+        icon: Option<ServoUrl>,
+    ) -> Notification {
+        Notification {
+            eventtarget: EventTarget::new_inherited(),
+            id: NotificationId::new(),
+            title,
+            dir: options.dir,
+            lang: options.lang.clone(),
+            body: options.body.clone(),
+            tag: options.tag.clone(),
+            icon,
+            silent: options.silent,
+            require_interaction: options.requireInteraction,
+            shown: Cell::new(true),
+        }
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-notification>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        title: DOMString,
+        options: &NotificationOptions,
+    ) -> Fallible<DomRoot<Notification>> {
+        // Step 3. Let notification be the result of creating a notification. The URL of the
+        // icon is ignored if it can't be parsed.
+        let global = window.upcast::<GlobalScope>();
+        let icon = options
+            .icon
+            .as_ref()
+            .and_then(|icon| ServoUrl::parse_with_base(Some(&global.api_base_url()), icon).ok());
+        let notification = reflect_dom_object_with_proto(
+            Box::new(Notification::new_inherited(title, options, icon)),
+            global,
+            proto,
+        );
+
+        // Step 5. Run the notification show steps for notification.
+        notification.show(window);
+        Ok(notification)
+    }
+
+    /// <https://notifications.spec.whatwg.org/#notification-show-steps>
+    fn show(&self, window: &Window) {
+        // Step 2. If the notification permission is not "granted", queue a task to fire an
+        // event named error at the notification and abort these steps.
+        if Notification::Permission(window) != NotificationPermission::Granted {
+            self.shown.set(false);
+            let notification = Trusted::new(self);
+            let _ = window.task_manager().dom_manipulation_task_source().queue(
+                task!(notification_error: move || {
+                    notification.root().upcast::<EventTarget>().fire_event(atom!("error"));
+                }),
+                window.upcast(),
+            );
+            return;
+        }
+
+        // Step 3-6. The embedder replaces the notification of the same origin with the same tag,
+        // then displays it and reports that it is shown.
+        window.register_notification(self);
+        window.send_to_constellation(ScriptMsg::ShowNotification(embedder_traits::Notification {
+            id: self.id,
+            origin: window.upcast::<GlobalScope>().origin().immutable().clone(),
+            title: String::from(self.title.clone()),
+            body: String::from(self.body.clone()),
+            tag: String::from(self.tag.clone()),
+            icon: self.icon.clone(),
+            lang: String::from(self.lang.clone()),
+            direction: match self.dir {
+                NotificationDirection::Auto => embedder_traits::NotificationDirection::Auto,
+                NotificationDirection::Ltr => embedder_traits::NotificationDirection::LeftToRight,
+                NotificationDirection::Rtl => embedder_traits::NotificationDirection::RightToLeft,
+            },
+            silent: self.silent.unwrap_or(false),
+            require_interaction: self.require_interaction,
+        }));
+    }
+
+    pub fn id(&self) -> NotificationId {
+        self.id
+    }
+
+    /// Fire the event for something that happened to the notification in the embedder.
+    pub fn handle_event(&self, event: NotificationEvent) {
+        if !self.shown.get() {
+            return;
+        }
+        let event_target = self.upcast::<EventTarget>();
+        match event {
+            NotificationEvent::Show => {
+                event_target.fire_event(atom!("show"));
+            },
+            NotificationEvent::Click => {
+                event_target.fire_cancelable_event(atom!("click"));
+            },
+            NotificationEvent::Error => {
+                self.forget();
+                event_target.fire_event(atom!("error"));
+            },
+            NotificationEvent::Close => {
+                self.forget();
+                event_target.fire_event(atom!("close"));
+            },
+        }
+    }
+
+    /// Remove the notification from the list of notifications, after which its events are not
+    /// fired anymore.
+    fn forget(&self) {
+        self.shown.set(false);
+        self.global().as_window().unregister_notification(self.id);
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-permission>
+    #[allow(non_snake_case)]
+    pub fn Permission(window: &Window) -> NotificationPermission {
+        match get_descriptor_permission_state(
+            PermissionName::Notifications,
+            Some(window.upcast::<GlobalScope>()),
+        ) {
+            PermissionState::Granted => NotificationPermission::Granted,
+            PermissionState::Denied => NotificationPermission::Denied,
+            PermissionState::Prompt => NotificationPermission::Default,
+        }
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-requestpermission>
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(
+        window: &Window,
+        deprecated_callback: Option<Rc<NotificationPermissionCallback>>,
+    ) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+
+        // Step 3.1. Let permissionState be the result of requesting permission to use
+        // "notifications".
+        let mut permission = Notification::Permission(window);
+        if permission == NotificationPermission::Default {
+            let global = window.upcast::<GlobalScope>();
+            let state = prompt_user_from_embedder(
+                PermissionPrompt::Request(embedder_traits::PermissionName::Notifications),
+                global,
+            );
+            global
+                .permission_state_invocation_results()
+                .borrow_mut()
+                .insert(PermissionName::Notifications.to_string(), state);
+            permission = match state {
+                PermissionState::Granted => NotificationPermission::Granted,
+                _ => NotificationPermission::Denied,
+            };
+        }
+
+        // Step 3.2. Invoke deprecatedCallback, if given, and resolve promise with
+        // permissionState. The user was already asked, so there is no need to wait for a task.
+        if let Some(callback) = deprecated_callback {
+            let _ = callback.Call__(permission, ExceptionHandling::Report);
+        }
+        promise.resolve_native(&permission);
+        promise
+    }
+}
+
+impl NotificationMethods for Notification {
+    // https://notifications.spec.whatwg.org/#dom-notification-onclick
+    event_handler!(click, GetOnclick, SetOnclick);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onshow
+    event_handler!(show, GetOnshow, SetOnshow);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://notifications.spec.whatwg.org/#dom-notification-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-title>
+    fn Title(&self) -> DOMString {
+        self.title.clone()
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-dir>
+    fn Dir(&self) -> NotificationDirection {
+        self.dir
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-lang>
+    fn Lang(&self) -> DOMString {
+        self.lang.clone()
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-body>
+    fn Body(&self) -> DOMString {
+        self.body.clone()
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-tag>
+    fn Tag(&self) -> DOMString {
+        self.tag.clone()
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-icon>
+    fn Icon(&self) -> USVString {
+        USVString(
+            self.icon
+                .as_ref()
+                .map(|icon| icon.to_string())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-silent>
+    fn GetSilent(&self) -> Option<bool> {
+        self.silent
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-requireinteraction>
+    fn RequireInteraction(&self) -> bool {
+        self.require_interaction
+    }
+
+    /// <https://notifications.spec.whatwg.org/#dom-notification-close>
+    fn Close(&self) {
+        // Run the close steps: remove the notification from the list of notifications, and
+        // queue a task to fire an event named close at it.
+        if !self.shown.get() {
+            return;
+        }
+        self.forget();
+        let global = self.global();
+        let window = global.as_window();
+        window.send_to_constellation(ScriptMsg::CloseNotification(self.id));
+        let notification = Trusted::new(self);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(notification_close: move || {
+                notification.root().upcast::<EventTarget>().fire_event(atom!("close"));
+            }),
+            window.upcast(),
+        );
+    }
+}
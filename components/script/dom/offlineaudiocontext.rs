@@ -16,6 +16,7 @@ use crate::dom::audiobuffer::{AudioBuffer, MAX_SAMPLE_RATE, MIN_SAMPLE_RATE};
 use crate::dom::audionode::MAX_CHANNEL_COUNT;
 use crate::dom::baseaudiocontext::{BaseAudioContext, BaseAudioContextOptions};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::AudioContextState;
 use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContext_Binding::BaseAudioContextMethods;
 use crate::dom::bindings::codegen::Bindings::OfflineAudioContextBinding::{
     OfflineAudioContextMethods, OfflineAudioContextOptions,
@@ -27,6 +28,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::offlineaudiocompletionevent::OfflineAudioCompletionEvent;
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
@@ -118,6 +120,44 @@ impl OfflineAudioContext {
     ) -> Fallible<DomRoot<OfflineAudioContext>> {
         OfflineAudioContext::new(window, proto, number_of_channels, length, *sample_rate)
     }
+
+    /// Whether `startRendering()` has been called, which is the only way to start an
+    /// offline context.
+    pub fn rendering_started(&self) -> bool {
+        self.rendering_started.get()
+    }
+
+    /// Only called from tasks, so the event is fired right away and its listeners see the
+    /// state it is about.
+    fn set_state_and_notify(&self, state: AudioContextState) {
+        self.context.set_state_attribute(state);
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("statechange"));
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#rendering-loop>
+    ///
+    /// Called once the whole buffer has been rendered: the context is closed, then the
+    /// rendering promise is resolved and `complete` is fired with the rendered buffer.
+    fn finish_rendering(&self, buffer: &AudioBuffer) {
+        if self.context.State() == AudioContextState::Suspended {
+            self.set_state_and_notify(AudioContextState::Running);
+        }
+        self.set_state_and_notify(AudioContextState::Closed);
+        if let Some(promise) = self.pending_rendering_promise.borrow_mut().take() {
+            promise.resolve_native(buffer);
+        }
+        let global = self.global();
+        let window = global.as_window();
+        let event = OfflineAudioCompletionEvent::new(
+            window,
+            atom!("complete"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            buffer,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
 }
 
 impl OfflineAudioContextMethods for OfflineAudioContext {
@@ -132,7 +172,7 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
     // https://webaudio.github.io/web-audio-api/#dom-offlineaudiocontext-startrendering
     fn StartRendering(&self, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-        if self.rendering_started.get() {
+        if self.rendering_started.get() || self.context.State() == AudioContextState::Closed {
             promise.reject_error(Error::InvalidState);
             return promise;
         }
@@ -169,14 +209,17 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
                 let _ = task_source.queue_with_canceller(
                     task!(resolve: move || {
                         let this = this.root();
+                        let length = this.length as usize;
                         let processed_audio = processed_audio.lock().unwrap();
+                        // The rendered audio is planar, one channel after the other.
                         let mut processed_audio: Vec<_> = processed_audio
-                            .chunks(this.length as usize)
+                            .chunks(length)
                             .map(|channel| channel.to_vec())
                             .collect();
-                        // it can end up being empty if the task failed
-                        if processed_audio.len() != this.length as usize {
-                            processed_audio.resize(this.length as usize, Vec::new())
+                        // It can end up being short, or even empty, if rendering failed.
+                        processed_audio.resize(this.channel_count as usize, Vec::new());
+                        for channel in processed_audio.iter_mut() {
+                            channel.resize(length, 0.);
                         }
                         let buffer = AudioBuffer::new(
                             this.global().as_window(),
@@ -184,15 +227,7 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
                             this.length,
                             *this.context.SampleRate(),
                             Some(processed_audio.as_slice()));
-                        (*this.pending_rendering_promise.borrow_mut()).take().unwrap().resolve_native(&buffer);
-                        let global = &this.global();
-                        let window = global.as_window();
-                        let event = OfflineAudioCompletionEvent::new(window,
-                                                                     atom!("complete"),
-                                                                     EventBubbles::DoesNotBubble,
-                                                                     EventCancelable::NotCancelable,
-                                                                     &buffer);
-                        event.upcast::<Event>().fire(this.upcast());
+                        this.finish_rendering(&buffer);
                     }),
                     &canceller,
                 );
@@ -208,8 +243,22 @@ impl OfflineAudioContextMethods for OfflineAudioContext {
             .is_err()
         {
             promise.reject_error(Error::Type("Could not start offline rendering".to_owned()));
+            return promise;
         }
 
+        // The rendering thread is now running, let the page know. Rendering may also have
+        // already finished by the time this task runs, see `finish_rendering`.
+        let this = Trusted::new(self);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(offline_rendering_started: move || {
+                let this = this.root();
+                if this.context.State() == AudioContextState::Suspended {
+                    this.set_state_and_notify(AudioContextState::Running);
+                }
+            }),
+            window.upcast(),
+        );
+
         promise
     }
 }
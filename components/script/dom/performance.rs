@@ -22,6 +22,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performancelongtasktiming::PerformanceLongTaskTiming;
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
@@ -53,6 +54,10 @@ const INVALID_ENTRY_NAMES: &[&str] = &[
     "loadEventEnd",
 ];
 
+/// <https://w3c.github.io/longtasks/#long-task>
+/// Tasks running for longer than this, in milliseconds, are reported as long tasks.
+pub const LONG_TASK_THRESHOLD: f64 = 50.;
+
 /// Implementation of a list of PerformanceEntry items shared by the
 /// Performance and PerformanceObserverEntryList interfaces implementations.
 #[derive(JSTraceable, MallocSizeOf)]
@@ -330,7 +335,31 @@ impl Performance {
     }
 
     fn now(&self) -> f64 {
-        (time::precise_time_ns() - self.navigation_start_precise).to_ms()
+        self.time_since_origin(time::precise_time_ns())
+    }
+
+    /// The number of milliseconds between the time origin and `precise_time_ns`.
+    fn time_since_origin(&self, precise_time_ns: u64) -> f64 {
+        precise_time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
+    }
+
+    /// Queue a `longtask` entry if the work that ran between `start_time` and `end_time`, both
+    /// given in `time::precise_time_ns()` units, took long enough to be a long task.
+    ///
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    pub fn report_long_task(&self, start_time: u64, end_time: u64) {
+        let duration = end_time.saturating_sub(start_time).to_ms();
+        if duration <= LONG_TASK_THRESHOLD {
+            return;
+        }
+        let entry = PerformanceLongTaskTiming::new(
+            &self.global(),
+            self.time_since_origin(start_time),
+            duration,
+        );
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
     }
 
     fn can_add_resource_timing_entry(&self) -> bool {
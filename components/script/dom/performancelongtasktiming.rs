@@ -0,0 +1,61 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::PerformanceLongTaskTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming>
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    attribution: Dom<TaskAttributionTiming>,
+}
+
+impl PerformanceLongTaskTiming {
+    #[allow(crown::unrooted_must_root)]
+    fn new_inherited(
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            // The culprit of a task run by the window itself is always "self".
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("self"),
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: Dom::from_ref(attribution),
+        }
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        start_time: f64,
+        duration: f64,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let attribution = TaskAttributionTiming::new(global);
+        let entry = PerformanceLongTaskTiming::new_inherited(start_time, duration, &attribution);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    fn Attribution(&self, cx: JSContext) -> JSVal {
+        to_frozen_array(&[DomRoot::from_ref(&*self.attribution)], cx)
+    }
+}
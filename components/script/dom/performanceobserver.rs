@@ -8,6 +8,7 @@ use std::rc::Rc;
 use dom_struct::dom_struct;
 use js::jsval::JSVal;
 use js::rust::HandleObject;
+use servo_config::pref;
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
@@ -29,6 +30,7 @@ use crate::script_runtime::JSContext;
 /// List of allowed performance entry types, in alphabetical order.
 pub const VALID_ENTRY_TYPES: &[&str] = &[
     // "frame", //TODO Frame Timing API
+    "longtask",   // Long Tasks API
     "mark",       // User Timing API
     "measure",    // User Timing API
     "navigation", // Navigation Timing API
@@ -37,6 +39,12 @@ pub const VALID_ENTRY_TYPES: &[&str] = &[
                   // "server", XXX Server Timing API
 ];
 
+/// Whether the given entry type is in [`VALID_ENTRY_TYPES`] and its API is enabled.
+pub fn is_supported_entry_type(entry_type: &str) -> bool {
+    VALID_ENTRY_TYPES.contains(&entry_type) &&
+        (entry_type != "longtask" || pref!(dom.longtask.enabled))
+}
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 enum ObserverType {
     Undefined,
@@ -185,7 +193,7 @@ impl PerformanceObserverMethods for PerformanceObserver {
             // Steps 6.1 - 6.2
             let entry_types = entry_types
                 .iter()
-                .filter(|e| VALID_ENTRY_TYPES.contains(&e.as_ref()))
+                .filter(|e| is_supported_entry_type(e))
                 .cloned()
                 .collect::<Vec<DOMString>>();
 
@@ -207,7 +215,7 @@ impl PerformanceObserverMethods for PerformanceObserver {
             Ok(())
         } else if let Some(entry_type) = &options.type_ {
             // Step 7.2
-            if !VALID_ENTRY_TYPES.contains(&entry_type.as_ref()) {
+            if !is_supported_entry_type(entry_type) {
                 Console::internal_warn(
                     &self.global(),
                     DOMString::from("No valid entry type provided to observe()."),
@@ -345,12 +345,19 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/clipboard-apis/#clipboard-permissions
+        // The Clipboard API is only exposed to secure contexts.
+        PermissionName::Clipboard_read => true,
     }
 }
 
-fn prompt_user_from_embedder(prompt: PermissionPrompt, gs: &GlobalScope) -> PermissionState {
+pub(crate) fn prompt_user_from_embedder(
+    prompt: PermissionPrompt,
+    gs: &GlobalScope,
+) -> PermissionState {
     let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
-    gs.send_to_embedder(EmbedderMsg::PromptPermission(prompt, sender));
+    let origin = gs.origin().immutable().clone();
+    gs.send_to_embedder(EmbedderMsg::PromptPermission(prompt, origin, sender));
 
     match receiver.recv() {
         Ok(PermissionRequest::Granted) => PermissionState::Granted,
@@ -381,6 +388,7 @@ impl From<PermissionName> for embedder_traits::PermissionName {
             PermissionName::Persistent_storage => {
                 embedder_traits::PermissionName::PersistentStorage
             },
+            PermissionName::Clipboard_read => embedder_traits::PermissionName::ClipboardRead,
         }
     }
 }
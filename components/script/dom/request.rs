@@ -26,8 +26,8 @@ use crate::body::{consume_body, BodyMixin, BodyType, Extractable};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::{
-    ReferrerPolicy, RequestCache, RequestCredentials, RequestDestination, RequestInfo, RequestInit,
-    RequestMethods, RequestMode, RequestRedirect,
+    ReferrerPolicy, RequestCache, RequestCredentials, RequestDestination, RequestDuplex,
+    RequestInfo, RequestInit, RequestMethods, RequestMode, RequestRedirect,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
@@ -417,9 +417,31 @@ impl Request {
 
         // Step 38 is done earlier
 
-        // Step 39 "TODO if body is non-null and body's source is null..."
-        // This looks like where we need to set the use-preflight flag
-        // if the request has a body and nothing else has set the flag.
+        // Step 39
+        if input_body
+            .as_ref()
+            .is_some_and(|body| body.source_is_null())
+        {
+            // Step 39.1
+            if matches!(init.body, Some(Some(_))) && init.duplex.is_none() {
+                return Err(Error::Type(
+                    "RequestInit's body is a ReadableStream and duplex option is not set"
+                        .to_string(),
+                ));
+            }
+            // Step 39.2
+            if !matches!(
+                r.request.borrow().mode,
+                NetTraitsRequestMode::SameOrigin | NetTraitsRequestMode::CorsMode
+            ) {
+                return Err(Error::Type(
+                    "Request mode must be \"cors\" or \"same-origin\" if request's body is a ReadableStream"
+                        .to_string(),
+                ));
+            }
+            // Step 39.3
+            r.request.borrow_mut().use_cors_preflight = true;
+        }
 
         // Step 40 is done earlier
 
@@ -593,6 +615,11 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-duplex
+    fn Duplex(&self) -> RequestDuplex {
+        RequestDuplex::Half
+    }
+
     /// <https://fetch.spec.whatwg.org/#dom-body-body>
     fn GetBody(&self, _cx: SafeJSContext) -> Option<NonNull<JSObject>> {
         self.body().map(|stream| stream.get_js_stream())
@@ -263,14 +263,16 @@ fn calculate_depth_for_node(target: &Element) -> ResizeObservationDepth {
 }
 
 /// <https://drafts.csswg.org/resize-observer/#calculate-box-size>
+///
+/// This reads the result of the last layout, which `Document` brings up to date once before
+/// gathering the active observations of all its observers.
 fn calculate_box_size(target: &Element, observed_box: &ResizeObserverBoxOptions) -> Rect<Au> {
     match observed_box {
         ResizeObserverBoxOptions::Content_box => {
             // Note: only taking first fragment,
             // but the spec will expand to cover all fragments.
-            target
-                .upcast::<Node>()
-                .content_boxes()
+            window_from_node(target)
+                .content_boxes_query_without_reflow(target.upcast())
                 .pop()
                 .unwrap_or_else(|| Rect::zero())
         },
@@ -0,0 +1,265 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use euclid::Length;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+
+use crate::dom::abortsignal::AbortAlgorithm;
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::{
+    SchedulerMethods, SchedulerPostTaskCallback, SchedulerPostTaskOptions, TaskPriority,
+};
+use crate::dom::bindings::error::take_pending_exception;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::tasksignal::TaskSignal;
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
+use crate::task_source::TaskSource;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+
+/// A task posted with `postTask()` that did not run yet.
+/// <https://wicg.github.io/scheduling-apis/#scheduler-task>
+#[derive(JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+struct ScheduledTask {
+    /// The enqueue order of the task, which also identifies it.
+    id: u64,
+    #[ignore_malloc_size_of = "Rc are hard"]
+    callback: Rc<SchedulerPostTaskCallback>,
+    #[ignore_malloc_size_of = "Rc are hard"]
+    result: Rc<Promise>,
+    /// The priority the task was posted with, used if it has no `priority_source`.
+    priority: TaskPriority,
+    /// The signal whose priority the task follows, if it was posted with a `TaskSignal` and no
+    /// explicit priority.
+    priority_source: Option<Dom<TaskSignal>>,
+    /// The timer that makes the task runnable once its delay elapsed.
+    delay_handle: Option<OneshotTimerHandle>,
+}
+
+impl ScheduledTask {
+    fn priority(&self) -> TaskPriority {
+        match self.priority_source {
+            Some(ref signal) => signal.priority(),
+            None => self.priority,
+        }
+    }
+
+    /// The key by which runnable tasks are ordered: first by priority, then by enqueue order.
+    fn order(&self) -> (u8, u64) {
+        let rank = match self.priority() {
+            TaskPriority::User_blocking => 0,
+            TaskPriority::User_visible => 1,
+            TaskPriority::Background => 2,
+        };
+        (rank, self.id)
+    }
+}
+
+/// <https://wicg.github.io/scheduling-apis/#sec-scheduler>
+#[dom_struct]
+pub struct Scheduler {
+    reflector_: Reflector,
+    /// The tasks posted with `postTask()` that did not run or get aborted yet. This plays the
+    /// role of the task queues of the scheduler, with the priority of each task computed when
+    /// the next task to run is selected, so that priority changes apply immediately.
+    tasks: DomRefCell<Vec<ScheduledTask>>,
+    next_enqueue_order: Cell<u64>,
+}
+
+impl Scheduler {
+    fn new_inherited() -> Scheduler {
+        Scheduler {
+            reflector_: Reflector::new(),
+            tasks: DomRefCell::new(vec![]),
+            next_enqueue_order: Cell::new(0),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<Scheduler> {
+        reflect_dom_object(Box::new(Scheduler::new_inherited()), window)
+    }
+
+    /// Queue a task on the event loop that runs the next task of the scheduler. User-blocking
+    /// tasks use the user interaction task source, and the others the DOM manipulation task
+    /// source, but the task that actually runs is always the one with the highest priority.
+    fn queue_task_runner(&self, priority: TaskPriority) {
+        let global = self.global();
+        let window = global.as_window();
+        let scheduler = Trusted::new(self);
+        let task = task!(run_scheduled_task: move || {
+            scheduler.root().run_next_task();
+        });
+        let task_manager = window.task_manager();
+        let _ = match priority {
+            TaskPriority::User_blocking => task_manager
+                .user_interaction_task_source()
+                .queue(task, window.upcast()),
+            TaskPriority::User_visible | TaskPriority::Background => task_manager
+                .dom_manipulation_task_source()
+                .queue(task, window.upcast()),
+        };
+    }
+
+    /// Run the runnable task with the highest priority, and settle its promise with the result.
+    /// <https://wicg.github.io/scheduling-apis/#schedule-a-task-to-invoke-a-callback>
+    #[allow(crown::unrooted_must_root, unsafe_code)]
+    fn run_next_task(&self) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            let next = tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| task.delay_handle.is_none())
+                .min_by_key(|(_, task)| task.order())
+                .map(|(index, _)| index);
+            match next {
+                Some(index) => tasks.remove(index),
+                None => return,
+            }
+        };
+        let ScheduledTask {
+            callback, result, ..
+        } = task;
+
+        let _realm = enter_realm(self);
+        let cx = GlobalScope::get_cx();
+        match callback.Call__(ExceptionHandling::Rethrow) {
+            Ok(value) => {
+                rooted!(in(*cx) let value = value);
+                result.resolve(cx, value.handle());
+            },
+            Err(_) => {
+                rooted!(in(*cx) let mut error = UndefinedValue());
+                unsafe { take_pending_exception(*cx, error.handle_mut()) };
+                result.reject(cx, error.handle());
+            },
+        }
+    }
+
+    /// Make the task with the given id runnable once its delay elapsed.
+    fn handle_delay_elapsed(&self, task_id: u64) {
+        let priority = {
+            let mut tasks = self.tasks.borrow_mut();
+            let Some(task) = tasks.iter_mut().find(|task| task.id == task_id) else {
+                return;
+            };
+            task.delay_handle = None;
+            task.priority()
+        };
+        self.queue_task_runner(priority);
+    }
+
+    /// Remove the task with the given id if it did not run yet, and reject its promise with
+    /// `reason`.
+    #[allow(crown::unrooted_must_root)]
+    pub fn abort_task(&self, task_id: u64, reason: HandleValue) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            let Some(index) = tasks.iter().position(|task| task.id == task_id) else {
+                return;
+            };
+            tasks.remove(index)
+        };
+        if let Some(handle) = task.delay_handle {
+            self.global().unschedule_callback(handle);
+        }
+        task.result.reject(GlobalScope::get_cx(), reason);
+    }
+}
+
+impl SchedulerMethods for Scheduler {
+    /// <https://wicg.github.io/scheduling-apis/#dom-scheduler-posttask>
+    #[allow(crown::unrooted_must_root)]
+    fn PostTask(
+        &self,
+        callback: Rc<SchedulerPostTaskCallback>,
+        options: &SchedulerPostTaskOptions,
+    ) -> Rc<Promise> {
+        // Let result be a new promise.
+        let in_realm_proof = AlreadyInRealm::assert();
+        let result = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+
+        // If signal is not null and it is aborted, then reject result with signal’s abort
+        // reason and return result.
+        if let Some(ref signal) = options.signal {
+            if signal.aborted() {
+                let cx = GlobalScope::get_cx();
+                rooted!(in(*cx) let reason = signal.Reason(cx));
+                result.reject(cx, reason.handle());
+                return result;
+            }
+        }
+
+        // The priority of the task is the priority of the options if there is one, or else the
+        // one of the signal if it is a TaskSignal.
+        let priority_source = match options.priority {
+            Some(_) => None,
+            None => options
+                .signal
+                .as_ref()
+                .and_then(|signal| signal.downcast::<TaskSignal>())
+                .map(Dom::from_ref),
+        };
+        let id = self.next_enqueue_order.get();
+        self.next_enqueue_order.set(id + 1);
+
+        // If signal is not null, then add an algorithm to it that removes the task from its
+        // queue and rejects result with the abort reason.
+        if let Some(ref signal) = options.signal {
+            signal.add_algorithm(AbortAlgorithm::ScheduledTask(Dom::from_ref(self), id));
+        }
+
+        // If the delay is greater than 0, wait for it before queuing the task.
+        let delay_handle = (options.delay > 0).then(|| {
+            let callback = OneshotTimerCallback::ScheduledTaskDelay(ScheduledTaskDelayCallback {
+                scheduler: Trusted::new(self),
+                task_id: id,
+            });
+            self.global()
+                .schedule_callback(callback, Length::new(options.delay))
+        });
+        let task = ScheduledTask {
+            id,
+            callback,
+            result: result.clone(),
+            priority: options.priority.unwrap_or(TaskPriority::User_visible),
+            priority_source,
+            delay_handle,
+        };
+        let runnable = task.delay_handle.is_none();
+        let priority = task.priority();
+        self.tasks.borrow_mut().push(task);
+        if runnable {
+            self.queue_task_runner(priority);
+        }
+
+        result
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct ScheduledTaskDelayCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    scheduler: Trusted<Scheduler>,
+    task_id: u64,
+}
+
+impl ScheduledTaskDelayCallback {
+    pub fn invoke(self) {
+        self.scheduler.root().handle_delay_elapsed(self.task_id);
+    }
+}
@@ -4,6 +4,7 @@
 
 use std::cell::Cell;
 
+use devtools_traits::{ScriptToDevtoolsControlMsg, SelectionInfo};
 use dom_struct::dom_struct;
 
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
@@ -17,6 +18,7 @@ use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::node::{window_from_node, Node};
 use crate::dom::range::Range;
 use crate::task_source::TaskSource;
@@ -96,6 +98,7 @@ impl Selection {
                     let this = this.root();
                     this.task_queued.set(false);
                     this.document.upcast::<EventTarget>().fire_event(atom!("selectionchange"));
+                    this.notify_devtools();
                 }),
                 window.upcast(),
             )
@@ -103,6 +106,30 @@ impl Selection {
         self.task_queued.set(true);
     }
 
+    /// Report the new state of this selection to attached devtools clients.
+    fn notify_devtools(&self) {
+        let window = window_from_node(&*self.document);
+        let global = window.upcast::<GlobalScope>();
+        if !global.live_devtools_updates() {
+            return;
+        }
+        let Some(chan) = global.devtools_chan() else {
+            return;
+        };
+        let selection = SelectionInfo {
+            anchor_node: self.GetAnchorNode().map(|node| node.summarize()),
+            anchor_offset: self.AnchorOffset(),
+            focus_node: self.GetFocusNode().map(|node| node.summarize()),
+            focus_offset: self.FocusOffset(),
+            is_collapsed: self.IsCollapsed(),
+            text: self.Stringifier().into(),
+        };
+        let _ = chan.send(ScriptToDevtoolsControlMsg::SelectionChanged(
+            global.pipeline_id(),
+            selection,
+        ));
+    }
+
     fn is_same_root(&self, node: &Node) -> bool {
         &*node.GetRootNode(&GetRootNodeOptions::empty()) == self.document.upcast::<Node>()
     }
@@ -421,6 +421,9 @@ impl ServiceWorkerGlobalScope {
                 DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
                     devtools::handle_wants_live_notifications(self.upcast(), bool_val)
                 },
+                DevtoolScriptControlMsg::SetServerRunning(running) => {
+                    devtools::handle_set_server_running(running)
+                },
                 _ => debug!("got an unusable devtools control message inside the worker!"),
             },
             MixedMessage::ServiceWorker(msg) => {
@@ -11,7 +11,7 @@ use base64::engine::general_purpose;
 use base64::Engine as _;
 use content_security_policy::{self as csp, CspList};
 use dom_struct::dom_struct;
-use embedder_traits::resources::{self, Resource};
+use embedder_traits::error_pages::{self, ErrorPage};
 use encoding_rs::Encoding;
 use html5ever::buffer_queue::BufferQueue;
 use html5ever::tendril::fmt::UTF8;
@@ -739,6 +739,33 @@ impl Tokenizer {
     }
 }
 
+/// Whether the response to a navigation should be downloaded instead of being loaded as a
+/// document: either it is an attachment, or `ParserContext::process_response` does not know
+/// how to display its content type.
+pub(crate) fn is_download(metadata: &Metadata) -> bool {
+    if metadata.is_attachment() {
+        return true;
+    }
+    let Some(content_type) = metadata.content_type.clone() else {
+        return false;
+    };
+    let content_type = Mime::from(content_type.into_inner());
+    match (
+        content_type.type_(),
+        content_type.subtype(),
+        content_type.suffix(),
+    ) {
+        (mime::IMAGE, _, _) |
+        (mime::TEXT, mime::PLAIN, _) |
+        (mime::TEXT, mime::HTML, _) |
+        (mime::TEXT, mime::XML, _) |
+        (mime::APPLICATION, mime::XML, _) |
+        (mime::APPLICATION, mime::JSON, _) => false,
+        (mime::APPLICATION, subtype, Some(mime::XML)) => subtype != "xhtml",
+        _ => true,
+    }
+}
+
 /// The context required for asynchronously fetching a document
 /// and parsing it progressively.
 pub struct ParserContext {
@@ -788,6 +815,7 @@ impl FetchResponseListener for ParserContext {
                 match &error {
                     NetworkError::SslValidation(..) |
                     NetworkError::Internal(..) |
+                    NetworkError::DnsResolution(..) |
                     NetworkError::Crash(..) => {
                         let mut meta = Metadata::default(self.url.clone());
                         let mime: Option<Mime> = "text/html".parse().ok();
@@ -881,7 +909,7 @@ impl FetchResponseListener for ParserContext {
             (mime::TEXT, mime::HTML, _) => match error {
                 Some(NetworkError::SslValidation(reason, bytes)) => {
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::BadCertHTML);
+                    let page = error_pages::template(ErrorPage::BadCertificate);
                     let page = page.replace("${reason}", &reason);
                     let encoded_bytes = general_purpose::STANDARD_NO_PAD.encode(bytes);
                     let page = page.replace("${bytes}", encoded_bytes.as_str());
@@ -892,14 +920,24 @@ impl FetchResponseListener for ParserContext {
                 },
                 Some(NetworkError::Internal(reason)) => {
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::NetErrorHTML);
+                    let page = error_pages::template(ErrorPage::NetworkError);
+                    let page = page.replace("${reason}", &reason);
+                    parser.push_string_input_chunk(page);
+                    parser.parse_sync();
+                },
+                Some(NetworkError::DnsResolution(reason)) => {
+                    self.is_synthesized_document = true;
+                    let page = error_pages::template(ErrorPage::DnsResolution);
+                    let host = self.url.host_str().unwrap_or_default();
+                    let reason = format!("Could not resolve host {host} ({reason})");
+                    let page = page.replace("${host}", host);
                     let page = page.replace("${reason}", &reason);
                     parser.push_string_input_chunk(page);
                     parser.parse_sync();
                 },
                 Some(NetworkError::Crash(details)) => {
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::CrashHTML);
+                    let page = error_pages::template(ErrorPage::Crash);
                     let page = page.replace("${details}", &details);
                     parser.push_string_input_chunk(page);
                     parser.parse_sync();
@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// check-tidy: no specs after this line
+
+use js::jsapi::{
+    GCOptions, GCReason, GetContextZone, NonIncrementalGC, PrepareForFullGC, PrepareZoneForGC,
+};
+use js::rust::HandleValue;
+
+use crate::dom::bindings::codegen::Bindings::ServoTestUtilsBinding::{
+    GarbageCollectionOptions, LayoutResult, MemoryReport, TestTaskSource,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::live_objects::live_object_count;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::window::Window;
+use crate::javascript_evaluation::copy_to_embedder;
+use crate::script_runtime::JSContext;
+use crate::script_thread::ScriptThread;
+use crate::task_source::TaskSourceName;
+
+pub struct ServoTestUtils(());
+
+#[allow(non_snake_case)]
+impl ServoTestUtils {
+    pub fn ForceLayout(window: &Window) -> LayoutResult {
+        let statistics = window.force_layout_with_statistics();
+        let restyle = statistics.restyle;
+        let durations = statistics.phase_durations;
+        LayoutResult {
+            elementsTraversed: restyle.elements_traversed as u32,
+            elementsRebuilt: restyle.elements_rebuilt as u32,
+            elementsRepaired: (restyle.elements_damaged - restyle.elements_rebuilt) as u32,
+            boxTreeUpdate: DOMString::from(statistics.box_tree_update.as_str()),
+            layoutSkipped: restyle.layout_skipped_for_repaint,
+            dirtyRootDamage: statistics
+                .dirty_root_damage
+                .into_iter()
+                .map(DOMString::from)
+                .collect(),
+            styleDuration: Finite::wrap(durations.style.as_secs_f64() * 1000.),
+            boxTreeDuration: Finite::wrap(durations.box_tree.as_secs_f64() * 1000.),
+            layoutDuration: Finite::wrap(durations.layout.as_secs_f64() * 1000.),
+            displayListDuration: Finite::wrap(durations.display_list.as_secs_f64() * 1000.),
+        }
+    }
+
+    pub fn ForceLayoutWithTrace(window: &Window) -> DOMString {
+        DOMString::from(window.force_layout_with_trace())
+    }
+
+    pub fn DrainTaskSource(_window: &Window, source: TestTaskSource) -> u32 {
+        let task_source = match source {
+            TestTaskSource::Dom_manipulation => TaskSourceName::DOMManipulation,
+            TestTaskSource::File_reading => TaskSourceName::FileReading,
+            TestTaskSource::Gamepad => TaskSourceName::Gamepad,
+            TestTaskSource::Idle => TaskSourceName::Idle,
+            TestTaskSource::Media_element => TaskSourceName::MediaElement,
+            TestTaskSource::Networking => TaskSourceName::Networking,
+            TestTaskSource::Performance_timeline => TaskSourceName::PerformanceTimeline,
+            TestTaskSource::Port_message => TaskSourceName::PortMessage,
+            TestTaskSource::Remote_event => TaskSourceName::RemoteEvent,
+            TestTaskSource::Timer => TaskSourceName::Timer,
+            TestTaskSource::User_interaction => TaskSourceName::UserInteraction,
+            TestTaskSource::Websocket => TaskSourceName::Websocket,
+        };
+        ScriptThread::drain_task_source_for_testing(task_source)
+    }
+
+    pub fn RunAnimationFrames(window: &Window, count: u32) {
+        let document = window.Document();
+        for _ in 0..count {
+            document.run_the_animation_frame_callbacks();
+            window
+                .upcast::<GlobalScope>()
+                .perform_a_microtask_checkpoint();
+        }
+    }
+
+    pub fn FlushMicrotasks(window: &Window) {
+        window
+            .upcast::<GlobalScope>()
+            .perform_a_microtask_checkpoint();
+    }
+
+    pub fn ReportMemory(window: &Window) -> MemoryReport {
+        let mut report = MemoryReport {
+            domNodes: window
+                .Document()
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::Yes)
+                .count() as u32,
+            jsHeap: 0,
+            jsMallocHeap: 0,
+            layout: 0,
+            imageCache: window.image_cache().decoded_images_size() as u64,
+        };
+        // The first segment of each path is the URL of the document.
+        for memory_report in window.collect_memory_reports() {
+            let path: Vec<&str> = memory_report
+                .path
+                .iter()
+                .skip(1)
+                .map(String::as_str)
+                .collect();
+            let size = memory_report.size as u64;
+            match path.as_slice() {
+                ["js", "gc-heap", "used"] => report.jsHeap += size,
+                ["js", "malloc-heap"] | ["js", "non-heap"] => report.jsMallocHeap += size,
+                ["layout-thread", ..] => report.layout += size,
+                _ => {},
+            }
+        }
+        report
+    }
+
+    #[allow(unsafe_code)]
+    pub fn CollectGarbage(window: &Window, options: &GarbageCollectionOptions) {
+        let cx = window.get_cx();
+        let gc_options = if options.shrinking {
+            GCOptions::Shrink
+        } else {
+            GCOptions::Normal
+        };
+        unsafe {
+            if options.zone {
+                // Every global has a zone of its own.
+                PrepareZoneForGC(*cx, GetContextZone(*cx));
+            } else {
+                PrepareForFullGC(*cx);
+            }
+            NonIncrementalGC(*cx, gc_options, GCReason::API);
+        }
+    }
+
+    pub fn LiveObjectCount(_window: &Window, interface_name: DOMString) -> Fallible<u32> {
+        live_object_count(&interface_name)
+            .map(|count| count as u32)
+            .ok_or_else(|| Error::Type(format!("{} is not an interface", interface_name)))
+    }
+
+    pub fn CopyForEmbedder(
+        cx: JSContext,
+        _window: &Window,
+        value: HandleValue,
+    ) -> Fallible<DOMString> {
+        let copy = copy_to_embedder(cx, value)
+            .map_err(|error| Error::Type(format!("Can't copy the value: {:?}", error)))?;
+        serde_json::to_string(&copy)
+            .map(DOMString::from)
+            .map_err(|error| Error::Type(error.to_string()))
+    }
+}
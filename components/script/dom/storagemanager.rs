@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::PermissionPrompt;
+use ipc_channel::ipc::IpcSender;
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::IpcSend;
+use profile_traits::ipc;
+
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::{
+    StorageEstimate, StorageManagerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::prompt_user_from_embedder;
+use crate::dom::promise::Promise;
+use crate::realms::{AlreadyInRealm, InRealm};
+
+// https://storage.spec.whatwg.org/#storagemanager
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<StorageManager> {
+        reflect_dom_object(Box::new(StorageManager::new_inherited()), global)
+    }
+
+    fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
+        self.global().resource_threads().sender()
+    }
+
+    /// Create the promise returned by one of the methods of this interface, rejecting it
+    /// straight away if the origin of the environment settings object is opaque.
+    fn new_promise(&self) -> (Rc<Promise>, bool) {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+        let is_opaque = !self.global().origin().is_tuple();
+        if is_opaque {
+            promise.reject_error(Error::Type(
+                "Storage is not available for opaque origins".to_owned(),
+            ));
+        }
+        (promise, !is_opaque)
+    }
+
+    /// Whether the persistent-storage permission has been granted to this global. Unlike
+    /// `get_descriptor_permission_state`, this never prompts the user.
+    fn persistence_granted(&self) -> bool {
+        self.global()
+            .permission_state_invocation_results()
+            .borrow()
+            .get(&PermissionName::Persistent_storage.to_string())
+            .map_or(false, |state| *state == PermissionState::Granted)
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persisted
+    fn Persisted(&self) -> Rc<Promise> {
+        let (promise, usable) = self.new_promise();
+        if usable {
+            promise.resolve_native(&self.persistence_granted());
+        }
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persist
+    fn Persist(&self) -> Rc<Promise> {
+        let (promise, usable) = self.new_promise();
+        if !usable {
+            return promise;
+        }
+        if self.persistence_granted() {
+            promise.resolve_native(&true);
+            return promise;
+        }
+
+        // Step 5.2. Let permission be the result of requesting permission to use
+        // "persistent-storage".
+        let global = self.global();
+        let state = prompt_user_from_embedder(
+            PermissionPrompt::Request(embedder_traits::PermissionName::PersistentStorage),
+            &global,
+        );
+        global
+            .permission_state_invocation_results()
+            .borrow_mut()
+            .insert(PermissionName::Persistent_storage.to_string(), state);
+        promise.resolve_native(&(state == PermissionState::Granted));
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-estimate
+    fn Estimate(&self) -> Rc<Promise> {
+        let (promise, usable) = self.new_promise();
+        if !usable {
+            return promise;
+        }
+
+        // There is no IndexedDB or Cache API storage yet, so usage only accounts for Web
+        // Storage, which shares a single quota between local and session storage.
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Estimate(sender, global.get_url()))
+            .unwrap();
+        let (usage, quota) = receiver.recv().unwrap();
+        promise.resolve_native(&StorageEstimate {
+            usage: Some(usage as u64),
+            quota: Some(quota as u64),
+        });
+        promise
+    }
+}
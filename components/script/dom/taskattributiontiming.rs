@@ -0,0 +1,61 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::TaskAttributionTimingBinding::TaskAttributionTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+
+/// <https://w3c.github.io/longtasks/#sec-TaskAttributionTiming>
+///
+/// Long tasks are only attributed to the window that ran them, so the container is always
+/// that window and has no source, id or name.
+#[dom_struct]
+pub struct TaskAttributionTiming {
+    entry: PerformanceEntry,
+}
+
+impl TaskAttributionTiming {
+    fn new_inherited() -> TaskAttributionTiming {
+        TaskAttributionTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("unknown"),
+                DOMString::from("taskattribution"),
+                0.,
+                0.,
+            ),
+        }
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    pub fn new(global: &GlobalScope) -> DomRoot<TaskAttributionTiming> {
+        reflect_dom_object(Box::new(TaskAttributionTiming::new_inherited()), global)
+    }
+}
+
+impl TaskAttributionTimingMethods for TaskAttributionTiming {
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containertype
+    fn ContainerType(&self) -> DOMString {
+        DOMString::from("window")
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containersrc
+    fn ContainerSrc(&self) -> DOMString {
+        DOMString::new()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containerid
+    fn ContainerId(&self) -> DOMString {
+        DOMString::new()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containername
+    fn ContainerName(&self) -> DOMString {
+        DOMString::new()
+    }
+}
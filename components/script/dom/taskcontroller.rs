@@ -0,0 +1,56 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+
+use crate::dom::abortcontroller::AbortController;
+use crate::dom::bindings::codegen::Bindings::AbortControllerBinding::AbortControllerMethods;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::TaskPriority;
+use crate::dom::bindings::codegen::Bindings::TaskControllerBinding::{
+    TaskControllerInit, TaskControllerMethods,
+};
+use crate::dom::bindings::error::ErrorResult;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::tasksignal::TaskSignal;
+use crate::dom::window::Window;
+
+/// <https://wicg.github.io/scheduling-apis/#sec-task-controller>
+#[dom_struct]
+pub struct TaskController {
+    abortcontroller: AbortController,
+}
+
+impl TaskController {
+    /// <https://wicg.github.io/scheduling-apis/#dom-taskcontroller-taskcontroller>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        init: &TaskControllerInit,
+    ) -> DomRoot<TaskController> {
+        let signal = TaskSignal::new(window, init.priority);
+        reflect_dom_object_with_proto(
+            Box::new(TaskController {
+                abortcontroller: AbortController::new_inherited(signal.upcast()),
+            }),
+            window,
+            proto,
+        )
+    }
+
+    fn signal(&self) -> DomRoot<TaskSignal> {
+        DomRoot::downcast(self.abortcontroller.Signal())
+            .expect("The signal of a TaskController is a TaskSignal")
+    }
+}
+
+impl TaskControllerMethods for TaskController {
+    /// <https://wicg.github.io/scheduling-apis/#dom-taskcontroller-setpriority>
+    fn SetPriority(&self, priority: TaskPriority) -> ErrorResult {
+        self.signal().signal_priority_change(priority)
+    }
+}
@@ -9,7 +9,7 @@ use std::ptr::{self, NonNull};
 use std::rc::Rc;
 
 use dom_struct::dom_struct;
-use js::jsapi::{Heap, JSObject, JS_NewPlainObject};
+use js::jsapi::{GetCurrentRealmOrNull, Heap, JSObject, JS_NewPlainObject};
 use js::jsval::{JSVal, NullValue};
 use js::rust::{CustomAutoRooterGuard, HandleObject, HandleValue};
 use js::typedarray::{self, Uint8ClampedArray};
@@ -39,6 +39,7 @@ use crate::dom::bindings::record::Record;
 use crate::dom::bindings::refcounted::TrustedPromise;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::settings_stack::{current_entry_realm, incumbent_realm};
 use crate::dom::bindings::str::{ByteString, DOMString, USVString};
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::weakref::MutableWeakRef;
@@ -1064,6 +1065,17 @@ impl TestBindingMethods for TestBinding {
         GlobalScope::incumbent().unwrap()
     }
 
+    #[allow(unsafe_code)]
+    fn EntryRealmIsCurrentRealm(&self) -> bool {
+        let current_realm = unsafe { GetCurrentRealmOrNull(*GlobalScope::get_cx()) };
+        current_entry_realm() == Some(current_realm)
+    }
+    #[allow(unsafe_code)]
+    fn IncumbentRealmIsCurrentRealm(&self) -> bool {
+        let current_realm = unsafe { GetCurrentRealmOrNull(*GlobalScope::get_cx()) };
+        incumbent_realm() == Some(current_realm)
+    }
+
     fn SemiExposedBoolFromInterface(&self) -> bool {
         true
     }
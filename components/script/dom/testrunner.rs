@@ -49,4 +49,20 @@ impl TestRunnerMethods for TestRunner {
             Err(error) => Err(Error::from(error)),
         }
     }
+
+    // Drops the connection of a device of the mock data set, as if it went out of range.
+    #[allow(non_snake_case)]
+    fn SimulateBluetoothDisconnection(&self, deviceId: DOMString) -> ErrorResult {
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.get_bluetooth_thread()
+            .send(BluetoothRequest::SimulateDisconnection(
+                String::from(deviceId),
+                sender,
+            ))
+            .unwrap();
+        match receiver.recv().unwrap() {
+            Ok(()) => Ok(()),
+            Err(error) => Err(Error::from(error)),
+        }
+    }
 }
@@ -7,11 +7,13 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use embedder_traits::user_script::UserScriptRunAt;
 use js::jsval::UndefinedValue;
 
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlscriptelement::SourceCode;
@@ -58,3 +60,28 @@ pub fn load_script(head: &HTMLHeadElement) {
         }
     }));
 }
+
+/// Run the user scripts of the embedder that match the URL of the document and run at the
+/// given phase of its load, in the order they were added.
+pub fn run_user_scripts(document: &Document, run_at: UserScriptRunAt) {
+    let window = document.window();
+    let url = document.url();
+    let global = window.upcast::<GlobalScope>();
+    let cx = window.get_cx();
+    for script in window
+        .user_scripts()
+        .iter()
+        .filter(|script| script.run_at == run_at && script.matches_url(&url))
+    {
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        let script_text = SourceCode::Text(Rc::new(DOMString::from(script.source.clone())));
+        global.evaluate_script_on_global_with_result(
+            &script_text,
+            "",
+            rval.handle_mut(),
+            1,
+            ScriptFetchOptions::default_classic_script(global),
+            global.api_base_url(),
+        );
+    }
+}
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::{HandleObject, HandleValue};
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::ValueEventBinding;
+use crate::dom::bindings::codegen::Bindings::ValueEventBinding::ValueEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+
+// https://webbluetoothcg.github.io/web-bluetooth/#valueevent
+#[dom_struct]
+pub struct ValueEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    value: Heap<JSVal>,
+}
+
+impl ValueEvent {
+    fn new_inherited() -> ValueEvent {
+        ValueEvent {
+            event: Event::new_inherited(),
+            value: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: EventBubbles,
+        cancelable: EventCancelable,
+        value: HandleValue,
+    ) -> DomRoot<ValueEvent> {
+        let ev =
+            reflect_dom_object_with_proto(Box::new(ValueEvent::new_inherited()), window, proto);
+        ev.value.set(value.get());
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        }
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: RootedTraceableBox<ValueEventBinding::ValueEventInit>,
+    ) -> Fallible<DomRoot<ValueEvent>> {
+        Ok(ValueEvent::new(
+            window,
+            proto,
+            Atom::from(type_),
+            EventBubbles::from(init.parent.bubbles),
+            EventCancelable::from(init.parent.cancelable),
+            init.value.handle(),
+        ))
+    }
+}
+
+impl ValueEventMethods for ValueEvent {
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-valueevent-value
+    fn Value(&self, _cx: JSContext) -> JSVal {
+        self.value.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
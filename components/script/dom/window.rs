@@ -5,34 +5,47 @@
 use std::borrow::{Cow, ToOwned};
 use std::cell::{Cell, RefCell, RefMut};
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
+use std::ffi::c_void;
 use std::io::{stderr, stdout, Write};
 use std::ptr::NonNull;
 use std::rc::Rc;
+use std::slice;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{cmp, env, mem};
 
 use app_units::Au;
 use backtrace::Backtrace;
-use base::id::{BrowsingContextId, PipelineId};
+use base::id::{BrowsingContextId, NotificationId, PipelineId, TopLevelBrowsingContextId};
 use base64::Engine;
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLChan;
 use crossbeam_channel::{unbounded, Sender, TryRecvError};
 use cssparser::{Parser, ParserInput, SourceLocation};
-use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType};
+use devtools_traits::{
+    DamagedNodeInfo, LayoutInvalidationInfo, ScriptToDevtoolsControlMsg, TimelineMarker,
+    TimelineMarkerType,
+};
 use dom_struct::dom_struct;
-use embedder_traits::{EmbedderMsg, PromptDefinition, PromptOrigin, PromptResult};
+use embedder_traits::user_script::UserScript;
+use embedder_traits::{
+    AutoplayPolicy, EmbedderMsg, MediaPolicy, NotificationEvent, PromptDefinition, PromptOrigin,
+    PromptResult, ScriptMessage,
+};
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
-use euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
+use euclid::{Length, Point2D, Rect, Scale, Size2D, Vector2D};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::conversions::ToJSValConvertible;
-use js::jsapi::{GCReason, Heap, JSAutoRealm, JSObject, StackFormat, JSPROP_ENUMERATE, JS_GC};
+use js::jsapi::{
+    GCReason, Heap, JSAutoRealm, JSObject, JS_ClearPendingException, StackFormat, JSPROP_ENUMERATE,
+    JS_GC,
+};
 use js::jsval::{JSVal, NullValue, UndefinedValue};
-use js::rust::wrappers::JS_DefineProperty;
+use js::rust::wrappers::{JS_DefineProperty, JS_GetPendingException, JS_ParseJSON, JS_Stringify};
 use js::rust::{
     CustomAutoRooter, CustomAutoRooterGuard, HandleObject, HandleValue, MutableHandleObject,
 };
@@ -46,17 +59,18 @@ use net_traits::ResourceThreads;
 use num_traits::ToPrimitive;
 use parking_lot::Mutex as ParkMutex;
 use profile_traits::ipc as ProfiledIpc;
-use profile_traits::mem::ProfilerChan as MemProfilerChan;
+use profile_traits::mem::{ProfilerChan as MemProfilerChan, Report};
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
 use script_layout_interface::{
-    combine_id_with_fragment_type, FragmentType, Layout, PendingImageState, QueryMsg, Reflow,
-    ReflowGoal, ScriptReflow, TrustedNodeAddress,
+    combine_id_with_fragment_type, BoxTreeUpdate, FragmentType, InvalidationTrace, Layout,
+    PendingImageState, PendingRestyle, QueryMsg, Reflow, ReflowGoal, ReflowPhaseDurations,
+    RestyleStatistics, ScriptReflow, TrustedNodeAddress,
 };
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use script_traits::{
-    ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData, ScriptMsg,
-    ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
-    TimerSchedulerMsg, WindowSizeData, WindowSizeType,
+    ConstellationControlMsg, ContainerPolicy, DocumentState, HistoryEntryReplacement, LoadData,
+    ScriptMsg, ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
+    TimerSchedulerMsg, UntrustedNodeAddress, WindowSizeData, WindowSizeType,
 };
 use selectors::attr::CaseSensitivity;
 use servo_arc::Arc as ServoArc;
@@ -65,6 +79,7 @@ use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
+use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
 use style::properties::style_structs::Font;
@@ -79,6 +94,8 @@ use webrender_api::{DocumentId, ExternalScrollId};
 use webrender_traits::WebRenderScriptApi;
 
 use super::bindings::trace::HashMapTracedValues;
+use crate::dom::audiocontext::AudioContext;
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
@@ -92,14 +109,14 @@ use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryLi
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
-    self, FrameRequestCallback, ScrollBehavior, ScrollToOptions, WindowMethods,
-    WindowPostMessageOptions,
+    self, FrameRequestCallback, IdleRequestCallback, IdleRequestOptions, ScrollBehavior,
+    ScrollToOptions, WindowMethods, WindowPostMessageOptions,
 };
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
@@ -107,7 +124,7 @@ use crate::dom::bindings::structuredclone;
 use crate::dom::bindings::trace::{JSTraceable, RootedTraceableBox};
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
-use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::bluetooth::{Bluetooth, BluetoothExtraPermissionData};
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
@@ -120,15 +137,19 @@ use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::identityhub::Identities;
+use crate::dom::idledeadline::IdleDeadline;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
+use crate::dom::notification::Notification;
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
 use crate::dom::storage::Storage;
@@ -139,9 +160,10 @@ use crate::dom::worklet::Worklet;
 use crate::dom::workletglobalscope::WorkletGlobalScopeType;
 use crate::layout_image::fetch_image_for_layout;
 use crate::microtask::MicrotaskQueue;
-use crate::realms::InRealm;
+use crate::realms::{enter_realm, InRealm};
 use crate::script_runtime::{
-    CommonScriptMsg, JSContext, Runtime, ScriptChan, ScriptPort, ScriptThreadEventCategory,
+    get_reports, CommonScriptMsg, JSContext, Runtime, ScriptChan, ScriptPort,
+    ScriptThreadEventCategory,
 };
 use crate::script_thread::{
     ImageCacheMsg, MainThreadScriptChan, MainThreadScriptMsg, ScriptThread,
@@ -149,10 +171,24 @@ use crate::script_thread::{
 };
 use crate::task_manager::TaskManager;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::timers::{IsInterval, TimerCallback};
+use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle, TimerCallback};
 use crate::webdriver_handlers::jsval_to_webdriver;
 use crate::{fetch, window_named_properties};
 
+/// How long a user gesture lets the page use the APIs that need transient activation.
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>
+const TRANSIENT_ACTIVATION_DURATION: Duration = Duration::from_secs(5);
+
+/// A callback passed to `requestIdleCallback()` that did not run yet.
+#[derive(JSTraceable, MallocSizeOf)]
+struct IdleRequest {
+    handle: u32,
+    #[ignore_malloc_size_of = "Rc are hard"]
+    callback: Rc<IdleRequestCallback>,
+    /// The timer that runs the callback once its timeout elapsed, if it was given one.
+    timeout_handle: Option<OneshotTimerHandle>,
+}
+
 /// Current state of the window object
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 enum WindowState {
@@ -184,6 +220,92 @@ pub enum ReflowReason {
     Viewport,
     WindowResize,
     WorkletLoaded,
+    ForceLayout,
+}
+
+/// Why the elements damaged by a reflow were restyled, as reported to devtools.
+struct RestyleCauses {
+    /// The causes of the elements that had a pending restyle, keyed by their address.
+    pending: HashMap<usize, Vec<&'static str>>,
+    web_font_loaded: bool,
+    stylesheets_changed: bool,
+}
+
+impl RestyleCauses {
+    fn new(
+        pending_restyles: &[(TrustedNodeAddress, PendingRestyle)],
+        damaged_before_web_font_load: Option<HashSet<usize>>,
+        stylesheets_changed: bool,
+    ) -> Self {
+        let pending = pending_restyles
+            .iter()
+            .map(|(node, restyle)| {
+                let mut causes = vec![];
+                if let Some(snapshot) = &restyle.snapshot {
+                    if snapshot.state.is_some() {
+                        causes.push("state-change");
+                    }
+                    if snapshot.attrs.is_some() {
+                        causes.push("attribute-change");
+                    }
+                }
+                if restyle.hint.contains(RestyleHint::RESTYLE_STYLE_ATTRIBUTE) {
+                    causes.push("style-attribute");
+                }
+                // Web font loads force the damage of every element, so only the damage forced
+                // before one is known to come from DOM mutations.
+                let mutated = damaged_before_web_font_load
+                    .as_ref()
+                    .map_or(true, |damaged| damaged.contains(&(node.0 as usize)));
+                if !restyle.damage.is_empty() && mutated {
+                    causes.push("dom-mutation");
+                }
+                if causes.is_empty() {
+                    // An explicit restyle, e.g. for an animation or for a selector that may
+                    // have started or stopped matching.
+                    causes.push("restyle");
+                }
+                (node.0 as usize, causes)
+            })
+            .collect();
+        RestyleCauses {
+            pending,
+            web_font_loaded: damaged_before_web_font_load.is_some(),
+            stylesheets_changed,
+        }
+    }
+
+    /// The causes of the restyle of the given element. An element without a pending restyle
+    /// was restyled because of a change to its ancestors or earlier siblings.
+    fn of(&self, node: UntrustedNodeAddress) -> Vec<String> {
+        let mut causes: Vec<String> = self
+            .pending
+            .get(&(node.0 as usize))
+            .into_iter()
+            .flatten()
+            .map(|cause| cause.to_string())
+            .collect();
+        if self.web_font_loaded {
+            causes.push("font-load".into());
+        }
+        if self.stylesheets_changed {
+            causes.push("stylesheet-change".into());
+        }
+        if causes.is_empty() {
+            causes.push("inherited".into());
+        }
+        causes
+    }
+}
+
+/// A summary of the work done by a reflow, kept for `ServoTestUtils.forceLayout()`.
+#[derive(Default)]
+pub(crate) struct ReflowStatistics {
+    pub(crate) restyle: RestyleStatistics,
+    pub(crate) box_tree_update: BoxTreeUpdate,
+    /// The layout damage of the element from which the style traversal started.
+    pub(crate) dirty_root_damage: Vec<&'static str>,
+    pub(crate) phase_durations: ReflowPhaseDurations,
 }
 
 #[dom_struct]
@@ -213,8 +335,19 @@ pub struct Window {
     screen: MutNullableDom<Screen>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
+    scheduler: MutNullableDom<Scheduler>,
     status: DomRefCell<DOMString>,
 
+    /// <https://w3c.github.io/requestidlecallback/#dfn-idle-callback-identifier>
+    idle_callback_identifier: Cell<u32>,
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-idle-request-callbacks>
+    idle_request_callbacks: DomRefCell<VecDeque<IdleRequest>>,
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-runnable-idle-callbacks>
+    runnable_idle_callbacks: DomRefCell<VecDeque<IdleRequest>>,
+    /// Whether a task to invoke the runnable idle callbacks is queued, so that the event loop
+    /// does not start another idle period for this window before it ran.
+    idle_period_in_progress: Cell<bool>,
+
     /// For sending timeline markers. Will be ignored if
     /// no devtools server
     #[no_trace]
@@ -282,6 +415,14 @@ pub struct Window {
     /// All the MediaQueryLists we need to update
     media_query_lists: DOMTracker<MediaQueryList>,
 
+    /// The media elements and audio contexts of this window, which the media policy of the
+    /// webview applies to.
+    media_elements: DOMTracker<HTMLMediaElement>,
+    audio_contexts: DOMTracker<AudioContext>,
+
+    /// Whether any of the media of this window was audible when the constellation was last told.
+    audible: Cell<bool>,
+
     test_runner: MutNullableDom<TestRunner>,
 
     /// A handle for communicating messages to the WebGL thread, if available.
@@ -299,6 +440,16 @@ pub struct Window {
     /// available at some point in the future.
     pending_layout_images: DomRefCell<HashMapTracedValues<PendingImageId, Vec<Dom<Node>>>>,
 
+    /// The notifications of this window that the embedder was asked to show, which are kept
+    /// alive until they are closed so that their events can be fired.
+    /// <https://notifications.spec.whatwg.org/#list-of-notifications>
+    notifications: DomRefCell<HashMapTracedValues<NotificationId, Dom<Notification>>>,
+
+    /// When the user last interacted with this window, for the APIs that need a user gesture.
+    /// <https://html.spec.whatwg.org/multipage/#last-activation-timestamp>
+    #[ignore_malloc_size_of = "Defined in std::time"]
+    last_activation_timestamp: Cell<Option<Instant>>,
+
     /// Directory to store unminified scripts for this window if unminify-js
     /// opt is enabled.
     unminified_js_dir: DomRefCell<Option<String>>,
@@ -330,6 +481,21 @@ pub struct Window {
     /// Emits notifications when there is a relayout.
     relayout_event: bool,
 
+    /// Whether reflows should keep a summary of their work in `reflow_statistics`.
+    collect_reflow_statistics: Cell<bool>,
+
+    /// The summary of the work done by the last reflow that collected one.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Only used by tests"]
+    reflow_statistics: RefCell<Option<ReflowStatistics>>,
+
+    /// Whether reflows should keep the state of layout after them in `layout_dump`.
+    collect_layout_dump: Cell<bool>,
+
+    /// The box tree, fragment tree and damaged elements after the last reflow that collected
+    /// them, serialized as JSON.
+    layout_dump: DomRefCell<Option<String>>,
+
     /// True if it is safe to write to the image.
     prepare_for_screenshot: bool,
 
@@ -341,6 +507,14 @@ pub struct Window {
     /// won't be loaded.
     userscripts_path: Option<String>,
 
+    /// The user scripts of the embedder that are injected into the documents they match.
+    #[no_trace]
+    user_scripts: Vec<UserScript>,
+
+    /// The container policy of the iframe this window is loaded in, if any.
+    #[no_trace]
+    container_policy: Option<ContainerPolicy>,
+
     /// Replace unpaired surrogates in DOM strings with U+FFFD.
     /// See <https://github.com/servo/servo/issues/6564>
     replace_surrogates: bool,
@@ -375,6 +549,16 @@ impl Window {
         self.layout.borrow_mut()
     }
 
+    /// The memory reports of the JS runtime and of the layout of this window, as the script
+    /// thread sends them to the memory profiler.
+    #[allow(unsafe_code)]
+    pub fn collect_memory_reports(&self) -> Vec<Report> {
+        let path_seg = format!("url({})", self.get_url());
+        let mut reports = unsafe { get_reports(*self.get_cx(), path_seg) };
+        self.layout().collect_reports(&mut reports);
+        reports
+    }
+
     pub fn get_exists_mut_observer(&self) -> bool {
         self.exists_mut_observer.get()
     }
@@ -536,10 +720,52 @@ impl Window {
         self.webrender_api_sender.clone()
     }
 
+    /// Serialize a value with the engine's own `JSON.stringify()`, which the page can't replace,
+    /// returning `None` if that throws. Values without a JSON representation, like `undefined`,
+    /// become `null`.
+    #[allow(unsafe_code)]
+    unsafe fn stringify_as_json(&self, cx: JSContext, value: HandleValue) -> Option<String> {
+        unsafe extern "C" fn write_callback(
+            string: *const u16,
+            len: u32,
+            data: *mut c_void,
+        ) -> bool {
+            let json = &mut *(data as *mut Vec<u16>);
+            json.extend_from_slice(slice::from_raw_parts(string, len as usize));
+            true
+        }
+
+        let mut json: Vec<u16> = vec![];
+        rooted!(in(*cx) let mut value = value.get());
+        if !JS_Stringify(
+            *cx,
+            value.handle_mut(),
+            HandleObject::null(),
+            HandleValue::null(),
+            Some(write_callback),
+            &mut json as *mut Vec<u16> as *mut c_void,
+        ) {
+            JS_ClearPendingException(*cx);
+            return None;
+        }
+        if json.is_empty() {
+            return Some("null".to_owned());
+        }
+        Some(String::from_utf16_lossy(&json))
+    }
+
     pub fn get_userscripts_path(&self) -> Option<String> {
         self.userscripts_path.clone()
     }
 
+    pub fn user_scripts(&self) -> &[UserScript] {
+        &self.user_scripts
+    }
+
+    pub fn container_policy(&self) -> Option<&ContainerPolicy> {
+        self.container_policy.as_ref()
+    }
+
     pub fn replace_surrogates(&self) -> bool {
         self.replace_surrogates
     }
@@ -561,6 +787,21 @@ impl Window {
     }
 }
 
+/// Whether the given autoplay policy lets media that would play muted or not start playing, in a
+/// window that has transient activation or not. Every start is checked on its own, so an earlier
+/// activation does not let later media start.
+pub fn autoplay_policy_allows(
+    autoplay: AutoplayPolicy,
+    muted: bool,
+    has_transient_activation: bool,
+) -> bool {
+    match autoplay {
+        AutoplayPolicy::Allowed => true,
+        AutoplayPolicy::AllowedMuted => muted || has_transient_activation,
+        AutoplayPolicy::Blocked => has_transient_activation,
+    }
+}
+
 // https://html.spec.whatwg.org/multipage/#atob
 pub fn base64_btoa(input: DOMString) -> Fallible<DOMString> {
     // "The btoa() method must throw an InvalidCharacterError exception if
@@ -686,9 +927,17 @@ impl WindowMethods for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-window-stop
     fn Stop(&self) {
-        // TODO: Cancel ongoing navigation.
+        // https://html.spec.whatwg.org/multipage/#nav-stop
+        // Step 2. Cancel the ongoing navigation, if any.
+        self.send_to_constellation(ScriptMsg::CancelOngoingNavigation);
+
+        // Step 3.
         let doc = self.Document();
         doc.abort();
+
+        // The cancelled loads may never complete this document, so let the embedder know
+        // that it is no longer loading.
+        doc.send_load_complete_to_embedder();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-open
@@ -1026,6 +1275,55 @@ impl WindowMethods for Window {
         doc.cancel_animation_frame(ident);
     }
 
+    /// <https://w3c.github.io/requestidlecallback/#the-requestidlecallback-method>
+    fn RequestIdleCallback(
+        &self,
+        callback: Rc<IdleRequestCallback>,
+        options: &IdleRequestOptions,
+    ) -> u32 {
+        // Step 2-3. Increment the idle callback identifier and use it as the handle.
+        let handle = self.idle_callback_identifier.get() + 1;
+        self.idle_callback_identifier.set(handle);
+
+        // Step 5. If the timeout property is present and has a value greater than zero, start a
+        // timer that invokes the idle callback timeout algorithm once it elapsed.
+        let timeout_handle = options
+            .timeout
+            .filter(|timeout| *timeout > 0)
+            .map(|timeout| {
+                let callback = IdleCallbackTimeoutCallback {
+                    window: Trusted::new(self),
+                    handle,
+                };
+                self.upcast::<GlobalScope>().schedule_callback(
+                    OneshotTimerCallback::IdleCallbackTimeout(callback),
+                    Length::new(timeout as u64),
+                )
+            });
+
+        // Step 4. Append callback to the list of idle request callbacks.
+        self.idle_request_callbacks
+            .borrow_mut()
+            .push_back(IdleRequest {
+                handle,
+                callback,
+                timeout_handle,
+            });
+
+        // Step 6. Return handle.
+        handle
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#the-cancelidlecallback-method>
+    fn CancelIdleCallback(&self, handle: u32) {
+        if let Some(request) = self.take_idle_request(handle) {
+            if let Some(timeout_handle) = request.timeout_handle {
+                self.upcast::<GlobalScope>()
+                    .unschedule_callback(timeout_handle);
+            }
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-postmessage
     fn PostMessage(
         &self,
@@ -1125,6 +1423,55 @@ impl WindowMethods for Window {
         }
     }
 
+    #[allow(unsafe_code)]
+    fn PostMessageToEmbedder(
+        &self,
+        cx: JSContext,
+        handler_name: DOMString,
+        message: HandleValue,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(self.upcast());
+        let body = match unsafe { self.stringify_as_json(cx, message) } {
+            Some(body) => body,
+            None => {
+                promise.reject_error(Error::DataClone);
+                return promise;
+            },
+        };
+
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        let (task_source, canceller) = self
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let Some(trusted_promise) = trusted_promise.take() else {
+                    return;
+                };
+                let reply = message
+                    .to::<Result<String, String>>()
+                    .unwrap_or_else(|error| Err(format!("{error:?}")));
+                let _ = task_source.queue_with_canceller(
+                    task!(settle_embedder_reply: move || {
+                        settle_with_embedder_reply(&trusted_promise.root(), reply);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        self.send_to_embedder(EmbedderMsg::ScriptMessage(
+            ScriptMessage {
+                handler_name: handler_name.into(),
+                body,
+                origin: self.globalscope.origin().immutable().clone(),
+            },
+            sender,
+        ));
+        promise
+    }
+
     // https://drafts.csswg.org/cssom/#dom-window-getcomputedstyle
     fn GetComputedStyle(
         &self,
@@ -1377,6 +1724,11 @@ impl WindowMethods for Window {
         USVString(self.origin().immutable().ascii_serialization())
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.scheduler.or_init(|| Scheduler::new(self))
+    }
+
     // https://w3c.github.io/selection-api/#dom-window-getselection
     fn GetSelection(&self) -> Option<DomRoot<Selection>> {
         self.document.get().and_then(|d| d.GetSelection())
@@ -1652,6 +2004,11 @@ impl Window {
         self.upcast::<GlobalScope>()
             .remove_web_messaging_and_dedicated_workers_infra();
 
+        // Stop whatever Web Bluetooth watches for this window.
+        if let Some(bluetooth) = self.bluetooth() {
+            bluetooth.stop_watching();
+        }
+
         // Clean up any active promises
         // https://github.com/servo/servo/issues/15318
         if let Some(custom_elements) = self.custom_element_registry.get() {
@@ -1861,6 +2218,17 @@ impl Window {
         }
 
         let pending_restyles = document.drain_pending_restyles();
+        let damaged_before_web_font_load = document.take_damaged_before_web_font_load();
+        let restyle_causes = self
+            .upcast::<GlobalScope>()
+            .live_devtools_updates()
+            .then(|| {
+                RestyleCauses::new(
+                    &pending_restyles,
+                    damaged_before_web_font_load,
+                    stylesheets_changed,
+                )
+            });
 
         let dirty_root = document
             .take_dirty_root()
@@ -1885,11 +2253,15 @@ impl Window {
             pending_restyles,
             animation_timeline_value: document.current_animation_timeline_value(),
             animations: document.animations().sets.clone(),
+            trace_invalidations: self.upcast::<GlobalScope>().live_devtools_updates() ||
+                self.collect_reflow_statistics.get() ||
+                self.collect_layout_dump.get(),
+            dump_layout: self.collect_layout_dump.get(),
         };
 
         self.layout.borrow_mut().reflow(reflow);
 
-        let complete = match join_port.try_recv() {
+        let mut complete = match join_port.try_recv() {
             Err(TryRecvError::Empty) => {
                 debug!("script: waiting on layout");
                 join_port.recv().unwrap()
@@ -1912,6 +2284,37 @@ impl Window {
             self.emit_timeline_marker(marker.end());
         }
 
+        if self.collect_reflow_statistics.get() {
+            let trace = complete.invalidation_trace.as_ref();
+            let dirty_root_damage = trace.and_then(|trace| {
+                trace
+                    .damaged_nodes
+                    .iter()
+                    .find(|damaged_node| Some(damaged_node.node) == trace.dirty_root)
+            });
+            *self.reflow_statistics.borrow_mut() = Some(ReflowStatistics {
+                restyle: complete.restyle_statistics,
+                box_tree_update: trace.map(|trace| trace.box_tree_update).unwrap_or_default(),
+                dirty_root_damage: dirty_root_damage
+                    .map(|damaged_node| damaged_node.damage.clone())
+                    .unwrap_or_default(),
+                phase_durations: complete.phase_durations,
+            });
+        }
+
+        if self.collect_layout_dump.get() {
+            *self.layout_dump.borrow_mut() = complete.layout_dump.take();
+        }
+
+        if let (Some(trace), Some(restyle_causes)) = (complete.invalidation_trace, restyle_causes) {
+            self.report_invalidation_trace(
+                trace,
+                &complete.restyle_statistics,
+                &reason,
+                &restyle_causes,
+            );
+        }
+
         for image in complete.pending_images {
             let id = image.id;
             let node = unsafe { from_untrusted_node_address(image.node) };
@@ -2058,6 +2461,28 @@ impl Window {
         receiver.recv().unwrap();
     }
 
+    /// Reflow for display, even if nothing is dirty, and return a summary of the work that
+    /// layout did.
+    pub(crate) fn force_layout_with_statistics(&self) -> ReflowStatistics {
+        self.collect_reflow_statistics.set(true);
+        self.force_reflow(ReflowGoal::Full, ReflowReason::ForceLayout, None);
+        self.collect_reflow_statistics.set(false);
+        self.reflow_statistics.take().unwrap_or_default()
+    }
+
+    /// Reflow for display, even if nothing is dirty, and return the box tree, fragment tree and
+    /// damaged elements of layout afterwards, serialized as JSON. This is `null` if the reflow
+    /// was suppressed.
+    pub(crate) fn force_layout_with_trace(&self) -> String {
+        self.collect_layout_dump.set(true);
+        self.force_reflow(ReflowGoal::Full, ReflowReason::ForceLayout, None);
+        self.collect_layout_dump.set(false);
+        self.layout_dump
+            .borrow_mut()
+            .take()
+            .unwrap_or_else(|| String::from("null"))
+    }
+
     pub fn layout_reflow(&self, query_msg: QueryMsg) -> bool {
         self.reflow(
             ReflowGoal::LayoutQuery(query_msg, time::precise_time_ns()),
@@ -2094,6 +2519,13 @@ impl Window {
         self.layout.borrow().query_content_boxes(node.to_opaque())
     }
 
+    /// The content boxes of the given node in the fragment tree of the last layout, for callers
+    /// that already brought layout up to date with a [`QueryMsg::ContentBoxes`] reflow and query
+    /// many nodes at once.
+    pub(crate) fn content_boxes_query_without_reflow(&self, node: &Node) -> Vec<UntypedRect<Au>> {
+        self.layout.borrow().query_content_boxes(node.to_opaque())
+    }
+
     pub fn client_rect_query(&self, node: &Node) -> UntypedRect<i32> {
         if !self.layout_reflow(QueryMsg::ClientRectQuery) {
             return Rect::zero();
@@ -2403,6 +2835,42 @@ impl Window {
         markers.contains(&timeline_type)
     }
 
+    /// Send the style invalidations found by a reflow to devtools.
+    fn report_invalidation_trace(
+        &self,
+        trace: InvalidationTrace,
+        statistics: &RestyleStatistics,
+        reason: &ReflowReason,
+        restyle_causes: &RestyleCauses,
+    ) {
+        let global = self.upcast::<GlobalScope>();
+        let Some(chan) = global.running_devtools_chan() else {
+            return;
+        };
+        let summarize = |address| unsafe { from_untrusted_node_address(address) }.summarize();
+        let info = LayoutInvalidationInfo {
+            dirty_root: trace.dirty_root.map(summarize),
+            damaged_nodes: trace
+                .damaged_nodes
+                .into_iter()
+                .map(|damaged_node| DamagedNodeInfo {
+                    causes: restyle_causes.of(damaged_node.node),
+                    node: summarize(damaged_node.node),
+                    damage: damaged_node.damage.into_iter().map(Into::into).collect(),
+                    depth: damaged_node.depth,
+                })
+                .collect(),
+            elements_traversed: statistics.elements_traversed,
+            box_tree_update: trace.box_tree_update.as_str().into(),
+            layout_skipped: statistics.layout_skipped_for_repaint,
+            reflow_reason: format!("{reason:?}"),
+        };
+        let _ = chan.send(ScriptToDevtoolsControlMsg::LayoutInvalidated(
+            global.pipeline_id(),
+            info,
+        ));
+    }
+
     pub fn emit_timeline_marker(&self, marker: TimelineMarker) {
         let sender = self.devtools_marker_sender.borrow();
         let sender = sender.as_ref().expect("There is no marker sender");
@@ -2428,6 +2896,19 @@ impl Window {
         }
     }
 
+    /// Switch the media type used to evaluate media queries between `screen` and `print`, as
+    /// requested by the devtools print simulation, and restyle the document accordingly.
+    pub fn set_print_media_simulation(&self, enabled: bool) {
+        let media_type = if enabled {
+            media_queries::MediaType::print()
+        } else {
+            media_queries::MediaType::screen()
+        };
+        self.layout_mut().set_media_type(media_type);
+        self.Document().invalidate_stylesheets();
+        self.reflow(ReflowGoal::Full, ReflowReason::Viewport);
+    }
+
     pub fn set_webdriver_script_chan(&self, chan: Option<IpcSender<WebDriverJSResult>>) {
         *self.webdriver_script_chan.borrow_mut() = chan;
     }
@@ -2479,6 +2960,58 @@ impl Window {
         self.throttled.get()
     }
 
+    /// The webview this window is in, unless its browsing context has been discarded.
+    pub fn webview_id(&self) -> Option<TopLevelBrowsingContextId> {
+        self.undiscarded_window_proxy()
+            .map(|window_proxy| window_proxy.top_level_browsing_context_id())
+    }
+
+    /// How the embedder wants the media of the webview of this window to be played.
+    pub fn media_policy(&self) -> MediaPolicy {
+        self.webview_id()
+            .map(ScriptThread::media_policy)
+            .unwrap_or_default()
+    }
+
+    /// Whether media that would play muted or not, as given, may start playing now.
+    pub fn is_allowed_to_start_media(&self, muted: bool) -> bool {
+        autoplay_policy_allows(
+            self.media_policy().autoplay,
+            muted,
+            self.has_transient_activation(),
+        )
+    }
+
+    pub fn track_media_element(&self, element: &HTMLMediaElement) {
+        self.media_elements.track(element);
+    }
+
+    pub fn track_audio_context(&self, context: &AudioContext) {
+        self.audio_contexts.track(context);
+    }
+
+    /// Apply a new media policy of the webview to the media of this window.
+    pub fn apply_media_policy(&self) {
+        self.media_elements
+            .for_each(|element| element.update_player_mute());
+        self.audio_contexts
+            .for_each(|context| context.base().apply_media_policy());
+        self.update_audible();
+    }
+
+    /// Tell the constellation when this window starts or stops playing audio that the user can
+    /// hear.
+    pub fn update_audible(&self) {
+        let mut audible = false;
+        self.media_elements
+            .for_each(|element| audible |= element.is_audible());
+        self.audio_contexts
+            .for_each(|context| audible |= context.base().is_audible());
+        if audible != self.audible.replace(audible) {
+            self.send_to_constellation(ScriptMsg::AudibleChanged(audible));
+        }
+    }
+
     pub fn unminified_js_dir(&self) -> Option<String> {
         self.unminified_js_dir.borrow().clone()
     }
@@ -2505,10 +3038,52 @@ impl Window {
             .unwrap();
     }
 
+    /// Record that the user interacted with the window, on an activation-triggering input
+    /// event.
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    pub fn notify_activation(&self) {
+        self.last_activation_timestamp.set(Some(Instant::now()));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    pub fn has_transient_activation(&self) -> bool {
+        self.last_activation_timestamp
+            .get()
+            .is_some_and(|timestamp| timestamp.elapsed() < TRANSIENT_ACTIVATION_DURATION)
+    }
+
+    pub fn register_notification(&self, notification: &Notification) {
+        self.notifications
+            .borrow_mut()
+            .insert(notification.id(), Dom::from_ref(notification));
+    }
+
+    pub fn unregister_notification(&self, id: NotificationId) {
+        self.notifications.borrow_mut().remove(&id);
+    }
+
+    pub fn handle_notification_event(&self, id: NotificationId, event: NotificationEvent) {
+        let notification = self
+            .notifications
+            .borrow()
+            .get(&id)
+            .map(|notification| DomRoot::from_ref(&**notification));
+        if let Some(notification) = notification {
+            notification.handle_event(event);
+        }
+    }
+
     pub fn webrender_document(&self) -> DocumentId {
         self.webrender_document
     }
 
+    /// The `navigator.bluetooth` of this window, if script ever used it.
+    pub fn bluetooth(&self) -> Option<DomRoot<Bluetooth>> {
+        self.navigator
+            .get()
+            .and_then(|navigator| navigator.bluetooth())
+    }
+
     pub fn in_immersive_xr_session(&self) -> bool {
         self.navigator
             .get()
@@ -2553,6 +3128,8 @@ impl Window {
         unminify_js: bool,
         local_script_source: Option<String>,
         userscripts_path: Option<String>,
+        user_scripts: Vec<UserScript>,
+        container_policy: Option<ContainerPolicy>,
         is_headless: bool,
         replace_surrogates: bool,
         user_agent: Cow<'static, str>,
@@ -2604,7 +3181,12 @@ impl Window {
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
+            scheduler: Default::default(),
             status: DomRefCell::new(DOMString::new()),
+            idle_callback_identifier: Cell::new(0),
+            idle_request_callbacks: Default::default(),
+            runnable_idle_callbacks: Default::default(),
+            idle_period_in_progress: Cell::new(false),
             parent_info,
             dom_static: GlobalStaticData::new(),
             js_runtime: DomRefCell::new(Some(runtime.clone())),
@@ -2623,10 +3205,15 @@ impl Window {
             error_reporter,
             scroll_offsets: Default::default(),
             media_query_lists: DOMTracker::new(),
+            media_elements: DOMTracker::new(),
+            audio_contexts: DOMTracker::new(),
+            audible: Cell::new(false),
             test_runner: Default::default(),
             webgl_chan,
             webxr_registry,
             pending_layout_images: Default::default(),
+            notifications: Default::default(),
+            last_activation_timestamp: Cell::new(None),
             unminified_js_dir: Default::default(),
             local_script_source,
             test_worklet: Default::default(),
@@ -2636,9 +3223,15 @@ impl Window {
             webrender_api_sender,
             has_sent_idle_message: Cell::new(false),
             relayout_event,
+            collect_reflow_statistics: Default::default(),
+            reflow_statistics: Default::default(),
+            collect_layout_dump: Default::default(),
+            layout_dump: Default::default(),
             prepare_for_screenshot,
             unminify_js,
             userscripts_path,
+            user_scripts,
+            container_policy,
             replace_surrogates,
             player_context,
             throttled: Cell::new(false),
@@ -2745,6 +3338,122 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
     println!("**** pipeline={}\t{}\t{:?}", id, goal_string, reason);
 }
 
+impl Window {
+    pub(crate) fn has_idle_callbacks(&self) -> bool {
+        !self.idle_request_callbacks.borrow().is_empty() ||
+            !self.runnable_idle_callbacks.borrow().is_empty()
+    }
+
+    /// Remove the callback with the given handle from whichever list of idle callbacks holds it.
+    fn take_idle_request(&self, handle: u32) -> Option<IdleRequest> {
+        for list in [&self.idle_request_callbacks, &self.runnable_idle_callbacks] {
+            let mut list = list.borrow_mut();
+            if let Some(index) = list.iter().position(|request| request.handle == handle) {
+                return list.remove(index);
+            }
+        }
+        None
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    ///
+    /// The `deadline` is given in `time::precise_time_ns()` units.
+    pub(crate) fn start_an_idle_period(&self, deadline: u64) {
+        if self.idle_period_in_progress.get() || !self.has_idle_callbacks() {
+            return;
+        }
+
+        // Step 1-4. Move the pending callbacks to the end of the runnable ones, so that callbacks
+        // requested during this idle period only run in the next one.
+        let mut pending = mem::take(&mut *self.idle_request_callbacks.borrow_mut());
+        self.runnable_idle_callbacks
+            .borrow_mut()
+            .append(&mut pending);
+
+        // Step 5. Queue a task to invoke the idle callbacks.
+        self.idle_period_in_progress.set(true);
+        self.queue_invoke_idle_callbacks(deadline);
+    }
+
+    fn queue_invoke_idle_callbacks(&self, deadline: u64) {
+        let this = Trusted::new(self);
+        let _ = self.task_manager.idle_task_source().queue(
+            task!(invoke_idle_callbacks: move || {
+                this.root().invoke_idle_callbacks(deadline);
+            }),
+            self.upcast(),
+        );
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callbacks-algorithm>
+    fn invoke_idle_callbacks(&self, deadline: u64) {
+        // Step 2-3. If the deadline passed, the remaining callbacks wait for the next idle period.
+        if time::precise_time_ns() >= deadline {
+            self.idle_period_in_progress.set(false);
+            return;
+        }
+        let Some(request) = self.runnable_idle_callbacks.borrow_mut().pop_front() else {
+            self.idle_period_in_progress.set(false);
+            return;
+        };
+        if let Some(timeout_handle) = request.timeout_handle {
+            self.upcast::<GlobalScope>()
+                .unschedule_callback(timeout_handle);
+        }
+
+        // Step 3.2-3.3. Call the callback with an IdleDeadline for this idle period.
+        let idle_deadline = IdleDeadline::new(self, deadline, false);
+        let _ = request
+            .callback
+            .Call__(&idle_deadline, ExceptionHandling::Report);
+
+        // Step 3.4. Keep going in another task while there are runnable callbacks left.
+        if self.runnable_idle_callbacks.borrow().is_empty() {
+            self.idle_period_in_progress.set(false);
+        } else {
+            self.queue_invoke_idle_callbacks(deadline);
+        }
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callback-timeout-algorithm>
+    fn invoke_idle_callback_timeout(&self, handle: u32) {
+        // Step 1-2. Find the callback, which is gone if it already ran or got cancelled.
+        let Some(request) = self.take_idle_request(handle) else {
+            return;
+        };
+
+        // Step 3. Call the callback with a deadline of now, and didTimeout set.
+        let idle_deadline = IdleDeadline::new(self, time::precise_time_ns(), true);
+        let _ = request
+            .callback
+            .Call__(&idle_deadline, ExceptionHandling::Report);
+    }
+}
+
+/// The timer started by `requestIdleCallback()` for a callback given a timeout.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct IdleCallbackTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    window: Trusted<Window>,
+    handle: u32,
+}
+
+impl IdleCallbackTimeoutCallback {
+    /// <https://w3c.github.io/requestidlecallback/#the-requestidlecallback-method> step 5.
+    pub fn invoke(self) {
+        // Queue a task on the idle task source to invoke the timeout algorithm.
+        let window = self.window.root();
+        let this = self.window;
+        let handle = self.handle;
+        let _ = window.task_manager().idle_task_source().queue(
+            task!(invoke_idle_callback_timeout: move || {
+                this.root().invoke_idle_callback_timeout(handle);
+            }),
+            window.upcast(),
+        );
+    }
+}
+
 impl Window {
     // https://html.spec.whatwg.org/multipage/#dom-window-postmessage step 7.
     pub fn post_message(
@@ -2867,3 +3576,28 @@ fn is_named_element_with_name_attribute(elem: &Element) -> bool {
 fn is_named_element_with_id_attribute(elem: &Element) -> bool {
     elem.is_html_element()
 }
+
+/// Settle the promise returned by `window.postMessageToEmbedder()` with the reply of the
+/// embedder, which is either a JSON value or the reason of a rejection.
+#[allow(unsafe_code)]
+fn settle_with_embedder_reply(promise: &Promise, reply: Result<String, String>) {
+    let global = promise.global();
+    let _ac = enter_realm(&*global);
+    let cx = GlobalScope::get_cx();
+    let json = match reply {
+        Ok(json) => json,
+        Err(reason) => return promise.reject_native(&DOMString::from(reason)),
+    };
+    let json: Vec<u16> = json.encode_utf16().collect();
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    unsafe {
+        if JS_ParseJSON(*cx, json.as_ptr(), json.len() as u32, value.handle_mut()) {
+            promise.resolve(cx, value.handle());
+        } else {
+            rooted!(in(*cx) let mut exception = UndefinedValue());
+            JS_GetPendingException(*cx, exception.handle_mut());
+            JS_ClearPendingException(*cx);
+            promise.reject(cx, exception.handle());
+        }
+    }
+}
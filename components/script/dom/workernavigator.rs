@@ -14,6 +14,7 @@ use crate::dom::gpu::GPU;
 use crate::dom::navigator::hardware_concurrency;
 use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::script_runtime::JSContext;
 
@@ -22,6 +23,7 @@ use crate::script_runtime::JSContext;
 pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
+    storage: MutNullableDom<StorageManager>,
     gpu: MutNullableDom<GPU>,
 }
 
@@ -30,6 +32,7 @@ impl WorkerNavigator {
         WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
+            storage: Default::default(),
             gpu: Default::default(),
         }
     }
@@ -107,6 +110,11 @@ impl WorkerNavigatorMethods for WorkerNavigator {
             .or_init(|| Permissions::new(&self.global()))
     }
 
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
     // https://gpuweb.github.io/gpuweb/#dom-navigator-gpu
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
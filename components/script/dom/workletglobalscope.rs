@@ -70,6 +70,8 @@ impl WorkletGlobalScope {
                 init.resource_threads.clone(),
                 MutableOrigin::new(ImmutableOrigin::new_opaque()),
                 None,
+                // Each worklet global has its own microtask queue, even though worklet globals
+                // of the same thread share a runtime.
                 Default::default(),
                 init.is_headless,
                 init.user_agent.clone(),
@@ -87,6 +87,14 @@ impl FetchCanceller {
         }
     }
 
+    /// Another canceller for the same fetch, for an owner that may need to cancel it
+    /// sooner than this one
+    pub fn duplicate(&self) -> Self {
+        FetchCanceller {
+            cancel_chan: self.cancel_chan.clone(),
+        }
+    }
+
     /// Use this if you don't want it to send a cancellation request
     /// on drop (e.g. if the fetch completes)
     pub fn ignore(&mut self) {
@@ -123,6 +131,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         referrer: request.referrer.clone(),
         referrer_policy: request.referrer_policy,
         pipeline_id: request.pipeline_id,
+        target_webview_id: request.target_webview_id,
         redirect_mode: request.redirect_mode,
         integrity_metadata: request.integrity_metadata.clone(),
         url_list: vec![],
@@ -132,6 +141,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         https_state: request.https_state,
         response_tainting: request.response_tainting,
         crash: None,
+        initiated_by_embedder: false,
     }
 }
 
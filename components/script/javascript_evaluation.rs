@@ -0,0 +1,235 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scripts that the embedder runs in a document with `Servo::evaluate_javascript`.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use embedder_traits::{EmbedderMsg, JSValue, JavaScriptEvaluationError, JavaScriptEvaluationId};
+use js::jsapi::{
+    self, ESClass, Heap, IsCallable, JSContext, JSObject, JS_ClearPendingException,
+    JS_IsTypedArrayObject,
+};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::{GetBuiltinClass, GetPropertyKeys, JS_GetPropertyById, JS_IdToValue};
+use js::rust::{HandleValue, IdVector};
+
+use crate::dom::bindings::conversions::{
+    get_property, get_property_jsval, ConversionBehavior, ConversionResult, FromJSValConvertible,
+    StringificationBehavior,
+};
+use crate::dom::bindings::error::ErrorInfo;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlscriptelement::SourceCode;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_module::ScriptFetchOptions;
+use crate::script_runtime::JSContext as SafeJSContext;
+
+/// How deeply objects and arrays can be nested in a value copied out of the script engine, so
+/// that copying a deeply nested value doesn't overflow the stack.
+const MAX_NESTING_DEPTH: usize = 128;
+
+/// Reports the value of a settled evaluation to the embedder.
+#[derive(JSTraceable, MallocSizeOf)]
+struct EvaluationHandler {
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    evaluation_id: JavaScriptEvaluationId,
+    rejected: bool,
+}
+
+impl Callback for EvaluationHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: SafeJSContext, value: HandleValue, realm: InRealm) {
+        let result = if self.rejected {
+            let error_info = unsafe { ErrorInfo::from_value(value, *cx) };
+            Err(JavaScriptEvaluationError::Exception {
+                message: error_info.message,
+                source_url: error_info.filename,
+                line: error_info.lineno,
+                column: error_info.column,
+            })
+        } else {
+            copy_to_embedder(cx, value)
+        };
+        let global = unsafe { GlobalScope::from_context(*cx, realm) };
+        global.send_to_embedder(EmbedderMsg::JavaScriptEvaluated(self.evaluation_id, result));
+    }
+}
+
+/// Run a script in the global of `window`. A promise that the script evaluates to is awaited,
+/// so that the embedder gets its value, or the reason it was rejected.
+#[allow(unsafe_code)]
+pub fn handle_evaluate_javascript(
+    window: &Window,
+    evaluation_id: JavaScriptEvaluationId,
+    script: String,
+) {
+    let global = window.upcast::<GlobalScope>();
+    let cx = GlobalScope::get_cx();
+    let ar = enter_realm(global);
+    rooted!(in(*cx) let mut rval = UndefinedValue());
+    let source_code = SourceCode::Text(Rc::new(DOMString::from_string(script)));
+    let evaluated = global.evaluate_script_on_global_catching_exception(
+        &source_code,
+        "",
+        rval.handle_mut(),
+        1,
+        ScriptFetchOptions::default_classic_script(global),
+        global.api_base_url(),
+    );
+
+    // Resolving adopts the state of the promise or thenable the script evaluated to, if any.
+    let promise = if evaluated {
+        Promise::new_resolved(global, cx, rval.handle())
+    } else {
+        Promise::new_rejected(global, cx, rval.handle())
+    }
+    .expect("Creating a settled promise never fails");
+    let handler = PromiseNativeHandler::new(
+        global,
+        Some(Box::new(EvaluationHandler {
+            evaluation_id,
+            rejected: false,
+        })),
+        Some(Box::new(EvaluationHandler {
+            evaluation_id,
+            rejected: true,
+        })),
+    );
+    promise.append_native_handler(&handler, InRealm::Entered(&ar));
+}
+
+/// Copy a value out of the script engine, the way the result of `Servo::evaluate_javascript` is.
+#[allow(unsafe_code)]
+pub(crate) fn copy_to_embedder(
+    cx: SafeJSContext,
+    value: HandleValue,
+) -> Result<JSValue, JavaScriptEvaluationError> {
+    unsafe { jsval_to_embedder(*cx, value, &mut RootedTraceableBox::new(Vec::new())) }
+}
+
+/// Copy a value out of the script engine. `parents` are the objects that contain `value`, to
+/// detect cycles and limit the nesting depth. They are kept rooted, since getters can run a
+/// compacting GC that moves them.
+#[allow(unsafe_code)]
+unsafe fn jsval_to_embedder(
+    cx: *mut JSContext,
+    value: HandleValue,
+    parents: &mut RootedTraceableBox<Vec<Box<Heap<*mut JSObject>>>>,
+) -> Result<JSValue, JavaScriptEvaluationError> {
+    if value.is_undefined() {
+        return Ok(JSValue::Undefined);
+    } else if value.is_null() {
+        return Ok(JSValue::Null);
+    } else if value.is_boolean() {
+        return Ok(JSValue::Boolean(value.to_boolean()));
+    } else if value.is_double() || value.is_int32() {
+        return match FromJSValConvertible::from_jsval(cx, value, ()) {
+            Ok(ConversionResult::Success(number)) => Ok(JSValue::Number(number)),
+            _ => Err(JavaScriptEvaluationError::SerializationError),
+        };
+    } else if value.is_string() {
+        let string: DOMString =
+            match FromJSValConvertible::from_jsval(cx, value, StringificationBehavior::Default) {
+                Ok(ConversionResult::Success(string)) => string,
+                _ => return Err(JavaScriptEvaluationError::SerializationError),
+            };
+        return Ok(JSValue::String(String::from(string)));
+    } else if !value.is_object() {
+        // Symbols and BigInts.
+        return Ok(JSValue::Undefined);
+    }
+
+    rooted!(in(cx) let object = value.to_object());
+    if IsCallable(*object) {
+        return Ok(JSValue::Undefined);
+    }
+
+    if parents.len() >= MAX_NESTING_DEPTH || parents.iter().any(|parent| parent.get() == *object) {
+        return Err(JavaScriptEvaluationError::SerializationError);
+    }
+    parents.push(Heap::boxed(*object));
+    let result = object_to_embedder(cx, value, parents);
+    parents.pop();
+
+    if result.is_err() {
+        // Getters and proxies run script, which may have thrown.
+        JS_ClearPendingException(cx);
+    }
+    result
+}
+
+#[allow(unsafe_code)]
+unsafe fn object_to_embedder(
+    cx: *mut JSContext,
+    value: HandleValue,
+    parents: &mut RootedTraceableBox<Vec<Box<Heap<*mut JSObject>>>>,
+) -> Result<JSValue, JavaScriptEvaluationError> {
+    rooted!(in(cx) let object = value.to_object());
+    let mut object_class = ESClass::Other;
+    if !GetBuiltinClass(cx, object.handle(), &mut object_class as *mut _) {
+        return Err(JavaScriptEvaluationError::SerializationError);
+    }
+
+    if object_class == ESClass::Array || JS_IsTypedArrayObject(*object) {
+        let length =
+            match get_property::<u32>(cx, object.handle(), "length", ConversionBehavior::Default) {
+                Ok(Some(length)) => length,
+                _ => return Err(JavaScriptEvaluationError::SerializationError),
+            };
+
+        let mut items = Vec::with_capacity(length as usize);
+        for index in 0..length {
+            rooted!(in(cx) let mut item = UndefinedValue());
+            if get_property_jsval(cx, object.handle(), &index.to_string(), item.handle_mut())
+                .is_err()
+            {
+                return Err(JavaScriptEvaluationError::SerializationError);
+            }
+            items.push(jsval_to_embedder(cx, item.handle(), parents)?);
+        }
+        return Ok(JSValue::Array(items));
+    }
+
+    let mut ids = IdVector::new(cx);
+    if !GetPropertyKeys(cx, object.handle(), jsapi::JSITER_OWNONLY, ids.handle_mut()) {
+        return Err(JavaScriptEvaluationError::SerializationError);
+    }
+
+    let mut properties = HashMap::with_capacity(ids.len());
+    for id in ids.iter() {
+        rooted!(in(cx) let id = *id);
+        rooted!(in(cx) let mut key = UndefinedValue());
+        let raw_id: jsapi::HandleId = id.handle().into();
+        if !JS_IdToValue(cx, *raw_id.ptr, key.handle_mut()) {
+            return Err(JavaScriptEvaluationError::SerializationError);
+        }
+        let key: DOMString = match FromJSValConvertible::from_jsval(
+            cx,
+            key.handle(),
+            StringificationBehavior::Default,
+        ) {
+            Ok(ConversionResult::Success(key)) => key,
+            _ => return Err(JavaScriptEvaluationError::SerializationError),
+        };
+
+        rooted!(in(cx) let mut property = UndefinedValue());
+        if !JS_GetPropertyById(cx, object.handle(), id.handle(), property.handle_mut()) {
+            return Err(JavaScriptEvaluationError::SerializationError);
+        }
+        properties.insert(
+            String::from(key),
+            jsval_to_embedder(cx, property.handle(), parents)?,
+        );
+    }
+    Ok(JSValue::Object(properties))
+}
@@ -38,6 +38,8 @@ mod devtools;
 #[warn(deprecated)]
 pub mod document_loader;
 #[warn(deprecated)]
+mod document_state;
+#[warn(deprecated)]
 #[macro_use]
 mod dom;
 #[warn(deprecated)]
@@ -49,6 +51,8 @@ mod image_listener;
 #[warn(deprecated)]
 mod init;
 #[warn(deprecated)]
+mod javascript_evaluation;
+#[warn(deprecated)]
 mod layout_image;
 
 pub mod layout_dom;
@@ -9,19 +9,24 @@
 use std::cell::Cell;
 use std::mem;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use base::id::PipelineId;
-use js::jsapi::{JSAutoRealm, JobQueueIsEmpty, JobQueueMayNotBeEmpty};
+use devtools_traits::{StartedTimelineMarker, TimelineMarker, TimelineMarkerType};
+use js::jsapi::{ClearKeptObjects, JSAutoRealm, JobQueueIsEmpty, JobQueueMayNotBeEmpty};
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PromiseBinding::PromiseJobCallback;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlimageelement::ImageElementMicrotask;
 use crate::dom::htmlmediaelement::MediaElementMicrotask;
 use crate::dom::mutationobserver::MutationObserver;
+use crate::dom::performance::LONG_TASK_THRESHOLD;
+use crate::dom::window::Window;
 use crate::realms::enter_realm;
 use crate::script_runtime::{notify_about_rejected_promises, JSContext};
 use crate::script_thread::ScriptThread;
@@ -33,6 +38,20 @@ pub struct MicrotaskQueue {
     microtask_queue: DomRefCell<Vec<Microtask>>,
     /// <https://html.spec.whatwg.org/multipage/#performing-a-microtask-checkpoint>
     performing_a_microtask_checkpoint: Cell<bool>,
+    /// How long a checkpoint may run before it stops running microtasks, so that a page that
+    /// keeps queueing microtasks cannot hang the event loop.
+    #[ignore_malloc_size_of = "Defined in std"]
+    #[no_trace]
+    checkpoint_budget: Cell<Option<Duration>>,
+}
+
+/// How a microtask checkpoint ended.
+pub enum CheckpointOutcome {
+    /// The checkpoint ran every microtask, or none because another checkpoint is running.
+    Finished,
+    /// The checkpoint used up its budget and left microtasks in the queue. The pipeline is the
+    /// one whose script ran last, which is likely the one that keeps queueing microtasks.
+    OutOfBudget(Option<PipelineId>),
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -57,7 +76,7 @@ pub struct EnqueuedPromiseCallback {
     pub callback: Rc<PromiseJobCallback>,
     #[no_trace]
     pub pipeline: PipelineId,
-    pub is_user_interacting: bool,
+    pub continuation_state: ContinuationState,
 }
 
 /// A microtask that comes from a queueMicrotask() Javascript call,
@@ -68,6 +87,48 @@ pub struct UserMicrotask {
     pub callback: Rc<VoidFunction>,
     #[no_trace]
     pub pipeline: PipelineId,
+    pub continuation_state: ContinuationState,
+}
+
+/// Script thread state that follows a microtask from the code that enqueued it into its
+/// callback, the way an AsyncContext variable would: it is captured when the microtask is
+/// enqueued, and is the current state while the callback runs.
+#[derive(Clone, Copy, Default, JSTraceable, MallocSizeOf)]
+pub struct ContinuationState {
+    /// Whether the microtask was enqueued while handling user input, which lets its callback use
+    /// the APIs that are only available to user interactions.
+    pub is_user_interacting: bool,
+}
+
+impl ContinuationState {
+    /// The state of the code that is currently running.
+    pub fn capture() -> ContinuationState {
+        ContinuationState {
+            is_user_interacting: ScriptThread::is_user_interacting(),
+        }
+    }
+
+    /// Make this the current state until the returned guard is dropped, which restores the state
+    /// that was current before.
+    fn enter(self) -> ContinuationStateGuard {
+        let previous = ContinuationState::capture();
+        self.apply();
+        ContinuationStateGuard { previous }
+    }
+
+    fn apply(self) {
+        ScriptThread::set_user_interacting(self.is_user_interacting);
+    }
+}
+
+struct ContinuationStateGuard {
+    previous: ContinuationState,
+}
+
+impl Drop for ContinuationStateGuard {
+    fn drop(&mut self) {
+        self.previous.apply();
+    }
 }
 
 impl MicrotaskQueue {
@@ -79,19 +140,29 @@ impl MicrotaskQueue {
         unsafe { JobQueueMayNotBeEmpty(*cx) };
     }
 
+    pub fn set_checkpoint_budget(&self, budget: Duration) {
+        self.checkpoint_budget.set(Some(budget));
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#perform-a-microtask-checkpoint>
-    /// Perform a microtask checkpoint, executing all queued microtasks until the queue is empty.
+    /// Perform a microtask checkpoint, executing all queued microtasks until the queue is empty,
+    /// or until the checkpoint budget is used up. In the latter case, `queue_continuation` is
+    /// called with the pipeline whose script ran last, to queue a task after which the remaining
+    /// microtasks run.
     #[allow(unsafe_code)]
-    pub fn checkpoint<F>(
+    pub fn checkpoint<F, C>(
         &self,
         cx: JSContext,
         target_provider: F,
         globalscopes: Vec<DomRoot<GlobalScope>>,
-    ) where
+        queue_continuation: C,
+    ) -> CheckpointOutcome
+    where
         F: Fn(PipelineId) -> Option<DomRoot<GlobalScope>>,
+        C: FnOnce(Option<PipelineId>),
     {
         if self.performing_a_microtask_checkpoint.get() {
-            return;
+            return CheckpointOutcome::Finished;
         }
 
         // Step 1
@@ -99,11 +170,30 @@ impl MicrotaskQueue {
 
         debug!("Now performing a microtask checkpoint");
 
+        // Only checkpoints that run microtasks are shown in the devtools timeline.
+        let timeline_windows: Vec<DomRoot<Window>> = if self.microtask_queue.borrow().is_empty() {
+            vec![]
+        } else {
+            globalscopes
+                .iter()
+                .filter_map(|global| DomRoot::downcast::<Window>(global.clone()))
+                .filter(|window| {
+                    window.need_emit_timeline_marker(TimelineMarkerType::MicrotaskCheckpoint)
+                })
+                .collect()
+        };
+        let marker = TimelineMarker::start("MicrotaskCheckpoint".to_owned());
+
+        let start_time = Instant::now();
+        let mut last_pipeline = None;
+        let mut outcome = CheckpointOutcome::Finished;
+
         // Steps 2
         while !self.microtask_queue.borrow().is_empty() {
             rooted_vec!(let mut pending_queue);
             mem::swap(&mut *pending_queue, &mut *self.microtask_queue.borrow_mut());
 
+            let mut yield_at = None;
             for (idx, job) in pending_queue.iter().enumerate() {
                 if idx == pending_queue.len() - 1 && self.microtask_queue.borrow().is_empty() {
                     unsafe { JobQueueIsEmpty(*cx) };
@@ -112,17 +202,22 @@ impl MicrotaskQueue {
                 match *job {
                     Microtask::Promise(ref job) => {
                         if let Some(target) = target_provider(job.pipeline) {
-                            let was_interacting = ScriptThread::is_user_interacting();
-                            ScriptThread::set_user_interacting(job.is_user_interacting);
+                            let _state = job.continuation_state.enter();
                             let _realm = enter_realm(&*target);
+                            let marker = start_microtask_marker(&target);
                             let _ = job.callback.Call_(&*target, ExceptionHandling::Report);
-                            ScriptThread::set_user_interacting(was_interacting);
+                            record_microtask_timing(&target, marker);
+                            last_pipeline = Some(job.pipeline);
                         }
                     },
                     Microtask::User(ref job) => {
                         if let Some(target) = target_provider(job.pipeline) {
+                            let _state = job.continuation_state.enter();
                             let _realm = enter_realm(&*target);
+                            let marker = start_microtask_marker(&target);
                             let _ = job.callback.Call_(&*target, ExceptionHandling::Report);
+                            record_microtask_timing(&target, marker);
+                            last_pipeline = Some(job.pipeline);
                         }
                     },
                     Microtask::MediaElement(ref task) => {
@@ -140,18 +235,64 @@ impl MicrotaskQueue {
                         MutationObserver::notify_mutation_observers();
                     },
                 }
+
+                if self.is_over_budget(start_time) {
+                    yield_at = Some(idx + 1);
+                    break;
+                }
             }
+
+            // Out of budget: keep the microtasks that did not run, ahead of the ones they queued,
+            // for a later checkpoint, and let the event loop handle other tasks in between.
+            if let Some(index) = yield_at {
+                let mut microtask_queue = self.microtask_queue.borrow_mut();
+                let queued = mem::take(&mut *microtask_queue);
+                microtask_queue.extend(pending_queue.drain(index..));
+                microtask_queue.extend(queued);
+                if !microtask_queue.is_empty() {
+                    unsafe { JobQueueMayNotBeEmpty(*cx) };
+                    outcome = CheckpointOutcome::OutOfBudget(last_pipeline);
+                }
+                break;
+            }
+        }
+
+        // A checkpoint that ran out of budget still performs the rest of its steps. Its
+        // continuation is queued first, so that the microtasks left in the queue run, and may
+        // handle the rejections, before the task that reports them.
+        if let CheckpointOutcome::OutOfBudget(pipeline_id) = outcome {
+            queue_continuation(pipeline_id);
         }
 
-        // Step 3
+        // Step 3.
         for global in globalscopes.into_iter() {
             notify_about_rejected_promises(&global);
         }
 
         // TODO: Step 4 - Cleanup Indexed Database transactions.
 
-        // Step 5
+        // Step 5. Perform ClearKeptObjects(), so that the targets of WeakRefs that were
+        // dereferenced or created since the last checkpoint can be collected again.
+        unsafe { ClearKeptObjects(*cx) };
+
+        // Step 6
         self.performing_a_microtask_checkpoint.set(false);
+
+        // Step 7. Record timing info for the microtask checkpoint.
+        if !timeline_windows.is_empty() {
+            let marker = marker.end();
+            for window in timeline_windows {
+                window.emit_timeline_marker(marker.clone());
+            }
+        }
+
+        outcome
+    }
+
+    fn is_over_budget(&self, start_time: Instant) -> bool {
+        self.checkpoint_budget
+            .get()
+            .is_some_and(|budget| start_time.elapsed() >= budget)
     }
 
     pub fn empty(&self) -> bool {
@@ -162,3 +303,26 @@ impl MicrotaskQueue {
         self.microtask_queue.borrow_mut().clear();
     }
 }
+
+/// Start timing a script microtask, if the devtools timeline of the window it runs in shows
+/// long microtasks.
+fn start_microtask_marker(global: &GlobalScope) -> Option<StartedTimelineMarker> {
+    global
+        .downcast::<Window>()
+        .filter(|window| window.need_emit_timeline_marker(TimelineMarkerType::LongMicrotask))
+        .map(|_| TimelineMarker::start("LongMicrotask".to_owned()))
+}
+
+/// Show a script microtask that ran for as long as a long task in the devtools timeline of the
+/// window it ran in. The performance timeline does not tell microtasks apart: the task whose
+/// checkpoint ran them is reported as a whole, so that its long microtasks are not counted twice.
+fn record_microtask_timing(global: &GlobalScope, marker: Option<StartedTimelineMarker>) {
+    let Some(marker) = marker else {
+        return;
+    };
+    let marker = marker.end();
+    let duration = marker.start_time.to(marker.end_time).as_secs_f64() * 1000.;
+    if duration > LONG_TASK_THRESHOLD {
+        global.as_window().emit_timeline_marker(marker);
+    }
+}
@@ -8,14 +8,14 @@
 #![allow(dead_code)]
 
 use core::ffi::c_char;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::ffi::CString;
 use std::io::{stdout, Write};
 use std::ops::Deref;
 use std::os::raw::c_void;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fmt, os, ptr, thread};
 
 use base::id::PipelineId;
@@ -48,6 +48,7 @@ use lazy_static::lazy_static;
 use malloc_size_of::MallocSizeOfOps;
 use profile_traits::mem::{Report, ReportKind, ReportsChan};
 use profile_traits::path;
+use profile_traits::time::{send_profile_data, ProfilerCategory, ProfilerChan};
 use servo_config::{opts, pref};
 use style::thread_state::{self, ThreadState};
 
@@ -74,7 +75,7 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::promiserejectionevent::PromiseRejectionEvent;
 use crate::dom::response::Response;
-use crate::microtask::{EnqueuedPromiseCallback, Microtask, MicrotaskQueue};
+use crate::microtask::{ContinuationState, EnqueuedPromiseCallback, Microtask, MicrotaskQueue};
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_module::EnsureModuleHooksInitialized;
 use crate::script_thread::trace_thread;
@@ -194,7 +195,7 @@ unsafe extern "C" fn empty(extra: *const c_void) -> bool {
 /// global's microtask queue.
 #[allow(unsafe_code)]
 unsafe extern "C" fn enqueue_promise_job(
-    extra: *const c_void,
+    _: *const c_void,
     cx: *mut RawJSContext,
     promise: HandleObject,
     job: HandleObject,
@@ -204,7 +205,6 @@ unsafe extern "C" fn enqueue_promise_job(
     let cx = JSContext::from_ptr(cx);
     let mut result = false;
     wrap_panic(&mut || {
-        let microtask_queue = &*(extra as *const MicrotaskQueue);
         let global = if !incumbent_global.is_null() {
             GlobalScope::from_object(incumbent_global.get())
         } else {
@@ -219,14 +219,15 @@ unsafe extern "C" fn enqueue_promise_job(
         };
         let is_user_interacting =
             interaction == PromiseUserInputEventHandlingState::HadUserInteractionAtCreation;
-        microtask_queue.enqueue(
-            Microtask::Promise(EnqueuedPromiseCallback {
-                callback: PromiseJobCallback::new(cx, job.get()),
-                pipeline,
+        // Enqueue the job in the queue of the global rather than the one of the runtime, which
+        // is not the same for worklets, whose globals share a runtime.
+        global.enqueue_microtask(Microtask::Promise(EnqueuedPromiseCallback {
+            callback: PromiseJobCallback::new(cx, job.get()),
+            pipeline,
+            continuation_state: ContinuationState {
                 is_user_interacting,
-            }),
-            cx,
-        );
+            },
+        }));
         result = true
     });
     result
@@ -749,6 +750,35 @@ pub unsafe fn get_reports(cx: *mut RawJSContext, path_seg: String) -> Vec<Report
 
 thread_local!(static GC_CYCLE_START: Cell<Option<Instant>> = Cell::new(None));
 thread_local!(static GC_SLICE_START: Cell<Option<Instant>> = Cell::new(None));
+thread_local!(static GC_PROFILER_CHAN: RefCell<Option<ProfilerChan>> = RefCell::new(None));
+
+/// Report the pause caused by every GC slice of the runtime of this thread to the time profiler.
+/// Only meant to be called when time profiling is enabled, since it sends a message per slice.
+#[allow(unsafe_code)]
+pub fn report_gc_pauses_to(cx: JSContext, profiler_chan: ProfilerChan) {
+    GC_PROFILER_CHAN.with(|chan| *chan.borrow_mut() = Some(profiler_chan));
+    unsafe { SetGCSliceCallback(*cx, Some(gc_slice_callback)) };
+}
+
+fn report_gc_pause(duration: Duration) {
+    GC_PROFILER_CHAN.with(|chan| {
+        let Some(ref chan) = *chan.borrow() else {
+            return;
+        };
+        let end_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        let start_time = end_time.saturating_sub(duration.as_nanos() as u64);
+        send_profile_data(
+            ProfilerCategory::ScriptGarbageCollection,
+            None,
+            chan,
+            start_time,
+            end_time,
+        );
+    });
+}
 
 #[allow(unsafe_code)]
 unsafe extern "C" fn gc_slice_callback(
@@ -756,26 +786,42 @@ unsafe extern "C" fn gc_slice_callback(
     progress: GCProgress,
     desc: *const GCDescription,
 ) {
+    let print = opts::get().debug.gc_profile;
     match progress {
         GCProgress::GC_CYCLE_BEGIN => GC_CYCLE_START.with(|start| {
             start.set(Some(Instant::now()));
-            println!("GC cycle began");
+            if print {
+                println!("GC cycle began");
+            }
         }),
         GCProgress::GC_SLICE_BEGIN => GC_SLICE_START.with(|start| {
             start.set(Some(Instant::now()));
-            println!("GC slice began");
+            if print {
+                println!("GC slice began");
+            }
         }),
         GCProgress::GC_SLICE_END => GC_SLICE_START.with(|start| {
-            let duration = start.get().unwrap().elapsed();
-            start.set(None);
-            println!("GC slice ended: duration={:?}", duration);
+            let Some(slice_start) = start.take() else {
+                return;
+            };
+            let duration = slice_start.elapsed();
+            report_gc_pause(duration);
+            if print {
+                println!("GC slice ended: duration={:?}", duration);
+            }
         }),
         GCProgress::GC_CYCLE_END => GC_CYCLE_START.with(|start| {
-            let duration = start.get().unwrap().elapsed();
-            start.set(None);
-            println!("GC cycle ended: duration={:?}", duration);
+            let Some(cycle_start) = start.take() else {
+                return;
+            };
+            if print {
+                println!("GC cycle ended: duration={:?}", cycle_start.elapsed());
+            }
         }),
     };
+    if !print {
+        return;
+    }
     if !desc.is_null() {
         let desc: &GCDescription = &*desc;
         let options = match desc.options_ {
@@ -19,7 +19,7 @@
 
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
-use std::collections::{hash_map, HashMap, HashSet};
+use std::collections::{hash_map, HashMap, HashSet, VecDeque};
 use std::default::Default;
 use std::option::Option;
 use std::rc::Rc;
@@ -30,22 +30,27 @@ use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use background_hang_monitor_api::{
-    BackgroundHangMonitor, BackgroundHangMonitorExitSignal, HangAnnotation, MonitoredComponentId,
-    MonitoredComponentType, ScriptHangAnnotation,
+    BackgroundHangMonitor, BackgroundHangMonitorExitSignal, BackgroundHangMonitorRegister,
+    HangAnnotation, MonitoredComponentId, MonitoredComponentType, ScriptHangAnnotation,
 };
 use base::id::{
-    BrowsingContextId, HistoryStateId, PipelineId, PipelineNamespace, TopLevelBrowsingContextId,
+    BrowsingContextId, HistoryStateId, NotificationId, PipelineId, PipelineNamespace,
+    TopLevelBrowsingContextId,
 };
 use base::Epoch;
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use chrono::{DateTime, Local};
-use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use crossbeam_channel::{select, unbounded, Receiver, Sender, TryRecvError};
 use devtools_traits::{
-    CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState,
+    CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, InputEventInfo, NavigationState,
     ScriptToDevtoolsControlMsg, WorkerId,
 };
-use embedder_traits::EmbedderMsg;
+use embedder_traits::user_script::{UserScript, UserScriptRunAt};
+use embedder_traits::{
+    DownloadRequest, EmbedderMsg, ImeEvent, JavaScriptEvaluationError, JavaScriptEvaluationId,
+    MediaPolicy, SavedDocumentState,
+};
 use euclid::default::{Point2D, Rect};
 use fonts::FontCacheThread;
 use headers::{HeaderMapExt, LastModified, ReferrerPolicy as ReferrerPolicyHeader};
@@ -55,15 +60,18 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::{
-    JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, SetWindowProxyClass,
+    GCOptions, GCReason, JSContext as UnsafeJSContext, JSGCParamKey, JSTracer,
+    JS_AddInterruptCallback, JS_GetGCParameter, JS_MaybeGC, NonIncrementalGC, PrepareForFullGC,
+    SetWindowProxyClass, JS_GC,
 };
 use js::jsval::UndefinedValue;
 use js::rust::ParentRuntime;
+use keyboard_types::{CompositionState, KeyState};
 use media::WindowGLContext;
 use metrics::{PaintTimeMetrics, MAX_TASK_NS};
 use mime::{self, Mime};
 use net_traits::image_cache::{ImageCache, PendingImageResponse};
-use net_traits::request::{CredentialsMode, Destination, RedirectMode, RequestBuilder};
+use net_traits::request::{CredentialsMode, Destination, RedirectMode, Referrer, RequestBuilder};
 use net_traits::storage_thread::StorageType;
 use net_traits::{
     FetchMetadata, FetchResponseListener, FetchResponseMsg, Metadata, NetworkError, ReferrerPolicy,
@@ -78,15 +86,16 @@ use script_layout_interface::{
 };
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::{
-    CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext, DocumentActivity,
-    EventResult, HistoryEntryReplacement, InitialScriptState, JsEvalResult, LayoutMsg, LoadData,
-    LoadOrigin, MediaSessionActionType, MouseButton, MouseEventType, NewLayoutInfo, Painter,
-    ProgressiveWebMetricType, ScriptMsg, ScriptToConstellationChan, ScrollState,
-    StructuredSerializedData, TimerSchedulerMsg, TouchEventType, TouchId, UntrustedNodeAddress,
-    UpdatePipelineIdReason, WheelDelta, WindowSizeData, WindowSizeType,
+    CompositorEvent, ConstellationControlMsg, ContainerPolicy, DiscardBrowsingContext,
+    DocumentActivity, EventResult, GamepadEvent, HistoryEntryReplacement, InitialScriptState,
+    JsEvalResult, LayoutMsg, LoadData, LoadOrigin, MediaSessionActionType, MouseButton,
+    MouseEventType, NewLayoutInfo, Painter, ProgressiveWebMetricType, ScriptMsg,
+    ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerSchedulerMsg,
+    TouchEventType, TouchId, UntrustedNodeAddress, UpdatePipelineIdReason, WheelDelta,
+    WindowSizeData, WindowSizeType,
 };
 use servo_atoms::Atom;
-use servo_config::opts;
+use servo_config::{opts, pref};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
@@ -107,11 +116,13 @@ use crate::dom::bindings::conversions::{
     ConversionResult, FromJSValConvertible, StringificationBehavior,
 };
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::live_objects::enable_live_object_counting;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{
     Dom, DomRoot, MutNullableDom, RootCollection, ThreadLocalStackRoots,
 };
+use crate::dom::bindings::settings_stack::debug_assert_execution_stack_is_empty;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::{HashMapTracedValues, JSTraceable};
 use crate::dom::customelementregistry::{
@@ -127,24 +138,25 @@ use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::identityhub::Identities;
 use crate::dom::mutationobserver::MutationObserver;
-use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::node::{from_untrusted_node_address, window_from_node, Node, ShadowIncluding};
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancepainttiming::PerformancePaintTiming;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
-use crate::dom::servoparser::{ParserContext, ServoParser};
+use crate::dom::servoparser::{self, ParserContext, ServoParser};
 use crate::dom::uievent::UIEvent;
+use crate::dom::userscripts::run_user_scripts;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::{CreatorBrowsingContextInfo, WindowProxy};
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::worklet::WorkletThreadPool;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
 use crate::fetch::FetchCanceller;
-use crate::microtask::{Microtask, MicrotaskQueue};
+use crate::microtask::{CheckpointOutcome, Microtask, MicrotaskQueue};
 use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{
-    get_reports, new_rt_and_cx, CommonScriptMsg, ContextForRequestInterrupt, JSContext, Runtime,
-    ScriptChan, ScriptPort, ScriptThreadEventCategory,
+    get_reports, new_rt_and_cx, report_gc_pauses_to, CommonScriptMsg, ContextForRequestInterrupt,
+    JSContext, Runtime, ScriptChan, ScriptPort, ScriptThreadEventCategory,
 };
 use crate::task_manager::TaskManager;
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
@@ -152,6 +164,7 @@ use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::gamepad::GamepadTaskSource;
 use crate::task_source::history_traversal::HistoryTraversalTaskSource;
+use crate::task_source::idle::IdleTaskSource;
 use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
@@ -162,7 +175,7 @@ use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::{devtools, webdriver_handlers};
+use crate::{devtools, document_state, javascript_evaluation, webdriver_handlers};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
@@ -220,6 +233,15 @@ struct InProgressLoad {
     canceller: FetchCanceller,
     /// If inheriting the security context
     inherited_secure_context: Option<bool>,
+    /// The request that fetches the document, kept in case the response is a download.
+    #[no_trace]
+    request: Option<RequestBuilder>,
+    /// The user scripts of the embedder that are injected into the document they match.
+    #[no_trace]
+    user_scripts: Vec<UserScript>,
+    /// The container policy of the iframe the document is loaded in, if any.
+    #[no_trace]
+    container_policy: Option<ContainerPolicy>,
 }
 
 impl InProgressLoad {
@@ -256,8 +278,37 @@ impl InProgressLoad {
             navigation_start_precise,
             canceller: Default::default(),
             inherited_secure_context,
+            request: None,
+            user_scripts: Vec::new(),
+            container_policy: None,
         }
     }
+
+    /// The request to fetch the resource again with once the response turned out to be a
+    /// download: the request of the load itself, with its method, body and referrer, unless it
+    /// was redirected.
+    fn take_download_request(&mut self, metadata: &Metadata) -> RequestBuilder {
+        let request = match self.request.take() {
+            Some(request) if request.url == metadata.final_url => request,
+            // TODO: Keep the method and body of requests redirected with a 307 or 308 status.
+            _ => RequestBuilder::new(
+                metadata.final_url.clone(),
+                metadata
+                    .referrer
+                    .clone()
+                    .map(Referrer::ReferrerUrl)
+                    .unwrap_or(Referrer::NoReferrer),
+            )
+            .referrer_policy(metadata.referrer_policy)
+            .credentials_mode(CredentialsMode::Include)
+            .target_webview_id(Some(self.top_level_browsing_context_id)),
+        };
+        // The pipeline of the load goes away once it is turned into a download.
+        request
+            .pipeline_id(None)
+            .redirect_mode(RedirectMode::Follow)
+            .crash(None)
+    }
 }
 
 #[derive(Debug)]
@@ -511,6 +562,17 @@ pub struct IncompleteParserContexts(RefCell<Vec<(PipelineId, ParserContext)>>);
 
 unsafe_no_jsmanaged_fields!(TaskQueue<MainThreadScriptMsg>);
 
+/// The longest an idle period can last.
+/// <https://w3c.github.io/requestidlecallback/#why50>
+const MAX_IDLE_PERIOD: Duration = Duration::from_millis(50);
+
+/// The expected time between two rendering opportunities, assuming a 60Hz display.
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// How far below `js.mem.event_loop_limit_mb`, in percent of it, the JS heap has to fall
+/// before going over the limit again triggers another GC.
+const MEMORY_LIMIT_LOW_WATER_PERCENT: u64 = 80;
+
 #[derive(JSTraceable)]
 // ScriptThread instances are rooted on creation, so this is okay
 #[allow(crown::unrooted_must_root)]
@@ -542,11 +604,45 @@ pub struct ScriptThread {
     /// A queue of tasks to be executed in this script-thread.
     task_queue: TaskQueue<MainThreadScriptMsg>,
 
+    /// The means to register this script-thread with the background-hang-monitor again when
+    /// the pipeline it is registered under closes.
+    #[no_trace]
+    background_hang_monitor_register: Box<dyn BackgroundHangMonitorRegister>,
     /// The dedicated means of communication with the background-hang-monitor for this script-thread.
     #[no_trace]
-    background_hang_monitor: Box<dyn BackgroundHangMonitor>,
+    background_hang_monitor: RefCell<Box<dyn BackgroundHangMonitor>>,
+    /// The pipeline this event loop is registered under with the background-hang-monitor, which
+    /// a slow script response has to name for the monitor to interrupt script in this thread.
+    /// It is always a live pipeline of this event loop while there is one, so that the
+    /// constellation can map a hang alert to a webview.
+    #[no_trace]
+    background_hang_monitor_pipeline_id: Cell<PipelineId>,
     /// A flag set to `true` by the BHM on exit, and checked from within the interrupt handler.
     closing: Arc<AtomicBool>,
+    /// A flag set to `true` by the BHM when the embedder asked to stop a slow script, and
+    /// checked from within the interrupt handler.
+    slow_script_interrupted: Arc<AtomicBool>,
+    /// Whether this event loop has already reported its pipelines for going over the
+    /// `js.mem.event_loop_limit_mb` memory limit.
+    memory_limit_exceeded: Cell<bool>,
+    /// Whether a GC was already run because the JS heap went over `js.mem.event_loop_limit_mb`,
+    /// and the heap has not fallen back below [`MEMORY_LIMIT_LOW_WATER_PERCENT`] of it since.
+    memory_limit_collected: Cell<bool>,
+    /// Whether a full GC should run the next time the event loop is idle, because every
+    /// document it hosts went to the background.
+    background_gc_pending: Cell<bool>,
+    /// Whether the event loop is paused by the devtools thread actor, and only handles
+    /// devtools messages.
+    paused_for_devtools: Cell<bool>,
+    /// Messages from the constellation that arrived while the event loop was paused by the
+    /// devtools, to be handled before any newer one.
+    #[no_trace]
+    held_constellation_msgs: DomRefCell<VecDeque<ConstellationControlMsg>>,
+    /// When microtask checkpoints started running out of their budget without a checkpoint
+    /// emptying the microtask queue since.
+    runaway_microtasks_since: Cell<Option<Instant>>,
+    /// Whether the runaway microtasks were already reported to the embedder as a slow script.
+    runaway_microtasks_reported: Cell<bool>,
 
     /// A channel to hand out to script thread-based entities that need to be able to enqueue
     /// events in the event queue.
@@ -556,6 +652,8 @@ pub struct ScriptThread {
 
     gamepad_task_sender: Box<dyn ScriptChan>,
 
+    idle_task_sender: Box<dyn ScriptChan>,
+
     #[no_trace]
     media_element_task_sender: Sender<MainThreadScriptMsg>,
 
@@ -719,6 +817,11 @@ pub struct ScriptThread {
     /// Code is running as a consequence of a user interaction
     is_user_interacting: Cell<bool>,
 
+    /// How the media of the documents of each webview is played, when the embedder set it to
+    /// something else than the default.
+    #[no_trace]
+    media_policies: DomRefCell<HashMap<TopLevelBrowsingContextId, MediaPolicy>>,
+
     /// Identity manager for WebGPU resources
     #[no_trace]
     gpu_id_hub: Arc<Mutex<Identities>>,
@@ -737,6 +840,7 @@ pub struct ScriptThread {
 
 struct BHMExitSignal {
     closing: Arc<AtomicBool>,
+    slow_script_interrupted: Arc<AtomicBool>,
     js_context: ContextForRequestInterrupt,
 }
 
@@ -745,6 +849,11 @@ impl BackgroundHangMonitorExitSignal for BHMExitSignal {
         self.closing.store(true, Ordering::SeqCst);
         self.js_context.request_interrupt();
     }
+
+    fn signal_to_interrupt(&self) {
+        self.slow_script_interrupted.store(true, Ordering::SeqCst);
+        self.js_context.request_interrupt();
+    }
 }
 
 #[allow(unsafe_code)]
@@ -752,8 +861,10 @@ unsafe extern "C" fn interrupt_callback(_cx: *mut UnsafeJSContext) -> bool {
     let res = ScriptThread::can_continue_running();
     if !res {
         ScriptThread::prepare_for_shutdown();
+        return res;
     }
-    res
+    // Returning false terminates the running script without running any handlers.
+    !ScriptThread::take_slow_script_interrupt()
 }
 
 /// In the event of thread panic, all data on the stack runs its destructor. However, there
@@ -810,6 +921,7 @@ impl ScriptThreadFactory for ScriptThread {
                 let secure = load_data.inherited_secure_context;
                 let mem_profiler_chan = state.mem_profiler_chan.clone();
                 let window_size = state.window_size;
+                let user_scripts = state.user_scripts.clone();
 
                 let script_thread = ScriptThread::new(
                     state,
@@ -827,7 +939,7 @@ impl ScriptThreadFactory for ScriptThread {
                 let mut failsafe = ScriptMemoryFailsafe::new(&script_thread);
 
                 let origin = MutableOrigin::new(load_data.url.origin());
-                let new_load = InProgressLoad::new(
+                let mut new_load = InProgressLoad::new(
                     id,
                     browsing_context_id,
                     top_level_browsing_context_id,
@@ -838,6 +950,8 @@ impl ScriptThreadFactory for ScriptThread {
                     origin,
                     secure,
                 );
+                new_load.user_scripts = user_scripts;
+                new_load.container_policy = load_data.container_policy.clone();
                 script_thread.pre_page_load(new_load, load_data);
 
                 let reporter_name = format!("script-reporter-{:?}", id);
@@ -859,6 +973,15 @@ impl ScriptThreadFactory for ScriptThread {
 }
 
 impl ScriptThread {
+    /// Perform a microtask checkpoint of the microtask queue shared by the windows of this
+    /// event loop, which runs each microtask in the window that queued it.
+    pub fn perform_a_microtask_checkpoint_for_event_loop() {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread.perform_a_microtask_checkpoint();
+        })
+    }
+
     pub fn note_rendering_opportunity(pipeline_id: PipelineId) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -880,6 +1003,15 @@ impl ScriptThread {
         })
     }
 
+    /// Consume a pending request to stop a slow script, returning whether the currently
+    /// running script should be terminated.
+    pub fn take_slow_script_interrupt() -> bool {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread.take_slow_script_interrupt_inner()
+        })
+    }
+
     pub fn prepare_for_shutdown() {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -1106,6 +1238,20 @@ impl ScriptThread {
         })
     }
 
+    pub fn media_policy(webview_id: TopLevelBrowsingContextId) -> MediaPolicy {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            root.get().map_or(MediaPolicy::default(), |script_thread| {
+                let script_thread = unsafe { &*script_thread };
+                script_thread
+                    .media_policies
+                    .borrow()
+                    .get(&webview_id)
+                    .copied()
+                    .unwrap_or_default()
+            })
+        })
+    }
+
     pub fn get_fully_active_document_ids() -> HashSet<PipelineId> {
         SCRIPT_THREAD_ROOT.with(|root| {
             root.get().map_or(HashSet::new(), |script_thread| {
@@ -1304,6 +1450,25 @@ impl ScriptThread {
             JS_AddInterruptCallback(cx, Some(interrupt_callback));
         }
 
+        // Let `ServoTestUtils` report how many DOM objects are alive.
+        if pref!(dom.servo_helpers.enabled) {
+            enable_live_object_counting();
+        }
+
+        let microtask_budget = pref!(js.slow_script.microtask_budget_ms);
+        if microtask_budget > 0 {
+            runtime
+                .microtask_queue
+                .set_checkpoint_budget(Duration::from_millis(microtask_budget as u64));
+        }
+
+        if opts.time_profiling.is_some() {
+            report_gc_pauses_to(
+                unsafe { JSContext::from_ptr(cx) },
+                state.time_profiler_chan.clone(),
+            );
+        }
+
         // Ask the router to proxy IPC messages from the devtools to us.
         let (ipc_devtools_sender, ipc_devtools_receiver) = ipc::channel().unwrap();
         let devtools_port =
@@ -1314,16 +1479,13 @@ impl ScriptThread {
         let task_queue = TaskQueue::new(port, chan.clone());
 
         let closing = Arc::new(AtomicBool::new(false));
-        let background_hang_monitor_exit_signal = BHMExitSignal {
-            closing: closing.clone(),
-            js_context: ContextForRequestInterrupt::new(cx),
-        };
-
-        let background_hang_monitor = state.background_hang_monitor_register.register_component(
-            MonitoredComponentId(state.id, MonitoredComponentType::Script),
-            Duration::from_millis(1000),
-            Duration::from_millis(5000),
-            Some(Box::new(background_hang_monitor_exit_signal)),
+        let slow_script_interrupted = Arc::new(AtomicBool::new(false));
+        let background_hang_monitor = Self::register_with_background_hang_monitor(
+            &*state.background_hang_monitor_register,
+            state.id,
+            &closing,
+            &slow_script_interrupted,
+            cx,
         );
 
         // Ask the router to proxy IPC messages from the control port to us.
@@ -1346,12 +1508,23 @@ impl ScriptThread {
 
             task_queue,
 
-            background_hang_monitor,
+            background_hang_monitor_register: state.background_hang_monitor_register,
+            background_hang_monitor: RefCell::new(background_hang_monitor),
+            background_hang_monitor_pipeline_id: Cell::new(state.id),
             closing,
+            slow_script_interrupted,
+            memory_limit_exceeded: Cell::new(false),
+            memory_limit_collected: Cell::new(false),
+            background_gc_pending: Cell::new(false),
+            paused_for_devtools: Cell::new(false),
+            held_constellation_msgs: DomRefCell::new(VecDeque::new()),
+            runaway_microtasks_since: Cell::new(None),
+            runaway_microtasks_reported: Cell::new(false),
 
             chan: MainThreadScriptChan(chan.clone()),
             dom_manipulation_task_sender: boxed_script_sender.clone(),
             gamepad_task_sender: boxed_script_sender.clone(),
+            idle_task_sender: boxed_script_sender.clone(),
             media_element_task_sender: chan.clone(),
             user_interaction_task_sender: chan.clone(),
             networking_task_sender: boxed_script_sender.clone(),
@@ -1419,6 +1592,7 @@ impl ScriptThread {
 
             node_ids: Default::default(),
             is_user_interacting: Cell::new(false),
+            media_policies: Default::default(),
             gpu_id_hub: Arc::new(Mutex::new(Identities::new())),
             webgpu_port: RefCell::new(None),
             inherited_secure_context: state.inherited_secure_context,
@@ -1439,6 +1613,81 @@ impl ScriptThread {
         true
     }
 
+    /// Check if the embedder asked to stop a slow script, clearing the request if so.
+    ///
+    /// This runs inside the interrupt callback, where no script may run, so the interruption is
+    /// reported to devtools as a page warning, which only sends a message, rather than through
+    /// the console of the page.
+    fn take_slow_script_interrupt_inner(&self) -> bool {
+        if !self.slow_script_interrupted.swap(false, Ordering::SeqCst) {
+            return false;
+        }
+        warn!("Stopped a script that was running for too long.");
+        if let Some(global) = GlobalScope::current() {
+            global.issue_page_warning("Stopped a script that was running for too long.");
+        }
+        true
+    }
+
+    /// Register an event loop with the background hang monitor under `pipeline_id`. A
+    /// permanent hang is reported to the embedder as a slow script, which it can choose to
+    /// stop.
+    fn register_with_background_hang_monitor(
+        register: &dyn BackgroundHangMonitorRegister,
+        pipeline_id: PipelineId,
+        closing: &Arc<AtomicBool>,
+        slow_script_interrupted: &Arc<AtomicBool>,
+        cx: *mut UnsafeJSContext,
+    ) -> Box<dyn BackgroundHangMonitor> {
+        let exit_signal = BHMExitSignal {
+            closing: closing.clone(),
+            slow_script_interrupted: slow_script_interrupted.clone(),
+            js_context: ContextForRequestInterrupt::new(cx),
+        };
+        let slow_script_timeout = pref!(js.slow_script.timeout_ms).max(0) as u64;
+        register.register_component(
+            MonitoredComponentId(pipeline_id, MonitoredComponentType::Script),
+            Duration::from_millis(1000),
+            Duration::from_millis(slow_script_timeout),
+            Some(Box::new(exit_signal)),
+        )
+    }
+
+    /// Once the pipeline this event loop is registered under with the background hang monitor
+    /// has closed, register it again under one of its live pipelines. The constellation drops
+    /// the hang alerts of closed pipelines, so a slow script would otherwise never be reported.
+    fn rekey_background_hang_monitor(&self) {
+        let closed_pipelines = self.closed_pipelines.borrow();
+        if !closed_pipelines.contains(&self.background_hang_monitor_pipeline_id.get()) {
+            return;
+        }
+        let live_pipeline = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(id, _)| id)
+            .chain(
+                self.incomplete_loads
+                    .borrow()
+                    .iter()
+                    .map(|load| load.pipeline_id),
+            )
+            .find(|id| !closed_pipelines.contains(id));
+        let Some(pipeline_id) = live_pipeline else {
+            return;
+        };
+
+        self.background_hang_monitor.borrow().unregister();
+        *self.background_hang_monitor.borrow_mut() = Self::register_with_background_hang_monitor(
+            &*self.background_hang_monitor_register,
+            pipeline_id,
+            &self.closing,
+            &self.slow_script_interrupted,
+            self.js_runtime.cx(),
+        );
+        self.background_hang_monitor_pipeline_id.set(pipeline_id);
+    }
+
     /// We are closing, ensure no script can run and potentially hang.
     fn prepare_for_shutdown_inner(&self) {
         let docs = self.documents.borrow();
@@ -1555,6 +1804,9 @@ impl ScriptThread {
         let window = document.window();
         let _realm = enter_realm(document.window());
         for event in document.take_pending_compositor_events().into_iter() {
+            let devtools_input_event = Self::devtools_input_event(&document, &event);
+            let dispatch_start = Instant::now();
+
             match event {
                 CompositorEvent::ResizeEvent(new_size, size_type) => {
                     window.add_resize_event(new_size, size_type);
@@ -1630,15 +1882,114 @@ impl ScriptThread {
                     document.dispatch_composition_event(composition_event);
                 },
 
+                CompositorEvent::ImeEvent(ime_event) => {
+                    document.handle_ime_event(ime_event);
+                },
+
                 CompositorEvent::GamepadEvent(gamepad_event) => {
                     let global = window.upcast::<GlobalScope>();
                     global.handle_gamepad_event(gamepad_event);
                 },
             }
+
+            if let Some((event_type, target)) = devtools_input_event {
+                let global = window.upcast::<GlobalScope>();
+                if let Some(chan) = global.running_devtools_chan() {
+                    let info = InputEventInfo {
+                        event_type: event_type.into(),
+                        target: target.map(|node| node.summarize()),
+                        dispatch_duration: dispatch_start.elapsed().as_secs_f64() * 1000.,
+                    };
+                    let _ = chan.send(ScriptToDevtoolsControlMsg::InputEventDispatched(
+                        pipeline_id,
+                        info,
+                    ));
+                }
+            }
         }
         ScriptThread::set_user_interacting(false);
     }
 
+    /// The event type and target of an input event to report to devtools, if a devtools
+    /// client is following this document. The target is looked up before dispatch, so that
+    /// it is known even when a handler removes it from the document.
+    fn devtools_input_event(
+        document: &Document,
+        event: &CompositorEvent,
+    ) -> Option<(&'static str, Option<DomRoot<Node>>)> {
+        if !document
+            .window()
+            .upcast::<GlobalScope>()
+            .live_devtools_updates()
+        {
+            return None;
+        }
+        let hit_test_target = |address: &Option<UntrustedNodeAddress>| {
+            address.map(|address| unsafe { from_untrusted_node_address(address) })
+        };
+        let focus_target = || {
+            document
+                .get_focused_element()
+                .map(DomRoot::upcast::<Node>)
+                .or_else(|| document.GetBody().map(DomRoot::upcast::<Node>))
+        };
+        let input_event = match event {
+            CompositorEvent::ResizeEvent(..) | CompositorEvent::IMEDismissedEvent => return None,
+            CompositorEvent::MouseButtonEvent(event_type, _, _, address, ..) => {
+                let event_type = match event_type {
+                    MouseEventType::Click => "click",
+                    MouseEventType::MouseDown => "mousedown",
+                    MouseEventType::MouseUp => "mouseup",
+                };
+                (event_type, hit_test_target(address))
+            },
+            CompositorEvent::MouseMoveEvent(_, address, _) => {
+                ("mousemove", hit_test_target(address))
+            },
+            CompositorEvent::TouchEvent(event_type, _, _, address) => {
+                let event_type = match event_type {
+                    TouchEventType::Down => "touchstart",
+                    TouchEventType::Move => "touchmove",
+                    TouchEventType::Up => "touchend",
+                    TouchEventType::Cancel => "touchcancel",
+                };
+                (event_type, hit_test_target(address))
+            },
+            CompositorEvent::WheelEvent(_, _, address) => ("wheel", hit_test_target(address)),
+            CompositorEvent::KeyboardEvent(key_event) => {
+                let event_type = match key_event.state {
+                    KeyState::Down => "keydown",
+                    KeyState::Up => "keyup",
+                };
+                (event_type, focus_target())
+            },
+            CompositorEvent::CompositionEvent(composition_event) => {
+                let event_type = match composition_event.state {
+                    CompositionState::Start => "compositionstart",
+                    CompositionState::Update => "compositionupdate",
+                    CompositionState::End => "compositionend",
+                };
+                (event_type, focus_target())
+            },
+            CompositorEvent::ImeEvent(ime_event) => {
+                let event_type = match ime_event {
+                    ImeEvent::SetComposition(..) => "compositionupdate",
+                    ImeEvent::Commit(..) | ImeEvent::Cancel => "compositionend",
+                };
+                (event_type, focus_target())
+            },
+            CompositorEvent::GamepadEvent(gamepad_event) => {
+                let event_type = match gamepad_event {
+                    GamepadEvent::Connected(..) => "gamepadconnected",
+                    GamepadEvent::Disconnected(..) => "gamepaddisconnected",
+                    GamepadEvent::Updated(..) => "gamepadupdated",
+                };
+                (event_type, None)
+            },
+        };
+        Some(input_event)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#update-the-rendering>
     fn update_the_rendering(&self) {
         *self.has_queued_update_the_rendering_task.borrow_mut() = false;
@@ -1777,25 +2128,34 @@ impl ScriptThread {
         let mut sequential = vec![];
 
         // Notify the background-hang-monitor we are waiting for an event.
-        self.background_hang_monitor.notify_wait();
+        self.background_hang_monitor.borrow().notify_wait();
+
+        // A request to stop a slow script that arrived after the script finished on its own
+        // must not stop the next one.
+        self.slow_script_interrupted.store(false, Ordering::SeqCst);
 
         // Receive at least one message so we don't spinloop.
         debug!("Waiting for event.");
-        let mut event = select! {
-            recv(self.task_queue.select()) -> msg => {
-                self.task_queue.take_tasks(msg.unwrap());
-                let event = self
-                    .task_queue
-                    .recv()
-                    .expect("Spurious wake-up of the event-loop, task-queue has no tasks available");
-                FromScript(event)
-            },
-            recv(self.control_port) -> msg => FromConstellation(msg.unwrap()),
-            recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
-                => FromDevtools(msg.unwrap()),
-            recv(self.image_cache_port) -> msg => FromImageCache(msg.unwrap()),
-            recv(self.webgpu_port.borrow().as_ref().unwrap_or(&crossbeam_channel::never())) -> msg
-                => FromWebGPUServer(msg.unwrap()),
+        let held_constellation_msg = self.held_constellation_msgs.borrow_mut().pop_front();
+        let mut event = if let Some(msg) = held_constellation_msg {
+            FromConstellation(msg)
+        } else {
+            select! {
+                recv(self.task_queue.select()) -> msg => {
+                    self.task_queue.take_tasks(msg.unwrap());
+                    let event = self
+                        .task_queue
+                        .recv()
+                        .expect("Spurious wake-up of the event-loop, task-queue has no tasks available");
+                    FromScript(event)
+                },
+                recv(self.control_port) -> msg => FromConstellation(msg.unwrap()),
+                recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
+                    => FromDevtools(msg.unwrap()),
+                recv(self.image_cache_port) -> msg => FromImageCache(msg.unwrap()),
+                recv(self.webgpu_port.borrow().as_ref().unwrap_or(&crossbeam_channel::never())) -> msg
+                    => FromWebGPUServer(msg.unwrap()),
+            }
         };
         debug!("Got event.");
 
@@ -1898,7 +2258,7 @@ impl ScriptThread {
             // If any of our input sources has an event pending, we'll perform another iteration
             // and check for more resize events. If there are no events pending, we'll move
             // on and execute the sequential non-resize events we've seen.
-            match self.control_port.try_recv() {
+            match self.try_recv_constellation_msg() {
                 Err(_) => match self.task_queue.take_tasks_and_recv() {
                     Err(_) => match self.devtools_port.try_recv() {
                         Err(_) => match self.image_cache_port.try_recv() {
@@ -1949,6 +2309,11 @@ impl ScriptThread {
                 continue;
             }
 
+            let is_task = matches!(
+                msg,
+                FromScript(MainThreadScriptMsg::Common(CommonScriptMsg::Task(..)))
+            );
+            let task_start = precise_time_ns();
             let result = self.profile_event(category, pipeline_id, move || {
                 match msg {
                     FromConstellation(ConstellationControlMsg::ExitScriptThread) => {
@@ -1972,6 +2337,10 @@ impl ScriptThread {
             // https://html.spec.whatwg.org/multipage/#event-loop-processing-model step 6
             // TODO(#32003): A microtask checkpoint is only supposed to be performed after running a task.
             self.perform_a_microtask_checkpoint();
+
+            if is_task {
+                self.report_long_task(pipeline_id, task_start);
+            }
         }
 
         {
@@ -2018,9 +2387,179 @@ impl ScriptThread {
             }
         }
 
+        self.enforce_memory_limit();
+        self.maybe_collect_garbage_while_idle();
+        self.maybe_start_idle_periods();
+
+        // No script runs between iterations of the event loop.
+        debug_assert_execution_stack_is_empty();
+
         true
     }
 
+    /// If there is no more work queued, start an idle period for every fully active window that
+    /// has idle callbacks.
+    /// <https://html.spec.whatwg.org/multipage/#event-loop-processing-model:start-an-idle-period-algorithm>
+    fn maybe_start_idle_periods(&self) {
+        if self.has_queued_work() {
+            return;
+        }
+        let windows: Vec<DomRoot<Window>> = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| document.is_fully_active())
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .filter(|window| window.has_idle_callbacks())
+            .collect();
+        if windows.is_empty() {
+            return;
+        }
+        let deadline = self.compute_idle_deadline();
+        for window in windows {
+            window.start_an_idle_period(deadline);
+        }
+    }
+
+    /// Estimate how long the event loop is going to stay idle, returning the end of the idle
+    /// period in `time::precise_time_ns()` units.
+    /// <https://html.spec.whatwg.org/multipage/#compute-deadline>
+    fn compute_idle_deadline(&self) -> u64 {
+        // An idle period never lasts longer than 50ms, so that the page stays responsive to
+        // input arriving while idle callbacks run.
+        let mut time_remaining = MAX_IDLE_PERIOD;
+        let now = Instant::now();
+        let documents = self.documents.borrow();
+
+        // Stop in time for the next frame if a document is going to render.
+        let has_pending_renders = documents.iter().any(|(_, document)| {
+            document.has_pending_animation_frame_callbacks() ||
+                document.animations().running_animation_count() > 0
+        });
+        if has_pending_renders {
+            if let Some(last_render_opportunity) = *self.last_render_opportunity_time.borrow() {
+                let next_render_opportunity = last_render_opportunity + FRAME_INTERVAL;
+                time_remaining =
+                    time_remaining.min(next_render_opportunity.saturating_duration_since(now));
+            }
+        }
+
+        // Stop in time for the next timer of any window of this event loop.
+        for (_, document) in documents.iter() {
+            if let Some(next_timer) = document
+                .window()
+                .upcast::<GlobalScope>()
+                .time_until_next_timer()
+            {
+                time_remaining = time_remaining.min(Duration::from_millis(next_timer.get()));
+            }
+        }
+
+        time::precise_time_ns() + time_remaining.as_nanos() as u64
+    }
+
+    /// Report a task of the given pipeline that started at `task_start`, including the microtask
+    /// checkpoint that followed it, if it took long enough to be a long task.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#event-loop-processing-model:report-long-tasks>
+    fn report_long_task(&self, pipeline_id: Option<PipelineId>, task_start: u64) {
+        if !pref!(dom.longtask.enabled) {
+            return;
+        }
+        let Some(window) = pipeline_id.and_then(|id| self.documents.borrow().find_window(id))
+        else {
+            return;
+        };
+        window
+            .Performance()
+            .report_long_task(task_start, precise_time_ns());
+    }
+
+    /// Whether any message from the constellation or any task is still waiting to be handled.
+    fn has_queued_work(&self) -> bool {
+        !self.control_port.is_empty() ||
+            !self.held_constellation_msgs.borrow().is_empty() ||
+            !self.task_queue.is_empty()
+    }
+
+    /// Give the JS engine a chance to run a GC slice (or a GC that it has scheduled) once all
+    /// queued work, including the rendering update above, has been handled, so that collections
+    /// happen between frames instead of in the middle of running script. This is also where the
+    /// full GC requested once every document went to the background runs.
+    #[allow(unsafe_code)]
+    fn maybe_collect_garbage_while_idle(&self) {
+        if self.has_queued_work() {
+            return;
+        }
+        if self.background_gc_pending.take() && self.all_documents_in_background() {
+            unsafe { JS_GC(*self.get_cx(), GCReason::API) };
+            return;
+        }
+        unsafe { JS_MaybeGC(*self.get_cx()) };
+    }
+
+    /// If the JS heap of this event loop is over `js.mem.event_loop_limit_mb` even after a full
+    /// GC, report every top-level browsing context it hosts to the constellation as having run
+    /// out of memory, so that they are replaced by a crash page instead of taking the whole
+    /// process down with them.
+    ///
+    /// Only one GC is run when the limit is crossed. If it brings the heap back under the limit,
+    /// no further GC is run here until the heap has fallen below the low-water mark, so that a
+    /// page hovering around the limit doesn't get a full GC on every task.
+    #[allow(unsafe_code)]
+    fn enforce_memory_limit(&self) {
+        let limit_mb = pref!(js.mem.event_loop_limit_mb);
+        if limit_mb <= 0 || self.memory_limit_exceeded.get() {
+            return;
+        }
+        let limit = limit_mb as u64 * 1024 * 1024;
+        let cx = self.get_cx();
+        let heap_bytes = || unsafe { JS_GetGCParameter(*cx, JSGCParamKey::JSGC_BYTES) } as u64;
+        let used = heap_bytes();
+        if used < limit / 100 * MEMORY_LIMIT_LOW_WATER_PERCENT {
+            self.memory_limit_collected.set(false);
+            return;
+        }
+        if used <= limit {
+            return;
+        }
+
+        // Try to get back under the limit before giving up on the pipelines. A shrinking GC
+        // also purges the engine's caches and releases empty chunks. It is only worth doing
+        // once until the heap shrinks again, but the limit stays enforced in the meantime.
+        let used = if self.memory_limit_collected.replace(true) {
+            used
+        } else {
+            unsafe {
+                PrepareForFullGC(*cx);
+                NonIncrementalGC(*cx, GCOptions::Shrink, GCReason::API);
+            }
+            heap_bytes()
+        };
+        if used <= limit {
+            return;
+        }
+
+        self.memory_limit_exceeded.set(true);
+        let reason = format!(
+            "Out of memory: the JS heap of this page's event loop uses {} MiB, \
+             over its limit of {} MiB ({} documents)",
+            used / (1024 * 1024),
+            limit_mb,
+            self.documents.borrow().iter().count(),
+        );
+        warn!("{}", reason);
+        let mut reported = HashSet::new();
+        for (_, document) in self.documents.borrow().iter() {
+            let window = document.window();
+            let top_level_browsing_context_id =
+                window.window_proxy().top_level_browsing_context_id();
+            if reported.insert(top_level_browsing_context_id) {
+                window.send_to_constellation(ScriptMsg::MemoryLimitExceeded(reason.clone()));
+            }
+        }
+    }
+
     // Perform step 7.10 from https://html.spec.whatwg.org/multipage/#event-loop-processing-model.
     // Described at: https://drafts.csswg.org/web-animations-1/#update-animations-and-send-events
     fn update_animations_and_send_events(&self) {
@@ -2094,6 +2633,7 @@ impl ScriptThread {
             ScriptThreadEventCategory::WebGPUMsg => ScriptHangAnnotation::WebGPUMsg,
         };
         self.background_hang_monitor
+            .borrow()
             .notify_activity(HangAnnotation::Script(hang_annotation));
     }
 
@@ -2117,6 +2657,12 @@ impl ScriptThread {
                 SetDocumentActivity(id, ..) => Some(id),
                 SetThrottled(id, ..) => Some(id),
                 SetThrottledInContainingIframe(id, ..) => Some(id),
+                SetMediaPolicy(..) => None,
+                EvaluateJavaScript(id, ..) => Some(id),
+                GetDocumentState(id, ..) => Some(id),
+                RestoreDocumentState(id, ..) => Some(id),
+                GeolocationUpdate(id, ..) => Some(id),
+                NotificationEvent(id, ..) => Some(id),
                 NavigateIframe(id, ..) => Some(id),
                 PostMessage { target: id, .. } => Some(id),
                 UpdatePipelineId(_, _, _, id, _) => Some(id),
@@ -2134,6 +2680,7 @@ impl ScriptThread {
                 DispatchStorageEvent(id, ..) => Some(id),
                 ReportCSSError(id, ..) => Some(id),
                 Reload(id, ..) => Some(id),
+                Stop(id) => Some(id),
                 PaintMetric(id, ..) => Some(id),
                 ExitFullScreen(id, ..) => Some(id),
                 MediaSessionAction(..) => None,
@@ -2276,6 +2823,24 @@ impl ScriptThread {
             ConstellationControlMsg::SetThrottled(pipeline_id, throttled) => {
                 self.handle_set_throttled_msg(pipeline_id, throttled)
             },
+            ConstellationControlMsg::SetMediaPolicy(webview_id, media_policy) => {
+                self.handle_set_media_policy_msg(webview_id, media_policy)
+            },
+            ConstellationControlMsg::EvaluateJavaScript(pipeline_id, evaluation_id, script) => {
+                self.handle_evaluate_javascript(pipeline_id, evaluation_id, script)
+            },
+            ConstellationControlMsg::GetDocumentState(pipeline_id, sender) => {
+                self.handle_get_document_state(pipeline_id, sender)
+            },
+            ConstellationControlMsg::RestoreDocumentState(pipeline_id, state) => {
+                self.handle_restore_document_state(pipeline_id, state)
+            },
+            ConstellationControlMsg::GeolocationUpdate(pipeline_id, update) => {
+                self.handle_geolocation_update(pipeline_id, update)
+            },
+            ConstellationControlMsg::NotificationEvent(pipeline_id, notification_id, event) => {
+                self.handle_notification_event(pipeline_id, notification_id, event)
+            },
             ConstellationControlMsg::SetThrottledInContainingIframe(
                 parent_pipeline_id,
                 browsing_context_id,
@@ -2345,6 +2910,7 @@ impl ScriptThread {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg)
             },
             ConstellationControlMsg::Reload(pipeline_id) => self.handle_reload(pipeline_id),
+            ConstellationControlMsg::Stop(pipeline_id) => self.handle_stop(pipeline_id),
             ConstellationControlMsg::ExitPipeline(pipeline_id, discard_browsing_context) => {
                 self.handle_exit_pipeline_msg(pipeline_id, discard_browsing_context)
             },
@@ -2510,12 +3076,21 @@ impl ScriptThread {
             DevtoolScriptControlMsg::GetRootNode(id, reply) => {
                 devtools::handle_get_root_node(&documents, id, reply)
             },
+            DevtoolScriptControlMsg::SetServerRunning(running) => {
+                devtools::handle_set_server_running(running)
+            },
             DevtoolScriptControlMsg::GetDocumentElement(id, reply) => {
                 devtools::handle_get_document_element(&documents, id, reply)
             },
             DevtoolScriptControlMsg::GetChildren(id, node_id, reply) => {
                 devtools::handle_get_children(&documents, id, node_id, reply)
             },
+            DevtoolScriptControlMsg::GetElementsFromPoint(id, x, y, reply) => {
+                devtools::handle_get_elements_from_point(&documents, id, x, y, reply)
+            },
+            DevtoolScriptControlMsg::GetSecurityInfo(id, reply) => {
+                devtools::handle_get_security_info(&documents, id, reply)
+            },
             DevtoolScriptControlMsg::GetLayout(id, node_id, reply) => {
                 devtools::handle_get_layout(&documents, id, node_id, reply)
             },
@@ -2538,6 +3113,65 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&documents, id, name)
             },
             DevtoolScriptControlMsg::Reload(id) => devtools::handle_reload(&documents, id),
+            DevtoolScriptControlMsg::SimulatePrintMedia(id, enabled) => {
+                devtools::handle_simulate_print_media(&documents, id, enabled)
+            },
+            DevtoolScriptControlMsg::Pause(id) => {
+                drop(documents);
+                self.pause_for_devtools(id)
+            },
+            // Only meaningful while paused, in which case `pause_for_devtools` handles it.
+            DevtoolScriptControlMsg::Resume(_) => {},
+        }
+    }
+
+    /// Run a nested event loop that only handles devtools messages, until the devtools ask to
+    /// resume `pipeline_id`. No task, timer or rendering update of any document of this event
+    /// loop runs in the meantime, but the devtools can still inspect them and evaluate script.
+    fn pause_for_devtools(&self, pipeline_id: PipelineId) {
+        if self.documents.borrow().find_document(pipeline_id).is_none() {
+            return warn!("Pause requested for closed pipeline {}.", pipeline_id);
+        }
+        if self.paused_for_devtools.replace(true) {
+            return;
+        }
+
+        // Waiting for the devtools is not a hang.
+        self.background_hang_monitor.borrow().notify_wait();
+        loop {
+            select! {
+                recv(self.devtools_port) -> msg => match msg {
+                    Ok(DevtoolScriptControlMsg::Resume(id)) if id == pipeline_id => break,
+                    Ok(msg) => self.handle_msg_from_devtools(msg),
+                    Err(_) => break,
+                },
+                recv(self.control_port) -> msg => {
+                    let Ok(msg) = msg else {
+                        break;
+                    };
+                    // The paused pipeline or the whole event loop going away ends the pause,
+                    // other messages wait until the event loop is resumed.
+                    let exiting = match msg {
+                        ConstellationControlMsg::ExitScriptThread => true,
+                        ConstellationControlMsg::ExitPipeline(id, _) => id == pipeline_id,
+                        _ => false,
+                    };
+                    self.held_constellation_msgs.borrow_mut().push_back(msg);
+                    if exiting {
+                        break;
+                    }
+                },
+            }
+        }
+        self.paused_for_devtools.set(false);
+    }
+
+    /// Receive the next message from the constellation without blocking, starting with those
+    /// that arrived while the event loop was paused by the devtools.
+    fn try_recv_constellation_msg(&self) -> Result<ConstellationControlMsg, TryRecvError> {
+        match self.held_constellation_msgs.borrow_mut().pop_front() {
+            Some(msg) => Ok(msg),
+            None => self.control_port.try_recv(),
         }
     }
 
@@ -2828,10 +3462,11 @@ impl ScriptThread {
             opener,
             load_data,
             window_size,
+            user_scripts,
         } = new_layout_info;
 
         // Kick off the fetch for the new resource.
-        let new_load = InProgressLoad::new(
+        let mut new_load = InProgressLoad::new(
             new_pipeline_id,
             browsing_context_id,
             top_level_browsing_context_id,
@@ -2842,6 +3477,8 @@ impl ScriptThread {
             origin,
             load_data.inherited_secure_context,
         );
+        new_load.user_scripts = user_scripts;
+        new_load.container_policy = load_data.container_policy.clone();
         if load_data.url.as_str() == "about:blank" {
             self.start_page_load_about_blank(new_load, load_data.js_eval_result);
         } else if load_data.url.as_str() == "about:srcdoc" {
@@ -2849,6 +3486,10 @@ impl ScriptThread {
         } else {
             self.pre_page_load(new_load, load_data);
         }
+
+        // This event loop may have outlived the pipeline it is registered under with the hang
+        // monitor.
+        self.rekey_background_hang_monitor();
     }
 
     fn collect_reports(&self, reports_chan: ReportsChan) {
@@ -2882,6 +3523,130 @@ impl ScriptThread {
         }
     }
 
+    /// Whether this event loop hosts documents and all of them are in a background tab.
+    fn all_documents_in_background(&self) -> bool {
+        let documents = self.documents.borrow();
+        documents.iter().next().is_some() &&
+            documents
+                .iter()
+                .all(|(_, document)| document.window().throttled())
+    }
+
+    /// Once every document of this event loop is in a background tab, nothing will be
+    /// rendered until one is shown again, so request a full GC to release memory without the
+    /// pause being noticeable. It runs the next time the event loop has no work queued,
+    /// rather than while handling the message that throttled the last document.
+    fn collect_garbage_if_in_background(&self) {
+        if self.all_documents_in_background() {
+            self.background_gc_pending.set(true);
+        }
+    }
+
+    fn handle_set_media_policy_msg(
+        &self,
+        webview_id: TopLevelBrowsingContextId,
+        media_policy: MediaPolicy,
+    ) {
+        self.media_policies
+            .borrow_mut()
+            .insert(webview_id, media_policy);
+        for (_, document) in self.documents.borrow().iter() {
+            let window = document.window();
+            if window.webview_id() == Some(webview_id) {
+                window.apply_media_policy();
+            }
+        }
+    }
+
+    fn handle_evaluate_javascript(
+        &self,
+        pipeline_id: PipelineId,
+        evaluation_id: JavaScriptEvaluationId,
+        script: String,
+    ) {
+        // The script might mutate `self.documents`, so it must not be borrowed while it runs.
+        let window = self.documents.borrow().find_window(pipeline_id);
+        match window {
+            Some(window) => {
+                javascript_evaluation::handle_evaluate_javascript(&window, evaluation_id, script)
+            },
+            None => {
+                warn!(
+                    "EvaluateJavaScript sent to nonexistent pipeline {}",
+                    pipeline_id
+                );
+                let msg = EmbedderMsg::JavaScriptEvaluated(
+                    evaluation_id,
+                    Err(JavaScriptEvaluationError::WebViewNotFound),
+                );
+                self.script_sender
+                    .send((pipeline_id, ScriptMsg::ForwardToEmbedder(msg)))
+                    .unwrap();
+            },
+        }
+    }
+
+    fn handle_get_document_state(
+        &self,
+        pipeline_id: PipelineId,
+        sender: IpcSender<(PipelineId, Option<SavedDocumentState>)>,
+    ) {
+        // The constellation waits for an answer from every document it asked, so one is sent
+        // even if the document is gone.
+        let state = self
+            .documents
+            .borrow()
+            .find_document(pipeline_id)
+            .map(|document| document_state::collect_document_state(&document));
+        if state.is_none() {
+            warn!(
+                "GetDocumentState sent to nonexistent pipeline {}",
+                pipeline_id
+            );
+        }
+        let _ = sender.send((pipeline_id, state));
+    }
+
+    fn handle_restore_document_state(&self, pipeline_id: PipelineId, state: SavedDocumentState) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => {
+                return warn!(
+                    "RestoreDocumentState sent to nonexistent pipeline {}",
+                    pipeline_id
+                )
+            },
+        };
+        document_state::restore_document_state(&document, &state);
+    }
+
+    fn handle_geolocation_update(
+        &self,
+        pipeline_id: PipelineId,
+        update: embedder_traits::GeolocationUpdate,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            warn!("Received geolocation update for closed pipeline {pipeline_id}");
+            return;
+        };
+        if let Some(geolocation) = window.Navigator().geolocation() {
+            geolocation.handle_update(update);
+        }
+    }
+
+    fn handle_notification_event(
+        &self,
+        pipeline_id: PipelineId,
+        notification_id: NotificationId,
+        event: embedder_traits::NotificationEvent,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            warn!("Received notification event for closed pipeline {pipeline_id}");
+            return;
+        };
+        window.handle_notification_event(notification_id, event);
+    }
+
     fn handle_set_throttled_msg(&self, id: PipelineId, throttled: bool) {
         // Separate message sent since parent script thread could be different (Iframe of different
         // domain)
@@ -2893,6 +3658,7 @@ impl ScriptThread {
         match window {
             Some(window) => {
                 window.set_throttled(throttled);
+                self.collect_garbage_if_in_background();
                 return;
             },
             None => {
@@ -3092,11 +3858,19 @@ impl ScriptThread {
             Some(idx) => {
                 // https://html.spec.whatwg.org/multipage/#process-a-navigate-response
                 // 2. If response's status is 204 or 205, then abort these steps.
-                if let Some(Metadata {
-                    status: Some((204..=205, _)),
-                    ..
-                }) = metadata
-                {
+                let is_no_content = matches!(
+                    metadata,
+                    Some(Metadata {
+                        status: Some((204..=205, _)),
+                        ..
+                    })
+                );
+                // Responses that cannot be displayed are handed to the embedder instead.
+                // https://html.spec.whatwg.org/multipage/#handle-as-a-download
+                let download = metadata
+                    .as_ref()
+                    .filter(|metadata| !is_no_content && servoparser::is_download(metadata));
+                if is_no_content || download.is_some() {
                     // If we have an existing window that is being navigated:
                     if let Some(window) = self.documents.borrow().find_window(*id) {
                         let window_proxy = window.window_proxy();
@@ -3111,6 +3885,23 @@ impl ScriptThread {
                             window_proxy.stop_delaying_load_events_mode();
                         }
                     }
+                    if let Some(metadata) = download {
+                        let request = DownloadRequest {
+                            url: metadata.final_url.clone(),
+                            pipeline_id: *id,
+                            suggested_filename: metadata.suggested_filename(),
+                            mime_type: metadata
+                                .content_type
+                                .clone()
+                                .map(|content_type| Mime::from(content_type.into_inner()))
+                                .map(|mime| mime.essence_str().to_owned()),
+                        };
+                        let fetch_request =
+                            self.incomplete_loads.borrow_mut()[idx].take_download_request(metadata);
+                        self.script_sender
+                            .send((*id, ScriptMsg::DownloadRequested(request, fetch_request)))
+                            .unwrap();
+                    }
                     self.script_sender
                         .send((*id, ScriptMsg::AbortLoadUrl))
                         .unwrap();
@@ -3138,6 +3929,10 @@ impl ScriptThread {
         GamepadTaskSource(self.gamepad_task_sender.clone(), pipeline_id)
     }
 
+    pub fn idle_task_source(&self, pipeline_id: PipelineId) -> IdleTaskSource {
+        IdleTaskSource(self.idle_task_sender.clone(), pipeline_id)
+    }
+
     pub fn media_element_task_source(&self, pipeline_id: PipelineId) -> MediaElementTaskSource {
         MediaElementTaskSource(self.media_element_task_sender.clone(), pipeline_id)
     }
@@ -3251,6 +4046,8 @@ impl ScriptThread {
             window.clear_js_runtime();
         }
 
+        self.rekey_background_hang_monitor();
+
         debug!("{id}: Finished pipeline exit");
     }
 
@@ -3278,7 +4075,7 @@ impl ScriptThread {
             self.handle_exit_pipeline_msg(pipeline_id, DiscardBrowsingContext::Yes);
         }
 
-        self.background_hang_monitor.unregister();
+        self.background_hang_monitor.borrow().unregister();
 
         // If we're in multiprocess mode, shut-down the IPC router for this process.
         if opts::multiprocess() {
@@ -3301,6 +4098,24 @@ impl ScriptThread {
         });
     }
 
+    /// Runs the tasks currently queued on the given task source, performing a microtask
+    /// checkpoint after each of them, and returns the number of tasks that were run.
+    /// Only used by tests.
+    pub fn drain_task_source_for_testing(task_source: TaskSourceName) -> u32 {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            let tasks = script_thread
+                .task_queue
+                .take_tasks_from_source(&task_source);
+            let count = tasks.len() as u32;
+            for task in tasks {
+                script_thread.handle_msg_from_script(task);
+                script_thread.perform_a_microtask_checkpoint();
+            }
+            count
+        })
+    }
+
     /// Handles a Web font being loaded. Does nothing if the page no longer exists.
     fn handle_web_font_loaded(&self, pipeline_id: PipelineId, _success: bool) {
         let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
@@ -3309,7 +4124,7 @@ impl ScriptThread {
         };
 
         // TODO: This should only dirty nodes that are waiting for a web font to finish loading!
-        document.dirty_all_nodes();
+        document.dirty_all_nodes_for_web_font();
         document.window().add_pending_reflow();
 
         // This is required because the handlers added to the promise exposed at
@@ -3538,6 +4353,7 @@ impl ScriptThread {
             self.file_reading_task_source(incomplete.pipeline_id),
             self.gamepad_task_source(incomplete.pipeline_id),
             self.history_traversal_task_source(incomplete.pipeline_id),
+            self.idle_task_source(incomplete.pipeline_id),
             self.media_element_task_source(incomplete.pipeline_id),
             self.networking_task_source(incomplete.pipeline_id),
             self.performance_timeline_task_source(incomplete.pipeline_id)
@@ -3607,6 +4423,8 @@ impl ScriptThread {
             self.unminify_js,
             self.local_script_source.clone(),
             self.userscripts_path.clone(),
+            incomplete.user_scripts.clone(),
+            incomplete.container_policy.clone(),
             self.headless,
             self.replace_surrogates,
             self.user_agent.clone(),
@@ -3730,6 +4548,8 @@ impl ScriptThread {
         document.set_https_state(metadata.https_state);
         document.set_navigation_start(incomplete.navigation_start_precise);
 
+        run_user_scripts(&document, UserScriptRunAt::DocumentStart);
+
         if is_html_document == IsHTMLDocument::NonHTMLDocument {
             ServoParser::parse_xml_document(&document, None, final_url);
         } else {
@@ -3951,18 +4771,23 @@ impl ScriptThread {
     /// argument until a notification is received that the fetch is complete.
     fn pre_page_load(&self, mut incomplete: InProgressLoad, load_data: LoadData) {
         let id = incomplete.pipeline_id;
+        let initiated_by_embedder = matches!(load_data.load_origin, LoadOrigin::Constellation) &&
+            incomplete.browsing_context_id ==
+                BrowsingContextId::from(incomplete.top_level_browsing_context_id);
         let req_init = RequestBuilder::new(load_data.url.clone(), load_data.referrer)
             .method(load_data.method)
             .destination(Destination::Document)
             .credentials_mode(CredentialsMode::Include)
             .use_url_credentials(true)
             .pipeline_id(Some(id))
+            .target_webview_id(Some(incomplete.top_level_browsing_context_id))
             .referrer_policy(load_data.referrer_policy)
             .headers(load_data.headers)
             .body(load_data.data)
             .redirect_mode(RedirectMode::Manual)
             .origin(incomplete.origin.immutable().clone())
-            .crash(load_data.crash);
+            .crash(load_data.crash)
+            .initiated_by_embedder(initiated_by_embedder);
 
         let context = ParserContext::new(id, load_data.url);
         self.incomplete_parser_contexts
@@ -3971,6 +4796,7 @@ impl ScriptThread {
             .push((id, context));
 
         let cancel_chan = incomplete.canceller.initialize();
+        incomplete.request = Some(req_init.clone());
 
         self.script_sender
             .send((
@@ -4114,6 +4940,13 @@ impl ScriptThread {
         }
     }
 
+    fn handle_stop(&self, pipeline_id: PipelineId) {
+        let window = self.documents.borrow().find_window(pipeline_id);
+        if let Some(window) = window {
+            window.Stop();
+        }
+    }
+
     fn handle_paint_metric(
         &self,
         pipeline_id: PipelineId,
@@ -4161,12 +4994,65 @@ impl ScriptThread {
                 .map(|(_id, document)| DomRoot::from_ref(document.window().upcast()))
                 .collect();
 
-            self.microtask_queue.checkpoint(
+            let outcome = self.microtask_queue.checkpoint(
                 self.get_cx(),
                 |id| self.documents.borrow().find_global(id),
                 globals,
-            )
+                |pipeline_id| self.queue_microtask_checkpoint_continuation(pipeline_id),
+            );
+            match outcome {
+                CheckpointOutcome::Finished => {
+                    if self.microtask_queue.empty() {
+                        self.runaway_microtasks_since.set(None);
+                        self.runaway_microtasks_reported.set(false);
+                    }
+                },
+                CheckpointOutcome::OutOfBudget(pipeline_id) => {
+                    self.handle_runaway_microtasks(pipeline_id)
+                },
+            }
+        }
+    }
+
+    /// The microtasks left by a checkpoint that ran out of its budget run in the checkpoint that
+    /// follows the next task, so make sure that there is one even if nothing else is queued.
+    fn queue_microtask_checkpoint_continuation(&self, pipeline_id: Option<PipelineId>) {
+        let task = task!(continue_microtask_checkpoint: move || {});
+        let _ = self.chan.send(CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ScriptEvent,
+            Box::new(task),
+            pipeline_id,
+            TaskSourceName::DOMManipulation,
+        ));
+    }
+
+    /// A microtask checkpoint ran out of its budget, most likely because a page keeps queueing
+    /// microtasks. Once this went on for as long as a slow script, ask the embedder whether to
+    /// stop the page's script, which terminates the microtask that runs next.
+    fn handle_runaway_microtasks(&self, pipeline_id: Option<PipelineId>) {
+        let since = match self.runaway_microtasks_since.get() {
+            Some(since) => since,
+            None => {
+                let now = Instant::now();
+                self.runaway_microtasks_since.set(Some(now));
+                now
+            },
+        };
+        let slow_script_timeout =
+            Duration::from_millis(pref!(js.slow_script.timeout_ms).max(0) as u64);
+        if self.runaway_microtasks_reported.get() || since.elapsed() < slow_script_timeout {
+            return;
         }
+        let Some(window) = pipeline_id.and_then(|id| self.documents.borrow().find_window(id))
+        else {
+            return;
+        };
+        // The slow script is reported for the pipeline the hang monitor knows this event loop
+        // by, so that stopping it interrupts this thread, but to the webview of the page.
+        self.runaway_microtasks_reported.set(true);
+        window.send_to_embedder(EmbedderMsg::SlowScript(
+            self.background_hang_monitor_pipeline_id.get(),
+        ));
     }
 }
 
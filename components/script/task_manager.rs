@@ -12,6 +12,7 @@ use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::gamepad::GamepadTaskSource;
 use crate::task_source::history_traversal::HistoryTraversalTaskSource;
+use crate::task_source::idle::IdleTaskSource;
 use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
@@ -48,6 +49,8 @@ pub struct TaskManager {
     #[ignore_malloc_size_of = "task sources are hard"]
     history_traversal_task_source: HistoryTraversalTaskSource,
     #[ignore_malloc_size_of = "task sources are hard"]
+    idle_task_source: IdleTaskSource,
+    #[ignore_malloc_size_of = "task sources are hard"]
     media_element_task_source: MediaElementTaskSource,
     #[ignore_malloc_size_of = "task sources are hard"]
     networking_task_source: NetworkingTaskSource,
@@ -74,6 +77,7 @@ impl TaskManager {
         file_reading_task_source: FileReadingTaskSource,
         gamepad_task_source: GamepadTaskSource,
         history_traversal_task_source: HistoryTraversalTaskSource,
+        idle_task_source: IdleTaskSource,
         media_element_task_source: MediaElementTaskSource,
         networking_task_source: NetworkingTaskSource,
         performance_timeline_task_source: PerformanceTimelineTaskSource,
@@ -89,6 +93,7 @@ impl TaskManager {
             file_reading_task_source,
             gamepad_task_source,
             history_traversal_task_source,
+            idle_task_source,
             media_element_task_source,
             networking_task_source,
             performance_timeline_task_source,
@@ -118,6 +123,14 @@ impl TaskManager {
         Gamepad
     );
 
+    task_source_functions!(
+        self,
+        idle_task_source_with_canceller,
+        idle_task_source,
+        IdleTaskSource,
+        Idle
+    );
+
     task_source_functions!(
         self,
         media_element_task_source_with_canceller,
@@ -132,7 +132,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             };
 
             match task_source {
-                TaskSourceName::PerformanceTimeline => {
+                TaskSourceName::PerformanceTimeline | TaskSourceName::Idle => {
                     to_be_throttled.push(incoming.remove(index - 1));
                     index -= 1; // We've removed an element, so the next has the same index.
                 },
@@ -187,6 +187,12 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         &self.port
     }
 
+    /// Whether no task is waiting to be taken, either on the port or in the queue. Unlike
+    /// `select()`, this doesn't start a new iteration of the event-loop.
+    pub fn is_empty(&self) -> bool {
+        self.port.is_empty() && self.msg_queue.borrow().is_empty()
+    }
+
     /// Take a message from the front of the queue, without waiting if empty.
     pub fn recv(&self) -> Result<T, ()> {
         self.msg_queue.borrow_mut().pop_front().ok_or(())
@@ -198,6 +204,38 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         self.recv()
     }
 
+    /// Remove every task currently queued on the given task-source, including throttled ones,
+    /// in the order in which they should run. Tasks queued by running them are left in the queue.
+    pub fn take_tasks_from_source(&self, task_source: &TaskSourceName) -> Vec<T> {
+        let fully_active = ScriptThread::get_fully_active_document_ids();
+        self.process_incoming_tasks(T::wake_up_msg(), &fully_active);
+
+        let mut msg_queue = self.msg_queue.borrow_mut();
+        let (mut taken, remaining): (Vec<T>, VecDeque<T>) = msg_queue
+            .drain(..)
+            .partition(|msg| msg.task_source_name() == Some(task_source));
+        *msg_queue = remaining;
+        drop(msg_queue);
+
+        let throttled = self.throttled.borrow_mut().remove(task_source);
+        for queued_task in throttled.into_iter().flatten() {
+            let msg = T::from_queued_task(queued_task);
+            match msg.pipeline_id() {
+                Some(pipeline_id) if !fully_active.contains(&pipeline_id) => {
+                    self.store_task_for_inactive_pipeline(msg, &pipeline_id);
+                },
+                _ => taken.push(msg),
+            }
+        }
+
+        if !self.msg_queue.borrow().is_empty() {
+            // The port was drained above, make sure the event-loop still
+            // wakes up for the tasks left in the queue.
+            let _ = self.wake_up_sender.send(T::wake_up_msg());
+        }
+        taken
+    }
+
     /// Drain the queue for the current iteration of the event-loop.
     /// Holding-back throttles above a given high-water mark.
     pub fn take_tasks(&self, first_msg: T) {
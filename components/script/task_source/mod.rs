@@ -6,6 +6,7 @@ pub mod dom_manipulation;
 pub mod file_reading;
 pub mod gamepad;
 pub mod history_traversal;
+pub mod idle;
 pub mod media_element;
 pub mod networking;
 pub mod performance_timeline;
@@ -44,6 +45,8 @@ pub enum TaskSourceName {
     Timer,
     /// <https://www.w3.org/TR/gamepad/#dfn-gamepad-task-source>
     Gamepad,
+    /// <https://w3c.github.io/requestidlecallback/#dfn-idle-task-source>
+    Idle,
 }
 
 impl TaskSourceName {
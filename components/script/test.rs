@@ -60,6 +60,20 @@ pub mod size_of {
     }
 }
 
+pub mod autoplay {
+    pub use embedder_traits::AutoplayPolicy;
+
+    pub use crate::dom::window::autoplay_policy_allows;
+}
+
+pub mod contenteditable {
+    pub use crate::dom::htmlelement::is_editable_by_contenteditable;
+}
+
+pub mod mixed_replace {
+    pub use crate::dom::htmlimageelement::MixedReplaceFirstPart;
+}
+
 pub mod srcset {
     pub use crate::dom::htmlimageelement::{parse_a_srcset_attribute, Descriptor, ImageSource};
 }
@@ -10,8 +10,10 @@ use std::default::Default;
 use std::ops::{Add, AddAssign, Range};
 use std::usize;
 
+use embedder_traits::{InputMethodType, TextInputState};
 use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
 use unicode_segmentation::UnicodeSegmentation;
+use webrender_api::units::DeviceIntRect;
 
 use crate::clipboard_provider::ClipboardProvider;
 use crate::dom::bindings::str::DOMString;
@@ -154,6 +156,14 @@ impl TextPoint {
     }
 }
 
+/// The part of the content that an input method is currently composing, as byte offsets from
+/// the start of the content.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+struct Composition {
+    start: UTF8Bytes,
+    end: UTF8Bytes,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub struct SelectionState {
     start: TextPoint,
@@ -189,6 +199,9 @@ pub struct TextInput<T: ClipboardProvider> {
 
     /// Was last change made by set_content?
     was_last_change_by_set_content: bool,
+
+    /// The text of an in-progress input method composition, if any.
+    composition: Option<Composition>,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -275,6 +288,7 @@ impl<T: ClipboardProvider> TextInput<T> {
             min_length,
             selection_direction,
             was_last_change_by_set_content: true,
+            composition: None,
         };
         i.set_content(initial);
         i
@@ -838,6 +852,34 @@ impl<T: ClipboardProvider> TextInput<T> {
         self.handle_keydown_aux(key, mods, cfg!(target_os = "macos"))
     }
 
+    /// The `inputType` and `data` of the `beforeinput` and `input` events reported
+    /// for the edit that a keydown would perform, or `None` if it does not edit.
+    /// <https://w3c.github.io/input-events/#interface-InputEvent-Attributes>
+    pub fn input_type_for_keydown(
+        &self,
+        event: &KeyboardEvent,
+    ) -> Option<(&'static str, Option<DOMString>)> {
+        let mut mods = event.modifiers();
+        mods.remove(Modifiers::SHIFT);
+        match event.key() {
+            Key::Character(ref c) if mods == CMD_OR_CONTROL => {
+                if c.eq_ignore_ascii_case("x") && self.has_selection() {
+                    Some(("deleteByCut", None))
+                } else if c.eq_ignore_ascii_case("v") {
+                    Some(("insertFromPaste", None))
+                } else {
+                    None
+                }
+            },
+            Key::Character(_) if mods.intersects(Modifiers::CONTROL | Modifiers::META) => None,
+            Key::Character(c) => Some(("insertText", Some(DOMString::from(c)))),
+            Key::Backspace if mods.is_empty() => Some(("deleteContentBackward", None)),
+            Key::Delete if mods.is_empty() => Some(("deleteContentForward", None)),
+            Key::Enter if mods.is_empty() && self.multiline => Some(("insertLineBreak", None)),
+            _ => None,
+        }
+    }
+
     // This function exists for easy unit testing.
     // To test Mac OS shortcuts on other systems a flag is passed.
     pub fn handle_keydown_aux(
@@ -975,11 +1017,60 @@ impl<T: ClipboardProvider> TextInput<T> {
             .unwrap()
     }
 
+    pub fn handle_compositionupdate(&mut self, event: &CompositionEvent) -> KeyReaction {
+        self.set_composition(event.data());
+        KeyReaction::DispatchInput
+    }
+
     pub fn handle_compositionend(&mut self, event: &CompositionEvent) -> KeyReaction {
-        self.insert_string(event.data());
+        self.set_composition(event.data());
+        self.composition = None;
         KeyReaction::DispatchInput
     }
 
+    /// Replace the text of the in-progress composition with `text`, starting a new composition
+    /// in place of the current selection if there is none.
+    pub fn set_composition<S: Into<String>>(&mut self, text: S) {
+        if let Some(composition) = self.composition {
+            self.set_selection_range(
+                composition.start.0 as u32,
+                composition.end.0 as u32,
+                SelectionDirection::None,
+            );
+        }
+        let start = self.selection_start_offset();
+        self.insert_string(text);
+        self.composition = Some(Composition {
+            start,
+            end: self.text_point_to_offset(&self.edit_point),
+        });
+    }
+
+    /// The byte offsets of the text of the in-progress composition, if any.
+    pub fn composition_range(&self) -> Option<Range<UTF8Bytes>> {
+        self.composition
+            .map(|composition| composition.start..composition.end)
+    }
+
+    /// The state of this text input for the input method of the embedder.
+    pub fn text_input_state(
+        &self,
+        input_method_type: InputMethodType,
+        caret_rect: DeviceIntRect,
+    ) -> TextInputState {
+        let selection = self.sorted_selection_offsets_range();
+        TextInputState {
+            input_method_type,
+            text: self.get_content().into(),
+            selection: selection.start.0..selection.end.0,
+            composition: self
+                .composition
+                .map(|composition| composition.start.0..composition.end.0),
+            multiline: self.multiline,
+            caret_rect,
+        }
+    }
+
     /// Whether the content is empty.
     pub fn is_empty(&self) -> bool {
         self.lines.len() <= 1 && self.lines.get(0).map_or(true, |line| line.is_empty())
@@ -1046,6 +1137,7 @@ impl<T: ClipboardProvider> TextInput<T> {
         };
 
         self.was_last_change_by_set_content = true;
+        self.composition = None;
         self.edit_point = self.edit_point.constrain_to(&self.lines);
 
         if let Some(origin) = self.selection_origin {
@@ -27,9 +27,12 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::FakeRequestAnimationFrameCallback;
 use crate::dom::eventsource::EventSourceTimeoutCallback;
+use crate::dom::geolocation::GeolocationTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlmetaelement::RefreshRedirectDue;
+use crate::dom::scheduler::ScheduledTaskDelayCallback;
 use crate::dom::testbinding::TestBindingCallback;
+use crate::dom::window::IdleCallbackTimeoutCallback;
 use crate::dom::xmlhttprequest::XHRTimeoutCallback;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_thread::ScriptThread;
@@ -91,6 +94,9 @@ pub enum OneshotTimerCallback {
     TestBindingCallback(TestBindingCallback),
     FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
     RefreshRedirectDue(RefreshRedirectDue),
+    GeolocationTimeout(GeolocationTimeoutCallback),
+    ScheduledTaskDelay(ScheduledTaskDelayCallback),
+    IdleCallbackTimeout(IdleCallbackTimeoutCallback),
 }
 
 impl OneshotTimerCallback {
@@ -102,6 +108,9 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(),
             OneshotTimerCallback::RefreshRedirectDue(callback) => callback.invoke(),
+            OneshotTimerCallback::GeolocationTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::ScheduledTaskDelay(callback) => callback.invoke(),
+            OneshotTimerCallback::IdleCallbackTimeout(callback) => callback.invoke(),
         }
     }
 }
@@ -191,6 +200,18 @@ impl OneshotTimers {
         }
     }
 
+    /// How long until the next timer is due, or `None` if there is no timer or the timers are
+    /// suspended.
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        if self.suspended_since.get().is_some() {
+            return None;
+        }
+        let next_timer_time = self.timers.borrow().last()?.scheduled_for;
+        Some(MsDuration::new(
+            next_timer_time.get().saturating_sub(self.base_time().get()),
+        ))
+    }
+
     fn is_next_timer(&self, handle: OneshotTimerHandle) -> bool {
         match self.timers.borrow().last() {
             None => false,
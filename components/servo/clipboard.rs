@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders provide the clipboard that pages use, both when the user copies and pastes
+//! in text fields and with the asynchronous Clipboard API.
+
+use base::id::TopLevelBrowsingContextId;
+use embedder_traits::ClipboardContents;
+
+/// Reads and writes the clipboard, on the thread that handles the events of Servo. The page
+/// that uses the clipboard waits until the delegate returns.
+pub trait ClipboardDelegate {
+    /// The contents of the clipboard, in the formats that are available.
+    fn clipboard_contents(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+    ) -> ClipboardContents;
+
+    /// Replace the contents of the clipboard. All the formats that are set describe the same
+    /// contents, so embedders can keep the one they support best.
+    fn set_clipboard_contents(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        contents: ClipboardContents,
+    );
+}
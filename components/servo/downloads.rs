@@ -0,0 +1,359 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Saves the resources that navigations hand over to the embedder instead of displaying them.
+
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use compositing_traits::ConstellationMsg;
+use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use embedder_traits::DownloadRequest;
+use headers::{ContentLength, HeaderMapExt};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use log::{info, warn};
+use net_traits::{FetchChannels, FetchMetadata, FetchResponseMsg};
+
+static NEXT_DOWNLOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// How many times to ask the delegate for another destination when the one it returned was
+/// created by someone else in the meantime.
+const MAX_DESTINATION_ATTEMPTS: usize = 16;
+
+/// Identifies a download for as long as this instance of Servo runs.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DownloadId(u64);
+
+/// Why a download did not complete.
+#[derive(Debug)]
+pub enum DownloadError {
+    /// The download was cancelled with [`Download::cancel`].
+    Cancelled,
+    /// The resource could not be fetched.
+    Network(String),
+    /// The destination file could not be written.
+    Io(io::Error),
+}
+
+/// Decides where downloads are saved and follows their progress. All the methods but
+/// `destination` are called on the thread of the download.
+pub trait DownloadDelegate: Send + Sync {
+    /// The file to save the given resource to, or `None` not to download it. Existing files are
+    /// never overwritten: if the file exists by the time the download creates it, this is
+    /// called again.
+    fn destination(&self, request: &DownloadRequest) -> Option<PathBuf>;
+
+    /// A download started. The given handle can pause, resume or cancel it.
+    fn download_started(&self, _download: &Download) {}
+
+    /// More of a download was written. `total_bytes` is the length of the resource, if the
+    /// server reported it.
+    fn download_progressed(
+        &self,
+        _download: &Download,
+        _received_bytes: u64,
+        _total_bytes: Option<u64>,
+    ) {
+    }
+
+    /// A download completed, failed or was cancelled. Unless it completed, the partially
+    /// written file was removed.
+    fn download_finished(&self, _download: &Download, _result: Result<(), DownloadError>) {}
+}
+
+/// A [`DownloadDelegate`] that saves everything in a directory, under the file name that the
+/// response suggested, without overwriting existing files.
+pub struct DirectoryDownloadDelegate {
+    directory: PathBuf,
+}
+
+impl DirectoryDownloadDelegate {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+impl DownloadDelegate for DirectoryDownloadDelegate {
+    fn destination(&self, request: &DownloadRequest) -> Option<PathBuf> {
+        if let Err(error) = fs::create_dir_all(&self.directory) {
+            warn!(
+                "Could not create download directory {} ({error}).",
+                self.directory.display()
+            );
+            return None;
+        }
+        let filename = request
+            .suggested_filename
+            .as_deref()
+            .map(sanitize_filename)
+            .filter(|filename| !filename.is_empty())
+            .unwrap_or_else(|| "download".to_owned());
+        let path = Path::new(&filename);
+        let stem = path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .unwrap_or(&filename);
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|extension| format!(".{extension}"))
+            .unwrap_or_default();
+        (0..)
+            .map(|copy| match copy {
+                0 => self.directory.join(&filename),
+                _ => self.directory.join(format!("{stem} ({copy}){extension}")),
+            })
+            .find(|path| !path.exists())
+    }
+
+    fn download_finished(&self, download: &Download, result: Result<(), DownloadError>) {
+        match result {
+            Ok(()) => info!(
+                "Downloaded {} to {}.",
+                download.request().url,
+                download.path().display()
+            ),
+            Err(error) => warn!("Downloading {} failed ({error:?}).", download.request().url),
+        }
+    }
+}
+
+/// Turn a suggested file name into one that cannot escape the download directory or create a
+/// hidden file.
+fn sanitize_filename(filename: &str) -> String {
+    let filename: String = filename
+        .chars()
+        .map(|character| match character {
+            '/' | '\\' => '_',
+            character if character.is_control() => '_',
+            character => character,
+        })
+        .collect();
+    filename.trim().trim_start_matches('.').to_owned()
+}
+
+enum DownloadControl {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// A handle to a download, which stays valid after the download finished.
+#[derive(Clone)]
+pub struct Download {
+    id: DownloadId,
+    request: DownloadRequest,
+    path: PathBuf,
+    control_sender: Sender<DownloadControl>,
+}
+
+impl Download {
+    pub fn id(&self) -> DownloadId {
+        self.id
+    }
+
+    pub fn request(&self) -> &DownloadRequest {
+        &self.request
+    }
+
+    /// The file that the resource is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop writing the resource until the download is resumed. The resource keeps being
+    /// received in the meantime, and what was not written yet is held in memory.
+    pub fn pause(&self) {
+        let _ = self.control_sender.send(DownloadControl::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.control_sender.send(DownloadControl::Resume);
+    }
+
+    pub fn cancel(&self) {
+        let _ = self.control_sender.send(DownloadControl::Cancel);
+    }
+}
+
+/// Starts the downloads that navigations requested, on behalf of a [`DownloadDelegate`].
+pub(crate) struct DownloadManager {
+    constellation_chan: Sender<ConstellationMsg>,
+    delegate: Arc<dyn DownloadDelegate>,
+}
+
+impl DownloadManager {
+    pub(crate) fn new(
+        constellation_chan: Sender<ConstellationMsg>,
+        delegate: Arc<dyn DownloadDelegate>,
+    ) -> Self {
+        Self {
+            constellation_chan,
+            delegate,
+        }
+    }
+
+    pub(crate) fn start(&self, request: DownloadRequest) {
+        let Some((path, file)) = self.create_destination(&request) else {
+            let _ = self
+                .constellation_chan
+                .send(ConstellationMsg::DiscardDownload(request.pipeline_id));
+            return;
+        };
+        let (control_sender, control_receiver) = unbounded();
+        let download = Download {
+            id: DownloadId(NEXT_DOWNLOAD_ID.fetch_add(1, Ordering::Relaxed)),
+            request,
+            path,
+            control_sender,
+        };
+        let constellation_chan = self.constellation_chan.clone();
+        let delegate = self.delegate.clone();
+        let spawned = thread::Builder::new()
+            .name("Download".to_owned())
+            .spawn(move || {
+                let result = run_download(
+                    file,
+                    &download,
+                    &constellation_chan,
+                    &*delegate,
+                    control_receiver,
+                );
+                delegate.download_finished(&download, result);
+            });
+        if let Err(error) = spawned {
+            warn!("Could not spawn a download thread ({error}).");
+        }
+    }
+
+    /// Create the file chosen by the delegate, without overwriting an existing one.
+    fn create_destination(&self, request: &DownloadRequest) -> Option<(PathBuf, File)> {
+        for _ in 0..MAX_DESTINATION_ATTEMPTS {
+            let path = self.delegate.destination(request)?;
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Some((path, file)),
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+                Err(error) => {
+                    warn!(
+                        "Could not create download file {} ({error}).",
+                        path.display()
+                    );
+                    return None;
+                },
+            }
+        }
+        warn!("Could not find a download file that does not exist yet.");
+        None
+    }
+}
+
+fn run_download(
+    file: File,
+    download: &Download,
+    constellation_chan: &Sender<ConstellationMsg>,
+    delegate: &dyn DownloadDelegate,
+    control_receiver: Receiver<DownloadControl>,
+) -> Result<(), DownloadError> {
+    let result = fetch_to_file(
+        BufWriter::new(file),
+        download,
+        constellation_chan,
+        delegate,
+        control_receiver,
+    );
+    if result.is_err() {
+        let _ = fs::remove_file(download.path());
+    }
+    result
+}
+
+fn fetch_to_file(
+    mut writer: BufWriter<File>,
+    download: &Download,
+    constellation_chan: &Sender<ConstellationMsg>,
+    delegate: &dyn DownloadDelegate,
+    control_receiver: Receiver<DownloadControl>,
+) -> Result<(), DownloadError> {
+    // TODO: Take over the response of the navigation instead of fetching the resource again
+    // with its request, which repeats requests that are not idempotent.
+    let (response_sender, response_receiver) = ipc::channel().unwrap();
+    let (cancel_sender, cancel_receiver) = ipc::channel().unwrap();
+    constellation_chan
+        .send(ConstellationMsg::FetchDownload(
+            download.request.pipeline_id,
+            FetchChannels::ResponseMsg(response_sender, Some(cancel_receiver)),
+        ))
+        .map_err(|error| DownloadError::Network(format!("{error:?}")))?;
+    let response_receiver = ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(response_receiver);
+
+    delegate.download_started(download);
+    let mut paused = false;
+    let mut received_bytes = 0;
+    let mut total_bytes = None;
+    loop {
+        // While paused, only wait for the download to be resumed or cancelled.
+        let control = if paused {
+            control_receiver.recv().ok()
+        } else {
+            select! {
+                recv(control_receiver) -> control => control.ok(),
+                recv(response_receiver) -> message => {
+                    let Ok(message) = message else {
+                        return Err(DownloadError::Network("The fetch was dropped.".to_owned()));
+                    };
+                    match message {
+                        FetchResponseMsg::ProcessResponse(Ok(metadata)) => {
+                            let metadata = match metadata {
+                                FetchMetadata::Unfiltered(metadata) => metadata,
+                                FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+                            };
+                            if let Some((status, _)) = metadata
+                                .status
+                                .filter(|(status, _)| !(200..300).contains(status))
+                            {
+                                let _ = cancel_sender.send(());
+                                return Err(DownloadError::Network(format!("HTTP status {status}")));
+                            }
+                            total_bytes = metadata
+                                .headers
+                                .as_ref()
+                                .and_then(|headers| headers.typed_get::<ContentLength>())
+                                .map(|content_length| content_length.0);
+                        },
+                        FetchResponseMsg::ProcessResponseChunk(bytes) => {
+                            writer.write_all(&bytes).map_err(DownloadError::Io)?;
+                            received_bytes += bytes.len() as u64;
+                            delegate.download_progressed(download, received_bytes, total_bytes);
+                        },
+                        FetchResponseMsg::ProcessResponseEOF(Ok(_)) => {
+                            return writer.flush().map_err(DownloadError::Io);
+                        },
+                        FetchResponseMsg::ProcessResponse(Err(error)) |
+                        FetchResponseMsg::ProcessResponseEOF(Err(error)) => {
+                            return Err(DownloadError::Network(format!("{error:?}")));
+                        },
+                        FetchResponseMsg::ProcessRequestBody |
+                        FetchResponseMsg::ProcessRequestEOF => {},
+                    }
+                    None
+                },
+            }
+        };
+        match control {
+            Some(DownloadControl::Pause) => paused = true,
+            Some(DownloadControl::Resume) => paused = false,
+            Some(DownloadControl::Cancel) => {
+                let _ = cancel_sender.send(());
+                return Err(DownloadError::Cancelled);
+            },
+            None => {},
+        }
+    }
+}
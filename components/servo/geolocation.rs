@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders provide the position of the device to `navigator.geolocation`, which is only
+//! exposed when the `dom.geolocation.enabled` preference is set.
+
+use compositing_traits::ConstellationMsg;
+use crossbeam_channel::Sender;
+use embedder_traits::{GeolocationPosition, GeolocationUpdate};
+use log::warn;
+
+/// Acquires the position of the device while pages wait for it, on the thread that handles the
+/// events of Servo. Pages are asked for permission before they can wait for it.
+pub trait GeolocationDelegate {
+    /// Start acquiring the position of the device, and report it with `updater` whenever it
+    /// changes. This is called again when the accuracy that pages ask for changes.
+    fn start_updates(&self, high_accuracy: bool, updater: GeolocationUpdater);
+
+    /// Stop acquiring the position of the device, as no page waits for it anymore.
+    fn stop_updates(&self);
+}
+
+/// Reports the position of the device to the pages that wait for it. It can be sent to and
+/// used from any thread.
+#[derive(Clone)]
+pub struct GeolocationUpdater {
+    constellation_chan: Sender<ConstellationMsg>,
+}
+
+impl GeolocationUpdater {
+    pub(crate) fn new(constellation_chan: Sender<ConstellationMsg>) -> Self {
+        Self { constellation_chan }
+    }
+
+    pub fn update_position(&self, position: GeolocationPosition) {
+        self.send(GeolocationUpdate::Position(position));
+    }
+
+    /// Tell the pages that the position of the device can't be acquired, for the given reason.
+    pub fn report_position_unavailable(&self, reason: String) {
+        self.send(GeolocationUpdate::PositionUnavailable(reason));
+    }
+
+    fn send(&self, update: GeolocationUpdate) {
+        if let Err(error) = self
+            .constellation_chan
+            .send(ConstellationMsg::GeolocationUpdate(update))
+        {
+            warn!("Sending geolocation update to constellation failed ({error:?}).");
+        }
+    }
+}
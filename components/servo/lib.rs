@@ -17,18 +17,40 @@
 //! `Servo` is fed events from a generic type that implements the
 //! `WindowMethods` trait.
 
+mod clipboard;
+mod downloads;
+mod geolocation;
+mod notifications;
+mod permissions;
+mod script_messages;
+mod site_data_manager;
+mod user_content_manager;
+
 use std::borrow::{BorrowMut, Cow};
 use std::cmp::max;
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::vec::Drain;
 
+pub use crate::clipboard::ClipboardDelegate;
+use crate::downloads::DownloadManager;
+pub use crate::downloads::{
+    DirectoryDownloadDelegate, Download, DownloadDelegate, DownloadError, DownloadId,
+};
+pub use crate::geolocation::{GeolocationDelegate, GeolocationUpdater};
+pub use crate::notifications::{NotificationDelegate, NotificationHandle};
+use crate::permissions::PermissionManager;
+pub use crate::permissions::{PermissionDecision, PermissionDelegate};
+pub use crate::script_messages::{ScriptMessageHandler, ScriptMessageReply};
+pub use crate::site_data_manager::SiteDataManager;
+pub use crate::user_content_manager::UserContentManager;
 pub use base::id::TopLevelBrowsingContextId;
 use base::id::{PipelineNamespace, PipelineNamespaceId};
 use bluetooth::BluetoothThreadFactory;
-use bluetooth_traits::BluetoothRequest;
+use bluetooth_traits::{BluetoothDeviceGrant, BluetoothRequest};
 use canvas::canvas_paint_thread::{self, CanvasPaintThread};
 use canvas::WebGLComm;
 use canvas_traits::webgl::WebGLThreads;
@@ -52,7 +74,14 @@ use constellation::{
     UnprivilegedContent,
 };
 use crossbeam_channel::{unbounded, Sender};
-use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
+pub use embedder_traits::error_pages::{ErrorPage, ErrorPageProvider};
+pub use embedder_traits::user_script::{
+    MatchPattern, MatchPatternError, UserScript, UserScriptRunAt,
+};
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker, JSValue,
+    JavaScriptEvaluationError, JavaScriptEvaluationId, NotificationEvent, WebViewState,
+};
 use env_logger::Builder as EnvLoggerBuilder;
 use euclid::Scale;
 use fonts::FontCacheThread;
@@ -66,10 +95,20 @@ use fonts::FontCacheThread;
 ))]
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods};
 pub use gleam::gl;
-use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use log::{error, trace, warn, Log, Metadata, Record};
 use media::{GLPlayerThreads, GlApi, NativeDisplay, WindowGLContext};
+use net::content_filters::ContentFilters;
+pub use net::content_filters::{
+    ContentFilterAction, ContentFilterError, ContentFilterList, ContentFilterRule, ResourceType,
+};
+use net::protocols::ProtocolRegistry;
+pub use net::protocols::{
+    ProtocolHandler, ProtocolRegistrationError, ProtocolResponse, ProtocolResponseBody,
+};
 use net::resource_thread::new_resource_threads;
+use net_traits::ResourceThreads;
+use pixels::Image;
 use profile::{mem as profile_mem, time as profile_time};
 use profile_traits::{mem, time};
 use script::serviceworker_manager::ServiceWorkerManager;
@@ -79,6 +118,7 @@ use script_traits::{ScriptToConstellationChan, WindowSizeData};
 use servo_config::{opts, pref, prefs};
 use servo_media::player::context::GlContext;
 use servo_media::ServoMedia;
+use servo_url::ImmutableOrigin;
 #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
 use surfman::platform::generic::multi::connection::NativeConnection as LinuxNativeConnection;
 #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
@@ -87,6 +127,7 @@ use surfman::{GLApi, GLVersion};
 #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
 use surfman::{NativeConnection, NativeContext};
 use webrender::{RenderApiSender, ShaderPrecacheFlags};
+use webrender_api::units::DeviceRect;
 use webrender_api::{
     ColorF, DocumentId, FontInstanceFlags, FontInstanceKey, FontKey, FramePublishId, ImageKey,
     NativeFontHandle,
@@ -97,11 +138,11 @@ use webrender_traits::{
 };
 pub use {
     background_hang_monitor, base, bluetooth, bluetooth_traits, canvas, canvas_traits, compositing,
-    constellation, devtools, devtools_traits, embedder_traits, euclid, fonts, ipc_channel,
-    keyboard_types, layout_thread_2013, layout_thread_2020, media, net, net_traits, profile,
-    profile_traits, script, script_layout_interface, script_traits, servo_config as config,
-    servo_config, servo_geometry, servo_url as url, servo_url, style, style_traits, webgpu,
-    webrender_api, webrender_traits,
+    constellation, cookie, devtools, devtools_traits, embedder_traits, euclid, fonts, ipc_channel,
+    keyboard_types, layout_thread_2013, layout_thread_2020, media, net, net_traits, pixels,
+    profile, profile_traits, script, script_layout_interface, script_traits,
+    servo_config as config, servo_config, servo_geometry, servo_url as url, servo_url, style,
+    style_traits, webgpu, webrender_api, webrender_traits,
 };
 
 #[cfg(feature = "webdriver")]
@@ -160,6 +201,9 @@ mod media_platform {
     }
 }
 
+/// Receives the result of a script run with [`Servo::evaluate_javascript`].
+pub type JavaScriptEvaluationCallback = Box<dyn FnOnce(Result<JSValue, JavaScriptEvaluationError>)>;
+
 /// The in-process interface to Servo.
 ///
 /// It does everything necessary to render the web, primarily
@@ -174,6 +218,35 @@ mod media_platform {
 pub struct Servo<Window: WindowMethods + 'static + ?Sized> {
     compositor: IOCompositor<Window>,
     constellation_chan: Sender<ConstellationMsg>,
+    /// The resource threads shared by all the browsing contexts that are not private.
+    public_resource_threads: ResourceThreads,
+    /// Also remembers the Bluetooth devices that origins were given access to.
+    bluetooth_thread: IpcSender<BluetoothRequest>,
+    /// Saves the resources that navigations did not display, if the embedder set a delegate.
+    download_manager: Option<DownloadManager>,
+    /// Answers permission requests, if the embedder set a delegate.
+    permission_manager: Option<PermissionManager>,
+    /// Reads and writes the clipboard, if the embedder set a delegate.
+    clipboard_delegate: Option<Box<dyn ClipboardDelegate>>,
+    /// Acquires the position of the device for the Geolocation API, if the embedder set a
+    /// delegate.
+    geolocation_delegate: Option<Box<dyn GeolocationDelegate>>,
+    /// Shows the notifications of the Notifications API, if the embedder set a delegate.
+    notification_delegate: Option<Box<dyn NotificationDelegate>>,
+    /// The handlers of the messages that pages post to the embedder, by name.
+    script_message_handlers: HashMap<String, Box<dyn ScriptMessageHandler>>,
+    /// The handlers of the URL schemes that the embedder loads, shared with the network layer.
+    protocol_registry: Arc<ProtocolRegistry>,
+    /// The content filters of the embedder, shared with the network layer.
+    content_filters: Arc<ContentFilters>,
+    /// The user scripts of the embedder, shared with the constellation.
+    user_scripts: Arc<RwLock<Vec<UserScript>>>,
+    devtools_sender: Sender<devtools_traits::DevtoolsControlMsg>,
+    /// The id of the next script evaluation started with `evaluate_javascript`.
+    next_javascript_evaluation_id: usize,
+    /// The script evaluations that have not produced a value yet, with the webview they run in.
+    pending_javascript_evaluations:
+        HashMap<JavaScriptEvaluationId, (TopLevelBrowsingContextId, JavaScriptEvaluationCallback)>,
     embedder_receiver: EmbedderReceiver,
     messages_for_embedder: Vec<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
     profiler_enabled: bool,
@@ -303,14 +376,12 @@ where
         );
         let mem_profiler_chan = profile_mem::Profiler::create(opts.mem_profiler_period);
 
-        let devtools_sender = if opts.devtools_server_enabled {
-            Some(devtools::start_server(
-                opts.devtools_port,
-                embedder_proxy.clone(),
-            ))
-        } else {
-            None
-        };
+        // The devtools thread always runs, so that the embedder can start a server at any time
+        // and still see the pages that are already open.
+        let devtools_sender = devtools::start_thread(embedder_proxy.clone());
+        if opts.devtools_server_enabled {
+            devtools::listen(&devtools_sender, opts.devtools_port);
+        }
 
         let coordinates: compositing::windowing::EmbedderCoordinates = window.get_coordinates();
         let device_pixel_ratio = coordinates.hidpi_factor.get();
@@ -424,16 +495,25 @@ where
             device_pixel_ratio: Scale::new(device_pixel_ratio),
         };
 
+        let protocol_registry = Arc::new(ProtocolRegistry::default());
+        let content_filters = Arc::new(ContentFilters::default());
+        let user_scripts = Arc::new(RwLock::new(Vec::new()));
+
+        let (bluetooth_thread, private_bluetooth_thread): (IpcSender<BluetoothRequest>, _) =
+            BluetoothThreadFactory::new(embedder_proxy.clone(), opts.config_dir.clone());
+
         // Create the constellation, which maintains the engine pipelines, including script and
         // layout, as well as the navigation context.
-        let constellation_chan = create_constellation(
+        let (constellation_chan, public_resource_threads) = create_constellation(
             user_agent,
             opts.config_dir.clone(),
             embedder_proxy,
+            bluetooth_thread.clone(),
+            private_bluetooth_thread,
             compositor_proxy.clone(),
             time_profiler_chan.clone(),
             mem_profiler_chan.clone(),
-            devtools_sender,
+            Some(devtools_sender.clone()),
             webrender_document,
             webrender_api_sender,
             webxr_main_thread.registry(),
@@ -443,6 +523,9 @@ where
             window_size,
             external_images,
             wgpu_image_map,
+            protocol_registry.clone(),
+            content_filters.clone(),
+            user_scripts.clone(),
         );
 
         if cfg!(feature = "webdriver") {
@@ -483,6 +566,20 @@ where
         let servo = Servo {
             compositor,
             constellation_chan,
+            public_resource_threads,
+            bluetooth_thread,
+            download_manager: None,
+            permission_manager: None,
+            clipboard_delegate: None,
+            geolocation_delegate: None,
+            notification_delegate: None,
+            script_message_handlers: HashMap::new(),
+            protocol_registry,
+            content_filters,
+            user_scripts,
+            devtools_sender,
+            next_javascript_evaluation_id: 0,
+            pending_javascript_evaluations: HashMap::new(),
             embedder_receiver,
             messages_for_embedder: Vec::new(),
             profiler_enabled: false,
@@ -628,6 +725,16 @@ where
                 }
             },
 
+            EmbedderEvent::SlowScriptResponse(pipeline_id, allow_continue) => {
+                let msg = ConstellationMsg::SlowScriptResponse(pipeline_id, allow_continue);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending slow script response to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::LoadUrl(top_level_browsing_context_id, url) => {
                 let msg = ConstellationMsg::LoadUrl(top_level_browsing_context_id, url);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -673,6 +780,10 @@ where
                 self.compositor.on_zoom_reset_window_event();
             },
 
+            EmbedderEvent::SetPageZoom(webview_id, page_zoom) => {
+                self.compositor.set_page_zoom(webview_id, page_zoom);
+            },
+
             EmbedderEvent::PinchZoom(zoom) => {
                 self.compositor.on_pinch_zoom_window_event(zoom);
             },
@@ -689,6 +800,41 @@ where
                 ));
             },
 
+            EmbedderEvent::GoToHistoryIndex(top_level_browsing_context_id, index) => {
+                let msg =
+                    ConstellationMsg::TraverseHistoryToIndex(top_level_browsing_context_id, index);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending history traversal to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::TruncateHistory(top_level_browsing_context_id, entries) => {
+                let msg = ConstellationMsg::TruncateHistory(top_level_browsing_context_id, entries);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending history truncation to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::RestoreHistory(top_level_browsing_context_id, urls, current_index) => {
+                let msg = ConstellationMsg::RestoreHistory(
+                    top_level_browsing_context_id,
+                    urls,
+                    current_index,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending history restoration to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::Keyboard(key_event) => {
                 let msg = ConstellationMsg::Keyboard(key_event);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -706,6 +852,13 @@ where
                 }
             },
 
+            EmbedderEvent::Ime(event) => {
+                let msg = ConstellationMsg::Ime(event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending Ime event to constellation failed ({:?}).", e);
+                }
+            },
+
             EmbedderEvent::Quit => {
                 self.compositor.maybe_start_shutting_down();
             },
@@ -724,6 +877,13 @@ where
                 }
             },
 
+            EmbedderEvent::Stop(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::Stop(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending stop to constellation failed ({:?}).", e);
+                }
+            },
+
             EmbedderEvent::ToggleSamplingProfiler(rate, max_duration) => {
                 self.profiler_enabled = !self.profiler_enabled;
                 let msg = if self.profiler_enabled {
@@ -831,6 +991,23 @@ where
                 }
             },
 
+            EmbedderEvent::SetWebViewMuted(webview_id, muted) => {
+                let msg = ConstellationMsg::SetWebViewMuted(webview_id, muted);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetWebViewMuted to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewAutoplayPolicy(webview_id, policy) => {
+                let msg = ConstellationMsg::SetWebViewAutoplayPolicy(webview_id, policy);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewAutoplayPolicy to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::Gamepad(gamepad_event) => {
                 let msg = ConstellationMsg::Gamepad(gamepad_event);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -866,6 +1043,203 @@ where
                     self.messages_for_embedder.push(event);
                 },
 
+                (EmbedderMsg::DownloadRequested(request), ShutdownState::NotShuttingDown) => {
+                    match self.download_manager {
+                        Some(ref download_manager) => download_manager.start(request),
+                        None => {
+                            let msg = ConstellationMsg::DiscardDownload(request.pipeline_id);
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!(
+                                    "Sending discard download to constellation failed ({:?}).",
+                                    e
+                                );
+                            }
+                            self.messages_for_embedder.push((
+                                top_level_browsing_context,
+                                EmbedderMsg::DownloadRequested(request),
+                            ));
+                        },
+                    }
+                },
+
+                (
+                    EmbedderMsg::PromptPermission(prompt, origin, sender),
+                    ShutdownState::NotShuttingDown,
+                ) => match self.permission_manager {
+                    Some(ref mut permission_manager) => {
+                        let request = permission_manager.request_permission(
+                            top_level_browsing_context,
+                            origin,
+                            prompt,
+                        );
+                        if let Err(error) = sender.send(request) {
+                            warn!("Failed to answer permission request ({error:?}).");
+                        }
+                    },
+                    None => self.messages_for_embedder.push((
+                        top_level_browsing_context,
+                        EmbedderMsg::PromptPermission(prompt, origin, sender),
+                    )),
+                },
+
+                (
+                    EmbedderMsg::GetSelectedBluetoothDevice(origin, devices, sender),
+                    ShutdownState::NotShuttingDown,
+                ) => match self.permission_manager {
+                    Some(ref permission_manager) => {
+                        let selected = permission_manager.select_bluetooth_device(
+                            top_level_browsing_context,
+                            &origin,
+                            &devices,
+                        );
+                        if let Err(error) = sender.send(selected) {
+                            warn!("Failed to answer Bluetooth device request ({error:?}).");
+                        }
+                    },
+                    None => self.messages_for_embedder.push((
+                        top_level_browsing_context,
+                        EmbedderMsg::GetSelectedBluetoothDevice(origin, devices, sender),
+                    )),
+                },
+
+                (
+                    EmbedderMsg::GetAudioOutputDevices(origin, devices, sender),
+                    ShutdownState::NotShuttingDown,
+                ) => match self.permission_manager {
+                    Some(ref permission_manager) => {
+                        let exposed = permission_manager.audio_output_devices(
+                            top_level_browsing_context,
+                            &origin,
+                            &devices,
+                        );
+                        if let Err(error) = sender.send(exposed) {
+                            warn!("Failed to answer audio output device request ({error:?}).");
+                        }
+                    },
+                    None => self.messages_for_embedder.push((
+                        top_level_browsing_context,
+                        EmbedderMsg::GetAudioOutputDevices(origin, devices, sender),
+                    )),
+                },
+
+                (EmbedderMsg::GetClipboardContents(sender), ShutdownState::NotShuttingDown) => {
+                    match self.clipboard_delegate {
+                        Some(ref delegate) => {
+                            let contents = delegate.clipboard_contents(top_level_browsing_context);
+                            if let Err(error) = sender.send(contents) {
+                                warn!("Failed to send clipboard contents ({error:?}).");
+                            }
+                        },
+                        None => self.messages_for_embedder.push((
+                            top_level_browsing_context,
+                            EmbedderMsg::GetClipboardContents(sender),
+                        )),
+                    }
+                },
+
+                (EmbedderMsg::SetClipboardContents(contents), ShutdownState::NotShuttingDown) => {
+                    match self.clipboard_delegate {
+                        Some(ref delegate) => {
+                            delegate.set_clipboard_contents(top_level_browsing_context, contents)
+                        },
+                        None => self.messages_for_embedder.push((
+                            top_level_browsing_context,
+                            EmbedderMsg::SetClipboardContents(contents),
+                        )),
+                    }
+                },
+
+                (
+                    EmbedderMsg::StartGeolocationUpdates(high_accuracy),
+                    ShutdownState::NotShuttingDown,
+                ) => {
+                    let updater = GeolocationUpdater::new(self.constellation_chan.clone());
+                    match self.geolocation_delegate {
+                        Some(ref delegate) => delegate.start_updates(high_accuracy, updater),
+                        None => updater.report_position_unavailable(
+                            "There is no geolocation delegate".to_owned(),
+                        ),
+                    }
+                },
+
+                (EmbedderMsg::StopGeolocationUpdates, ShutdownState::NotShuttingDown) => {
+                    if let Some(ref delegate) = self.geolocation_delegate {
+                        delegate.stop_updates();
+                    }
+                },
+
+                (EmbedderMsg::ShowNotification(notification), ShutdownState::NotShuttingDown) => {
+                    let handle =
+                        NotificationHandle::new(notification.id, self.constellation_chan.clone());
+                    match self.notification_delegate {
+                        Some(ref delegate) => {
+                            delegate.show_notification(
+                                top_level_browsing_context,
+                                notification,
+                                handle.clone(),
+                            );
+                            handle.send(NotificationEvent::Show);
+                        },
+                        None => handle.send(NotificationEvent::Error),
+                    }
+                },
+
+                (EmbedderMsg::CloseNotification(id), ShutdownState::NotShuttingDown) => {
+                    if let Some(ref delegate) = self.notification_delegate {
+                        delegate.close_notification(id);
+                    }
+                },
+
+                (EmbedderMsg::ScriptMessage(message, sender), ShutdownState::NotShuttingDown) => {
+                    let reply = ScriptMessageReply::new(sender);
+                    match self.script_message_handlers.get(&message.handler_name) {
+                        Some(handler) => {
+                            handler.handle_message(top_level_browsing_context, message, reply)
+                        },
+                        None => reply.reject(format!(
+                            "There is no script message handler named {:?}",
+                            message.handler_name
+                        )),
+                    }
+                },
+
+                (
+                    EmbedderMsg::JavaScriptEvaluated(evaluation_id, result),
+                    ShutdownState::NotShuttingDown,
+                ) => {
+                    if let Some((_, callback)) =
+                        self.pending_javascript_evaluations.remove(&evaluation_id)
+                    {
+                        callback(result);
+                    }
+                },
+
+                (EmbedderMsg::WebViewClosed(webview_id), ShutdownState::NotShuttingDown) => {
+                    self.protocol_registry.unregister_webview(webview_id);
+                    self.content_filters.forget_webview(webview_id);
+                    // The scripts that were still waiting for a promise will never produce a
+                    // value.
+                    let closed_evaluations: Vec<_> = self
+                        .pending_javascript_evaluations
+                        .iter()
+                        .filter(|(_, (evaluation_webview_id, _))| {
+                            *evaluation_webview_id == webview_id
+                        })
+                        .map(|(evaluation_id, _)| *evaluation_id)
+                        .collect();
+                    for evaluation_id in closed_evaluations {
+                        if let Some((_, callback)) =
+                            self.pending_javascript_evaluations.remove(&evaluation_id)
+                        {
+                            callback(Err(JavaScriptEvaluationError::WebViewNotFound));
+                        }
+                    }
+                    self.messages_for_embedder.push((
+                        top_level_browsing_context,
+                        EmbedderMsg::WebViewClosed(webview_id),
+                    ));
+                },
+
                 (msg, ShutdownState::NotShuttingDown) => {
                     self.messages_for_embedder
                         .push((top_level_browsing_context, msg));
@@ -900,10 +1274,218 @@ where
         self.compositor.repaint_synchronously()
     }
 
+    /// Capture the composited output of a webview, or of the given area of it in device pixels
+    /// relative to the webview. The returned future resolves to the image once the output of the
+    /// webview is stable, which requires the embedder to keep handling events until then, or to
+    /// `None` if the webview goes away or if the area is outside of it.
+    pub fn take_screenshot(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        rect: Option<DeviceRect>,
+    ) -> Result<impl Future<Output = Option<Image>>, UnknownWebView> {
+        self.compositor.take_screenshot(webview_id, rect)
+    }
+
+    /// A handle to the cookies and other data that sites stored outside of private browsing.
+    pub fn site_data_manager(&self) -> SiteDataManager {
+        SiteDataManager::new(self.public_resource_threads.clone())
+    }
+
+    /// A handle to the content filters that decide which requests web content may make, and to
+    /// the user scripts injected into web content.
+    pub fn user_content_manager(&self) -> UserContentManager {
+        UserContentManager::new(self.content_filters.clone(), self.user_scripts.clone())
+    }
+
+    /// Save the resources that navigations did not display using the given delegate. Without
+    /// one, they are reported to the embedder with `EmbedderMsg::DownloadRequested`.
+    pub fn set_download_delegate(&mut self, delegate: Option<Arc<dyn DownloadDelegate>>) {
+        self.download_manager = delegate
+            .map(|delegate| DownloadManager::new(self.constellation_chan.clone(), delegate));
+    }
+
+    /// Answer the permission requests of web content, choose the Bluetooth devices it asks
+    /// for, and the audio output devices it may use, using the given delegate. Without one,
+    /// they are reported to the embedder with `EmbedderMsg::PromptPermission`,
+    /// `EmbedderMsg::GetSelectedBluetoothDevice` and `EmbedderMsg::GetAudioOutputDevices`.
+    pub fn set_permission_delegate(&mut self, delegate: Option<Box<dyn PermissionDelegate>>) {
+        self.permission_manager = delegate.map(PermissionManager::new);
+    }
+
+    /// Forget the permissions that the delegate always granted or denied to the given origin,
+    /// or to any origin, and the Bluetooth devices they were given access to.
+    pub fn forget_permission_decisions(&mut self, origin: Option<&ImmutableOrigin>) {
+        if let Some(ref mut permission_manager) = self.permission_manager {
+            permission_manager.forget_decisions(origin);
+        }
+        if let Err(error) = self
+            .bluetooth_thread
+            .send(BluetoothRequest::RevokeDeviceGrants(origin.cloned(), None))
+        {
+            warn!("Failed to revoke Bluetooth device grants ({error:?}).");
+        }
+    }
+
+    /// The Bluetooth devices that origins were given access to, which they can get again with
+    /// `navigator.bluetooth.getDevices()` until the grants are revoked.
+    pub fn bluetooth_device_grants(&self) -> Vec<BluetoothDeviceGrant> {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        if let Err(error) = self
+            .bluetooth_thread
+            .send(BluetoothRequest::GetDeviceGrants(sender))
+        {
+            warn!("Failed to ask for the Bluetooth device grants ({error:?}).");
+            return Vec::new();
+        }
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// Revoke the access of `origin` to the Bluetooth device with the given address, or to all
+    /// the devices it was given access to.
+    pub fn revoke_bluetooth_device_grants(&self, origin: &ImmutableOrigin, address: Option<&str>) {
+        if let Err(error) = self
+            .bluetooth_thread
+            .send(BluetoothRequest::RevokeDeviceGrants(
+                Some(origin.clone()),
+                address.map(str::to_owned),
+            ))
+        {
+            warn!("Failed to revoke Bluetooth device grants ({error:?}).");
+        }
+    }
+
+    /// Read and write the clipboard with the given delegate. Without one, the clipboard
+    /// messages are delivered to the embedder with the other messages.
+    pub fn set_clipboard_delegate(&mut self, delegate: Option<Box<dyn ClipboardDelegate>>) {
+        self.clipboard_delegate = delegate;
+    }
+
+    /// Acquire the position of the device for `navigator.geolocation` with the given delegate.
+    /// Without one, the position is never available.
+    pub fn set_geolocation_delegate(&mut self, delegate: Option<Box<dyn GeolocationDelegate>>) {
+        self.geolocation_delegate = delegate;
+    }
+
+    /// Show the notifications that pages create with the given delegate. Without one, pages
+    /// get an `error` event for each of their notifications.
+    pub fn set_notification_delegate(&mut self, delegate: Option<Box<dyn NotificationDelegate>>) {
+        self.notification_delegate = delegate;
+    }
+
+    /// Show the error pages of the given provider in place of the ones of Servo. In multiprocess
+    /// mode, content processes have to set it with `embedder_traits::error_pages::set_provider`
+    /// as well.
+    pub fn set_error_page_provider(&mut self, provider: Option<Box<dyn ErrorPageProvider>>) {
+        embedder_traits::error_pages::set_provider(provider);
+    }
+
+    /// Deliver the messages that pages post to `name` to the given handler, replacing the
+    /// previous handler of that name.
+    pub fn add_script_message_handler(
+        &mut self,
+        name: String,
+        handler: Box<dyn ScriptMessageHandler>,
+    ) {
+        self.script_message_handlers.insert(name, handler);
+    }
+
+    pub fn remove_script_message_handler(&mut self, name: &str) {
+        self.script_message_handlers.remove(name);
+    }
+
+    /// Load the URLs of `scheme` with the given handler, either in all webviews or only in the
+    /// given one, whose handlers are removed when it closes. The responses go through fetch like
+    /// any other, so they are reported to devtools.
+    pub fn register_protocol_handler(
+        &self,
+        scheme: &str,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        handler: Arc<dyn ProtocolHandler>,
+    ) -> Result<(), ProtocolRegistrationError> {
+        self.protocol_registry.register(scheme, webview_id, handler)
+    }
+
+    pub fn unregister_protocol_handler(
+        &self,
+        scheme: &str,
+        webview_id: Option<TopLevelBrowsingContextId>,
+    ) {
+        self.protocol_registry.unregister(scheme, webview_id);
+    }
+
+    /// Start a devtools server on the given port, or on any free port if it is 0, replacing the
+    /// server that is already running. The bound port and the token that lets clients skip the
+    /// permission prompt are reported with `EmbedderMsg::OnDevtoolsStarted`.
+    pub fn start_devtools_server(&self, port: u16) -> devtools::DevtoolsServerHandle {
+        devtools::listen(&self.devtools_sender, port)
+    }
+
+    /// Run a script in the top-level document of a webview, and pass its result to `callback`
+    /// once the promise it evaluated to, if any, has settled. The callback is always called,
+    /// with an error if the webview does not exist or is closed before the script produced a
+    /// value.
+    pub fn evaluate_javascript(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        script: String,
+        callback: impl FnOnce(Result<JSValue, JavaScriptEvaluationError>) + 'static,
+    ) {
+        let evaluation_id = JavaScriptEvaluationId(self.next_javascript_evaluation_id);
+        self.next_javascript_evaluation_id += 1;
+        let msg = ConstellationMsg::EvaluateJavaScript(webview_id, evaluation_id, script);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!(
+                "Sending script evaluation to constellation failed ({:?}).",
+                e
+            );
+            return callback(Err(JavaScriptEvaluationError::WebViewNotFound));
+        }
+        self.pending_javascript_evaluations
+            .insert(evaluation_id, (webview_id, Box::new(callback)));
+    }
+
+    /// Save the session history of a webview, and the scroll position and form values of its
+    /// current document. The state is sent on the returned channel, or `None` if the webview
+    /// does not exist, and can be written to disk with `WebViewState::to_bytes`.
+    pub fn serialize_webview_state(
+        &self,
+        webview_id: TopLevelBrowsingContextId,
+    ) -> IpcReceiver<Option<WebViewState>> {
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let msg = ConstellationMsg::GetWebViewState(webview_id, sender);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!(
+                "Sending webview state request to constellation failed ({:?}).",
+                e
+            );
+        }
+        receiver
+    }
+
+    /// Open the state saved with `serialize_webview_state` in a webview, typically one that was
+    /// just created for it. Its current document is loaded again, from the HTTP cache if the
+    /// response allows it, and the other entries are loaded when they are traversed to.
+    pub fn restore_webview_state(
+        &self,
+        webview_id: TopLevelBrowsingContextId,
+        state: WebViewState,
+    ) {
+        let msg = ConstellationMsg::RestoreWebViewState(webview_id, state);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending webview state to constellation failed ({:?}).", e);
+        }
+    }
+
     pub fn pinch_zoom_level(&self) -> f32 {
         self.compositor.pinch_zoom_level().get()
     }
 
+    /// The page zoom of a webview, as set with `EmbedderEvent::SetPageZoom` or changed by
+    /// `EmbedderEvent::Zoom`.
+    pub fn page_zoom(&self, webview_id: TopLevelBrowsingContextId) -> Option<f32> {
+        self.compositor.page_zoom(webview_id)
+    }
+
     pub fn setup_logging(&self) {
         let constellation_chan = self.constellation_chan.clone();
         let env = env_logger::Env::default();
@@ -966,6 +1548,8 @@ fn create_constellation(
     user_agent: Cow<'static, str>,
     config_dir: Option<PathBuf>,
     embedder_proxy: EmbedderProxy,
+    bluetooth_thread: IpcSender<BluetoothRequest>,
+    private_bluetooth_thread: IpcSender<BluetoothRequest>,
     compositor_proxy: CompositorProxy,
     time_profiler_chan: time::ProfilerChan,
     mem_profiler_chan: mem::ProfilerChan,
@@ -979,13 +1563,13 @@ fn create_constellation(
     initial_window_size: WindowSizeData,
     external_images: Arc<Mutex<WebrenderExternalImageRegistry>>,
     wgpu_image_map: Arc<Mutex<HashMap<u64, webgpu::PresentationData>>>,
-) -> Sender<ConstellationMsg> {
+    protocol_registry: Arc<ProtocolRegistry>,
+    content_filters: Arc<ContentFilters>,
+    user_scripts: Arc<RwLock<Vec<UserScript>>>,
+) -> (Sender<ConstellationMsg>, ResourceThreads) {
     // Global configuration options, parsed from the command line.
     let opts = opts::get();
 
-    let bluetooth_thread: IpcSender<BluetoothRequest> =
-        BluetoothThreadFactory::new(embedder_proxy.clone());
-
     let (public_resource_threads, private_resource_threads) = new_resource_threads(
         user_agent.clone(),
         devtools_sender.clone(),
@@ -995,6 +1579,8 @@ fn create_constellation(
         config_dir,
         opts.certificate_path.clone(),
         opts.ignore_certificate_errors,
+        protocol_registry,
+        content_filters,
     );
 
     let font_cache_thread = FontCacheThread::new(Box::new(WebRenderFontApiCompositorProxy(
@@ -1012,8 +1598,9 @@ fn create_constellation(
         embedder_proxy,
         devtools_sender,
         bluetooth_thread,
+        private_bluetooth_thread,
         font_cache_thread,
-        public_resource_threads,
+        public_resource_threads: public_resource_threads.clone(),
         private_resource_threads,
         time_profiler_chan,
         mem_profiler_chan,
@@ -1024,6 +1611,7 @@ fn create_constellation(
         glplayer_threads,
         player_context,
         user_agent,
+        user_scripts,
         webrender_external_images: external_images,
         wgpu_image_map,
     };
@@ -1034,7 +1622,7 @@ fn create_constellation(
         Arc::new(layout_thread_2020::LayoutFactoryImpl())
     };
 
-    Constellation::<
+    let constellation_chan = Constellation::<
         script::script_thread::ScriptThread,
         script::serviceworker_manager::ServiceWorkerManager,
     >::start(
@@ -1047,7 +1635,8 @@ fn create_constellation(
         !opts.debug.disable_canvas_antialiasing,
         canvas_create_sender,
         canvas_ipc_sender,
-    )
+    );
+    (constellation_chan, public_resource_threads)
 }
 
 struct WebRenderFontApiCompositorProxy(CompositorProxy);
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders show the notifications that pages create with the Notifications API, which is
+//! only exposed when the `dom.notifications.enabled` preference is set. Pages are asked for
+//! permission before they can create notifications.
+
+use base::id::{NotificationId, TopLevelBrowsingContextId};
+use compositing_traits::ConstellationMsg;
+use crossbeam_channel::Sender;
+use embedder_traits::{Notification, NotificationEvent};
+use log::warn;
+
+/// Shows notifications to the user, on the thread that handles the events of Servo.
+pub trait NotificationDelegate {
+    /// Show a notification, replacing the one of the same origin with the same tag, if any.
+    /// `handle` reports what the user does with it to its page.
+    fn show_notification(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        notification: Notification,
+        handle: NotificationHandle,
+    );
+
+    /// Close a notification, because its page closed it or was closed.
+    fn close_notification(&self, id: NotificationId);
+}
+
+/// Reports what happens to a notification to the page that created it. It can be sent to and
+/// used from any thread.
+#[derive(Clone)]
+pub struct NotificationHandle {
+    id: NotificationId,
+    constellation_chan: Sender<ConstellationMsg>,
+}
+
+impl NotificationHandle {
+    pub(crate) fn new(id: NotificationId, constellation_chan: Sender<ConstellationMsg>) -> Self {
+        Self {
+            id,
+            constellation_chan,
+        }
+    }
+
+    pub fn id(&self) -> NotificationId {
+        self.id
+    }
+
+    /// The user activated the notification, which fires a `click` event at it.
+    pub fn click(&self) {
+        self.send(NotificationEvent::Click);
+    }
+
+    /// The notification was closed by the user or the system, which fires a `close` event at
+    /// it. The handle can't be used anymore once this is called.
+    pub fn close(&self) {
+        self.send(NotificationEvent::Close);
+    }
+
+    pub(crate) fn send(&self, event: NotificationEvent) {
+        if let Err(error) = self
+            .constellation_chan
+            .send(ConstellationMsg::NotificationEvent(self.id, event))
+        {
+            warn!("Sending notification event to constellation failed ({error:?}).");
+        }
+    }
+}
@@ -0,0 +1,323 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders answer the requests of web content for powerful features from one place.
+
+use std::collections::HashMap;
+
+use base::id::TopLevelBrowsingContextId;
+use embedder_traits::{
+    AudioOutputDeviceDescription, BluetoothDeviceDescription, PermissionName, PermissionPrompt,
+    PermissionRequest,
+};
+use servo_url::ImmutableOrigin;
+
+/// How an embedder answered a permission request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PermissionDecision {
+    Grant,
+    Deny,
+    /// Grant this request and all the later ones of the same origin for the same feature.
+    AlwaysGrant,
+    /// Deny this request and all the later ones of the same origin for the same feature.
+    AlwaysDeny,
+}
+
+/// Answers the permission requests of web content, on the thread that handles the events of
+/// Servo. The page that made a request waits until it is answered.
+pub trait PermissionDelegate {
+    /// Decide whether `origin` may use the feature of the prompt. This is not called for
+    /// features that the origin was always granted or denied before.
+    fn request_permission(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: &ImmutableOrigin,
+        prompt: &PermissionPrompt,
+    ) -> PermissionDecision;
+
+    /// Let the user choose one of the devices found for the `navigator.bluetooth.requestDevice()`
+    /// call of `origin`, returning the address of the chosen device. Returning `None` cancels the
+    /// request, which is rejected with a `NotFoundError`.
+    fn select_bluetooth_device(
+        &self,
+        _webview_id: Option<TopLevelBrowsingContextId>,
+        _origin: &ImmutableOrigin,
+        _devices: &[BluetoothDeviceDescription],
+    ) -> Option<String> {
+        None
+    }
+
+    /// Choose which of the audio output devices of the system `origin` may render to with
+    /// `AudioContext.setSinkId()`, returning their ids. The others are reported as not found,
+    /// so that pages can not tell which devices exist. By default, none of them is exposed.
+    fn audio_output_devices(
+        &self,
+        _webview_id: Option<TopLevelBrowsingContextId>,
+        _origin: &ImmutableOrigin,
+        _devices: &[AudioOutputDeviceDescription],
+    ) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Asks a [`PermissionDelegate`] about permission requests, remembering the decisions that
+/// apply to all the later requests of an origin.
+pub(crate) struct PermissionManager {
+    delegate: Box<dyn PermissionDelegate>,
+    remembered_decisions: HashMap<(ImmutableOrigin, PermissionName), PermissionRequest>,
+}
+
+impl PermissionManager {
+    pub(crate) fn new(delegate: Box<dyn PermissionDelegate>) -> Self {
+        Self {
+            delegate,
+            remembered_decisions: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn request_permission(
+        &mut self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: ImmutableOrigin,
+        prompt: PermissionPrompt,
+    ) -> PermissionRequest {
+        let key = (origin, prompt.permission_name());
+        if let Some(request) = self.remembered_decisions.get(&key) {
+            return request.clone();
+        }
+        let (request, remember) = match self
+            .delegate
+            .request_permission(webview_id, &key.0, &prompt)
+        {
+            PermissionDecision::Grant => (PermissionRequest::Granted, false),
+            PermissionDecision::Deny => (PermissionRequest::Denied, false),
+            PermissionDecision::AlwaysGrant => (PermissionRequest::Granted, true),
+            PermissionDecision::AlwaysDeny => (PermissionRequest::Denied, true),
+        };
+        if remember {
+            self.remembered_decisions.insert(key, request.clone());
+        }
+        request
+    }
+
+    pub(crate) fn select_bluetooth_device(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: &ImmutableOrigin,
+        devices: &[BluetoothDeviceDescription],
+    ) -> Option<String> {
+        self.delegate
+            .select_bluetooth_device(webview_id, origin, devices)
+    }
+
+    pub(crate) fn audio_output_devices(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        origin: &ImmutableOrigin,
+        devices: &[AudioOutputDeviceDescription],
+    ) -> Vec<String> {
+        self.delegate
+            .audio_output_devices(webview_id, origin, devices)
+    }
+
+    /// Forget the decisions remembered for the given origin, or for all of them.
+    pub(crate) fn forget_decisions(&mut self, origin: Option<&ImmutableOrigin>) {
+        match origin {
+            Some(origin) => self
+                .remembered_decisions
+                .retain(|(decision_origin, _), _| decision_origin != origin),
+            None => self.remembered_decisions.clear(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use servo_url::ServoUrl;
+
+    use super::*;
+
+    /// Answers with the given decisions in order, counting how often it was asked.
+    struct TestDelegate {
+        decisions: RefCell<Vec<PermissionDecision>>,
+        requests: Rc<Cell<usize>>,
+    }
+
+    impl PermissionDelegate for TestDelegate {
+        fn request_permission(
+            &self,
+            _webview_id: Option<TopLevelBrowsingContextId>,
+            _origin: &ImmutableOrigin,
+            _prompt: &PermissionPrompt,
+        ) -> PermissionDecision {
+            self.requests.set(self.requests.get() + 1);
+            self.decisions.borrow_mut().remove(0)
+        }
+    }
+
+    fn manager(decisions: Vec<PermissionDecision>) -> (PermissionManager, Rc<Cell<usize>>) {
+        let requests = Rc::new(Cell::new(0));
+        let delegate = TestDelegate {
+            decisions: RefCell::new(decisions),
+            requests: requests.clone(),
+        };
+        (PermissionManager::new(Box::new(delegate)), requests)
+    }
+
+    fn origin(url: &str) -> ImmutableOrigin {
+        ServoUrl::parse(url).unwrap().origin()
+    }
+
+    fn request(
+        manager: &mut PermissionManager,
+        url: &str,
+        permission_name: PermissionName,
+    ) -> PermissionRequest {
+        manager.request_permission(
+            None,
+            origin(url),
+            PermissionPrompt::Request(permission_name),
+        )
+    }
+
+    #[test]
+    fn test_grant_and_deny_are_not_remembered() {
+        let (mut manager, requests) =
+            manager(vec![PermissionDecision::Grant, PermissionDecision::Deny]);
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.com",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Granted
+        ));
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.com",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Denied
+        ));
+        assert_eq!(requests.get(), 2);
+    }
+
+    #[test]
+    fn test_always_grant_is_remembered() {
+        let (mut manager, requests) = manager(vec![PermissionDecision::AlwaysGrant]);
+        for _ in 0..2 {
+            assert!(matches!(
+                request(
+                    &mut manager,
+                    "https://example.com",
+                    PermissionName::Geolocation
+                ),
+                PermissionRequest::Granted
+            ));
+        }
+        assert_eq!(requests.get(), 1);
+    }
+
+    #[test]
+    fn test_always_deny_is_remembered() {
+        let (mut manager, requests) = manager(vec![PermissionDecision::AlwaysDeny]);
+        for _ in 0..2 {
+            assert!(matches!(
+                request(
+                    &mut manager,
+                    "https://example.com",
+                    PermissionName::Notifications
+                ),
+                PermissionRequest::Denied
+            ));
+        }
+        assert_eq!(requests.get(), 1);
+    }
+
+    #[test]
+    fn test_remembered_decisions_are_per_origin_and_feature() {
+        let (mut manager, requests) = manager(vec![
+            PermissionDecision::AlwaysGrant,
+            PermissionDecision::Deny,
+            PermissionDecision::Deny,
+        ]);
+        request(
+            &mut manager,
+            "https://example.com",
+            PermissionName::Geolocation,
+        );
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.org",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Denied
+        ));
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.com",
+                PermissionName::Notifications
+            ),
+            PermissionRequest::Denied
+        ));
+        assert_eq!(requests.get(), 3);
+    }
+
+    #[test]
+    fn test_forget_decisions() {
+        let (mut manager, requests) = manager(vec![
+            PermissionDecision::AlwaysGrant,
+            PermissionDecision::AlwaysGrant,
+            PermissionDecision::Deny,
+            PermissionDecision::Deny,
+        ]);
+        request(
+            &mut manager,
+            "https://example.com",
+            PermissionName::Geolocation,
+        );
+        request(
+            &mut manager,
+            "https://example.org",
+            PermissionName::Geolocation,
+        );
+
+        // Forgetting the decisions of one origin keeps those of the others.
+        manager.forget_decisions(Some(&origin("https://example.com")));
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.com",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Denied
+        ));
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.org",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Granted
+        ));
+        assert_eq!(requests.get(), 3);
+
+        manager.forget_decisions(None);
+        assert!(matches!(
+            request(
+                &mut manager,
+                "https://example.org",
+                PermissionName::Geolocation
+            ),
+            PermissionRequest::Denied
+        ));
+        assert_eq!(requests.get(), 4);
+    }
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Delivers the messages that pages post with `window.postMessageToEmbedder()`, which is only
+//! exposed when the `dom.embedder_messages.enabled` preference is set.
+
+use base::id::TopLevelBrowsingContextId;
+use embedder_traits::ScriptMessage;
+use ipc_channel::ipc::IpcSender;
+use log::warn;
+
+/// Receives the messages posted to the name it was registered under with
+/// [`crate::Servo::add_script_message_handler`], on the thread that handles the events of
+/// Servo.
+pub trait ScriptMessageHandler {
+    /// Handle a message. The promise of the page stays pending until `reply` is used, or is
+    /// rejected if `reply` is dropped first.
+    fn handle_message(
+        &self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        message: ScriptMessage,
+        reply: ScriptMessageReply,
+    );
+}
+
+/// Settles the promise returned to the page that posted a message.
+pub struct ScriptMessageReply {
+    sender: Option<IpcSender<Result<String, String>>>,
+}
+
+impl ScriptMessageReply {
+    pub(crate) fn new(sender: IpcSender<Result<String, String>>) -> Self {
+        Self {
+            sender: Some(sender),
+        }
+    }
+
+    /// Fulfill the promise with the given JSON value. A reply that is not valid JSON rejects
+    /// the promise with a `SyntaxError`.
+    pub fn fulfill(mut self, json: String) {
+        self.send(Ok(json));
+    }
+
+    /// Reject the promise with the given reason.
+    pub fn reject(mut self, reason: String) {
+        self.send(Err(reason));
+    }
+
+    fn send(&mut self, reply: Result<String, String>) {
+        if let Some(sender) = self.sender.take() {
+            if let Err(error) = sender.send(reply) {
+                warn!("Failed to reply to a script message ({error:?}).");
+            }
+        }
+    }
+}
+
+impl Drop for ScriptMessageReply {
+    fn drop(&mut self) {
+        self.send(Err("The handler did not reply".to_owned()));
+    }
+}
@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders inspect and clear the data that sites stored outside of private browsing.
+
+use cookie::Cookie;
+use hyper_serde::Serde;
+use ipc_channel::ipc;
+use log::warn;
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::{CoreResourceMsg, IpcSend, ResourceThreads, SiteDataFilter};
+
+/// A handle to the cookies, HTTP cache and web storage of the public resource threads, as
+/// returned by [`crate::Servo::site_data_manager`].
+#[derive(Clone)]
+pub struct SiteDataManager {
+    resource_threads: ResourceThreads,
+}
+
+impl SiteDataManager {
+    pub(crate) fn new(resource_threads: ResourceThreads) -> Self {
+        Self { resource_threads }
+    }
+
+    /// The unexpired cookies whose domain matches the given filter.
+    pub fn cookies(&self, filter: SiteDataFilter) -> Vec<Cookie<'static>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        if let Err(error) = self
+            .resource_threads
+            .send(CoreResourceMsg::GetCookies(filter, sender))
+        {
+            warn!("Sending GetCookies to the resource thread failed ({error:?}).");
+            return Vec::new();
+        }
+        receiver
+            .recv()
+            .map(|cookies| cookies.into_iter().map(Serde::into_inner).collect())
+            .unwrap_or_default()
+    }
+
+    /// Delete the stored cookie with the same name, domain and path as the given one.
+    pub fn delete_cookie(&self, cookie: Cookie<'static>) {
+        if let Err(error) = self
+            .resource_threads
+            .send(CoreResourceMsg::DeleteCookie(Serde(cookie)))
+        {
+            warn!("Sending DeleteCookie to the resource thread failed ({error:?}).");
+        }
+    }
+
+    /// Clear the cookies, HTTP cache entries and web storage that match the given filter,
+    /// returning once all of them are gone.
+    pub fn clear_site_data(&self, filter: SiteDataFilter) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let messages = [
+            CoreResourceMsg::ClearCookies(filter.clone(), sender.clone()),
+            CoreResourceMsg::ClearHttpCache(filter.clone(), sender.clone()),
+        ];
+        let mut pending_replies = 0;
+        for message in messages {
+            match self.resource_threads.send(message) {
+                Ok(()) => pending_replies += 1,
+                Err(error) => {
+                    warn!("Clearing site data in the resource thread failed ({error:?}).")
+                },
+            }
+        }
+        match self
+            .resource_threads
+            .send(StorageThreadMsg::ClearSiteData(filter, sender))
+        {
+            Ok(()) => pending_replies += 1,
+            Err(error) => warn!("Clearing site data in the storage thread failed ({error:?})."),
+        }
+        for _ in 0..pending_replies {
+            let _ = receiver.recv();
+        }
+    }
+}
@@ -0,0 +1,228 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets embedders change what web content loads, for example to block ads and trackers, and
+//! inject their own scripts into it.
+
+use std::sync::{Arc, RwLock};
+
+use base::id::TopLevelBrowsingContextId;
+use embedder_traits::user_script::UserScript;
+use net::content_filters::{ContentFilterList, ContentFilters};
+use servo_url::ServoUrl;
+
+/// A handle to the content filters that the network layer consults before making requests, and
+/// to the user scripts injected into documents, as returned by
+/// [`crate::Servo::user_content_manager`]. They apply to all webviews, in and out of private
+/// browsing.
+#[derive(Clone)]
+pub struct UserContentManager {
+    content_filters: Arc<ContentFilters>,
+    user_scripts: Arc<RwLock<Vec<UserScript>>>,
+}
+
+impl UserContentManager {
+    pub(crate) fn new(
+        content_filters: Arc<ContentFilters>,
+        user_scripts: Arc<RwLock<Vec<UserScript>>>,
+    ) -> Self {
+        Self {
+            content_filters,
+            user_scripts,
+        }
+    }
+
+    /// Block the requests that match the given list, unless another list allows them. A list
+    /// that was added with the same identifier is replaced.
+    pub fn add_content_filter(&self, identifier: &str, list: ContentFilterList) {
+        self.content_filters.add(identifier.to_owned(), list);
+    }
+
+    pub fn remove_content_filter(&self, identifier: &str) {
+        self.content_filters.remove(identifier);
+    }
+
+    pub fn remove_all_content_filters(&self) {
+        self.content_filters.remove_all();
+    }
+
+    /// The number of requests that content filters blocked for a webview since it was opened.
+    pub fn blocked_request_count(&self, webview_id: TopLevelBrowsingContextId) -> usize {
+        self.content_filters.blocked_request_count(webview_id)
+    }
+
+    /// Inject the given script into the documents that it matches, after the scripts that were
+    /// added before it. This applies to the documents loaded from now on.
+    pub fn add_user_script(&self, script: UserScript) {
+        self.user_scripts.write().unwrap().push(script);
+    }
+
+    pub fn remove_all_user_scripts(&self) {
+        self.user_scripts.write().unwrap().clear();
+    }
+
+    /// The user scripts that are injected into a document with the given URL, in the order
+    /// they run in for each of their `run_at` phases.
+    pub fn user_scripts_for_url(&self, url: &ServoUrl) -> Vec<UserScript> {
+        self.user_scripts
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|script| script.matches_url(url))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedder_traits::user_script::{MatchPattern, MatchPatternError};
+
+    use super::*;
+
+    fn manager() -> UserContentManager {
+        UserContentManager::new(Default::default(), Default::default())
+    }
+
+    fn pattern(pattern: &str) -> MatchPattern {
+        pattern.parse().unwrap()
+    }
+
+    fn url(url: &str) -> ServoUrl {
+        ServoUrl::parse(url).unwrap()
+    }
+
+    fn matches(pattern_str: &str, url_str: &str) -> bool {
+        pattern(pattern_str).matches(&url(url_str))
+    }
+
+    #[test]
+    fn test_invalid_match_patterns() {
+        let error = |pattern: &str| pattern.parse::<MatchPattern>().unwrap_err();
+        assert_eq!(
+            error("example.com/*"),
+            MatchPatternError::MissingSchemeSeparator
+        );
+        assert_eq!(
+            error("chrome://example.com/*"),
+            MatchPatternError::InvalidScheme
+        );
+        assert_eq!(error("https://example.com"), MatchPatternError::MissingPath);
+        assert_eq!(error("https:///*"), MatchPatternError::InvalidHost);
+        assert_eq!(
+            error("https://*example.com/*"),
+            MatchPatternError::InvalidHost
+        );
+        assert_eq!(error("https://www.*.com/*"), MatchPatternError::InvalidHost);
+        assert_eq!(
+            error("https://example.com:8080/*"),
+            MatchPatternError::InvalidHost
+        );
+    }
+
+    #[test]
+    fn test_match_pattern_schemes() {
+        assert!(matches("*://example.com/*", "http://example.com/"));
+        assert!(matches("*://example.com/*", "https://example.com/"));
+        assert!(!matches("*://example.com/*", "ftp://example.com/"));
+        assert!(matches("https://example.com/*", "https://example.com/"));
+        assert!(!matches("https://example.com/*", "http://example.com/"));
+        assert!(matches("file:///home/*", "file:///home/user/page.html"));
+        assert!(matches("<all_urls>", "ftp://example.com/"));
+        assert!(matches("<all_urls>", "data:text/html,hello"));
+        assert!(!matches("<all_urls>", "about:blank"));
+    }
+
+    #[test]
+    fn test_match_pattern_hosts() {
+        assert!(matches("https://*/*", "https://example.org/"));
+        assert!(matches("https://*.example.com/*", "https://example.com/"));
+        assert!(matches(
+            "https://*.example.com/*",
+            "https://a.b.example.com/"
+        ));
+        assert!(!matches(
+            "https://*.example.com/*",
+            "https://notexample.com/"
+        ));
+        assert!(matches("https://EXAMPLE.com/*", "https://example.com/"));
+        assert!(!matches(
+            "https://example.com/*",
+            "https://www.example.com/"
+        ));
+        assert!(matches(
+            "https://example.com/*",
+            "https://example.com:8080/"
+        ));
+    }
+
+    #[test]
+    fn test_match_pattern_paths() {
+        assert!(matches("https://example.com/", "https://example.com/"));
+        assert!(!matches("https://example.com/", "https://example.com/a"));
+        assert!(matches(
+            "https://example.com/a/*",
+            "https://example.com/a/b/c"
+        ));
+        assert!(!matches(
+            "https://example.com/a/*",
+            "https://example.com/b/a/"
+        ));
+        assert!(matches(
+            "https://example.com/*.html",
+            "https://example.com/a/b.html"
+        ));
+        assert!(!matches(
+            "https://example.com/*.html",
+            "https://example.com/b.htm"
+        ));
+        assert!(matches(
+            "https://example.com/*a*b",
+            "https://example.com/xaxb"
+        ));
+        assert!(!matches(
+            "https://example.com/*ab*ba",
+            "https://example.com/aba"
+        ));
+        assert!(matches(
+            "https://example.com/*?q=*",
+            "https://example.com/search?q=servo"
+        ));
+        assert!(!matches(
+            "https://example.com/page",
+            "https://example.com/page?q=1"
+        ));
+    }
+
+    #[test]
+    fn test_user_scripts_are_filtered_by_url() {
+        let manager = manager();
+        let mut example =
+            UserScript::new("example".to_owned(), vec![pattern("*://*.example.com/*")]);
+        example.exclude_matches = vec![pattern("*://private.example.com/*")];
+        manager.add_user_script(example);
+        manager.add_user_script(UserScript::new(
+            "everywhere".to_owned(),
+            vec![MatchPattern::all_urls()],
+        ));
+        manager.add_user_script(UserScript::new("nowhere".to_owned(), vec![]));
+
+        let sources = |url_str: &str| -> Vec<String> {
+            manager
+                .user_scripts_for_url(&url(url_str))
+                .into_iter()
+                .map(|script| script.source)
+                .collect()
+        };
+        assert_eq!(
+            sources("https://www.example.com/"),
+            ["example", "everywhere"]
+        );
+        assert_eq!(sources("https://private.example.com/"), ["everywhere"]);
+        assert_eq!(sources("https://servo.org/"), ["everywhere"]);
+
+        manager.remove_all_user_scripts();
+        assert!(sources("https://www.example.com/").is_empty());
+    }
+}
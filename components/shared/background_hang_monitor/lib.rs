@@ -199,6 +199,10 @@ pub trait BackgroundHangMonitor {
 pub trait BackgroundHangMonitorExitSignal: Send {
     /// Called by the BHM, to notify the monitored component to exit.
     fn signal_to_exit(&self);
+
+    /// Called by the BHM, to notify the monitored component that the activity it is currently
+    /// hanging on should be interrupted, without exiting the component.
+    fn signal_to_interrupt(&self) {}
 }
 
 /// Messages to control the sampling profiler.
@@ -207,6 +211,8 @@ pub enum BackgroundHangMonitorControlMsg {
     /// Enable the sampler, with a given sampling rate and max total sampling duration.
     EnableSampler(Duration, Duration),
     DisableSampler,
+    /// Interrupt the current activity of a hanging component, if it is monitored by this BHM.
+    InterruptComponent(MonitoredComponentId),
     /// Exit, and propagate the signal to monitored components.
     Exit(IpcSender<()>),
 }
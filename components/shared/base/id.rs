@@ -191,6 +191,7 @@ impl PipelineNamespace {
     namespace_id_method! {next_service_worker_registration_id, ServiceWorkerRegistrationId,
     self, ServiceWorkerRegistrationIndex}
     namespace_id_method! {next_blob_id, BlobId, self, BlobIndex}
+    namespace_id_method! {next_notification_id, NotificationId, self, NotificationIndex}
 }
 
 thread_local!(pub static PIPELINE_NAMESPACE: Cell<Option<PipelineNamespace>> = Cell::new(None));
@@ -420,6 +421,19 @@ impl HistoryStateId {
     }
 }
 
+namespace_id! {NotificationId, NotificationIndex, "Notification"}
+
+impl NotificationId {
+    pub fn new() -> NotificationId {
+        PIPELINE_NAMESPACE.with(|tls| {
+            let mut namespace = tls.get().expect("No namespace set for this thread!");
+            let next_notification_id = namespace.next_notification_id();
+            tls.set(Some(namespace));
+            next_notification_id
+        })
+    }
+}
+
 // We provide ids just for unit testing.
 pub const TEST_NAMESPACE: PipelineNamespaceId = PipelineNamespaceId(1234);
 #[allow(unsafe_code)]
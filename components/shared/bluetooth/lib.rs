@@ -5,8 +5,10 @@
 pub mod blocklist;
 pub mod scanfilter;
 
+use base::id::TopLevelBrowsingContextId;
 use ipc_channel::ipc::IpcSender;
 use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
 
 use crate::scanfilter::{BluetoothScanfilterSequence, RequestDeviceoptions};
 
@@ -65,6 +67,29 @@ pub struct BluetoothDescriptorMsg {
     pub instance_id: String,
 }
 
+/// The data of an advertising packet received from a device.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BluetoothAdvertisementMsg {
+    pub name: Option<String>,
+    pub appearance: Option<u16>,
+    pub tx_power: Option<i8>,
+    pub rssi: Option<i8>,
+}
+
+/// A device that an origin was given access to with `requestDevice()`, which the origin can
+/// get again with `getDevices()` until the grant is revoked.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BluetoothDeviceGrant {
+    pub origin: ImmutableOrigin,
+    pub address: String,
+    pub name: Option<String>,
+    /// The UUIDs of the services of the device that the origin may use.
+    pub services: Vec<String>,
+    /// Whether the grant was made in a private browsing context, and is forgotten when Servo
+    /// exits.
+    pub private: bool,
+}
+
 pub type BluetoothServicesMsg = Vec<BluetoothServiceMsg>;
 
 pub type BluetoothCharacteristicsMsg = Vec<BluetoothCharacteristicMsg>;
@@ -77,8 +102,32 @@ pub type BluetoothResponseResult = Result<BluetoothResponse, BluetoothError>;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum BluetoothRequest {
-    RequestDevice(RequestDeviceoptions, IpcSender<BluetoothResponseResult>),
-    GATTServerConnect(String, IpcSender<BluetoothResponseResult>),
+    /// Scan for the devices matching the options, and let the user choose one of them for the
+    /// origin in the given webview.
+    RequestDevice(
+        Option<TopLevelBrowsingContextId>,
+        ImmutableOrigin,
+        RequestDeviceoptions,
+        IpcSender<BluetoothResponseResult>,
+    ),
+    /// Get the devices that an origin was given access to before.
+    GetDevices(ImmutableOrigin, IpcSender<BluetoothResponseResult>),
+    /// Get the devices that all the origins were given access to.
+    GetDeviceGrants(IpcSender<Vec<BluetoothDeviceGrant>>),
+    /// Revoke the access of the given origin, or of all of them, to the device with the given
+    /// address, or to all the devices they were given access to.
+    RevokeDeviceGrants(Option<ImmutableOrigin>, Option<String>),
+    /// Connect to the GATT server of a device. The first sender is notified if the device
+    /// disconnects afterwards, and replaces any earlier one with the same id, the second field.
+    GATTServerConnect(
+        String,
+        String,
+        IpcSender<()>,
+        IpcSender<BluetoothResponseResult>,
+    ),
+    /// Forget a disconnection listener registered with `GATTServerConnect`, given the device id
+    /// and the listener id.
+    RemoveDisconnectionListener(String, String),
     GATTServerDisconnect(String, IpcSender<BluetoothResult<()>>),
     GetGATTChildren(
         String,
@@ -89,23 +138,48 @@ pub enum BluetoothRequest {
     ),
     ReadValue(String, IpcSender<BluetoothResponseResult>),
     WriteValue(String, Vec<u8>, IpcSender<BluetoothResponseResult>),
-    EnableNotification(String, bool, IpcSender<BluetoothResponseResult>),
-    WatchAdvertisements(String, IpcSender<BluetoothResponseResult>),
+    /// Subscribe to the notifications of a characteristic. The second field identifies the
+    /// subscription, and the new values of the characteristic are sent through the first sender.
+    StartNotifications(
+        String,
+        String,
+        IpcSender<Vec<u8>>,
+        IpcSender<BluetoothResponseResult>,
+    ),
+    /// End a subscription started with `StartNotifications`.
+    StopNotifications(String, String, IpcSender<BluetoothResponseResult>),
+    /// Start watching the advertisements of a device. The second field identifies the watch,
+    /// and the advertisements are sent through the first sender.
+    WatchAdvertisements(
+        String,
+        String,
+        IpcSender<BluetoothAdvertisementMsg>,
+        IpcSender<BluetoothResponseResult>,
+    ),
+    /// Stop a watch started with `WatchAdvertisements`.
+    UnwatchAdvertisements(String),
     SetRepresentedToNull(Vec<String>, Vec<String>, Vec<String>),
     IsRepresentedDeviceNull(String, IpcSender<bool>),
     GetAvailability(IpcSender<BluetoothResponseResult>),
+    /// Send the availability of Bluetooth to the sender whenever it changes. The first field
+    /// identifies the watch.
+    WatchAvailability(String, IpcSender<bool>),
+    /// Stop a watch started with `WatchAvailability`.
+    UnwatchAvailability(String),
     MatchesFilter(
         String,
         BluetoothScanfilterSequence,
         IpcSender<BluetoothResult<bool>>,
     ),
     Test(String, IpcSender<BluetoothResult<()>>),
+    SimulateDisconnection(String, IpcSender<BluetoothResult<()>>),
     Exit,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum BluetoothResponse {
     RequestDevice(BluetoothDeviceMsg),
+    GetDevices(Vec<BluetoothDeviceMsg>),
     GATTServerConnect(bool),
     GetPrimaryServices(BluetoothServicesMsg, bool),
     GetIncludedServices(BluetoothServicesMsg, bool),
@@ -113,7 +187,8 @@ pub enum BluetoothResponse {
     GetDescriptors(BluetoothDescriptorsMsg, bool),
     ReadValue(Vec<u8>),
     WriteValue(Vec<u8>),
-    EnableNotification(()),
-    WatchAdvertisements(()),
+    StartNotifications,
+    StopNotifications,
+    WatchAdvertisements(String),
     GetAvailability(bool),
 }
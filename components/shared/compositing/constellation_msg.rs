@@ -4,13 +4,20 @@
 
 use std::collections::HashMap;
 use std::fmt;
+use std::ops::Range;
 use std::time::Duration;
 
-use base::id::{BrowsingContextId, PipelineId, TopLevelBrowsingContextId, WebViewId};
+use base::id::{
+    BrowsingContextId, NotificationId, PipelineId, TopLevelBrowsingContextId, WebViewId,
+};
 use base::Epoch;
-use embedder_traits::Cursor;
+use embedder_traits::{
+    AutoplayPolicy, Cursor, GeolocationUpdate, ImeEvent, JavaScriptEvaluationId, NotificationEvent,
+    WebViewState,
+};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
+use net_traits::FetchChannels;
 use script_traits::{
     AnimationTickType, CompositorEvent, GamepadEvent, LogEntry, MediaSessionActionType,
     TraversalDirection, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
@@ -36,12 +43,29 @@ pub enum ConstellationMsg {
     Keyboard(KeyboardEvent),
     /// Whether to allow script to navigate.
     AllowNavigationResponse(PipelineId, bool),
+    /// Whether to let a slow script continue running (true) or to stop it (false).
+    SlowScriptResponse(PipelineId, bool),
     /// Request to load a page.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Clear the network cache.
     ClearCache,
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(TopLevelBrowsingContextId, TraversalDirection),
+    /// Request to traverse the joint session history of the provided browsing context to the
+    /// entry at the given index.
+    TraverseHistoryToIndex(TopLevelBrowsingContextId, usize),
+    /// Remove the entries of the joint session history of the provided browsing context that
+    /// are outside of the given range of indices, which must contain the current entry.
+    TruncateHistory(TopLevelBrowsingContextId, Range<usize>),
+    /// Replace the joint session history of the provided browsing context with entries for the
+    /// given URLs, the current document standing for the entry at the given index.
+    RestoreHistory(TopLevelBrowsingContextId, Vec<ServoUrl>, usize),
+    /// Request the session history of the provided browsing context and the state of its
+    /// current document, or `None` if it has no document.
+    GetWebViewState(TopLevelBrowsingContextId, IpcSender<Option<WebViewState>>),
+    /// Load the current entry of the given state in the provided browsing context, then restore
+    /// the rest of the state once the document has loaded.
+    RestoreWebViewState(TopLevelBrowsingContextId, WebViewState),
     /// Inform the constellation of a window being resized.
     WindowSize(TopLevelBrowsingContextId, WindowSizeData, WindowSizeType),
     /// Requests that the constellation instruct layout to begin a new tick of the animation.
@@ -50,6 +74,8 @@ pub enum ConstellationMsg {
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
     Reload(TopLevelBrowsingContextId),
+    /// Stop loading a top-level browsing context.
+    Stop(TopLevelBrowsingContextId),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Create a new top level browsing context.
@@ -78,12 +104,31 @@ pub enum ConstellationMsg {
     MediaSessionAction(MediaSessionActionType),
     /// Set whether to use less resources, by stopping animations and running timers at a heavily limited rate.
     SetWebViewThrottled(TopLevelBrowsingContextId, bool),
+    /// Set whether the audio of a webview is silenced.
+    SetWebViewMuted(TopLevelBrowsingContextId, bool),
+    /// Set whether the media of a webview may start playing without the user interacting with it.
+    SetWebViewAutoplayPolicy(TopLevelBrowsingContextId, AutoplayPolicy),
     /// Virtual keyboard was dismissed
     IMEDismissed,
+    /// The input method of the embedder edited the focused element.
+    Ime(ImeEvent),
+    /// Run a script in the top-level document of a webview, and report its result to the
+    /// embedder.
+    EvaluateJavaScript(TopLevelBrowsingContextId, JavaScriptEvaluationId, String),
     /// Notify the embedder that it needs to present a new frame.
     ReadyToPresent(Vec<WebViewId>),
     /// Gamepad state has changed
     Gamepad(GamepadEvent),
+    /// The position of the device changed, or can't be acquired anymore.
+    GeolocationUpdate(GeolocationUpdate),
+    /// Something happened to a notification shown by the embedder.
+    NotificationEvent(NotificationId, NotificationEvent),
+    /// Fetch the resource of the download that the navigation of the given pipeline turned
+    /// into, with the request of the navigation.
+    FetchDownload(PipelineId, FetchChannels),
+    /// Forget the request of the download that the navigation of the given pipeline turned
+    /// into, as it won't be fetched.
+    DiscardDownload(PipelineId),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -104,12 +149,19 @@ impl ConstellationMsg {
             IsReadyToSaveImage(..) => "IsReadyToSaveImage",
             Keyboard(..) => "Keyboard",
             AllowNavigationResponse(..) => "AllowNavigationResponse",
+            SlowScriptResponse(..) => "SlowScriptResponse",
             LoadUrl(..) => "LoadUrl",
             TraverseHistory(..) => "TraverseHistory",
+            TraverseHistoryToIndex(..) => "TraverseHistoryToIndex",
+            TruncateHistory(..) => "TruncateHistory",
+            RestoreHistory(..) => "RestoreHistory",
+            GetWebViewState(..) => "GetWebViewState",
+            RestoreWebViewState(..) => "RestoreWebViewState",
             WindowSize(..) => "WindowSize",
             TickAnimation(..) => "TickAnimation",
             WebDriverCommand(..) => "WebDriverCommand",
             Reload(..) => "Reload",
+            Stop(..) => "Stop",
             LogEntry(..) => "LogEntry",
             NewWebView(..) => "NewWebView",
             WebViewOpened(..) => "WebViewOpened",
@@ -124,10 +176,18 @@ impl ConstellationMsg {
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
             SetWebViewThrottled(..) => "SetWebViewThrottled",
+            SetWebViewMuted(..) => "SetWebViewMuted",
+            SetWebViewAutoplayPolicy(..) => "SetWebViewAutoplayPolicy",
             IMEDismissed => "IMEDismissed",
+            Ime(..) => "Ime",
+            EvaluateJavaScript(..) => "EvaluateJavaScript",
             ClearCache => "ClearCache",
             ReadyToPresent(..) => "ReadyToPresent",
             Gamepad(..) => "Gamepad",
+            GeolocationUpdate(..) => "GeolocationUpdate",
+            NotificationEvent(..) => "NotificationEvent",
+            FetchDownload(..) => "FetchDownload",
+            DiscardDownload(..) => "DiscardDownload",
         }
     }
 }
@@ -54,6 +54,12 @@ pub enum DevtoolsControlMsg {
 pub enum ChromeToDevtoolsControlMsg {
     /// A new client has connected to the server.
     AddClient(TcpStream),
+    /// Accept clients on the given port, replacing any previous listener. The listener is
+    /// identified by the given generation.
+    StartListening(u16, u64),
+    /// Stop accepting clients and disconnect the connected ones, if the listener is still the
+    /// one with the given generation.
+    StopListening(u64),
     /// The browser is shutting down.
     ServerExitMsg,
     /// A network event occurred (request, reply, etc.). The actor with the
@@ -96,6 +102,18 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page title change
     TitleChanged(PipelineId, String),
+
+    /// Report that the focused element of the given pipeline changed
+    FocusChanged(PipelineId, Option<NodeInfo>),
+
+    /// Report that the selection of the given pipeline changed
+    SelectionChanged(PipelineId, SelectionInfo),
+
+    /// Report that an input event was dispatched to the given pipeline
+    InputEventDispatched(PipelineId, InputEventInfo),
+
+    /// Report the style invalidations found by a reflow of the given pipeline
+    LayoutInvalidated(PipelineId, LayoutInvalidationInfo),
 }
 
 /// Serialized JS return values
@@ -139,13 +157,65 @@ pub struct NodeInfo {
     pub incompleteValue: bool,
 }
 
+/// The state of the selection of a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SelectionInfo {
+    pub anchor_node: Option<NodeInfo>,
+    pub anchor_offset: u32,
+    pub focus_node: Option<NodeInfo>,
+    pub focus_offset: u32,
+    pub is_collapsed: bool,
+    pub text: String,
+}
+
+/// An input event that was dispatched to a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InputEventInfo {
+    /// The type of the DOM event, e.g. `mousedown`, `keyup` or `gamepadconnected`.
+    pub event_type: String,
+    /// The node the event was dispatched to, found by hit testing for pointer events and
+    /// from the focus for keyboard events.
+    pub target: Option<NodeInfo>,
+    /// How long it took to dispatch the event, in milliseconds.
+    pub dispatch_duration: f64,
+}
+
+/// An element whose style changed during a reflow.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DamagedNodeInfo {
+    pub node: NodeInfo,
+    /// The kinds of layout work the change calls for, e.g. `REPAINT_ONLY` or `REBUILD_BOX`.
+    pub damage: Vec<String>,
+    /// The depth of the element in the DOM.
+    pub depth: usize,
+    /// Why the element was restyled, e.g. `attribute-change`, `dom-mutation`, `font-load` or
+    /// `inherited` when it was only restyled because of its ancestors or earlier siblings.
+    pub causes: Vec<String>,
+}
+
+/// The style invalidations found by a reflow and how much layout work they caused.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LayoutInvalidationInfo {
+    /// The element from which the style traversal started.
+    pub dirty_root: Option<NodeInfo>,
+    pub damaged_nodes: Vec<DamagedNodeInfo>,
+    /// The number of elements visited by the style traversal.
+    pub elements_traversed: usize,
+    /// Whether the box tree was `skipped`, updated `incremental`ly or `rebuilt` from the root.
+    pub box_tree_update: String,
+    /// Whether the new styles were applied to the existing fragments without layout.
+    pub layout_skipped: bool,
+    /// What made the script thread reflow, e.g. `RefreshTick` or `Query`.
+    pub reflow_reason: String,
+}
+
 pub struct StartedTimelineMarker {
     name: String,
     start_time: PreciseTime,
     start_stack: Option<Vec<()>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TimelineMarker {
     pub name: String,
     pub start_time: PreciseTime,
@@ -158,6 +228,29 @@ pub struct TimelineMarker {
 pub enum TimelineMarkerType {
     Reflow,
     DOMEvent,
+    MicrotaskCheckpoint,
+    LongMicrotask,
+}
+
+/// The security state of a document, as it applies to the scripts and frames it loads.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SecurityInfo {
+    pub origin: String,
+    pub is_secure_context: bool,
+    pub content_security_policies: Vec<ContentSecurityPolicyInfo>,
+    /// The keywords of the `sandbox` attribute of the iframe containing the document, if it
+    /// is sandboxed. This is only known when the iframe is in the same script thread.
+    pub sandbox: Option<Vec<String>>,
+    pub referrer_policy: Option<String>,
+}
+
+/// A content security policy in effect for a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ContentSecurityPolicyInfo {
+    /// The serialized directives of the policy, e.g. `script-src 'self'`.
+    pub directives: Vec<String>,
+    /// Whether violations are only reported instead of being blocked.
+    pub report_only: bool,
 }
 
 /// The properties of a DOM node as computed by layout.
@@ -208,12 +301,21 @@ pub enum DevtoolScriptControlMsg {
     GetDocumentElement(PipelineId, IpcSender<Option<NodeInfo>>),
     /// Retrieve the details of the child nodes of the given node in the given pipeline.
     GetChildren(PipelineId, String, IpcSender<Option<Vec<NodeInfo>>>),
+    /// Retrieve the security state (CSP, sandboxing, referrer policy) of the document of the
+    /// given pipeline.
+    GetSecurityInfo(PipelineId, IpcSender<Option<SecurityInfo>>),
     /// Retrieve the computed layout properties of the given node in the given pipeline.
     GetLayout(PipelineId, String, IpcSender<Option<ComputedNodeLayout>>),
+    /// Retrieve the details of the elements hit at the given point, in CSS pixels relative to the
+    /// viewport, from topmost to bottommost.
+    GetElementsFromPoint(PipelineId, f32, f32, IpcSender<Vec<NodeInfo>>),
     /// Update a given node's attributes with a list of modifications.
     ModifyAttribute(PipelineId, String, Vec<Modification>),
     /// Request live console messages for a given pipeline (true if desired, false otherwise).
     WantsLiveNotifications(PipelineId, bool),
+    /// A devtools server started (true) or stopped (false) running. Events that only a
+    /// connected client could show, such as console messages, are not sent while none runs.
+    SetServerRunning(bool),
     /// Request live notifications for a given set of timeline events for a given pipeline.
     SetTimelineMarkers(
         PipelineId,
@@ -227,6 +329,14 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Evaluate media queries in the given pipeline against the `print` media type instead of
+    /// `screen` (true to enable the simulation, false to disable it).
+    SimulatePrintMedia(PipelineId, bool),
+    /// Pause the event loop of the given pipeline: until it is resumed, only devtools messages
+    /// are handled, so that no task runs for any document of that event loop.
+    Pause(PipelineId),
+    /// Resume an event loop that was paused for the given pipeline.
+    Resume(PipelineId),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
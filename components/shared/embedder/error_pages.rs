@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The pages shown in place of documents that failed to load, which embedders can replace with
+//! their own.
+
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::resources::{self, Resource};
+
+lazy_static! {
+    static ref PROVIDER: RwLock<Option<Box<dyn ErrorPageProvider>>> = RwLock::new(None);
+}
+
+/// A page shown in place of a document that could not be loaded. Templates refer to the details
+/// of the failure with `${name}` placeholders.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorPage {
+    /// The certificate of the server failed validation. The page can offer to accept it anyway
+    /// with the `${bytes}` of the certificate and the `${secret}` of privileged requests, which
+    /// are only given for hosts that are not HSTS hosts. Placeholders: `${reason}`, `${bytes}`,
+    /// `${secret}`.
+    BadCertificate,
+    /// The request failed for another reason. Placeholders: `${reason}`.
+    NetworkError,
+    /// The host name of the request could not be resolved. Placeholders: `${host}`, `${reason}`.
+    DnsResolution,
+    /// The document crashed, either in a top-level browsing context or in a subframe.
+    /// Placeholders: `${details}`.
+    Crash,
+}
+
+/// Provides the templates of the error pages, in place of the ones of Servo.
+pub trait ErrorPageProvider: Send + Sync {
+    /// The template of the given page, or `None` to use the one of Servo.
+    fn template(&self, page: ErrorPage) -> Option<String>;
+}
+
+/// Set the provider of the error page templates of this process. Like the resource reader, it
+/// has to be set in every content process to be used there.
+pub fn set_provider(provider: Option<Box<dyn ErrorPageProvider>>) {
+    *PROVIDER.write().unwrap() = provider;
+}
+
+/// The template of `page`, from the provider of the embedder if it has one.
+pub fn template(page: ErrorPage) -> String {
+    let template = PROVIDER
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|provider| provider.template(page));
+    template.unwrap_or_else(|| {
+        resources::read_string(match page {
+            ErrorPage::BadCertificate => Resource::BadCertHTML,
+            ErrorPage::NetworkError | ErrorPage::DnsResolution => Resource::NetErrorHTML,
+            ErrorPage::Crash => Resource::CrashHTML,
+        })
+    })
+}
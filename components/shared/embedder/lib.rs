@@ -2,18 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+pub mod error_pages;
 pub mod resources;
+pub mod user_script;
 
+use std::collections::HashMap;
 use std::fmt::{Debug, Error, Formatter};
+use std::ops::Range;
 
-use base::id::{PipelineId, TopLevelBrowsingContextId, WebViewId};
+use base::id::{NotificationId, PipelineId, TopLevelBrowsingContextId, WebViewId};
 use crossbeam_channel::{Receiver, Sender};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use log::warn;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
 
@@ -169,17 +173,17 @@ pub enum EmbedderMsg {
     /// Sends an unconsumed key event back to the embedder.
     Keyboard(KeyboardEvent),
     /// Gets system clipboard contents
-    GetClipboardContents(IpcSender<String>),
+    GetClipboardContents(IpcSender<ClipboardContents>),
     /// Sets system clipboard contents
-    SetClipboardContents(String),
+    SetClipboardContents(ClipboardContents),
     /// Changes the cursor.
     SetCursor(Cursor),
     /// A favicon was detected
     NewFavicon(ServoUrl),
     /// `<head>` tag finished parsing
     HeadParsed,
-    /// The history state has changed.
-    HistoryChanged(Vec<ServoUrl>, usize),
+    /// The history state has changed. The entries are followed by the index of the current one.
+    HistoryChanged(Vec<SessionHistoryEntry>, usize),
     /// Enter or exit fullscreen
     SetFullscreenState(bool),
     /// The load of a page has begun
@@ -188,12 +192,29 @@ pub enum EmbedderMsg {
     LoadComplete,
     /// A pipeline panicked. First string is the reason, second one is the backtrace.
     Panic(String, Option<String>),
-    /// Open dialog to select bluetooth device.
-    GetSelectedBluetoothDevice(Vec<String>, IpcSender<Option<String>>),
+    /// Open dialog to select bluetooth device on behalf of the given origin. The embedder answers
+    /// with the address of the chosen device, or `None` if the user cancelled the dialog.
+    GetSelectedBluetoothDevice(
+        ImmutableOrigin,
+        Vec<BluetoothDeviceDescription>,
+        IpcSender<Option<String>>,
+    ),
+    /// List the audio output devices that the given origin may render to with
+    /// `AudioContext.setSinkId()`. The embedder answers with the ids of the devices exposed to
+    /// the origin, and the others are treated as if they did not exist.
+    GetAudioOutputDevices(
+        ImmutableOrigin,
+        Vec<AudioOutputDeviceDescription>,
+        IpcSender<Vec<String>>,
+    ),
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
     SelectFiles(Vec<FilterPattern>, bool, IpcSender<Option<Vec<String>>>),
-    /// Open interface to request permission specified by prompt.
-    PromptPermission(PermissionPrompt, IpcSender<PermissionRequest>),
+    /// Open interface to request permission specified by prompt, on behalf of the given origin.
+    PromptPermission(
+        PermissionPrompt,
+        ImmutableOrigin,
+        IpcSender<PermissionRequest>,
+    ),
     /// Request to present an IME to the user when an editable element is focused.
     /// If the input is text, the second parameter defines the pre-existing string
     /// text content and the zero-based index into the string locating the insertion point.
@@ -201,6 +222,8 @@ pub enum EmbedderMsg {
     ShowIME(InputMethodType, Option<(String, i32)>, bool, DeviceIntRect),
     /// Request to hide the IME when the editable element is blurred.
     HideIME,
+    /// The text, selection or composition of the focused editable element changed.
+    UpdateIME(TextInputState),
     /// Servo has shut down
     Shutdown,
     /// Report a complete sampled profile
@@ -214,6 +237,36 @@ pub enum EmbedderMsg {
     ReadyToPresent(Vec<WebViewId>),
     /// The given event was delivered to a pipeline in the given browser.
     EventDelivered(CompositorEventVariant),
+    /// A script in the given pipeline has been running for longer than the slow script timeout.
+    /// The embedder should answer with `EmbedderEvent::SlowScriptResponse`.
+    SlowScript(PipelineId),
+    /// A navigation led to a resource that should be saved instead of displayed.
+    DownloadRequested(DownloadRequest),
+    /// The user asked for a context menu and the page did not cancel the `contextmenu` event.
+    ContextMenuRequested(ContextMenuInfo),
+    /// A page posted a message to a script message handler of the embedder. The reply is either
+    /// a JSON value that fulfills the promise of the page, or a reason to reject it.
+    ScriptMessage(ScriptMessage, IpcSender<Result<String, String>>),
+    /// The webview started or stopped playing audio that the user can hear.
+    AudibleChanged(bool),
+    /// A page used the console API, or a script error was not handled by the page.
+    ConsoleMessage(ConsoleMessage),
+    /// The result of a script run with `Servo::evaluate_javascript`, which passes it to the
+    /// callback of the evaluation. If the script evaluated to a promise, this is only sent once
+    /// the promise has settled.
+    JavaScriptEvaluated(
+        JavaScriptEvaluationId,
+        Result<JSValue, JavaScriptEvaluationError>,
+    ),
+    /// Pages started waiting for the position of the device. The flag is whether one of them
+    /// asked for a high accuracy position.
+    StartGeolocationUpdates(bool),
+    /// No page waits for the position of the device anymore.
+    StopGeolocationUpdates,
+    /// A page created a notification with the Notifications API.
+    ShowNotification(Notification),
+    /// A notification was closed by its page, or its page was closed.
+    CloseNotification(NotificationId),
 }
 
 /// The variant of CompositorEvent that was delivered to a pipeline.
@@ -227,6 +280,7 @@ pub enum CompositorEventVariant {
     KeyboardEvent,
     CompositionEvent,
     IMEDismissedEvent,
+    ImeEvent,
     GamepadEvent,
 }
 
@@ -252,10 +306,12 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::LoadComplete => write!(f, "LoadComplete"),
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
             EmbedderMsg::GetSelectedBluetoothDevice(..) => write!(f, "GetSelectedBluetoothDevice"),
+            EmbedderMsg::GetAudioOutputDevices(..) => write!(f, "GetAudioOutputDevices"),
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
             EmbedderMsg::PromptPermission(..) => write!(f, "PromptPermission"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
+            EmbedderMsg::UpdateIME(..) => write!(f, "UpdateIME"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
             EmbedderMsg::AllowOpeningWebView(..) => write!(f, "AllowOpeningWebView"),
             EmbedderMsg::WebViewOpened(..) => write!(f, "WebViewOpened"),
@@ -268,10 +324,279 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::ShowContextMenu(..) => write!(f, "ShowContextMenu"),
             EmbedderMsg::ReadyToPresent(..) => write!(f, "ReadyToPresent"),
             EmbedderMsg::EventDelivered(..) => write!(f, "HitTestedEvent"),
+            EmbedderMsg::SlowScript(..) => write!(f, "SlowScript"),
+            EmbedderMsg::DownloadRequested(..) => write!(f, "DownloadRequested"),
+            EmbedderMsg::ContextMenuRequested(..) => write!(f, "ContextMenuRequested"),
+            EmbedderMsg::ScriptMessage(..) => write!(f, "ScriptMessage"),
+            EmbedderMsg::AudibleChanged(..) => write!(f, "AudibleChanged"),
+            EmbedderMsg::ConsoleMessage(..) => write!(f, "ConsoleMessage"),
+            EmbedderMsg::JavaScriptEvaluated(..) => write!(f, "JavaScriptEvaluated"),
+            EmbedderMsg::StartGeolocationUpdates(..) => write!(f, "StartGeolocationUpdates"),
+            EmbedderMsg::StopGeolocationUpdates => write!(f, "StopGeolocationUpdates"),
+            EmbedderMsg::ShowNotification(..) => write!(f, "ShowNotification"),
+            EmbedderMsg::CloseNotification(..) => write!(f, "CloseNotification"),
         }
     }
 }
 
+/// An entry of the session history of a webview, as reported to the embedder.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionHistoryEntry {
+    pub url: ServoUrl,
+    /// The title of the document of this entry, if it is still loaded.
+    pub title: Option<String>,
+    /// The state of the document of this entry, if it was saved with the state of its webview.
+    #[serde(default)]
+    pub document_state: Option<SavedDocumentState>,
+}
+
+/// The state of a webview that is needed to open it again later as it was, for example to
+/// discard a background tab and restore it when the user comes back to it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebViewState {
+    pub session_history: Vec<SessionHistoryEntry>,
+    /// The index of the current entry in `session_history`.
+    pub current_index: usize,
+}
+
+impl WebViewState {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Serializing a webview state never fails")
+    }
+
+    /// Read a state serialized with `to_bytes`, or `None` if the bytes are not one.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// The state of a document that is lost when it is loaded again.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SavedDocumentState {
+    /// The scroll position of the viewport, in CSS pixels.
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    /// The form controls whose value the user can change, in tree order. Passwords and file
+    /// selections are not saved.
+    pub form_controls: Vec<FormControlState>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FormControlState {
+    /// The `name` attribute of the control, used to check that the document still has the same
+    /// form when the state is restored.
+    pub name: String,
+    pub value: FormControlValue,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FormControlValue {
+    /// The value of a text field or `<textarea>`.
+    Text(String),
+    /// The checkedness of a checkbox or radio button.
+    Checked(bool),
+    /// The indices of the options of a `<select>` that are selected.
+    SelectedOptions(Vec<usize>),
+}
+
+/// A message posted with `window.postMessageToEmbedder()`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScriptMessage {
+    /// The name of the handler the message is for.
+    pub handler_name: String,
+    /// The message, serialized as JSON.
+    pub body: String,
+    /// The origin of the document that posted the message.
+    pub origin: ImmutableOrigin,
+}
+
+/// The severity of a console message.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ConsoleLogLevel {
+    Log,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A message logged with the console API, or an uncaught script error.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLogLevel,
+    pub message: String,
+    /// The URL of the script that logged the message or threw the error.
+    pub source_url: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Identifies a script evaluation started with `Servo::evaluate_javascript`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct JavaScriptEvaluationId(pub usize);
+
+/// A JavaScript value, copied out of the script engine.
+///
+/// Typed arrays are serialized as arrays of numbers. Values that can't be represented, like
+/// functions and symbols, are serialized as `Undefined`, the same way `JSON.stringify()` skips
+/// them.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum JSValue {
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JSValue>),
+    Object(HashMap<String, JSValue>),
+}
+
+/// Why a script run with `Servo::evaluate_javascript` did not produce a value.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum JavaScriptEvaluationError {
+    /// The webview does not exist, has no document to run the script in, or was closed before
+    /// the script produced a value.
+    WebViewNotFound,
+    /// The script threw, or evaluated to a promise that was rejected.
+    Exception {
+        message: String,
+        source_url: String,
+        line: u32,
+        column: u32,
+    },
+    /// The result contains a value that can't be copied out of the script engine, like an
+    /// object that contains itself or that is nested too deeply.
+    SerializationError,
+}
+
+/// A position of the device, as reported by the embedder for the Geolocation API.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeolocationPosition {
+    /// The latitude and longitude, in decimal degrees.
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The accuracy of the latitude and longitude, in meters.
+    pub accuracy: f64,
+    /// The height above the WGS84 ellipsoid, in meters, and its accuracy.
+    pub altitude: Option<f64>,
+    pub altitude_accuracy: Option<f64>,
+    /// The direction of travel, in degrees clockwise from true north.
+    pub heading: Option<f64>,
+    /// The speed, in meters per second.
+    pub speed: Option<f64>,
+    /// When the position was acquired, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum GeolocationUpdate {
+    Position(GeolocationPosition),
+    /// The position of the device can't be acquired, for the given reason.
+    PositionUnavailable(String),
+}
+
+/// The contents of the system clipboard, in the formats that pages can read and write. The
+/// formats that are not set are not available, or were not written.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClipboardContents {
+    /// The contents as plain text, for the `text/plain` type.
+    pub text: Option<String>,
+    /// The contents as markup, for the `text/html` type.
+    pub html: Option<String>,
+    /// An image encoded as PNG, for the `image/png` type.
+    pub png: Option<Vec<u8>>,
+}
+
+impl ClipboardContents {
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+}
+
+/// A notification created by a page with the Notifications API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Notification {
+    pub id: NotificationId,
+    /// The origin of the page, which embedders usually show with the notification.
+    pub origin: ImmutableOrigin,
+    pub title: String,
+    pub body: String,
+    /// A notification replaces the one of the same origin with the same tag, if it is not
+    /// empty.
+    pub tag: String,
+    pub icon: Option<ServoUrl>,
+    /// The language of the title and body, as a language tag, or empty if unknown.
+    pub lang: String,
+    pub direction: NotificationDirection,
+    /// Whether the notification should be shown without sound or vibration.
+    pub silent: bool,
+    /// Whether the notification should stay visible until the user closes it.
+    pub require_interaction: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NotificationDirection {
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// What happened to a notification, for its page to fire the matching event.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NotificationEvent {
+    /// The notification is shown to the user.
+    Show,
+    /// The notification could not be shown.
+    Error,
+    /// The user activated the notification.
+    Click,
+    /// The notification was closed, by the user or by the system.
+    Close,
+}
+
+/// What was under the pointer when the user asked for a context menu, for the embedder to
+/// decide which entries to show.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ContextMenuInfo {
+    /// The target of the innermost link that was clicked.
+    pub link_url: Option<ServoUrl>,
+    /// The current source of the image that was clicked.
+    pub image_url: Option<ServoUrl>,
+    /// The text selected in the document, if any.
+    pub selected_text: Option<String>,
+    /// Whether the click was on a field that accepts text input.
+    pub is_editable: bool,
+    /// The state of the audio or video element that was clicked.
+    pub media: Option<ContextMenuMediaInfo>,
+}
+
+/// The state of a media element, as reported in a [`ContextMenuInfo`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ContextMenuMediaInfo {
+    pub source_url: Option<ServoUrl>,
+    pub is_video: bool,
+    pub paused: bool,
+    pub muted: bool,
+    pub has_controls: bool,
+}
+
+/// A resource that a navigation did not display, because it was sent as an attachment or
+/// because its type cannot be rendered.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DownloadRequest {
+    pub url: ServoUrl,
+    /// The pipeline whose navigation turned into this download. The constellation keeps the
+    /// request of the navigation, to fetch the resource again with it.
+    pub pipeline_id: PipelineId,
+    /// The name to save the resource as, if the response or its URL suggested one.
+    pub suggested_filename: Option<String>,
+    /// The essence of the MIME type of the response, such as `application/pdf`.
+    pub mime_type: Option<String>,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -339,8 +664,29 @@ pub enum MediaSessionEvent {
     SetPositionState(MediaPositionState),
 }
 
+/// Whether the media of a webview may start playing without the user interacting with it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AutoplayPolicy {
+    /// Media may always start playing.
+    #[default]
+    Allowed,
+    /// Media may start playing on its own only if it plays muted, and otherwise only while the
+    /// page has transient activation.
+    AllowedMuted,
+    /// Media may only start playing while the page has transient activation.
+    Blocked,
+}
+
+/// How the media of a webview is played, as set by the embedder.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MediaPolicy {
+    /// Whether the audio of the webview is silenced, without the page being able to tell.
+    pub muted: bool,
+    pub autoplay: AutoplayPolicy,
+}
+
 /// Enum with variants that match the DOM PermissionName enum
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PermissionName {
     Geolocation,
     Notifications,
@@ -353,6 +699,7 @@ pub enum PermissionName {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    ClipboardRead,
 }
 
 /// Information required to display a permission prompt
@@ -362,6 +709,15 @@ pub enum PermissionPrompt {
     Request(PermissionName),
 }
 
+impl PermissionPrompt {
+    pub fn permission_name(&self) -> PermissionName {
+        match *self {
+            PermissionPrompt::Insecure(permission_name) |
+            PermissionPrompt::Request(permission_name) => permission_name,
+        }
+    }
+}
+
 /// Status for prompting user for permission.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum PermissionRequest {
@@ -369,10 +725,24 @@ pub enum PermissionRequest {
     Denied,
 }
 
+/// A Bluetooth device that a page can be given access to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BluetoothDeviceDescription {
+    pub address: String,
+    pub name: String,
+}
+
+/// An audio output device that a page can render to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AudioOutputDeviceDescription {
+    pub device_id: String,
+    pub label: String,
+}
+
 /// Used to specify the kind of input method editor appropriate to edit a field.
 /// This is a subset of htmlinputelement::InputType because some variants of InputType
 /// don't make sense in this context.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum InputMethodType {
     Color,
     Date,
@@ -388,3 +758,31 @@ pub enum InputMethodType {
     Url,
     Week,
 }
+
+/// The state of the focused editable element, for the input method of the embedder.
+///
+/// All ranges are byte offsets into `text`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TextInputState {
+    pub input_method_type: InputMethodType,
+    /// The value of the element. Lines of a `<textarea>` are separated by `\n`.
+    pub text: String,
+    /// The selection, or an empty range at the caret.
+    pub selection: Range<usize>,
+    /// The text of the composition in progress, if any.
+    pub composition: Option<Range<usize>>,
+    pub multiline: bool,
+    /// Where the caret is drawn. This is currently the content box of the whole element.
+    pub caret_rect: DeviceIntRect,
+}
+
+/// An edit from the input method of the embedder, applied to the focused editable element.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ImeEvent {
+    /// Replace the text of the composition in progress, starting one if needed.
+    SetComposition(String),
+    /// Finish the composition, replacing its text with the given committed text.
+    Commit(String),
+    /// Abandon the composition and remove its text.
+    Cancel,
+}
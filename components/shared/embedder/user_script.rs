@@ -0,0 +1,225 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scripts that embedders inject into the documents whose URL matches their patterns, with the
+//! semantics of the content scripts of WebExtensions.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+/// When a user script runs in a document.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UserScriptRunAt {
+    /// Once the document is created, before any of its own scripts run.
+    DocumentStart,
+    /// Once the document is parsed, right before `DOMContentLoaded` is fired.
+    DocumentEnd,
+    /// Once the document and its subresources are loaded, right after `load` is fired.
+    #[default]
+    DocumentIdle,
+}
+
+/// A script injected into the documents whose URL matches one of its `matches` patterns and
+/// none of its `exclude_matches` patterns.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserScript {
+    pub source: String,
+    pub matches: Vec<MatchPattern>,
+    pub exclude_matches: Vec<MatchPattern>,
+    pub run_at: UserScriptRunAt,
+}
+
+impl UserScript {
+    pub fn new(source: String, matches: Vec<MatchPattern>) -> Self {
+        Self {
+            source,
+            matches,
+            exclude_matches: Vec::new(),
+            run_at: UserScriptRunAt::default(),
+        }
+    }
+
+    /// Whether this script is injected into a document with the given URL.
+    pub fn matches_url(&self, url: &ServoUrl) -> bool {
+        self.matches.iter().any(|pattern| pattern.matches(url)) &&
+            !self
+                .exclude_matches
+                .iter()
+                .any(|pattern| pattern.matches(url))
+    }
+}
+
+/// Why a string is not a valid match pattern.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MatchPatternError {
+    MissingSchemeSeparator,
+    InvalidScheme,
+    InvalidHost,
+    MissingPath,
+}
+
+impl fmt::Display for MatchPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            MatchPatternError::MissingSchemeSeparator => "missing \"://\"",
+            MatchPatternError::InvalidScheme => "invalid scheme",
+            MatchPatternError::InvalidHost => "invalid host",
+            MatchPatternError::MissingPath => "missing path",
+        };
+        write!(f, "Invalid match pattern: {reason}")
+    }
+}
+
+/// <https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/Match_patterns>
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MatchPattern {
+    scheme: SchemePattern,
+    host: HostPattern,
+    /// Matched against the path and query of URLs, `*` matching any sequence of characters.
+    path: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+enum SchemePattern {
+    /// `<all_urls>`, which matches all the schemes below.
+    All,
+    /// `*`, which matches the schemes of the web.
+    Web,
+    Exact(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+enum HostPattern {
+    /// `*`, which matches any host. This is also used for the empty host of `file:` patterns.
+    Any,
+    /// `*.example.com`, which matches `example.com` and all of its subdomains.
+    Subdomains(String),
+    Exact(String),
+}
+
+const SUPPORTED_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "data", "file"];
+const WEB_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+
+impl MatchPattern {
+    /// `<all_urls>`, which matches all the URLs with a supported scheme.
+    pub fn all_urls() -> Self {
+        Self {
+            scheme: SchemePattern::All,
+            host: HostPattern::Any,
+            path: "/*".to_owned(),
+        }
+    }
+
+    pub fn matches(&self, url: &ServoUrl) -> bool {
+        let scheme = url.scheme();
+        let scheme_matches = match self.scheme {
+            SchemePattern::All => SUPPORTED_SCHEMES.contains(&scheme),
+            SchemePattern::Web => WEB_SCHEMES.contains(&scheme),
+            SchemePattern::Exact(ref pattern) => scheme == pattern,
+        };
+        if !scheme_matches {
+            return false;
+        }
+        // `<all_urls>` matches whole URLs, including `data:` ones which have no path to speak of.
+        if self.scheme == SchemePattern::All {
+            return true;
+        }
+
+        let host = url.host_str().unwrap_or("");
+        let host_matches = match self.host {
+            HostPattern::Any => true,
+            HostPattern::Subdomains(ref domain) => {
+                host == domain ||
+                    host.strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            },
+            HostPattern::Exact(ref pattern) => host == pattern,
+        };
+        if !host_matches {
+            return false;
+        }
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        glob_matches(&self.path, &path)
+    }
+}
+
+impl FromStr for MatchPattern {
+    type Err = MatchPatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        if pattern == "<all_urls>" {
+            return Ok(Self::all_urls());
+        }
+
+        let (scheme, rest) = pattern
+            .split_once("://")
+            .ok_or(MatchPatternError::MissingSchemeSeparator)?;
+        let scheme = match scheme {
+            "*" => SchemePattern::Web,
+            scheme if SUPPORTED_SCHEMES.contains(&scheme) => {
+                SchemePattern::Exact(scheme.to_owned())
+            },
+            _ => return Err(MatchPatternError::InvalidScheme),
+        };
+
+        let path_start = rest.find('/').ok_or(MatchPatternError::MissingPath)?;
+        let (host, path) = rest.split_at(path_start);
+        let host = host.to_ascii_lowercase();
+        let host = match host.as_str() {
+            "" if scheme == SchemePattern::Exact("file".to_owned()) => HostPattern::Any,
+            "" => return Err(MatchPatternError::InvalidHost),
+            "*" => HostPattern::Any,
+            _ => {
+                let (host, subdomains) = match host.strip_prefix("*.") {
+                    Some(domain) => (domain, true),
+                    None => (host.as_str(), false),
+                };
+                // Wildcards are only allowed as a whole label at the start, and there is no
+                // way to match a port.
+                if host.is_empty() || host.contains(['*', ':']) {
+                    return Err(MatchPatternError::InvalidHost);
+                }
+                if subdomains {
+                    HostPattern::Subdomains(host.to_owned())
+                } else {
+                    HostPattern::Exact(host.to_owned())
+                }
+            },
+        };
+
+        Ok(Self {
+            scheme,
+            host,
+            path: path.to_owned(),
+        })
+    }
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches any sequence of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // There is no `*` in the pattern.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
@@ -140,4 +140,7 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// The number of bytes used by the decoded images held by the cache.
+    fn decoded_images_size(&self) -> usize;
 }
@@ -446,6 +446,14 @@ pub enum CoreResourceMsg {
         CookieSource,
     ),
     DeleteCookies(ServoUrl),
+    /// Retrieve the unexpired cookies that match the given filter
+    GetCookies(SiteDataFilter, IpcSender<Vec<Serde<Cookie<'static>>>>),
+    /// Delete the cookie with the same name, domain and path as the given one
+    DeleteCookie(Serde<Cookie<'static>>),
+    /// Delete the cookies that match the given filter, replying when done
+    ClearCookies(SiteDataFilter, IpcSender<()>),
+    /// Remove the responses that match the given filter from the HTTP cache, replying when done
+    ClearHttpCache(SiteDataFilter, IpcSender<()>),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
     /// Set a history state for a given history state id
@@ -465,6 +473,48 @@ pub enum CoreResourceMsg {
     Exit(IpcSender<()>),
 }
 
+/// Selects the data that sites stored, for embedders to list or clear it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SiteDataFilter {
+    /// The hosts whose data to select, along with the data of their subdomains, or `None` to
+    /// select the data of all hosts.
+    pub hosts: Option<Vec<String>>,
+    /// Only select the data stored since this time. Data without a timestamp, such as web
+    /// storage, is only selected when this is `None`.
+    pub since: Option<SystemTime>,
+}
+
+impl SiteDataFilter {
+    /// Whether data stored by the given host is selected, either because the host is one of
+    /// the filter's hosts or because it is a subdomain of one of them.
+    pub fn matches_host(&self, host: &str) -> bool {
+        let Some(hosts) = &self.hosts else {
+            return true;
+        };
+        let host = host.trim_start_matches('.').to_ascii_lowercase();
+        hosts.iter().any(|filter| {
+            let filter = filter.trim_start_matches('.').to_ascii_lowercase();
+            host.strip_suffix(filter.as_str())
+                .is_some_and(|subdomain| subdomain.is_empty() || subdomain.ends_with('.'))
+        })
+    }
+
+    /// Whether data stored at the given number of seconds since the UNIX epoch is selected.
+    pub fn matches_time(&self, seconds_since_epoch: i64) -> bool {
+        self.since.map_or(true, |since| {
+            since
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs() as i64) <=
+                seconds_since_epoch
+        })
+    }
+
+    /// Whether data without a timestamp is selected.
+    pub fn matches_untimed_data(&self) -> bool {
+        self.since.is_none()
+    }
+}
+
 /// Instruct the resource thread to make a new request.
 pub fn fetch_async<F>(request: RequestBuilder, core_resource_thread: &CoreResourceThread, f: F)
 where
@@ -731,6 +781,66 @@ impl Metadata {
                 .typed_insert::<ReferrerPolicyHeader>(referrer_policy.into());
         }
     }
+
+    fn content_disposition(&self) -> Option<&str> {
+        self.headers
+            .as_ref()?
+            .get(http::header::CONTENT_DISPOSITION)?
+            .to_str()
+            .ok()
+    }
+
+    /// Whether the `Content-Disposition` header asks for the resource to be saved rather than
+    /// displayed. <https://httpwg.org/specs/rfc6266.html#disposition.type>
+    pub fn is_attachment(&self) -> bool {
+        self.content_disposition()
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|disposition| disposition.trim().eq_ignore_ascii_case("attachment"))
+    }
+
+    /// The name to save the resource as: the file name given by the `Content-Disposition`
+    /// header, or else the last segment of the path of the final URL.
+    /// <https://httpwg.org/specs/rfc6266.html#disposition.parameter.filename>
+    pub fn suggested_filename(&self) -> Option<String> {
+        let from_header = self.content_disposition().and_then(|value| {
+            let mut filename = None;
+            for parameter in value.split(';').skip(1) {
+                let Some((name, value)) = parameter.split_once('=') else {
+                    continue;
+                };
+                match name.trim().to_ascii_lowercase().as_str() {
+                    // An extended value takes precedence over a plain `filename`.
+                    // <https://httpwg.org/specs/rfc8187.html#rfc.section.3.2>
+                    "filename*" => {
+                        let mut parts = value.trim().splitn(3, '\'');
+                        if let (Some(charset), Some(_language), Some(encoded)) =
+                            (parts.next(), parts.next(), parts.next())
+                        {
+                            if charset.eq_ignore_ascii_case("utf-8") {
+                                if let Ok(decoded) =
+                                    percent_encoding::percent_decode_str(encoded).decode_utf8()
+                                {
+                                    return Some(decoded.into_owned());
+                                }
+                            }
+                        }
+                    },
+                    "filename" if filename.is_none() => {
+                        filename = Some(value.trim().trim_matches('"').to_owned());
+                    },
+                    _ => {},
+                }
+            }
+            filename
+        });
+        from_header
+            .or_else(|| {
+                let segment = self.final_url.path_segments()?.last()?;
+                let decoded = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+                Some(decoded.into_owned())
+            })
+            .filter(|filename| !filename.is_empty())
+    }
 }
 
 /// The creator of a given cookie
@@ -752,6 +862,8 @@ pub enum NetworkError {
     SslValidation(String, Vec<u8>),
     /// Crash error, to be converted to Resource::Crash in the HTML parser.
     Crash(String),
+    /// The host name of the request could not be resolved.
+    DnsResolution(String),
 }
 
 impl NetworkError {
@@ -4,7 +4,7 @@
 
 use std::sync::{Arc, Mutex};
 
-use base::id::PipelineId;
+use base::id::{PipelineId, TopLevelBrowsingContextId};
 use content_security_policy::{self as csp, CspList};
 use http::header::{HeaderName, AUTHORIZATION};
 use http::{HeaderMap, Method};
@@ -250,6 +250,9 @@ pub struct RequestBuilder {
     pub referrer: Referrer,
     pub referrer_policy: Option<ReferrerPolicy>,
     pub pipeline_id: Option<PipelineId>,
+    /// The webview that the request was made for, which selects the protocol handlers that
+    /// apply to it.
+    pub target_webview_id: Option<TopLevelBrowsingContextId>,
     pub redirect_mode: RedirectMode,
     pub integrity_metadata: String,
     // This is nominally a part of the client's global object.
@@ -265,6 +268,9 @@ pub struct RequestBuilder {
     pub response_tainting: ResponseTainting,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
+    /// Servo internal: whether this is a top-level navigation started by the embedder, like a
+    /// URL typed by the user, rather than by any content.
+    pub initiated_by_embedder: bool,
 }
 
 impl RequestBuilder {
@@ -287,6 +293,7 @@ impl RequestBuilder {
             referrer,
             referrer_policy: None,
             pipeline_id: None,
+            target_webview_id: None,
             redirect_mode: RedirectMode::Follow,
             integrity_metadata: "".to_owned(),
             url_list: vec![],
@@ -296,6 +303,7 @@ impl RequestBuilder {
             https_state: HttpsState::None,
             response_tainting: ResponseTainting::Basic,
             crash: None,
+            initiated_by_embedder: false,
         }
     }
 
@@ -369,6 +377,14 @@ impl RequestBuilder {
         self
     }
 
+    pub fn target_webview_id(
+        mut self,
+        target_webview_id: Option<TopLevelBrowsingContextId>,
+    ) -> RequestBuilder {
+        self.target_webview_id = target_webview_id;
+        self
+    }
+
     pub fn redirect_mode(mut self, redirect_mode: RedirectMode) -> RequestBuilder {
         self.redirect_mode = redirect_mode;
         self
@@ -399,6 +415,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn initiated_by_embedder(mut self, initiated_by_embedder: bool) -> Self {
+        self.initiated_by_embedder = initiated_by_embedder;
+        self
+    }
+
     pub fn build(self) -> Request {
         let mut request = Request::new(
             self.url.clone(),
@@ -421,6 +442,7 @@ impl RequestBuilder {
         request.use_url_credentials = self.use_url_credentials;
         request.cache_mode = self.cache_mode;
         request.referrer_policy = self.referrer_policy;
+        request.target_webview_id = self.target_webview_id;
         request.redirect_mode = self.redirect_mode;
         let mut url_list = self.url_list;
         if url_list.is_empty() {
@@ -433,6 +455,7 @@ impl RequestBuilder {
         request.csp_list = self.csp_list;
         request.response_tainting = self.response_tainting;
         request.crash = self.crash;
+        request.initiated_by_embedder = self.initiated_by_embedder;
         request
     }
 }
@@ -474,6 +497,8 @@ pub struct Request {
     /// <https://fetch.spec.whatwg.org/#concept-request-referrer-policy>
     pub referrer_policy: Option<ReferrerPolicy>,
     pub pipeline_id: Option<PipelineId>,
+    /// The webview that the request was made for, if known.
+    pub target_webview_id: Option<TopLevelBrowsingContextId>,
     /// <https://fetch.spec.whatwg.org/#synchronous-flag>
     pub synchronous: bool,
     /// <https://fetch.spec.whatwg.org/#concept-request-mode>
@@ -508,6 +533,9 @@ pub struct Request {
     pub https_state: HttpsState,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
+    /// Servo internal: whether this is a top-level navigation started by the embedder, like a
+    /// URL typed by the user, rather than by any content.
+    pub initiated_by_embedder: bool,
 }
 
 impl Request {
@@ -534,6 +562,7 @@ impl Request {
             referrer,
             referrer_policy: None,
             pipeline_id,
+            target_webview_id: None,
             synchronous: false,
             mode: RequestMode::NoCors,
             use_cors_preflight: false,
@@ -549,6 +578,7 @@ impl Request {
             csp_list: None,
             https_state,
             crash: None,
+            initiated_by_embedder: false,
         }
     }
 
@@ -7,6 +7,8 @@ use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
 use servo_url::ServoUrl;
 
+use crate::SiteDataFilter;
+
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum StorageType {
     Session,
@@ -43,6 +45,13 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// gets the number of bytes used by the origin of the url across local and session
+    /// storage, along with the quota that applies to them
+    Estimate(IpcSender<(usize, usize)>, ServoUrl),
+
+    /// clears the local and session storage of the origins whose host matches the filter
+    ClearSiteData(SiteDataFilter, IpcSender<()>),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
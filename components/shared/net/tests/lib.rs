@@ -2,7 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use net_traits::{ResourceAttribute, ResourceFetchTiming, ResourceTimeValue, ResourceTimingType};
+use http::header::{HeaderMap, HeaderValue, CONTENT_DISPOSITION};
+use hyper_serde::Serde;
+use net_traits::{
+    Metadata, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue, ResourceTimingType,
+    SiteDataFilter,
+};
+use servo_url::ServoUrl;
 
 #[test]
 fn test_set_start_time_to_fetch_start_if_nonzero_tao() {
@@ -210,3 +216,81 @@ fn test_reset_start_time() {
         "failed to reset `start_time`"
     );
 }
+
+#[test]
+fn test_metadata_content_disposition() {
+    fn metadata(url: &str, content_disposition: Option<&'static str>) -> Metadata {
+        let mut metadata = Metadata::default(ServoUrl::parse(url).unwrap());
+        if let Some(value) = content_disposition {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_DISPOSITION, HeaderValue::from_static(value));
+            metadata.headers = Some(Serde(headers));
+        }
+        metadata
+    }
+
+    let inline = metadata("https://example.com/files/report%20v2.pdf", None);
+    assert!(!inline.is_attachment());
+    assert_eq!(
+        inline.suggested_filename().as_deref(),
+        Some("report v2.pdf")
+    );
+
+    let attachment = metadata(
+        "https://example.com/download?id=1",
+        Some("Attachment; filename=\"notes.txt\""),
+    );
+    assert!(attachment.is_attachment());
+    assert_eq!(
+        attachment.suggested_filename().as_deref(),
+        Some("notes.txt")
+    );
+
+    let extended = metadata(
+        "https://example.com/download",
+        Some("attachment; filename=\"fallback.txt\"; filename*=UTF-8''%E2%82%AC%20rates.txt"),
+    );
+    assert_eq!(
+        extended.suggested_filename().as_deref(),
+        Some("€ rates.txt")
+    );
+
+    let directory = metadata("https://example.com/", Some("inline"));
+    assert!(!directory.is_attachment());
+    assert_eq!(directory.suggested_filename(), None);
+}
+
+fn site_data_filter(hosts: &[&str]) -> SiteDataFilter {
+    SiteDataFilter {
+        hosts: Some(hosts.iter().map(|host| host.to_string()).collect()),
+        since: None,
+    }
+}
+
+#[test]
+fn test_site_data_filter_matches_hosts_and_subdomains() {
+    let filter = site_data_filter(&["example.com"]);
+    assert!(filter.matches_host("example.com"));
+    assert!(filter.matches_host(".example.com"));
+    assert!(filter.matches_host("www.example.com"));
+    assert!(filter.matches_host("WWW.Example.COM"));
+    assert!(!filter.matches_host("notexample.com"));
+    assert!(!filter.matches_host("example.org"));
+    assert!(!filter.matches_host("com"));
+    assert!(!filter.matches_host(""));
+
+    assert!(SiteDataFilter::default().matches_host("example.com"));
+}
+
+#[test]
+fn test_site_data_filter_does_not_panic_on_non_ascii_hosts() {
+    let filter = site_data_filter(&["example.com"]);
+    assert!(!filter.matches_host("ébample.com"));
+    assert!(!filter.matches_host("日本.com"));
+
+    let filter = site_data_filter(&["bücher.example"]);
+    assert!(filter.matches_host("bücher.example"));
+    assert!(filter.matches_host("www.bücher.example"));
+    assert!(!filter.matches_host("xbücher.example"));
+    assert!(!filter.matches_host("ü.example"));
+}
@@ -21,8 +21,8 @@ use std::sync::Arc;
 
 use background_hang_monitor_api::BackgroundHangMonitorRegister;
 use base::id::{
-    BlobId, BrowsingContextId, HistoryStateId, MessagePortId, PipelineId, PipelineNamespaceId,
-    TopLevelBrowsingContextId,
+    BlobId, BrowsingContextId, HistoryStateId, MessagePortId, NotificationId, PipelineId,
+    PipelineNamespaceId, TopLevelBrowsingContextId,
 };
 use base::Epoch;
 use bitflags::bitflags;
@@ -30,7 +30,11 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::CompositorEventVariant;
+use embedder_traits::user_script::UserScript;
+use embedder_traits::{
+    CompositorEventVariant, GeolocationUpdate, ImeEvent, JavaScriptEvaluationId, MediaPolicy,
+    NotificationEvent, SavedDocumentState,
+};
 use euclid::default::Point2D;
 use euclid::{Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use http::{HeaderMap, Method};
@@ -157,6 +161,8 @@ pub struct LoadData {
     pub srcdoc: String,
     /// The inherited context is Secure, None if not inherited
     pub inherited_secure_context: Option<bool>,
+    /// The container policy of the iframe this load happens in, if any.
+    pub container_policy: Option<ContainerPolicy>,
 
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
@@ -172,6 +178,45 @@ pub enum JsEvalResult {
     Ok(Vec<u8>),
 }
 
+/// The origins of the documents allowed to use a policy-controlled feature.
+/// <https://w3c.github.io/webappsec-permissions-policy/#allowlists>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum FeatureAllowlist {
+    /// Documents of any origin.
+    All,
+    /// Documents of one of the given origins.
+    Origins(Vec<ImmutableOrigin>),
+}
+
+impl FeatureAllowlist {
+    /// Whether a document of the given origin is allowed to use the feature.
+    pub fn allows(&self, origin: &ImmutableOrigin) -> bool {
+        match self {
+            FeatureAllowlist::All => true,
+            FeatureAllowlist::Origins(origins) => origins.contains(origin),
+        }
+    }
+}
+
+/// The allowlists an iframe declares for the policy-controlled features of the documents
+/// loaded in it, computed by the document of the iframe so that it can be checked in the
+/// script thread of a cross-origin nested document.
+/// <https://w3c.github.io/webappsec-permissions-policy/#container-policy>
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, Serialize)]
+pub struct ContainerPolicy {
+    /// The allowlist of each feature; features without one are not allowed.
+    pub allowlists: HashMap<String, FeatureAllowlist>,
+}
+
+impl ContainerPolicy {
+    /// Whether a document of the given origin is allowed to use the feature with the given name.
+    pub fn allows(&self, feature: &str, origin: &ImmutableOrigin) -> bool {
+        self.allowlists
+            .get(feature)
+            .is_some_and(|allowlist| allowlist.allows(origin))
+    }
+}
+
 impl LoadData {
     /// Create a new `LoadData` object.
     pub fn new(
@@ -194,6 +239,7 @@ impl LoadData {
             referrer_policy,
             srcdoc: "".to_string(),
             inherited_secure_context,
+            container_policy: None,
             crash: None,
         }
     }
@@ -217,6 +263,8 @@ pub struct NewLayoutInfo {
     pub load_data: LoadData,
     /// Information about the initial window size.
     pub window_size: WindowSizeData,
+    /// The user scripts injected into the documents of the pipeline that they match.
+    pub user_scripts: Vec<UserScript>,
 }
 
 /// When a pipeline is closed, should its browsing context be discarded too?
@@ -301,6 +349,23 @@ pub enum ConstellationControlMsg {
     SetThrottled(PipelineId, bool),
     /// Notify the containing iframe (in PipelineId) that the nested browsing context (BrowsingContextId) is throttled.
     SetThrottledInContainingIframe(PipelineId, BrowsingContextId, bool),
+    /// Set how the media of the documents of a webview is played.
+    SetMediaPolicy(TopLevelBrowsingContextId, MediaPolicy),
+    /// Run a script in the given pipeline on behalf of the embedder, and report its result.
+    EvaluateJavaScript(PipelineId, JavaScriptEvaluationId, String),
+    /// Send the scroll position and the values of the form controls of the given document, or
+    /// `None` if it does not exist anymore.
+    GetDocumentState(
+        PipelineId,
+        IpcSender<(PipelineId, Option<SavedDocumentState>)>,
+    ),
+    /// Restore the scroll position and the values of the form controls of the given document.
+    RestoreDocumentState(PipelineId, SavedDocumentState),
+    /// Notifies the Geolocation API of the given pipeline that the position of the device
+    /// changed.
+    GeolocationUpdate(PipelineId, GeolocationUpdate),
+    /// Notifies the given pipeline that something happened to one of its notifications.
+    NotificationEvent(PipelineId, NotificationId, NotificationEvent),
     /// Notifies script thread that a url should be loaded in this iframe.
     /// PipelineId is for the parent, BrowsingContextId is for the nested browsing context
     NavigateIframe(
@@ -371,6 +436,8 @@ pub enum ConstellationControlMsg {
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
     Reload(PipelineId),
+    /// Stop loading the given page.
+    Stop(PipelineId),
     /// Notifies the script thread about a new recorded paint metric.
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
     /// Notifies the media session about a user requested media session action.
@@ -402,6 +469,12 @@ impl fmt::Debug for ConstellationControlMsg {
             SetDocumentActivity(..) => "SetDocumentActivity",
             SetThrottled(..) => "SetThrottled",
             SetThrottledInContainingIframe(..) => "SetThrottledInContainingIframe",
+            SetMediaPolicy(..) => "SetMediaPolicy",
+            EvaluateJavaScript(..) => "EvaluateJavaScript",
+            GetDocumentState(..) => "GetDocumentState",
+            RestoreDocumentState(..) => "RestoreDocumentState",
+            GeolocationUpdate(..) => "GeolocationUpdate",
+            NotificationEvent(..) => "NotificationEvent",
             NavigateIframe(..) => "NavigateIframe",
             PostMessage { .. } => "PostMessage",
             UpdatePipelineId(..) => "UpdatePipelineId",
@@ -415,6 +488,7 @@ impl fmt::Debug for ConstellationControlMsg {
             DispatchStorageEvent(..) => "DispatchStorageEvent",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
+            Stop(..) => "Stop",
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
@@ -551,6 +625,8 @@ pub enum CompositorEvent {
     CompositionEvent(CompositionEvent),
     /// Virtual keyboard was dismissed
     IMEDismissedEvent,
+    /// The input method of the embedder edited the focused element.
+    ImeEvent(ImeEvent),
     /// Connected gamepad state updated
     GamepadEvent(GamepadEvent),
 }
@@ -566,6 +642,7 @@ impl From<&CompositorEvent> for CompositorEventVariant {
             CompositorEvent::KeyboardEvent(..) => CompositorEventVariant::KeyboardEvent,
             CompositorEvent::CompositionEvent(..) => CompositorEventVariant::CompositionEvent,
             CompositorEvent::IMEDismissedEvent => CompositorEventVariant::IMEDismissedEvent,
+            CompositorEvent::ImeEvent(..) => CompositorEventVariant::ImeEvent,
             CompositorEvent::GamepadEvent(..) => CompositorEventVariant::GamepadEvent,
         }
     }
@@ -678,6 +755,8 @@ pub struct InitialScriptState {
     pub webrender_api_sender: WebRenderScriptApi,
     /// Application window's GL Context for Media player
     pub player_context: WindowGLContext,
+    /// The user scripts injected into the documents of the pipeline that they match.
+    pub user_scripts: Vec<UserScript>,
 }
 
 /// This trait allows creating a `ServiceWorkerManager` without depending on the `script`
@@ -7,13 +7,13 @@ use std::fmt;
 
 use base::id::{
     BroadcastChannelRouterId, BrowsingContextId, HistoryStateId, MessagePortId,
-    MessagePortRouterId, PipelineId, ServiceWorkerId, ServiceWorkerRegistrationId,
+    MessagePortRouterId, NotificationId, PipelineId, ServiceWorkerId, ServiceWorkerRegistrationId,
     TopLevelBrowsingContextId,
 };
 use base::Epoch;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use embedder_traits::{DownloadRequest, EmbedderMsg, MediaSessionEvent, Notification};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
@@ -144,6 +144,9 @@ pub enum ScriptMsg {
     /// Requests are sent to constellation and fetches are checked manually
     /// for cross-origin loads
     InitiateNavigateRequest(RequestBuilder, /* cancellation_chan */ IpcReceiver<()>),
+    /// The response to a navigation is a download, to be reported to the embedder. The request
+    /// is the one to fetch the resource again with, if the embedder downloads it.
+    DownloadRequested(DownloadRequest, RequestBuilder),
     /// Broadcast a storage event to every same-origin pipeline.
     /// The strings are key, old value and new value.
     BroadcastStorageEvent(
@@ -188,6 +191,9 @@ pub enum ScriptMsg {
     LoadUrl(LoadData, HistoryEntryReplacement),
     /// Abort loading after sending a LoadUrl message.
     AbortLoadUrl,
+    /// Cancel any navigation of this pipeline's browsing context whose new document has not
+    /// been activated yet.
+    CancelOngoingNavigation,
     /// Post a message to the currently active window of a given browsing context.
     PostMessage {
         /// The target of the posted message.
@@ -241,6 +247,9 @@ pub enum ScriptMsg {
     DiscardTopLevelBrowsingContext,
     /// Notifies the constellation that this pipeline has exited.
     PipelineExited,
+    /// The event loop of this pipeline went over its memory limit, with a description of its
+    /// memory use. Its top-level browsing context should be treated as having crashed.
+    MemoryLimitExceeded(String),
     /// Send messages from postMessage calls from serviceworker
     /// to constellation for storing in service worker manager
     ForwardDOMMessage(DOMMessage, ServoUrl),
@@ -265,6 +274,19 @@ pub enum ScriptMsg {
     GetWebGPUChan(IpcSender<Option<WebGPU>>),
     /// Notify the constellation of a pipeline's document's title.
     TitleChanged(PipelineId, String),
+    /// Notify the constellation that a pipeline started or stopped playing audio that the user
+    /// can hear.
+    AudibleChanged(bool),
+    /// Notify the constellation that a pipeline waits for the position of the device. The flag
+    /// is whether it asked for a high accuracy position.
+    StartGeolocationUpdates(bool),
+    /// Notify the constellation that a pipeline does not wait for the position of the device
+    /// anymore.
+    StopGeolocationUpdates,
+    /// Ask the embedder to show a notification created by a pipeline.
+    ShowNotification(Notification),
+    /// Ask the embedder to close a notification of a pipeline.
+    CloseNotification(NotificationId),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -287,6 +309,7 @@ impl fmt::Debug for ScriptMsg {
             ScheduleBroadcast(..) => "ScheduleBroadcast",
             ForwardToEmbedder(..) => "ForwardToEmbedder",
             InitiateNavigateRequest(..) => "InitiateNavigateRequest",
+            DownloadRequested(..) => "DownloadRequested",
             BroadcastStorageEvent(..) => "BroadcastStorageEvent",
             ChangeRunningAnimationsState(..) => "ChangeRunningAnimationsState",
             CreateCanvasPaintThread(..) => "CreateCanvasPaintThread",
@@ -297,6 +320,7 @@ impl fmt::Debug for ScriptMsg {
             LoadComplete => "LoadComplete",
             LoadUrl(..) => "LoadUrl",
             AbortLoadUrl => "AbortLoadUrl",
+            CancelOngoingNavigation => "CancelOngoingNavigation",
             PostMessage { .. } => "PostMessage",
             NavigatedToFragment(..) => "NavigatedToFragment",
             TraverseHistory(..) => "TraverseHistory",
@@ -317,6 +341,7 @@ impl fmt::Debug for ScriptMsg {
             DiscardDocument => "DiscardDocument",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
             PipelineExited => "PipelineExited",
+            MemoryLimitExceeded(..) => "MemoryLimitExceeded",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
             ScheduleJob(..) => "ScheduleJob",
             GetClientWindow(..) => "GetClientWindow",
@@ -326,6 +351,11 @@ impl fmt::Debug for ScriptMsg {
             RequestAdapter(..) => "RequestAdapter",
             GetWebGPUChan(..) => "GetWebGPUChan",
             TitleChanged(..) => "TitleChanged",
+            AudibleChanged(..) => "AudibleChanged",
+            StartGeolocationUpdates(..) => "StartGeolocationUpdates",
+            StopGeolocationUpdates => "StopGeolocationUpdates",
+            ShowNotification(..) => "ShowNotification",
+            CloseNotification(..) => "CloseNotification",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
@@ -14,6 +14,7 @@ use std::any::Any;
 use std::borrow::Cow;
 use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use app_units::Au;
 use atomic_refcell::AtomicRefCell;
@@ -44,7 +45,7 @@ use style::context::QuirksMode;
 use style::data::ElementData;
 use style::dom::OpaqueNode;
 use style::invalidation::element::restyle_hints::RestyleHint;
-use style::media_queries::Device;
+use style::media_queries::{Device, MediaType};
 use style::properties::style_structs::Font;
 use style::properties::PropertyId;
 use style::selector_parser::{PseudoElement, RestyleDamage, Snapshot};
@@ -211,6 +212,10 @@ pub trait Layout {
     /// Sets quirks mode for the document, causing the quirks mode stylesheet to be used.
     fn set_quirks_mode(&mut self, quirks_mode: QuirksMode);
 
+    /// Sets the media type used to evaluate media queries. The new media type is applied
+    /// at the next reflow.
+    fn set_media_type(&mut self, media_type: MediaType);
+
     /// Removes a stylesheet from the Layout.
     fn remove_stylesheet(&mut self, stylesheet: ServoArc<Stylesheet>);
 
@@ -374,6 +379,98 @@ pub struct Reflow {
 pub struct ReflowComplete {
     /// The list of images that were encountered that are in progress.
     pub pending_images: Vec<PendingImage>,
+    /// Counters describing how much of the tree this reflow had to restyle and lay out.
+    pub restyle_statistics: RestyleStatistics,
+    /// The style invalidations found by this reflow, if [`ScriptReflow::trace_invalidations`]
+    /// was set.
+    pub invalidation_trace: Option<InvalidationTrace>,
+    /// How long each phase of this reflow took.
+    pub phase_durations: ReflowPhaseDurations,
+    /// The box tree, fragment tree and damaged elements after this reflow, serialized as JSON,
+    /// if [`ScriptReflow::dump_layout`] was set.
+    pub layout_dump: Option<String>,
+}
+
+/// How long each phase of a reflow took. Phases that were skipped take no time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReflowPhaseDurations {
+    /// Restyling the dirty part of the DOM.
+    pub style: Duration,
+    /// Building or updating the box tree.
+    pub box_tree: Duration,
+    /// Laying out the box tree into a fragment tree, or updating the styles of the existing
+    /// fragment tree.
+    pub layout: Duration,
+    /// Building the display list and sending it to WebRender.
+    pub display_list: Duration,
+}
+
+/// Counters describing the work done by the style traversal of a reflow.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RestyleStatistics {
+    /// The number of elements visited by the style traversal.
+    pub elements_traversed: usize,
+    /// The number of traversed elements whose style changed in a way that has to be reflected
+    /// in the box tree or in the display list.
+    pub elements_damaged: usize,
+    /// The number of damaged elements whose boxes had to be rebuilt, as opposed to being laid
+    /// out again or repainted.
+    pub elements_rebuilt: usize,
+    /// Whether the box tree and fragment tree were left untouched because the restyle only
+    /// involved selector-only attribute or state changes that did not change any style, or
+    /// only changed how boxes are painted.
+    pub box_tree_update_skipped: bool,
+    /// Whether the restyle only changed properties that affect painting, so that the new styles
+    /// were applied to the existing fragments instead of laying them out again.
+    pub layout_skipped_for_repaint: bool,
+}
+
+/// An element whose style changed during the style traversal of a reflow.
+#[derive(Debug)]
+pub struct DamagedNode {
+    /// The address of the element.
+    pub node: UntrustedNodeAddress,
+    /// The names of the kinds of layout work the change calls for, e.g. `REPAINT_ONLY` or
+    /// `RELAYOUT`.
+    pub damage: Vec<&'static str>,
+    /// The depth of the element in the DOM, which tells how far below the dirty root the
+    /// invalidation propagated.
+    pub depth: usize,
+}
+
+/// How a reflow brought the box tree up to date with the styles of the document.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoxTreeUpdate {
+    /// The box tree was left untouched.
+    #[default]
+    Skipped,
+    /// Only the boxes below the dirty root were rebuilt.
+    Incremental,
+    /// The whole box tree was rebuilt from the root element.
+    Rebuilt,
+}
+
+impl BoxTreeUpdate {
+    /// The name of this update in the traces and results of layout for tests and debugging.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoxTreeUpdate::Skipped => "skipped",
+            BoxTreeUpdate::Incremental => "incremental",
+            BoxTreeUpdate::Rebuilt => "rebuilt",
+        }
+    }
+}
+
+/// The style invalidations found by a reflow and the box tree work they led to.
+#[derive(Debug, Default)]
+pub struct InvalidationTrace {
+    /// The element from which the style traversal started.
+    pub dirty_root: Option<UntrustedNodeAddress>,
+    /// The elements whose style changed, from the shallowest to the deepest. Elements at the same
+    /// depth are ordered by address.
+    pub damaged_nodes: Vec<DamagedNode>,
+    /// How the box tree was updated.
+    pub box_tree_update: BoxTreeUpdate,
 }
 
 /// Information needed for a script-initiated reflow.
@@ -402,6 +499,10 @@ pub struct ScriptReflow {
     pub animation_timeline_value: f64,
     /// The set of animations for this document.
     pub animations: DocumentAnimationSet,
+    /// Whether to record the style invalidations found by this reflow.
+    pub trace_invalidations: bool,
+    /// Whether to serialize the box tree, fragment tree and damaged elements after this reflow.
+    pub dump_layout: bool,
 }
 
 /// A pending restyle.
@@ -19,12 +19,12 @@ use ipc_channel::ipc::{self, IpcBytesReceiver, IpcSender};
 use libc::c_void;
 use log::warn;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use webrender_api::units::{DevicePoint, LayoutPoint, TexelRect};
+use webrender_api::units::{DevicePoint, LayoutPoint, LayoutTransform, TexelRect};
 use webrender_api::{
     BuiltDisplayList, BuiltDisplayListDescriptor, ExternalImage, ExternalImageData,
     ExternalImageHandler, ExternalImageId, ExternalImageSource, ExternalScrollId,
     FontInstanceFlags, FontInstanceKey, FontKey, HitTestFlags, ImageData, ImageDescriptor,
-    ImageKey, NativeFontHandle, PipelineId as WebRenderPipelineId,
+    ImageKey, NativeFontHandle, PipelineId as WebRenderPipelineId, PropertyValue,
 };
 
 pub use crate::rendering_context::RenderingContext;
@@ -245,6 +245,16 @@ pub enum ScriptToCompositorMsg {
         /// An [ipc::IpcBytesReceiver] used to send the raw data of the display list.
         display_list_receiver: ipc::IpcBytesReceiver,
     },
+    /// Update the transforms and opacities that the most recent display list of the given
+    /// pipeline bound to WebRender properties, without sending a new display list.
+    UpdateAnimatedProperties {
+        /// The pipeline whose display list binds these properties.
+        pipeline_id: WebRenderPipelineId,
+        /// The new values of the bound transforms.
+        transforms: Vec<PropertyValue<LayoutTransform>>,
+        /// The new values of the bound opacities.
+        opacities: Vec<PropertyValue<f32>>,
+    },
     /// Perform a hit test operation. The result will be returned via
     /// the provided channel sender.
     HitTest(
@@ -352,6 +362,26 @@ impl WebRenderScriptApi {
         }
     }
 
+    /// Update the transforms and opacities bound to WebRender properties by the most recent
+    /// display list of the given pipeline.
+    pub fn send_animated_properties(
+        &self,
+        pipeline_id: WebRenderPipelineId,
+        transforms: Vec<PropertyValue<LayoutTransform>>,
+        opacities: Vec<PropertyValue<f32>>,
+    ) {
+        if let Err(error) = self
+            .0
+            .send(ScriptToCompositorMsg::UpdateAnimatedProperties {
+                pipeline_id,
+                transforms,
+                opacities,
+            })
+        {
+            warn!("Error sending animated properties: {}", error);
+        }
+    }
+
     /// Perform a hit test operation. Blocks until the operation is complete and
     /// and a result is available.
     pub fn hit_test(
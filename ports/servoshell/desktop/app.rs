@@ -7,6 +7,7 @@
 use std::cell::{Cell, RefCell, RefMut};
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Instant;
 use std::{env, fs};
 
@@ -15,8 +16,8 @@ use log::{info, trace, warn};
 use servo::compositing::windowing::EmbedderEvent;
 use servo::compositing::CompositeTarget;
 use servo::config::{opts, set_pref};
-use servo::servo_config::pref;
-use servo::Servo;
+use servo::servo_config::{basedir, pref};
+use servo::{DirectoryDownloadDelegate, Servo};
 use surfman::GLApi;
 use webxr::glwindow::GlWindowDiscovery;
 use winit::event::WindowEvent;
@@ -179,6 +180,11 @@ impl App {
                     composite_target,
                 );
                 let mut servo = servo_data.servo;
+                if let Some(download_dir) = basedir::default_download_dir() {
+                    servo.set_download_delegate(Some(Arc::new(DirectoryDownloadDelegate::new(
+                        download_dir,
+                    ))));
+                }
 
                 servo.handle_events(vec![EmbedderEvent::NewWebView(
                     initial_url.to_owned(),
@@ -9,12 +9,12 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use euclid::{Angle, Length, Point2D, Rotation3D, Scale, Size2D, UnknownUnit, Vector2D, Vector3D};
-use log::{debug, info, trace};
+use log::{debug, trace};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use servo::compositing::windowing::{
     AnimationState, EmbedderCoordinates, EmbedderEvent, MouseWindowEvent, WindowMethods,
 };
-use servo::embedder_traits::Cursor;
+use servo::embedder_traits::{Cursor, ImeEvent};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::script_traits::{TouchEventType, WheelDelta, WheelMode};
 use servo::servo_config::{opts, pref};
@@ -25,8 +25,8 @@ use servo::webrender_api::ScrollLocation;
 use servo::webrender_traits::RenderingContext;
 use surfman::{Connection, Context, Device, SurfaceType};
 use winit::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
-use winit::event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase};
-use winit::keyboard::{Key as LogicalKey, ModifiersState, NamedKey};
+use winit::event::{ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{Key as LogicalKey, ModifiersState, NamedKey, PhysicalKey};
 #[cfg(any(target_os = "linux", target_os = "windows"))]
 use winit::window::Icon;
 
@@ -46,15 +46,17 @@ pub struct Window {
     primary_monitor: winit::monitor::MonitorHandle,
     event_queue: RefCell<Vec<EmbedderEvent>>,
     mouse_pos: Cell<Point2D<i32, DevicePixel>>,
-    last_pressed: Cell<Option<(KeyboardEvent, Option<LogicalKey>)>>,
-    /// A map of winit's key codes to key values that are interpreted from
-    /// winit's ReceivedChar events.
-    keys_down: RefCell<HashMap<LogicalKey, Key>>,
+    /// The key values reported for the physical keys that are currently pressed, so that
+    /// the matching keyup reports the same value even if the modifiers or the text produced
+    /// by the keyboard layout have changed in the meantime.
+    keys_down: RefCell<HashMap<PhysicalKey, Key>>,
     animation_state: Cell<AnimationState>,
     fullscreen: Cell<bool>,
     device_pixel_ratio_override: Option<f32>,
     xr_window_poses: RefCell<Vec<Rc<XRWindowPose>>>,
     modifiers_state: Cell<ModifiersState>,
+    /// Whether the input method has shown composing text since it last committed.
+    ime_preedit: Cell<bool>,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -142,7 +144,6 @@ impl Window {
             mouse_down_button: Cell::new(None),
             mouse_down_point: Cell::new(Point2D::new(0, 0)),
             mouse_pos: Cell::new(Point2D::new(0, 0)),
-            last_pressed: Cell::new(None),
             keys_down: RefCell::new(HashMap::new()),
             animation_state: Cell::new(AnimationState::Idle),
             fullscreen: Cell::new(false),
@@ -152,88 +153,38 @@ impl Window {
             device_pixel_ratio_override,
             xr_window_poses: RefCell::new(vec![]),
             modifiers_state: Cell::new(ModifiersState::empty()),
+            ime_preedit: Cell::new(false),
             toolbar_height: Cell::new(Default::default()),
         }
     }
 
-    fn handle_received_character(&self, mut ch: char) {
-        info!("winit received character: {:?}", ch);
-        if ch.is_control() {
-            if ch as u8 >= 32 {
-                return;
-            }
-            // shift ASCII control characters to lowercase
-            ch = (ch as u8 + 96) as char;
-        }
-        let (mut event, key_code) = if let Some((event, key_code)) = self.last_pressed.replace(None)
-        {
-            (event, key_code)
-        } else if ch.is_ascii() {
-            // Some keys like Backspace emit a control character in winit
-            // but they are already dealt with in handle_keyboard_input
-            // so just ignore the character.
-            return;
-        } else {
-            // For combined characters like the letter e with an acute accent
-            // no keyboard event is emitted. A dummy event is created in this case.
-            (KeyboardEvent::default(), None)
-        };
-        event.key = Key::Character(ch.to_string());
+    fn handle_keyboard_input(&self, input: KeyEvent) {
+        let mut event = keyboard_event_from_winit(&input, self.modifiers_state.get());
+        trace!("handling {:?}", event);
 
-        if event.state == KeyState::Down {
-            // Ensure that when we receive a keyup event from winit, we are able
-            // to infer that it's related to this character and set the event
-            // properties appropriately.
-            if let Some(key_code) = key_code {
+        match event.state {
+            KeyState::Down => {
                 self.keys_down
                     .borrow_mut()
-                    .insert(key_code, event.key.clone());
-            }
+                    .insert(input.physical_key, event.key.clone());
+            },
+            KeyState::Up => {
+                if let Some(key) = self.keys_down.borrow_mut().remove(&input.physical_key) {
+                    event.key = key;
+                }
+            },
         }
 
         let xr_poses = self.xr_window_poses.borrow();
         for xr_window_pose in &*xr_poses {
             xr_window_pose.handle_xr_translation(&event);
+            xr_window_pose.handle_xr_rotation(&input, self.modifiers_state.get());
         }
         self.event_queue
             .borrow_mut()
             .push(EmbedderEvent::Keyboard(event));
     }
 
-    fn handle_keyboard_input(&self, input: KeyEvent) {
-        if let Some(input_text) = &input.text {
-            for ch in input_text.chars() {
-                self.handle_received_character(ch);
-            }
-        }
-
-        let mut event = keyboard_event_from_winit(&input, self.modifiers_state.get());
-        trace!("handling {:?}", event);
-        if event.state == KeyState::Down && event.key == Key::Unidentified {
-            // If pressed and probably printable, we expect a ReceivedCharacter event.
-            // Wait for that to be received and don't queue any event right now.
-            self.last_pressed
-                .set(Some((event, Some(input.logical_key))));
-            return;
-        } else if event.state == KeyState::Up && event.key == Key::Unidentified {
-            // If release and probably printable, this is following a ReceiverCharacter event.
-            if let Some(key) = self.keys_down.borrow_mut().remove(&input.logical_key) {
-                event.key = key;
-            }
-        }
-
-        if event.key != Key::Unidentified {
-            self.last_pressed.set(None);
-            let xr_poses = self.xr_window_poses.borrow();
-            for xr_window_pose in &*xr_poses {
-                xr_window_pose.handle_xr_rotation(&input, self.modifiers_state.get());
-            }
-            self.event_queue
-                .borrow_mut()
-                .push(EmbedderEvent::Keyboard(event));
-        }
-    }
-
     /// Helper function to handle a click
     fn handle_mouse(
         &self,
@@ -395,6 +346,17 @@ impl WindowPortsMethods for Window {
         self.winit_window.set_cursor_visible(true);
     }
 
+    fn set_ime_allowed(&self, allowed: bool) {
+        self.winit_window.set_ime_allowed(allowed);
+    }
+
+    fn set_ime_cursor_area(&self, rect: DeviceIntRect) {
+        self.winit_window.set_ime_cursor_area(
+            PhysicalPosition::new(rect.min.x, rect.min.y),
+            PhysicalSize::new(rect.width(), rect.height()),
+        );
+    }
+
     fn is_animating(&self) -> bool {
         self.animation_state.get() == AnimationState::Animating
     }
@@ -408,6 +370,32 @@ impl WindowPortsMethods for Window {
             winit::event::WindowEvent::KeyboardInput { event, .. } => {
                 self.handle_keyboard_input(event)
             },
+            winit::event::WindowEvent::Ime(ime) => {
+                let event = match ime {
+                    Ime::Preedit(text, _) => {
+                        // winit clears the preedit text right before committing it.
+                        if !text.is_empty() {
+                            self.ime_preedit.set(true);
+                        }
+                        ImeEvent::SetComposition(text)
+                    },
+                    // Text committed without composing it first is a plain key press, which
+                    // arrives as a keyboard event as well.
+                    Ime::Commit(_) if !self.ime_preedit.get() => return,
+                    Ime::Commit(text) => {
+                        self.ime_preedit.set(false);
+                        ImeEvent::Commit(text)
+                    },
+                    Ime::Disabled => {
+                        self.ime_preedit.set(false);
+                        ImeEvent::Cancel
+                    },
+                    Ime::Enabled => return,
+                };
+                self.event_queue
+                    .borrow_mut()
+                    .push(EmbedderEvent::Ime(event));
+            },
             winit::event::WindowEvent::ModifiersChanged(modifiers) => {
                 self.modifiers_state.set(modifiers.state())
             },
@@ -21,6 +21,14 @@ pub const CMD_OR_ALT: Modifiers = Modifiers::META;
 #[cfg(not(target_os = "macos"))]
 pub const CMD_OR_ALT: Modifiers = Modifiers::ALT;
 
+/// The text produced by the active keyboard layout for a key press, if it is printable.
+fn printable_text(key_event: &KeyEvent) -> Option<&str> {
+    key_event
+        .text
+        .as_deref()
+        .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
+}
+
 trait FromWinitKeyEvent {
     fn from_winit_key_event(key_event: &KeyEvent) -> Self;
 }
@@ -29,9 +37,21 @@ impl FromWinitKeyEvent for Key {
     fn from_winit_key_event(key_event: &KeyEvent) -> Self {
         let named_key = match key_event.logical_key {
             WinitKey::Named(named_key) => named_key,
-            WinitKey::Character(ref string) => return Key::Character(string.to_string()),
-            WinitKey::Unidentified(_) => return Key::Unidentified,
-            WinitKey::Dead(_) => return Key::Unidentified,
+            // The text takes dead keys and compose sequences into account, so after typing
+            // "´" and then "e" on a layout with dead keys it is "é" while the logical key is
+            // still "e". Control characters (for instance when Ctrl is held) are not text.
+            WinitKey::Character(ref string) => {
+                return Key::Character(
+                    printable_text(key_event)
+                        .unwrap_or(string.as_str())
+                        .to_string(),
+                )
+            },
+            WinitKey::Unidentified(_) => {
+                return printable_text(key_event)
+                    .map_or(Key::Unidentified, |text| Key::Character(text.to_string()))
+            },
+            WinitKey::Dead(_) => return Key::Dead,
         };
 
         match named_key {
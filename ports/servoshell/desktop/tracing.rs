@@ -166,16 +166,29 @@ mod from_servo {
                 Self::LoadComplete => target!("LoadComplete"),
                 Self::Panic(..) => target!("Panic"),
                 Self::GetSelectedBluetoothDevice(..) => target!("GetSelectedBluetoothDevice"),
+                Self::GetAudioOutputDevices(..) => target!("GetAudioOutputDevices"),
                 Self::SelectFiles(..) => target!("SelectFiles"),
                 Self::PromptPermission(..) => target!("PromptPermission"),
                 Self::ShowIME(..) => target!("ShowIME"),
                 Self::HideIME => target!("HideIME"),
+                Self::UpdateIME(..) => target!("UpdateIME"),
                 Self::Shutdown => target!("Shutdown"),
                 Self::ReportProfile(..) => target!("ReportProfile"),
                 Self::MediaSessionEvent(..) => target!("MediaSessionEvent"),
                 Self::OnDevtoolsStarted(..) => target!("OnDevtoolsStarted"),
                 Self::ReadyToPresent(..) => target!("ReadyToPresent"),
                 Self::EventDelivered(..) => target!("EventDelivered"),
+                Self::SlowScript(..) => target!("SlowScript"),
+                Self::DownloadRequested(..) => target!("DownloadRequested"),
+                Self::ContextMenuRequested(..) => target!("ContextMenuRequested"),
+                Self::ScriptMessage(..) => target!("ScriptMessage"),
+                Self::AudibleChanged(..) => target!("AudibleChanged"),
+                Self::ConsoleMessage(..) => target!("ConsoleMessage"),
+                Self::JavaScriptEvaluated(..) => target!("JavaScriptEvaluated"),
+                Self::StartGeolocationUpdates(..) => target!("StartGeolocationUpdates"),
+                Self::StopGeolocationUpdates => target!("StopGeolocationUpdates"),
+                Self::ShowNotification(..) => target!("ShowNotification"),
+                Self::CloseNotification(..) => target!("CloseNotification"),
             }
         }
     }
@@ -197,6 +210,7 @@ mod to_servo {
                 Self::Refresh => target!("Refresh"),
                 Self::WindowResize => target!("WindowResize"),
                 Self::AllowNavigationResponse(..) => target!("AllowNavigationResponse"),
+                Self::SlowScriptResponse(..) => target!("SlowScriptResponse"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::MouseWindowEventClass(..) => target!("MouseWindowEventClass"),
                 Self::MouseWindowMoveEventClass(..) => target!("MouseWindowMoveEventClass"),
@@ -206,11 +220,16 @@ mod to_servo {
                 Self::Zoom(..) => target!("Zoom"),
                 Self::PinchZoom(..) => target!("PinchZoom"),
                 Self::ResetZoom => target!("ResetZoom"),
+                Self::SetPageZoom(..) => target!("SetPageZoom"),
                 Self::Navigation(..) => target!("Navigation"),
+                Self::GoToHistoryIndex(..) => target!("GoToHistoryIndex"),
+                Self::TruncateHistory(..) => target!("TruncateHistory"),
+                Self::RestoreHistory(..) => target!("RestoreHistory"),
                 Self::Quit => target!("Quit"),
                 Self::ExitFullScreen(..) => target!("ExitFullScreen"),
                 Self::Keyboard(..) => target!("Keyboard"),
                 Self::Reload(..) => target!("Reload"),
+                Self::Stop(..) => target!("Stop"),
                 Self::NewWebView(..) => target!("NewWebView"),
                 Self::CloseWebView(..) => target!("CloseWebView"),
                 Self::SendError(..) => target!("SendError"),
@@ -226,7 +245,10 @@ mod to_servo {
                 Self::ToggleSamplingProfiler(..) => target!("ToggleSamplingProfiler"),
                 Self::MediaSessionAction(..) => target!("MediaSessionAction"),
                 Self::SetWebViewThrottled(..) => target!("SetWebViewThrottled"),
+                Self::SetWebViewMuted(..) => target!("SetWebViewMuted"),
+                Self::SetWebViewAutoplayPolicy(..) => target!("SetWebViewAutoplayPolicy"),
                 Self::IMEDismissed => target!("IMEDismissed"),
+                Self::Ime(..) => target!("Ime"),
                 Self::InvalidateNativeSurface => target!("InvalidateNativeSurface"),
                 Self::ReplaceNativeSurface(..) => target!("ReplaceNativeSurface"),
                 Self::Gamepad(..) => target!("Gamepad"),
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
@@ -10,7 +11,7 @@ use std::time::Duration;
 use std::vec::Drain;
 use std::{env, thread};
 
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
 use euclid::{Point2D, Vector2D};
 use gilrs::{EventType, Gilrs};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
@@ -18,15 +19,16 @@ use log::{debug, error, info, trace, warn};
 use servo::base::id::TopLevelBrowsingContextId as WebViewId;
 use servo::compositing::windowing::{EmbedderEvent, WebRenderDebugOption};
 use servo::embedder_traits::{
-    CompositorEventVariant, ContextMenuResult, EmbedderMsg, FilterPattern, PermissionPrompt,
-    PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
+    BluetoothDeviceDescription, ClipboardContents, CompositorEventVariant, ContextMenuResult,
+    EmbedderMsg, FilterPattern, PermissionPrompt, PermissionRequest, PromptDefinition,
+    PromptOrigin, PromptResult,
 };
 use servo::script_traits::{
     GamepadEvent, GamepadIndex, GamepadInputBounds, GamepadUpdateType, TouchEventType,
     TraversalDirection,
 };
 use servo::servo_config::opts;
-use servo::servo_url::ServoUrl;
+use servo::servo_url::{ImmutableOrigin, ServoUrl};
 use servo::webrender_api::units::DeviceRect;
 use servo::webrender_api::ScrollLocation;
 use tinyfiledialogs::{self, MessageBoxIcon, OkCancel, YesNo};
@@ -370,6 +372,68 @@ where
     fn platform_handle_key(&mut self, _key_event: KeyboardEvent) {}
 
     /// Handle key events after they have been handled by Servo.
+    fn clipboard_contents(&mut self) -> ClipboardContents {
+        let Some(ref mut clipboard) = self.clipboard else {
+            return ClipboardContents::default();
+        };
+        let text = clipboard.get_text().ok();
+        let png = clipboard.get_image().ok().and_then(|image| {
+            let buffer = image::RgbaImage::from_raw(
+                image.width as u32,
+                image.height as u32,
+                image.bytes.into_owned(),
+            )?;
+            let mut png = Vec::new();
+            buffer
+                .write_to(
+                    &mut std::io::Cursor::new(&mut png),
+                    image::ImageOutputFormat::Png,
+                )
+                .ok()?;
+            Some(png)
+        });
+        if text.is_none() && png.is_none() {
+            warn!("Error getting clipboard contents. Returning empty contents.");
+        }
+        ClipboardContents {
+            text,
+            html: None,
+            png,
+        }
+    }
+
+    fn set_clipboard_contents(&mut self, contents: ClipboardContents) {
+        let Some(ref mut clipboard) = self.clipboard else {
+            return;
+        };
+        let result = match contents {
+            ClipboardContents {
+                html: Some(html),
+                text,
+                ..
+            } => clipboard.set_html(html, text),
+            ClipboardContents {
+                png: Some(png),
+                text: None,
+                ..
+            } => match image::load_from_memory_with_format(&png, image::ImageFormat::Png) {
+                Ok(image) => {
+                    let image = image.into_rgba8();
+                    clipboard.set_image(ImageData {
+                        width: image.width() as usize,
+                        height: image.height() as usize,
+                        bytes: Cow::Owned(image.into_raw()),
+                    })
+                },
+                Err(e) => return warn!("Error decoding clipboard image ({})", e),
+            },
+            ClipboardContents { text, .. } => clipboard.set_text(text.unwrap_or_default()),
+        };
+        if let Err(e) = result {
+            warn!("Error setting clipboard contents ({})", e);
+        }
+    }
+
     fn handle_key_from_servo(&mut self, _: Option<WebViewId>, event: KeyboardEvent) {
         ShortcutMatcher::from_event(event)
             .shortcut(CMD_OR_CONTROL, '=', || {
@@ -630,24 +694,13 @@ where
                     self.handle_key_from_servo(webview_id, key_event);
                 },
                 EmbedderMsg::GetClipboardContents(sender) => {
-                    let contents = self
-                        .clipboard
-                        .as_mut()
-                        .and_then(|clipboard| clipboard.get_text().ok())
-                        .unwrap_or_else(|| {
-                            warn!("Error getting clipboard text. Returning empty string.");
-                            String::new()
-                        });
+                    let contents = self.clipboard_contents();
                     if let Err(e) = sender.send(contents) {
                         warn!("Failed to send clipboard ({})", e);
                     }
                 },
-                EmbedderMsg::SetClipboardContents(text) => {
-                    if let Some(ref mut clipboard) = self.clipboard {
-                        if let Err(e) = clipboard.set_text(text) {
-                            warn!("Error setting clipboard contents ({})", e);
-                        }
-                    }
+                EmbedderMsg::SetClipboardContents(contents) => {
+                    self.set_clipboard_contents(contents);
                 },
                 EmbedderMsg::SetCursor(cursor) => {
                     self.window.set_cursor(cursor);
@@ -659,9 +712,9 @@ where
                     self.load_status = LoadStatus::HeadParsed;
                     need_update = true;
                 },
-                EmbedderMsg::HistoryChanged(urls, current) => {
-                    self.current_url = Some(urls[current].clone());
-                    self.current_url_string = Some(urls[current].clone().into_string());
+                EmbedderMsg::HistoryChanged(entries, current) => {
+                    self.current_url = Some(entries[current].url.clone());
+                    self.current_url_string = Some(entries[current].url.clone().into_string());
                     need_update = true;
                 },
                 EmbedderMsg::SetFullscreenState(state) => {
@@ -679,8 +732,8 @@ where
                     self.shutdown_requested = true;
                 },
                 EmbedderMsg::Panic(_reason, _backtrace) => {},
-                EmbedderMsg::GetSelectedBluetoothDevice(devices, sender) => {
-                    let selected = platform_get_selected_devices(devices);
+                EmbedderMsg::GetSelectedBluetoothDevice(origin, devices, sender) => {
+                    let selected = platform_get_selected_devices(origin, devices);
                     if let Err(e) = sender.send(selected) {
                         let reason =
                             format!("Failed to send GetSelectedBluetoothDevice response: {}", e);
@@ -688,6 +741,15 @@ where
                             .push(EmbedderEvent::SendError(None, reason));
                     };
                 },
+                EmbedderMsg::GetAudioOutputDevices(_, _, sender) => {
+                    // There is no user interface to choose them, so none is exposed.
+                    if let Err(e) = sender.send(Vec::new()) {
+                        let reason =
+                            format!("Failed to send GetAudioOutputDevices response: {}", e);
+                        self.event_queue
+                            .push(EmbedderEvent::SendError(None, reason));
+                    };
+                },
                 EmbedderMsg::SelectFiles(patterns, multiple_files, sender) => {
                     let res = match (
                         opts::get().headless,
@@ -702,15 +764,29 @@ where
                             .push(EmbedderEvent::SendError(None, reason));
                     };
                 },
-                EmbedderMsg::PromptPermission(prompt, sender) => {
-                    let permission_state = prompt_user(prompt);
+                EmbedderMsg::PromptPermission(prompt, origin, sender) => {
+                    let permission_state = prompt_user(prompt, origin);
                     let _ = sender.send(permission_state);
                 },
                 EmbedderMsg::ShowIME(_kind, _text, _multiline, _rect) => {
                     debug!("ShowIME received");
+                    self.window.set_ime_allowed(true);
                 },
                 EmbedderMsg::HideIME => {
                     debug!("HideIME received");
+                    self.window.set_ime_allowed(false);
+                },
+                EmbedderMsg::UpdateIME(state) => {
+                    // The caret rect is in CSS pixels relative to the webview.
+                    if let Some(webview) = webview_id.and_then(|id| self.webviews.get(&id)) {
+                        let scale = self.window.hidpi_factor().get();
+                        let rect = state
+                            .caret_rect
+                            .to_f32()
+                            .scale(scale, scale)
+                            .translate(webview.rect.min.to_vector());
+                        self.window.set_ime_cursor_area(rect.round_out().to_i32());
+                    }
                 },
                 EmbedderMsg::ReportProfile(bytes) => {
                     let filename = env::var("PROFILE_OUTPUT").unwrap_or("samples.json".to_string());
@@ -733,6 +809,41 @@ where
                 EmbedderMsg::ReadyToPresent(_webview_ids) => {
                     need_present = true;
                 },
+                EmbedderMsg::SlowScript(pipeline_id) => {
+                    let allow_continue = prompt_slow_script();
+                    self.event_queue.push(EmbedderEvent::SlowScriptResponse(
+                        pipeline_id,
+                        allow_continue,
+                    ));
+                },
+                EmbedderMsg::ScriptMessage(_message, _sender) => {
+                    // Answered by the handlers registered with `Servo::add_script_message_handler`.
+                },
+                EmbedderMsg::AudibleChanged(audible) => {
+                    debug!("AudibleChanged received: {}", audible);
+                },
+                EmbedderMsg::ConsoleMessage(message) => {
+                    // Script already prints console output to stdout.
+                    debug!("ConsoleMessage received: {:?}", message);
+                },
+                EmbedderMsg::JavaScriptEvaluated(..) => {
+                    // Passed to the callback given to `Servo::evaluate_javascript`.
+                },
+                EmbedderMsg::StartGeolocationUpdates(..) | EmbedderMsg::StopGeolocationUpdates => {
+                    // Answered by the delegate set with `Servo::set_geolocation_delegate`.
+                },
+                EmbedderMsg::ShowNotification(..) | EmbedderMsg::CloseNotification(..) => {
+                    // Handled by the delegate set with `Servo::set_notification_delegate`.
+                },
+                EmbedderMsg::ContextMenuRequested(info) => {
+                    debug!("ContextMenuRequested received: {:?}", info);
+                },
+                EmbedderMsg::DownloadRequested(request) => {
+                    warn!(
+                        "Not downloading {} without a download directory",
+                        request.url
+                    );
+                },
                 EmbedderMsg::EventDelivered(event) => {
                     if let (Some(webview_id), CompositorEventVariant::MouseButtonEvent) =
                         (webview_id, event)
@@ -755,20 +866,24 @@ where
 }
 
 #[cfg(target_os = "linux")]
-fn prompt_user(prompt: PermissionPrompt) -> PermissionRequest {
+fn prompt_user(prompt: PermissionPrompt, origin: ImmutableOrigin) -> PermissionRequest {
     if opts::get().headless {
         return PermissionRequest::Denied;
     }
 
+    let origin = origin.ascii_serialization();
     let message = match prompt {
         PermissionPrompt::Request(permission_name) => {
-            format!("Do you want to grant permission for {:?}?", permission_name)
+            format!(
+                "Do you want to grant {} permission for {:?}?",
+                origin, permission_name
+            )
         },
         PermissionPrompt::Insecure(permission_name) => {
             format!(
                 "The {:?} feature is only safe to use in secure context, but servo can't guarantee\n\
-                that the current context is secure. Do you want to proceed and grant permission?",
-                permission_name
+                that {} is secure. Do you want to proceed and grant permission?",
+                permission_name, origin
             )
         },
     };
@@ -785,20 +900,55 @@ fn prompt_user(prompt: PermissionPrompt) -> PermissionRequest {
 }
 
 #[cfg(not(target_os = "linux"))]
-fn prompt_user(_prompt: PermissionPrompt) -> PermissionRequest {
+fn prompt_user(_prompt: PermissionPrompt, _origin: ImmutableOrigin) -> PermissionRequest {
     // TODO popup only supported on linux
     PermissionRequest::Denied
 }
 
 #[cfg(target_os = "linux")]
-fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
+fn prompt_slow_script() -> bool {
+    // Without a way to ask the user, let the script continue rather than breaking the page.
+    if opts::get().headless {
+        return true;
+    }
+
+    match tinyfiledialogs::message_box_yes_no(
+        "Slow script",
+        "A script on this page is taking a long time to run. Do you want to let it continue?",
+        MessageBoxIcon::Warning,
+        YesNo::No,
+    ) {
+        YesNo::Yes => true,
+        YesNo::No => false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn prompt_slow_script() -> bool {
+    // TODO popup only supported on linux
+    true
+}
+
+#[cfg(target_os = "linux")]
+fn platform_get_selected_devices(
+    origin: ImmutableOrigin,
+    devices: Vec<BluetoothDeviceDescription>,
+) -> Option<String> {
+    if opts::get().headless {
+        return None;
+    }
+
     thread::Builder::new()
         .name("DevicePicker".to_owned())
         .spawn(move || {
-            let dialog_rows: Vec<&str> = devices.iter().map(|s| s.as_ref()).collect();
+            let title = format!("Choose a device for {}", origin.ascii_serialization());
+            let dialog_rows: Vec<&str> = devices
+                .iter()
+                .flat_map(|device| [device.address.as_str(), device.name.as_str()])
+                .collect();
             let dialog_rows: Option<&[&str]> = Some(dialog_rows.as_slice());
 
-            match tinyfiledialogs::list_dialog("Choose a device", &["Id", "Name"], dialog_rows) {
+            match tinyfiledialogs::list_dialog(&title, &["Id", "Name"], dialog_rows) {
                 Some(device) => {
                     // The device string format will be "Address|Name". We need the first part of it.
                     device.split('|').next().map(|s| s.to_string())
@@ -812,12 +962,11 @@ fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
 }
 
 #[cfg(not(target_os = "linux"))]
-fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
-    for device in devices {
-        if let Some(address) = device.split('|').next().map(|s| s.to_string()) {
-            return Some(address);
-        }
-    }
+fn platform_get_selected_devices(
+    _origin: ImmutableOrigin,
+    _devices: Vec<BluetoothDeviceDescription>,
+) -> Option<String> {
+    // TODO popup only supported on linux
     None
 }
 
@@ -11,7 +11,7 @@ use servo::config::opts;
 use servo::embedder_traits::Cursor;
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::style_traits::DevicePixel;
-use servo::webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 
 use super::events_loop::WakerEvent;
 
@@ -41,6 +41,8 @@ pub trait WindowPortsMethods: WindowMethods {
     fn set_position(&self, _point: DeviceIntPoint) {}
     fn set_fullscreen(&self, _state: bool) {}
     fn set_cursor(&self, _cursor: Cursor) {}
+    fn set_ime_allowed(&self, _allowed: bool) {}
+    fn set_ime_cursor_area(&self, _rect: DeviceIntRect) {}
     fn new_glwindow(
         &self,
         events_loop: &winit::event_loop::EventLoopWindowTarget<WakerEvent>,
@@ -15,8 +15,9 @@ use servo::compositing::windowing::{
     WindowMethods,
 };
 use servo::embedder_traits::{
-    ContextMenuResult, EmbedderMsg, EmbedderProxy, EventLoopWaker, MediaSessionEvent,
-    PermissionPrompt, PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
+    ClipboardContents, ContextMenuResult, EmbedderMsg, EmbedderProxy, EventLoopWaker, ImeEvent,
+    MediaSessionEvent, PermissionPrompt, PermissionRequest, PromptDefinition, PromptOrigin,
+    PromptResult,
 };
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
@@ -193,6 +194,14 @@ impl ServoGlue {
         self.process_event(event)
     }
 
+    /// Stop loading the page.
+    pub fn stop(&mut self) -> Result<(), &'static str> {
+        info!("stop");
+        let browser_id = self.get_browser_id()?;
+        let event = EmbedderEvent::Stop(browser_id);
+        self.process_event(event)
+    }
+
     /// Redraw the page.
     pub fn refresh(&mut self) -> Result<(), &'static str> {
         info!("refresh");
@@ -409,6 +418,21 @@ impl ServoGlue {
         self.process_event(EmbedderEvent::IMEDismissed)
     }
 
+    pub fn ime_set_composition(&mut self, text: String) -> Result<(), &'static str> {
+        info!("ime_set_composition");
+        self.process_event(EmbedderEvent::Ime(ImeEvent::SetComposition(text)))
+    }
+
+    pub fn ime_commit(&mut self, text: String) -> Result<(), &'static str> {
+        info!("ime_commit");
+        self.process_event(EmbedderEvent::Ime(ImeEvent::Commit(text)))
+    }
+
+    pub fn ime_cancel(&mut self) -> Result<(), &'static str> {
+        info!("ime_cancel");
+        self.process_event(EmbedderEvent::Ime(ImeEvent::Cancel))
+    }
+
     pub fn on_context_menu_closed(
         &mut self,
         result: ContextMenuResult,
@@ -458,7 +482,7 @@ impl ServoGlue {
                         .on_history_changed(can_go_back, can_go_forward);
                     self.callbacks
                         .host_callbacks
-                        .on_url_changed(entries[current].clone().to_string());
+                        .on_url_changed(entries[current].url.clone().to_string());
                 },
                 EmbedderMsg::LoadStart => {
                     self.callbacks.host_callbacks.on_load_started();
@@ -466,9 +490,12 @@ impl ServoGlue {
                 EmbedderMsg::LoadComplete => {
                     self.callbacks.host_callbacks.on_load_ended();
                 },
-                EmbedderMsg::GetSelectedBluetoothDevice(_, sender) => {
+                EmbedderMsg::GetSelectedBluetoothDevice(_, _, sender) => {
                     let _ = sender.send(None);
                 },
+                EmbedderMsg::GetAudioOutputDevices(_, _, sender) => {
+                    let _ = sender.send(Vec::new());
+                },
                 EmbedderMsg::AllowUnload(sender) => {
                     let _ = sender.send(true);
                 },
@@ -540,24 +567,34 @@ impl ServoGlue {
                 },
                 EmbedderMsg::GetClipboardContents(sender) => {
                     let contents = self.callbacks.host_callbacks.get_clipboard_contents();
-                    let _ = sender.send(contents.unwrap_or("".to_owned()));
+                    let _ = sender.send(ClipboardContents {
+                        text: contents,
+                        ..Default::default()
+                    });
                 },
-                EmbedderMsg::SetClipboardContents(text) => {
-                    self.callbacks.host_callbacks.set_clipboard_contents(text);
+                EmbedderMsg::SetClipboardContents(contents) => {
+                    // Only plain text is supported by the host.
+                    if let Some(text) = contents.text {
+                        self.callbacks.host_callbacks.set_clipboard_contents(text);
+                    }
                 },
                 EmbedderMsg::Shutdown => {
                     self.callbacks.host_callbacks.on_shutdown_complete();
                 },
-                EmbedderMsg::PromptPermission(prompt, sender) => {
+                EmbedderMsg::PromptPermission(prompt, origin, sender) => {
+                    let origin = origin.ascii_serialization();
                     let message = match prompt {
                         PermissionPrompt::Request(permission_name) => {
-                            format!("Do you want to grant permission for {:?}?", permission_name)
+                            format!(
+                                "Do you want to grant {} permission for {:?}?",
+                                origin, permission_name
+                            )
                         },
                         PermissionPrompt::Insecure(permission_name) => {
                             format!(
                                 "The {:?} feature is only safe to use in secure context, but servo can't guarantee\n\
-                                that the current context is secure. Do you want to proceed and grant permission?",
-                                permission_name
+                                that {} is secure. Do you want to proceed and grant permission?",
+                                permission_name, origin
                             )
                         },
                     };
@@ -571,6 +608,20 @@ impl ServoGlue {
 
                     let _ = sender.send(result);
                 },
+                EmbedderMsg::SlowScript(pipeline_id) => {
+                    let message = "A script on this page is taking a long time to run. Do you \
+                                   want to let it continue?"
+                        .to_owned();
+                    let allow_continue =
+                        match self.callbacks.host_callbacks.prompt_yes_no(message, true) {
+                            PromptResult::Primary | PromptResult::Dismissed => true,
+                            PromptResult::Secondary => false,
+                        };
+                    self.events.push(EmbedderEvent::SlowScriptResponse(
+                        pipeline_id,
+                        allow_continue,
+                    ));
+                },
                 EmbedderMsg::ShowIME(kind, text, multiline, bounds) => {
                     self.callbacks
                         .host_callbacks
@@ -623,7 +674,18 @@ impl ServoGlue {
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
                 EmbedderMsg::ReportProfile(..) |
-                EmbedderMsg::EventDelivered(..) => {},
+                EmbedderMsg::EventDelivered(..) |
+                EmbedderMsg::DownloadRequested(..) |
+                EmbedderMsg::ContextMenuRequested(..) |
+                EmbedderMsg::ScriptMessage(..) |
+                EmbedderMsg::AudibleChanged(..) |
+                EmbedderMsg::UpdateIME(..) |
+                EmbedderMsg::ConsoleMessage(..) |
+                EmbedderMsg::JavaScriptEvaluated(..) |
+                EmbedderMsg::StartGeolocationUpdates(..) |
+                EmbedderMsg::StopGeolocationUpdates |
+                EmbedderMsg::ShowNotification(..) |
+                EmbedderMsg::CloseNotification(..) => {},
             }
         }
 
@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::autoplay::{autoplay_policy_allows, AutoplayPolicy};
+
+#[test]
+fn allowed_policy_always_allows_media() {
+    for muted in [false, true] {
+        for has_transient_activation in [false, true] {
+            assert!(autoplay_policy_allows(
+                AutoplayPolicy::Allowed,
+                muted,
+                has_transient_activation
+            ));
+        }
+    }
+}
+
+#[test]
+fn allowed_muted_policy_needs_activation_for_audible_media() {
+    assert!(autoplay_policy_allows(
+        AutoplayPolicy::AllowedMuted,
+        true,
+        false
+    ));
+    assert!(!autoplay_policy_allows(
+        AutoplayPolicy::AllowedMuted,
+        false,
+        false
+    ));
+    assert!(autoplay_policy_allows(
+        AutoplayPolicy::AllowedMuted,
+        false,
+        true
+    ));
+}
+
+#[test]
+fn blocked_policy_needs_activation() {
+    assert!(!autoplay_policy_allows(
+        AutoplayPolicy::Blocked,
+        true,
+        false
+    ));
+    assert!(!autoplay_policy_allows(
+        AutoplayPolicy::Blocked,
+        false,
+        false
+    ));
+    assert!(autoplay_policy_allows(AutoplayPolicy::Blocked, true, true));
+    assert!(autoplay_policy_allows(AutoplayPolicy::Blocked, false, true));
+}
@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::contenteditable::is_editable_by_contenteditable;
+
+#[test]
+fn no_contenteditable_is_not_editable() {
+    assert!(!is_editable_by_contenteditable::<&str>([None, None]));
+}
+
+#[test]
+fn editing_host_is_editable() {
+    assert!(is_editable_by_contenteditable([Some("")]));
+    assert!(is_editable_by_contenteditable([Some("TRUE")]));
+    assert!(is_editable_by_contenteditable([Some("plaintext-only")]));
+}
+
+#[test]
+fn descendant_of_editing_host_is_editable() {
+    assert!(is_editable_by_contenteditable([None, None, Some("true")]));
+}
+
+#[test]
+fn nearest_contenteditable_wins() {
+    assert!(!is_editable_by_contenteditable([
+        None,
+        Some("false"),
+        Some("true")
+    ]));
+    assert!(is_editable_by_contenteditable([
+        Some("true"),
+        Some("false")
+    ]));
+}
+
+#[test]
+fn invalid_contenteditable_inherits() {
+    assert!(is_editable_by_contenteditable([Some("yes"), Some("true")]));
+    assert!(!is_editable_by_contenteditable([Some("inherit"), None]));
+}
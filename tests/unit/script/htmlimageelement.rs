@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use script::test::mixed_replace::MixedReplaceFirstPart;
 use script::test::srcset::{parse_a_srcset_attribute, Descriptor, ImageSource};
 
 #[test]
@@ -162,3 +163,127 @@ fn different_descriptor() {
         sources
     );
 }
+
+fn first_part_of(boundary: &str, chunks: &[&[u8]]) -> (Vec<u8>, bool) {
+    let mut parser = MixedReplaceFirstPart::new(boundary);
+    let mut body = Vec::new();
+    for chunk in chunks {
+        body.extend(parser.push(chunk));
+    }
+    let complete = parser.is_complete();
+    body.extend(parser.finish());
+    (body, complete)
+}
+
+#[test]
+fn mixed_replace_first_part() {
+    let response: &[u8] = b"--frame\r\nContent-Type: image/png\r\n\r\nfirst\r\n--frame\r\n\
+        Content-Type: image/png\r\n\r\nsecond\r\n--frame--\r\n";
+    assert_eq!(
+        first_part_of("frame", &[response]),
+        (b"first".to_vec(), true)
+    );
+}
+
+#[test]
+fn mixed_replace_skips_preamble() {
+    let response: &[u8] = b"ignored preamble\r\n--frame\r\n\r\nbody\r\n--frame\r\n";
+    assert_eq!(
+        first_part_of("frame", &[response]),
+        (b"body".to_vec(), true)
+    );
+}
+
+#[test]
+fn mixed_replace_bare_line_feeds() {
+    let response: &[u8] = b"--frame\nContent-Type: image/png\n\nbody\n--frame\n";
+    assert_eq!(
+        first_part_of("frame", &[response]),
+        (b"body".to_vec(), true)
+    );
+}
+
+#[test]
+fn mixed_replace_boundary_must_match() {
+    let response: &[u8] = b"--other\r\n\r\nnot it\r\n--frame\r\n\r\nbody\r\n--frame\r\n";
+    assert_eq!(
+        first_part_of("frame", &[response]),
+        (b"body".to_vec(), true)
+    );
+}
+
+#[test]
+fn mixed_replace_delimiter_split_across_chunks() {
+    let chunks: &[&[u8]] = &[
+        b"--fra",
+        b"me\r\nContent-Type: im",
+        b"age/png\r\n\r\nfir",
+        b"st\r\n--fr",
+        b"ame\r\n\r\nsecond",
+    ];
+    assert_eq!(first_part_of("frame", chunks), (b"first".to_vec(), true));
+}
+
+#[test]
+fn mixed_replace_byte_by_byte() {
+    let response: &[u8] = b"--frame\r\n\r\nbody with -- dashes\r\n--frame\r\n";
+    let chunks: Vec<&[u8]> = response.chunks(1).collect();
+    assert_eq!(
+        first_part_of("frame", &chunks),
+        (b"body with -- dashes".to_vec(), true)
+    );
+}
+
+#[test]
+fn mixed_replace_truncated_body() {
+    // The stream ends before the delimiter of the next part, so the body is whatever arrived.
+    let response: &[u8] = b"--frame\r\nContent-Type: image/png\r\n\r\ntruncated body";
+    assert_eq!(
+        first_part_of("frame", &[response]),
+        (b"truncated body".to_vec(), false)
+    );
+}
+
+#[test]
+fn mixed_replace_truncated_headers() {
+    let response: &[u8] = b"--frame\r\nContent-Type: ima";
+    assert_eq!(first_part_of("frame", &[response]), (Vec::new(), false));
+}
+
+#[test]
+fn mixed_replace_truncated_delimiter() {
+    let response: &[u8] = b"preamble --fra";
+    assert_eq!(first_part_of("frame", &[response]), (Vec::new(), false));
+}
+
+#[test]
+fn mixed_replace_ignores_data_after_first_part() {
+    let mut parser = MixedReplaceFirstPart::new("frame");
+    assert_eq!(parser.push(b"--frame\r\n\r\nbody\r\n--frame\r\n"), b"body");
+    assert!(parser.is_complete());
+    assert!(parser.push(b"\r\nsecond\r\n--frame\r\n").is_empty());
+    assert!(parser.finish().is_empty());
+}
+
+#[test]
+fn mixed_replace_long_preamble() {
+    // The preamble is skipped as it arrives instead of being buffered until the delimiter.
+    let preamble = [b'-'; 64 * 1024];
+    let chunks: &[&[u8]] = &[
+        &preamble,
+        &preamble,
+        b"\r\n--frame\r\n\r\nbody\r\n--frame\r\n",
+    ];
+    assert_eq!(first_part_of("frame", chunks), (b"body".to_vec(), true));
+}
+
+#[test]
+fn mixed_replace_header_line_too_long() {
+    let mut parser = MixedReplaceFirstPart::new("frame");
+    assert!(parser.push(b"--frame\r\nX-Padding: ").is_empty());
+    assert!(!parser.is_failed());
+    assert!(parser.push(&[b'a'; 64 * 1024]).is_empty());
+    assert!(parser.is_failed());
+    assert!(parser.push(b"\r\n\r\nbody\r\n--frame\r\n").is_empty());
+    assert!(parser.finish().is_empty());
+}
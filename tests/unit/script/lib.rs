@@ -2,11 +2,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(test)]
+mod autoplay;
 #[cfg(test)]
 mod headers;
 #[cfg(test)]
 mod htmlareaelement;
 #[cfg(test)]
+mod htmlelement;
+#[cfg(test)]
 mod htmlimageelement;
 #[cfg(test)]
 mod origin;
@@ -857,3 +857,34 @@ fn test_select_all() {
         textinput.selection_end()
     );
 }
+
+#[test]
+fn test_textinput_composition() {
+    let mut textinput = text_input(Lines::Single, "abc");
+    textinput.set_selection_range(1, 2, SelectionDirection::None);
+
+    textinput.set_composition("x");
+    assert_eq!(textinput.get_content(), "axc");
+    assert_eq!(
+        textinput.composition_range(),
+        Some(UTF8Bytes(1)..UTF8Bytes(2))
+    );
+
+    textinput.set_composition("xyz");
+    assert_eq!(textinput.get_content(), "axyzc");
+    assert_eq!(
+        textinput.composition_range(),
+        Some(UTF8Bytes(1)..UTF8Bytes(4))
+    );
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(4));
+
+    textinput.set_composition("");
+    assert_eq!(textinput.get_content(), "ac");
+    assert_eq!(
+        textinput.composition_range(),
+        Some(UTF8Bytes(1)..UTF8Bytes(1))
+    );
+
+    textinput.set_content(DOMString::from("abc"));
+    assert_eq!(textinput.composition_range(), None);
+}
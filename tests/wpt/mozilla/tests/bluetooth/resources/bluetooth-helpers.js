@@ -0,0 +1,17 @@
+// Loads a data set of the mock adapter and asks for the device of the data set with the heart
+// rate service, or with the given options.
+async function requestMockDevice(dataSet, options = {
+  filters: [{services: ["heart_rate"]}],
+}) {
+  window.testRunner.setBluetoothMockDataSet(dataSet);
+  return navigator.bluetooth.requestDevice(options);
+}
+
+// Connects to the device of the heart rate data set and returns its heart rate measurement
+// characteristic, which supports notifications.
+async function getHeartRateMeasurement() {
+  const device = await requestMockDevice("HeartRateAdapter");
+  const server = await device.gatt.connect();
+  const service = await server.getPrimaryService("heart_rate");
+  return service.getCharacteristic("heart_rate_measurement");
+}
@@ -0,0 +1 @@
+Promise.resolve().then(() => registerKeyValue("promise", "resolved"));